@@ -0,0 +1,177 @@
+//! The binary layout of pack and index files. All integers are little endian.
+//!
+//! A pack file is a [PACK_HEADER_SIZE] bytes header (the [PACK_MAGIC] followed
+//! by the [FORMAT_VERSION]) and a sequence of records, each being a
+//! [RecordHeader] followed by the compressed blob.
+//!
+//! An index file is a [IndexHeader] followed by [IndexEntry]s sorted by key.
+
+use anyhow::{bail, Result};
+use turbo_tasks_hash::{DeterministicHasher, Xxh3Hash64Hasher};
+
+use crate::store::BlobKey;
+
+/// Bumped whenever the layout of pack or index files changes. Files of other
+/// versions are ignored.
+pub const FORMAT_VERSION: u32 = 2;
+
+pub const PACK_MAGIC: &[u8; 8] = b"TTCPACK\0";
+pub const PACK_HEADER_SIZE: usize = 16;
+
+pub const INDEX_MAGIC: &[u8; 8] = b"TTCINDX\0";
+
+/// Computes the checksum of compressed blobs and of the entries of an index.
+pub fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = Xxh3Hash64Hasher::new();
+    hasher.write_bytes(bytes);
+    hasher.finish()
+}
+
+pub fn encode_pack_header() -> [u8; PACK_HEADER_SIZE] {
+    let mut bytes = [0; PACK_HEADER_SIZE];
+    bytes[..8].copy_from_slice(PACK_MAGIC);
+    bytes[8..12].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes
+}
+
+pub fn validate_pack_header(bytes: &[u8]) -> Result<()> {
+    if bytes.len() < PACK_HEADER_SIZE || &bytes[..8] != PACK_MAGIC {
+        bail!("not a pack file");
+    }
+    let version = read_u32(bytes, 8);
+    if version != FORMAT_VERSION {
+        bail!("unsupported pack file version {}", version);
+    }
+    Ok(())
+}
+
+/// Precedes every blob in a pack file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordHeader {
+    pub key: BlobKey,
+    pub uncompressed_len: u32,
+    pub compressed_len: u32,
+    /// The [checksum] of the compressed blob.
+    pub checksum: u64,
+}
+
+impl RecordHeader {
+    pub const SIZE: usize = 48;
+
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..32].copy_from_slice(&self.key.0);
+        bytes[32..36].copy_from_slice(&self.uncompressed_len.to_le_bytes());
+        bytes[36..40].copy_from_slice(&self.compressed_len.to_le_bytes());
+        bytes[40..48].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Self {
+        Self {
+            key: read_key(bytes, 0),
+            uncompressed_len: read_u32(bytes, 32),
+            compressed_len: read_u32(bytes, 36),
+            checksum: read_u64(bytes, 40),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexHeader {
+    /// The number of the last build that opened the cache.
+    pub build: u32,
+    pub entry_count: u64,
+    /// The [checksum] of all entries.
+    pub checksum: u64,
+}
+
+impl IndexHeader {
+    pub const SIZE: usize = 32;
+
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..8].copy_from_slice(INDEX_MAGIC);
+        bytes[8..12].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.build.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.entry_count.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < Self::SIZE || &bytes[..8] != INDEX_MAGIC {
+            bail!("not an index file");
+        }
+        let version = read_u32(bytes, 8);
+        if version != FORMAT_VERSION {
+            bail!("unsupported index file version {}", version);
+        }
+        Ok(Self {
+            build: read_u32(bytes, 12),
+            entry_count: read_u64(bytes, 16),
+            checksum: read_u64(bytes, 24),
+        })
+    }
+}
+
+/// The location of a blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub key: BlobKey,
+    pub pack: u32,
+    pub compressed_len: u32,
+    pub uncompressed_len: u32,
+    /// The offset of the compressed blob in the pack file, i.e. after its
+    /// [RecordHeader].
+    pub offset: u64,
+    /// The [checksum] of the compressed blob.
+    pub checksum: u64,
+    /// The number of the last build that read or wrote the blob.
+    pub last_used_build: u32,
+    /// When the blob was last read or written, in seconds since the Unix
+    /// epoch.
+    pub last_used_at: u64,
+}
+
+impl IndexEntry {
+    pub const SIZE: usize = 72;
+
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[..32].copy_from_slice(&self.key.0);
+        bytes[32..36].copy_from_slice(&self.pack.to_le_bytes());
+        bytes[36..40].copy_from_slice(&self.compressed_len.to_le_bytes());
+        bytes[40..44].copy_from_slice(&self.uncompressed_len.to_le_bytes());
+        bytes[44..48].copy_from_slice(&self.last_used_build.to_le_bytes());
+        bytes[48..56].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[56..64].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes[64..72].copy_from_slice(&self.last_used_at.to_le_bytes());
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Self {
+        Self {
+            key: read_key(bytes, 0),
+            pack: read_u32(bytes, 32),
+            compressed_len: read_u32(bytes, 36),
+            uncompressed_len: read_u32(bytes, 40),
+            offset: read_u64(bytes, 48),
+            checksum: read_u64(bytes, 56),
+            last_used_build: read_u32(bytes, 44),
+            last_used_at: read_u64(bytes, 64),
+        }
+    }
+}
+
+fn read_key(bytes: &[u8], offset: usize) -> BlobKey {
+    BlobKey(bytes[offset..offset + 32].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
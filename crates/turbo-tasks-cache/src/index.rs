@@ -0,0 +1,107 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use memmap2::Mmap;
+
+use crate::{
+    format::{checksum, IndexEntry, IndexHeader},
+    store::BlobKey,
+};
+
+/// A memory mapped index file.
+pub struct Index {
+    mmap: Option<Mmap>,
+    len: usize,
+    build: u32,
+}
+
+impl Index {
+    pub fn empty() -> Self {
+        Self {
+            mmap: None,
+            len: 0,
+            build: 0,
+        }
+    }
+
+    /// Maps and validates the index file at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: Index files are never modified after they have been written.
+        // They are replaced by renaming a new file over them instead, which
+        // leaves the mapping of the old file intact.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let header = IndexHeader::decode(&mmap)?;
+        let len = usize::try_from(header.entry_count)?;
+        let entries = &mmap[IndexHeader::SIZE..];
+        if len.checked_mul(IndexEntry::SIZE) != Some(entries.len()) {
+            bail!("index file is truncated");
+        }
+        if checksum(entries) != header.checksum {
+            bail!("index file is corrupted");
+        }
+        Ok(Self {
+            mmap: Some(mmap),
+            len,
+            build: header.build,
+        })
+    }
+
+    /// The number of the last build that opened the cache.
+    pub fn build(&self) -> u32 {
+        self.build
+    }
+
+    pub fn entry(&self, index: usize) -> IndexEntry {
+        let mmap = self.mmap.as_ref().expect("entry of an empty index");
+        let start = IndexHeader::SIZE + index * IndexEntry::SIZE;
+        IndexEntry::decode(&mmap[start..start + IndexEntry::SIZE])
+    }
+
+    pub fn get(&self, key: &BlobKey) -> Option<IndexEntry> {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let entry = self.entry(mid);
+            match entry.key.cmp(key) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(entry),
+            }
+        }
+        None
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = IndexEntry> + '_ {
+        (0..self.len).map(|index| self.entry(index))
+    }
+
+    /// Atomically replaces the index file at `path` with `entries`, which
+    /// must be sorted by key.
+    pub fn write(path: &Path, build: u32, entries: &[IndexEntry]) -> Result<()> {
+        let mut bytes = Vec::with_capacity(entries.len() * IndexEntry::SIZE);
+        for entry in entries {
+            bytes.extend_from_slice(&entry.encode());
+        }
+        let header = IndexHeader {
+            build,
+            entry_count: entries.len() as u64,
+            checksum: checksum(&bytes),
+        };
+
+        let tmp_path = path.with_extension("tmp");
+        let file = File::create(&tmp_path)
+            .with_context(|| format!("unable to create {}", tmp_path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&header.encode())?;
+        writer.write_all(&bytes)?;
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("unable to replace {}", path.display()))?;
+        Ok(())
+    }
+}
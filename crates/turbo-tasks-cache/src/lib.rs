@@ -0,0 +1,41 @@
+//! On-disk storage for the persistent cache of turbo-tasks.
+//!
+//! Instead of serializing the whole task graph into a single blob, which has
+//! to be read and deserialized completely before the first task can be
+//! restored, the cache is stored as content-addressed blobs:
+//!
+//! - Blobs are zstd-compressed and appended to immutable pack files. A pack
+//!   file is written once per [CacheStore::flush] and never modified after.
+//! - A single index file maps the [BlobKey] of every blob to its location. The
+//!   entries have a fixed size and are sorted by key, so the index is memory
+//!   mapped and looked up with a binary search instead of being parsed. Opening
+//!   a cache only validates the index, which keeps restoring large graphs
+//!   sub-second.
+//!
+//! Every blob carries a checksum that is verified on read. A corrupted blob is
+//! reported as missing, so only the affected entries have to be recomputed and
+//! the rest of the cache is still reused. A corrupted or missing index is
+//! rebuilt from the pack files, which contain the keys of their blobs, too.
+//!
+//! To keep the cache from growing indefinitely, it can be pruned by a
+//! [PrunePolicy] that limits its size and how long unused blobs are kept.
+//!
+//! Blobs can be shared between machines with a [remote] cache.
+//!
+//! This crate only provides the storage. No backend reads or writes task data
+//! through it yet: the memory backend keeps the task graph in memory only, and
+//! a `turbo_tasks::persisted_graph::PersistedGraph` on top of [CacheStore],
+//! which `MemoryBackendWithPersistedGraph` would restore the graph from, is
+//! still to be written. Until then the store is only maintained by the `cache`
+//! command of turbopack-cli.
+
+mod format;
+mod index;
+mod prune;
+pub mod remote;
+mod store;
+
+pub use crate::{
+    prune::PrunePolicy,
+    store::{BlobKey, CacheStats, CacheStore, PruneResult},
+};
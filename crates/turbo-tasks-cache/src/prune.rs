@@ -0,0 +1,113 @@
+use std::{cmp::Reverse, time::Duration};
+
+use crate::format::IndexEntry;
+
+/// Limits for the blobs kept in a [CacheStore](crate::CacheStore). Blobs
+/// that exceed any of the limits are removed when the cache is pruned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrunePolicy {
+    /// The maximum compressed size of all blobs in bytes. When exceeded, the
+    /// least recently used blobs are removed first.
+    pub max_size: Option<u64>,
+    /// Blobs that haven't been used for this long are removed.
+    pub max_age: Option<Duration>,
+    /// Blobs that haven't been used by any of the last `max_unused_builds`
+    /// builds are removed.
+    pub max_unused_builds: Option<u32>,
+}
+
+impl PrunePolicy {
+    /// Whether the size budget is exceeded by `size` bytes of blobs, in which
+    /// case pruning must not wait for the next build.
+    pub(crate) fn exceeds_size(&self, size: u64) -> bool {
+        self.max_size.map_or(false, |max_size| size > max_size)
+    }
+
+    /// Removes the entries that exceed the policy, given the number of the
+    /// current build and the current time in seconds since the Unix epoch.
+    pub(crate) fn retain(&self, entries: &mut Vec<IndexEntry>, build: u32, now: u64) {
+        entries.retain(|entry| {
+            let too_old = self.max_age.map_or(false, |max_age| {
+                now.saturating_sub(entry.last_used_at) > max_age.as_secs()
+            });
+            let unused = self.max_unused_builds.map_or(false, |max_unused_builds| {
+                build.saturating_sub(entry.last_used_build) >= max_unused_builds
+            });
+            !too_old && !unused
+        });
+
+        let Some(max_size) = self.max_size else {
+            return;
+        };
+        let size = entries
+            .iter()
+            .map(|entry| entry.compressed_len as u64)
+            .sum::<u64>();
+        if size <= max_size {
+            return;
+        }
+        // Keep the most recently used entries that fit into the budget.
+        entries.sort_by_key(|entry| Reverse((entry.last_used_build, entry.last_used_at)));
+        let mut size = 0;
+        entries.retain(|entry| {
+            size += entry.compressed_len as u64;
+            size <= max_size
+        });
+        entries.sort_unstable_by_key(|entry| entry.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlobKey;
+
+    fn entry(key: u8, size: u32, last_used_build: u32, last_used_at: u64) -> IndexEntry {
+        IndexEntry {
+            key: BlobKey([key; 32]),
+            pack: 0,
+            compressed_len: size,
+            uncompressed_len: size,
+            offset: 0,
+            checksum: 0,
+            last_used_build,
+            last_used_at,
+        }
+    }
+
+    fn retained(policy: &PrunePolicy, mut entries: Vec<IndexEntry>) -> Vec<u8> {
+        policy.retain(&mut entries, 10, 1000);
+        entries.iter().map(|entry| entry.key.0[0]).collect()
+    }
+
+    #[test]
+    fn test_max_size() {
+        let policy = PrunePolicy {
+            max_size: Some(250),
+            ..Default::default()
+        };
+        let entries = vec![
+            entry(1, 100, 8, 800),
+            entry(2, 100, 10, 900),
+            entry(3, 100, 9, 1000),
+            entry(4, 100, 10, 950),
+        ];
+        assert_eq!(retained(&policy, entries.clone()), vec![2, 4]);
+        assert_eq!(retained(&policy, entries[..2].to_vec()), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_max_age_and_builds() {
+        let entries = vec![entry(1, 1, 10, 100), entry(2, 1, 5, 1000)];
+        let policy = PrunePolicy {
+            max_age: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert_eq!(retained(&policy, entries.clone()), vec![2]);
+        let policy = PrunePolicy {
+            max_unused_builds: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(retained(&policy, entries), vec![1]);
+    }
+}
@@ -0,0 +1,194 @@
+//! A client for a remote cache, which shares blobs between machines, e.g.
+//! to give CI preview builds and teammates a warm cache.
+//!
+//! The protocol is plain HTTP, authenticated with a bearer token:
+//!
+//! - `HEAD`, `GET` and `PUT /v1/blobs/<key>` check, download and upload the
+//!   blob with the content-addressed [BlobKey] `<key>`.
+//! - `GET` and `PUT /v1/entries/<input hash>` download and upload a
+//!   [RemoteCacheEntry], which lists the artifacts (e.g. emitted chunks or
+//!   analysis results) computed from inputs with the given hash.
+//!
+//! An optional namespace is sent as the `namespace` query parameter, so a
+//! server can keep the caches of different projects or teams apart.
+//!
+//! Downloaded blobs are verified against their key, so a remote cache can
+//! never inject content that doesn't match what has been requested.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use reqwest::{header, Method, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::{BlobKey, CacheStore};
+
+#[derive(Debug, Clone)]
+pub struct RemoteCacheOptions {
+    /// The base URL of the remote cache, e.g. `https://cache.example.com`.
+    pub url: String,
+    pub token: String,
+    pub namespace: Option<String>,
+    pub timeout: Option<Duration>,
+}
+
+/// The artifacts computed from inputs with a certain hash.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteCacheEntry {
+    /// Maps the names of the artifacts (e.g. the paths of emitted chunks) to
+    /// the keys of their blobs.
+    pub artifacts: BTreeMap<String, BlobKey>,
+}
+
+pub struct RemoteCacheClient {
+    client: reqwest::Client,
+    url: String,
+    token: String,
+    namespace: Option<String>,
+}
+
+impl RemoteCacheClient {
+    pub fn new(options: RemoteCacheOptions) -> Result<Self> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout) = options.timeout {
+            builder = builder.timeout(timeout);
+        }
+        Ok(Self {
+            client: builder.build()?,
+            url: options.url.trim_end_matches('/').to_string(),
+            token: options.token,
+            namespace: options.namespace,
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .request(method, format!("{}/v1/{}", self.url, path))
+            .bearer_auth(&self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.query(&[("namespace", namespace)]);
+        }
+        request
+    }
+
+    /// Sends `request`, returning `None` if the resource doesn't exist.
+    async fn send(&self, request: RequestBuilder) -> Result<Option<Response>> {
+        let response = request
+            .send()
+            .await
+            .context("remote cache is unreachable")?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                bail!("remote cache rejected the token ({})", response.status())
+            }
+            _ => Ok(Some(response.error_for_status()?)),
+        }
+    }
+
+    pub async fn has_blob(&self, key: &BlobKey) -> Result<bool> {
+        let request = self.request(Method::HEAD, &format!("blobs/{}", key));
+        Ok(self.send(request).await?.is_some())
+    }
+
+    /// Downloads the blob with `key`. Returns `None` if the remote cache
+    /// doesn't have it.
+    pub async fn get_blob(&self, key: &BlobKey) -> Result<Option<Vec<u8>>> {
+        let request = self.request(Method::GET, &format!("blobs/{}", key));
+        let Some(response) = self.send(request).await? else {
+            return Ok(None);
+        };
+        let bytes = response.bytes().await?;
+        if BlobKey::from_content(&bytes) != *key {
+            bail!("remote cache returned invalid content for blob {}", key);
+        }
+        Ok(Some(bytes.to_vec()))
+    }
+
+    /// Uploads `bytes` as a content-addressed blob, unless the remote cache
+    /// already has it, and returns its key.
+    pub async fn put_blob(&self, bytes: Vec<u8>) -> Result<BlobKey> {
+        let key = BlobKey::from_content(&bytes);
+        if !self.has_blob(&key).await? {
+            let request = self
+                .request(Method::PUT, &format!("blobs/{}", key))
+                .header(header::CONTENT_TYPE, "application/octet-stream")
+                .body(bytes);
+            self.send(request).await?;
+        }
+        Ok(key)
+    }
+
+    /// Reads the blob with `key` from `store`, or downloads it from the
+    /// remote cache and stores it in `store` if it's missing.
+    pub async fn get_blob_cached(
+        &self,
+        store: &CacheStore,
+        key: &BlobKey,
+    ) -> Result<Option<Vec<u8>>> {
+        if let Some(bytes) = store.get(key)? {
+            return Ok(Some(bytes));
+        }
+        let Some(bytes) = self.get_blob(key).await? else {
+            return Ok(None);
+        };
+        store.put(*key, &bytes)?;
+        Ok(Some(bytes))
+    }
+
+    pub async fn get_entry(&self, input_hash: &str) -> Result<Option<RemoteCacheEntry>> {
+        let request = self.request(Method::GET, &format!("entries/{}", input_hash));
+        let Some(response) = self.send(request).await? else {
+            return Ok(None);
+        };
+        Ok(Some(response.json().await.with_context(|| {
+            format!("remote cache returned an invalid entry for {}", input_hash)
+        })?))
+    }
+
+    pub async fn put_entry(&self, input_hash: &str, entry: &RemoteCacheEntry) -> Result<()> {
+        let request = self
+            .request(Method::PUT, &format!("entries/{}", input_hash))
+            .json(entry);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Uploads `artifacts` as the entry for `input_hash`. The entry is only
+    /// uploaded after all of its blobs, so it never references missing
+    /// blobs.
+    pub async fn upload(
+        &self,
+        input_hash: &str,
+        artifacts: impl IntoIterator<Item = (String, Vec<u8>)>,
+    ) -> Result<RemoteCacheEntry> {
+        let mut entry = RemoteCacheEntry::default();
+        for (name, bytes) in artifacts {
+            entry.artifacts.insert(name, self.put_blob(bytes).await?);
+        }
+        self.put_entry(input_hash, &entry).await?;
+        Ok(entry)
+    }
+
+    /// Downloads all artifacts of the entry for `input_hash`, reusing and
+    /// filling `store` for their blobs. Returns `None` if the entry or any of
+    /// its blobs is missing.
+    pub async fn download(
+        &self,
+        input_hash: &str,
+        store: &CacheStore,
+    ) -> Result<Option<BTreeMap<String, Vec<u8>>>> {
+        let Some(entry) = self.get_entry(input_hash).await? else {
+            return Ok(None);
+        };
+        let mut artifacts = BTreeMap::new();
+        for (name, key) in entry.artifacts {
+            let Some(bytes) = self.get_blob_cached(store, &key).await? else {
+                return Ok(None);
+            };
+            artifacts.insert(name, bytes);
+        }
+        Ok(Some(artifacts))
+    }
+}
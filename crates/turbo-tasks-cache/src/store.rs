@@ -0,0 +1,816 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use memmap2::Mmap;
+use parking_lot::{Mutex, RwLock};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use turbo_tasks_hash::{encode_hex_string, Blake3Hasher, DeterministicHasher};
+
+use crate::{
+    format::{
+        checksum, encode_pack_header, validate_pack_header, IndexEntry, IndexHeader, RecordHeader,
+        PACK_HEADER_SIZE,
+    },
+    index::Index,
+    prune::PrunePolicy,
+};
+
+const INDEX_FILE: &str = "index";
+const PACKS_DIR: &str = "packs";
+const PACK_EXTENSION: &str = "pack";
+
+const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+/// The key of a blob in a [CacheStore].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlobKey(pub [u8; 32]);
+
+impl BlobKey {
+    /// The key of a content-addressed blob, i.e. the BLAKE3 hash of `bytes`.
+    pub fn from_content(bytes: &[u8]) -> Self {
+        let mut hasher = Blake3Hasher::new();
+        hasher.write_bytes(bytes);
+        Self(hasher.finish_bytes())
+    }
+}
+
+impl Display for BlobKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode_hex_string(&self.0))
+    }
+}
+
+impl FromStr for BlobKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut key = [0; 32];
+        if s.len() != key.len() * 2 || !s.is_ascii() {
+            bail!("invalid blob key {:?}", s);
+        }
+        for (byte, hex) in key.iter_mut().zip(s.as_bytes().chunks(2)) {
+            // The input is ASCII, so this never splits a character.
+            let hex = std::str::from_utf8(hex)?;
+            *byte =
+                u8::from_str_radix(hex, 16).with_context(|| format!("invalid blob key {:?}", s))?;
+        }
+        Ok(Self(key))
+    }
+}
+
+impl Serialize for BlobKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlobKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <Cow<str>>::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+struct PendingBlob {
+    uncompressed_len: u32,
+    compressed: Vec<u8>,
+}
+
+/// A directory of compressed blobs, see the [crate] docs for the format.
+///
+/// Blobs written with [CacheStore::put] can be read immediately, but are only
+/// persisted by [CacheStore::flush].
+///
+/// Every time a cache is opened counts as a new build. The index records the
+/// last build and time each blob has been used in, which is what
+/// [CacheStore::prune] selects the blobs to remove by.
+pub struct CacheStore {
+    dir: PathBuf,
+    compression_level: i32,
+    prune_policy: Option<PrunePolicy>,
+    /// The number of the current build.
+    build: u32,
+    index: RwLock<Index>,
+    /// Memory mapped pack files, or `None` if a pack file is missing or
+    /// invalid.
+    packs: Mutex<HashMap<u32, Option<Arc<Mmap>>>>,
+    /// Blobs that haven't been flushed yet. They stay here until the index
+    /// that contains them has replaced the old one, so they can be read while
+    /// flushing.
+    pending: Mutex<HashMap<BlobKey, Arc<PendingBlob>>>,
+    /// Keys of persisted blobs that have been read since the last flush.
+    used: Mutex<HashSet<BlobKey>>,
+    /// Keys of persisted blobs that failed validation. They are treated as
+    /// missing and dropped from the index on the next flush.
+    invalid: Mutex<HashSet<BlobKey>>,
+    /// Held while flushing or pruning.
+    files: Mutex<FilesState>,
+}
+
+struct FilesState {
+    /// The id of the next pack file.
+    next_pack: u32,
+    /// Whether the cache has been pruned by the current build.
+    pruned: bool,
+}
+
+/// Statistics of the blobs persisted in a [CacheStore].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of the current build.
+    pub build: u32,
+    pub blobs: usize,
+    /// The compressed size of all blobs in bytes.
+    pub size: u64,
+    /// The uncompressed size of all blobs in bytes.
+    pub uncompressed_size: u64,
+    pub pack_files: usize,
+    /// The size of the index and pack files in bytes. This includes removed
+    /// blobs whose pack files haven't been compacted yet.
+    pub disk_size: u64,
+}
+
+/// The outcome of [CacheStore::prune].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneResult {
+    pub removed_blobs: usize,
+    /// The number of bytes by which the pack files shrank.
+    pub freed_bytes: u64,
+}
+
+impl CacheStore {
+    /// Opens the cache in `dir`, creating it if it doesn't exist.
+    ///
+    /// When the index is missing or corrupted, it is rebuilt from the pack
+    /// files.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        let packs_dir = dir.join(PACKS_DIR);
+        fs::create_dir_all(&packs_dir)
+            .with_context(|| format!("unable to create cache directory {}", dir.display()))?;
+
+        let pack_ids = list_packs(&packs_dir)?;
+        let next_pack = pack_ids.iter().max().map_or(0, |id| id + 1);
+
+        let index_path = dir.join(INDEX_FILE);
+        let index = match Index::open(&index_path) {
+            Ok(index) => index,
+            Err(_) if pack_ids.is_empty() => Index::empty(),
+            Err(_) => {
+                // Keep the build counter if the header of the index is intact,
+                // so the recovered blobs aren't pruned as long unused.
+                let build = fs::read(&index_path)
+                    .ok()
+                    .and_then(|bytes| IndexHeader::decode(&bytes).ok())
+                    .map_or(0, |header| header.build);
+                let entries = rebuild_entries(&packs_dir, &pack_ids, build);
+                Index::write(&index_path, build, &entries)?;
+                Index::open(&index_path)?
+            }
+        };
+
+        Ok(Self {
+            dir,
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+            prune_policy: None,
+            build: index.build() + 1,
+            index: RwLock::new(index),
+            packs: Default::default(),
+            pending: Default::default(),
+            used: Default::default(),
+            invalid: Default::default(),
+            files: Mutex::new(FilesState {
+                next_pack,
+                pruned: false,
+            }),
+        })
+    }
+
+    /// Sets the zstd compression level used for new blobs.
+    pub fn with_compression_level(mut self, level: i32) -> Self {
+        self.compression_level = level;
+        self
+    }
+
+    /// Prunes the cache automatically according to `policy`: on the first
+    /// flush of every build, and on every flush that exceeds the size budget.
+    pub fn with_prune_policy(mut self, policy: PrunePolicy) -> Self {
+        self.prune_policy = Some(policy);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether a valid blob is stored for `key`. The content of the blob is
+    /// only validated once it is read.
+    pub fn contains(&self, key: &BlobKey) -> bool {
+        if self.pending.lock().contains_key(key) {
+            return true;
+        }
+        if self.invalid.lock().contains(key) {
+            return false;
+        }
+        self.index.read().get(key).is_some()
+    }
+
+    /// Reads the blob stored for `key`. Returns `None` if there is no blob or
+    /// if it is corrupted.
+    pub fn get(&self, key: &BlobKey) -> Result<Option<Vec<u8>>> {
+        if let Some(blob) = self.pending.lock().get(key) {
+            return Ok(Some(zstd::bulk::decompress(
+                &blob.compressed,
+                blob.uncompressed_len as usize,
+            )?));
+        }
+        if self.invalid.lock().contains(key) {
+            return Ok(None);
+        }
+        let Some(entry) = self.index.read().get(key) else {
+            return Ok(None);
+        };
+        let blob = self.read_entry(&entry);
+        if blob.is_some() {
+            self.used.lock().insert(*key);
+        } else {
+            self.invalid.lock().insert(*key);
+        }
+        Ok(blob)
+    }
+
+    fn read_entry(&self, entry: &IndexEntry) -> Option<Vec<u8>> {
+        self.with_compressed_blob(entry, |compressed| {
+            zstd::bulk::decompress(compressed, entry.uncompressed_len as usize).ok()
+        })
+        .flatten()
+    }
+
+    /// Calls `f` with the compressed blob of `entry`, if its checksum is
+    /// valid.
+    fn with_compressed_blob<R>(&self, entry: &IndexEntry, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        let pack = self.pack(entry.pack)?;
+        let start = usize::try_from(entry.offset).ok()?;
+        let compressed = pack.get(start..start + entry.compressed_len as usize)?;
+        if checksum(compressed) != entry.checksum {
+            return None;
+        }
+        Some(f(compressed))
+    }
+
+    fn pack(&self, id: u32) -> Option<Arc<Mmap>> {
+        self.packs
+            .lock()
+            .entry(id)
+            .or_insert_with(|| {
+                let file = File::open(pack_path(&self.dir.join(PACKS_DIR), id)).ok()?;
+                // SAFETY: Pack files are never modified after they have been
+                // written.
+                let mmap = unsafe { Mmap::map(&file) }.ok()?;
+                validate_pack_header(&mmap).ok()?;
+                Some(Arc::new(mmap))
+            })
+            .clone()
+    }
+
+    /// Stores `bytes` under `key`, replacing any existing blob.
+    pub fn put(&self, key: BlobKey, bytes: &[u8]) -> Result<()> {
+        let Ok(uncompressed_len) = u32::try_from(bytes.len()) else {
+            bail!("blob {} is too large ({} bytes)", key, bytes.len());
+        };
+        let compressed = zstd::bulk::compress(bytes, self.compression_level)?;
+        if u32::try_from(compressed.len()).is_err() {
+            bail!("blob {} is too large ({} bytes)", key, compressed.len());
+        }
+        self.pending.lock().insert(
+            key,
+            Arc::new(PendingBlob {
+                uncompressed_len,
+                compressed,
+            }),
+        );
+        Ok(())
+    }
+
+    /// Stores `bytes` as a content-addressed blob, unless it's already
+    /// stored, and returns its key.
+    pub fn put_content(&self, bytes: &[u8]) -> Result<BlobKey> {
+        let key = BlobKey::from_content(bytes);
+        if !self.contains(&key) {
+            self.put(key, bytes)?;
+        } else if !self.pending.lock().contains_key(&key) {
+            self.used.lock().insert(key);
+        }
+        Ok(key)
+    }
+
+    /// Persists all blobs stored since the last flush into a new pack file
+    /// and updates the index.
+    pub fn flush(&self) -> Result<()> {
+        let mut files = self.files.lock();
+        self.flush_locked(&mut files)?;
+        if let Some(policy) = &self.prune_policy {
+            if !files.pruned || policy.exceeds_size(blobs_size(&self.index.read())) {
+                self.prune_locked(&mut files, policy)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_locked(&self, files: &mut FilesState) -> Result<()> {
+        let pending = self.pending.lock().clone();
+        let used = std::mem::take(&mut *self.used.lock());
+        let invalid = std::mem::take(&mut *self.invalid.lock());
+        if pending.is_empty() && used.is_empty() && invalid.is_empty() {
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let blobs = pending.iter().map(|(key, blob)| {
+            let entry = IndexEntry {
+                key: *key,
+                pack: 0,
+                compressed_len: blob.compressed.len() as u32,
+                uncompressed_len: blob.uncompressed_len,
+                offset: 0,
+                checksum: checksum(&blob.compressed),
+                last_used_build: self.build,
+                last_used_at: now,
+            };
+            (entry, &blob.compressed[..])
+        });
+        let new_entries = if pending.is_empty() {
+            Vec::new()
+        } else {
+            match self.write_pack(files.next_pack, blobs) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    // The blobs are still pending, so they are written by the
+                    // next flush.
+                    self.used.lock().extend(used);
+                    self.invalid.lock().extend(invalid);
+                    return Err(err);
+                }
+            }
+        };
+        if !new_entries.is_empty() {
+            files.next_pack += 1;
+        }
+
+        let mut index = self.index.write();
+        let entries = index
+            .iter()
+            .filter(|entry| !pending.contains_key(&entry.key) && !invalid.contains(&entry.key))
+            .map(|mut entry| {
+                if used.contains(&entry.key) {
+                    entry.last_used_build = self.build;
+                    entry.last_used_at = now;
+                }
+                entry
+            })
+            .chain(new_entries)
+            .collect();
+        self.replace_index(&mut index, entries)?;
+        drop(index);
+
+        // Blobs that have been replaced while flushing are written by the next
+        // flush.
+        self.pending.lock().retain(|key, blob| {
+            pending
+                .get(key)
+                .map_or(true, |flushed| !Arc::ptr_eq(flushed, blob))
+        });
+        Ok(())
+    }
+
+    /// Removes the persisted blobs that exceed `policy`, after flushing.
+    ///
+    /// Pack files that no longer contain any blobs are deleted, and pack files
+    /// that mostly contain removed blobs are compacted.
+    pub fn prune(&self, policy: &PrunePolicy) -> Result<PruneResult> {
+        let mut files = self.files.lock();
+        self.flush_locked(&mut files)?;
+        self.prune_locked(&mut files, policy)
+    }
+
+    fn prune_locked(&self, files: &mut FilesState, policy: &PrunePolicy) -> Result<PruneResult> {
+        files.pruned = true;
+        let packs_dir = self.dir.join(PACKS_DIR);
+        let pack_ids = list_packs(&packs_dir)?;
+        let size_before = pack_files_size(&packs_dir, &pack_ids);
+
+        let mut index = self.index.write();
+        let mut entries = index.iter().collect::<Vec<_>>();
+        let blobs_before = entries.len();
+        policy.retain(&mut entries, self.build, unix_now());
+        let removed_blobs = blobs_before - entries.len();
+
+        // Copy the remaining blobs of pack files that mostly contain removed
+        // blobs into a new pack file.
+        let mut live_sizes = HashMap::<u32, u64>::new();
+        for entry in &entries {
+            *live_sizes.entry(entry.pack).or_default() +=
+                (RecordHeader::SIZE + entry.compressed_len as usize) as u64;
+        }
+        let sparse_packs = pack_ids
+            .iter()
+            .copied()
+            .filter(|&id| {
+                let live_size = live_sizes.get(&id).copied().unwrap_or_default();
+                let size = fs::metadata(pack_path(&packs_dir, id)).map_or(0, |m| m.len());
+                live_size > 0 && live_size * 2 < size.saturating_sub(PACK_HEADER_SIZE as u64)
+            })
+            .collect::<HashSet<_>>();
+        if !sparse_packs.is_empty() {
+            let (moved, mut kept): (Vec<_>, Vec<_>) = entries
+                .into_iter()
+                .partition(|entry| sparse_packs.contains(&entry.pack));
+            // Blobs that fail validation are dropped.
+            let blobs = moved
+                .iter()
+                .filter_map(|entry| {
+                    Some((*entry, self.with_compressed_blob(entry, <[u8]>::to_vec)?))
+                })
+                .collect::<Vec<_>>();
+            let compacted = self.write_pack(
+                files.next_pack,
+                blobs.iter().map(|(entry, blob)| (*entry, &blob[..])),
+            )?;
+            files.next_pack += 1;
+            kept.extend(compacted);
+            entries = kept;
+        }
+
+        let live_packs = entries
+            .iter()
+            .map(|entry| entry.pack)
+            .collect::<HashSet<_>>();
+        self.replace_index(&mut index, entries)?;
+        drop(index);
+
+        let mut packs = self.packs.lock();
+        for id in pack_ids {
+            if !live_packs.contains(&id) {
+                packs.remove(&id);
+                // This fails on some platforms while the pack file is still
+                // mapped. It will be deleted by the next prune then.
+                let _ = fs::remove_file(pack_path(&packs_dir, id));
+            }
+        }
+        drop(packs);
+
+        let size_after = pack_files_size(&packs_dir, &list_packs(&packs_dir)?);
+        Ok(PruneResult {
+            removed_blobs,
+            freed_bytes: size_before.saturating_sub(size_after),
+        })
+    }
+
+    /// Statistics of the persisted blobs. Blobs that haven't been flushed yet
+    /// are not included.
+    pub fn stats(&self) -> Result<CacheStats> {
+        let index = self.index.read();
+        let packs_dir = self.dir.join(PACKS_DIR);
+        let pack_ids = list_packs(&packs_dir)?;
+        let index_size = fs::metadata(self.dir.join(INDEX_FILE)).map_or(0, |m| m.len());
+        Ok(CacheStats {
+            build: self.build,
+            blobs: index.iter().count(),
+            size: blobs_size(&index),
+            uncompressed_size: index
+                .iter()
+                .map(|entry| entry.uncompressed_len as u64)
+                .sum(),
+            pack_files: pack_ids.len(),
+            disk_size: index_size + pack_files_size(&packs_dir, &pack_ids),
+        })
+    }
+
+    fn replace_index(&self, index: &mut Index, mut entries: Vec<IndexEntry>) -> Result<()> {
+        entries.sort_unstable_by_key(|entry| entry.key);
+        let index_path = self.dir.join(INDEX_FILE);
+        Index::write(&index_path, self.build, &entries)?;
+        *index = Index::open(&index_path)?;
+        Ok(())
+    }
+
+    /// Writes `blobs` into a new pack file and returns their entries, which
+    /// are taken from `blobs` with the location updated.
+    fn write_pack<'a>(
+        &self,
+        pack_id: u32,
+        blobs: impl IntoIterator<Item = (IndexEntry, &'a [u8])>,
+    ) -> Result<Vec<IndexEntry>> {
+        let path = pack_path(&self.dir.join(PACKS_DIR), pack_id);
+        let tmp_path = path.with_extension("tmp");
+        let file = File::create(&tmp_path)
+            .with_context(|| format!("unable to create {}", tmp_path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&encode_pack_header())?;
+
+        let mut offset = PACK_HEADER_SIZE as u64;
+        let mut entries = Vec::new();
+        for (mut entry, compressed) in blobs {
+            let header = RecordHeader {
+                key: entry.key,
+                uncompressed_len: entry.uncompressed_len,
+                compressed_len: entry.compressed_len,
+                checksum: entry.checksum,
+            };
+            writer.write_all(&header.encode())?;
+            writer.write_all(compressed)?;
+            offset += RecordHeader::SIZE as u64;
+            entry.pack = pack_id;
+            entry.offset = offset;
+            entries.push(entry);
+            offset += entry.compressed_len as u64;
+        }
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&tmp_path, &path)
+            .with_context(|| format!("unable to write {}", path.display()))?;
+        Ok(entries)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+fn blobs_size(index: &Index) -> u64 {
+    index.iter().map(|entry| entry.compressed_len as u64).sum()
+}
+
+fn pack_files_size(packs_dir: &Path, pack_ids: &[u32]) -> u64 {
+    pack_ids
+        .iter()
+        .map(|&id| fs::metadata(pack_path(packs_dir, id)).map_or(0, |m| m.len()))
+        .sum()
+}
+
+fn pack_path(packs_dir: &Path, id: u32) -> PathBuf {
+    packs_dir.join(format!("{:08}.{}", id, PACK_EXTENSION))
+}
+
+fn list_packs(packs_dir: &Path) -> io::Result<Vec<u32>> {
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(packs_dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == PACK_EXTENSION) {
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok())
+            {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+/// Recovers the index entries from the pack files. Blobs of later packs
+/// replace blobs of earlier ones. Reading a pack stops at the first invalid
+/// record, e.g. when writing it was interrupted.
+///
+/// The blobs are considered used by `build`, the last build before the index
+/// was lost.
+fn rebuild_entries(packs_dir: &Path, pack_ids: &[u32], build: u32) -> Vec<IndexEntry> {
+    let mut entries = HashMap::new();
+    for &id in pack_ids {
+        let Ok(file) = File::open(pack_path(packs_dir, id)) else {
+            continue;
+        };
+        // SAFETY: Pack files are never modified after they have been written.
+        let Ok(mmap) = (unsafe { Mmap::map(&file) }) else {
+            continue;
+        };
+        if validate_pack_header(&mmap).is_err() {
+            continue;
+        }
+        // When the blobs were last used is lost with the index, so the time the
+        // pack file was written is used instead.
+        let last_used_at = file
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs());
+        let mut offset = PACK_HEADER_SIZE;
+        while let Some(bytes) = mmap.get(offset..offset + RecordHeader::SIZE) {
+            let header = RecordHeader::decode(bytes);
+            let start = offset + RecordHeader::SIZE;
+            let end = start + header.compressed_len as usize;
+            match mmap.get(start..end) {
+                Some(compressed) if checksum(compressed) == header.checksum => {}
+                _ => break,
+            }
+            entries.insert(
+                header.key,
+                IndexEntry {
+                    key: header.key,
+                    pack: id,
+                    compressed_len: header.compressed_len,
+                    uncompressed_len: header.uncompressed_len,
+                    offset: start as u64,
+                    checksum: header.checksum,
+                    last_used_build: build,
+                    last_used_at,
+                },
+            );
+            offset = end;
+        }
+    }
+    let mut entries = entries.into_values().collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|entry| entry.key);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+
+    use super::*;
+
+    fn corrupt_last_byte(path: &Path) {
+        let mut bytes = fs::read(path).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::open(dir.path()).unwrap();
+        let a = store.put_content(b"hello").unwrap();
+        let b = store.put_content(&[42; 10_000]).unwrap();
+        assert_eq!(store.get(&a).unwrap().as_deref(), Some(&b"hello"[..]));
+        store.flush().unwrap();
+        let c = store.put_content(b"world").unwrap();
+        store.flush().unwrap();
+        drop(store);
+
+        let store = CacheStore::open(dir.path()).unwrap();
+        assert_eq!(store.get(&a).unwrap().as_deref(), Some(&b"hello"[..]));
+        assert_eq!(store.get(&b).unwrap(), Some(vec![42; 10_000]));
+        assert_eq!(store.get(&c).unwrap().as_deref(), Some(&b"world"[..]));
+        assert_eq!(store.get(&BlobKey([0; 32])).unwrap(), None);
+    }
+
+    #[test]
+    fn test_replace() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::open(dir.path()).unwrap();
+        let key = BlobKey([1; 32]);
+        store.put(key, b"old").unwrap();
+        store.flush().unwrap();
+        store.put(key, b"new").unwrap();
+        store.flush().unwrap();
+        drop(store);
+
+        let store = CacheStore::open(dir.path()).unwrap();
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some(&b"new"[..]));
+    }
+
+    #[test]
+    fn test_corrupted_blob() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::open(dir.path()).unwrap();
+        let a = store.put_content(b"first").unwrap();
+        store.flush().unwrap();
+        let b = store.put_content(b"second").unwrap();
+        store.flush().unwrap();
+        drop(store);
+
+        corrupt_last_byte(&pack_path(&dir.path().join(PACKS_DIR), 1));
+
+        let store = CacheStore::open(dir.path()).unwrap();
+        assert_eq!(store.get(&a).unwrap().as_deref(), Some(&b"first"[..]));
+        assert_eq!(store.get(&b).unwrap(), None);
+        assert!(!store.contains(&b));
+        store.put_content(b"second").unwrap();
+        store.flush().unwrap();
+        assert_eq!(store.get(&b).unwrap().as_deref(), Some(&b"second"[..]));
+    }
+
+    #[test]
+    fn test_rebuild_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::open(dir.path()).unwrap();
+        let a = store.put_content(b"first").unwrap();
+        store.flush().unwrap();
+        let b = store.put_content(b"second").unwrap();
+        store.flush().unwrap();
+        drop(store);
+
+        corrupt_last_byte(&dir.path().join(INDEX_FILE));
+        // Simulate a write of the last pack that was interrupted.
+        let pack = pack_path(&dir.path().join(PACKS_DIR), 1);
+        let len = fs::metadata(&pack).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&pack)
+            .unwrap()
+            .set_len(len - 1)
+            .unwrap();
+
+        let store = CacheStore::open(dir.path()).unwrap();
+        assert_eq!(store.get(&a).unwrap().as_deref(), Some(&b"first"[..]));
+        assert_eq!(store.get(&b).unwrap(), None);
+    }
+
+    #[test]
+    fn test_rebuild_index_keeps_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::open(dir.path()).unwrap();
+        let a = store.put_content(b"first").unwrap();
+        store.flush().unwrap();
+        drop(store);
+        let store = CacheStore::open(dir.path()).unwrap();
+        store.put_content(b"second").unwrap();
+        store.flush().unwrap();
+        drop(store);
+
+        corrupt_last_byte(&dir.path().join(INDEX_FILE));
+
+        let store = CacheStore::open(dir.path()).unwrap();
+        assert_eq!(store.stats().unwrap().build, 3);
+        store
+            .prune(&PrunePolicy {
+                max_unused_builds: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(store.get(&a).unwrap().as_deref(), Some(&b"first"[..]));
+    }
+
+    #[test]
+    fn test_index_with_invalid_entry_count() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(PACKS_DIR)).unwrap();
+        let header = IndexHeader {
+            build: 1,
+            entry_count: u64::MAX / 2,
+            checksum: checksum(&[]),
+        };
+        fs::write(dir.path().join(INDEX_FILE), header.encode()).unwrap();
+
+        let store = CacheStore::open(dir.path()).unwrap();
+        assert_eq!(store.stats().unwrap().blobs, 0);
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::open(dir.path()).unwrap();
+        let a = store.put_content(&(0..=255).collect::<Vec<u8>>()).unwrap();
+        let b = store.put_content(&[1; 1000]).unwrap();
+        let c = store.put_content(b"unused").unwrap();
+        store.flush().unwrap();
+        drop(store);
+
+        let store = CacheStore::open(dir.path()).unwrap();
+        assert_eq!(store.stats().unwrap().build, 2);
+        store.get(&a).unwrap();
+        store.put_content(&[1; 1000]).unwrap();
+        let d = store.put_content(b"second").unwrap();
+        let result = store
+            .prune(&PrunePolicy {
+                max_unused_builds: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(result.removed_blobs, 1);
+        assert!(!store.contains(&c));
+        drop(store);
+
+        let store = CacheStore::open(dir.path())
+            .unwrap()
+            .with_prune_policy(PrunePolicy {
+                max_size: Some(100),
+                ..Default::default()
+            });
+        store.get(&d).unwrap();
+        store.flush().unwrap();
+        assert_eq!(store.get(&d).unwrap().as_deref(), Some(&b"second"[..]));
+        assert!(!store.contains(&a));
+        assert!(!store.contains(&b));
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.blobs, 1);
+        // The first pack file no longer contains any blobs.
+        assert_eq!(stats.pack_files, 1);
+    }
+}
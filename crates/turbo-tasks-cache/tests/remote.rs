@@ -0,0 +1,96 @@
+#![cfg(test)]
+
+use httpmock::Method::{GET, HEAD, PUT};
+use turbo_tasks_cache::{
+    remote::{RemoteCacheClient, RemoteCacheOptions},
+    BlobKey, CacheStore,
+};
+
+fn client(server: &httpmock::MockServer) -> RemoteCacheClient {
+    RemoteCacheClient::new(RemoteCacheOptions {
+        url: server.base_url(),
+        token: "secret".to_string(),
+        namespace: Some("team".to_string()),
+        timeout: None,
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn uploads_missing_blobs_and_entry() {
+    let server = httpmock::MockServer::start();
+    let key = BlobKey::from_content(b"chunk");
+    let blob_path = format!("/v1/blobs/{}", key);
+
+    let head_mock = server.mock(|when, then| {
+        when.method(HEAD)
+            .path(&blob_path)
+            .query_param("namespace", "team")
+            .header("Authorization", "Bearer secret");
+        then.status(404);
+    });
+    let put_blob_mock = server.mock(|when, then| {
+        when.method(PUT).path(&blob_path).body("chunk");
+        then.status(200);
+    });
+    let put_entry_mock = server.mock(|when, then| {
+        when.method(PUT)
+            .path("/v1/entries/abc")
+            .json_body(serde_json::json!({ "artifacts": { "main.js": key.to_string() } }));
+        then.status(200);
+    });
+
+    let entry = client(&server)
+        .upload("abc", [("main.js".to_string(), b"chunk".to_vec())])
+        .await
+        .unwrap();
+    assert_eq!(entry.artifacts["main.js"], key);
+    head_mock.assert();
+    put_blob_mock.assert();
+    put_entry_mock.assert();
+}
+
+#[tokio::test]
+async fn downloads_into_store() {
+    let server = httpmock::MockServer::start();
+    let key = BlobKey::from_content(b"chunk");
+
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/entries/abc");
+        then.status(200)
+            .json_body(serde_json::json!({ "artifacts": { "main.js": key.to_string() } }));
+    });
+    let get_blob_mock = server.mock(|when, then| {
+        when.method(GET).path(format!("/v1/blobs/{}", key));
+        then.status(200).body("chunk");
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/v1/entries/missing");
+        then.status(404);
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let store = CacheStore::open(dir.path()).unwrap();
+    let client = client(&server);
+
+    let artifacts = client.download("abc", &store).await.unwrap().unwrap();
+    assert_eq!(artifacts["main.js"], b"chunk");
+    // The second download is served from the store.
+    client.download("abc", &store).await.unwrap().unwrap();
+    get_blob_mock.assert_hits(1);
+    assert_eq!(store.get(&key).unwrap().as_deref(), Some(&b"chunk"[..]));
+
+    assert!(client.download("missing", &store).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn rejects_invalid_blobs() {
+    let server = httpmock::MockServer::start();
+    let key = BlobKey::from_content(b"chunk");
+    server.mock(|when, then| {
+        when.method(GET).path(format!("/v1/blobs/{}", key));
+        then.status(200).body("tampered");
+    });
+
+    assert!(client(&server).get_blob(&key).await.is_err());
+}
@@ -14,6 +14,7 @@ mod invalidator_map;
 pub mod json;
 mod mutex_map;
 mod read_glob;
+pub mod recording;
 mod retry;
 pub mod rope;
 pub mod source_context;
@@ -31,6 +32,7 @@ use std::{
     io::{
         BufRead, ErrorKind, {self},
     },
+    iter::once,
     mem::take,
     path::{Path, PathBuf, MAIN_SEPARATOR},
     sync::{
@@ -51,6 +53,7 @@ use mime::Mime;
 use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use read_glob::read_glob;
 pub use read_glob::ReadGlobResult;
+use recording::{InvalidationRecorder, RecordedChange, RecordedChangeKind};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::{
@@ -183,6 +186,10 @@ pub struct DiskFileSystem {
     #[turbo_tasks(debug_ignore, trace_ignore)]
     #[serde(skip)]
     watcher: Arc<DiskWatcher>,
+    /// Records the changes detected by the watcher, if set.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    #[serde(skip)]
+    invalidation_recorder: Arc<Mutex<Option<Arc<InvalidationRecorder>>>>,
 }
 
 impl DiskFileSystem {
@@ -256,6 +263,33 @@ impl DiskFileSystem {
         }
     }
 
+    /// Invalidates all reads of `path` (relative to the root, with `/`
+    /// separators) and everything below it, as if it had been changed on
+    /// disk.
+    pub async fn invalidate_subtree(&self, path: &str) {
+        let root_path = self.root_path().to_path_buf();
+        let path = root_path.join(&*unix_to_sys(path));
+        let report_invalidation_reason = Some((self.name.clone(), root_path));
+        let _lock = self.invalidation_lock.write().await;
+        if let Some(parent) = path.parent() {
+            invalidate_path(
+                &report_invalidation_reason,
+                &mut self.dir_invalidator_map.lock().unwrap(),
+                once(parent.to_path_buf()),
+            );
+        }
+        invalidate_path_and_children_execute(
+            &report_invalidation_reason,
+            &mut self.invalidator_map.lock().unwrap(),
+            once(path.clone()),
+        );
+        invalidate_path_and_children_execute(
+            &report_invalidation_reason,
+            &mut self.dir_invalidator_map.lock().unwrap(),
+            once(path),
+        );
+    }
+
     pub fn start_watching(&self) -> Result<()> {
         self.start_watching_internal(false)
     }
@@ -278,6 +312,8 @@ impl DiskFileSystem {
             report_invalidation_reason.then(|| (self.name.clone(), root_path.clone()));
 
         let invalidation_lock = self.invalidation_lock.clone();
+        let invalidation_recorder = self.invalidation_recorder.clone();
+        let name = self.name.clone();
         // Create a channel to receive the events.
         let (tx, rx) = channel();
         // Linux watching is too fast, so we need to throttle it a bit to avoid reading
@@ -410,50 +446,6 @@ impl DiskFileSystem {
                     }
                     event = rx.try_recv();
                 }
-                #[instrument(parent = None, level = Level::INFO, name = "DiskFileSystem file change", skip_all, fields(name = display(path.display())))]
-                fn invalidate(
-                    report_invalidation_reason: &Option<(String, PathBuf)>,
-                    path: &Path,
-                    invalidator: Invalidator,
-                ) {
-                    if let Some((name, root_path)) = report_invalidation_reason {
-                        if let Some(path) = format_absolute_fs_path(path, name, root_path) {
-                            invalidator.invalidate_with_reason(WatchChange { path });
-                            return;
-                        }
-                    }
-                    invalidator.invalidate();
-                }
-                fn invalidate_path(
-                    report_invalidation_reason: &Option<(String, PathBuf)>,
-                    invalidator_map: &mut HashMap<String, HashSet<Invalidator>>,
-                    paths: impl Iterator<Item = PathBuf>,
-                ) {
-                    for path in paths {
-                        let key = path_to_key(&path);
-                        if let Some(invalidators) = invalidator_map.remove(&key) {
-                            invalidators
-                                .into_iter()
-                                .for_each(|i| invalidate(report_invalidation_reason, &path, i));
-                        }
-                    }
-                }
-                fn invalidate_path_and_children_execute(
-                    report_invalidation_reason: &Option<(String, PathBuf)>,
-                    invalidator_map: &mut HashMap<String, HashSet<Invalidator>>,
-                    paths: impl Iterator<Item = PathBuf>,
-                ) {
-                    for path in paths {
-                        let path_key = path_to_key(&path);
-                        for (_, invalidators) in
-                            invalidator_map.extract_if(|key, _| key.starts_with(&path_key))
-                        {
-                            invalidators
-                                .into_iter()
-                                .for_each(|i| invalidate(report_invalidation_reason, &path, i));
-                        }
-                    }
-                }
                 // We need to start watching first before invalidating the changed paths
                 #[cfg(not(any(target_os = "macos", target_os = "windows")))]
                 {
@@ -461,6 +453,14 @@ impl DiskFileSystem {
                         let _ = disk_watcher.restore_if_watching(&path, &root_path);
                     }
                 }
+                if let Some(recorder) = &*invalidation_recorder.lock().unwrap() {
+                    recorder.record_changes(
+                        &name,
+                        &root_path,
+                        &batched_invalidate_path,
+                        &batched_invalidate_path_and_children,
+                    );
+                }
                 let _lock = invalidation_lock.blocking_write();
                 {
                     let mut invalidator_map = invalidator_map.lock().unwrap();
@@ -493,6 +493,74 @@ impl DiskFileSystem {
         Ok(())
     }
 
+    /// Records all changes detected by the watcher with `recorder`, or stops
+    /// recording when `None` is passed.
+    pub fn set_invalidation_recorder(&self, recorder: Option<Arc<InvalidationRecorder>>) {
+        *self.invalidation_recorder.lock().unwrap() = recorder;
+    }
+
+    /// Replays changes captured by an [InvalidationRecorder]: the recorded
+    /// state of the changed paths is restored on disk and the tasks that read
+    /// them are invalidated, the same way the watcher would invalidate them.
+    ///
+    /// Watching should be stopped while replaying, so the replayed changes are
+    /// the only source of invalidations.
+    pub async fn replay_changes(&self, changes: &[RecordedChange]) -> Result<()> {
+        let root_path = self.root_path().to_path_buf();
+        let mut writes = HashSet::new();
+        let mut trees = HashSet::new();
+        let mut dirs = HashSet::new();
+        // Check all paths before touching the disk, so an invalid recording
+        // doesn't leave the changes applied partially.
+        for change in changes {
+            change.resolve_path(&root_path)?;
+        }
+        for change in changes {
+            let path = change.apply(&root_path).await?;
+            match change.kind {
+                RecordedChangeKind::Write => {
+                    writes.insert(path);
+                }
+                RecordedChangeKind::Tree => {
+                    if let Some(parent) = path.parent() {
+                        dirs.insert(parent.to_path_buf());
+                    }
+                    trees.insert(path);
+                }
+            }
+        }
+
+        let report_invalidation_reason = Some((self.name.clone(), root_path));
+        let _lock = self.invalidation_lock.write().await;
+        {
+            let mut invalidator_map = self.invalidator_map.lock().unwrap();
+            invalidate_path(
+                &report_invalidation_reason,
+                &mut invalidator_map,
+                writes.into_iter(),
+            );
+            invalidate_path_and_children_execute(
+                &report_invalidation_reason,
+                &mut invalidator_map,
+                trees.iter().cloned(),
+            );
+        }
+        {
+            let mut dir_invalidator_map = self.dir_invalidator_map.lock().unwrap();
+            invalidate_path(
+                &report_invalidation_reason,
+                &mut dir_invalidator_map,
+                dirs.into_iter(),
+            );
+            invalidate_path_and_children_execute(
+                &report_invalidation_reason,
+                &mut dir_invalidator_map,
+                trees.into_iter(),
+            );
+        }
+        Ok(())
+    }
+
     pub fn stop_watching(&self) {
         if let Some(watcher) = self.watcher.watcher.lock().unwrap().take() {
             drop(watcher);
@@ -531,6 +599,49 @@ impl DiskFileSystem {
     }
 }
 
+#[instrument(parent = None, level = Level::INFO, name = "DiskFileSystem file change", skip_all, fields(name = display(path.display())))]
+fn invalidate(
+    report_invalidation_reason: &Option<(String, PathBuf)>,
+    path: &Path,
+    invalidator: Invalidator,
+) {
+    if let Some((name, root_path)) = report_invalidation_reason {
+        if let Some(path) = format_absolute_fs_path(path, name, root_path) {
+            invalidator.invalidate_with_reason(WatchChange { path });
+            return;
+        }
+    }
+    invalidator.invalidate();
+}
+fn invalidate_path(
+    report_invalidation_reason: &Option<(String, PathBuf)>,
+    invalidator_map: &mut HashMap<String, HashSet<Invalidator>>,
+    paths: impl Iterator<Item = PathBuf>,
+) {
+    for path in paths {
+        let key = path_to_key(&path);
+        if let Some(invalidators) = invalidator_map.remove(&key) {
+            invalidators
+                .into_iter()
+                .for_each(|i| invalidate(report_invalidation_reason, &path, i));
+        }
+    }
+}
+fn invalidate_path_and_children_execute(
+    report_invalidation_reason: &Option<(String, PathBuf)>,
+    invalidator_map: &mut HashMap<String, HashSet<Invalidator>>,
+    paths: impl Iterator<Item = PathBuf>,
+) {
+    for path in paths {
+        let path_key = path_to_key(&path);
+        for (_, invalidators) in invalidator_map.extract_if(|key, _| key.starts_with(&path_key)) {
+            invalidators
+                .into_iter()
+                .for_each(|i| invalidate(report_invalidation_reason, &path, i));
+        }
+    }
+}
+
 struct PathLockGuard<'a>(
     RwLockReadGuard<'a, ()>,
     mutex_map::MutexMapGuard<'a, PathBuf>,
@@ -571,6 +682,7 @@ impl DiskFileSystem {
             invalidator_map: Arc::new(InvalidatorMap::new()),
             dir_invalidator_map: Arc::new(InvalidatorMap::new()),
             watcher: Default::default(),
+            invalidation_recorder: Default::default(),
         };
 
         Ok(Self::cell(instance))
@@ -2272,4 +2384,47 @@ mod tests {
         .await
         .unwrap()
     }
+
+    #[tokio::test]
+    async fn invalidate_subtree() {
+        crate::register();
+
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/a.txt"), "a").unwrap();
+        std::fs::write(root.path().join("b.txt"), "b").unwrap();
+        let root = root.into_path();
+
+        let tt = turbo_tasks::TurboTasks::new(turbo_tasks_memory::MemoryBackend::default());
+        tt.run_once(async move {
+            let disk_fs = DiskFileSystem::new("test".to_string(), root.to_string_lossy().into());
+            let fs = Vc::upcast::<Box<dyn FileSystem>>(disk_fs);
+            let a = fs.root().join("src/a.txt".to_string());
+            let b = fs.root().join("b.txt".to_string());
+            assert_eq!(read_to_string(a).await?, "a");
+            assert_eq!(read_to_string(b).await?, "b");
+
+            // Nothing is watching, so the changes are only picked up once the
+            // paths are invalidated.
+            std::fs::write(root.join("src/a.txt"), "changed a")?;
+            std::fs::write(root.join("b.txt"), "changed b")?;
+            assert_eq!(read_to_string(a).await?, "a");
+
+            disk_fs.await?.invalidate_subtree("src").await;
+            assert_eq!(read_to_string(a).await?, "changed a");
+            assert_eq!(read_to_string(b).await?, "b");
+
+            std::fs::remove_dir_all(&root)?;
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn read_to_string(path: Vc<FileSystemPath>) -> Result<String> {
+        match &*path.read().strongly_consistent().await? {
+            FileContent::Content(file) => Ok(file.content().to_str()?.into_owned()),
+            FileContent::NotFound => bail!("{} not found", path.to_string().await?),
+        }
+    }
 }
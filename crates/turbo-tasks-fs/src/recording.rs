@@ -0,0 +1,329 @@
+//! Recording of file system invalidations and the updates they cause, to be
+//! replayed deterministically with [DiskFileSystem::replay_changes].
+//!
+//! A recording is a file of JSON lines, each being a [RecordedEvent].
+//!
+//! [DiskFileSystem::replay_changes]: crate::DiskFileSystem::replay_changes
+
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, ErrorKind, Write},
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::util::{normalize_path, sys_to_unix, unix_to_sys};
+
+/// An event in a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RecordedEvent {
+    /// A batch of changes detected by the watcher of a file system, which were
+    /// invalidated together.
+    #[serde(rename_all = "camelCase")]
+    Changes {
+        /// The name of the file system.
+        fs: String,
+        elapsed_ms: u64,
+        changes: Vec<RecordedChange>,
+    },
+    /// An update of the task graph, i.e. the task re-executions caused by
+    /// previous invalidations.
+    #[serde(rename_all = "camelCase")]
+    Update {
+        elapsed_ms: u64,
+        duration_ms: u64,
+        tasks: usize,
+        reasons: String,
+    },
+}
+
+/// A single change to a path of a file system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedChange {
+    pub kind: RecordedChangeKind,
+    /// The path relative to the root of the file system, using `/` as
+    /// separator.
+    pub path: String,
+    /// Whether the path existed when the change was recorded.
+    pub exists: bool,
+    /// The content of the file when the change was recorded, if it was a
+    /// UTF-8 text file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// The content of the file when the change was recorded, if it was not a
+    /// UTF-8 text file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_content: Option<Vec<u8>>,
+}
+
+impl RecordedChange {
+    /// Returns the path of the change below `root_path`.
+    ///
+    /// Recordings can come from anywhere, so paths that are absolute or not
+    /// normalized are rejected, as are paths that lead out of `root_path`
+    /// through a symlink.
+    pub fn resolve_path(&self, root_path: &Path) -> Result<PathBuf> {
+        let relative_path = PathBuf::from(&*unix_to_sys(&self.path));
+        if normalize_path(&self.path).as_deref() != Some(self.path.as_str())
+            || !relative_path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            bail!("invalid path {:?} in recording", self.path);
+        }
+        let path = root_path.join(relative_path);
+        // The path itself might not exist yet, so check the closest existing
+        // ancestor.
+        let root_path = root_path.canonicalize()?;
+        for ancestor in path.ancestors() {
+            match ancestor.canonicalize() {
+                Ok(ancestor) if ancestor.starts_with(&root_path) => return Ok(path),
+                Ok(_) => break,
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        bail!("path {:?} in recording is outside of the root", self.path)
+    }
+
+    /// Restores the recorded state of the change below `root_path` on disk,
+    /// and returns its path. The root itself is never removed.
+    pub async fn apply(&self, root_path: &Path) -> Result<PathBuf> {
+        let path = self.resolve_path(root_path)?;
+        let content = match (&self.content, &self.binary_content) {
+            (Some(content), _) => Some(content.as_bytes()),
+            (None, Some(content)) => Some(content.as_slice()),
+            (None, None) => None,
+        };
+        if let Some(content) = content {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, content).await?;
+        } else if !self.exists && !self.path.is_empty() {
+            match tokio::fs::symlink_metadata(&path).await {
+                Ok(metadata) if metadata.is_dir() => tokio::fs::remove_dir_all(&path).await?,
+                Ok(_) => tokio::fs::remove_file(&path).await?,
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(path)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordedChangeKind {
+    /// The content of a file changed.
+    Write,
+    /// The path was created, removed or renamed, which affects the path, its
+    /// children and its parent directory.
+    Tree,
+}
+
+/// Writes a recording of invalidations and updates to a file.
+pub struct InvalidationRecorder {
+    start: Instant,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl InvalidationRecorder {
+    /// Creates a recorder writing to `path`, replacing any existing file.
+    pub fn new(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("unable to create recording {}", path.display()))?;
+        Ok(Self {
+            start: Instant::now(),
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    fn write_event(&self, event: &RecordedEvent) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        serde_json::to_writer(&mut *writer, event)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Records a batch of changes of the file system `fs` rooted at
+    /// `root_path`, as collected by its watcher. Errors are logged, as the
+    /// watcher can't handle them.
+    pub(crate) fn record_changes(
+        &self,
+        fs: &str,
+        root_path: &Path,
+        writes: &HashSet<PathBuf>,
+        trees: &HashSet<PathBuf>,
+    ) {
+        let changes = writes
+            .iter()
+            .map(|path| (RecordedChangeKind::Write, path))
+            .chain(trees.iter().map(|path| (RecordedChangeKind::Tree, path)))
+            .filter_map(|(kind, path)| {
+                let relative_path = path.strip_prefix(root_path).ok()?;
+                let exists = path.exists();
+                let (content, binary_content) =
+                    match path.is_file().then(|| std::fs::read(path).ok()).flatten() {
+                        Some(bytes) => match String::from_utf8(bytes) {
+                            Ok(content) => (Some(content), None),
+                            Err(err) => (None, Some(err.into_bytes())),
+                        },
+                        None => (None, None),
+                    };
+                Some(RecordedChange {
+                    kind,
+                    path: sys_to_unix(&relative_path.to_string_lossy()).into_owned(),
+                    exists,
+                    content,
+                    binary_content,
+                })
+            })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return;
+        }
+        if let Err(err) = self.write_event(&RecordedEvent::Changes {
+            fs: fs.to_string(),
+            elapsed_ms: self.elapsed_ms(),
+            changes,
+        }) {
+            tracing::error!("unable to write invalidation recording: {:?}", err);
+        }
+    }
+
+    /// Records an update of the task graph, e.g. as reported by
+    /// `TurboTasks::aggregated_update_info`.
+    pub fn record_update(&self, duration: Duration, tasks: usize, reasons: String) -> Result<()> {
+        self.write_event(&RecordedEvent::Update {
+            elapsed_ms: self.elapsed_ms(),
+            duration_ms: duration.as_millis() as u64,
+            tasks,
+            reasons,
+        })
+        .context("unable to write invalidation recording")
+    }
+}
+
+/// Reads all events of a recording.
+pub fn read_recording(path: &Path) -> Result<Vec<RecordedEvent>> {
+    let file =
+        File::open(path).with_context(|| format!("unable to open recording {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(index, line)| {
+            serde_json::from_str(&line?).with_context(|| {
+                format!(
+                    "invalid event at line {} of recording {}",
+                    index + 1,
+                    path.display()
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(path: &str) -> RecordedChange {
+        RecordedChange {
+            kind: RecordedChangeKind::Write,
+            path: path.to_string(),
+            exists: true,
+            content: Some(String::new()),
+            binary_content: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() -> Result<()> {
+        let source = tempfile::tempdir()?;
+        let target = tempfile::tempdir()?;
+        let recording = tempfile::NamedTempFile::new()?;
+
+        std::fs::write(source.path().join("a.txt"), "hello")?;
+        std::fs::create_dir(source.path().join("dir"))?;
+        std::fs::write(source.path().join("dir/b.bin"), [0xff, 0, 1])?;
+        std::fs::write(target.path().join("gone.txt"), "stale")?;
+
+        let recorder = InvalidationRecorder::new(recording.path())?;
+        recorder.record_changes(
+            "project",
+            source.path(),
+            &HashSet::from([source.path().join("a.txt"), source.path().join("dir/b.bin")]),
+            &HashSet::from([source.path().join("gone.txt")]),
+        );
+        recorder.record_update(Duration::from_millis(5), 3, "1 change".to_string())?;
+
+        let events = read_recording(recording.path())?;
+        let [RecordedEvent::Changes { fs, changes, .. }, RecordedEvent::Update { tasks: 3, .. }] =
+            &events[..]
+        else {
+            panic!("unexpected events {events:?}");
+        };
+        assert_eq!(fs, "project");
+        assert_eq!(changes.len(), 3);
+
+        for change in changes {
+            change.apply(target.path()).await?;
+        }
+        assert_eq!(
+            std::fs::read_to_string(target.path().join("a.txt"))?,
+            "hello"
+        );
+        assert_eq!(
+            std::fs::read(target.path().join("dir/b.bin"))?,
+            [0xff, 0, 1]
+        );
+        assert!(!target.path().join("gone.txt").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_paths_outside_of_the_root() -> Result<()> {
+        let root = tempfile::tempdir()?;
+        for path in [
+            "../outside",
+            "/etc/passwd",
+            "a/../../b",
+            "a/../b",
+            "./a",
+            "a//b",
+        ] {
+            assert!(
+                change(path).resolve_path(root.path()).is_err(),
+                "{path} should be rejected"
+            );
+        }
+        assert_eq!(
+            change("a/b").resolve_path(root.path())?,
+            root.path().join("a").join("b")
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_paths_through_symlinks_out_of_the_root() -> Result<()> {
+        let root = tempfile::tempdir()?;
+        let outside = tempfile::tempdir()?;
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link"))?;
+        assert!(change("link/file").resolve_path(root.path()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{encode_hex_string, DeterministicHash, DeterministicHasher};
+
+/// Hash some content with the BLAKE3 cryptographic hash function, returning
+/// the 256-bit digest as a hexadecimal string.
+pub fn hash_blake3<T: DeterministicHash>(input: T) -> String {
+    let mut hasher = Blake3Hasher::new();
+    input.deterministic_hash(&mut hasher);
+    hasher.finish_hex()
+}
+
+/// BLAKE3 hasher.
+pub struct Blake3Hasher(blake3::Hasher);
+
+impl Blake3Hasher {
+    /// Create a new hasher.
+    pub fn new() -> Self {
+        Self(blake3::Hasher::new())
+    }
+
+    /// Uses the DeterministicHash trait to to hash the input in a
+    /// cross-platform way.
+    pub fn write_value<T: DeterministicHash>(&mut self, input: T) {
+        input.deterministic_hash(self);
+    }
+
+    /// Uses the DeterministicHash trait to to hash the input in a
+    /// cross-platform way.
+    pub fn write_ref<T: DeterministicHash>(&mut self, input: &T) {
+        input.deterministic_hash(self);
+    }
+
+    /// Finish the hash computation and return the full 256-bit digest.
+    pub fn finish_bytes(&self) -> [u8; 32] {
+        *self.0.finalize().as_bytes()
+    }
+
+    /// Finish the hash computation and return the full 256-bit digest as a
+    /// hexadecimal string.
+    pub fn finish_hex(&self) -> String {
+        encode_hex_string(&self.finish_bytes())
+    }
+}
+
+impl DeterministicHasher for Blake3Hasher {
+    /// Returns the first 64 bits of the digest.
+    fn finish(&self) -> u64 {
+        let bytes = self.finish_bytes();
+        u64::from_le_bytes(bytes[..8].try_into().unwrap())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+}
+
+impl Default for Blake3Hasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,6 +5,7 @@
 //! file name.
 
 mod base16;
+mod blake3;
 mod deterministic_hash;
 mod hex;
 mod md4;
@@ -12,6 +13,7 @@ mod xxh3_hash64;
 
 pub use crate::{
     base16::encode_base16,
+    blake3::{hash_blake3, Blake3Hasher},
     deterministic_hash::{DeterministicHash, DeterministicHasher},
     hex::{encode_hex, encode_hex_string},
     md4::hash_md4,
@@ -20,5 +20,5 @@ pub mod stats;
 mod task;
 pub mod viz;
 
-pub use memory_backend::MemoryBackend;
+pub use memory_backend::{MemoryBackend, TaskCacheStats};
 pub use memory_backend_with_pg::MemoryBackendWithPersistedGraph;
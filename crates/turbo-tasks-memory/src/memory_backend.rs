@@ -6,7 +6,7 @@ use std::{
     hash::{BuildHasher, BuildHasherDefault, Hash},
     pin::Pin,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     time::{Duration, Instant},
@@ -17,7 +17,7 @@ use auto_hash_map::AutoMap;
 use dashmap::{mapref::entry::Entry, DashMap};
 use rustc_hash::FxHasher;
 use tokio::task::futures::TaskLocalFuture;
-use tracing::trace_span;
+use tracing::{trace, trace_span};
 use turbo_tasks::{
     backend::{
         Backend, BackendJobId, CellContent, PersistentTaskType, TaskExecutionSpec,
@@ -25,7 +25,7 @@ use turbo_tasks::{
     },
     event::EventListener,
     util::{IdFactory, NoMoveVec},
-    CellId, RawVc, TaskId, TaskIdSet, TraitTypeId, TurboTasksBackendApi, Unused,
+    CellId, ConcreteTaskInput, RawVc, TaskId, TaskIdSet, TraitTypeId, TurboTasksBackendApi, Unused,
 };
 
 use crate::{
@@ -43,6 +43,28 @@ pub struct MemoryBackend {
     memory_limit: usize,
     gc_queue: Option<GcQueue>,
     idle_gc_active: AtomicBool,
+    task_cache_hits: AtomicUsize,
+    task_cache_misses: AtomicUsize,
+}
+
+/// How often persistent tasks were looked up in the task cache, see
+/// [MemoryBackend::task_cache_stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TaskCacheStats {
+    /// The number of lookups that found an existing task.
+    pub hits: usize,
+    /// The number of lookups that created a new task.
+    pub misses: usize,
+}
+
+impl TaskCacheStats {
+    /// The lookups after `earlier` stats were taken.
+    pub fn since(self, earlier: TaskCacheStats) -> TaskCacheStats {
+        TaskCacheStats {
+            hits: self.hits.saturating_sub(earlier.hits),
+            misses: self.misses.saturating_sub(earlier.misses),
+        }
+    }
 }
 
 impl Default for MemoryBackend {
@@ -65,6 +87,17 @@ impl MemoryBackend {
             memory_limit,
             gc_queue: (memory_limit != usize::MAX).then(GcQueue::new),
             idle_gc_active: AtomicBool::new(false),
+            task_cache_hits: AtomicUsize::new(0),
+            task_cache_misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns how often persistent tasks were found in the task cache and
+    /// how often they were created since the backend was created.
+    pub fn task_cache_stats(&self) -> TaskCacheStats {
+        TaskCacheStats {
+            hits: self.task_cache_hits.load(Ordering::Relaxed),
+            misses: self.task_cache_misses.load(Ordering::Relaxed),
         }
     }
 
@@ -275,6 +308,18 @@ impl Backend for MemoryBackend {
         self.with_task(task, |task| task.get_description())
     }
 
+    fn get_task_inputs_description(&self, task: TaskId) -> Vec<String> {
+        self.with_task(task, |task| task.get_inputs())
+            .into_iter()
+            .map(|input| match input {
+                ConcreteTaskInput::TaskOutput(task) | ConcreteTaskInput::TaskCell(task, _) => {
+                    format!("{} ({})", input, self.get_task_description(task))
+                }
+                input => input.to_string(),
+            })
+            .collect()
+    }
+
     type ExecutionScopeFuture<T: Future<Output = Result<()>> + Send + 'static> =
         TaskLocalFuture<RefCell<TaskDependencySet>, T>;
     fn execution_scope<T: Future<Output = Result<()>> + Send + 'static>(
@@ -512,8 +557,12 @@ impl Backend for MemoryBackend {
             self.lookup_and_connect_task(parent_task, &self.task_cache, &task_type, turbo_tasks)
         {
             // fast pass without creating a new task
+            trace!(task_cache_hit = true);
+            self.task_cache_hits.fetch_add(1, Ordering::Relaxed);
             task
         } else {
+            trace!(task_cache_hit = false);
+            self.task_cache_misses.fetch_add(1, Ordering::Relaxed);
             // It's important to avoid overallocating memory as this will go into the task
             // cache and stay there forever. We can to be as small as possible.
             task_type.shrink_to_fit();
@@ -30,8 +30,8 @@ use tracing::Span;
 use turbo_tasks::{
     backend::{PersistentTaskType, TaskExecutionSpec},
     event::{Event, EventListener},
-    get_invalidator, registry, CellId, Invalidator, NativeFunction, RawVc, StatsType, TaskId,
-    TaskIdSet, TraitType, TraitTypeId, TurboTasksBackendApi, ValueTypeId,
+    get_invalidator, registry, CellId, ConcreteTaskInput, Invalidator, NativeFunction, RawVc,
+    StatsType, TaskId, TaskIdSet, TraitType, TraitTypeId, TurboTasksBackendApi, ValueTypeId,
 };
 
 use crate::{
@@ -540,6 +540,17 @@ impl Task {
         Self::format_description(&TaskTypeForDescription::from(&self.ty), self.id)
     }
 
+    pub(crate) fn get_inputs(&self) -> Vec<ConcreteTaskInput> {
+        match &self.ty {
+            TaskType::Root(..) | TaskType::Once(..) => Vec::new(),
+            TaskType::Persistent { ty, .. } => match &**ty {
+                PersistentTaskType::Native(_, inputs)
+                | PersistentTaskType::ResolveNative(_, inputs)
+                | PersistentTaskType::ResolveTrait(_, _, inputs) => inputs.clone(),
+            },
+        }
+    }
+
     fn format_description(ty: &TaskTypeForDescription, id: TaskId) -> String {
         match ty {
             TaskTypeForDescription::Root => format!("[{}] root", id),
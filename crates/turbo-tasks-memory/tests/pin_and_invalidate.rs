@@ -0,0 +1,52 @@
+#![feature(arbitrary_self_types)]
+
+use std::{
+    fmt::{Display, Formatter},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use turbo_tasks::{InvalidationReason, TurboTasks, Vc};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+
+register!();
+
+static EXECUTIONS: AtomicU32 = AtomicU32::new(0);
+
+#[tokio::test]
+async fn pin_and_invalidate() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let api = tt.clone();
+    tt.run_once(async move {
+        let executions = count_executions();
+        assert_eq!(*executions.strongly_consistent().await?, 1);
+
+        let pin = api.pin_vc(executions);
+        assert_eq!(*executions.strongly_consistent().await?, 1);
+
+        api.invalidate_vc(executions, Restart);
+        assert_eq!(*executions.strongly_consistent().await?, 2);
+
+        api.unpin_vc(pin);
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[turbo_tasks::function]
+fn count_executions() -> Vc<u32> {
+    Vc::cell(EXECUTIONS.fetch_add(1, Ordering::SeqCst) + 1)
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct Restart;
+
+impl InvalidationReason for Restart {}
+
+impl Display for Restart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "restart")
+    }
+}
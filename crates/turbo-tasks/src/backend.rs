@@ -200,6 +200,11 @@ pub trait Backend: Sync + Send {
 
     fn get_task_description(&self, task: TaskId) -> String;
 
+    /// Describes the inputs of a task, e.g. for diagnostics when it panicked.
+    fn get_task_inputs_description(&self, _task: TaskId) -> Vec<String> {
+        Vec::new()
+    }
+
     type ExecutionScopeFuture<T: Future<Output = Result<()>> + Send + 'static>: Future<Output = Result<()>>
         + Send
         + 'static;
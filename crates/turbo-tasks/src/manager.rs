@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
     cell::RefCell,
+    collections::VecDeque,
     future::Future,
     hash::Hash,
     mem::take,
@@ -258,6 +259,24 @@ pub struct UpdateInfo {
     placeholder_for_future_fields: (),
 }
 
+/// The number of invalidations that are kept to be reported with a
+/// [TaskPanic].
+const RECENT_INVALIDATIONS: usize = 32;
+
+/// Describes a task that panicked. Passed to the handler set with
+/// [TurboTasks::set_panic_handler].
+pub struct TaskPanic {
+    pub task: String,
+    /// Descriptions of the inputs of the task.
+    pub inputs: Vec<String>,
+    pub message: Option<String>,
+    /// The most recent invalidations, oldest first, with the time that has
+    /// passed since they happened.
+    pub recent_invalidations: Vec<(Duration, String)>,
+}
+
+pub type TaskPanicHandler = Arc<dyn Fn(&TaskPanic) + Send + Sync>;
+
 pub struct TurboTasks<B: Backend + 'static> {
     this: Weak<Self>,
     backend: B,
@@ -269,6 +288,8 @@ pub struct TurboTasks<B: Backend + 'static> {
     scheduled_tasks: AtomicUsize,
     start: Mutex<Option<Instant>>,
     aggregated_update: Mutex<(Option<(Duration, usize)>, InvalidationReasonSet)>,
+    recent_invalidations: Mutex<VecDeque<(Instant, StaticOrArc<dyn InvalidationReason>)>>,
+    panic_handler: Mutex<Option<TaskPanicHandler>>,
     event: Event,
     event_start: Event,
     event_foreground: Event,
@@ -322,6 +343,8 @@ impl<B: Backend + 'static> TurboTasks<B> {
             scheduled_tasks: AtomicUsize::new(0),
             start: Default::default(),
             aggregated_update: Default::default(),
+            recent_invalidations: Default::default(),
+            panic_handler: Default::default(),
             event: Event::new(|| "TurboTasks::event".to_string()),
             event_start: Event::new(|| "TurboTasks::event_start".to_string()),
             event_foreground: Event::new(|| "TurboTasks::event_foreground".to_string()),
@@ -337,6 +360,45 @@ impl<B: Backend + 'static> TurboTasks<B> {
         self.this.upgrade().unwrap()
     }
 
+    /// Sets a handler that is called when a task panics, e.g. to write a
+    /// diagnostic report. The panic is still turned into an error of the
+    /// task.
+    pub fn set_panic_handler(&self, handler: impl Fn(&TaskPanic) + Send + Sync + 'static) {
+        *self.panic_handler.lock().unwrap() = Some(Arc::new(handler));
+    }
+
+    fn report_panic(&self, task_id: TaskId, message: Option<&str>) {
+        let Some(handler) = self.panic_handler.lock().unwrap().clone() else {
+            return;
+        };
+        let now = Instant::now();
+        let recent_invalidations = self
+            .recent_invalidations
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(time, reason)| (now.duration_since(*time), reason.to_string()))
+            .collect();
+        handler(&TaskPanic {
+            task: self.backend.get_task_description(task_id),
+            inputs: self.backend.get_task_inputs_description(task_id),
+            message: message.map(ToString::to_string),
+            recent_invalidations,
+        });
+    }
+
+    fn record_invalidation(&self, reason: StaticOrArc<dyn InvalidationReason>) {
+        {
+            let mut recent_invalidations = self.recent_invalidations.lock().unwrap();
+            if recent_invalidations.len() == RECENT_INVALIDATIONS {
+                recent_invalidations.pop_front();
+            }
+            recent_invalidations.push_back((Instant::now(), reason.clone()));
+        }
+        let (_, reason_set) = &mut *self.aggregated_update.lock().unwrap();
+        reason_set.insert(reason);
+    }
+
     /// Creates a new root task
     pub fn spawn_root_task<T, F, Fut>(&self, functor: F) -> TaskId
     where
@@ -359,6 +421,33 @@ impl<B: Backend + 'static> TurboTasks<B> {
         self.backend.dispose_root_task(task_id, self);
     }
 
+    /// Keeps the task that computes `vc` active until [`Self::unpin_vc`] is
+    /// called with the returned id, so its result is not evicted from the
+    /// cache while it's not read by anyone else.
+    pub fn pin_vc<T: ?Sized + Send + 'static>(&self, vc: Vc<T>) -> TaskId {
+        let node = vc.node;
+        self.spawn_root_task(move || async move {
+            node.connect();
+            Ok(Completion::new())
+        })
+    }
+
+    /// Releases a pin created by [`Self::pin_vc`].
+    pub fn unpin_vc(&self, pin: TaskId) {
+        self.dispose_root_task(pin);
+    }
+
+    /// Forces the task that computes `vc` to be executed again, e.g. when a
+    /// dev server is asked to rebuild a route. Tasks depending on its result
+    /// are invalidated when the result changes.
+    pub fn invalidate_vc<T: ?Sized + Send, R: InvalidationReason>(&self, vc: Vc<T>, reason: R) {
+        TurboTasksApi::invalidate_with_reason(
+            self,
+            vc.node.get_task_id(),
+            (Arc::new(reason) as Arc<dyn InvalidationReason>).into(),
+        );
+    }
+
     // TODO make sure that all dependencies settle before reading them
     /// Creates a new root task, that is only executed once.
     /// Dependencies will not invalidate the task.
@@ -487,6 +576,9 @@ impl<B: Backend + 'static> TurboTasks<B> {
                                         Err(_) => None,
                                     },
                                 });
+                                if let Err(message) = &result {
+                                    this.report_panic(task_id, message.as_deref());
+                                }
                                 this.backend.task_execution_result(task_id, result, &*this);
                                 let stateful = this.finish_current_task_state();
                                 this.backend.task_execution_completed(
@@ -849,10 +941,7 @@ impl<B: Backend + 'static> TurboTasksCallApi for TurboTasks<B> {
         reason: StaticOrArc<dyn InvalidationReason>,
         future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
     ) -> TaskId {
-        {
-            let (_, reason_set) = &mut *self.aggregated_update.lock().unwrap();
-            reason_set.insert(reason);
-        }
+        self.record_invalidation(reason);
         self.spawn_once_task(async move {
             future.await?;
             Ok(Completion::new())
@@ -886,10 +975,7 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
 
     #[instrument(level = Level::INFO, skip_all, name = "invalidate", fields(name = display(&reason)))]
     fn invalidate_with_reason(&self, task: TaskId, reason: StaticOrArc<dyn InvalidationReason>) {
-        {
-            let (_, reason_set) = &mut *self.aggregated_update.lock().unwrap();
-            reason_set.insert(reason);
-        }
+        self.record_invalidation(reason);
         self.backend.invalidate_task(task, self);
     }
 
@@ -1364,6 +1450,11 @@ pub fn spawn_detached(f: impl Future<Output = Result<()>> + Send + 'static) {
     tokio::spawn(turbo_tasks().detached(Box::pin(f.in_current_span())));
 }
 
+/// Returns the id of the task that is currently executed, if any.
+pub fn try_current_task() -> Option<TaskId> {
+    CURRENT_TASK_ID.try_with(|id| *id).ok()
+}
+
 pub fn current_task_for_testing() -> TaskId {
     CURRENT_TASK_ID.with(|id| *id)
 }
@@ -42,6 +42,7 @@ impl Vc<Vec<String>> {
 }
 
 __turbo_tasks_internal_primitive!(Option<u16>);
+__turbo_tasks_internal_primitive!(Option<u64>);
 
 #[turbo_tasks::function]
 fn option_string_none() -> Vc<Option<String>> {
@@ -9,23 +9,34 @@ use turbo_tasks_hash::DeterministicHash;
 use turbopack_core::{
     chunk::{
         availability_info::AvailabilityInfo,
-        chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        chunk_group::{
+            make_chunk_group, plan_chunk_group, MakeChunkGroupResult, PlanChunkGroupResult,
+        },
+        integrity::chunk_group_integrity,
+        precache::{precache_manifest_path, PrecacheManifestAsset},
+        usage_profile::{OptionUsageProfile, UsageProfile},
         Chunk, ChunkGroupResult, ChunkItem, ChunkableModule, ChunkingContext, EvaluatableAssets,
         ModuleId,
     },
     environment::Environment,
+    hash::HashAlgorithm,
     ident::AssetIdent,
-    module::Module,
-    output::OutputAsset,
+    module::{Module, ModulesSet},
+    output::{OutputAsset, OutputAssets},
+    source_map::SourceMapMode,
 };
 use turbopack_ecmascript::{
-    chunk::{EcmascriptChunk, EcmascriptChunkPlaceable, EcmascriptChunkingContext},
+    chunk::{
+        check_strict_esm, EcmascriptChunk, EcmascriptChunkPlaceable, EcmascriptChunkingContext,
+        StrictEsm,
+    },
     manifest::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem},
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 
-use crate::ecmascript::node::{
-    chunk::EcmascriptBuildNodeChunk, entry::chunk::EcmascriptBuildNodeEntryChunk,
+use crate::ecmascript::{
+    node::{chunk::EcmascriptBuildNodeChunk, entry::chunk::EcmascriptBuildNodeEntryChunk},
+    service_worker::EcmascriptBuildServiceWorkerChunk,
 };
 
 #[derive(
@@ -61,6 +72,28 @@ impl BuildChunkingContextBuilder {
         self
     }
 
+    /// Names static assets by their content hash only, so that identical files
+    /// referenced from different source paths (e.g. copied assets or fonts
+    /// duplicated across packages) are emitted once, and all references point
+    /// to the same output.
+    pub fn dedupe_assets_by_content(mut self) -> Self {
+        self.chunking_context.dedupe_assets_by_content = true;
+        self
+    }
+
+    pub fn hash_algorithm(mut self, hash_algorithm: HashAlgorithm) -> Self {
+        self.chunking_context.hash_algorithm = hash_algorithm;
+        self
+    }
+
+    /// Computes Subresource Integrity hashes of the chunks, which the runtime
+    /// verifies before it loads them. The hashes are also exposed on
+    /// [ChunkGroupResult::integrity].
+    pub fn subresource_integrity(mut self) -> Self {
+        self.chunking_context.subresource_integrity = true;
+        self
+    }
+
     pub fn minify_type(mut self, minify_type: MinifyType) -> Self {
         self.chunking_context.minify_type = minify_type;
         self
@@ -71,6 +104,66 @@ impl BuildChunkingContextBuilder {
         self
     }
 
+    /// Transforms the code of chunks so that it runs in `environment`, which
+    /// can be older than the environment modules have been compiled for. This
+    /// allows emitting chunks for legacy browsers from the same module graph.
+    pub fn downlevel(mut self, environment: Vc<Environment>) -> Self {
+        self.chunking_context.downlevel_environment = Some(environment);
+        self
+    }
+
+    /// Places `split_points` into their own async chunk groups, even when they
+    /// are imported statically.
+    pub fn split_points(mut self, split_points: Vc<ModulesSet>) -> Self {
+        self.chunking_context.split_points = split_points;
+        self
+    }
+
+    /// Flattens the getters of bindings re-exported by several modules, e.g.
+    /// through nested barrel files, see
+    /// [EcmascriptChunkingContext::flatten_reexports].
+    pub fn flatten_reexports(mut self, flatten_reexports: bool) -> Self {
+        self.chunking_context.flatten_reexports = flatten_reexports;
+        self
+    }
+
+    /// Places the modules executed according to `usage_profile` into the
+    /// first chunks of chunk groups.
+    pub fn usage_profile(mut self, usage_profile: Vc<UsageProfile>) -> Self {
+        self.chunking_context.usage_profile = Some(usage_profile);
+        self
+    }
+
+    /// Reports modules whose generated code exceeds `threshold` bytes as
+    /// issues.
+    pub fn module_size_warning_threshold(mut self, threshold: u64) -> Self {
+        self.chunking_context.module_size_warning_threshold = Some(threshold);
+        self
+    }
+
+    /// Reports packages that are placed into a chunk group together with a
+    /// version of one of their peer dependencies outside of the declared
+    /// range.
+    pub fn check_peer_dependencies(mut self) -> Self {
+        self.chunking_context.check_peer_dependencies = true;
+        self
+    }
+
+    /// Reports the modules of chunk groups that use CommonJS, with the chain
+    /// of modules importing them. With [StrictEsm::Error], such modules fail
+    /// the build.
+    pub fn strict_esm(mut self, mode: StrictEsm) -> Self {
+        self.chunking_context.strict_esm = mode;
+        self
+    }
+
+    /// Makes `chunk_group` return a plan of the chunks it would create, see
+    /// [turbopack_core::chunk::plan], without generating any assets.
+    pub fn dry_run(mut self) -> Self {
+        self.chunking_context.dry_run = true;
+        self
+    }
+
     /// Builds the chunking context.
     pub fn build(self) -> Vc<BuildChunkingContext> {
         BuildChunkingContext::new(Value::new(self.chunking_context))
@@ -92,6 +185,12 @@ pub struct BuildChunkingContext {
     chunk_root_path: Vc<FileSystemPath>,
     /// Static assets are placed at this path
     asset_root_path: Vc<FileSystemPath>,
+    /// Whether static assets are named by their content hash only
+    dedupe_assets_by_content: bool,
+    /// The hash function used for content hashes and chunk idents
+    hash_algorithm: HashAlgorithm,
+    /// Whether Subresource Integrity hashes are computed for chunks
+    subresource_integrity: bool,
     /// Static assets requested from this url base
     asset_prefix: Vc<Option<String>>,
     /// The environment chunks will be evaluated in.
@@ -100,6 +199,22 @@ pub struct BuildChunkingContext {
     runtime_type: RuntimeType,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// The environment the code of chunks is transformed for
+    downlevel_environment: Option<Vc<Environment>>,
+    /// Modules that are placed in their own async chunk groups
+    split_points: Vc<ModulesSet>,
+    /// The modules executed during a previous session
+    usage_profile: Option<Vc<UsageProfile>>,
+    /// Modules larger than this are reported as issues
+    module_size_warning_threshold: Option<u64>,
+    /// Report peer dependencies outside of the range declared by packages
+    check_peer_dependencies: bool,
+    /// Only plan the chunks of chunk groups instead of generating them
+    dry_run: bool,
+    /// Whether getters of re-exported bindings read from the declaring module
+    flatten_reexports: bool,
+    /// How CommonJS modules in chunk groups are reported
+    strict_esm: StrictEsm,
 }
 
 impl BuildChunkingContext {
@@ -120,9 +235,20 @@ impl BuildChunkingContext {
                 chunk_root_path,
                 asset_root_path,
                 asset_prefix: Default::default(),
+                dedupe_assets_by_content: false,
+                hash_algorithm: Default::default(),
+                subresource_integrity: false,
                 environment,
                 runtime_type: Default::default(),
                 minify_type: MinifyType::Minify,
+                downlevel_environment: None,
+                split_points: ModulesSet::empty(),
+                usage_profile: None,
+                module_size_warning_threshold: None,
+                check_peer_dependencies: false,
+                dry_run: false,
+                flatten_reexports: false,
+                strict_esm: StrictEsm::Off,
             },
         }
     }
@@ -140,6 +266,10 @@ impl BuildChunkingContext {
     pub fn minify_type(&self) -> MinifyType {
         self.minify_type
     }
+
+    pub fn downlevel_environment(&self) -> Option<Vc<Environment>> {
+        self.downlevel_environment
+    }
 }
 
 #[turbo_tasks::value]
@@ -174,9 +304,25 @@ impl BuildChunkingContext {
     ) -> Result<Vc<EntryChunkGroupResult>> {
         let availability_info = availability_info.into_value();
 
+        check_strict_esm(
+            Vc::upcast(self),
+            Vc::cell(
+                once(Vc::upcast(module))
+                    .chain(
+                        evaluatable_assets
+                            .await?
+                            .iter()
+                            .map(|&asset| Vc::upcast(asset)),
+                    )
+                    .collect(),
+            ),
+        )
+        .await?;
+
         let MakeChunkGroupResult {
             chunks,
             availability_info,
+            ..
         } = make_chunk_group(
             Vc::upcast(self),
             once(Vc::upcast(module)).chain(
@@ -247,6 +393,26 @@ impl ChunkingContext for BuildChunkingContext {
         self.environment
     }
 
+    #[turbo_tasks::function]
+    fn split_points(&self) -> Vc<ModulesSet> {
+        self.split_points
+    }
+
+    #[turbo_tasks::function]
+    fn usage_profile(&self) -> Vc<OptionUsageProfile> {
+        Vc::cell(self.usage_profile)
+    }
+
+    #[turbo_tasks::function]
+    fn module_size_warning_threshold(&self) -> Vc<Option<u64>> {
+        Vc::cell(self.module_size_warning_threshold)
+    }
+
+    #[turbo_tasks::function]
+    fn check_peer_dependencies(&self) -> Vc<bool> {
+        Vc::cell(self.check_peer_dependencies)
+    }
+
     #[turbo_tasks::function]
     async fn asset_url(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<String>> {
         let this = self.await?;
@@ -254,6 +420,11 @@ impl ChunkingContext for BuildChunkingContext {
         let asset_path = asset_path
             .strip_prefix(&format!("{}/", this.client_root.await?.path))
             .context("expected client root to contain asset path")?;
+        let default_prefix = if *this.environment.document_relative_urls().await? {
+            ""
+        } else {
+            "/"
+        };
 
         Ok(Vc::cell(format!(
             "{}{}",
@@ -261,7 +432,7 @@ impl ChunkingContext for BuildChunkingContext {
                 .await?
                 .as_ref()
                 .map(|s| s.to_owned())
-                .unwrap_or_else(|| "/".to_owned()),
+                .unwrap_or_else(|| default_prefix.to_owned()),
             asset_path
         )))
     }
@@ -273,13 +444,25 @@ impl ChunkingContext for BuildChunkingContext {
         extension: String,
     ) -> Result<Vc<FileSystemPath>> {
         let root_path = self.chunk_root_path;
-        let name = ident.output_name(self.context_path, extension).await?;
+        let name = ident
+            .output_name(self.context_path, extension, self.hash_algorithm.cell())
+            .await?;
         Ok(root_path.join(name.clone_value()))
     }
 
     #[turbo_tasks::function]
-    fn reference_chunk_source_maps(&self, _chunk: Vc<Box<dyn OutputAsset>>) -> Vc<bool> {
-        Vc::cell(true)
+    fn reference_chunk_source_maps(&self, _chunk: Vc<Box<dyn OutputAsset>>) -> Vc<SourceMapMode> {
+        SourceMapMode::External.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn hash_algorithm(&self) -> Vc<HashAlgorithm> {
+        self.hash_algorithm.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn subresource_integrity(&self) -> Vc<bool> {
+        Vc::cell(self.subresource_integrity)
     }
 
     #[turbo_tasks::function]
@@ -307,6 +490,13 @@ impl ChunkingContext for BuildChunkingContext {
         original_asset_ident: Vc<AssetIdent>,
     ) -> Result<Vc<FileSystemPath>> {
         let source_path = original_asset_ident.path().await?;
+        if self.dedupe_assets_by_content {
+            let asset_path = match source_path.extension_ref() {
+                Some(ext) => format!("{content_hash}.{ext}"),
+                None => content_hash,
+            };
+            return Ok(self.asset_root_path.join(asset_path));
+        }
         let basename = source_path.file_name();
         let asset_path = match source_path.extension_ref() {
             Some(ext) => format!(
@@ -330,9 +520,33 @@ impl ChunkingContext for BuildChunkingContext {
     ) -> Result<Vc<ChunkGroupResult>> {
         let span = tracing::info_span!("chunking", module = *module.ident().to_string().await?);
         async move {
+            if self.await?.dry_run {
+                let PlanChunkGroupResult {
+                    plan,
+                    availability_info,
+                } = plan_chunk_group(
+                    Vc::upcast(self),
+                    [Vc::upcast(module)],
+                    availability_info.into_value(),
+                    |chunk| self.generate_chunk(chunk),
+                )
+                .await?;
+                return Ok(ChunkGroupResult {
+                    assets: OutputAssets::empty(),
+                    availability_info,
+                    plan: Some(plan),
+                    static_only: false,
+                    integrity: None,
+                }
+                .cell());
+            }
+
+            check_strict_esm(Vc::upcast(self), Vc::cell(vec![Vc::upcast(module)])).await?;
+
             let MakeChunkGroupResult {
                 chunks,
                 availability_info,
+                ..
             } = make_chunk_group(
                 Vc::upcast(self),
                 [Vc::upcast(module)],
@@ -350,9 +564,13 @@ impl ChunkingContext for BuildChunkingContext {
                 *asset = asset.resolve().await?;
             }
 
+            let assets = Vc::cell(assets);
             Ok(ChunkGroupResult {
-                assets: Vc::cell(assets),
+                assets,
                 availability_info,
+                plan: None,
+                static_only: false,
+                integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
             }
             .cell())
         }
@@ -372,6 +590,80 @@ impl ChunkingContext for BuildChunkingContext {
         bail!("the build chunking context does not support evaluated chunk groups")
     }
 
+    /// Workers are started with an entry chunk that loads the other chunks of
+    /// the chunk group with the Node.js runtime, e.g. for
+    /// `new Worker(new URL(...))` of `worker_threads`.
+    #[turbo_tasks::function]
+    async fn worker_chunk_group(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        let Some(&entry) = evaluatable_assets.await?.last() else {
+            bail!(
+                "the worker chunk group of {} has no entry",
+                ident.to_string().await?
+            );
+        };
+        let Some(module) =
+            Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(entry).await?
+        else {
+            bail!(
+                "{} can't be loaded as a worker, as it's not an ecmascript module",
+                entry.ident().to_string().await?
+            );
+        };
+        let EntryChunkGroupResult {
+            asset,
+            availability_info,
+        } = *self
+            .entry_chunk_group(
+                self.chunk_path(ident, ".js".to_string()),
+                module,
+                evaluatable_assets,
+                Value::new(AvailabilityInfo::Root),
+            )
+            .await?;
+        let assets = Vc::cell(vec![asset]);
+        Ok(ChunkGroupResult {
+            assets,
+            availability_info,
+            plan: None,
+            static_only: false,
+            integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    async fn service_worker_chunk_group(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        precache: Vc<OutputAssets>,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        let assets: Vc<OutputAssets> = Vc::cell(vec![
+            Vc::upcast(PrecacheManifestAsset::new(
+                precache_manifest_path(path),
+                precache,
+            )),
+            Vc::upcast(EcmascriptBuildServiceWorkerChunk::new(
+                self,
+                path,
+                evaluatable_assets,
+                precache,
+            )),
+        ]);
+        Ok(ChunkGroupResult {
+            assets,
+            availability_info: AvailabilityInfo::Root,
+            plan: None,
+            static_only: false,
+            integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
+        }
+        .cell())
+    }
+
     #[turbo_tasks::function]
     fn async_loader_chunk_item(
         self: Vc<Self>,
@@ -395,4 +687,14 @@ impl ChunkingContext for BuildChunkingContext {
 }
 
 #[turbo_tasks::value_impl]
-impl EcmascriptChunkingContext for BuildChunkingContext {}
+impl EcmascriptChunkingContext for BuildChunkingContext {
+    #[turbo_tasks::function]
+    fn flatten_reexports(&self) -> Vc<bool> {
+        Vc::cell(self.flatten_reexports)
+    }
+
+    #[turbo_tasks::function]
+    fn strict_esm(&self) -> Vc<StrictEsm> {
+        self.strict_esm.cell()
+    }
+}
@@ -1,2 +1,2 @@
-pub(crate) mod minify;
 pub(crate) mod node;
+pub(crate) mod service_worker;
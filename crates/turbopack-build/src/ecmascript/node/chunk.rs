@@ -7,7 +7,7 @@ use turbopack_core::{
     ident::AssetIdent,
     introspect::{Introspectable, IntrospectableChildren},
     output::{OutputAsset, OutputAssets},
-    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset, SourceMapMode},
     version::VersionedContent,
 };
 use turbopack_ecmascript::chunk::EcmascriptChunk;
@@ -73,10 +73,13 @@ impl OutputAsset for EcmascriptBuildNodeChunk {
     async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
         let chunk_references = this.chunk.references().await?;
-        let include_source_map = *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?;
+        let include_source_map = matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        );
         let mut references =
             Vec::with_capacity(chunk_references.len() + if include_source_map { 1 } else { 0 });
 
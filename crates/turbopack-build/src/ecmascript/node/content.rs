@@ -7,18 +7,20 @@ use turbo_tasks_fs::File;
 use turbopack_core::{
     asset::AssetContent,
     chunk::{ChunkItemExt, ChunkingContext, ModuleId},
-    code_builder::{Code, CodeBuilder},
+    code_builder::{append_source_map_url, Code, CodeBuilder},
     output::OutputAsset,
     source_map::{GenerateSourceMap, OptionSourceMap},
     version::{Version, VersionedContent},
 };
 use turbopack_ecmascript::{
     chunk::{EcmascriptChunkContent, EcmascriptChunkItemExt},
+    downlevel::downlevel,
+    minify::minify,
     utils::StringifyJs,
 };
 
 use super::{chunk::EcmascriptBuildNodeChunk, version::EcmascriptBuildNodeChunkVersion};
-use crate::{chunking_context::MinifyType, ecmascript::minify::minify, BuildChunkingContext};
+use crate::{chunking_context::MinifyType, BuildChunkingContext};
 
 #[turbo_tasks::value]
 pub(super) struct EcmascriptBuildNodeChunkContent {
@@ -44,7 +46,7 @@ impl EcmascriptBuildNodeChunkContent {
     }
 }
 
-pub(super) async fn chunk_items(
+pub(crate) async fn chunk_items(
     content: Vc<EcmascriptChunkContent>,
 ) -> Result<Vec<(ReadRef<ModuleId>, ReadRef<Code>)>> {
     content
@@ -87,20 +89,26 @@ impl EcmascriptBuildNodeChunkContent {
 
         write!(code, "\n}};")?;
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        let mut code = code.build().cell();
+        let chunking_context = this.chunking_context.await?;
+        if let Some(environment) = chunking_context.downlevel_environment() {
+            code = downlevel(chunk_path_vc, code, environment);
         }
-
-        let code = code.build().cell();
-        if matches!(
-            this.chunking_context.await?.minify_type(),
-            MinifyType::Minify
-        ) {
-            return Ok(minify(chunk_path_vc, code));
+        if matches!(chunking_context.minify_type(), MinifyType::Minify) {
+            code = minify(chunk_path_vc, code, true);
         }
 
-        Ok(code)
+        append_source_map_url(
+            code,
+            &chunk_path,
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(this.chunk))
+                .await?,
+            this.chunking_context.source_map_privacy(),
+            |url| format!("\n\n//# sourceMappingURL={url}"),
+        )
+        .await
     }
 
     #[turbo_tasks::function]
@@ -6,13 +6,19 @@ use turbo_tasks::{ValueToString, Vc};
 use turbo_tasks_fs::{File, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets},
+    chunk::{
+        integrity::{chunk_integrity_hashes, ChunkIntegrityHashes},
+        ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets,
+    },
     code_builder::{Code, CodeBuilder},
     ident::AssetIdent,
     output::{OutputAsset, OutputAssets},
-    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+    reference::all_assets_from_entries,
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset, SourceMapMode},
+};
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkPlaceable, downlevel::downlevel, utils::StringifyJs,
 };
-use turbopack_ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs};
 
 use super::runtime::EcmascriptBuildNodeRuntimeChunk;
 use crate::BuildChunkingContext;
@@ -89,6 +95,16 @@ impl EcmascriptBuildNodeEntryChunk {
             StringifyJs(&*runtime_relative_path)
         )?;
 
+        if *this.chunking_context.subresource_integrity().await? {
+            writedoc!(
+                code,
+                r#"
+                    runtime.registerChunkIntegrity({});
+                "#,
+                StringifyJs(&*self.integrity().await?)
+            )?;
+        }
+
         let other_chunks = this.other_chunks.await?;
         for other_chunk in &*other_chunks {
             let other_chunk_path = &*other_chunk.ident().path().await?;
@@ -140,7 +156,38 @@ impl EcmascriptBuildNodeEntryChunk {
             StringifyJs(&*runtime_module_id),
         )?;
 
-        Ok(Code::cell(code.build()))
+        let code = code.build().cell();
+        if let Some(environment) = this.chunking_context.await?.downlevel_environment() {
+            return Ok(downlevel(self.ident().path(), code, environment));
+        }
+        Ok(code)
+    }
+
+    /// The Subresource Integrity hashes of the chunks the runtime may load: the
+    /// other chunks and all chunks reachable from them, e.g. of async chunk
+    /// groups.
+    #[turbo_tasks::function]
+    async fn integrity(self: Vc<Self>) -> Result<Vc<ChunkIntegrityHashes>> {
+        let this = self.await?;
+        let mut chunks = Vec::new();
+        for &asset in &*all_assets_from_entries(this.other_chunks).await? {
+            // Entry chunks, e.g. of workers, aren't loaded by the runtime, and a
+            // worker that starts itself would make its entry chunk depend on its
+            // own hash.
+            if Vc::try_resolve_downcast_type::<EcmascriptBuildNodeEntryChunk>(asset)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            if asset.ident().path().await?.extension_ref() == Some("js") {
+                chunks.push(asset);
+            }
+        }
+        Ok(chunk_integrity_hashes(
+            this.chunking_context.output_root(),
+            Vc::cell(chunks),
+        ))
     }
 
     #[turbo_tasks::function]
@@ -180,11 +227,13 @@ impl OutputAsset for EcmascriptBuildNodeEntryChunk {
         let this = self.await?;
         let mut references = vec![Vc::upcast(self.runtime_chunk())];
 
-        if *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?
-        {
+        if matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        ) {
             references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))))
         }
 
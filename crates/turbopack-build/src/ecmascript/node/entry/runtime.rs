@@ -10,9 +10,9 @@ use turbopack_core::{
     code_builder::{Code, CodeBuilder},
     ident::AssetIdent,
     output::{OutputAsset, OutputAssets},
-    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset, SourceMapMode},
 };
-use turbopack_ecmascript::utils::StringifyJs;
+use turbopack_ecmascript::{downlevel::downlevel, utils::StringifyJs};
 use turbopack_ecmascript_runtime::RuntimeType;
 
 use crate::BuildChunkingContext;
@@ -82,7 +82,11 @@ impl EcmascriptBuildNodeRuntimeChunk {
             }
         }
 
-        Ok(Code::cell(code.build()))
+        let code = code.build().cell();
+        if let Some(environment) = this.chunking_context.await?.downlevel_environment() {
+            return Ok(downlevel(self.ident().path(), code, environment));
+        }
+        Ok(code)
     }
 }
 
@@ -112,11 +116,13 @@ impl OutputAsset for EcmascriptBuildNodeRuntimeChunk {
         let this = self.await?;
         let mut references = vec![];
 
-        if *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?
-        {
+        if matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        ) {
             references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))))
         }
 
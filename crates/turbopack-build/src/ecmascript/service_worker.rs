@@ -0,0 +1,242 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::{TryFlatJoinIterExt, TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        availability_info::AvailabilityInfo,
+        chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        precache::precache_entries,
+        Chunk, ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets,
+    },
+    code_builder::{append_source_map_url, Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset, SourceMapMode},
+};
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunk, EcmascriptChunkPlaceable},
+    downlevel::downlevel,
+    minify::minify,
+    utils::StringifyJs,
+};
+
+use super::node::content::chunk_items;
+use crate::{chunking_context::MinifyType, BuildChunkingContext};
+
+/// The production chunk of a service worker, see
+/// [ChunkingContext::service_worker_chunk_group].
+///
+/// Service workers can't load other chunks after they were installed, so the
+/// chunk contains all modules of the service worker and the worklet runtime,
+/// which evaluates the entries. It exposes the precache entries of the
+/// `precache` assets.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptBuildServiceWorkerChunk {
+    chunking_context: Vc<BuildChunkingContext>,
+    path: Vc<FileSystemPath>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+    precache: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value(transparent)]
+struct EcmascriptChunks(Vec<Vc<EcmascriptChunk>>);
+
+#[turbo_tasks::value_impl]
+impl EcmascriptBuildServiceWorkerChunk {
+    /// Creates a new [`Vc<EcmascriptBuildServiceWorkerChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        chunking_context: Vc<BuildChunkingContext>,
+        path: Vc<FileSystemPath>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        precache: Vc<OutputAssets>,
+    ) -> Vc<Self> {
+        EcmascriptBuildServiceWorkerChunk {
+            chunking_context,
+            path,
+            evaluatable_assets,
+            precache,
+        }
+        .cell()
+    }
+
+    /// The chunks of the service worker's module graph. Their modules are all
+    /// placed into the service worker chunk.
+    #[turbo_tasks::function]
+    async fn chunks(&self) -> Result<Vc<EcmascriptChunks>> {
+        let entries = self
+            .evaluatable_assets
+            .await?
+            .iter()
+            .map(|&evaluatable| Vc::upcast(evaluatable))
+            .collect::<Vec<_>>();
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
+            Vc::upcast(self.chunking_context),
+            entries,
+            AvailabilityInfo::Root,
+        )
+        .await?;
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk))
+            .try_flat_join()
+            .await?;
+        Ok(Vc::cell(chunks))
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+
+        let entry_ids = this
+            .evaluatable_assets
+            .await?
+            .iter()
+            .map({
+                let chunking_context = this.chunking_context;
+                move |entry| async move {
+                    if let Some(placeable) =
+                        Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(*entry)
+                            .await?
+                    {
+                        Ok(Some(
+                            placeable
+                                .as_chunk_item(Vc::upcast(chunking_context))
+                                .id()
+                                .await?,
+                        ))
+                    } else {
+                        Ok(None)
+                    }
+                }
+            })
+            .try_flat_join()
+            .await?;
+
+        let mut code = CodeBuilder::default();
+
+        write!(code, "const MODULE_FACTORIES = {{")?;
+        for &chunk in self.chunks().await?.iter() {
+            for (id, item_code) in chunk_items(chunk.chunk_content()).await? {
+                write!(code, "\n{}: ", StringifyJs(&id))?;
+                code.push_code(&item_code);
+                write!(code, ",")?;
+            }
+        }
+        writeln!(code, "\n}};\n")?;
+
+        let entries = precache_entries(this.path.parent(), this.precache).await?;
+        writeln!(
+            code,
+            "self.TURBOPACK_PRECACHE_MANIFEST = {};\n",
+            StringifyJs(&*entries)
+        )?;
+
+        let runtime_code = turbopack_ecmascript_runtime::get_worklet_runtime_code(
+            this.chunking_context.environment(),
+        );
+        code.push_code(&*runtime_code.await?);
+
+        write!(
+            code,
+            "\nevaluateWorkletEntries({});",
+            StringifyJs(&entry_ids)
+        )?;
+
+        let mut code = code.build().cell();
+        let chunking_context = this.chunking_context.await?;
+        if let Some(environment) = chunking_context.downlevel_environment() {
+            code = downlevel(this.path, code, environment);
+        }
+        if matches!(chunking_context.minify_type(), MinifyType::Minify) {
+            code = minify(this.path, code, true);
+        }
+
+        append_source_map_url(
+            code,
+            &*this.path.await?,
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            this.chunking_context.source_map_privacy(),
+            |url| format!("\n\n//# sourceMappingURL={url}"),
+        )
+        .await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(
+            "Ecmascript Build Service Worker Chunk".to_string(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut references = Vec::new();
+
+        let include_source_map = matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        );
+
+        if include_source_map {
+            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+        }
+
+        // Assets referenced by the modules of the service worker, e.g. images,
+        // are still emitted on their own.
+        let chunk_references = self
+            .chunks()
+            .await?
+            .iter()
+            .map(|&chunk| chunk.references())
+            .try_join()
+            .await?;
+        references.extend(
+            chunk_references
+                .iter()
+                .flat_map(|chunk_references| chunk_references.iter().copied()),
+        );
+
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptBuildServiceWorkerChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
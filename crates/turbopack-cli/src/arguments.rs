@@ -3,7 +3,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use clap::{Args, Parser};
+use clap::{Args, Parser, Subcommand};
 use turbopack_cli_utils::issue::IssueSeverityCliOption;
 
 #[derive(Debug, Parser)]
@@ -11,6 +11,9 @@ use turbopack_cli_utils::issue::IssueSeverityCliOption;
 pub enum Arguments {
     Build(BuildArguments),
     Dev(DevArguments),
+    Metrics(MetricsArguments),
+    Inspect(InspectArguments),
+    Cache(CacheArguments),
 }
 
 impl Arguments {
@@ -19,6 +22,18 @@ impl Arguments {
         match self {
             Arguments::Build(args) => args.common.dir.as_deref(),
             Arguments::Dev(args) => args.common.dir.as_deref(),
+            Arguments::Metrics(args) => args.dir.as_deref(),
+            Arguments::Cache(args) => args.dir.as_deref(),
+            Arguments::Inspect(_) => None,
+        }
+    }
+    /// The number of modules to report with their build time. see
+    /// [CommonArguments]::module_timings
+    pub fn module_timings(&self) -> Option<usize> {
+        match self {
+            Arguments::Build(args) => args.common.module_timings,
+            Arguments::Dev(args) => args.common.module_timings,
+            Arguments::Metrics(_) | Arguments::Inspect(_) | Arguments::Cache(_) => None,
         }
     }
 }
@@ -61,6 +76,25 @@ pub struct CommonArguments {
     /// MB.
     #[clap(long)]
     pub memory_limit: Option<usize>,
+    /// Print the slowest modules with the time spent on transforming,
+    /// analyzing and generating code for them after each compilation.
+    #[clap(long, value_name = "COUNT", num_args = 0..=1, default_missing_value = "50")]
+    pub module_timings: Option<usize>,
+
+    /// Record the duration, task cache hits and output sizes of every build
+    /// to `.turbopack/build-metrics.jsonl`. See the `metrics` command.
+    #[clap(long)]
+    pub record_metrics: bool,
+
+    /// Report modules whose generated code exceeds this size in bytes, which
+    /// usually means that data has been imported by accident.
+    #[clap(long, value_name = "BYTES")]
+    pub module_size_warning_threshold: Option<u64>,
+
+    /// Report packages that are bundled with a version of one of their peer
+    /// dependencies outside of the range they declare.
+    #[clap(long)]
+    pub check_peer_dependencies: bool,
 }
 
 #[derive(Debug, Args)]
@@ -85,10 +119,88 @@ pub struct DevArguments {
     #[clap(long)]
     pub eager_compile: bool,
 
+    /// Compute the chunk groups of the entries when the dev server starts, so
+    /// that the first request of the page doesn't have to wait for the
+    /// analysis of the app.
+    #[clap(long)]
+    pub warm_up: bool,
+
     /// Don't open the browser automatically when the dev server has started.
     #[clap(long)]
     pub no_open: bool,
 
+    /// Record the file changes detected while the dev server is running, and
+    /// the updates they cause, to the given file. The recording can be replayed
+    /// with `--replay-invalidations` to reproduce incremental build bugs.
+    #[clap(long, value_parser)]
+    pub record_invalidations: Option<PathBuf>,
+
+    /// Replay a recording created with `--record-invalidations` instead of
+    /// watching the project for changes. The project directory should contain
+    /// the state from the start of the recording, as the recorded changes are
+    /// written to it.
+    #[clap(long, value_parser, conflicts_with = "record_invalidations")]
+    pub replay_invalidations: Option<PathBuf>,
+
+    /// Bias chunking towards the modules executed during a previous session.
+    /// The usage profile can be exported by running
+    /// `copy(TURBOPACK_USAGE_PROFILE())` in the browser console.
+    #[clap(long, value_parser)]
+    pub usage_profile: Option<PathBuf>,
+
+    /// Don't emit JavaScript for entries that only import CSS, e.g. static
+    /// marketing pages. Their CSS isn't hot reloaded.
+    #[clap(long)]
+    pub static_chunk_groups: bool,
+
+    /// Keep vendor chunks independent of app code, so that the browser keeps
+    /// them cached while app code is edited. Vendor modules can then be
+    /// loaded by several chunk groups.
+    #[clap(long)]
+    pub isolate_vendor_chunks: bool,
+
+    /// Don't place modules in `node_modules` into separate chunks from app
+    /// code. This results in fewer, larger chunks, which can load faster when
+    /// each request has a high latency, e.g. in remote development containers.
+    #[clap(long)]
+    pub no_split_node_modules: bool,
+
+    /// Pin module ids and chunk names to those recorded in this JSON file,
+    /// relative to the project directory, so that browser caches and HMR
+    /// sessions stay valid after a restart. The file is kept up to date with
+    /// the output, which compiles the whole app eagerly.
+    #[clap(long, value_name = "PATH")]
+    pub records: Option<String>,
+
+    /// Declare a build-time feature flag, which modules read as
+    /// `FEATURE_FLAGS.<name>`. Flags are disabled in the variant served at `/`.
+    #[clap(long = "feature-flag", value_name = "NAME")]
+    pub feature_flags: Vec<String>,
+
+    /// Also serve the variant with these flags enabled (joined by `+`) at
+    /// `/__flags/<flags>/`, or every combination of flags with `all`.
+    #[clap(long = "feature-flag-variant", value_name = "FLAGS")]
+    pub feature_flag_variants: Vec<String>,
+
+    /// Load chunks and static assets from the base paths of the request's host
+    /// in this JSON file, relative to the project directory, e.g. to serve
+    /// each tenant from its own CDN. It maps host names (or their first label)
+    /// to a `chunkBasePath` and an `assetBasePath`.
+    #[clap(long, value_name = "PATH")]
+    pub base_paths: Option<String>,
+
+    /// Serve on the already bound TCP listener with this file descriptor
+    /// instead of binding `--hostname` and `--port`, e.g. a socket passed by a
+    /// parent process or by systemd socket activation (usually `3`).
+    #[clap(long, value_name = "FD", conflicts_with = "allow_retry")]
+    pub listen_fd: Option<i32>,
+
+    /// Also serve hot updates on a Unix domain socket at this path, e.g. for
+    /// an embedded WebView. Messages are newline-delimited JSON.
+    #[cfg(unix)]
+    #[clap(long, value_parser)]
+    pub hmr_socket: Option<PathBuf>,
+
     // ==
     // = Inherited options from next-dev, need revisit later.
     // ==
@@ -107,4 +219,165 @@ pub struct BuildArguments {
     /// Don't minify build output.
     #[clap(long)]
     pub no_minify: bool,
+
+    /// Additionally emit entry chunks for the legacy browsers matching this
+    /// browserslist query to `dist/legacy`. They share the module graph with
+    /// the modern chunks, but their code is downleveled. The entry chunks are
+    /// paired in `dist/differential-loading.json`, so that HTML can load them
+    /// with `<script type="module">` and `<script nomodule>`.
+    #[clap(long)]
+    pub legacy_browserslist: Option<String>,
+
+    /// Modules that are evaluated before the entries of legacy chunks, e.g.
+    /// `core-js/stable`.
+    #[clap(long, requires = "legacy_browserslist")]
+    pub legacy_polyfills: Vec<String>,
+
+    /// Print the longest chains of re-exports, e.g. through nested barrel
+    /// files.
+    #[clap(long, value_name = "COUNT", num_args = 0..=1, default_missing_value = "50")]
+    pub reexport_chains: Option<usize>,
+
+    /// Flatten the getters of bindings re-exported more than once to read the
+    /// binding from the declaring module, unless the re-exporting module is
+    /// an async module.
+    #[clap(long)]
+    pub flatten_reexports: bool,
+
+    /// Bias chunking towards the modules executed during a session, as
+    /// exported by the dev server's `TURBOPACK_USAGE_PROFILE()`.
+    #[clap(long, value_parser)]
+    pub usage_profile: Option<PathBuf>,
+
+    /// Write the contents of the emitted assets to this content-addressed
+    /// store instead of `dist`. Contents that are already in the store, e.g.
+    /// from a previous build or another entry, aren't written again.
+    /// `dist/content-store.json` maps the paths of the assets to their blobs
+    /// in the store.
+    #[clap(long, value_name = "DIR")]
+    pub content_store: Option<PathBuf>,
+
+    /// Fail the build when it would emit a source map, e.g. to keep the
+    /// sources of a production deployment private. No output is written
+    /// then.
+    #[clap(long)]
+    pub forbid_source_maps: bool,
+
+    /// Write `dist/build-manifest.json`, which describes the files of every
+    /// entry and is read by `turbopack inspect`. Every file is hashed for it,
+    /// and its serving hints (e.g. `cache-control`) are included.
+    #[clap(long)]
+    pub build_manifest: bool,
+
+    /// Print the chunks every entry would be split into as JSON, without
+    /// generating or writing any output. This is much faster than a build.
+    #[clap(long)]
+    pub plan: bool,
+
+    /// Write `dist/prefetch-manifest.json`, which maps every entry to the JS
+    /// chunks, CSS chunks and assets a client-side router prefetches when a
+    /// link to it is hovered. The chunks of the entries are written as well.
+    #[clap(long)]
+    pub prefetch_manifest: bool,
+
+    /// Build this module (relative to the project's directory) as a service
+    /// worker to `dist/<name>.js`, which precaches every asset of the entries.
+    /// The precached URLs are also written to `dist/<name>.precache.json`.
+    #[clap(long, value_name = "PATH")]
+    pub service_worker: Option<String>,
+
+    /// Report the files matching this glob (relative to the project's
+    /// directory) that no entry imports, e.g. `src/**/*.{ts,tsx}`. Files that
+    /// are only imported for their types and files in `public` are used.
+    #[clap(long = "unused-files", value_name = "GLOB")]
+    pub unused_files: Vec<String>,
+
+    /// Declare a build-time feature flag, which modules read as
+    /// `FEATURE_FLAGS.<name>`. Flags are disabled in the variant built to
+    /// `dist`.
+    #[clap(long = "feature-flag", value_name = "NAME")]
+    pub feature_flags: Vec<String>,
+
+    /// Also build the variant with these flags enabled (joined by `+`) to
+    /// `dist/__flags/<flags>`, or every combination of flags with `all`.
+    #[clap(long = "feature-flag-variant", value_name = "FLAGS")]
+    pub feature_flag_variants: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+#[clap(author, version, about, long_about = None)]
+pub struct MetricsArguments {
+    /// The directory of the application.
+    /// If no directory is provided, the current directory will be used.
+    #[clap(short, long, value_parser)]
+    pub dir: Option<PathBuf>,
+
+    /// The number of builds to print. Their averages are compared with the
+    /// same number of builds before them.
+    #[clap(long, default_value_t = 10)]
+    pub last: usize,
+
+    /// Print the metrics of dev server updates instead of builds.
+    #[clap(long)]
+    pub dev: bool,
+}
+
+/// Prints the statistics of the persistent cache in `.turbopack/cache`, and
+/// prunes it when any limits are given. Builds don't write to the cache yet,
+/// see `turbo_tasks_cache`.
+#[derive(Debug, Args)]
+#[clap(author, version, about, long_about = None)]
+pub struct CacheArguments {
+    /// The directory of the application.
+    /// If no directory is provided, the current directory will be used.
+    #[clap(short, long, value_parser)]
+    pub dir: Option<PathBuf>,
+
+    /// Remove the least recently used blobs until the cache has at most this
+    /// many megabytes.
+    #[clap(long, value_name = "MB")]
+    pub max_size: Option<u64>,
+
+    /// Remove blobs that haven't been used for this many days.
+    #[clap(long, value_name = "DAYS")]
+    pub max_age: Option<u64>,
+
+    /// Remove blobs that haven't been used by any of this many last builds.
+    #[clap(long, value_name = "BUILDS")]
+    pub max_unused_builds: Option<u32>,
+}
+
+/// Answers queries about the output directory of a build, using the
+/// `build-manifest.json` written by `build`.
+#[derive(Debug, Args)]
+#[clap(author, version, about, long_about = None)]
+pub struct InspectArguments {
+    /// The output directory of the build.
+    #[clap(short, long, value_parser, default_value = "dist")]
+    pub output_dir: PathBuf,
+
+    #[clap(subcommand)]
+    pub query: InspectQuery,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum InspectQuery {
+    /// List the files of the chunk group of an entry.
+    Chunks {
+        /// The name of the entry, e.g. `index` for `src/index.js`.
+        entry: String,
+    },
+    /// Find the chunks that include a module.
+    Module {
+        /// The id of the module.
+        id: String,
+    },
+    /// Verify the files against the SRI hashes of the build manifest.
+    Verify,
+    /// Print the changes from another output directory, e.g. of a previous
+    /// build, to the output directory.
+    Diff {
+        /// The output directory to compare with.
+        other_output_dir: PathBuf,
+    },
 }
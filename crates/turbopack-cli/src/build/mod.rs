@@ -1,41 +1,63 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     env::current_dir,
-    path::{PathBuf, MAIN_SEPARATOR},
+    path::{Path, PathBuf, MAIN_SEPARATOR},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context, Result};
+use serde_json::json;
 use turbo_tasks::{TransientInstance, TryJoinIterExt, TurboTasks, Value, Vc};
-use turbo_tasks_fs::FileSystem;
+use turbo_tasks_fs::{File, FileContent, FileSystem, FileSystemEntryType, FileSystemPath};
 use turbo_tasks_memory::MemoryBackend;
-use turbopack::ecmascript::EcmascriptModuleAsset;
+use turbopack::{
+    ecmascript::{references::esm::reexport_chains_report, EcmascriptModuleAsset},
+    emit_assets_with_policy,
+};
 use turbopack_build::{BuildChunkingContext, MinifyType};
 use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
 use turbopack_core::{
-    asset::Asset,
+    asset::{Asset, AssetContent},
     chunk::{
-        availability_info::AvailabilityInfo, ChunkableModule, ChunkingContextExt, EvaluatableAssets,
+        availability_info::AvailabilityInfo,
+        integrity::sha384_integrity,
+        plan::ChunkGroupPlanTree,
+        prefetch_manifest::{PrefetchManifestAsset, PrefetchRoutes},
+        usage_profile::UsageProfile,
+        ChunkableModule, ChunkingContext, ChunkingContextExt, EvaluatableAsset, EvaluatableAssets,
+        OutputChunk,
     },
+    emit_policy::{check_emit_policy, NoSourceMapsPolicy, OptionEmitPolicy},
     environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
+    feature_flags::{compile_time_info_with_feature_flags, FeatureFlagSet, FeatureFlags},
+    hash::HashAlgorithm,
     issue::{handle_issues, IssueReporter, IssueSeverity},
     module::Module,
-    output::OutputAsset,
-    reference::all_assets_from_entries,
+    output::{OutputAsset, OutputAssets},
+    reference::{all_assets_from_entries, all_modules},
     reference_type::{EntryReferenceSubType, ReferenceType},
     resolve::{
         origin::{PlainResolveOrigin, ResolveOriginExt},
         parse::Request,
     },
+    unused_files::{unused_files as find_unused_files, UnusedFilesOptions},
+    virtual_output::VirtualOutputAsset,
 };
 use turbopack_env::dotenv::load_env;
 use turbopack_node::execution_context::ExecutionContext;
 
 use crate::{
     arguments::BuildArguments,
-    contexts::{get_client_asset_context, get_client_compile_time_info, NodeEnv},
+    contexts::{
+        get_client_asset_context, get_client_compile_time_info, get_client_types_asset_context,
+        NodeEnv,
+    },
+    inspect::{BuildManifest, BuildManifestFile, BUILD_MANIFEST},
+    metrics::{BuildKind, BuildMetrics, BuildMetricsStore},
     util::{
-        normalize_dirs, normalize_entries, output_fs, project_fs, EntryRequest, EntryRequests,
+        content_store_fs, normalize_dirs, normalize_entries, output_fs, print_module_timings,
+        print_reexport_chains, project_fs, selected_feature_flag_variants, EntryRequest,
         NormalizedDirs,
     },
 };
@@ -45,16 +67,42 @@ pub fn register() {
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
 
-pub struct TurbopackBuildBuilder {
-    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+/// The options of a build, collected by [TurbopackBuildBuilder]. Every
+/// feature flag variant is built with the same options.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+struct BuildOptions {
     project_dir: String,
     root_dir: String,
     entry_requests: Vec<EntryRequest>,
     browserslist_query: String,
+    minify_type: MinifyType,
+    legacy_browserslist: Option<String>,
+    legacy_polyfills: Vec<String>,
+    reexport_chains: Option<usize>,
+    flatten_reexports: bool,
+    usage_profile: Option<Vec<String>>,
+    module_size_warning_threshold: Option<u64>,
+    check_peer_dependencies: bool,
+    content_store: Option<String>,
+    record_metrics: bool,
+    forbid_source_maps: bool,
+    build_manifest: bool,
+    plan: bool,
+    prefetch_manifest: bool,
+    service_worker: Option<String>,
+    unused_files: Vec<String>,
+}
+
+pub struct TurbopackBuildBuilder {
+    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+    options: BuildOptions,
     log_level: IssueSeverity,
     show_all: bool,
     log_detail: bool,
-    minify_type: MinifyType,
+    metrics_store: Option<BuildMetricsStore>,
+    feature_flags: Vec<String>,
+    feature_flag_variants: Vec<String>,
 }
 
 impl TurbopackBuildBuilder {
@@ -65,24 +113,45 @@ impl TurbopackBuildBuilder {
     ) -> Self {
         TurbopackBuildBuilder {
             turbo_tasks,
-            project_dir,
-            root_dir,
-            entry_requests: vec![],
-            browserslist_query: "chrome 64, edge 79, firefox 67, opera 51, safari 12".to_owned(),
+            options: BuildOptions {
+                project_dir,
+                root_dir,
+                entry_requests: vec![],
+                browserslist_query: "chrome 64, edge 79, firefox 67, opera 51, safari 12"
+                    .to_owned(),
+                minify_type: MinifyType::Minify,
+                legacy_browserslist: None,
+                legacy_polyfills: vec![],
+                reexport_chains: None,
+                flatten_reexports: false,
+                usage_profile: None,
+                module_size_warning_threshold: None,
+                check_peer_dependencies: false,
+                content_store: None,
+                record_metrics: false,
+                forbid_source_maps: false,
+                build_manifest: false,
+                plan: false,
+                prefetch_manifest: false,
+                service_worker: None,
+                unused_files: Vec::new(),
+            },
             log_level: IssueSeverity::Warning,
             show_all: false,
             log_detail: false,
-            minify_type: MinifyType::Minify,
+            metrics_store: None,
+            feature_flags: Vec::new(),
+            feature_flag_variants: Vec::new(),
         }
     }
 
     pub fn entry_request(mut self, entry_asset_path: EntryRequest) -> Self {
-        self.entry_requests.push(entry_asset_path);
+        self.options.entry_requests.push(entry_asset_path);
         self
     }
 
     pub fn browserslist_query(mut self, browserslist_query: String) -> Self {
-        self.browserslist_query = browserslist_query;
+        self.options.browserslist_query = browserslist_query;
         self
     }
 
@@ -102,74 +171,262 @@ impl TurbopackBuildBuilder {
     }
 
     pub fn minify_type(mut self, minify_type: MinifyType) -> Self {
-        self.minify_type = minify_type;
+        self.options.minify_type = minify_type;
         self
     }
 
-    pub async fn build(self) -> Result<()> {
-        let task = self.turbo_tasks.spawn_once_task::<(), _>(async move {
-            let build_result = build_internal(
-                self.project_dir.clone(),
-                self.root_dir,
-                EntryRequests(
-                    self.entry_requests
-                        .iter()
-                        .cloned()
-                        .map(EntryRequest::cell)
-                        .collect(),
-                )
-                .cell(),
-                self.browserslist_query,
-                self.minify_type,
-            );
+    /// Additionally emits entry chunks for the legacy browsers matching
+    /// `browserslist_query`, which evaluate `polyfills` first.
+    pub fn legacy(mut self, browserslist_query: String, polyfills: Vec<String>) -> Self {
+        self.options.legacy_browserslist = Some(browserslist_query);
+        self.options.legacy_polyfills = polyfills;
+        self
+    }
+
+    /// Prints the `limit` longest chains of re-exports after the build.
+    pub fn reexport_chains(mut self, limit: usize) -> Self {
+        self.options.reexport_chains = Some(limit);
+        self
+    }
+
+    /// Flattens the getters of bindings re-exported by several modules, so
+    /// they read the binding from the module declaring it.
+    pub fn flatten_reexports(mut self, flatten_reexports: bool) -> Self {
+        self.options.flatten_reexports = flatten_reexports;
+        self
+    }
+
+    /// Places the modules executed during a session first in their chunk
+    /// groups, see [UsageProfile].
+    pub fn usage_profile(mut self, executed_modules: Vec<String>) -> Self {
+        self.options.usage_profile = Some(executed_modules);
+        self
+    }
+
+    /// Reports modules whose generated code exceeds `threshold` bytes.
+    pub fn module_size_warning_threshold(mut self, threshold: u64) -> Self {
+        self.options.module_size_warning_threshold = Some(threshold);
+        self
+    }
+
+    /// Reports packages that are bundled with a version of one of their peer
+    /// dependencies outside of the declared range.
+    pub fn check_peer_dependencies(mut self, check_peer_dependencies: bool) -> Self {
+        self.options.check_peer_dependencies = check_peer_dependencies;
+        self
+    }
+
+    /// Writes the contents of the emitted assets to the content-addressed
+    /// store in `store_dir` instead of the output directory. Every content is
+    /// stored once, at a path derived from its hash, and
+    /// `dist/content-store.json` maps the paths of the assets to it.
+    pub fn content_store(mut self, store_dir: String) -> Self {
+        self.options.content_store = Some(store_dir);
+        self
+    }
+
+    /// Fails the build without writing any output when it would emit a source
+    /// map, see [NoSourceMapsPolicy].
+    pub fn forbid_source_maps(mut self, forbid_source_maps: bool) -> Self {
+        self.options.forbid_source_maps = forbid_source_maps;
+        self
+    }
 
-            // Await the result to propagate any errors.
-            build_result.await?;
+    /// Writes a [BuildManifest] to `dist/build-manifest.json`, which the
+    /// `inspect` command reads. It hashes the content of every asset.
+    pub fn build_manifest(mut self, build_manifest: bool) -> Self {
+        self.options.build_manifest = build_manifest;
+        self
+    }
+
+    /// Prints the [ChunkGroupPlanTree] of every entry as JSON instead of
+    /// building, see [turbopack_core::chunk::plan]. Only the default variant
+    /// is planned.
+    pub fn plan(mut self, plan: bool) -> Self {
+        self.options.plan = plan;
+        self
+    }
+
+    /// Writes the [PrefetchManifest] of the entries to
+    /// `dist/prefetch-manifest.json`, along with the chunk groups it lists.
+    ///
+    /// [PrefetchManifest]: turbopack_core::chunk::prefetch_manifest::PrefetchManifest
+    pub fn prefetch_manifest(mut self, prefetch_manifest: bool) -> Self {
+        self.options.prefetch_manifest = prefetch_manifest;
+        self
+    }
+
+    /// Builds the module at `path`, relative to the project directory, as a
+    /// service worker to `dist/<name>.js`, which precaches every asset of the
+    /// entries, see [ChunkingContext::service_worker_chunk_group].
+    pub fn service_worker(mut self, path: String) -> Self {
+        self.options.service_worker = Some(path);
+        self
+    }
+
+    /// Reports the files matching `glob` that no entry imports after the
+    /// build, see [turbopack_core::unused_files].
+    pub fn unused_files(mut self, glob: String) -> Self {
+        self.options.unused_files.push(glob);
+        self
+    }
+
+    /// Declares a feature flag, which is disabled in the default variant.
+    pub fn feature_flag(mut self, name: String) -> Self {
+        self.feature_flags.push(name);
+        self
+    }
+
+    /// Also builds the variant with the `enabled` flags (joined by `+`) to
+    /// `dist/__flags/<enabled>`, or every variant with `all`.
+    pub fn feature_flag_variant(mut self, enabled: String) -> Self {
+        self.feature_flag_variants.push(enabled);
+        self
+    }
+
+    /// Records the metrics of the build, including the size of every chunk
+    /// group, to `store`.
+    pub fn record_metrics(mut self, store: BuildMetricsStore) -> Self {
+        self.metrics_store = Some(store);
+        self
+    }
 
+    pub async fn build(self) -> Result<()> {
+        let start = Instant::now();
+        let record_metrics = self.metrics_store.is_some();
+        let (chunk_group_sizes_tx, chunk_group_sizes_rx) = tokio::sync::oneshot::channel();
+        let task = self.turbo_tasks.spawn_once_task::<(), _>(async move {
+            let options = BuildOptions {
+                record_metrics,
+                ..self.options.clone()
+            }
+            .cell();
             let issue_reporter: Vc<Box<dyn IssueReporter>> =
                 Vc::upcast(ConsoleUi::new(TransientInstance::new(LogOptions {
-                    project_dir: PathBuf::from(self.project_dir),
+                    project_dir: PathBuf::from(self.options.project_dir.clone()),
                     current_dir: current_dir().unwrap(),
                     show_all: self.show_all,
                     log_detail: self.log_detail,
                     log_level: self.log_level,
                 })));
 
-            handle_issues(
-                build_result,
-                issue_reporter,
-                IssueSeverity::Error.into(),
-                None,
-                None,
-            )
-            .await?;
+            // The default variant is built to `dist`, and the other selected
+            // variants to `dist/__flags/<enabled flags>`.
+            let mut variants = vec![(None, "dist".to_string())];
+            if !self.feature_flags.is_empty() {
+                let feature_flags: Vc<FeatureFlags> = Vc::cell(
+                    self.feature_flags
+                        .iter()
+                        .map(|name| (name.clone(), false))
+                        .collect(),
+                );
+                let defaults = feature_flags.defaults();
+                variants = vec![(Some(defaults), "dist".to_string())];
+                for variant in
+                    selected_feature_flag_variants(feature_flags, self.feature_flag_variants)
+                        .await?
+                {
+                    if *variant.await? != *defaults.await? {
+                        variants.push((
+                            Some(variant),
+                            format!("dist/__flags/{}", variant.key().await?),
+                        ));
+                    }
+                }
+            }
+
+            if self.options.plan {
+                variants.truncate(1);
+            }
+
+            for (i, (feature_flags, output_dir)) in variants.into_iter().enumerate() {
+                let build_result = build_internal(options, feature_flags, output_dir);
+
+                // Await the result to propagate any errors.
+                let build_output = build_result.await?;
+
+                handle_issues(
+                    build_result,
+                    issue_reporter,
+                    IssueSeverity::Error.into(),
+                    None,
+                    None,
+                )
+                .await?;
+
+                if let Some(plan) = &build_output.plan {
+                    println!("{plan}");
+                }
+
+                // The report and the metrics describe the default variant.
+                if i == 0 {
+                    if let Some(report) = &build_output.reexport_chains {
+                        print_reexport_chains(report);
+                    }
+                    if !build_output.unused_files.is_empty() {
+                        println!("Files that no entry imports:");
+                        for path in &build_output.unused_files {
+                            println!("  {path}");
+                        }
+                    }
+                    let _ = chunk_group_sizes_tx.send(build_output.chunk_group_sizes.clone());
+                }
+            }
 
             Ok(Default::default())
         });
 
         self.turbo_tasks.wait_task_completion(task, true).await?;
 
+        if let Some(store) = &self.metrics_store {
+            let tasks = self
+                .turbo_tasks
+                .aggregated_update_info(Duration::ZERO, Duration::ZERO)
+                .await
+                .map_or(0, |update| update.tasks);
+            let mut metrics = BuildMetrics::new(
+                BuildKind::Build,
+                start.elapsed(),
+                tasks,
+                self.turbo_tasks.backend().task_cache_stats(),
+            );
+            metrics.chunk_group_sizes = chunk_group_sizes_rx.await.unwrap_or_default();
+            store.record(&metrics)?;
+        }
+
         Ok(())
     }
 }
 
 #[turbo_tasks::function]
 async fn build_internal(
-    project_dir: String,
-    root_dir: String,
-    entry_requests: Vc<EntryRequests>,
-    browserslist_query: String,
-    minify_type: MinifyType,
-) -> Result<Vc<()>> {
-    let env = Environment::new(Value::new(ExecutionEnvironment::Browser(
-        BrowserEnvironment {
-            dom: true,
-            web_worker: false,
-            service_worker: false,
-            browserslist_query: browserslist_query.clone(),
-        }
-        .into(),
-    )));
+    options: Vc<BuildOptions>,
+    feature_flags: Option<Vc<FeatureFlagSet>>,
+    output_dir: String,
+) -> Result<Vc<BuildOutput>> {
+    let BuildOptions {
+        project_dir,
+        root_dir,
+        entry_requests,
+        browserslist_query,
+        minify_type,
+        legacy_browserslist,
+        legacy_polyfills,
+        reexport_chains,
+        flatten_reexports,
+        usage_profile,
+        module_size_warning_threshold,
+        check_peer_dependencies,
+        content_store,
+        record_metrics,
+        forbid_source_maps,
+        build_manifest,
+        plan,
+        prefetch_manifest,
+        service_worker,
+        unused_files,
+    } = options.await?.clone_value();
+    let env = browser_environment(browserslist_query.clone());
     let output_fs = output_fs(project_dir.clone());
     let project_fs = project_fs(root_dir.clone());
     let project_relative = project_dir.strip_prefix(&root_dir).unwrap();
@@ -178,51 +435,354 @@ async fn build_internal(
         .unwrap_or(project_relative)
         .replace(MAIN_SEPARATOR, "/");
     let project_path = project_fs.root().join(project_relative);
-    let build_output_root = output_fs.root().join("dist".to_string());
-
-    let chunking_context = Vc::upcast(
-        BuildChunkingContext::builder(
-            project_path,
-            build_output_root,
-            build_output_root,
-            build_output_root,
-            build_output_root,
-            env,
-        )
-        .minify_type(minify_type)
-        .build(),
-    );
+    let build_output_root = output_fs.root().join(output_dir);
+
+    let usage_profile = usage_profile.map(UsageProfile::new);
+    let mut build_chunking_context = BuildChunkingContext::builder(
+        project_path,
+        build_output_root,
+        build_output_root,
+        build_output_root,
+        build_output_root,
+        env,
+    )
+    .minify_type(minify_type)
+    .flatten_reexports(flatten_reexports);
+    if let Some(usage_profile) = usage_profile {
+        build_chunking_context = build_chunking_context.usage_profile(usage_profile);
+    }
+    if let Some(threshold) = module_size_warning_threshold {
+        build_chunking_context = build_chunking_context.module_size_warning_threshold(threshold);
+    }
+    if check_peer_dependencies {
+        build_chunking_context = build_chunking_context.check_peer_dependencies();
+    }
+    if plan {
+        build_chunking_context = build_chunking_context.dry_run();
+    }
+    let build_chunking_context = build_chunking_context.build();
+    let chunking_context = Vc::upcast(build_chunking_context);
 
     let node_env = NodeEnv::Production.cell();
-    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
+    let mut compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
+    if let Some(feature_flags) = feature_flags {
+        compile_time_info = compile_time_info_with_feature_flags(compile_time_info, feature_flags);
+    }
     let execution_context =
         ExecutionContext::new(project_path, chunking_context, load_env(project_path));
     let asset_context =
         get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
 
-    let entry_requests = (*entry_requests
-        .await?
+    let entry_requests = entry_requests
         .iter()
-        .cloned()
-        .map(|r| async move {
-            Ok(match &*r.await? {
-                EntryRequest::Relative(p) => {
-                    Request::relative(Value::new(p.clone().into()), Default::default(), false)
-                }
-                EntryRequest::Module(m, p) => {
-                    Request::module(m.clone(), Value::new(p.clone().into()), Default::default())
-                }
-            })
+        .map(|r| match r {
+            EntryRequest::Relative(p) => {
+                Request::relative(Value::new(p.clone().into()), Default::default(), false)
+            }
+            EntryRequest::Module(m, p) => {
+                Request::module(m.clone(), Value::new(p.clone().into()), Default::default())
+            }
         })
-        .try_join()
-        .await?)
-        .to_vec();
+        .collect::<Vec<_>>();
 
     let origin = PlainResolveOrigin::new(asset_context, output_fs.root().join("_".to_string()));
-    let project_dir = &project_dir;
-    let entries = entry_requests
+    let entries = resolve_entries(origin, &entry_requests, &project_dir).await?;
+
+    let unused_files = if unused_files.is_empty() {
+        Vec::new()
+    } else {
+        // Files that are only imported for their types are used as well
+        let types_origin = PlainResolveOrigin::new(
+            get_client_types_asset_context(
+                project_path,
+                execution_context,
+                compile_time_info,
+                node_env,
+            ),
+            output_fs.root().join("_".to_string()),
+        );
+        let modules = resolve_entries(types_origin, &entry_requests, &project_dir).await?;
+        let options = UnusedFilesOptions {
+            include: unused_files,
+            public_dirs: vec!["public".to_string()],
+            ..Default::default()
+        }
+        .cell();
+        let project_path_ref = project_path.await?;
+        let mut paths = Vec::new();
+        for &file in find_unused_files(project_path, Vc::cell(modules), options)
+            .await?
+            .iter()
+        {
+            paths.extend(
+                project_path_ref
+                    .get_path_to(&*file.await?)
+                    .map(|path| path.to_string()),
+            );
+        }
+        paths
+    };
+
+    let reexport_chains = match reexport_chains {
+        Some(limit) => {
+            let mut modules = HashSet::new();
+            for &entry in entries.iter() {
+                modules.extend(all_modules(entry).await?.iter().copied());
+            }
+            Some(
+                reexport_chains_report(Vc::cell(modules.into_iter().collect()), limit)
+                    .await?
+                    .clone_value(),
+            )
+        }
+        None => None,
+    };
+
+    if plan {
+        let mut plans = BTreeMap::new();
+        for &entry_module in entries.iter() {
+            let Some(chunkable) =
+                Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
+            else {
+                bail!("Entry module is not chunkable, so its chunks can't be planned")
+            };
+            let plan = chunking_context
+                .chunk_group(chunkable, Value::new(AvailabilityInfo::Root))
+                .await?
+                .plan
+                .context("the build chunking context didn't plan the chunk group")?;
+            plans.insert(
+                entry_name(entry_module).await?,
+                ChunkGroupPlanTree::capture(plan).await?,
+            );
+        }
+        return Ok(BuildOutput {
+            reexport_chains,
+            chunk_group_sizes: BTreeMap::new(),
+            unused_files,
+            plan: Some(serde_json::to_string_pretty(&plans)?),
+        }
+        .cell());
+    }
+
+    // Every entry is a route of the prefetch manifest, which is loaded as a
+    // chunk group by a client-side router.
+    let prefetch_manifest = if prefetch_manifest {
+        let mut routes = BTreeMap::new();
+        for &entry_module in entries.iter() {
+            if let Some(chunkable) =
+                Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
+            {
+                routes.insert(entry_name(entry_module).await?, chunkable);
+            }
+        }
+        Some(PrefetchManifestAsset::new(
+            build_output_root.join("prefetch-manifest.json".to_string()),
+            chunking_context,
+            Vc::<PrefetchRoutes>::cell(routes),
+        ))
+    } else {
+        None
+    };
+
+    let legacy = match legacy_browserslist {
+        Some(legacy_browserslist) => {
+            let legacy_env = browser_environment(legacy_browserslist);
+            let legacy_output_root = build_output_root.join("legacy".to_string());
+            let mut legacy_chunking_context = BuildChunkingContext::builder(
+                project_path,
+                legacy_output_root,
+                legacy_output_root,
+                legacy_output_root,
+                legacy_output_root,
+                legacy_env,
+            )
+            .minify_type(minify_type)
+            .flatten_reexports(flatten_reexports)
+            .downlevel(legacy_env);
+            if let Some(usage_profile) = usage_profile {
+                legacy_chunking_context = legacy_chunking_context.usage_profile(usage_profile);
+            }
+            if let Some(threshold) = module_size_warning_threshold {
+                legacy_chunking_context =
+                    legacy_chunking_context.module_size_warning_threshold(threshold);
+            }
+            if check_peer_dependencies {
+                legacy_chunking_context = legacy_chunking_context.check_peer_dependencies();
+            }
+            let legacy_chunking_context = legacy_chunking_context.build();
+            let polyfills = legacy_polyfills
+                .iter()
+                .map(|polyfill| async move {
+                    let request = Request::parse(Value::new(polyfill.clone().into()));
+                    let ty = Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined));
+                    let module = origin
+                        .resolve_asset(request, origin.resolve_options(ty.clone()), ty)
+                        .first_module()
+                        .await?
+                        .with_context(|| format!("Unable to resolve the polyfill {}.", polyfill))?;
+                    Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(module)
+                        .await?
+                        .with_context(|| format!("The polyfill {} can't be evaluated.", polyfill))
+                })
+                .try_join()
+                .await?;
+            Some((legacy_chunking_context, legacy_output_root, polyfills))
+        }
+        None => None,
+    };
+
+    let legacy = &legacy;
+    let entry_chunk_groups = entries
         .into_iter()
-        .map(|request_vc| async move {
+        .map(|entry_module| async move {
+            let name = entry_name(entry_module).await?;
+            let (chunk_group, differential_entry) = if let Some(ecmascript) =
+                Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(entry_module).await?
+            {
+                let modern = build_chunking_context
+                    .entry_chunk_group(
+                        build_output_root
+                            .join(name.clone())
+                            .with_extension("entry.js".to_string()),
+                        Vc::upcast(ecmascript),
+                        EvaluatableAssets::one(Vc::upcast(ecmascript)),
+                        Value::new(AvailabilityInfo::Root),
+                    )
+                    .await?
+                    .asset;
+                if let Some((legacy_chunking_context, legacy_output_root, polyfills)) = legacy {
+                    let mut evaluatable_assets = polyfills.clone();
+                    evaluatable_assets.push(Vc::upcast(ecmascript));
+                    let legacy = legacy_chunking_context
+                        .entry_chunk_group(
+                            legacy_output_root
+                                .join(name.clone())
+                                .with_extension("entry.js".to_string()),
+                            Vc::upcast(ecmascript),
+                            EvaluatableAssets::many(evaluatable_assets),
+                            Value::new(AvailabilityInfo::Root),
+                        )
+                        .await?
+                        .asset;
+                    (
+                        Vc::cell(vec![modern, legacy]),
+                        Some((name.clone(), modern, legacy)),
+                    )
+                } else {
+                    (Vc::cell(vec![modern]), None)
+                }
+            } else if let Some(chunkable) =
+                Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
+            {
+                (chunking_context.root_chunk_group_assets(chunkable), None)
+            } else {
+                // TODO convert into a serve-able asset
+                bail!(
+                    "Entry module is not chunkable, so it can't be used to bootstrap the \
+                     application"
+                )
+            };
+            Ok((name, chunk_group, differential_entry))
+        })
+        .try_join()
+        .await?;
+
+    let mut chunks: HashSet<Vc<Box<dyn OutputAsset>>> = HashSet::new();
+    let mut differential_entries = Vec::new();
+    let mut chunk_group_sizes = BTreeMap::new();
+    let mut manifest_entries = Vec::new();
+    for (name, chunk_group, differential_entry) in entry_chunk_groups {
+        let assets = all_assets_from_entries(chunk_group);
+        chunks.extend(&*assets.await?);
+        manifest_entries.push((name.clone(), assets));
+        differential_entries.extend(differential_entry);
+        if record_metrics {
+            *chunk_group_sizes.entry(name).or_default() += assets_size(assets).await?;
+        }
+    }
+
+    if let Some(prefetch_manifest) = prefetch_manifest {
+        chunks.extend(
+            &*all_assets_from_entries(Vc::cell(vec![Vc::upcast(prefetch_manifest)])).await?,
+        );
+    }
+
+    // The service worker precaches the assets of the entries, so it's built
+    // after them.
+    if let Some(service_worker) = service_worker {
+        let request = Request::relative(
+            Value::new(service_worker.clone().into()),
+            Default::default(),
+            false,
+        );
+        let module = resolve_entries(origin, &[request], &project_dir).await?[0];
+        let Some(evaluatable) =
+            Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(module).await?
+        else {
+            bail!("The service worker {service_worker} can't be evaluated")
+        };
+        let assets = chunking_context
+            .service_worker_chunk_group(
+                build_output_root.join(format!("{}.js", entry_name(module).await?)),
+                EvaluatableAssets::one(evaluatable),
+                Vc::cell(chunks.iter().copied().collect()),
+            )
+            .await?
+            .assets;
+        chunks.extend(&*all_assets_from_entries(assets).await?);
+    }
+
+    let manifest = if build_manifest {
+        Some(build_manifest_asset(build_output_root, manifest_entries, &chunks).await?)
+    } else {
+        None
+    };
+    let emit_policy: Vc<OptionEmitPolicy> =
+        Vc::cell(forbid_source_maps.then(|| Vc::upcast(NoSourceMapsPolicy::new())));
+    if let Some(store_dir) = content_store {
+        if let Some(policy) = *emit_policy.await? {
+            let assets = chunks.iter().copied().chain(manifest).collect::<Vec<_>>();
+            check_emit_policy(&assets, policy).await?;
+        }
+        write_to_content_store(build_output_root, content_store_fs(store_dir), &chunks).await?;
+        // The manifest describes the output directory, so it's written there
+        // instead of into the content store. It has been checked already.
+        if let Some(manifest) = manifest {
+            emit_assets_with_policy(Vc::cell(vec![manifest]), output_fs.root(), Vc::cell(None))
+                .await?;
+        }
+    } else {
+        emit_assets_with_policy(
+            Vc::cell(chunks.iter().copied().chain(manifest).collect()),
+            output_fs.root(),
+            emit_policy,
+        )
+        .await?;
+    }
+
+    if legacy.is_some() {
+        write_differential_loading_manifest(build_output_root, differential_entries).await?;
+    }
+
+    Ok(BuildOutput {
+        reexport_chains,
+        chunk_group_sizes,
+        unused_files,
+        plan: None,
+    }
+    .cell())
+}
+
+/// Resolves the `entry_requests` from `origin` to the entry modules.
+async fn resolve_entries(
+    origin: Vc<PlainResolveOrigin>,
+    entry_requests: &[Vc<Request>],
+    project_dir: &str,
+) -> Result<Vec<Vc<Box<dyn Module>>>> {
+    entry_requests
+        .iter()
+        .map(|&request_vc| async move {
             let ty = Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined));
             let request = request_vc.await?;
             origin
@@ -238,67 +798,210 @@ async fn build_internal(
                 })
         })
         .try_join()
-        .await?;
+        .await
+}
 
-    let entry_chunk_groups = entries
-        .into_iter()
-        .map(|entry_module| async move {
-            Ok(
-                if let Some(ecmascript) =
-                    Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(entry_module).await?
-                {
-                    Vc::cell(vec![
-                        Vc::try_resolve_downcast_type::<BuildChunkingContext>(chunking_context)
-                            .await?
-                            .unwrap()
-                            .entry_chunk_group(
-                                build_output_root
-                                    .join(
-                                        ecmascript
-                                            .ident()
-                                            .path()
-                                            .file_stem()
-                                            .await?
-                                            .as_deref()
-                                            .unwrap()
-                                            .to_string(),
-                                    )
-                                    .with_extension("entry.js".to_string()),
-                                Vc::upcast(ecmascript),
-                                EvaluatableAssets::one(Vc::upcast(ecmascript)),
-                                Value::new(AvailabilityInfo::Root),
-                            )
-                            .await?
-                            .asset,
-                    ])
-                } else if let Some(chunkable) =
-                    Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
-                {
-                    chunking_context.root_chunk_group_assets(chunkable)
-                } else {
-                    // TODO convert into a serve-able asset
-                    bail!(
-                        "Entry module is not chunkable, so it can't be used to bootstrap the \
-                         application"
-                    )
-                },
-            )
-        })
-        .try_join()
-        .await?;
+/// The name of the output of `entry_module`, which is the file stem of the
+/// module.
+async fn entry_name(entry_module: Vc<Box<dyn Module>>) -> Result<String> {
+    Ok(entry_module
+        .ident()
+        .path()
+        .file_stem()
+        .await?
+        .as_deref()
+        .unwrap_or_default()
+        .to_string())
+}
 
-    let mut chunks: HashSet<Vc<Box<dyn OutputAsset>>> = HashSet::new();
-    for chunk_group in entry_chunk_groups {
-        chunks.extend(&*all_assets_from_entries(chunk_group).await?);
+#[turbo_tasks::value(shared)]
+struct BuildOutput {
+    reexport_chains: Option<String>,
+    /// The total size in bytes of the assets of each entry, see
+    /// [BuildMetrics::chunk_group_sizes].
+    chunk_group_sizes: BTreeMap<String, u64>,
+    /// The paths of the files that no entry imports, relative to the project
+    /// directory.
+    unused_files: Vec<String>,
+    /// The chunks of every entry as JSON, when only planning the build.
+    plan: Option<String>,
+}
+
+/// Returns the total size in bytes of the file contents of `assets`.
+async fn assets_size(assets: Vc<OutputAssets>) -> Result<u64> {
+    let mut size = 0;
+    for asset in assets.await?.iter() {
+        let AssetContent::File(file) = &*asset.content().await? else {
+            continue;
+        };
+        if let FileContent::Content(file) = &*file.await? {
+            size += file.content().len() as u64;
+        }
     }
+    Ok(size)
+}
 
-    chunks
+/// Writes a manifest that pairs the modern and legacy entry chunks, so that
+/// HTML can load them with `<script type="module">` and `<script nomodule>`.
+async fn write_differential_loading_manifest(
+    build_output_root: Vc<FileSystemPath>,
+    entries: Vec<(String, Vc<Box<dyn OutputAsset>>, Vc<Box<dyn OutputAsset>>)>,
+) -> Result<()> {
+    let root = build_output_root.await?;
+    let mut manifest_entries = serde_json::Map::new();
+    for (name, modern, legacy) in entries {
+        let modern_path = modern.ident().path().await?;
+        let legacy_path = legacy.ident().path().await?;
+        manifest_entries.insert(
+            name,
+            json!({
+                "module": root.get_path_to(&modern_path),
+                "nomodule": root.get_path_to(&legacy_path),
+            }),
+        );
+    }
+    let manifest = serde_json::to_string_pretty(&json!({ "entries": manifest_entries }))?;
+    build_output_root
+        .join("differential-loading.json".to_string())
+        .write(FileContent::Content(File::from(manifest)).cell())
+        .await?;
+    Ok(())
+}
+
+/// Writes the contents of `assets` to a content-addressed store, at
+/// `{hash:.2}/{hash}.{extension}`, and a manifest that maps the paths of the
+/// assets to these blobs. Blobs that are already in the store aren't written
+/// again, so unchanged assets are only uploaded once across builds.
+///
+/// Assets without file content, e.g. redirects, are written to the output
+/// directory as usual.
+async fn write_to_content_store(
+    build_output_root: Vc<FileSystemPath>,
+    store_fs: Vc<Box<dyn FileSystem>>,
+    assets: &HashSet<Vc<Box<dyn OutputAsset>>>,
+) -> Result<()> {
+    let root = build_output_root.await?;
+    let blobs = assets
         .iter()
-        .map(|c| c.content().write(c.ident().path()))
+        .map(|&asset| {
+            let root = &root;
+            async move {
+                let path = asset.ident().path();
+                let content = asset.content();
+                let AssetContent::File(file) = &*content.await? else {
+                    content.write(path).await?;
+                    return Ok(None);
+                };
+                let FileContent::Content(file_content) = &*file.await? else {
+                    content.write(path).await?;
+                    return Ok(None);
+                };
+                let path_ref = path.await?;
+                let Some(asset_path) = root.get_path_to(&path_ref) else {
+                    content.write(path).await?;
+                    return Ok(None);
+                };
+                // The hash has to be collision resistant, as blobs are shared
+                // across builds. Blobs are keyed by the version of the hash
+                // as well, so that a store never mixes hashes of different
+                // formats.
+                let algorithm = HashAlgorithm::Blake3;
+                let hash = algorithm.hash_hex(file_content.content());
+                let version = algorithm.version();
+                let blob = match path_ref.extension_ref() {
+                    Some(extension) => format!("{version}/{}/{hash}.{extension}", &hash[..2]),
+                    None => format!("{version}/{}/{hash}", &hash[..2]),
+                };
+                let blob_path = store_fs.root().join(blob.clone());
+                if *blob_path.get_type().await? == FileSystemEntryType::NotFound {
+                    blob_path.write(*file).await?;
+                }
+                Ok(Some((asset_path.to_string(), blob)))
+            }
+        })
         .try_join()
         .await?;
+    let manifest_assets: BTreeMap<_, _> = blobs.into_iter().flatten().collect();
+    let manifest = serde_json::to_string_pretty(&json!({ "assets": manifest_assets }))?;
+    build_output_root
+        .join("content-store.json".to_string())
+        .write(FileContent::Content(File::from(manifest)).cell())
+        .await?;
+    Ok(())
+}
 
-    Ok(Default::default())
+/// Creates the [BuildManifest], which the `inspect` command reads to answer
+/// queries about the output directory, and which exports the serving hints of
+/// the files, e.g. for configuring the cache headers of a CDN.
+async fn build_manifest_asset(
+    build_output_root: Vc<FileSystemPath>,
+    entries: Vec<(String, Vc<OutputAssets>)>,
+    assets: &HashSet<Vc<Box<dyn OutputAsset>>>,
+) -> Result<Vc<Box<dyn OutputAsset>>> {
+    let root = build_output_root.await?;
+    let mut manifest = BuildManifest::default();
+    for (name, entry_assets) in entries {
+        let paths = manifest.entries.entry(name).or_default();
+        for asset in entry_assets.await?.iter() {
+            if let Some(path) = root.get_path_to(&*asset.ident().path().await?) {
+                paths.push(path.to_string());
+            }
+        }
+    }
+    for &asset in assets {
+        let Some(path) = root
+            .get_path_to(&*asset.ident().path().await?)
+            .map(|path| path.to_string())
+        else {
+            continue;
+        };
+        let AssetContent::File(file) = &*asset.content().await? else {
+            continue;
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            continue;
+        };
+        let content = file.content().to_bytes()?;
+        let mut modules = Vec::new();
+        if let Some(chunk) = Vc::try_resolve_sidecast::<Box<dyn OutputChunk>>(asset).await? {
+            if let Some(included_ids) = chunk.runtime_info().await?.included_ids {
+                for id in included_ids.await?.iter() {
+                    modules.push(id.await?.to_string());
+                }
+            }
+        }
+        modules.sort();
+        let serving_hints = asset.serving_hints().await?;
+        manifest.files.insert(
+            path,
+            BuildManifestFile {
+                size: content.len() as u64,
+                modules,
+                integrity: sha384_integrity(&content),
+                cache_control: serving_hints
+                    .cache_control
+                    .map(|cache_control| cache_control.header_value().to_string()),
+                content_type: serving_hints.content_type.clone(),
+            },
+        );
+    }
+    let content = FileContent::Content(File::from(serde_json::to_string_pretty(&manifest)?));
+    Ok(Vc::upcast(VirtualOutputAsset::new(
+        build_output_root.join(BUILD_MANIFEST.to_string()),
+        AssetContent::file(content.cell()),
+    )))
+}
+
+fn browser_environment(browserslist_query: String) -> Vc<Environment> {
+    Environment::new(Value::new(ExecutionEnvironment::Browser(
+        BrowserEnvironment {
+            dom: true,
+            web_worker: false,
+            service_worker: false,
+            browserslist_query,
+        }
+        .into(),
+    )))
 }
 
 pub async fn build(args: &BuildArguments) -> Result<()> {
@@ -313,6 +1016,11 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
             .map_or(usize::MAX, |l| l * 1024 * 1024),
     ));
 
+    let metrics_store = args
+        .common
+        .record_metrics
+        .then(|| BuildMetricsStore::open(Path::new(&project_dir)));
+
     let mut builder = TurbopackBuildBuilder::new(tt, project_dir, root_dir)
         .log_detail(args.common.log_detail)
         .log_level(
@@ -331,7 +1039,156 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
         builder = builder.entry_request(EntryRequest::Relative(entry));
     }
 
+    if let Some(legacy_browserslist) = &args.legacy_browserslist {
+        builder = builder.legacy(legacy_browserslist.clone(), args.legacy_polyfills.clone());
+    }
+
+    if let Some(limit) = args.reexport_chains {
+        builder = builder.reexport_chains(limit);
+    }
+
+    builder = builder.flatten_reexports(args.flatten_reexports);
+
+    if let Some(path) = &args.usage_profile {
+        let profile = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read usage profile {}", path.display()))?;
+        builder = builder.usage_profile(UsageProfile::parse_executed_modules(&profile)?);
+    }
+
+    if let Some(threshold) = args.common.module_size_warning_threshold {
+        builder = builder.module_size_warning_threshold(threshold);
+    }
+
+    builder = builder.check_peer_dependencies(args.common.check_peer_dependencies);
+
+    if let Some(store_dir) = &args.content_store {
+        let store_dir = current_dir()?.join(store_dir);
+        builder = builder.content_store(store_dir.to_string_lossy().to_string());
+    }
+
+    if let Some(store) = metrics_store {
+        builder = builder.record_metrics(store);
+    }
+
+    builder = builder
+        .forbid_source_maps(args.forbid_source_maps)
+        .build_manifest(args.build_manifest)
+        .plan(args.plan)
+        .prefetch_manifest(args.prefetch_manifest);
+
+    if let Some(path) = &args.service_worker {
+        builder = builder.service_worker(path.clone());
+    }
+
+    for glob in &args.unused_files {
+        builder = builder.unused_files(glob.clone());
+    }
+
+    for name in &args.feature_flags {
+        builder = builder.feature_flag(name.clone());
+    }
+
+    for enabled in &args.feature_flag_variants {
+        builder = builder.feature_flag_variant(enabled.clone());
+    }
+
     builder.build().await?;
 
+    if let Some(limit) = args.common.module_timings {
+        print_module_timings(limit);
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Builds `index.js` of `project_dir` to `dist` with the content store in
+    /// `store_dir`, and returns the assets of the content store manifest.
+    async fn build_to_content_store(
+        project_dir: &Path,
+        store_dir: &Path,
+    ) -> Result<BTreeMap<String, String>> {
+        crate::register();
+        turbopack_env::register();
+        turbopack_node::register();
+        let project = project_dir.to_string_lossy().to_string();
+        TurbopackBuildBuilder::new(
+            TurboTasks::new(MemoryBackend::default()),
+            project.clone(),
+            project,
+        )
+        .entry_request(EntryRequest::Relative("./index.js".to_string()))
+        .log_level(IssueSeverity::Error)
+        .content_store(store_dir.to_string_lossy().to_string())
+        .build()
+        .await?;
+
+        let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+            project_dir.join("dist/content-store.json"),
+        )?)?;
+        Ok(serde_json::from_value(manifest["assets"].clone())?)
+    }
+
+    /// The number of blobs in the content store in `store_dir`.
+    fn blob_count(store_dir: &Path) -> Result<usize> {
+        let mut count = 0;
+        for dir in fs::read_dir(store_dir)? {
+            count += fs::read_dir(dir?.path())?.count();
+        }
+        Ok(count)
+    }
+
+    #[tokio::test]
+    async fn writes_assets_to_the_content_store() -> Result<()> {
+        let project = tempfile::tempdir()?;
+        let store = tempfile::tempdir()?;
+        fs::write(project.path().join("index.js"), "console.log('index');\n")?;
+
+        let assets = build_to_content_store(project.path(), store.path()).await?;
+        assert!(
+            assets.keys().any(|path| path.ends_with(".js")),
+            "{assets:?}"
+        );
+        for (path, blob) in &assets {
+            // The assets are only written to the store, which names them by
+            // the hash of their content.
+            assert!(!project.path().join("dist").join(path).exists(), "{path}");
+            assert!(store.path().join(blob).is_file(), "{blob}");
+            let (dir, name) = blob.split_once('/').unwrap();
+            assert!(name.starts_with(dir), "{blob}");
+            assert_eq!(
+                Path::new(name).extension(),
+                Path::new(path).extension(),
+                "{blob}"
+            );
+        }
+        assert_eq!(blob_count(store.path())?, assets.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn shares_blobs_between_builds() -> Result<()> {
+        let first = tempfile::tempdir()?;
+        let second = tempfile::tempdir()?;
+        let store = tempfile::tempdir()?;
+        for project in [&first, &second] {
+            fs::write(project.path().join("index.js"), "console.log('index');\n")?;
+        }
+
+        let first_assets = build_to_content_store(first.path(), store.path()).await?;
+        // Blobs that are already in the store aren't written again.
+        let (_, blob) = first_assets.iter().next().unwrap();
+        fs::write(store.path().join(blob), "kept")?;
+
+        let second_assets = build_to_content_store(second.path(), store.path()).await?;
+        assert_eq!(second_assets, first_assets);
+        assert_eq!(blob_count(store.path())?, first_assets.len());
+        assert_eq!(fs::read_to_string(store.path().join(blob))?, "kept");
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! Maintenance of the persistent cache in `.turbopack/cache`, see
+//! [turbo_tasks_cache].
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use turbo_tasks::util::FormatBytes;
+use turbo_tasks_cache::{CacheStats, CacheStore, PrunePolicy};
+
+use crate::arguments::CacheArguments;
+
+/// The directory of the persistent cache of the project in `project_dir`.
+pub fn cache_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(".turbopack").join("cache")
+}
+
+impl CacheArguments {
+    /// The limits to prune the cache by, or `None` if none were given.
+    pub fn prune_policy(&self) -> Option<PrunePolicy> {
+        let policy = PrunePolicy {
+            max_size: self.max_size.map(|megabytes| megabytes * 1024 * 1024),
+            max_age: self
+                .max_age
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+            max_unused_builds: self.max_unused_builds,
+        };
+        (policy != PrunePolicy::default()).then_some(policy)
+    }
+}
+
+/// Prints the statistics of the persistent cache, after pruning it if any
+/// limits are given.
+pub fn run(args: &CacheArguments) -> Result<()> {
+    let project_dir = match &args.dir {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir()?,
+    };
+    let store = CacheStore::open(cache_dir(&project_dir))?;
+    if let Some(policy) = args.prune_policy() {
+        let result = store.prune(&policy)?;
+        println!(
+            "{} - removed {} blobs and freed {}",
+            "event".purple(),
+            result.removed_blobs,
+            FormatBytes(result.freed_bytes as usize)
+        );
+    }
+    print_stats(store.dir(), &store.stats()?);
+    Ok(())
+}
+
+fn print_stats(dir: &Path, stats: &CacheStats) {
+    println!(
+        "{} - {} blobs with {} ({} uncompressed) in {} pack files in {}",
+        "event".purple(),
+        stats.blobs,
+        FormatBytes(stats.size as usize),
+        FormatBytes(stats.uncompressed_size as usize),
+        stats.pack_files,
+        dir.display()
+    );
+    println!(
+        "{} - {} on disk after {} builds",
+        "event".purple(),
+        FormatBytes(stats.disk_size as usize),
+        stats.build.saturating_sub(1)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::arguments::Arguments;
+
+    fn cache_arguments(args: &[&str]) -> CacheArguments {
+        match Arguments::parse_from(["turbopack", "cache"].iter().chain(args)) {
+            Arguments::Cache(args) => args,
+            args => panic!("unexpected arguments {:?}", args),
+        }
+    }
+
+    #[test]
+    fn prune_policy() {
+        assert_eq!(cache_arguments(&[]).prune_policy(), None);
+        assert_eq!(
+            cache_arguments(&["--max-size", "2", "--max-age", "1"]).prune_policy(),
+            Some(PrunePolicy {
+                max_size: Some(2 * 1024 * 1024),
+                max_age: Some(Duration::from_secs(24 * 60 * 60)),
+                max_unused_builds: None,
+            })
+        );
+        assert_eq!(
+            cache_arguments(&["--max-unused-builds", "3"]).prune_policy(),
+            Some(PrunePolicy {
+                max_unused_builds: Some(3),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn prunes_the_cache_of_the_project() {
+        let project_dir = tempfile::tempdir().unwrap();
+        let store = CacheStore::open(cache_dir(project_dir.path())).unwrap();
+        let key = store.put_content(b"blob").unwrap();
+        store.flush().unwrap();
+        drop(store);
+
+        let dir = project_dir.path().to_string_lossy().to_string();
+        run(&cache_arguments(&["--dir", &dir])).unwrap();
+        let store = CacheStore::open(cache_dir(project_dir.path())).unwrap();
+        assert!(store.contains(&key));
+        drop(store);
+
+        run(&cache_arguments(&["--dir", &dir, "--max-size", "0"])).unwrap();
+        let store = CacheStore::open(cache_dir(project_dir.path())).unwrap();
+        assert!(!store.contains(&key));
+    }
+}
@@ -0,0 +1,386 @@
+//! Benchmarks of chunking on synthetic module graphs.
+//!
+//! A [SyntheticGraph] of plain Ecmascript modules is written to a temporary
+//! directory and built in phases, which are timed separately:
+//!
+//! 1. the module graph is parsed and analyzed,
+//! 2. the chunk group of the entry is computed with `chunk_group`,
+//! 3. the output assets referenced by the chunk group are walked, which
+//!    computes the chunk groups of dynamic imports with the availability info
+//!    of their parents.
+//!
+//! Every phase only measures the work that the previous phases didn't already
+//! do. Memory growth is only measured when [TurboMalloc] is the global
+//! allocator.
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Write as _},
+    fs,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, TurboTasks, Value, Vc};
+use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+use turbo_tasks_malloc::TurboMalloc;
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::evaluate_context::node_build_environment;
+use turbopack_core::{
+    chunk::{ChunkableModule, ChunkingContext, ChunkingContextExt},
+    context::AssetContext,
+    file_source::FileSource,
+    module::Module,
+    output::OutputAssets,
+    reference::primary_referenced_modules,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+};
+use turbopack_dev::DevChunkingContext;
+use turbopack_env::dotenv::load_env;
+use turbopack_node::execution_context::ExecutionContext;
+
+use crate::contexts::{get_client_asset_context, get_client_compile_time_info, NodeEnv};
+
+/// A module graph of `module_count` modules. Every module statically imports
+/// `imports_per_module` modules that are further down in the graph, so modules
+/// are shared between several importers.
+#[derive(Debug, Clone)]
+pub struct SyntheticGraph {
+    pub module_count: usize,
+    pub imports_per_module: usize,
+    /// Every n-th module is imported dynamically instead, which places it into
+    /// a chunk group of its own.
+    pub dynamic_import_interval: Option<usize>,
+}
+
+impl SyntheticGraph {
+    pub fn new(module_count: usize) -> SyntheticGraph {
+        SyntheticGraph {
+            module_count,
+            imports_per_module: 2,
+            dynamic_import_interval: None,
+        }
+    }
+
+    pub fn dynamic_import_interval(mut self, interval: usize) -> SyntheticGraph {
+        self.dynamic_import_interval = Some(interval);
+        self
+    }
+
+    /// The modules imported by module `index`, and whether they are imported
+    /// dynamically.
+    fn imports(&self, index: usize) -> impl Iterator<Item = (usize, bool)> + '_ {
+        let first = index * self.imports_per_module + 1;
+        // An additional import of a module in another subtree, so that modules
+        // are shared between chunk groups.
+        let shared = (index * 31 + 7) % self.module_count.max(1);
+        (first..first + self.imports_per_module)
+            .chain((shared > index).then_some(shared))
+            .filter(|&import| import < self.module_count)
+            .map(|import| {
+                let dynamic = self
+                    .dynamic_import_interval
+                    .map_or(false, |interval| interval > 0 && import % interval == 0);
+                (import, dynamic)
+            })
+    }
+
+    /// Writes the modules to `dir`. The entry is `src/m0.js`.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        let src = dir.join("src");
+        fs::create_dir_all(&src).context("creating src dir")?;
+        for index in 0..self.module_count {
+            let mut code = String::new();
+            let mut values = vec![index.to_string()];
+            for (import, dynamic) in self.imports(index) {
+                if dynamic {
+                    writeln!(
+                        code,
+                        "export const load{import} = () => import(\"./m{import}.js\");"
+                    )?;
+                } else {
+                    writeln!(
+                        code,
+                        "import {{ value as v{import} }} from \"./m{import}.js\";"
+                    )?;
+                    values.push(format!("v{import}"));
+                }
+            }
+            writeln!(code, "export const value = {};", values.join(" + "))?;
+            fs::write(src.join(format!("m{index}.js")), code)
+                .with_context(|| format!("writing module {index}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// The time a phase took and how much memory it retained.
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseReport {
+    pub duration: Duration,
+    pub memory_growth: usize,
+}
+
+impl Display for PhaseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} (+{} KiB)",
+            self.duration,
+            self.memory_growth / 1024
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChunkingBenchmarkReport {
+    pub module_count: usize,
+    pub output_asset_count: usize,
+    pub module_graph: PhaseReport,
+    pub chunk_group: PhaseReport,
+    pub output_assets: PhaseReport,
+}
+
+impl Display for ChunkingBenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "modules: {}", self.module_count)?;
+        writeln!(f, "output assets: {}", self.output_asset_count)?;
+        writeln!(f, "module graph: {}", self.module_graph)?;
+        writeln!(f, "chunk group: {}", self.chunk_group)?;
+        write!(f, "output assets: {}", self.output_assets)
+    }
+}
+
+/// Writes `graph` to a temporary directory and measures the phases of
+/// chunking it with a fresh turbo tasks instance.
+pub async fn run_chunking_benchmark(graph: &SyntheticGraph) -> Result<ChunkingBenchmarkReport> {
+    let dir = tempfile::tempdir().context("creating tempdir")?;
+    graph.write(dir.path())?;
+    let project_dir = dir
+        .path()
+        .to_str()
+        .context("tempdir contains invalid characters")?
+        .to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    let (module_count, module_graph) = measure(&tt, {
+        let project_dir = project_dir.clone();
+        async move { Ok(*module_graph_size(project_dir).await?) }
+    })
+    .await?;
+    let ((), chunk_group) = measure(&tt, {
+        let project_dir = project_dir.clone();
+        async move {
+            chunk_group_assets(project_dir).await?;
+            Ok(())
+        }
+    })
+    .await?;
+    let (output_asset_count, output_assets) =
+        measure(
+            &tt,
+            async move { Ok(*output_asset_count(project_dir).await?) },
+        )
+        .await?;
+
+    Ok(ChunkingBenchmarkReport {
+        module_count,
+        output_asset_count,
+        module_graph,
+        chunk_group,
+        output_assets,
+    })
+}
+
+async fn measure<T: TraceRawVcs + Send + 'static>(
+    tt: &Arc<TurboTasks<MemoryBackend>>,
+    future: impl std::future::Future<Output = Result<T>> + Send + 'static,
+) -> Result<(T, PhaseReport)> {
+    let memory = TurboMalloc::memory_usage();
+    let start = Instant::now();
+    let result = tt.run_once(future).await?;
+    let duration = start.elapsed();
+    Ok((
+        result,
+        PhaseReport {
+            duration,
+            memory_growth: TurboMalloc::memory_usage().saturating_sub(memory),
+        },
+    ))
+}
+
+fn project_path(project_dir: String) -> Vc<FileSystemPath> {
+    DiskFileSystem::new("project".to_string(), project_dir).root()
+}
+
+#[turbo_tasks::function]
+fn entry_module(project_dir: String) -> Vc<Box<dyn Module>> {
+    let project_path = project_path(project_dir);
+    asset_context(project_path)
+        .process(
+            Vc::upcast(FileSource::new(project_path.join("src/m0.js".to_string()))),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        )
+        .module()
+}
+
+fn asset_context(project_path: Vc<FileSystemPath>) -> Vc<Box<dyn AssetContext>> {
+    let build_output_root = project_path.join(".turbopack/build".to_string());
+    let build_chunking_context = DevChunkingContext::builder(
+        project_path,
+        build_output_root,
+        build_output_root.join("chunks".to_string()),
+        build_output_root.join("assets".to_string()),
+        node_build_environment(),
+    )
+    .build();
+    let execution_context = ExecutionContext::new(
+        project_path,
+        Vc::upcast(build_chunking_context),
+        load_env(project_path),
+    );
+    let node_env = NodeEnv::Development.cell();
+    let compile_time_info =
+        get_client_compile_time_info("last 1 Chrome versions".to_string(), node_env);
+    get_client_asset_context(project_path, execution_context, compile_time_info, node_env)
+}
+
+#[turbo_tasks::function]
+fn chunking_context(project_dir: String) -> Vc<Box<dyn ChunkingContext>> {
+    let project_path = project_path(project_dir);
+    let output_root = project_path.join(".turbopack/bench".to_string());
+    Vc::upcast(
+        DevChunkingContext::builder(
+            project_path,
+            output_root,
+            output_root.join("chunks".to_string()),
+            output_root.join("assets".to_string()),
+            get_client_compile_time_info(
+                "last 1 Chrome versions".to_string(),
+                NodeEnv::Development.cell(),
+            )
+            .environment(),
+        )
+        .build(),
+    )
+}
+
+/// Parses and analyzes every module of the graph and returns their count.
+#[turbo_tasks::function]
+async fn module_graph_size(project_dir: String) -> Result<Vc<usize>> {
+    let mut seen = HashSet::new();
+    let mut frontier = vec![entry_module(project_dir).resolve().await?];
+    // Modules are processed level by level, so modules of the same level are
+    // analyzed in parallel.
+    while !frontier.is_empty() {
+        seen.extend(frontier.iter().copied());
+        let references = frontier
+            .iter()
+            .map(|&module| async move {
+                primary_referenced_modules(module)
+                    .await?
+                    .iter()
+                    .map(|&reference| reference.resolve())
+                    .try_join()
+                    .await
+            })
+            .try_join()
+            .await?;
+        frontier = references
+            .into_iter()
+            .flatten()
+            .filter(|module| !seen.contains(module))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+    }
+    Ok(Vc::cell(seen.len()))
+}
+
+#[turbo_tasks::function]
+async fn chunk_group_assets(project_dir: String) -> Result<Vc<OutputAssets>> {
+    let module = entry_module(project_dir.clone());
+    let chunkable = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module)
+        .await?
+        .context("the entry can't be placed into chunks")?;
+    Ok(chunking_context(project_dir).root_chunk_group_assets(chunkable))
+}
+
+/// Walks the output assets of the chunk group, transitively, and returns their
+/// count.
+#[turbo_tasks::function]
+async fn output_asset_count(project_dir: String) -> Result<Vc<usize>> {
+    let mut seen = HashSet::new();
+    let mut queue = chunk_group_assets(project_dir)
+        .await?
+        .iter()
+        .copied()
+        .collect::<Vec<_>>();
+    while let Some(asset) = queue.pop() {
+        if !seen.insert(asset.resolve().await?) {
+            continue;
+        }
+        queue.extend(asset.references().await?.iter().copied());
+    }
+    Ok(Vc::cell(seen.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_modules_further_down_the_graph() {
+        let graph = SyntheticGraph::new(10).dynamic_import_interval(3);
+        assert_eq!(
+            graph.imports(0).collect::<Vec<_>>(),
+            vec![(1, false), (2, false), (7, false)]
+        );
+        assert_eq!(
+            graph.imports(1).collect::<Vec<_>>(),
+            vec![(3, true), (4, false), (8, false)]
+        );
+        for index in 0..10 {
+            for (import, _) in graph.imports(index) {
+                assert!(index < import && import < 10, "{index} imports {import}");
+            }
+        }
+    }
+
+    #[test]
+    fn writes_a_module_per_node() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        SyntheticGraph::new(10)
+            .dynamic_import_interval(3)
+            .write(dir.path())?;
+
+        assert_eq!(fs::read_dir(dir.path().join("src"))?.count(), 10);
+        let code = fs::read_to_string(dir.path().join("src/m1.js"))?;
+        assert!(code.contains(r#"import("./m3.js")"#), "{code}");
+        assert!(
+            code.contains(r#"import { value as v4 } from "./m4.js";"#),
+            "{code}"
+        );
+        assert!(code.contains("export const value = 1 + v4 + v8;"), "{code}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reports_the_size_of_the_output() -> Result<()> {
+        crate::register();
+        turbopack_env::register();
+        turbopack_node::register();
+
+        let report =
+            run_chunking_benchmark(&SyntheticGraph::new(20).dynamic_import_interval(5)).await?;
+
+        assert_eq!(report.module_count, 20);
+        // Dynamic imports have chunk groups of their own.
+        assert!(report.output_asset_count > 2, "{report}");
+        assert!(report.to_string().starts_with("modules: 20\n"), "{report}");
+        Ok(())
+    }
+}
@@ -1,12 +1,20 @@
 use std::{collections::HashMap, fmt};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use turbo_tasks::{Value, Vc};
-use turbo_tasks_fs::{FileSystem, FileSystemPath};
+use turbo_tasks_fs::{FileJsonContent, FileSystem, FileSystemEntryType, FileSystemPath};
 use turbopack::{
     condition::ContextCondition,
+    design_tokens::insert_design_tokens_aliases,
     ecmascript::TreeShakingMode,
-    module_options::{CustomEcmascriptTransformPlugins, JsxTransformOptions, ModuleOptionsContext},
+    federation::{
+        federation_remote_rule, federation_shared_rule, insert_federation_remote_aliases,
+        insert_federation_shared_aliases, read_federation_config, OptionFederationConfig,
+    },
+    module_options::{
+        CustomEcmascriptTransformPlugins, JsxTransformOptions, ModuleOptionsContext,
+        NoBundleOptions,
+    },
     resolve_options_context::ResolveOptionsContext,
     ModuleAssetContext,
 };
@@ -19,6 +27,7 @@ use turbopack_core::{
 };
 use turbopack_dev::react_refresh::assert_can_resolve_react_refresh;
 use turbopack_ecmascript_plugins::transform::{
+    console_location::ConsoleLocationTransformer,
     emotion::{EmotionTransformConfig, EmotionTransformer},
     styled_components::{StyledComponentsTransformConfig, StyledComponentsTransformer},
     styled_jsx::StyledJsxTransformer,
@@ -46,6 +55,35 @@ async fn foreign_code_context_condition() -> Result<ContextCondition> {
     Ok(ContextCondition::InDirectory("node_modules".to_string()))
 }
 
+/// The module federation configuration of the project, which is read from
+/// `federation.json`.
+#[turbo_tasks::function]
+pub fn get_federation_config(project_path: Vc<FileSystemPath>) -> Vc<OptionFederationConfig> {
+    read_federation_config(project_path.join("federation.json".to_string()))
+}
+
+/// Modules matching the globs listed in `no-bundle.json`, e.g.
+/// `["vendor/**", "sdk.js"]`, are emitted as standalone files instead of being
+/// bundled.
+async fn get_no_bundle_options(
+    project_path: Vc<FileSystemPath>,
+) -> Result<Option<Vc<NoBundleOptions>>> {
+    let path = project_path.join("no-bundle.json".to_string());
+    let rules = match &*path.read_json().await? {
+        FileJsonContent::Content(content) => serde_json::from_value(content.clone())
+            .context("no-bundle.json must contain an array of globs")?,
+        FileJsonContent::NotFound => return Ok(None),
+        FileJsonContent::Unparseable(e) => bail!("no-bundle.json is not valid JSON: {}", e),
+    };
+    Ok(Some(
+        NoBundleOptions {
+            base: project_path,
+            rules,
+        }
+        .cell(),
+    ))
+}
+
 #[turbo_tasks::function]
 pub async fn get_client_import_map(project_path: Vc<FileSystemPath>) -> Result<Vc<ImportMap>> {
     let mut import_map = ImportMap::empty();
@@ -64,6 +102,21 @@ pub async fn get_client_import_map(project_path: Vc<FileSystemPath>) -> Result<V
         .cell(),
     );
 
+    // Design tokens declared in `design-tokens.json` (or `design-tokens.ts`)
+    // are importable as `design-tokens` and `design-tokens.css`.
+    for file_name in ["design-tokens.json", "design-tokens.ts"] {
+        let design_tokens = project_path.join(file_name.to_string());
+        if matches!(&*design_tokens.get_type().await?, FileSystemEntryType::File) {
+            insert_design_tokens_aliases(&mut import_map, design_tokens);
+            break;
+        }
+    }
+
+    if let Some(federation) = &*get_federation_config(project_path).await? {
+        insert_federation_remote_aliases(&mut import_map, project_path, &federation.remotes());
+        insert_federation_shared_aliases(&mut import_map, &federation.shared);
+    }
+
     Ok(import_map.cell())
 }
 
@@ -99,10 +152,20 @@ async fn get_client_module_options_context(
     env: Vc<Environment>,
     node_env: Vc<NodeEnv>,
 ) -> Result<Vc<ModuleOptionsContext>> {
+    // Shared modules are also requested from `node_modules`, e.g. `react` by
+    // `react-dom`.
+    let custom_rules = if get_federation_config(project_path).await?.is_some() {
+        vec![federation_remote_rule(), federation_shared_rule()]
+    } else {
+        vec![]
+    };
+
     let module_options_context = ModuleOptionsContext {
         preset_env_versions: Some(env),
         execution_context: Some(execution_context),
         tree_shaking_mode: Some(TreeShakingMode::ReexportsOnly),
+        custom_rules,
+        no_bundle: get_no_bundle_options(project_path).await?,
         ..Default::default()
     };
 
@@ -121,20 +184,29 @@ async fn get_client_module_options_context(
         .cell(),
     );
 
+    let mut source_transforms = Vec::new();
+    if matches!(*node_env.await?, NodeEnv::Development) {
+        // Needs to run first, so that locations refer to the original source.
+        source_transforms.push(Vc::cell(
+            Box::new(ConsoleLocationTransformer::default()) as _
+        ));
+    }
+    source_transforms.extend([
+        Vc::cell(Box::new(
+            EmotionTransformer::new(&EmotionTransformConfig::default())
+                .expect("Should be able to create emotion transformer"),
+        ) as _),
+        Vc::cell(Box::new(StyledComponentsTransformer::new(
+            &StyledComponentsTransformConfig::default(),
+        )) as _),
+        Vc::cell(Box::new(StyledJsxTransformer::new(
+            module_options_context.use_lightningcss,
+        )) as _),
+    ]);
+
     let custom_ecma_transform_plugins = Some(CustomEcmascriptTransformPlugins::cell(
         CustomEcmascriptTransformPlugins {
-            source_transforms: vec![
-                Vc::cell(Box::new(
-                    EmotionTransformer::new(&EmotionTransformConfig::default())
-                        .expect("Should be able to create emotion transformer"),
-                ) as _),
-                Vc::cell(Box::new(StyledComponentsTransformer::new(
-                    &StyledComponentsTransformConfig::default(),
-                )) as _),
-                Vc::cell(Box::new(StyledJsxTransformer::new(
-                    module_options_context.use_lightningcss,
-                )) as _),
-            ],
+            source_transforms,
             output_transforms: vec![],
         },
     ));
@@ -181,6 +253,40 @@ pub fn get_client_asset_context(
     asset_context
 }
 
+/// Like [get_client_asset_context], but keeps the types of TypeScript modules
+/// instead of removing them, so imports of types are references as well, e.g.
+/// to find files that no entry imports.
+#[turbo_tasks::function]
+pub async fn get_client_types_asset_context(
+    project_path: Vc<FileSystemPath>,
+    execution_context: Vc<ExecutionContext>,
+    compile_time_info: Vc<CompileTimeInfo>,
+    node_env: Vc<NodeEnv>,
+) -> Result<Vc<Box<dyn AssetContext>>> {
+    let resolve_options_context = get_client_resolve_options_context(project_path);
+    let module_options_context = get_client_module_options_context(
+        project_path,
+        execution_context,
+        compile_time_info.environment(),
+        node_env,
+    )
+    .await?;
+    let module_options_context = ModuleOptionsContext {
+        enable_types: true,
+        enable_typescript_transform: None,
+        ..(*module_options_context).clone()
+    }
+    .cell();
+
+    Ok(Vc::upcast(ModuleAssetContext::new(
+        Vc::cell(HashMap::new()),
+        compile_time_info,
+        module_options_context,
+        resolve_options_context,
+        Vc::cell("client types".to_string()),
+    )))
+}
+
 fn client_defines(node_env: &NodeEnv) -> Vc<CompileTimeDefines> {
     compile_time_defines!(
         process.turbopack = true,
@@ -3,31 +3,43 @@ use std::{
     env::current_dir,
     future::{join, Future},
     io::{stdout, Write},
-    net::{IpAddr, SocketAddr},
-    path::{PathBuf, MAIN_SEPARATOR},
+    net::{IpAddr, SocketAddr, TcpListener},
+    path::{Path, PathBuf, MAIN_SEPARATOR},
     sync::Arc,
     time::{Duration, Instant},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use owo_colors::OwoColorize;
 use turbo_tasks::{
+    run_once,
     util::{FormatBytes, FormatDuration},
-    StatsType, TransientInstance, TurboTasks, TurboTasksBackendApi, UpdateInfo, Value, Vc,
+    Completion, ReadRef, StatsType, TransientInstance, TurboTasks, TurboTasksBackendApi,
+    UpdateInfo, Value, Vc,
+};
+use turbo_tasks_fs::{
+    recording::{read_recording, InvalidationRecorder, RecordedEvent},
+    DiskFileSystem, FileSystem,
 };
-use turbo_tasks_fs::FileSystem;
 use turbo_tasks_malloc::TurboMalloc;
-use turbo_tasks_memory::MemoryBackend;
-use turbopack::evaluate_context::node_build_environment;
+use turbo_tasks_memory::{MemoryBackend, TaskCacheStats};
+use turbopack::{emit_asset, evaluate_context::node_build_environment};
 use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
 use turbopack_core::{
+    chunk::{
+        usage_profile::UsageProfile,
+        warm_up::{warm_up, WarmUpEntry},
+        ChunkingContext,
+    },
     environment::ServerAddr,
+    feature_flags::FeatureFlags,
     issue::{IssueReporter, IssueSeverity},
     resolve::parse::Request,
     server_fs::ServerFileSystem,
 };
-use turbopack_dev::DevChunkingContext;
+use turbopack_dev::{records::ChunkingRecordsAsset, DevChunkingContext};
 use turbopack_dev_server::{
+    html::DevHtmlAsset,
     introspect::IntrospectionSource,
     source::{
         combined::CombinedContentSource, router::PrefixedRouterContentSource,
@@ -42,8 +54,11 @@ use self::web_entry_source::create_web_entry_source;
 use crate::{
     arguments::DevArguments,
     contexts::NodeEnv,
+    diagnostics::install_panic_diagnostics,
+    metrics::{BuildKind, BuildMetrics, BuildMetricsStore},
     util::{
-        normalize_dirs, normalize_entries, output_fs, project_fs, EntryRequest, NormalizedDirs,
+        normalize_dirs, normalize_entries, output_fs, print_module_timings, project_fs,
+        selected_feature_flag_variants, EntryRequest, NormalizedDirs,
     },
 };
 
@@ -56,14 +71,28 @@ pub struct TurbopackDevServerBuilder {
     root_dir: String,
     entry_requests: Vec<EntryRequest>,
     eager_compile: bool,
+    warm_up: bool,
     hostname: Option<IpAddr>,
     issue_reporter: Option<Box<dyn IssueReporterProvider>>,
     port: Option<u16>,
+    listener: Option<TcpListener>,
+    #[cfg(unix)]
+    update_socket: Option<PathBuf>,
     browserslist_query: String,
     log_level: IssueSeverity,
     show_all: bool,
     log_detail: bool,
     allow_retry: bool,
+    usage_profile: Option<Vec<String>>,
+    static_chunk_groups: bool,
+    isolate_vendor_chunks: bool,
+    split_node_modules: bool,
+    module_size_warning_threshold: Option<u64>,
+    check_peer_dependencies: bool,
+    feature_flags: Vec<String>,
+    feature_flag_variants: Vec<String>,
+    records: Option<String>,
+    base_paths: Option<String>,
 }
 
 impl TurbopackDevServerBuilder {
@@ -78,9 +107,13 @@ impl TurbopackDevServerBuilder {
             root_dir,
             entry_requests: vec![],
             eager_compile: false,
+            warm_up: false,
             hostname: None,
             issue_reporter: None,
             port: None,
+            listener: None,
+            #[cfg(unix)]
+            update_socket: None,
             browserslist_query: "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
                                  versions, last 1 Edge versions"
                 .to_owned(),
@@ -88,6 +121,16 @@ impl TurbopackDevServerBuilder {
             show_all: false,
             log_detail: false,
             allow_retry: false,
+            usage_profile: None,
+            static_chunk_groups: false,
+            isolate_vendor_chunks: false,
+            split_node_modules: true,
+            module_size_warning_threshold: None,
+            check_peer_dependencies: false,
+            feature_flags: Vec::new(),
+            feature_flag_variants: Vec::new(),
+            records: None,
+            base_paths: None,
         }
     }
 
@@ -101,6 +144,12 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    /// Computes the chunk groups of the entries when the server starts.
+    pub fn warm_up(mut self, warm_up: bool) -> TurbopackDevServerBuilder {
+        self.warm_up = warm_up;
+        self
+    }
+
     pub fn hostname(mut self, hostname: IpAddr) -> TurbopackDevServerBuilder {
         self.hostname = Some(hostname);
         self
@@ -111,6 +160,20 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    /// Serves on an already bound listener, e.g. one inherited from a parent
+    /// process, instead of binding `hostname` and `port`.
+    pub fn listener(mut self, listener: TcpListener) -> TurbopackDevServerBuilder {
+        self.listener = Some(listener);
+        self
+    }
+
+    /// Also serves hot updates on a Unix domain socket at `path`.
+    #[cfg(unix)]
+    pub fn update_socket(mut self, path: PathBuf) -> TurbopackDevServerBuilder {
+        self.update_socket = Some(path);
+        self
+    }
+
     pub fn browserslist_query(mut self, browserslist_query: String) -> TurbopackDevServerBuilder {
         self.browserslist_query = browserslist_query;
         self
@@ -136,6 +199,78 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    /// Biases chunking towards the modules with `executed_modules` ids.
+    pub fn usage_profile(mut self, executed_modules: Vec<String>) -> TurbopackDevServerBuilder {
+        self.usage_profile = Some(executed_modules);
+        self
+    }
+
+    /// Emits no JavaScript for entries that only import CSS.
+    pub fn static_chunk_groups(mut self, static_chunk_groups: bool) -> TurbopackDevServerBuilder {
+        self.static_chunk_groups = static_chunk_groups;
+        self
+    }
+
+    /// Keeps vendor chunks independent of app code.
+    pub fn isolate_vendor_chunks(
+        mut self,
+        isolate_vendor_chunks: bool,
+    ) -> TurbopackDevServerBuilder {
+        self.isolate_vendor_chunks = isolate_vendor_chunks;
+        self
+    }
+
+    /// Places modules in `node_modules` into separate chunks from app code.
+    /// Enabled by default.
+    pub fn split_node_modules(mut self, split_node_modules: bool) -> TurbopackDevServerBuilder {
+        self.split_node_modules = split_node_modules;
+        self
+    }
+
+    /// Reports modules whose generated code exceeds `threshold` bytes.
+    pub fn module_size_warning_threshold(mut self, threshold: u64) -> TurbopackDevServerBuilder {
+        self.module_size_warning_threshold = Some(threshold);
+        self
+    }
+
+    /// Reports packages that are bundled with a version of one of their peer
+    /// dependencies outside of the declared range.
+    pub fn check_peer_dependencies(
+        mut self,
+        check_peer_dependencies: bool,
+    ) -> TurbopackDevServerBuilder {
+        self.check_peer_dependencies = check_peer_dependencies;
+        self
+    }
+
+    /// Declares a feature flag, which is disabled in the default variant.
+    pub fn feature_flag(mut self, name: String) -> TurbopackDevServerBuilder {
+        self.feature_flags.push(name);
+        self
+    }
+
+    /// Also serves the variant with the `enabled` flags (joined by `+`) at
+    /// `/__flags/<enabled>/`, or every variant with `all`.
+    pub fn feature_flag_variant(mut self, enabled: String) -> TurbopackDevServerBuilder {
+        self.feature_flag_variants.push(enabled);
+        self
+    }
+
+    /// Pins module ids and chunk names to those recorded in the file at
+    /// `path`, relative to the project directory, and keeps the file up to
+    /// date with the output. This compiles the whole app eagerly.
+    pub fn records(mut self, path: String) -> TurbopackDevServerBuilder {
+        self.records = Some(path);
+        self
+    }
+
+    /// Loads chunks and static assets from the base paths of the request's
+    /// host in the JSON file at `path`, relative to the project directory.
+    pub fn base_paths(mut self, path: String) -> TurbopackDevServerBuilder {
+        self.base_paths = Some(path);
+        self
+    }
+
     pub fn issue_reporter(
         mut self,
         issue_reporter: Box<dyn IssueReporterProvider>,
@@ -184,19 +319,39 @@ impl TurbopackDevServerBuilder {
         }
     }
 
-    pub async fn build(self) -> Result<DevServer> {
-        let port = self.port.context("port must be set")?;
-        let host = self.hostname.context("hostname must be set")?;
-
-        let server = self.find_port(host, port, 10)?;
+    pub async fn build(mut self) -> Result<DevServer> {
+        let server = match self.listener.take() {
+            Some(listener) => DevServer::from_listener(listener)?,
+            None => {
+                let port = self.port.context("port must be set")?;
+                let host = self.hostname.context("hostname must be set")?;
+                self.find_port(host, port, 10)?
+            }
+        };
+        #[cfg(unix)]
+        let server = match self.update_socket {
+            Some(path) => server.update_socket(path),
+            None => server,
+        };
 
         let turbo_tasks = self.turbo_tasks;
         let project_dir = self.project_dir;
         let root_dir = self.root_dir;
         let eager_compile = self.eager_compile;
+        let warm_up = self.warm_up;
         let show_all = self.show_all;
         let log_detail = self.log_detail;
         let browserslist_query = self.browserslist_query;
+        let usage_profile = self.usage_profile;
+        let static_chunk_groups = self.static_chunk_groups;
+        let isolate_vendor_chunks = self.isolate_vendor_chunks;
+        let split_node_modules = self.split_node_modules;
+        let module_size_warning_threshold = self.module_size_warning_threshold;
+        let check_peer_dependencies = self.check_peer_dependencies;
+        let feature_flags = self.feature_flags;
+        let feature_flag_variants = self.feature_flag_variants;
+        let records = self.records;
+        let base_paths = self.base_paths;
         let log_args = Arc::new(LogOptions {
             current_dir: current_dir().unwrap(),
             project_dir: PathBuf::from(project_dir.clone()),
@@ -211,31 +366,129 @@ impl TurbopackDevServerBuilder {
             Box::new(move || Vc::upcast(ConsoleUi::new(log_args.clone().into())))
         });
 
-        let source = move || {
-            source(
+        let write_records = records.is_some();
+        let dev_source = move || {
+            dev_source(
                 root_dir.clone(),
                 project_dir.clone(),
                 entry_requests.clone().into(),
                 eager_compile,
                 turbo_tasks.clone().into(),
                 browserslist_query.clone(),
+                usage_profile.clone(),
+                static_chunk_groups,
+                isolate_vendor_chunks,
+                split_node_modules,
+                module_size_warning_threshold,
+                check_peer_dependencies,
+                feature_flags.clone(),
+                feature_flag_variants.clone(),
+                records.clone(),
+                base_paths.clone(),
             )
         };
+        let source = {
+            let dev_source = dev_source.clone();
+            move || source(dev_source())
+        };
+        if warm_up {
+            let dev_source = dev_source.clone();
+            tasks.spawn_once_task(async move {
+                if let Err(err) = warm_up_pages(dev_source()).await {
+                    println!("{} - {:?}", "error".red(), err);
+                }
+                Ok(Vc::<()>::default())
+            });
+        }
+        if write_records {
+            tasks.spawn_root_task(move || {
+                let dev_source = dev_source.clone();
+                Box::pin(async move {
+                    emit_records(dev_source()).await?;
+                    Ok(Vc::<()>::default())
+                })
+            });
+        }
 
         let issue_reporter_arc = Arc::new(move || issue_provider.get_issue_reporter());
         Ok(server.serve(tasks, source, issue_reporter_arc))
     }
 }
 
+/// The content source of the dev server, its HTML pages, and the records of
+/// the chunking of the web entry when they are written.
+#[turbo_tasks::value(shared)]
+struct DevSource {
+    source: Vc<Box<dyn ContentSource>>,
+    pages: Vec<Vc<DevHtmlAsset>>,
+    records: Option<Vc<ChunkingRecordsAsset>>,
+}
+
+#[turbo_tasks::function]
+async fn source(dev_source: Vc<DevSource>) -> Result<Vc<Box<dyn ContentSource>>> {
+    Ok(dev_source.await?.source)
+}
+
+/// Computes the chunk groups of the entries of all pages, so that their first
+/// request doesn't have to wait for the analysis of their modules.
+async fn warm_up_pages(dev_source: Vc<DevSource>) -> Result<()> {
+    let mut entries: Vec<(Vc<Box<dyn ChunkingContext>>, Vec<WarmUpEntry>)> = Vec::new();
+    for &page in &dev_source.await?.pages {
+        for (chunking_context, entry) in page.warm_up_entries().await? {
+            let chunking_context = chunking_context.resolve().await?;
+            match entries
+                .iter_mut()
+                .find(|(context, _)| *context == chunking_context)
+            {
+                Some((_, entries)) => entries.push(entry),
+                None => entries.push((chunking_context, vec![entry])),
+            }
+        }
+    }
+    for (chunking_context, entries) in entries {
+        warm_up(chunking_context, entries, &|progress| {
+            println!(
+                "{} - warmed up {} ({}/{})",
+                "event".purple(),
+                progress.entry,
+                progress.completed,
+                progress.total
+            );
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+/// Writes the records of the web entry whenever the output changes, so that
+/// the next session pins the module ids and chunk names of this one.
 #[turbo_tasks::function]
-async fn source(
+async fn emit_records(dev_source: Vc<DevSource>) -> Result<Vc<Completion>> {
+    if let Some(records) = dev_source.await?.records {
+        emit_asset(Vc::upcast(records)).await?;
+    }
+    Ok(Completion::new())
+}
+
+#[turbo_tasks::function]
+async fn dev_source(
     root_dir: String,
     project_dir: String,
     entry_requests: TransientInstance<Vec<EntryRequest>>,
     eager_compile: bool,
     turbo_tasks: TransientInstance<TurboTasks<MemoryBackend>>,
     browserslist_query: String,
-) -> Result<Vc<Box<dyn ContentSource>>> {
+    usage_profile: Option<Vec<String>>,
+    static_chunk_groups: bool,
+    isolate_vendor_chunks: bool,
+    split_node_modules: bool,
+    module_size_warning_threshold: Option<u64>,
+    check_peer_dependencies: bool,
+    feature_flags: Vec<String>,
+    feature_flag_variants: Vec<String>,
+    records: Option<String>,
+    base_paths: Option<String>,
+) -> Result<Vc<DevSource>> {
     let project_relative = project_dir.strip_prefix(&root_dir).unwrap();
     let project_relative = project_relative
         .strip_prefix(MAIN_SEPARATOR)
@@ -275,16 +528,69 @@ async fn source(
         })
         .collect();
 
-    let web_source = create_web_entry_source(
-        project_path,
-        execution_context,
-        entry_requests,
-        server_root,
-        env,
-        eager_compile,
-        NodeEnv::Development.cell(),
-        browserslist_query,
-    );
+    let usage_profile = Vc::cell(usage_profile.map(UsageProfile::new));
+    let base_paths_path = base_paths.map(|base_paths| project_path.join(base_paths));
+    let web_source = |feature_flags, html_path, records_path| {
+        create_web_entry_source(
+            project_path,
+            execution_context,
+            entry_requests.clone(),
+            server_root,
+            env,
+            eager_compile,
+            NodeEnv::Development.cell(),
+            browserslist_query.clone(),
+            usage_profile,
+            static_chunk_groups,
+            isolate_vendor_chunks,
+            split_node_modules,
+            module_size_warning_threshold,
+            check_peer_dependencies,
+            feature_flags,
+            html_path,
+            records_path,
+            base_paths_path,
+        )
+    };
+    // Variants share the module ids of the default variant, so only its
+    // records are written.
+    let records_path = records.map(|records| project_path.join(records));
+    let (web_source, pages, records) = if feature_flags.is_empty() {
+        let web_source = web_source(
+            None,
+            server_root.join("index.html".to_string()),
+            records_path,
+        )
+        .await?;
+        (web_source.source, vec![web_source.html], web_source.records)
+    } else {
+        let feature_flags: Vc<FeatureFlags> = Vc::cell(
+            feature_flags
+                .into_iter()
+                .map(|name| (name, false))
+                .collect(),
+        );
+        let default_source = web_source(
+            Some(feature_flags.defaults()),
+            server_root.join("index.html".to_string()),
+            records_path,
+        )
+        .await?;
+        let mut sources = vec![default_source.source];
+        let mut pages = vec![default_source.html];
+        for variant in selected_feature_flag_variants(feature_flags, feature_flag_variants).await? {
+            let html_path =
+                server_root.join(format!("__flags/{}/index.html", variant.key().await?));
+            let variant_source = web_source(Some(variant), html_path, None).await?;
+            sources.push(variant_source.source);
+            pages.push(variant_source.html);
+        }
+        (
+            Vc::upcast(CombinedContentSource::new(sources)),
+            pages,
+            default_source.records,
+        )
+    };
     let viz = Vc::upcast(turbo_tasks_viz::TurboTasksSource::new(turbo_tasks.into()));
     let static_source = Vc::upcast(StaticAssetsContentSource::new(
         String::new(),
@@ -307,7 +613,12 @@ async fn source(
         main_source,
     ));
 
-    Ok(source)
+    Ok(DevSource {
+        source,
+        pages,
+        records,
+    }
+    .cell())
 }
 
 pub fn register() {
@@ -339,11 +650,24 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         false => StatsType::Essential,
     };
     tt.set_stats_type(stats_type);
+    install_panic_diagnostics(
+        &tt,
+        Path::new(&project_dir)
+            .join(".turbopack")
+            .join("diagnostics"),
+    );
+
+    let metrics_store = args
+        .common
+        .record_metrics
+        .then(|| BuildMetricsStore::open(Path::new(&project_dir)));
 
     let tt_clone = tt.clone();
+    let project_root_dir = root_dir.clone();
 
     let mut server = TurbopackDevServerBuilder::new(tt, project_dir, root_dir)
         .eager_compile(args.eager_compile)
+        .warm_up(args.warm_up)
         .hostname(args.hostname)
         .port(args.port)
         .log_detail(args.common.log_detail)
@@ -363,8 +687,72 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         server = server.allow_retry(args.allow_retry);
     }
 
+    if let Some(fd) = args.listen_fd {
+        server = server.listener(inherited_listener(fd)?);
+    }
+
+    #[cfg(unix)]
+    if let Some(path) = &args.hmr_socket {
+        server = server.update_socket(path.clone());
+    }
+
+    server = server
+        .static_chunk_groups(args.static_chunk_groups)
+        .isolate_vendor_chunks(args.isolate_vendor_chunks)
+        .split_node_modules(!args.no_split_node_modules);
+
+    if let Some(threshold) = args.common.module_size_warning_threshold {
+        server = server.module_size_warning_threshold(threshold);
+    }
+
+    server = server.check_peer_dependencies(args.common.check_peer_dependencies);
+
+    if let Some(path) = &args.records {
+        server = server.records(path.clone());
+    }
+
+    if let Some(path) = &args.base_paths {
+        server = server.base_paths(path.clone());
+    }
+
+    for name in &args.feature_flags {
+        server = server.feature_flag(name.clone());
+    }
+
+    for enabled in &args.feature_flag_variants {
+        server = server.feature_flag_variant(enabled.clone());
+    }
+
+    if let Some(path) = &args.usage_profile {
+        let profile = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read usage profile {}", path.display()))?;
+        server = server.usage_profile(UsageProfile::parse_executed_modules(&profile)?);
+    }
+
     let server = server.build().await?;
 
+    let recorder = match &args.record_invalidations {
+        Some(path) => {
+            let recorder = Arc::new(InvalidationRecorder::new(path)?);
+            project_disk_fs(&tt_clone, project_root_dir.clone())
+                .await?
+                .set_invalidation_recorder(Some(recorder.clone()));
+            Some(recorder)
+        }
+        None => None,
+    };
+    let replay_future = {
+        let tt = tt_clone.clone();
+        let replay_invalidations = args.replay_invalidations.clone();
+        async move {
+            if let Some(path) = replay_invalidations {
+                if let Err(err) = replay(&tt, project_root_dir, &path).await {
+                    println!("{} - {:?}", "error".red(), err);
+                }
+            }
+        }
+    };
+
     {
         let index_uri = ServerAddr::new(server.addr).to_string()?;
         println!(
@@ -389,6 +777,7 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         }
 
         let mut progress_counter = 0;
+        let mut cache_stats = TaskCacheStats::default();
         loop {
             let update_future = profile_timeout(
                 tt_clone.as_ref(),
@@ -403,6 +792,24 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
             }) = update_future.await
             {
                 progress_counter = 0;
+                if let Some(recorder) = &recorder {
+                    if let Err(err) = recorder.record_update(duration, tasks, reasons.to_string()) {
+                        println!("{} - {:?}", "error".red(), err);
+                    }
+                }
+                if let Some(store) = &metrics_store {
+                    let latest_cache_stats = tt_clone.backend().task_cache_stats();
+                    let metrics = BuildMetrics::new(
+                        BuildKind::DevUpdate,
+                        duration,
+                        tasks,
+                        latest_cache_stats.since(cache_stats),
+                    );
+                    cache_stats = latest_cache_stats;
+                    if let Err(err) = store.record(&metrics) {
+                        println!("{} - {:?}", "error".red(), err);
+                    }
+                }
                 match (args.common.log_detail, !reasons.is_empty()) {
                     (true, true) => {
                         println!(
@@ -440,6 +847,9 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
                         }
                     }
                 }
+                if let Some(limit) = args.common.module_timings {
+                    print_module_timings(limit);
+                }
             } else {
                 progress_counter += 1;
                 if args.common.log_detail {
@@ -459,8 +869,90 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         }
     };
 
-    join!(stats_future, async { server.future.await.unwrap() }).await;
+    join!(stats_future, replay_future, async {
+        server.future.await.unwrap()
+    })
+    .await;
+
+    Ok(())
+}
+
+/// Takes ownership of the listener with the file descriptor `fd`, which was
+/// bound and passed to us by a parent process.
+#[cfg(unix)]
+fn inherited_listener(fd: i32) -> Result<TcpListener> {
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: The parent process passed the descriptor for us to use, and
+    // nothing else in this process owns it.
+    let listener = unsafe { TcpListener::from_raw_fd(fd) };
+    listener
+        .local_addr()
+        .with_context(|| format!("file descriptor {fd} is not a bound TCP listener"))?;
+    Ok(listener)
+}
 
+#[cfg(not(unix))]
+fn inherited_listener(_fd: i32) -> Result<TcpListener> {
+    bail!("--listen-fd is only supported on unix")
+}
+
+/// Returns the file system of the project, as used by the dev server.
+async fn project_disk_fs(
+    tt: &Arc<TurboTasks<MemoryBackend>>,
+    root_dir: String,
+) -> Result<ReadRef<DiskFileSystem>> {
+    run_once(tt.clone(), async move {
+        let Some(disk_fs) =
+            Vc::try_resolve_downcast_type::<DiskFileSystem>(project_fs(root_dir)).await?
+        else {
+            bail!("the project file system is not a disk file system");
+        };
+        Ok(disk_fs.await?)
+    })
+    .await
+}
+
+/// Replays the file changes of a recording against the project, preserving
+/// their order and timing. Each batch of changes is only applied once all
+/// updates caused by the previous ones completed.
+async fn replay(tt: &Arc<TurboTasks<MemoryBackend>>, root_dir: String, path: &Path) -> Result<()> {
+    let events = read_recording(path)?;
+    let disk_fs = project_disk_fs(tt, root_dir).await?;
+    disk_fs.stop_watching();
+
+    let start = Instant::now();
+    for event in events {
+        let RecordedEvent::Changes {
+            fs,
+            elapsed_ms,
+            changes,
+        } = event
+        else {
+            continue;
+        };
+        if fs != disk_fs.name || changes.is_empty() {
+            continue;
+        }
+        if let Some(delay) = Duration::from_millis(elapsed_ms).checked_sub(start.elapsed()) {
+            tokio::time::sleep(delay).await;
+        }
+        while tt.get_in_progress_count() > 0 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        println!(
+            "{event_type} - replaying {count} changes ({first}, ...)",
+            event_type = "event".purple(),
+            count = changes.len(),
+            first = changes[0].path,
+        );
+        disk_fs.replay_changes(&changes).await?;
+    }
+    println!(
+        "{event_type} - replayed {path}",
+        event_type = "event".purple(),
+        path = path.display()
+    );
     Ok(())
 }
 
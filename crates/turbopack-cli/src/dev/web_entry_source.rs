@@ -1,51 +1,117 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use indexmap::IndexMap;
 use turbo_tasks::{TryJoinIterExt, Value, Vc};
 use turbo_tasks_env::ProcessEnv;
 use turbo_tasks_fs::FileSystemPath;
-use turbopack::ecmascript::EcmascriptModuleAsset;
+use turbopack::ecmascript::{federation::RemoteContainerModule, EcmascriptModuleAsset};
 use turbopack_cli_utils::runtime_entry::{RuntimeEntries, RuntimeEntry};
 use turbopack_core::{
-    chunk::{ChunkableModule, ChunkingContext},
+    chunk::{usage_profile::OptionUsageProfile, ChunkableModule, ChunkingContext},
     environment::Environment,
+    feature_flags::{compile_time_info_with_feature_flags, FeatureFlagSet},
     file_source::FileSource,
+    output::{OutputAssets, OutputAssetsSet},
     reference_type::{EntryReferenceSubType, ReferenceType},
     resolve::{
         origin::{PlainResolveOrigin, ResolveOriginExt},
         parse::Request,
     },
 };
-use turbopack_dev::{react_refresh::assert_can_resolve_react_refresh, DevChunkingContext};
+use turbopack_dev::{
+    federation::FederationManifestAsset, react_refresh::assert_can_resolve_react_refresh,
+    records::ChunkingRecordsAsset, BasePathMode, DevChunkingContext,
+};
 use turbopack_dev_server::{
     html::DevHtmlAsset,
-    source::{asset_graph::AssetGraphContentSource, ContentSource},
+    source::{
+        asset_graph::AssetGraphContentSource,
+        base_paths::{read_host_base_paths, BasePaths, BasePathsContentSource},
+        combined::CombinedContentSource,
+        ContentSource,
+    },
 };
 use turbopack_node::execution_context::ExecutionContext;
 
 use crate::{
     contexts::{
         get_client_asset_context, get_client_compile_time_info, get_client_resolve_options_context,
-        NodeEnv,
+        get_federation_config, NodeEnv,
     },
     embed_js::embed_file_path,
 };
 
+/// The path of the script setting the base paths of the request's host.
+const BASE_PATHS_SCRIPT: &str = "__turbopack_base_paths__.js";
+
+/// The content source serving a web entry, its HTML page, and the records of
+/// its chunking when a records path is given.
+#[turbo_tasks::value(shared)]
+pub struct WebEntrySource {
+    pub source: Vc<Box<dyn ContentSource>>,
+    pub html: Vc<DevHtmlAsset>,
+    pub records: Option<Vc<ChunkingRecordsAsset>>,
+}
+
 #[turbo_tasks::function]
-pub fn get_client_chunking_context(
+pub async fn get_client_chunking_context(
     project_path: Vc<FileSystemPath>,
     server_root: Vc<FileSystemPath>,
     environment: Vc<Environment>,
-) -> Vc<Box<dyn ChunkingContext>> {
-    Vc::upcast(
-        DevChunkingContext::builder(
-            project_path,
-            server_root,
-            server_root.join("/_chunks".to_string()),
-            server_root.join("/_assets".to_string()),
-            environment,
-        )
-        .hot_module_replacement()
-        .build(),
+    usage_profile: Vc<OptionUsageProfile>,
+    static_chunk_groups: bool,
+    isolate_vendor_chunks: bool,
+    split_node_modules: bool,
+    module_size_warning_threshold: Option<u64>,
+    check_peer_dependencies: bool,
+    feature_flags: Option<Vc<FeatureFlagSet>>,
+    records_path: Option<Vc<FileSystemPath>>,
+    runtime_base_paths: bool,
+) -> Result<Vc<DevChunkingContext>> {
+    let mut builder = DevChunkingContext::builder(
+        project_path,
+        server_root,
+        server_root.join("/_chunks".to_string()),
+        server_root.join("/_assets".to_string()),
+        environment,
     )
+    .hot_module_replacement()
+    .stub_failing_modules(true)
+    .isolate_vendor_chunks(isolate_vendor_chunks)
+    .split_node_modules(split_node_modules);
+    if let Some(threshold) = module_size_warning_threshold {
+        builder = builder.module_size_warning_threshold(threshold);
+    }
+    if check_peer_dependencies {
+        builder = builder.check_peer_dependencies();
+    }
+    if let Some(usage_profile) = *usage_profile.await? {
+        builder = builder.usage_profile(usage_profile);
+    }
+    if static_chunk_groups {
+        builder = builder.static_chunk_groups();
+    }
+    if let Some(feature_flags) = feature_flags {
+        builder = builder.feature_flags(feature_flags);
+    }
+    if let Some(records_path) = records_path {
+        builder = builder.records(records_path);
+    }
+    if runtime_base_paths {
+        builder = builder.base_path_mode(BasePathMode::Runtime);
+    }
+    // An app exposing modules is loaded into the pages of other apps, so its
+    // chunks must not register with their runtimes, and are loaded relative to
+    // its remote entry unless base paths are set at runtime.
+    if let Some(name) = get_federation_config(project_path)
+        .await?
+        .as_ref()
+        .and_then(|federation| federation.name.as_ref())
+    {
+        builder = builder
+            .chunk_loading_global(format!("TURBOPACK_{}", name))?
+            .base_path_mode(BasePathMode::Auto);
+    }
+    Ok(builder.build())
 }
 
 #[turbo_tasks::function]
@@ -88,12 +154,38 @@ pub async fn create_web_entry_source(
     eager_compile: bool,
     node_env: Vc<NodeEnv>,
     browserslist_query: String,
-) -> Result<Vc<Box<dyn ContentSource>>> {
-    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
+    usage_profile: Vc<OptionUsageProfile>,
+    static_chunk_groups: bool,
+    isolate_vendor_chunks: bool,
+    split_node_modules: bool,
+    module_size_warning_threshold: Option<u64>,
+    check_peer_dependencies: bool,
+    feature_flags: Option<Vc<FeatureFlagSet>>,
+    html_path: Vc<FileSystemPath>,
+    records_path: Option<Vc<FileSystemPath>>,
+    base_paths_path: Option<Vc<FileSystemPath>>,
+) -> Result<Vc<WebEntrySource>> {
+    let mut compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
+    if let Some(feature_flags) = feature_flags {
+        compile_time_info = compile_time_info_with_feature_flags(compile_time_info, feature_flags);
+    }
     let asset_context =
         get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
-    let chunking_context =
-        get_client_chunking_context(project_path, server_root, compile_time_info.environment());
+    let dev_chunking_context = get_client_chunking_context(
+        project_path,
+        server_root,
+        compile_time_info.environment(),
+        usage_profile,
+        static_chunk_groups,
+        isolate_vendor_chunks,
+        split_node_modules,
+        module_size_warning_threshold,
+        check_peer_dependencies,
+        feature_flags,
+        records_path,
+        base_paths_path.is_some(),
+    );
+    let chunking_context: Vc<Box<dyn ChunkingContext>> = Vc::upcast(dev_chunking_context);
     let entries = get_client_runtime_entries(project_path);
 
     let runtime_entries = entries.resolve_entries(asset_context);
@@ -142,15 +234,83 @@ pub async fn create_web_entry_source(
         .try_join()
         .await?;
 
-    let entry_asset = Vc::upcast(DevHtmlAsset::new(
-        server_root.join("index.html".to_string()),
-        entries,
-    ));
+    let mut html = DevHtmlAsset::new(html_path, entries);
+    if base_paths_path.is_some() {
+        html = html.with_config_script(format!("/{BASE_PATHS_SCRIPT}"));
+    }
+    let mut root_assets = vec![Vc::upcast(html)];
+
+    // The remote entry of the modules the app exposes is served with its
+    // manifest at `/remote-entry.json`.
+    if let Some(federation) = &*get_federation_config(project_path).await? {
+        if !federation.exposes.is_empty() {
+            let name = federation
+                .name
+                .clone()
+                .context("a name is required to expose modules")?;
+            let exposes = federation
+                .exposes
+                .iter()
+                .map(|(expose, request)| async move {
+                    let ty = Value::new(ReferenceType::Entry(EntryReferenceSubType::Web));
+                    let request = Request::parse(Value::new(request.clone().into()));
+                    let module = (*origin
+                        .resolve_asset(request, origin.resolve_options(ty.clone()), ty)
+                        .first_module()
+                        .await?)
+                        .with_context(|| {
+                            format!("unable to resolve the exposed module {}", expose)
+                        })?;
+                    Ok((expose.clone(), module))
+                })
+                .try_join()
+                .await?;
+            let container = RemoteContainerModule::new(
+                project_path,
+                name,
+                Vc::cell(exposes.into_iter().collect::<IndexMap<_, _>>()),
+            );
+            root_assets.push(Vc::upcast(FederationManifestAsset::new(
+                server_root.join("remote-entry.json".to_string()),
+                dev_chunking_context,
+                container,
+            )));
+        }
+    }
 
+    let records = records_path.map(|_| {
+        ChunkingRecordsAsset::new(
+            dev_chunking_context,
+            Vc::<OutputAssets>::cell(root_assets.clone()),
+        )
+    });
+    let root_assets = Vc::<OutputAssetsSet>::cell(root_assets.into_iter().collect());
     let graph = Vc::upcast(if eager_compile {
-        AssetGraphContentSource::new_eager(server_root, entry_asset)
+        AssetGraphContentSource::new_eager_multiple(server_root, root_assets)
     } else {
-        AssetGraphContentSource::new_lazy(server_root, entry_asset)
+        AssetGraphContentSource::new_lazy_multiple(server_root, root_assets)
     });
-    Ok(graph)
+    // Feature flag variants share the chunk loading global, so they all serve
+    // the same config script.
+    let source = match base_paths_path {
+        Some(base_paths_path) => Vc::upcast(CombinedContentSource::new(vec![
+            Vc::upcast(BasePathsContentSource::new(
+                BASE_PATHS_SCRIPT.to_string(),
+                dev_chunking_context
+                    .await?
+                    .chunk_loading_global()
+                    .to_string(),
+                read_host_base_paths(base_paths_path),
+                BasePaths::default().cell(),
+            )),
+            graph,
+        ])),
+        None => graph,
+    };
+    Ok(WebEntrySource {
+        source,
+        html,
+        records,
+    }
+    .cell())
 }
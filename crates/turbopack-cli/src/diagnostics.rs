@@ -0,0 +1,295 @@
+//! Diagnostic bundles for tasks that panic. Instead of only printing the
+//! panic message, the task, its inputs, the recent invalidations and the
+//! environment are written to a bundle that users can attach to a bug report.
+//! Only the values of well-known env vars are kept, the values of all other
+//! env vars and the home directory are redacted from the bundle.
+
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use turbo_tasks::{try_current_task, TaskPanic, TurboTasks};
+use turbo_tasks_memory::MemoryBackend;
+
+/// Env vars whose values are written to the bundle. The values of all other
+/// env vars are considered secrets.
+const PUBLIC_ENV_VARS: &[&str] = &[
+    "CI",
+    "LANG",
+    "NODE_ENV",
+    "NODE_OPTIONS",
+    "PATH",
+    "RUST_BACKTRACE",
+    "RUST_LOG",
+    "SHELL",
+    "TERM",
+];
+
+/// Secret values shorter than this are not redacted from the texts of the
+/// bundle, since they would also match unrelated text.
+const MIN_SECRET_LEN: usize = 6;
+
+const REDACTED: &str = "[redacted]";
+
+thread_local! {
+    /// Where the last panic on this thread happened. The panic handler of
+    /// turbo-tasks is called on the thread that panicked, so it can pick it up.
+    static LAST_PANIC: RefCell<Option<PanicSite>> = RefCell::new(None);
+}
+
+struct PanicSite {
+    /// The panic message, to tell whether the site belongs to the panic that is
+    /// reported.
+    message: Option<String>,
+    location: Option<String>,
+    backtrace: String,
+}
+
+/// Takes the last panic site of this thread if it belongs to the panic with
+/// `message`. A site of a panic that was caught is never reported, so it might
+/// be left over.
+fn take_panic_site(message: Option<&str>) -> Option<PanicSite> {
+    LAST_PANIC
+        .with(|last| last.borrow_mut().take())
+        .filter(|site| site.message.as_deref() == message)
+}
+
+fn panic_message(info: &std::panic::PanicInfo) -> Option<String> {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticBundle {
+    version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    task: String,
+    inputs: Vec<String>,
+    message: Option<String>,
+    location: Option<String>,
+    backtrace: Option<String>,
+    recent_invalidations: Vec<RecentInvalidation>,
+    environment: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecentInvalidation {
+    ms_before_panic: u128,
+    reason: String,
+}
+
+/// Writes a diagnostic bundle to `dir` when a task of `turbo_tasks` panics.
+/// Panics outside of tasks are reported by the default panic hook.
+pub fn install_panic_diagnostics(turbo_tasks: &TurboTasks<MemoryBackend>, dir: PathBuf) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if try_current_task().is_none() {
+            default_hook(info);
+            return;
+        }
+        LAST_PANIC.with(|last| {
+            *last.borrow_mut() = Some(PanicSite {
+                message: panic_message(info),
+                location: info.location().map(|location| location.to_string()),
+                backtrace: Backtrace::force_capture().to_string(),
+            })
+        });
+    }));
+
+    // The same panic tends to repeat in many tasks, so only the first one gets a
+    // bundle.
+    let reported = Mutex::new(HashSet::new());
+    turbo_tasks.set_panic_handler(move |panic| {
+        let site = take_panic_site(panic.message.as_deref());
+        let message = panic.message.as_deref().unwrap_or("unknown panic");
+        eprintln!(
+            "{} - a task panicked: {}\n  in {}",
+            "error".red(),
+            message,
+            panic.task
+        );
+        let key = (
+            site.as_ref().and_then(|site| site.location.clone()),
+            panic.message.clone(),
+        );
+        if !reported.lock().unwrap().insert(key) {
+            return;
+        }
+        match write_bundle(&dir, panic, site) {
+            Ok(path) => eprintln!(
+                "A diagnostic bundle has been written to {}. Please attach it when reporting this \
+                 bug.",
+                path.display()
+            ),
+            Err(err) => eprintln!("Unable to write a diagnostic bundle: {:?}", err),
+        }
+    });
+}
+
+fn write_bundle(dir: &Path, panic: &TaskPanic, site: Option<PanicSite>) -> Result<PathBuf> {
+    let redactor = Redactor::new(
+        std::env::vars().collect(),
+        std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .ok(),
+    );
+    let (location, backtrace) = match site {
+        Some(site) => (site.location, Some(site.backtrace)),
+        None => (None, None),
+    };
+    let bundle = DiagnosticBundle {
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        task: redactor.redact(&panic.task),
+        inputs: panic
+            .inputs
+            .iter()
+            .map(|input| redactor.redact(input))
+            .collect(),
+        message: panic
+            .message
+            .as_deref()
+            .map(|message| redactor.redact(message)),
+        location: location.map(|location| redactor.redact(&location)),
+        backtrace: backtrace.map(|backtrace| redactor.redact(&backtrace)),
+        recent_invalidations: panic
+            .recent_invalidations
+            .iter()
+            .map(|(elapsed, reason)| RecentInvalidation {
+                ms_before_panic: elapsed.as_millis(),
+                reason: redactor.redact(reason),
+            })
+            .collect(),
+        environment: redactor.environment(),
+    };
+
+    fs::create_dir_all(dir)?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let path = dir.join(format!("panic-{}.json", timestamp));
+    fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+    Ok(path)
+}
+
+struct Redactor {
+    home: Option<String>,
+    /// The values of env vars that are considered secrets, longest first.
+    secrets: Vec<String>,
+    env: Vec<(String, String)>,
+}
+
+impl Redactor {
+    fn new(env: Vec<(String, String)>, home: Option<String>) -> Self {
+        let mut secrets: Vec<_> = env
+            .iter()
+            .filter(|(name, value)| !is_public(name) && value.len() >= MIN_SECRET_LEN)
+            .map(|(_, value)| value.clone())
+            .collect();
+        // A secret that contains another one has to be redacted first.
+        secrets.sort_by(|a, b| b.len().cmp(&a.len()));
+        let home = home.filter(|home| home.len() > 1);
+        Self { home, secrets, env }
+    }
+
+    fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for secret in &self.secrets {
+            text = text.replace(secret, REDACTED);
+        }
+        if let Some(home) = &self.home {
+            text = text.replace(home, "~");
+        }
+        text
+    }
+
+    fn environment(&self) -> BTreeMap<String, String> {
+        self.env
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_public(name) {
+                    self.redact(value)
+                } else {
+                    REDACTED.to_string()
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+fn is_public(name: &str) -> bool {
+    PUBLIC_ENV_VARS.contains(&name.to_uppercase().as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor() -> Redactor {
+        Redactor::new(
+            vec![
+                (
+                    "DATABASE_URL".to_string(),
+                    "postgres://admin:hunter22@db".to_string(),
+                ),
+                ("DB_PASSWORD".to_string(), "hunter22".to_string()),
+                ("NODE_ENV".to_string(), "development".to_string()),
+                ("PATH".to_string(), "/home/user/bin:/usr/bin".to_string()),
+                ("SHORT".to_string(), "1".to_string()),
+            ],
+            Some("/home/user".to_string()),
+        )
+    }
+
+    #[test]
+    fn environment_only_keeps_public_values() {
+        let environment = redactor().environment();
+        assert_eq!(environment["DATABASE_URL"], REDACTED);
+        assert_eq!(environment["DB_PASSWORD"], REDACTED);
+        assert_eq!(environment["SHORT"], REDACTED);
+        assert_eq!(environment["NODE_ENV"], "development");
+        assert_eq!(environment["PATH"], "~/bin:/usr/bin");
+    }
+
+    #[test]
+    fn redact_removes_secret_values_and_home() {
+        assert_eq!(
+            redactor().redact(
+                "connect postgres://admin:hunter22@db as hunter22 from /home/user/app in                  development"
+            ),
+            "connect [redacted] as [redacted] from ~/app in development"
+        );
+    }
+
+    #[test]
+    fn panic_site_of_another_panic_is_not_taken() {
+        let site = |message: &str| PanicSite {
+            message: Some(message.to_string()),
+            location: None,
+            backtrace: String::new(),
+        };
+
+        LAST_PANIC.with(|last| *last.borrow_mut() = Some(site("caught")));
+        assert!(take_panic_site(Some("reported")).is_none());
+        // The stale site is dropped.
+        assert!(take_panic_site(Some("caught")).is_none());
+
+        LAST_PANIC.with(|last| *last.borrow_mut() = Some(site("reported")));
+        assert!(take_panic_site(Some("reported")).is_some());
+    }
+}
@@ -0,0 +1,346 @@
+//! Offline queries against the output directory of a build, which is
+//! described by the `build-manifest.json` written along with the chunks by
+//! `turbopack build --build-manifest`.
+//!
+//! The manifest maps every entry to the files of its chunk group, and every
+//! file to its size, the ids of the modules it includes, its SRI hash and its
+//! serving hints.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::util::FormatBytes;
+use turbopack_core::chunk::{
+    integrity::sha384_integrity,
+    snapshot::{ChunkGraphSnapshot, ChunkGroupSnapshot, ChunkSnapshot},
+};
+
+use crate::arguments::{InspectArguments, InspectQuery};
+
+/// The name of the manifest in the output directory.
+pub const BUILD_MANIFEST: &str = "build-manifest.json";
+
+/// Describes the files of a build, see the [module docs](self).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    /// Maps the names of entries to the paths of the files of their chunk
+    /// group, relative to the output directory.
+    pub entries: BTreeMap<String, Vec<String>>,
+    /// Maps the paths of files, relative to the output directory, to their
+    /// descriptions.
+    pub files: BTreeMap<String, BuildManifestFile>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildManifestFile {
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The ids of the modules included in the file, if it's a chunk.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<String>,
+    /// The SRI hash of the file, e.g. `sha384-<base64 digest>`.
+    pub integrity: String,
+    /// The `cache-control` header to serve the file with, if it differs from
+    /// the server's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+    /// The content type to serve the file with, if it differs from the one
+    /// guessed from its path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+impl BuildManifest {
+    /// Reads the manifest of the output directory `output_dir`.
+    pub fn read(output_dir: &Path) -> Result<Self> {
+        let path = output_dir.join(BUILD_MANIFEST);
+        let json = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "unable to read {}, was the output directory built with `turbopack build \
+                 --build-manifest`?",
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&json).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// The files that include the module with the id `module_id`.
+    pub fn files_with_module(&self, module_id: &str) -> Vec<&str> {
+        self.files
+            .iter()
+            .filter(|(_, file)| file.modules.iter().any(|id| id == module_id))
+            .map(|(path, _)| path.as_str())
+            .collect()
+    }
+
+    /// The entries whose chunk group includes the file at `path`.
+    pub fn entries_with_file(&self, path: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(_, files)| files.iter().any(|file| file == path))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Converts the manifest into a [ChunkGraphSnapshot] with a chunk group
+    /// for every entry, e.g. to diff two builds.
+    pub fn to_snapshot(&self) -> ChunkGraphSnapshot {
+        ChunkGraphSnapshot {
+            chunk_groups: self
+                .entries
+                .iter()
+                .map(|(name, files)| {
+                    let chunks = files
+                        .iter()
+                        .filter_map(|path| {
+                            let file = self.files.get(path)?;
+                            Some((
+                                path.clone(),
+                                ChunkSnapshot {
+                                    size: file.size,
+                                    modules: file.modules.clone(),
+                                },
+                            ))
+                        })
+                        .collect();
+                    (name.clone(), ChunkGroupSnapshot { chunks })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A file whose content doesn't match the build manifest, see [verify].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    Missing { path: String },
+    IntegrityMismatch { path: String, actual: String },
+}
+
+/// Checks the files of `output_dir` against the SRI hashes of `manifest`.
+pub fn verify(output_dir: &Path, manifest: &BuildManifest) -> Result<Vec<VerifyError>> {
+    let mut errors = Vec::new();
+    for (path, file) in manifest.files.iter() {
+        let content = match fs::read(output_dir.join(path)) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                errors.push(VerifyError::Missing { path: path.clone() });
+                continue;
+            }
+            Err(err) => return Err(err).with_context(|| format!("unable to read {path}")),
+        };
+        let actual = sha384_integrity(&content);
+        if actual != file.integrity {
+            errors.push(VerifyError::IntegrityMismatch {
+                path: path.clone(),
+                actual,
+            });
+        }
+    }
+    Ok(errors)
+}
+
+/// Answers a query about an output directory, see [InspectArguments].
+pub fn inspect(args: &InspectArguments) -> Result<()> {
+    let output_dir = args.output_dir.as_path();
+    let manifest = BuildManifest::read(output_dir)?;
+    match &args.query {
+        InspectQuery::Chunks { entry } => {
+            let Some(files) = manifest.entries.get(entry) else {
+                bail!(
+                    "no entry named {entry}, the entries are: {}",
+                    manifest
+                        .entries
+                        .keys()
+                        .map(|name| name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            };
+            for path in files {
+                let size = manifest.files.get(path).map_or(0, |file| file.size);
+                println!("{:>10} {path}", FormatBytes(size as usize).to_string());
+            }
+        }
+        InspectQuery::Module { id } => {
+            let files = manifest.files_with_module(id);
+            if files.is_empty() {
+                bail!("no chunk includes the module {id}");
+            }
+            for path in files {
+                println!(
+                    "{path} ({})",
+                    manifest.entries_with_file(path).join(", ").dimmed()
+                );
+            }
+        }
+        InspectQuery::Verify => {
+            let errors = verify(output_dir, &manifest)?;
+            for error in errors.iter() {
+                match error {
+                    VerifyError::Missing { path } => {
+                        println!("{} - {path} is missing", "error".red())
+                    }
+                    VerifyError::IntegrityMismatch { path, actual } => println!(
+                        "{} - {path} has the hash {actual}, but {} is expected",
+                        "error".red(),
+                        manifest.files[path].integrity
+                    ),
+                }
+            }
+            if !errors.is_empty() {
+                bail!("{} files don't match the build manifest", errors.len());
+            }
+            println!(
+                "{} - all {} files match the build manifest",
+                "event".purple(),
+                manifest.files.len()
+            );
+        }
+        InspectQuery::Diff { other_output_dir } => {
+            let other = BuildManifest::read(other_output_dir)?;
+            print_diff(&other, &manifest);
+        }
+    }
+    Ok(())
+}
+
+/// Prints the changes from the build described by `before` to the build
+/// described by `after`: removed (`-`), added (`+`) and changed (`~`) files,
+/// the size changes of chunk groups, and removed and added modules.
+fn print_diff(before: &BuildManifest, after: &BuildManifest) {
+    let before_files = before.files.keys().collect::<BTreeSet<_>>();
+    let after_files = after.files.keys().collect::<BTreeSet<_>>();
+    for path in before_files.difference(&after_files) {
+        println!("{} {path}", "-".red());
+    }
+    for path in after_files.difference(&before_files) {
+        println!("{} {path}", "+".green());
+    }
+    for path in before_files.intersection(&after_files) {
+        if before.files[*path].integrity != after.files[*path].integrity {
+            println!("{} {path}", "~".yellow());
+        }
+    }
+
+    let diff = before.to_snapshot().diff(&after.to_snapshot());
+    for (name, delta) in diff.chunk_groups.iter() {
+        println!(
+            "{} - {name}: {} -> {} ({:+})",
+            "event".purple(),
+            FormatBytes(delta.before.unwrap_or_default() as usize),
+            FormatBytes(delta.after.unwrap_or_default() as usize),
+            delta.delta()
+        );
+    }
+    for id in diff.removed_modules.iter() {
+        println!("{} module {id}", "-".red());
+    }
+    for id in diff.added_modules.iter() {
+        println!("{} module {id}", "+".green());
+    }
+    for (name, directories) in diff.new_duplicate_packages.iter() {
+        println!(
+            "{} - {name} is duplicated in {}",
+            "warning".yellow(),
+            directories
+                .iter()
+                .map(|directory| directory.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(content: &[u8], modules: &[&str]) -> BuildManifestFile {
+        BuildManifestFile {
+            size: content.len() as u64,
+            modules: modules.iter().map(|id| id.to_string()).collect(),
+            integrity: sha384_integrity(content),
+            ..Default::default()
+        }
+    }
+
+    fn manifest() -> BuildManifest {
+        BuildManifest {
+            entries: BTreeMap::from([(
+                "main".to_string(),
+                vec![
+                    "a.js".to_string(),
+                    "b.js".to_string(),
+                    "missing.js".to_string(),
+                ],
+            )]),
+            files: BTreeMap::from([
+                ("a.js".to_string(), file(b"a", &["1", "2"])),
+                ("b.js".to_string(), file(b"bb", &["2"])),
+                ("c.css".to_string(), file(b"c", &[])),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_files_with_module() {
+        let manifest = manifest();
+        assert_eq!(manifest.files_with_module("1"), vec!["a.js"]);
+        assert_eq!(manifest.files_with_module("2"), vec!["a.js", "b.js"]);
+        assert!(manifest.files_with_module("3").is_empty());
+    }
+
+    #[test]
+    fn test_to_snapshot() {
+        let snapshot = manifest().to_snapshot();
+        let main = &snapshot.chunk_groups["main"];
+        // Files of the entry that aren't described are skipped.
+        assert_eq!(main.chunks.keys().collect::<Vec<_>>(), vec!["a.js", "b.js"]);
+        assert_eq!(main.chunks["b.js"].size, 2);
+        assert_eq!(main.chunks["a.js"].modules, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_serving_hints() -> Result<()> {
+        let json = serde_json::to_value(file(b"a", &[]))?;
+        assert!(json.get("cache_control").is_none());
+        assert!(json.get("content_type").is_none());
+
+        let hinted = BuildManifestFile {
+            cache_control: Some("must-revalidate".to_string()),
+            content_type: Some("application/manifest+json".to_string()),
+            ..file(b"a", &[])
+        };
+        let json = serde_json::to_string(&hinted)?;
+        assert_eq!(serde_json::from_str::<BuildManifestFile>(&json)?, hinted);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("a.js"), "a")?;
+        fs::write(dir.path().join("b.js"), "changed")?;
+        let errors = verify(dir.path(), &manifest())?;
+        assert_eq!(
+            errors,
+            vec![
+                VerifyError::IntegrityMismatch {
+                    path: "b.js".to_string(),
+                    actual: sha384_integrity(b"changed"),
+                },
+                VerifyError::Missing {
+                    path: "c.css".to_string()
+                },
+            ]
+        );
+        Ok(())
+    }
+}
@@ -4,9 +4,16 @@
 
 pub mod arguments;
 pub mod build;
+pub mod cache;
+#[cfg(feature = "bench")]
+pub mod chunking_bench;
 pub(crate) mod contexts;
 pub mod dev;
+pub(crate) mod diagnostics;
 pub(crate) mod embed_js;
+pub mod inspect;
+pub mod metrics;
+pub mod module_server;
 pub(crate) mod util;
 
 pub fn register() {
@@ -5,10 +5,17 @@ use std::path::Path;
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
+#[cfg(not(feature = "otel"))]
+use tracing_subscriber::layer::Identity;
+use tracing_subscriber::{
+    layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
 use turbopack_cli::{arguments::Arguments, register};
+#[cfg(feature = "otel")]
+use turbopack_trace_utils::otel::otlp_metrics_layer;
 use turbopack_trace_utils::{
     exit::ExitGuard,
+    module_timings::ModuleTimingsLayer,
     raw_trace::RawTraceLayer,
     trace_writer::TraceWriter,
     tracing_presets::{
@@ -24,9 +31,19 @@ fn main() {
 
     let args = Arguments::parse();
 
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .on_thread_stop(|| {
+            TurboMalloc::thread_stop();
+        })
+        .build()
+        .unwrap();
+    // The exit guard and the metrics exporter spawn tasks on the runtime.
+    let _runtime_guard = runtime.enter();
+
     let trace = std::env::var("TURBOPACK_TRACING").ok();
 
-    let _guard = if let Some(mut trace) = trace {
+    let (trace_layer, _guard) = if let Some(mut trace) = trace {
         // Trace presets
         match trace.as_str() {
             "overview" => {
@@ -41,10 +58,6 @@ fn main() {
             _ => {}
         }
 
-        let subscriber = Registry::default();
-
-        let subscriber = subscriber.with(EnvFilter::builder().parse(trace).unwrap());
-
         let internal_dir = args
             .dir()
             .unwrap_or_else(|| Path::new("."))
@@ -55,26 +68,40 @@ fn main() {
         let trace_file = internal_dir.join("trace.log");
         let trace_writer = std::fs::File::create(trace_file).unwrap();
         let (trace_writer, guard) = TraceWriter::new(trace_writer);
-        let subscriber = subscriber.with(RawTraceLayer::new(trace_writer));
+        // The filter only applies to the trace, so that metrics are not affected.
+        let layer = RawTraceLayer::new(trace_writer)
+            .with_filter(EnvFilter::builder().parse(trace).unwrap());
 
         let guard = ExitGuard::new(guard).unwrap();
 
-        subscriber.init();
-
-        Some(guard)
+        (Some(layer), Some(guard))
     } else {
-        None
+        (None, None)
     };
 
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .on_thread_stop(|| {
-            TurboMalloc::thread_stop();
-        })
-        .build()
-        .unwrap()
-        .block_on(main_inner(args))
-        .unwrap();
+    #[cfg(feature = "otel")]
+    let (metrics_layer, _metrics_guard) = match std::env::var("TURBOPACK_OTEL_ENDPOINT") {
+        Ok(endpoint) => {
+            let (layer, guard) =
+                otlp_metrics_layer(&endpoint, std::time::Duration::from_secs(10)).unwrap();
+            (Some(layer), Some(guard))
+        }
+        Err(_) => (None, None),
+    };
+    #[cfg(not(feature = "otel"))]
+    let metrics_layer = None::<Identity>;
+
+    let module_timings_layer = args.module_timings().map(|_| ModuleTimingsLayer::new());
+
+    if trace_layer.is_some() || metrics_layer.is_some() || module_timings_layer.is_some() {
+        Registry::default()
+            .with(trace_layer)
+            .with(metrics_layer)
+            .with(module_timings_layer)
+            .init();
+    }
+
+    runtime.block_on(main_inner(args)).unwrap();
 }
 
 async fn main_inner(args: Arguments) -> Result<()> {
@@ -83,5 +110,8 @@ async fn main_inner(args: Arguments) -> Result<()> {
     match args {
         Arguments::Build(args) => turbopack_cli::build::build(&args).await,
         Arguments::Dev(args) => turbopack_cli::dev::start_server(&args).await,
+        Arguments::Metrics(args) => turbopack_cli::metrics::print(&args),
+        Arguments::Inspect(args) => turbopack_cli::inspect::inspect(&args),
+        Arguments::Cache(args) => turbopack_cli::cache::run(&args),
     }
 }
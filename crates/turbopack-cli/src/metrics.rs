@@ -0,0 +1,399 @@
+//! A local store of build metrics, to tell whether builds got slower or larger
+//! over time, e.g. after a dependency upgrade.
+//!
+//! The metrics of every build and every dev server update are appended as a
+//! JSON line to `.turbopack/build-metrics.jsonl` in the project directory.
+//! Only the latest records are kept once the file grows large.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::util::{FormatBytes, FormatDuration};
+use turbo_tasks_memory::TaskCacheStats;
+
+use crate::arguments::MetricsArguments;
+
+/// What produced a [BuildMetrics] record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BuildKind {
+    /// A production build, see [crate::build::build].
+    Build,
+    /// An update of the dev server, i.e. a compilation caused by a request or
+    /// by changed files.
+    DevUpdate,
+}
+
+/// The metrics of a single build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildMetrics {
+    pub kind: BuildKind,
+    /// When the build finished, in milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub duration_ms: u64,
+    /// The number of tasks that were executed.
+    pub tasks: usize,
+    /// The number of tasks that were found in the task cache instead of being
+    /// created during the build.
+    pub task_cache_hits: usize,
+    /// The number of tasks that were created during the build.
+    pub task_cache_misses: usize,
+    /// The total size in bytes of the assets of each chunk group, by the name
+    /// of its entry.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub chunk_group_sizes: BTreeMap<String, u64>,
+}
+
+impl BuildMetrics {
+    /// `cache` are the task cache lookups during the build, e.g. the
+    /// [TaskCacheStats::since] the previous dev server update.
+    pub fn new(kind: BuildKind, duration: Duration, tasks: usize, cache: TaskCacheStats) -> Self {
+        BuildMetrics {
+            kind,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis() as u64),
+            duration_ms: duration.as_millis() as u64,
+            tasks,
+            task_cache_hits: cache.hits,
+            task_cache_misses: cache.misses,
+            chunk_group_sizes: BTreeMap::new(),
+        }
+    }
+
+    /// The total size in bytes of all chunk groups.
+    pub fn total_size(&self) -> u64 {
+        self.chunk_group_sizes.values().sum()
+    }
+}
+
+/// Compares the average metrics of the latest builds with the builds before
+/// them, see [BuildMetricsStore::trend].
+#[derive(Debug, Clone)]
+pub struct BuildMetricsTrend {
+    /// The number of builds that were averaged on each side.
+    pub builds: usize,
+    pub previous_duration: Duration,
+    pub latest_duration: Duration,
+    pub previous_size: u64,
+    pub latest_size: u64,
+    /// The chunk groups whose average size changed, with their previous and
+    /// latest average size.
+    pub chunk_group_sizes: BTreeMap<String, (u64, u64)>,
+}
+
+impl BuildMetricsTrend {
+    /// The relative change of the average duration, e.g. `0.1` if builds got
+    /// 10% slower.
+    pub fn duration_change(&self) -> f64 {
+        relative_change(
+            self.previous_duration.as_secs_f64(),
+            self.latest_duration.as_secs_f64(),
+        )
+    }
+
+    /// The relative change of the average total size.
+    pub fn size_change(&self) -> f64 {
+        relative_change(self.previous_size as f64, self.latest_size as f64)
+    }
+}
+
+fn relative_change(previous: f64, latest: f64) -> f64 {
+    if previous == 0.0 {
+        0.0
+    } else {
+        latest / previous - 1.0
+    }
+}
+
+/// The store is compacted when it's larger than this many bytes.
+const MAX_STORE_SIZE: u64 = 1024 * 1024;
+
+/// The number of records that are kept when the store is compacted.
+const KEPT_RECORDS: usize = 2000;
+
+/// The store of the metrics of past builds of a project.
+pub struct BuildMetricsStore {
+    path: PathBuf,
+}
+
+impl BuildMetricsStore {
+    /// Opens the store of the project in `project_dir`. It's created when the
+    /// first metrics are recorded.
+    pub fn open(project_dir: &Path) -> Self {
+        BuildMetricsStore {
+            path: project_dir.join(".turbopack").join("build-metrics.jsonl"),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `metrics` to the store, and drops the oldest records when it's
+    /// too large.
+    pub fn record(&self, metrics: &BuildMetrics) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("unable to create {}", dir.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("unable to open {}", self.path.display()))?;
+        let mut line = serde_json::to_string(metrics)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("unable to write to {}", self.path.display()))?;
+        let size = file.metadata()?.len();
+        drop(file);
+        if size > MAX_STORE_SIZE {
+            self.compact(KEPT_RECORDS)?;
+        }
+        Ok(())
+    }
+
+    /// Drops all but the last `keep` records of any kind.
+    fn compact(&self, keep: usize) -> Result<()> {
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("unable to read {}", self.path.display()))?;
+        let lines = content.lines().collect::<Vec<_>>();
+        let mut kept = lines[lines.len().saturating_sub(keep)..].join("\n");
+        kept.push('\n');
+        // Replace the file atomically, so that an interrupted compaction
+        // doesn't lose all records.
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp_path, kept)
+            .with_context(|| format!("unable to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("unable to replace {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Returns the metrics of all builds of `kind`, oldest first. Lines that
+    /// can't be parsed, e.g. from an interrupted write, are skipped.
+    pub fn all(&self, kind: BuildKind) -> Result<Vec<BuildMetrics>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("unable to open {}", self.path.display()))
+            }
+        };
+        let mut builds = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.with_context(|| format!("unable to read {}", self.path.display()))?;
+            if let Ok(metrics) = serde_json::from_str::<BuildMetrics>(&line) {
+                if metrics.kind == kind {
+                    builds.push(metrics);
+                }
+            }
+        }
+        Ok(builds)
+    }
+
+    /// Returns the metrics of the last `count` builds of `kind`, oldest first.
+    pub fn last(&self, kind: BuildKind, count: usize) -> Result<Vec<BuildMetrics>> {
+        let mut builds = self.all(kind)?;
+        let skip = builds.len().saturating_sub(count);
+        builds.drain(..skip);
+        Ok(builds)
+    }
+
+    /// Compares the average metrics of the last `count` builds of `kind` with
+    /// the `count` builds before them. With fewer than `2 * count` builds, the
+    /// latest half of them is compared with the previous half. Returns `None`
+    /// if there are fewer than 2 builds.
+    pub fn trend(&self, kind: BuildKind, count: usize) -> Result<Option<BuildMetricsTrend>> {
+        let mut builds = self.last(kind, 2 * count)?;
+        let count = count.min(builds.len() / 2);
+        if count == 0 {
+            return Ok(None);
+        }
+        builds.drain(..builds.len() - 2 * count);
+        let (previous, latest) = builds.split_at(count);
+        let average_duration = |builds: &[BuildMetrics]| {
+            Duration::from_millis(
+                builds.iter().map(|build| build.duration_ms).sum::<u64>() / count as u64,
+            )
+        };
+        let average_size = |builds: &[BuildMetrics], name: Option<&str>| {
+            builds
+                .iter()
+                .map(|build| match name {
+                    Some(name) => build.chunk_group_sizes.get(name).copied().unwrap_or(0),
+                    None => build.total_size(),
+                })
+                .sum::<u64>()
+                / count as u64
+        };
+        let mut chunk_group_sizes = BTreeMap::new();
+        for name in builds
+            .iter()
+            .flat_map(|build| build.chunk_group_sizes.keys())
+        {
+            let sizes = (
+                average_size(previous, Some(name)),
+                average_size(latest, Some(name)),
+            );
+            if sizes.0 != sizes.1 {
+                chunk_group_sizes.insert(name.clone(), sizes);
+            }
+        }
+        Ok(Some(BuildMetricsTrend {
+            builds: count,
+            previous_duration: average_duration(previous),
+            latest_duration: average_duration(latest),
+            previous_size: average_size(previous, None),
+            latest_size: average_size(latest, None),
+            chunk_group_sizes,
+        }))
+    }
+}
+
+/// Prints the last `count` builds of `kind` and how they compare to the
+/// builds before them.
+pub fn print_build_metrics(store: &BuildMetricsStore, kind: BuildKind, count: usize) -> Result<()> {
+    let builds = store.last(kind, count)?;
+    if builds.is_empty() {
+        println!(
+            "{} - no metrics recorded in {}",
+            "event".purple(),
+            store.path().display()
+        );
+        return Ok(());
+    }
+    for build in builds.iter() {
+        print!(
+            "{duration:>10} {tasks:>8} tasks {hits:>8} cache hits",
+            duration = FormatDuration(Duration::from_millis(build.duration_ms)).to_string(),
+            tasks = build.tasks,
+            hits = build.task_cache_hits,
+        );
+        if !build.chunk_group_sizes.is_empty() {
+            print!(
+                " {:>10}",
+                FormatBytes(build.total_size() as usize).to_string()
+            );
+        }
+        println!();
+    }
+    if let Some(trend) = store.trend(kind, count)? {
+        println!(
+            "{} - the last {} builds took {} on average ({:+.1}% compared to the {} builds before)",
+            "event".purple(),
+            trend.builds,
+            FormatDuration(trend.latest_duration),
+            trend.duration_change() * 100.0,
+            trend.builds,
+        );
+        if trend.latest_size != 0 || trend.previous_size != 0 {
+            println!(
+                "{} - their output has {} on average ({:+.1}%)",
+                "event".purple(),
+                FormatBytes(trend.latest_size as usize),
+                trend.size_change() * 100.0,
+            );
+        }
+        for (name, (previous, latest)) in trend.chunk_group_sizes.iter() {
+            println!(
+                "  {name}: {} -> {}",
+                FormatBytes(*previous as usize),
+                FormatBytes(*latest as usize)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prints the recorded metrics of the project, see [MetricsArguments].
+pub fn print(args: &MetricsArguments) -> Result<()> {
+    let project_dir = match &args.dir {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir()?,
+    };
+    let kind = if args.dev {
+        BuildKind::DevUpdate
+    } else {
+        BuildKind::Build
+    };
+    print_build_metrics(&BuildMetricsStore::open(&project_dir), kind, args.last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(duration_ms: u64, size: u64) -> BuildMetrics {
+        let mut metrics = BuildMetrics::new(
+            BuildKind::Build,
+            Duration::from_millis(duration_ms),
+            0,
+            TaskCacheStats::default(),
+        );
+        metrics.chunk_group_sizes.insert("main".to_string(), size);
+        metrics
+    }
+
+    #[test]
+    fn test_trend() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = BuildMetricsStore::open(dir.path());
+        assert!(store.trend(BuildKind::Build, 2)?.is_none());
+
+        store.record(&build(100, 10))?;
+        assert!(store.trend(BuildKind::Build, 2)?.is_none());
+
+        // With fewer than 2 * count builds, halves are compared.
+        store.record(&build(200, 10))?;
+        let trend = store.trend(BuildKind::Build, 2)?.unwrap();
+        assert_eq!(trend.builds, 1);
+        assert_eq!(trend.previous_duration, Duration::from_millis(100));
+        assert_eq!(trend.latest_duration, Duration::from_millis(200));
+        assert_eq!(trend.duration_change(), 1.0);
+        assert!(trend.chunk_group_sizes.is_empty());
+
+        // Dev updates are kept apart.
+        let mut update = build(1000, 0);
+        update.kind = BuildKind::DevUpdate;
+        store.record(&update)?;
+
+        store.record(&build(300, 20))?;
+        store.record(&build(500, 30))?;
+        let trend = store.trend(BuildKind::Build, 2)?.unwrap();
+        assert_eq!(trend.builds, 2);
+        assert_eq!(trend.previous_duration, Duration::from_millis(150));
+        assert_eq!(trend.latest_duration, Duration::from_millis(400));
+        assert_eq!(trend.previous_size, 10);
+        assert_eq!(trend.latest_size, 25);
+        assert_eq!(trend.chunk_group_sizes["main"], (10, 25));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_keeps_the_latest_records() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = BuildMetricsStore::open(dir.path());
+        for duration_ms in 0..5 {
+            store.record(&build(duration_ms, 0))?;
+        }
+        store.compact(2)?;
+        let durations = store
+            .all(BuildKind::Build)?
+            .iter()
+            .map(|build| build.duration_ms)
+            .collect::<Vec<_>>();
+        assert_eq!(durations, vec![3, 4]);
+        Ok(())
+    }
+}
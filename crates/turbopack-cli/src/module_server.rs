@@ -0,0 +1,396 @@
+//! Builds modules on demand for test runners.
+//!
+//! A test runner in the same process requests modules by specifier over a
+//! channel and receives their code as it would be placed into chunks, together
+//! with the modules they depend on. Modules are built with the same contexts as
+//! the dev server. The turbo tasks instance is kept across requests, so after a
+//! file changed only the affected modules are rebuilt.
+
+use std::{path::MAIN_SEPARATOR, sync::Arc};
+
+use anyhow::{anyhow, bail, Context, Result};
+use tokio::sync::{mpsc, oneshot};
+use turbo_tasks::{run_once, TryJoinIterExt, TurboTasks, Value, Vc};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    ecmascript::chunk::{EcmascriptChunk, EcmascriptChunkItem, EcmascriptChunkItemExt},
+    evaluate_context::node_build_environment,
+};
+use turbopack_core::{
+    chunk::{
+        availability_info::AvailabilityInfo,
+        chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        AsyncModuleInfo, ChunkItemExt, ChunkableModule, ChunkingContext,
+    },
+    module::Module,
+    reference::primary_referenced_modules,
+    reference_type::ReferenceType,
+    resolve::{
+        origin::{PlainResolveOrigin, ResolveOriginExt},
+        parse::Request,
+    },
+    source_map::GenerateSourceMap,
+};
+use turbopack_dev::DevChunkingContext;
+use turbopack_env::dotenv::load_env;
+use turbopack_node::execution_context::ExecutionContext;
+
+use crate::{
+    contexts::{get_client_asset_context, get_client_compile_time_info, NodeEnv},
+    util::{output_fs, project_fs},
+};
+
+/// A module the transformed module depends on.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct ModuleDependency {
+    /// The path of the module, relative to the project directory.
+    pub path: String,
+    /// The id the module is required with, or `None` when it isn't placed into
+    /// chunks (e.g. static assets).
+    pub id: Option<String>,
+}
+
+/// The code of a module, as it would be placed into chunks.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct TransformedModule {
+    /// The id the module is required with.
+    pub id: String,
+    /// The path of the module, relative to the project directory.
+    pub path: String,
+    /// The module factory.
+    pub code: String,
+    pub source_map: Option<String>,
+    pub dependencies: Vec<ModuleDependency>,
+}
+
+struct ModuleRequest {
+    specifier: String,
+    importer: Option<String>,
+    respond: oneshot::Sender<Result<TransformedModule>>,
+}
+
+pub struct ModuleServerBuilder {
+    turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+    project_dir: String,
+    root_dir: String,
+    browserslist_query: String,
+}
+
+impl ModuleServerBuilder {
+    pub fn new(
+        turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
+        project_dir: String,
+        root_dir: String,
+    ) -> ModuleServerBuilder {
+        ModuleServerBuilder {
+            turbo_tasks,
+            project_dir,
+            root_dir,
+            browserslist_query: "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
+                                 versions, last 1 Edge versions"
+                .to_owned(),
+        }
+    }
+
+    pub fn browserslist_query(mut self, browserslist_query: String) -> ModuleServerBuilder {
+        self.browserslist_query = browserslist_query;
+        self
+    }
+
+    /// Starts handling requests in the background. Requests are handled
+    /// concurrently until every [ModuleServer] handle has been dropped.
+    pub fn build(self) -> ModuleServer {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<ModuleRequest>();
+        let ModuleServerBuilder {
+            turbo_tasks,
+            project_dir,
+            root_dir,
+            browserslist_query,
+        } = self;
+        tokio::spawn(async move {
+            while let Some(request) = receiver.recv().await {
+                let turbo_tasks = turbo_tasks.clone();
+                let project_dir = project_dir.clone();
+                let root_dir = root_dir.clone();
+                let browserslist_query = browserslist_query.clone();
+                tokio::spawn(async move {
+                    let ModuleRequest {
+                        specifier,
+                        importer,
+                        respond,
+                    } = request;
+                    let result = run_once(turbo_tasks, async move {
+                        Ok(transformed_module(
+                            root_dir,
+                            project_dir,
+                            browserslist_query,
+                            specifier,
+                            importer,
+                        )
+                        .await?
+                        .clone_value())
+                    })
+                    .await;
+                    // The requester might not be waiting for the result anymore.
+                    let _ = respond.send(result);
+                });
+            }
+        });
+        ModuleServer { sender }
+    }
+}
+
+/// A handle to request modules from a running module server.
+#[derive(Clone)]
+pub struct ModuleServer {
+    sender: mpsc::UnboundedSender<ModuleRequest>,
+}
+
+impl ModuleServer {
+    /// Builds the module `specifier` resolves to. It is resolved from the
+    /// `importer` path relative to the project directory, or from the project
+    /// directory itself.
+    pub async fn request(
+        &self,
+        specifier: String,
+        importer: Option<String>,
+    ) -> Result<TransformedModule> {
+        let (respond, response) = oneshot::channel();
+        self.sender
+            .send(ModuleRequest {
+                specifier,
+                importer,
+                respond,
+            })
+            .map_err(|_| anyhow!("the module server has stopped"))?;
+        response
+            .await
+            .context("the module server dropped the request")?
+    }
+}
+
+#[turbo_tasks::function]
+async fn transformed_module(
+    root_dir: String,
+    project_dir: String,
+    browserslist_query: String,
+    specifier: String,
+    importer: Option<String>,
+) -> Result<Vc<TransformedModule>> {
+    let Some(project_relative) = project_dir.strip_prefix(&root_dir) else {
+        bail!("the project directory {project_dir} is not inside the root directory {root_dir}");
+    };
+    let project_relative = project_relative
+        .strip_prefix(MAIN_SEPARATOR)
+        .unwrap_or(project_relative)
+        .replace(MAIN_SEPARATOR, "/");
+
+    let output_fs = output_fs(project_dir);
+    let fs = project_fs(root_dir);
+    let project_path = fs.root().join(project_relative);
+
+    let env = load_env(project_path);
+    let build_output_root = output_fs.root().join(".turbopack/build".to_string());
+    let build_chunking_context = DevChunkingContext::builder(
+        project_path,
+        build_output_root,
+        build_output_root.join("chunks".to_string()),
+        build_output_root.join("assets".to_string()),
+        node_build_environment(),
+    )
+    .build();
+    let execution_context =
+        ExecutionContext::new(project_path, Vc::upcast(build_chunking_context), env);
+
+    let node_env = NodeEnv::Development.cell();
+    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
+    let asset_context =
+        get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
+    let output_root = output_fs.root().join(".turbopack/test".to_string());
+    let chunking_context: Vc<Box<dyn ChunkingContext>> = Vc::upcast(
+        DevChunkingContext::builder(
+            project_path,
+            output_root,
+            output_root.join("chunks".to_string()),
+            output_root.join("assets".to_string()),
+            compile_time_info.environment(),
+        )
+        .build(),
+    );
+
+    let origin_path = project_path.join(importer.unwrap_or_else(|| "_".to_string()));
+    let origin = PlainResolveOrigin::new(asset_context, origin_path);
+    let ty = Value::new(ReferenceType::Undefined);
+    let request = Request::parse(Value::new(specifier.clone().into()));
+    let Some(&module) = origin
+        .resolve_asset(request, origin.resolve_options(ty.clone()), ty)
+        .primary_modules()
+        .await?
+        .first()
+    else {
+        bail!("unable to resolve {specifier}");
+    };
+    let Some(chunkable) = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module).await?
+    else {
+        bail!("{specifier} doesn't resolve to a module that can be placed into chunks");
+    };
+    let chunk_item = chunkable.as_chunk_item(chunking_context);
+    let Some(ecmascript_chunk_item) =
+        Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkItem>>(chunk_item).await?
+    else {
+        bail!("{specifier} doesn't resolve to an ecmascript module");
+    };
+
+    let project_path_ref = project_path.await?;
+    let relative_path = |module: Vc<Box<dyn Module>>| {
+        let project_path_ref = &project_path_ref;
+        async move {
+            let path = module.ident().path().await?;
+            Ok::<_, anyhow::Error>(
+                project_path_ref
+                    .get_path_to(&path)
+                    .map_or_else(|| path.path.clone(), |path| path.to_string()),
+            )
+        }
+    };
+
+    let dependencies = primary_referenced_modules(module)
+        .await?
+        .iter()
+        .map(|&dependency| async move {
+            let id = match Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(dependency).await? {
+                Some(chunkable) => Some(
+                    chunkable
+                        .as_chunk_item(chunking_context)
+                        .id()
+                        .await?
+                        .to_string(),
+                ),
+                None => None,
+            };
+            Ok(ModuleDependency {
+                path: relative_path(dependency).await?,
+                id,
+            })
+        })
+        .try_join()
+        .await?;
+
+    let async_module_info =
+        async_module_info(chunking_context, module, ecmascript_chunk_item).await?;
+    let code = ecmascript_chunk_item.code(async_module_info);
+    let source_map = match *code.generate_source_map().await? {
+        Some(source_map) => Some(source_map.to_rope().await?.to_str()?.into_owned()),
+        None => None,
+    };
+
+    Ok(TransformedModule {
+        id: chunk_item.id().await?.to_string(),
+        path: relative_path(module).await?,
+        code: code.await?.source_code().to_str()?.into_owned(),
+        source_map,
+        dependencies,
+    }
+    .cell())
+}
+
+/// Returns the [AsyncModuleInfo] of the `chunk_item` of `module`, which tells
+/// whether it's an async module and which of its dependencies are. It's only
+/// known once the chunk group of the module has been built.
+async fn async_module_info(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    module: Vc<Box<dyn Module>>,
+    chunk_item: Vc<Box<dyn EcmascriptChunkItem>>,
+) -> Result<Option<Vc<AsyncModuleInfo>>> {
+    let MakeChunkGroupResult { chunks, .. } =
+        make_chunk_group(chunking_context, [module], AvailabilityInfo::Root).await?;
+    for chunk in chunks {
+        let Some(chunk) = Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk).await? else {
+            continue;
+        };
+        for &(item, async_module_info) in &chunk.chunk_content().await?.chunk_items {
+            if item.resolve().await? == chunk_item {
+                return Ok(async_module_info);
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn module_server(project_dir: &str, root_dir: &str) -> ModuleServer {
+        crate::register();
+        turbopack_env::register();
+        turbopack_node::register();
+        ModuleServerBuilder::new(
+            TurboTasks::new(MemoryBackend::default()),
+            project_dir.to_string(),
+            root_dir.to_string(),
+        )
+        .build()
+    }
+
+    #[tokio::test]
+    async fn transforms_modules_with_their_dependencies() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("index.js"),
+            "import { dep } from './dep.js';\nconsole.log(dep);\n",
+        )?;
+        fs::write(dir.path().join("dep.js"), "export const dep = 'dep';\n")?;
+        let project_dir = dir.path().to_string_lossy().to_string();
+        let server = module_server(&project_dir, &project_dir);
+
+        let index = server.request("./index.js".to_string(), None).await?;
+        assert_eq!(index.path, "index.js");
+        assert!(index.code.contains("console.log"), "{}", index.code);
+        assert!(index.source_map.is_some());
+        assert_eq!(index.dependencies.len(), 1);
+        assert_eq!(index.dependencies[0].path, "dep.js");
+
+        // Dependencies are requested with the ids the module requires them
+        // with.
+        let dep = server
+            .request("./dep.js".to_string(), Some("index.js".to_string()))
+            .await?;
+        assert_eq!(Some(&dep.id), index.dependencies[0].id.as_ref());
+        assert!(index.code.contains(&dep.id), "{}", index.code);
+
+        let error = server
+            .request("./missing.js".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{error:#}").contains("unable to resolve ./missing.js"),
+            "{error:#}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_projects_outside_of_the_root() -> Result<()> {
+        let root = tempfile::tempdir()?;
+        let project = tempfile::tempdir()?;
+        let server = module_server(
+            &project.path().to_string_lossy(),
+            &root.path().join("app").to_string_lossy(),
+        );
+
+        let error = server
+            .request("./index.js".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{error:#}").contains("is not inside the root directory"),
+            "{error:#}"
+        );
+        Ok(())
+    }
+}
@@ -2,11 +2,11 @@ use std::{env::current_dir, path::PathBuf};
 
 use anyhow::{Context, Result};
 use dunce::canonicalize;
+use owo_colors::OwoColorize;
 use turbo_tasks::Vc;
 use turbo_tasks_fs::{DiskFileSystem, FileSystem};
-
-#[turbo_tasks::value(transparent)]
-pub struct EntryRequests(pub Vec<Vc<EntryRequest>>);
+use turbopack_core::feature_flags::{FeatureFlagSet, FeatureFlags};
+use turbopack_trace_utils::module_timings::{format_module_timings, take_module_timings};
 
 #[turbo_tasks::value(shared)]
 #[derive(Clone)]
@@ -72,3 +72,57 @@ pub async fn output_fs(project_dir: String) -> Result<Vc<Box<dyn FileSystem>>> {
     disk_fs.await?.start_watching()?;
     Ok(Vc::upcast(disk_fs))
 }
+
+/// The file system of a content-addressed store, see
+/// [crate::build::TurbopackBuildBuilder::content_store].
+#[turbo_tasks::function]
+pub async fn content_store_fs(store_dir: String) -> Result<Vc<Box<dyn FileSystem>>> {
+    let disk_fs = DiskFileSystem::new("content-store".to_string(), store_dir);
+    disk_fs.await?.start_watching()?;
+    Ok(Vc::upcast(disk_fs))
+}
+
+/// Prints the `limit` modules that took the longest to build since the last
+/// call.
+pub fn print_module_timings(limit: usize) {
+    let timings = take_module_timings(limit);
+    if timings.is_empty() {
+        return;
+    }
+    println!("{} - {} slowest modules:", "event".purple(), timings.len());
+    print!("{}", format_module_timings(&timings));
+}
+
+/// Prints a report created by `reexport_chains_report`.
+pub fn print_reexport_chains(report: &str) {
+    if report.is_empty() {
+        return;
+    }
+    println!(
+        "{} - {} longest re-export chains:",
+        "event".purple(),
+        report.lines().count()
+    );
+    print!("{}", report);
+}
+
+/// Resolves the variants selected with `--feature-flag-variant`.
+pub async fn selected_feature_flag_variants(
+    feature_flags: Vc<FeatureFlags>,
+    selected: Vec<String>,
+) -> Result<Vec<Vc<FeatureFlagSet>>> {
+    let mut variants = Vec::new();
+    for enabled in selected {
+        if enabled == "all" {
+            variants.extend(feature_flags.variants().await?.iter().copied());
+        } else {
+            let enabled = enabled
+                .split('+')
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string())
+                .collect();
+            variants.push(feature_flags.variant(enabled));
+        }
+    }
+    Ok(variants)
+}
@@ -0,0 +1,159 @@
+//! Stable aliases for chunk groups.
+//!
+//! The paths of chunks change whenever their names or content hashes change,
+//! so HTML or native shells that aren't generated by turbopack can't reference
+//! them directly. An alias (e.g. `app-shell`) is emitted as a small script at
+//! a fixed path, `{alias}.js`, which loads the current chunks of its chunk
+//! group. It's served with `must-revalidate`, so clients pick up new chunks
+//! after a rebuild.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileSystemPath};
+
+use super::ChunkGroupResult;
+use crate::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets, ServingHints},
+};
+
+/// Maps aliases to the chunk groups they load.
+#[turbo_tasks::value(transparent)]
+pub struct ChunkGroupAliases(BTreeMap<String, Vc<ChunkGroupResult>>);
+
+/// Returns a [ChunkGroupAliasAsset] in `alias_root` for every alias.
+#[turbo_tasks::function]
+pub async fn chunk_group_alias_assets(
+    alias_root: Vc<FileSystemPath>,
+    output_root: Vc<FileSystemPath>,
+    aliases: Vc<ChunkGroupAliases>,
+) -> Result<Vc<OutputAssets>> {
+    let mut assets = Vec::new();
+    for (alias, &chunk_group) in aliases.await?.iter() {
+        if alias.is_empty() || alias.contains(['/', '\\']) || alias.starts_with('.') {
+            bail!("invalid chunk group alias {:?}", alias);
+        }
+        assets.push(Vc::upcast(ChunkGroupAliasAsset::new(
+            alias_root.join(format!("{alias}.js")),
+            output_root,
+            chunk_group,
+        )));
+    }
+    Ok(Vc::cell(assets))
+}
+
+#[derive(Serialize)]
+struct AliasedChunk {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    integrity: Option<String>,
+}
+
+/// A script at a stable path that loads the chunks of a chunk group, by
+/// injecting a `<link>` tag for every CSS chunk and a `<script>` tag for every
+/// JS chunk. The chunks are loaded relative to the URL of the script.
+#[turbo_tasks::value]
+pub struct ChunkGroupAliasAsset {
+    path: Vc<FileSystemPath>,
+    output_root: Vc<FileSystemPath>,
+    chunk_group: Vc<ChunkGroupResult>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkGroupAliasAsset {
+    /// Creates a new alias that will be emitted at `path`.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        output_root: Vc<FileSystemPath>,
+        chunk_group: Vc<ChunkGroupResult>,
+    ) -> Vc<Self> {
+        ChunkGroupAliasAsset {
+            path,
+            output_root,
+            chunk_group,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for ChunkGroupAliasAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<OutputAssets>> {
+        Ok(self.chunk_group.await?.assets)
+    }
+
+    #[turbo_tasks::function]
+    fn serving_hints(&self) -> Vc<ServingHints> {
+        ServingHints::must_revalidate()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ChunkGroupAliasAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let chunk_group = self.chunk_group.await?;
+        let integrity = match chunk_group.integrity {
+            Some(integrity) => Some(integrity.await?),
+            None => None,
+        };
+        let output_root = self.output_root.await?;
+        let alias_dir = self.path.parent().await?;
+
+        let mut css = Vec::new();
+        let mut js = Vec::new();
+        for &asset in chunk_group.assets.await?.iter() {
+            let path = asset.ident().path().await?;
+            let Some(url) = alias_dir.get_relative_path_to(&path) else {
+                continue;
+            };
+            let integrity = integrity.as_ref().and_then(|integrity| {
+                output_root
+                    .get_path_to(&path)
+                    .and_then(|path| integrity.get(path).cloned())
+            });
+            let chunk = AliasedChunk { url, integrity };
+            if path.path.ends_with(".css") {
+                css.push(chunk);
+            } else if path.path.ends_with(".js") {
+                js.push(chunk);
+            }
+        }
+
+        let code = format!(
+            r#"(() => {{
+    const base = document.currentScript.src;
+    for (const {{ url, integrity }} of {css}) {{
+        const link = document.createElement("link");
+        link.rel = "stylesheet";
+        link.href = new URL(url, base).href;
+        if (integrity) link.integrity = integrity;
+        document.head.appendChild(link);
+    }}
+    for (const {{ url, integrity }} of {js}) {{
+        const script = document.createElement("script");
+        script.src = new URL(url, base).href;
+        // Evaluate the chunks in order, like the tags of a page would be.
+        script.async = false;
+        if (integrity) script.integrity = integrity;
+        document.head.appendChild(script);
+    }}
+}})();
+"#,
+            css = serde_json::to_string(&css)?,
+            js = serde_json::to_string(&js)?,
+        );
+        Ok(AssetContent::file(File::from(code).into()))
+    }
+}
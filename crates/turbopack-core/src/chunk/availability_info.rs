@@ -1,5 +1,8 @@
+use std::fmt::{self, Display};
+
 use anyhow::Result;
-use turbo_tasks::Vc;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
 
 use super::available_chunk_items::{AvailableChunkItemInfoMap, AvailableChunkItems};
 
@@ -28,6 +31,42 @@ impl AvailabilityInfo {
         }
     }
 
+    /// Describes which modules are assumed to be available, e.g. to debug
+    /// "module not found" errors in nested async chunks, which occur when a
+    /// module is assumed to be available but wasn't loaded.
+    pub async fn dump(&self) -> Result<AvailabilityDump> {
+        let Some(available_chunk_items) = self.available_chunk_items() else {
+            return Ok(AvailabilityDump {
+                tracked: !matches!(self, AvailabilityInfo::Untracked),
+                levels: Vec::new(),
+            });
+        };
+        let levels = available_chunk_items
+            .levels()
+            .await?
+            .iter()
+            .map(|&level| async move {
+                let modules = level
+                    .await?
+                    .iter()
+                    .map(|(&chunk_item, info)| async move {
+                        Ok(AvailableModuleDump {
+                            ident: chunk_item.asset_ident().to_string().await?.clone_value(),
+                            is_async: info.is_async,
+                        })
+                    })
+                    .try_join()
+                    .await?;
+                Ok(AvailabilityLevelDump { modules })
+            })
+            .try_join()
+            .await?;
+        Ok(AvailabilityDump {
+            tracked: true,
+            levels,
+        })
+    }
+
     pub async fn with_chunk_items(
         self,
         chunk_items: Vc<AvailableChunkItemInfoMap>,
@@ -48,3 +87,65 @@ impl AvailabilityInfo {
         })
     }
 }
+
+/// The modules that are assumed to be available in a chunk group, see
+/// [AvailabilityInfo::dump].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityDump {
+    /// Whether availability is tracked at all. When it isn't, no modules are
+    /// assumed to be available.
+    pub tracked: bool,
+    /// Every chunk group along the chain of async imports makes the modules of
+    /// its chunks available to the chunk groups it imports. The modules of
+    /// the outermost chunk group come first.
+    pub levels: Vec<AvailabilityLevelDump>,
+}
+
+/// The modules made available by a single chunk group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailabilityLevelDump {
+    pub modules: Vec<AvailableModuleDump>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AvailableModuleDump {
+    pub ident: String,
+    /// Whether the module is an async module.
+    pub is_async: bool,
+}
+
+impl AvailabilityDump {
+    /// Returns the index of the level that made the module with `ident`
+    /// available, if any.
+    pub fn level_of(&self, ident: &str) -> Option<usize> {
+        self.levels
+            .iter()
+            .position(|level| level.modules.iter().any(|module| module.ident == ident))
+    }
+}
+
+impl Display for AvailabilityDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.tracked {
+            return writeln!(f, "availability is not tracked");
+        }
+        if self.levels.is_empty() {
+            return writeln!(f, "no modules are available");
+        }
+        for (i, level) in self.levels.iter().enumerate() {
+            writeln!(
+                f,
+                "level {i}: {} modules made available by an ancestor chunk group",
+                level.modules.len()
+            )?;
+            for module in level.modules.iter() {
+                if module.is_async {
+                    writeln!(f, "  {} (async)", module.ident)?;
+                } else {
+                    writeln!(f, "  {}", module.ident)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
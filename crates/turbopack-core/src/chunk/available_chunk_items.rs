@@ -20,6 +20,9 @@ pub struct OptionAvailableChunkItemInfo(Option<AvailableChunkItemInfo>);
 #[turbo_tasks::value(transparent)]
 pub struct AvailableChunkItemInfoMap(IndexMap<Vc<Box<dyn ChunkItem>>, AvailableChunkItemInfo>);
 
+#[turbo_tasks::value(transparent)]
+pub struct AvailableChunkItemInfoMaps(Vec<Vc<AvailableChunkItemInfoMap>>);
+
 /// Allows to gather information about which assets are already available.
 /// Adding more roots will form a linked list like structure to allow caching
 /// `include` queries.
@@ -86,6 +89,19 @@ impl AvailableChunkItems {
         Ok(Vc::cell(hasher.finish()))
     }
 
+    /// The chunk items of every link of the list, starting with the ones that
+    /// were added first, i.e. by the outermost chunk group.
+    #[turbo_tasks::function]
+    pub async fn levels(self: Vc<Self>) -> Result<Vc<AvailableChunkItemInfoMaps>> {
+        let this = self.await?;
+        let mut levels = match this.parent {
+            Some(parent) => parent.levels().await?.clone_value(),
+            None => Vec::new(),
+        };
+        levels.push(this.chunk_items);
+        Ok(Vc::cell(levels))
+    }
+
     #[turbo_tasks::function]
     pub async fn get(
         self: Vc<Self>,
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{glob::Glob, FileSystemPath};
+
+use super::chunking::package_name;
+use crate::ident::AssetIdent;
+
+/// A named group of modules that are placed into their own chunks, similar to
+/// `splitChunks.cacheGroups` in webpack, e.g. a `framework` group for `react`
+/// and `react-dom` that rarely changes.
+///
+/// A module belongs to the group when it matches any of the [path_globs] or
+/// [package_names].
+///
+/// [path_globs]: ChunkCacheGroup::path_globs
+/// [package_names]: ChunkCacheGroup::package_names
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Hash, PartialOrd, Ord)]
+pub struct ChunkCacheGroup {
+    /// The name of the group. It's part of the names of the chunks created
+    /// for the group.
+    pub name: String,
+    /// Globs matched against the path of a module relative to the context
+    /// path, e.g. `src/components/**`.
+    pub path_globs: Vec<String>,
+    /// Names of the `node_modules` packages whose modules belong to the group,
+    /// e.g. `react` or `@scope/package`.
+    pub package_names: Vec<String>,
+}
+
+impl ChunkCacheGroup {
+    pub fn new(name: impl Into<String>) -> Self {
+        ChunkCacheGroup {
+            name: name.into(),
+            path_globs: Vec::new(),
+            package_names: Vec::new(),
+        }
+    }
+
+    pub fn path_glob(mut self, glob: impl Into<String>) -> Self {
+        self.path_globs.push(glob.into());
+        self
+    }
+
+    pub fn package_name(mut self, package_name: impl Into<String>) -> Self {
+        self.package_names.push(package_name.into());
+        self
+    }
+}
+
+/// The cache groups of a chunking context. A module belongs to the first group
+/// it matches.
+#[turbo_tasks::value(transparent)]
+pub struct ChunkCacheGroups(Vec<ChunkCacheGroup>);
+
+#[turbo_tasks::value_impl]
+impl ChunkCacheGroups {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(Vec::new())
+    }
+
+    /// Returns the name of the first group the module with the given `ident`
+    /// belongs to. Path globs are matched relative to `context_path`.
+    #[turbo_tasks::function]
+    pub async fn group_name(
+        self: Vc<Self>,
+        context_path: Vc<FileSystemPath>,
+        ident: Vc<AssetIdent>,
+    ) -> Result<Vc<Option<String>>> {
+        let groups = self.await?;
+        if groups.is_empty() {
+            return Ok(Vc::cell(None));
+        }
+        let context_path = context_path.await?;
+        let path = ident.path().await?;
+        let relative_path = context_path.get_path_to(&path);
+        let ident_string = ident.to_string().await?;
+        let package_name = package_name(&ident_string);
+        for group in groups.iter() {
+            if !package_name.is_empty()
+                && group.package_names.iter().any(|name| name == package_name)
+            {
+                return Ok(Vc::cell(Some(group.name.clone())));
+            }
+            if let Some(relative_path) = relative_path {
+                for glob in group.path_globs.iter() {
+                    if Glob::new(glob.clone()).await?.execute(relative_path) {
+                        return Ok(Vc::cell(Some(group.name.clone())));
+                    }
+                }
+            }
+        }
+        Ok(Vc::cell(None))
+    }
+}
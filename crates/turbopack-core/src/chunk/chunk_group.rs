@@ -6,30 +6,280 @@ use indexmap::{IndexMap, IndexSet};
 use turbo_tasks::{TryFlatJoinIterExt, TryJoinIterExt, Value, Vc};
 
 use super::{
-    availability_info::AvailabilityInfo, available_chunk_items::AvailableChunkItemInfo,
-    chunk_content, chunking::make_chunks, AsyncModuleInfo, Chunk, ChunkContentResult, ChunkItem,
-    ChunkingContext,
+    availability_info::AvailabilityInfo,
+    available_chunk_items::AvailableChunkItemInfo,
+    chunk_content,
+    chunking::{make_chunks, plan_chunks, MakeChunksResult},
+    peer_dependencies::check_peer_dependencies,
+    plan::{async_chunk_group_plans, ChunkGroupPlan, ChunkPlan},
+    AsyncModuleInfo, Chunk, ChunkContentResult, ChunkItem, ChunkableModule, ChunkingContext,
+};
+use crate::{
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    reference::ModuleReference,
 };
-use crate::{module::Module, output::OutputAssets, reference::ModuleReference};
 
 pub struct MakeChunkGroupResult {
     pub chunks: Vec<Vc<Box<dyn Chunk>>>,
+    /// The chunks of `chunks` that don't need to be loaded before the entries
+    /// are evaluated, see [super::chunking::MakeChunksResult::deferred_chunks].
+    pub deferred_chunks: Vec<Vc<Box<dyn Chunk>>>,
+    pub availability_info: AvailabilityInfo,
+}
+
+pub struct PlanChunkGroupResult {
+    pub plan: Vc<ChunkGroupPlan>,
     pub availability_info: AvailabilityInfo,
 }
 
+/// The chunk items of a chunk group with their [AsyncModuleInfo], and the
+/// modules that are loaded asynchronously from it.
+struct ChunkGroupContent {
+    chunk_items: IndexMap<Vc<Box<dyn ChunkItem>>, Option<Vc<AsyncModuleInfo>>>,
+    async_modules: IndexSet<Vc<Box<dyn ChunkableModule>>>,
+    async_chunk_group_names: IndexMap<Vc<Box<dyn ChunkableModule>>, String>,
+    external_module_references: IndexSet<Vc<Box<dyn ModuleReference>>>,
+    availability_info: AvailabilityInfo,
+}
+
 /// Creates a chunk group from a set of entries.
 pub async fn make_chunk_group(
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
     availability_info: AvailabilityInfo,
 ) -> Result<MakeChunkGroupResult> {
+    let ChunkGroupChunks { result, .. } =
+        chunk_group_chunks(chunking_context, entries, availability_info, false).await?;
+    Ok(result)
+}
+
+/// Computes the chunks [make_chunk_group] would create for a set of entries,
+/// including the chunks of the async chunk loaders, without generating their
+/// code. `generate_chunk` turns a chunk into its output asset, which names the
+/// chunk in the plan. The plan links to the plans of the async chunk groups.
+pub async fn plan_chunk_group(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
+    availability_info: AvailabilityInfo,
+    generate_chunk: impl Fn(Vc<Box<dyn Chunk>>) -> Vc<Box<dyn OutputAsset>>,
+) -> Result<PlanChunkGroupResult> {
+    let assumed_available = availability_info;
+    let ChunkGroupChunks {
+        result:
+            MakeChunkGroupResult {
+                chunks,
+                availability_info,
+                ..
+            },
+        mut plans,
+        referenced_output_assets,
+        async_modules,
+    } = chunk_group_chunks(chunking_context, entries, availability_info, true).await?;
+
+    let output_root = chunking_context.output_root().await?;
+    for (plan, &chunk) in plans.iter_mut().zip(chunks.iter()) {
+        let path = generate_chunk(chunk).ident().path().await?;
+        plan.path = output_root.get_path_to(&path).map(|path| path.to_string());
+    }
+    let mut referenced_assets = Vec::new();
+    for output_assets in referenced_output_assets {
+        for &asset in output_assets.await?.iter() {
+            let path = asset.ident().path().await?;
+            referenced_assets.extend(output_root.get_path_to(&path).map(|path| path.to_string()));
+        }
+    }
+    let async_chunk_groups =
+        async_chunk_group_plans(chunking_context, async_modules, availability_info).await?;
+
+    Ok(PlanChunkGroupResult {
+        plan: ChunkGroupPlan {
+            chunks: plans,
+            referenced_assets,
+            async_chunk_groups,
+            availability_info: assumed_available,
+        }
+        .cell(),
+        availability_info,
+    })
+}
+
+/// The chunks of a chunk group, see [chunk_group_chunks].
+struct ChunkGroupChunks {
+    result: MakeChunkGroupResult,
+    /// The plans of [MakeChunkGroupResult::chunks], in the same order. Empty
+    /// unless the chunks are planned.
+    plans: Vec<ChunkPlan>,
+    /// The output assets referenced by the chunks.
+    referenced_output_assets: Vec<Vc<OutputAssets>>,
+    async_modules: IndexSet<Vc<Box<dyn ChunkableModule>>>,
+}
+
+/// Creates the chunks of a chunk group from a set of entries. When `plan` is
+/// set, the chunks are also described by [ChunkGroupChunks::plans], and issues
+/// are only reported when the chunk group is created for real.
+async fn chunk_group_chunks(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
+    availability_info: AvailabilityInfo,
+    plan: bool,
+) -> Result<ChunkGroupChunks> {
+    let ChunkGroupContent {
+        chunk_items,
+        async_modules,
+        async_chunk_group_names,
+        external_module_references,
+        availability_info,
+    } = chunk_group_content(chunking_context, entries, availability_info).await?;
+
+    // Report packages that are placed together with a peer dependency outside
+    // of their declared range
+    if !plan && *chunking_context.check_peer_dependencies().await? {
+        check_peer_dependencies(Vc::cell(chunk_items.keys().copied().collect())).await?;
+    }
+
+    // Insert async chunk loaders for every referenced async module
+    let async_loaders = async_modules
+        .iter()
+        .map(|&module| match async_chunk_group_names.get(&module) {
+            Some(name) => chunking_context.named_async_loader_chunk_item(
+                module,
+                Value::new(availability_info),
+                name.clone(),
+            ),
+            None => chunking_context.async_loader_chunk_item(module, Value::new(availability_info)),
+        })
+        .collect::<Vec<_>>();
+    let async_loader_chunk_items = async_loaders.iter().map(|&chunk_item| (chunk_item, None));
+
+    // And also add output assets referenced by async chunk loaders
+    let async_loader_references = async_loaders
+        .iter()
+        .map(|&loader| loader.references())
+        .try_join()
+        .await?;
+    let async_loader_external_module_references = async_loader_references
+        .iter()
+        .flat_map(|references| references.iter().copied())
+        .collect();
+    let async_loader_output_assets =
+        references_to_output_assets(async_loader_external_module_references).await?;
+    let output_assets = references_to_output_assets(external_module_references).await?;
+
+    let max_chunks = chunking_context
+        .max_chunks_per_chunk_group()
+        .await?
+        .map(|max_chunks| max_chunks as usize);
+
+    // Pass async chunk loaders to chunking algorithm
+    // We want them to be separate since they are specific to this chunk group due
+    // to available chunk items differing. They leave at least one chunk for the
+    // other chunk items.
+    let (
+        MakeChunksResult {
+            chunks: async_loader_chunks,
+            ..
+        },
+        async_loader_plans,
+    ) = chunks_and_plans(
+        plan,
+        chunking_context,
+        async_loader_chunk_items,
+        "async-loader-",
+        async_loader_output_assets,
+        max_chunks.map(|max_chunks| remaining_chunks(max_chunks, 1)),
+    )
+    .await?;
+
+    // Pass chunk items to chunking algorithm, within the chunks left by the
+    // async loader chunks
+    let (
+        MakeChunksResult {
+            mut chunks,
+            deferred_chunks,
+        },
+        mut plans,
+    ) = chunks_and_plans(
+        plan,
+        chunking_context,
+        chunk_items,
+        "",
+        output_assets,
+        max_chunks.map(|max_chunks| remaining_chunks(max_chunks, async_loader_chunks.len())),
+    )
+    .await?;
+
+    // concatenate chunks
+    chunks.extend(async_loader_chunks);
+    plans.extend(async_loader_plans);
+
+    Ok(ChunkGroupChunks {
+        result: MakeChunkGroupResult {
+            chunks,
+            deferred_chunks,
+            availability_info,
+        },
+        plans,
+        referenced_output_assets: vec![output_assets, async_loader_output_assets],
+        async_modules,
+    })
+}
+
+/// Calls [plan_chunks] when `plan` is set, or [make_chunks] otherwise, which
+/// doesn't describe the chunks.
+async fn chunks_and_plans(
+    plan: bool,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunk_items: impl IntoIterator<Item = (Vc<Box<dyn ChunkItem>>, Option<Vc<AsyncModuleInfo>>)>,
+    key_prefix: &str,
+    referenced_output_assets: Vc<OutputAssets>,
+    max_chunks: Option<usize>,
+) -> Result<(MakeChunksResult, Vec<ChunkPlan>)> {
+    if plan {
+        plan_chunks(
+            chunking_context,
+            chunk_items,
+            key_prefix,
+            referenced_output_assets,
+            max_chunks,
+        )
+        .await
+    } else {
+        Ok((
+            make_chunks(
+                chunking_context,
+                chunk_items,
+                key_prefix,
+                referenced_output_assets,
+                max_chunks,
+            )
+            .await?,
+            Vec::new(),
+        ))
+    }
+}
+
+/// The number of chunks left of `max_chunks` when `used` chunks have been
+/// created. At least one chunk is left, so a chunk group with async imports
+/// needs at least two chunks.
+fn remaining_chunks(max_chunks: usize, used: usize) -> usize {
+    max_chunks.saturating_sub(used).max(1)
+}
+
+async fn chunk_group_content(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
+    availability_info: AvailabilityInfo,
+) -> Result<ChunkGroupContent> {
     let ChunkContentResult {
         chunk_items,
         async_modules,
+        async_chunk_group_names,
         external_module_references,
         forward_edges_inherit_async,
         local_back_edges_inherit_async,
         available_async_modules_back_edges_inherit_async,
+        split_point_back_edges_inherit_async,
     } = chunk_content(chunking_context, entries, availability_info).await?;
 
     // Find all local chunk items that are self async
@@ -43,10 +293,12 @@ pub async fn make_chunk_group(
         .try_flat_join()
         .await?;
 
-    // Get all available async modules and concatenate with local async modules
+    // Get all available async modules and split points and concatenate with local
+    // async modules
     let mut async_chunk_items = available_async_modules_back_edges_inherit_async
         .keys()
         .copied()
+        .chain(split_point_back_edges_inherit_async.keys().copied())
         .chain(self_async_children.into_iter())
         .map(|chunk_item| (chunk_item, AutoSet::<Vc<Box<dyn ChunkItem>>>::new()))
         .collect::<IndexMap<_, _>>();
@@ -58,10 +310,14 @@ pub async fn make_chunk_group(
             break;
         };
         // The first few entries are from
-        // available_async_modules_back_edges_inherit_async and need to use that map,
-        // all other entries are local
-        let map = if i < available_async_modules_back_edges_inherit_async.len() {
+        // available_async_modules_back_edges_inherit_async and
+        // split_point_back_edges_inherit_async and need to use these maps, all
+        // other entries are local
+        let available_len = available_async_modules_back_edges_inherit_async.len();
+        let map = if i < available_len {
             &available_async_modules_back_edges_inherit_async
+        } else if i < available_len + split_point_back_edges_inherit_async.len() {
+            &split_point_back_edges_inherit_async
         } else {
             &local_back_edges_inherit_async
         };
@@ -85,6 +341,10 @@ pub async fn make_chunk_group(
 
     // Insert AsyncModuleInfo for every async module
     for (async_item, referenced_async_modules) in async_chunk_items {
+        // Split points are placed in their own chunk group
+        if split_point_back_edges_inherit_async.contains_key(&async_item) {
+            continue;
+        }
         let referenced_async_modules =
             if let Some(references) = forward_edges_inherit_async.get(&async_item) {
                 references
@@ -118,51 +378,11 @@ pub async fn make_chunk_group(
         availability_info.with_chunk_items(map).await?
     };
 
-    // Insert async chunk loaders for every referenced async module
-    let async_loaders = async_modules
-        .into_iter()
-        .map(|module| {
-            chunking_context.async_loader_chunk_item(module, Value::new(availability_info))
-        })
-        .collect::<Vec<_>>();
-    let async_loader_chunk_items = async_loaders.iter().map(|&chunk_item| (chunk_item, None));
-
-    // And also add output assets referenced by async chunk loaders
-    let async_loader_references = async_loaders
-        .iter()
-        .map(|&loader| loader.references())
-        .try_join()
-        .await?;
-    let async_loader_external_module_references = async_loader_references
-        .iter()
-        .flat_map(|references| references.iter().copied())
-        .collect();
-
-    // Pass chunk items to chunking algorithm
-    let mut chunks = make_chunks(
-        chunking_context,
+    Ok(ChunkGroupContent {
         chunk_items,
-        "",
-        references_to_output_assets(external_module_references).await?,
-    )
-    .await?;
-
-    // Pass async chunk loaders to chunking algorithm
-    // We want them to be separate since they are specific to this chunk group due
-    // to available chunk items differing
-    let async_loader_chunks = make_chunks(
-        chunking_context,
-        async_loader_chunk_items,
-        "async-loader-",
-        references_to_output_assets(async_loader_external_module_references).await?,
-    )
-    .await?;
-
-    // concatenate chunks
-    chunks.extend(async_loader_chunks);
-
-    Ok(MakeChunkGroupResult {
-        chunks,
+        async_modules,
+        async_chunk_group_names,
+        external_module_references,
         availability_info,
     })
 }
@@ -184,3 +404,23 @@ async fn references_to_output_assets(
         .collect::<Vec<_>>();
     Ok(OutputAssets::new(output_assets))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_chunks() {
+        // The async loader chunks leave one chunk for the other chunk items,
+        // which then use the chunks left by the async loader chunks.
+        for max_chunks in 2..10 {
+            for async_loader_chunks in 0..=remaining_chunks(max_chunks, 1) {
+                assert!(
+                    async_loader_chunks + remaining_chunks(max_chunks, async_loader_chunks)
+                        <= max_chunks
+                );
+            }
+        }
+        assert_eq!(remaining_chunks(1, 1), 1);
+    }
+}
@@ -4,7 +4,7 @@ use std::{
     pin::Pin,
 };
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use futures::Future;
 use indexmap::IndexMap;
 use once_cell::sync::Lazy;
@@ -12,18 +12,91 @@ use regex::Regex;
 use tracing::Level;
 use turbo_tasks::{ReadRef, TryJoinIterExt, ValueToString, Vc};
 
-use super::{AsyncModuleInfo, Chunk, ChunkItem, ChunkType, ChunkingContext};
-use crate::output::OutputAssets;
+use super::{
+    cache_group::ChunkCacheGroups, plan::ChunkPlan, policy::ChunkingPolicy,
+    usage_profile::UsageProfile, AsyncModuleInfo, Chunk, ChunkItem, ChunkItemExt, ChunkItems,
+    ChunkType, ChunkingContext,
+};
+use crate::{
+    issue::{
+        oversized_module::OversizedModuleIssue, usage_profile::UsageProfileChunkingIssue, IssueExt,
+    },
+    module::Module,
+    output::OutputAssets,
+};
+
+/// The chunks created by [make_chunks].
+pub struct MakeChunksResult {
+    pub chunks: Vec<Vc<Box<dyn Chunk>>>,
+    /// The chunks of `chunks` that only contain modules that haven't been
+    /// executed according to the [UsageProfile]. They don't need to be loaded
+    /// before the entries of the chunk group are evaluated.
+    pub deferred_chunks: Vec<Vc<Box<dyn Chunk>>>,
+}
 
 /// Creates chunks based on heuristics for the passed `chunk_items`. Also
-/// attaches `referenced_output_assets` to the first chunk.
+/// attaches `referenced_output_assets` to the first chunk, or to the first app
+/// chunk when vendor chunks are isolated. Creates at most
+/// `max_chunks` chunks, when possible.
 #[tracing::instrument(level = Level::TRACE, skip_all)]
 pub async fn make_chunks(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunk_items: impl IntoIterator<Item = (Vc<Box<dyn ChunkItem>>, Option<Vc<AsyncModuleInfo>>)>,
+    key_prefix: &str,
+    referenced_output_assets: Vc<OutputAssets>,
+    max_chunks: Option<usize>,
+) -> Result<MakeChunksResult> {
+    split_chunks(
+        chunking_context,
+        chunk_items,
+        key_prefix,
+        referenced_output_assets,
+        max_chunks,
+        None,
+    )
+    .await
+}
+
+/// Like [make_chunks], but also describes each of the chunks created, in the
+/// same order as [MakeChunksResult::chunks]. The chunks don't have a
+/// [ChunkPlan::path] yet, as they haven't been turned into output assets.
+/// Usage profile issues aren't reported, as the chunks are only planned.
+#[tracing::instrument(level = Level::TRACE, skip_all)]
+pub async fn plan_chunks(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunk_items: impl IntoIterator<Item = (Vc<Box<dyn ChunkItem>>, Option<Vc<AsyncModuleInfo>>)>,
+    key_prefix: &str,
+    referenced_output_assets: Vc<OutputAssets>,
+    max_chunks: Option<usize>,
+) -> Result<(MakeChunksResult, Vec<ChunkPlan>)> {
+    let mut plan = Vec::new();
+    let result = split_chunks(
+        chunking_context,
+        chunk_items,
+        key_prefix,
+        referenced_output_assets,
+        max_chunks,
+        Some(&mut plan),
+    )
+    .await?;
+    Ok((result, plan))
+}
+
+fn no_chunks() -> MakeChunksResult {
+    MakeChunksResult {
+        chunks: Vec::new(),
+        deferred_chunks: Vec::new(),
+    }
+}
+
+async fn split_chunks(
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     chunk_items: impl IntoIterator<Item = (Vc<Box<dyn ChunkItem>>, Option<Vc<AsyncModuleInfo>>)>,
     key_prefix: &str,
     mut referenced_output_assets: Vc<OutputAssets>,
-) -> Result<Vec<Vc<Box<dyn Chunk>>>> {
+    max_chunks: Option<usize>,
+    plan: Option<&mut Vec<ChunkPlan>>,
+) -> Result<MakeChunksResult> {
     let chunk_items = chunk_items
         .into_iter()
         .map(|(chunk_item, async_info)| async move {
@@ -32,12 +105,32 @@ pub async fn make_chunks(
         })
         .try_join()
         .await?;
+    let module_size_warning_threshold = *chunking_context.module_size_warning_threshold().await?;
+    let size_limits = ChunkSizeLimits::new(
+        *chunking_context.min_chunk_size().await?,
+        *chunking_context.max_chunk_size().await?,
+    )?;
+    let split_node_modules = *chunking_context.split_node_modules().await?;
+    let isolate_vendor_chunks = *chunking_context.isolate_vendor_chunks().await?;
+    let cache_groups = chunking_context.cache_groups().resolve().await?;
+    let policy = *chunking_context.chunking_policy().await?;
+    let usage_profile = match *chunking_context.usage_profile().await? {
+        Some(usage_profile) => Some(usage_profile.await?),
+        None => None,
+    };
+    let all_chunk_items: Vc<ChunkItems> = Vc::cell(
+        chunk_items
+            .iter()
+            .map(|&(_, chunk_item, _)| chunk_item)
+            .collect(),
+    );
     let mut map = IndexMap::<_, Vec<_>>::new();
     for (ty, chunk_item, async_info) in chunk_items {
         map.entry(ty).or_default().push((chunk_item, async_info));
     }
 
     let mut chunks = Vec::new();
+    let mut profile_issue: Option<UsageProfileChunkingIssue> = None;
     for (ty, chunk_items) in map {
         let ty_name = ty.to_string().await?;
 
@@ -59,19 +152,116 @@ pub async fn make_chunks(
             ty,
             chunking_context,
             chunks: &mut chunks,
-            referenced_output_assets: &mut referenced_output_assets,
-            empty_referenced_output_assets: OutputAssets::empty().resolve().await?,
+            size_limits,
+            split_node_modules,
+            isolate_vendor_chunks,
+            policy,
+            deferred: false,
+            group: None,
+            policy_split_keys: Vec::new(),
+            chunk_count: 0,
         };
 
-        app_vendors_split(
-            chunk_items,
-            format!("{key_prefix}{ty_name}"),
-            &mut split_context,
-        )
+        let name = format!("{key_prefix}{ty_name}");
+        let chunk_items =
+            cache_groups_split(chunk_items, &name, cache_groups, &mut split_context).await?;
+        if let Some(usage_profile) = &usage_profile {
+            let issue =
+                profile_guided_split(chunk_items, name, usage_profile, &mut split_context).await?;
+            match (&mut profile_issue, issue) {
+                (Some(total), Some(issue)) => total.extend(issue),
+                (None, issue) => profile_issue = issue,
+                (_, None) => {}
+            }
+        } else {
+            app_vendors_split(chunk_items, name, &mut split_context).await?;
+        }
+    }
+
+    // A single issue summarizes the changes to all chunk types, and plans
+    // don't report changes that don't happen.
+    if let (Some(issue), None) = (profile_issue, &plan) {
+        issue.cell().emit();
+    }
+
+    if let Some(max_chunks) = max_chunks {
+        merge_chunks(&mut chunks, max_chunks, isolate_vendor_chunks, |a, b| {
+            let same_origin = a.origin.can_merge_with(&b.origin);
+            let (modules_a, modules_b) = (a.modules.clone(), b.modules.clone());
+            async move {
+                Ok(same_origin
+                    && can_be_in_same_chunk(chunking_context, &modules_a, &modules_b).await?)
+            }
+        })
         .await?;
     }
 
-    Ok(chunks)
+    if let Some(plan) = plan {
+        plan.extend(
+            chunks
+                .iter()
+                .map(|chunk| chunk_plan(&chunk.chunk_items, &chunk.key)),
+        );
+    }
+
+    // Vendor chunks must not change when the assets referenced by app code do.
+    let referenced_output_assets_index = if isolate_vendor_chunks {
+        chunks
+            .iter()
+            .position(|chunk| !chunk.is_vendor())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let empty_referenced_output_assets = OutputAssets::empty().resolve().await?;
+    let mut result = no_chunks();
+    for (
+        index,
+        PendingChunk {
+            ty,
+            chunk_items,
+            origin,
+            ..
+        },
+    ) in chunks.into_iter().enumerate()
+    {
+        let chunk = ty.chunk(
+            chunking_context,
+            chunk_items
+                .iter()
+                .map(|&(chunk_item, async_info, ..)| (chunk_item, async_info))
+                .collect(),
+            if index == referenced_output_assets_index {
+                replace(
+                    &mut referenced_output_assets,
+                    empty_referenced_output_assets,
+                )
+            } else {
+                empty_referenced_output_assets
+            },
+        );
+        if let Some(limit) = module_size_warning_threshold {
+            for &(chunk_item, _, size, _) in &chunk_items {
+                if size as u64 > limit {
+                    OversizedModuleIssue {
+                        chunk_item,
+                        size: size as u64,
+                        limit,
+                        chunk,
+                        chunk_items: all_chunk_items,
+                    }
+                    .cell()
+                    .emit();
+                }
+            }
+        }
+        result.chunks.push(chunk);
+        if origin.deferred {
+            result.deferred_chunks.push(chunk);
+        }
+    }
+
+    Ok(result)
 }
 
 type ChunkItemWithInfo = (
@@ -81,25 +271,110 @@ type ChunkItemWithInfo = (
     ReadRef<String>,
 );
 
+/// A chunk that is created once all chunk items have been split, so that
+/// chunks can still be merged to stay within the maximum number of chunks.
+struct PendingChunk {
+    ty: Vc<Box<dyn ChunkType>>,
+    key: String,
+    chunk_items: Vec<ChunkItemWithInfo>,
+    /// The modules of the chunk items, which are passed to
+    /// [ChunkingContext::can_be_in_same_chunk] when chunks would be merged.
+    modules: Vec<Vc<Box<dyn Module>>>,
+    origin: ChunkOrigin,
+}
+
+/// Describes how the chunk items of a chunk have been split from the others,
+/// which decides which chunks can be merged again.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct ChunkOrigin {
+    /// The cache group or the chunk named by the chunking policy that the
+    /// chunk belongs to.
+    group: Option<String>,
+    /// Whether the chunk only contains modules that haven't been executed
+    /// according to the usage profile.
+    deferred: bool,
+    /// Whether the chunking policy asked to split the chunk items the chunk
+    /// has been split from.
+    policy_split: bool,
+}
+
+impl ChunkOrigin {
+    /// Chunks of different groups are kept apart like they are when splitting,
+    /// merging deferred chunks would load their modules eagerly, and the
+    /// chunking policy has the final say over the splits it asked for.
+    fn can_merge_with(&self, other: &Self) -> bool {
+        self.group == other.group
+            && self.deferred == other.deferred
+            && !self.policy_split
+            && !other.policy_split
+    }
+}
+
+impl PendingChunk {
+    fn size(&self) -> usize {
+        self.chunk_items.iter().map(|&(_, _, size, _)| size).sum()
+    }
+
+    /// Whether the chunk only contains modules in `node_modules`.
+    fn is_vendor(&self) -> bool {
+        !self.chunk_items.is_empty()
+            && self
+                .chunk_items
+                .iter()
+                .all(|(_, _, _, ident)| !is_app_code(ident))
+    }
+}
+
 struct SplitContext<'a> {
     ty: Vc<Box<dyn ChunkType>>,
     chunking_context: Vc<Box<dyn ChunkingContext>>,
-    chunks: &'a mut Vec<Vc<Box<dyn Chunk>>>,
-    referenced_output_assets: &'a mut Vc<OutputAssets>,
-    empty_referenced_output_assets: Vc<OutputAssets>,
+    chunks: &'a mut Vec<PendingChunk>,
+    size_limits: ChunkSizeLimits,
+    /// Whether node_modules are split into vendor chunks apart from app code.
+    split_node_modules: bool,
+    /// Whether small vendor chunks are kept apart from app code, see
+    /// [ChunkingContext::isolate_vendor_chunks].
+    isolate_vendor_chunks: bool,
+    /// Customizes the chunk assignment.
+    policy: Option<Vc<Box<dyn ChunkingPolicy>>>,
+    /// Whether the chunks created are deferred, see
+    /// [MakeChunksResult::deferred_chunks].
+    deferred: bool,
+    /// The cache group or named chunk that the chunks created belong to.
+    group: Option<String>,
+    /// The keys of the chunk items the chunking policy asked to split.
+    policy_split_keys: Vec<String>,
+    chunk_count: usize,
 }
 
 /// Handle chunk items based on their total size. If the total size is too
 /// small, they will be pushed into `remaining`, if possible. If the total size
 /// is too large, it will return `false` and the caller should hand of the chunk
-/// items to be further split. Otherwise it creates a chunk.
+/// items to be further split. Otherwise it creates a chunk. The chunking policy
+/// can decide whether to split the chunk items instead.
 async fn handle_split_group(
     chunk_items: &mut Vec<ChunkItemWithInfo>,
     key: &mut String,
     split_context: &mut SplitContext<'_>,
     remaining: Option<&mut Vec<ChunkItemWithInfo>>,
 ) -> Result<bool> {
-    Ok(match (chunk_size(chunk_items), remaining) {
+    let policy_decision = match split_context.policy {
+        Some(policy) => {
+            *policy
+                .should_split(chunk_plan(chunk_items, key).cell())
+                .await?
+        }
+        None => None,
+    };
+    let size = match policy_decision {
+        Some(true) => {
+            split_context.policy_split_keys.push(key.clone());
+            ChunkSize::Large
+        }
+        Some(false) => ChunkSize::Perfect,
+        None => chunk_size(chunk_items, split_context.size_limits),
+    };
+    Ok(match (size, remaining) {
         (ChunkSize::Large, _) => false,
         (ChunkSize::Perfect, _) | (ChunkSize::Small, None) => {
             make_chunk(take(chunk_items), key, split_context).await?;
@@ -112,30 +387,307 @@ async fn handle_split_group(
     })
 }
 
-/// Creates a chunk with the given `chunk_items. `key` should be unique and is
-/// used with [keyed_cell] to place the chunk items into a cell.
+/// Adds a chunk with the given `chunk_items`, which is created once all chunk
+/// items have been split. `key` should be unique and names the chunk in chunk
+/// plans.
 #[tracing::instrument(level = Level::TRACE, skip_all, fields(key = display(key)))]
 async fn make_chunk(
     chunk_items: Vec<ChunkItemWithInfo>,
     key: &mut String,
     split_context: &mut SplitContext<'_>,
 ) -> Result<()> {
-    split_context.chunks.push(
-        split_context.ty.chunk(
-            split_context.chunking_context,
-            chunk_items
-                .into_iter()
-                .map(|(chunk_item, async_info, ..)| (chunk_item, async_info))
-                .collect(),
-            replace(
-                split_context.referenced_output_assets,
-                split_context.empty_referenced_output_assets,
-            ),
-        ),
-    );
+    split_context.chunk_count += 1;
+    let modules = chunk_items
+        .iter()
+        .map(|&(chunk_item, ..)| chunk_item.module())
+        .collect();
+    // The keys of chunks extend the key of the chunk items they are split from.
+    let policy_split = split_context
+        .policy_split_keys
+        .iter()
+        .any(|split_key| *key == *split_key || key.starts_with(&format!("{split_key}-")));
+    split_context.chunks.push(PendingChunk {
+        ty: split_context.ty,
+        key: key.clone(),
+        chunk_items,
+        modules,
+        origin: ChunkOrigin {
+            group: split_context.group.clone(),
+            deferred: split_context.deferred,
+            policy_split,
+        },
+    });
+    Ok(())
+}
+
+/// Places the chunk items that the chunking policy names a chunk for, or that
+/// belong to one of the `cache_groups`, into chunks named after their group.
+/// Continues splitting with [folder_split] if necessary. Returns the chunk
+/// items that don't belong to any group.
+#[tracing::instrument(level = Level::TRACE, skip_all, fields(name = display(name)))]
+async fn cache_groups_split(
+    chunk_items: Vec<ChunkItemWithInfo>,
+    name: &str,
+    cache_groups: Vc<ChunkCacheGroups>,
+    split_context: &mut SplitContext<'_>,
+) -> Result<Vec<ChunkItemWithInfo>> {
+    let policy = split_context.policy;
+    if policy.is_none() && cache_groups.await?.is_empty() {
+        return Ok(chunk_items);
+    }
+    let context_path = split_context.chunking_context.context_path();
+    let group_names = chunk_items
+        .iter()
+        .map(|&(chunk_item, ..)| async move {
+            if let Some(policy) = policy {
+                let name = policy.chunk_name_for(chunk_item.module()).await?;
+                if name.is_some() {
+                    return Ok(name);
+                }
+            }
+            cache_groups
+                .group_name(context_path, chunk_item.asset_ident())
+                .await
+        })
+        .try_join()
+        .await?;
+    let mut map = IndexMap::<_, Vec<ChunkItemWithInfo>>::new();
+    let mut remaining = Vec::new();
+    for (item, group_name) in chunk_items.into_iter().zip(group_names) {
+        if let Some(group_name) = &*group_name {
+            map.entry(group_name.clone()).or_default().push(item);
+        } else {
+            remaining.push(item);
+        }
+    }
+    for (group_name, mut list) in map {
+        let mut key = format!("{}-{}", name, group_name);
+        split_context.group = Some(group_name);
+        if !handle_split_group(&mut list, &mut key, split_context, None).await? {
+            folder_split(list, 0, key.into(), split_context).await?;
+        }
+    }
+    split_context.group = None;
+    Ok(remaining)
+}
+
+/// Describes the chunk that would be created for `chunk_items`.
+fn chunk_plan(chunk_items: &[ChunkItemWithInfo], key: &str) -> ChunkPlan {
+    ChunkPlan {
+        key: key.to_string(),
+        path: None,
+        size: chunk_items.iter().map(|&(_, _, size, _)| size as u64).sum(),
+        modules: chunk_items
+            .iter()
+            .map(|(_, _, _, ident)| ident.to_string())
+            .collect(),
+    }
+}
+
+/// A chunk that can be merged with adjacent chunks by [merge_chunks].
+trait MergeableChunk {
+    type Ty: PartialEq;
+
+    fn ty(&self) -> Self::Ty;
+    fn size(&self) -> usize;
+    fn is_vendor(&self) -> bool;
+    /// Appends the chunk items of `next` to this chunk.
+    fn merge(&mut self, next: Self);
+}
+
+impl MergeableChunk for PendingChunk {
+    type Ty = Vc<Box<dyn ChunkType>>;
+
+    fn ty(&self) -> Self::Ty {
+        self.ty
+    }
+
+    fn size(&self) -> usize {
+        PendingChunk::size(self)
+    }
+
+    fn is_vendor(&self) -> bool {
+        PendingChunk::is_vendor(self)
+    }
+
+    fn merge(&mut self, next: Self) {
+        self.chunk_items.extend(next.chunk_items);
+        self.modules.extend(next.modules);
+    }
+}
+
+/// Whether [ChunkingContext::can_be_in_same_chunk] allows every module of
+/// `modules_a` to be placed into the same chunk as every module of
+/// `modules_b`.
+async fn can_be_in_same_chunk(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    modules_a: &[Vc<Box<dyn Module>>],
+    modules_b: &[Vc<Box<dyn Module>>],
+) -> Result<bool> {
+    let results = modules_a
+        .iter()
+        .flat_map(|&a| modules_b.iter().map(move |&b| (a, b)))
+        .map(|(a, b)| async move { Ok(*chunking_context.can_be_in_same_chunk(a, b).await?) })
+        .try_join()
+        .await?;
+    Ok(results.into_iter().all(|same_chunk| same_chunk))
+}
+
+/// Merges adjacent chunks of the same type, the smallest pair first, until
+/// there are at most `max_chunks` chunks. Chunks of different types can't be
+/// merged, and neither can chunks that `can_merge` keeps apart, so there can
+/// still be more chunks than that.
+///
+/// When `isolate_vendor_chunks` is set, vendor chunks are only merged with
+/// each other, until there are at most `max_chunks` vendor chunks, and app
+/// chunks are only merged with each other. This way the merged vendor chunks
+/// don't depend on the app chunks.
+async fn merge_chunks<C: MergeableChunk, F: Future<Output = Result<bool>>>(
+    chunks: &mut Vec<C>,
+    max_chunks: usize,
+    isolate_vendor_chunks: bool,
+    can_merge: impl Fn(&C, &C) -> F,
+) -> Result<()> {
+    if !isolate_vendor_chunks {
+        return merge_adjacent(
+            chunks,
+            |_| true,
+            &can_merge,
+            |chunks| chunks.len() > max_chunks,
+        )
+        .await;
+    }
+    merge_adjacent(
+        chunks,
+        |chunk| chunk.is_vendor(),
+        &can_merge,
+        |chunks| chunks.iter().filter(|chunk| chunk.is_vendor()).count() > max_chunks,
+    )
+    .await?;
+    merge_adjacent(
+        chunks,
+        |chunk| !chunk.is_vendor(),
+        &can_merge,
+        |chunks| chunks.len() > max_chunks,
+    )
+    .await
+}
+
+/// Merges the smallest pair of chunks of the same type that are adjacent among
+/// the chunks that are `mergeable` and that `can_merge` allows to merge, while
+/// `too_many` returns `true`. `can_merge` is only asked about adjacent pairs,
+/// smallest first, until one can be merged.
+async fn merge_adjacent<C: MergeableChunk, F: Future<Output = Result<bool>>>(
+    chunks: &mut Vec<C>,
+    mergeable: impl Fn(&C) -> bool,
+    can_merge: &impl Fn(&C, &C) -> F,
+    too_many: impl Fn(&[C]) -> bool,
+) -> Result<()> {
+    while too_many(chunks) {
+        let candidates = chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| mergeable(chunk))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let mut pairs = candidates
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .filter(|&(index, next)| chunks[index].ty() == chunks[next].ty())
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|&(index, next)| chunks[index].size() + chunks[next].size());
+        let mut merge = None;
+        for (index, next) in pairs {
+            if can_merge(&chunks[index], &chunks[next]).await? {
+                merge = Some((index, next));
+                break;
+            }
+        }
+        let Some((index, next)) = merge else {
+            break;
+        };
+        let next = chunks.remove(next);
+        chunks[index].merge(next);
+    }
     Ok(())
 }
 
+/// Split chunk items into the ones that have been executed according to the
+/// `usage_profile` and the others, which are placed into chunks after the
+/// executed ones. Both are split further with [app_vendors_split]. The
+/// chunks of the others are deferred, see [MakeChunksResult::deferred_chunks].
+/// Returns the issue reporting the changes compared to [app_vendors_split]
+/// alone, if any.
+async fn profile_guided_split(
+    chunk_items: Vec<ChunkItemWithInfo>,
+    name: String,
+    usage_profile: &UsageProfile,
+    split_context: &mut SplitContext<'_>,
+) -> Result<Option<UsageProfileChunkingIssue>> {
+    let ids = chunk_items
+        .iter()
+        .map(|(chunk_item, ..)| chunk_item.id())
+        .try_join()
+        .await?;
+    let (hot, cold): (Vec<_>, Vec<_>) = chunk_items
+        .iter()
+        .cloned()
+        .zip(ids.iter())
+        .partition(|(_, id)| usage_profile.is_executed(id));
+    if hot.is_empty() || cold.is_empty() {
+        app_vendors_split(chunk_items, name, split_context).await?;
+        return Ok(None);
+    }
+    let hot: Vec<_> = hot.into_iter().map(|(item, _)| item).collect();
+    let cold: Vec<_> = cold.into_iter().map(|(item, _)| item).collect();
+
+    // Count the chunks the default split would create, the pending chunks are
+    // discarded.
+    let mut default_split_chunks = Vec::new();
+    let mut default_split_context = SplitContext {
+        ty: split_context.ty,
+        chunking_context: split_context.chunking_context,
+        chunks: &mut default_split_chunks,
+        size_limits: split_context.size_limits,
+        split_node_modules: split_context.split_node_modules,
+        isolate_vendor_chunks: split_context.isolate_vendor_chunks,
+        policy: split_context.policy,
+        deferred: false,
+        group: None,
+        policy_split_keys: Vec::new(),
+        chunk_count: 0,
+    };
+    app_vendors_split(chunk_items, name.clone(), &mut default_split_context).await?;
+    let default_chunks = default_split_context.chunk_count;
+
+    let hot_size = hot.iter().map(|(_, _, size, _)| size).sum();
+    let cold_size = cold.iter().map(|(_, _, size, _)| size).sum();
+    let hot_modules = hot.len();
+    let cold_modules = cold
+        .iter()
+        .map(|(_, _, _, ident)| ident.to_string())
+        .collect();
+
+    let before = split_context.chunk_count;
+    app_vendors_split(hot, format!("{name}-hot"), split_context).await?;
+    let hot_chunks = split_context.chunk_count - before;
+    split_context.deferred = true;
+    app_vendors_split(cold, format!("{name}-cold"), split_context).await?;
+    split_context.deferred = false;
+    let cold_chunks = split_context.chunk_count - before - hot_chunks;
+
+    Ok(Some(UsageProfileChunkingIssue {
+        path: split_context.chunking_context.context_path(),
+        default_chunks,
+        hot_chunks,
+        hot_modules,
+        hot_size,
+        cold_chunks,
+        cold_modules,
+        cold_size,
+    }))
+}
+
 /// Split chunk items into app code and vendor code. Continues splitting with
 /// [package_name_split] if necessary.
 #[tracing::instrument(level = Level::TRACE, skip_all, fields(name = display(&name)))]
@@ -148,7 +700,7 @@ async fn app_vendors_split(
     let mut vendors_chunk_items = Vec::new();
     for item in chunk_items {
         let (_, _, _, asset_ident) = &item;
-        if is_app_code(asset_ident) {
+        if !split_context.split_node_modules || is_app_code(asset_ident) {
             app_chunk_items.push(item);
         } else {
             vendors_chunk_items.push(item);
@@ -166,12 +718,19 @@ async fn app_vendors_split(
     {
         folder_split(app_chunk_items, 0, key.into(), split_context).await?;
     }
+    // Small vendor chunks are merged with small app chunks unless vendor chunks
+    // are isolated.
+    let vendors_remaining = if split_context.isolate_vendor_chunks {
+        None
+    } else {
+        Some(&mut remaining)
+    };
     let mut key = format!("{}-vendors", name);
     if !handle_split_group(
         &mut vendors_chunk_items,
         &mut key,
         split_context,
-        Some(&mut remaining),
+        vendors_remaining,
     )
     .await?
     {
@@ -286,12 +845,12 @@ async fn folder_split(
 }
 
 /// Returns `true` if the given `ident` is app code.
-fn is_app_code(ident: &str) -> bool {
+pub(super) fn is_app_code(ident: &str) -> bool {
     !ident.contains("/node_modules/")
 }
 
 /// Returns the package name of the given `ident`.
-fn package_name(ident: &str) -> &str {
+pub(super) fn package_name(ident: &str) -> &str {
     static PACKAGE_NAME_REGEX: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"/node_modules/((?:@[^/]+/)?[^/]+)").unwrap());
     if let Some(result) = PACKAGE_NAME_REGEX.find_iter(ident).last() {
@@ -315,6 +874,35 @@ fn folder_name(ident: &str, location: usize) -> (&str, Option<usize>) {
 const LARGE_CHUNK: usize = 1_000_000;
 const SMALL_CHUNK: usize = 100_000;
 
+/// The sizes in bytes that chunks are considered too small or too large at.
+#[derive(Clone, Copy)]
+struct ChunkSizeLimits {
+    small: usize,
+    large: usize,
+}
+
+impl ChunkSizeLimits {
+    /// Creates the limits from the configured minimum and maximum chunk sizes,
+    /// falling back to the defaults for unset ones.
+    fn new(min: Option<u64>, max: Option<u64>) -> Result<Self> {
+        let to_usize = |size: u64| {
+            usize::try_from(size)
+                .with_context(|| format!("chunk size {} does not fit into usize", size))
+        };
+        let small = min.map(to_usize).transpose()?.unwrap_or(SMALL_CHUNK);
+        let large = max.map(to_usize).transpose()?.unwrap_or(LARGE_CHUNK);
+        if small > large {
+            bail!(
+                "the minimum chunk size ({} bytes) must not be larger than the maximum chunk size \
+                 ({} bytes)",
+                small,
+                large
+            );
+        }
+        Ok(Self { small, large })
+    }
+}
+
 enum ChunkSize {
     Large,
     Perfect,
@@ -323,16 +911,336 @@ enum ChunkSize {
 
 /// Determines the total size of the passed chunk items. Returns too small, too
 /// large or perfect fit.
-fn chunk_size(chunk_items: &[ChunkItemWithInfo]) -> ChunkSize {
+fn chunk_size(chunk_items: &[ChunkItemWithInfo], limits: ChunkSizeLimits) -> ChunkSize {
     let mut total_size = 0;
     for (_, _, size, _) in chunk_items {
         total_size += size;
     }
-    if total_size >= LARGE_CHUNK {
+    if total_size >= limits.large {
         ChunkSize::Large
-    } else if total_size > SMALL_CHUNK {
+    } else if total_size > limits.small {
         ChunkSize::Perfect
     } else {
         ChunkSize::Small
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TestChunk {
+        ty: &'static str,
+        modules: Vec<(&'static str, usize)>,
+        origin: ChunkOrigin,
+    }
+
+    impl MergeableChunk for TestChunk {
+        type Ty = &'static str;
+
+        fn ty(&self) -> Self::Ty {
+            self.ty
+        }
+
+        fn size(&self) -> usize {
+            self.modules.iter().map(|(_, size)| size).sum()
+        }
+
+        fn is_vendor(&self) -> bool {
+            self.modules.iter().all(|(ident, _)| !is_app_code(ident))
+        }
+
+        fn merge(&mut self, next: Self) {
+            self.modules.extend(next.modules);
+        }
+    }
+
+    fn chunk(ty: &'static str, modules: &[(&'static str, usize)]) -> TestChunk {
+        TestChunk {
+            ty,
+            modules: modules.to_vec(),
+            origin: ChunkOrigin::default(),
+        }
+    }
+
+    fn chunk_from(
+        origin: ChunkOrigin,
+        ty: &'static str,
+        modules: &[(&'static str, usize)],
+    ) -> TestChunk {
+        TestChunk {
+            origin,
+            ..chunk(ty, modules)
+        }
+    }
+
+    /// Runs [merge_chunks] with a synchronous `can_merge`.
+    fn merge_test_chunks(
+        chunks: &mut Vec<TestChunk>,
+        max_chunks: usize,
+        isolate_vendor_chunks: bool,
+        can_merge: impl Fn(&TestChunk, &TestChunk) -> bool,
+    ) {
+        futures::executor::block_on(merge_chunks(
+            chunks,
+            max_chunks,
+            isolate_vendor_chunks,
+            |a, b| std::future::ready(Ok(can_merge(a, b))),
+        ))
+        .unwrap();
+    }
+
+    fn vendor_chunks(chunks: &[TestChunk]) -> Vec<&TestChunk> {
+        chunks.iter().filter(|chunk| chunk.is_vendor()).collect()
+    }
+
+    #[test]
+    fn test_is_app_code() {
+        assert!(is_app_code("[project]/src/index.js [client] (ecmascript)"));
+        assert!(!is_app_code(
+            "[project]/node_modules/react/index.js [client] (ecmascript)"
+        ));
+    }
+
+    #[test]
+    fn test_merge_chunks() {
+        let mut chunks = vec![
+            chunk("js", &[("[project]/src/a.js", 10)]),
+            chunk("js", &[("[project]/node_modules/a/index.js", 5)]),
+            chunk("css", &[("[project]/src/a.css", 1)]),
+            chunk("js", &[("[project]/src/b.js", 1)]),
+        ];
+        merge_test_chunks(&mut chunks, 2, false, |_, _| true);
+        assert_eq!(
+            chunks,
+            vec![
+                chunk(
+                    "js",
+                    &[
+                        ("[project]/src/a.js", 10),
+                        ("[project]/node_modules/a/index.js", 5)
+                    ]
+                ),
+                chunk("css", &[("[project]/src/a.css", 1)]),
+                chunk("js", &[("[project]/src/b.js", 1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_chunks_isolated_vendors() {
+        let mut chunks = vec![
+            chunk("js", &[("[project]/src/a.js", 1)]),
+            chunk("js", &[("[project]/node_modules/a/index.js", 1)]),
+            chunk("js", &[("[project]/src/b.js", 1)]),
+            chunk("js", &[("[project]/node_modules/b/index.js", 1)]),
+        ];
+        merge_test_chunks(&mut chunks, 2, true, |_, _| true);
+        // There are already few enough vendor chunks, and vendor chunks aren't
+        // merged to make room for app chunks.
+        assert_eq!(
+            chunks,
+            vec![
+                chunk(
+                    "js",
+                    &[("[project]/src/a.js", 1), ("[project]/src/b.js", 1)]
+                ),
+                chunk("js", &[("[project]/node_modules/a/index.js", 1)]),
+                chunk("js", &[("[project]/node_modules/b/index.js", 1)]),
+            ]
+        );
+    }
+
+    /// Edits to app code must not change the content of vendor chunks, so
+    /// that they stay cached in the browser.
+    #[test]
+    fn test_vendor_chunks_stable_across_app_edits() {
+        let vendors = || {
+            vec![
+                chunk("js", &[("[project]/node_modules/react/index.js", 50)]),
+                chunk("js", &[("[project]/node_modules/react-dom/index.js", 300)]),
+                chunk("js", &[("[project]/node_modules/lodash/get.js", 20)]),
+            ]
+        };
+        let with_app = |app: &[(&'static str, usize)]| {
+            let mut chunks = app
+                .iter()
+                .map(|&module| chunk("js", &[module]))
+                .collect::<Vec<_>>();
+            chunks.extend(vendors());
+            merge_test_chunks(&mut chunks, 2, true, |_, _| true);
+            chunks
+        };
+
+        let before = with_app(&[("[project]/src/index.js", 10)]);
+        let after = with_app(&[
+            ("[project]/src/index.js", 1000),
+            ("[project]/src/page.js", 1),
+            ("[project]/src/utils.js", 1),
+        ]);
+        assert_eq!(vendor_chunks(&before), vendor_chunks(&after));
+
+        // Without isolation, vendor modules are merged into app chunks.
+        let mut chunks = vec![chunk("js", &[("[project]/src/index.js", 10)])];
+        chunks.extend(vendors());
+        merge_test_chunks(&mut chunks, 2, false, |_, _| true);
+        assert_eq!(
+            chunks[0],
+            chunk(
+                "js",
+                &[
+                    ("[project]/src/index.js", 10),
+                    ("[project]/node_modules/react/index.js", 50)
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_merge_chunks_kept_apart() {
+        let mut chunks = vec![
+            chunk("js", &[("[project]/src/team-a/a.js", 1)]),
+            chunk("js", &[("[project]/src/team-b/b.js", 1)]),
+            chunk("js", &[("[project]/src/team-a/c.js", 1)]),
+        ];
+        let team = |chunk: &TestChunk| chunk.modules[0].0.split('/').nth(2).unwrap();
+        merge_test_chunks(&mut chunks, 1, false, |a, b| team(a) == team(b));
+        // Only adjacent chunks are merged, so the chunks of team a stay apart.
+        assert_eq!(
+            chunks,
+            vec![
+                chunk("js", &[("[project]/src/team-a/a.js", 1)]),
+                chunk("js", &[("[project]/src/team-b/b.js", 1)]),
+                chunk("js", &[("[project]/src/team-a/c.js", 1)]),
+            ]
+        );
+
+        let mut chunks = vec![
+            chunk("js", &[("[project]/src/team-a/a.js", 5)]),
+            chunk("js", &[("[project]/src/team-a/c.js", 1)]),
+            chunk("js", &[("[project]/src/team-b/b.js", 1)]),
+        ];
+        merge_test_chunks(&mut chunks, 1, false, |a, b| team(a) == team(b));
+        // The smallest pair is skipped, as it would mix both teams.
+        assert_eq!(
+            chunks,
+            vec![
+                chunk(
+                    "js",
+                    &[
+                        ("[project]/src/team-a/a.js", 5),
+                        ("[project]/src/team-a/c.js", 1)
+                    ]
+                ),
+                chunk("js", &[("[project]/src/team-b/b.js", 1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_chunks_asks_lazily() {
+        let mut chunks = vec![
+            chunk("js", &[("[project]/src/a.js", 1)]),
+            chunk("js", &[("[project]/src/b.js", 1)]),
+            chunk("js", &[("[project]/src/c.js", 5)]),
+            chunk("css", &[("[project]/src/a.css", 1)]),
+            chunk("js", &[("[project]/src/d.js", 1)]),
+        ];
+        let asked = std::cell::RefCell::new(Vec::new());
+        merge_test_chunks(&mut chunks, 4, false, |a, b| {
+            asked.borrow_mut().push((a.modules[0].0, b.modules[0].0));
+            true
+        });
+        // Only the smallest adjacent pair of the same type is asked about.
+        assert_eq!(
+            asked.into_inner(),
+            vec![("[project]/src/a.js", "[project]/src/b.js")]
+        );
+        assert_eq!(chunks.len(), 4);
+    }
+
+    #[test]
+    fn test_merge_chunks_origins() {
+        let group = |name: &str| ChunkOrigin {
+            group: Some(name.to_string()),
+            ..Default::default()
+        };
+        let deferred = ChunkOrigin {
+            deferred: true,
+            ..Default::default()
+        };
+        let policy_split = ChunkOrigin {
+            policy_split: true,
+            ..Default::default()
+        };
+        let mut chunks = vec![
+            chunk_from(
+                group("framework"),
+                "js",
+                &[("[project]/node_modules/react/index.js", 1)],
+            ),
+            chunk_from(
+                group("framework"),
+                "js",
+                &[("[project]/node_modules/react-dom/index.js", 1)],
+            ),
+            chunk_from(
+                group("ui"),
+                "js",
+                &[("[project]/node_modules/ui/index.js", 1)],
+            ),
+            chunk("js", &[("[project]/src/a.js", 1)]),
+            chunk_from(deferred.clone(), "js", &[("[project]/src/b.js", 1)]),
+            chunk_from(deferred.clone(), "js", &[("[project]/src/c.js", 1)]),
+            chunk_from(policy_split.clone(), "js", &[("[project]/src/d.js", 1)]),
+            chunk_from(policy_split.clone(), "js", &[("[project]/src/e.js", 1)]),
+        ];
+        merge_test_chunks(&mut chunks, 1, false, |a, b| {
+            a.origin.can_merge_with(&b.origin)
+        });
+        // Only chunks of the same group or of deferred modules are merged.
+        assert_eq!(
+            chunks,
+            vec![
+                chunk_from(
+                    group("framework"),
+                    "js",
+                    &[
+                        ("[project]/node_modules/react/index.js", 1),
+                        ("[project]/node_modules/react-dom/index.js", 1)
+                    ]
+                ),
+                chunk_from(
+                    group("ui"),
+                    "js",
+                    &[("[project]/node_modules/ui/index.js", 1)]
+                ),
+                chunk("js", &[("[project]/src/a.js", 1)]),
+                chunk_from(
+                    deferred,
+                    "js",
+                    &[("[project]/src/b.js", 1), ("[project]/src/c.js", 1)]
+                ),
+                chunk_from(policy_split.clone(), "js", &[("[project]/src/d.js", 1)]),
+                chunk_from(policy_split, "js", &[("[project]/src/e.js", 1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn chunk_size_limits() {
+        let defaults = ChunkSizeLimits::new(None, None).unwrap();
+        assert_eq!((defaults.small, defaults.large), (SMALL_CHUNK, LARGE_CHUNK));
+
+        let custom = ChunkSizeLimits::new(Some(10), Some(20)).unwrap();
+        assert_eq!((custom.small, custom.large), (10, 20));
+
+        let equal = ChunkSizeLimits::new(Some(20), Some(20)).unwrap();
+        assert_eq!((equal.small, equal.large), (20, 20));
+
+        assert!(ChunkSizeLimits::new(Some(20), Some(10)).is_err());
+        assert!(ChunkSizeLimits::new(Some(LARGE_CHUNK as u64 + 1), None).is_err());
+        assert!(ChunkSizeLimits::new(None, Some(SMALL_CHUNK as u64 - 1)).is_err());
+    }
+}
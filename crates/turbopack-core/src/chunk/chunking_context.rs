@@ -1,20 +1,37 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use turbo_tasks::{Upcast, Value, ValueToString, Vc};
 use turbo_tasks_fs::FileSystemPath;
 
-use super::{availability_info::AvailabilityInfo, ChunkableModule, EvaluatableAssets};
+use super::{
+    availability_info::AvailabilityInfo, cache_group::ChunkCacheGroups,
+    integrity::ChunkIntegrityHashes, plan::ChunkGroupPlan, policy::OptionChunkingPolicy,
+    records::ChunkingRecords, usage_profile::OptionUsageProfile, ChunkableModule,
+    EvaluatableAssets,
+};
 use crate::{
-    chunk::{ChunkItem, ModuleId},
+    chunk::{ChunkItem, ModuleId, OptionChunkItem},
     environment::Environment,
+    hash::HashAlgorithm,
     ident::AssetIdent,
-    module::Module,
+    module::{Module, ModulesSet},
     output::{OutputAsset, OutputAssets},
+    source_map::{OptionSourceMapPrivacy, SourceMapMode},
 };
 
 #[turbo_tasks::value(shared)]
 pub struct ChunkGroupResult {
     pub assets: Vc<OutputAssets>,
     pub availability_info: AvailabilityInfo,
+    /// The chunks of the chunk group, when the chunking context is in dry-run
+    /// mode. In that case, no assets are generated.
+    pub plan: Option<Vc<ChunkGroupPlan>>,
+    /// Whether the chunk group only contains static assets, e.g. CSS, as its
+    /// entries don't need to be evaluated. In that case, no JavaScript (e.g.
+    /// the runtime) is emitted to load it.
+    pub static_only: bool,
+    /// The Subresource Integrity hashes of the assets, when
+    /// [ChunkingContext::subresource_integrity] is enabled.
+    pub integrity: Option<Vc<ChunkIntegrityHashes>>,
 }
 
 /// A context for the chunking that influences the way chunks are created
@@ -33,10 +50,59 @@ pub trait ChunkingContext {
     // dependency first.
     fn chunk_path(self: Vc<Self>, ident: Vc<AssetIdent>, extension: String) -> Vc<FileSystemPath>;
 
+    /// Whether chunks are named by a hash of their content, see
+    /// [ChunkingContext::content_hashed_chunk_path], so they are never served
+    /// stale.
+    fn content_hash_chunk_names(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// Like [ChunkingContext::chunk_path], but for a chunk with the given
+    /// hash of its content, when [ChunkingContext::content_hash_chunk_names]
+    /// is enabled.
+    fn content_hashed_chunk_path(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        _content_hash: Vc<String>,
+        extension: String,
+    ) -> Vc<FileSystemPath> {
+        self.chunk_path(ident, extension)
+    }
+
     // TODO(alexkirsz) Remove this from the chunking context.
-    /// Reference Source Map Assets for chunks
-    fn reference_chunk_source_maps(self: Vc<Self>, chunk: Vc<Box<dyn OutputAsset>>) -> Vc<bool>;
+    /// How `chunk` references its source map. Only chunks that reference it
+    /// as an external file reference a
+    /// [SourceMapAsset](crate::source_map::SourceMapAsset).
+    fn reference_chunk_source_maps(
+        self: Vc<Self>,
+        chunk: Vc<Box<dyn OutputAsset>>,
+    ) -> Vc<SourceMapMode>;
+
+    /// Which sources to redact from the source maps of chunks.
+    fn source_map_privacy(self: Vc<Self>) -> Vc<OptionSourceMapPrivacy> {
+        Vc::cell(None)
+    }
+
+    /// The hash function used for content hashes and chunk idents.
+    fn hash_algorithm(self: Vc<Self>) -> Vc<HashAlgorithm> {
+        HashAlgorithm::default().cell()
+    }
+
+    /// Whether Subresource Integrity hashes are computed for chunks, so that
+    /// the runtime can verify the chunks it loads.
+    fn subresource_integrity(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
 
+    /// Whether chunks are loaded as ES modules, i.e. with
+    /// `<script type="module">` and `import()` instead of classic scripts.
+    fn esm_chunks(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// Whether `asset_a` and `asset_b` may be placed into the same chunk.
+    /// Chunks are only merged to stay within the maximum number of chunks
+    /// when this holds for all modules of both chunks.
     fn can_be_in_same_chunk(
         self: Vc<Self>,
         asset_a: Vc<Box<dyn Module>>,
@@ -57,6 +123,88 @@ pub trait ChunkingContext {
         Vc::cell(false)
     }
 
+    /// Whether the idents of chunks carry the layer shared by all of their
+    /// modules (see [common_layer]), e.g. to place them at per-layer paths.
+    /// The layer is part of the chunk names then.
+    ///
+    /// [common_layer]: crate::ident::common_layer
+    fn layered_chunks(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// Biases chunking towards the modules executed during a session.
+    fn usage_profile(self: Vc<Self>) -> Vc<OptionUsageProfile> {
+        Vc::cell(None)
+    }
+
+    /// Modules whose generated code exceeds this size in bytes are reported
+    /// as issues when chunking.
+    fn module_size_warning_threshold(self: Vc<Self>) -> Vc<Option<u64>> {
+        Vc::cell(None)
+    }
+
+    /// Whether packages that are placed into a chunk group together with a
+    /// version of one of their peer dependencies outside of the declared range
+    /// are reported. This reads the package.json of every package in the chunk
+    /// group, so it's off by default.
+    fn check_peer_dependencies(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// Chunks smaller than this size in bytes are merged with other chunks
+    /// when possible. Uses the default heuristic when `None`.
+    fn min_chunk_size(self: Vc<Self>) -> Vc<Option<u64>> {
+        Vc::cell(None)
+    }
+
+    /// Chunks of at least this size in bytes are split further when possible.
+    /// Uses the default heuristic when `None`.
+    fn max_chunk_size(self: Vc<Self>) -> Vc<Option<u64>> {
+        Vc::cell(None)
+    }
+
+    /// Whether modules in `node_modules` are placed into separate vendor
+    /// chunks, apart from app code.
+    fn split_node_modules(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(true)
+    }
+
+    /// Whether the content of vendor chunks only depends on the modules in
+    /// `node_modules`, so that edits to app code never invalidate them. Vendor
+    /// chunks are then never merged with app chunks, and vendor modules are
+    /// placed into each chunk group that uses them, even when a parent chunk
+    /// group already loaded them.
+    fn isolate_vendor_chunks(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// The maximum number of chunks of a chunk group, which limits the number
+    /// of parallel requests needed to load it. Adjacent chunks are merged
+    /// until the chunk group is within the limit. The async loader chunks of
+    /// the chunk group count towards the limit. Unlimited when `None`.
+    fn max_chunks_per_chunk_group(self: Vc<Self>) -> Vc<Option<u64>> {
+        Vc::cell(None)
+    }
+
+    /// Customizes how modules are assigned to chunks.
+    fn chunking_policy(self: Vc<Self>) -> Vc<OptionChunkingPolicy> {
+        Vc::cell(None)
+    }
+
+    /// Named groups of modules that are placed into their own chunks before
+    /// the remaining modules are split into app and vendor chunks.
+    fn cache_groups(self: Vc<Self>) -> Vc<ChunkCacheGroups> {
+        ChunkCacheGroups::empty()
+    }
+
+    /// Modules that are placed in their own async chunk group even when they
+    /// are imported statically, e.g. the route components discovered by a
+    /// router. Their ESM importers become async modules that wait for the
+    /// chunk group to be loaded.
+    fn split_points(self: Vc<Self>) -> Vc<ModulesSet> {
+        ModulesSet::empty()
+    }
+
     fn async_loader_chunk_item(
         &self,
         module: Vc<Box<dyn ChunkableModule>>,
@@ -64,12 +212,46 @@ pub trait ChunkingContext {
     ) -> Vc<Box<dyn ChunkItem>>;
     fn async_loader_chunk_item_id(&self, module: Vc<Box<dyn ChunkableModule>>) -> Vc<ModuleId>;
 
+    /// Like [ChunkingContext::async_loader_chunk_item], but the chunks of the
+    /// chunk group loaded by it are named after `name`, e.g. from a
+    /// `turbopackChunkName` comment. By default the name is ignored.
+    fn named_async_loader_chunk_item(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+        _name: String,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        self.async_loader_chunk_item(module, availability_info)
+    }
+
+    /// A chunk item that replaces `module` when it fails to build with
+    /// `error`, so that the rest of the chunk group can still be emitted. By
+    /// default the error fails the whole chunk group.
+    fn failing_module_chunk_item(
+        self: Vc<Self>,
+        _module: Vc<Box<dyn ChunkableModule>>,
+        _error: String,
+    ) -> Vc<OptionChunkItem> {
+        Vc::cell(None)
+    }
+
     fn chunk_group(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
         availability_info: Value<AvailabilityInfo>,
     ) -> Vc<ChunkGroupResult>;
 
+    /// Like [ChunkingContext::chunk_group], but the chunks are named after
+    /// `name`. By default the name is ignored.
+    fn named_chunk_group(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+        _name: String,
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group(module, availability_info)
+    }
+
     fn evaluated_chunk_group(
         self: Vc<Self>,
         ident: Vc<AssetIdent>,
@@ -77,11 +259,92 @@ pub trait ChunkingContext {
         availability_info: Value<AvailabilityInfo>,
     ) -> Vc<ChunkGroupResult>;
 
+    /// A single chunk that contains `evaluatable_assets` and all modules they
+    /// depend on, and evaluates them when it is loaded as a module, e.g. by
+    /// `CSS.paintWorklet.addModule`. It must not depend on a runtime or other
+    /// chunks being loaded.
+    async fn worklet_chunk(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        _evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<Box<dyn OutputAsset>>> {
+        bail!(
+            "{} can't be loaded as a worklet, as the chunking context doesn't support worklets",
+            ident.to_string().await?
+        )
+    }
+
+    /// The chunk group of a web worker, e.g. of
+    /// `new Worker(new URL("./worker.ts", import.meta.url))`, which evaluates
+    /// `evaluatable_assets` in the worker. Its last asset is the entry chunk
+    /// the worker is started with, which contains a runtime that loads the
+    /// other chunks without a DOM.
+    ///
+    /// Workers have their own global scope, so none of the modules of the
+    /// page that starts them are available to them.
+    async fn worker_chunk_group(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        _evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        bail!(
+            "{} can't be loaded as a worker, as the chunking context doesn't support workers",
+            ident.to_string().await?
+        )
+    }
+
+    /// Workers whose code, as a [ChunkingContext::worklet_chunk], is at most
+    /// this size in bytes are inlined into the chunk that starts them and
+    /// started from a blob URL, instead of being emitted as a separate chunk
+    /// group. This avoids the same-origin restriction of workers when chunks
+    /// are served from another origin, e.g. a CDN. Workers that use `import()`
+    /// or `new URL(..., import.meta.url)` aren't inlined, as the URLs they
+    /// load are relative to their chunks. Workers aren't inlined when `None`.
+    fn inline_worker_size_limit(self: Vc<Self>) -> Vc<Option<u64>> {
+        Vc::cell(None)
+    }
+
+    /// The chunk group of a service worker. Like a
+    /// [ChunkingContext::worklet_chunk], the service worker is a single chunk
+    /// at `path` that contains all of its modules and no development runtime,
+    /// as it can't load other chunks after it was installed. `path` must not
+    /// change between builds, as the browser registers the service worker by
+    /// its URL. The chunk exposes the precache entries of `precache` and all
+    /// assets they reference as `self.TURBOPACK_PRECACHE_MANIFEST`, see
+    /// [super::precache::precache_entries]. The entries are also emitted as a
+    /// JSON [super::precache::PrecacheManifestAsset] next to the chunk. The
+    /// chunk is the last asset of the chunk group.
+    async fn service_worker_chunk_group(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        _evaluatable_assets: Vc<EvaluatableAssets>,
+        _precache: Vc<OutputAssets>,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        bail!(
+            "{} can't be loaded as a service worker, as the chunking context doesn't support \
+             service workers",
+            path.to_string().await?
+        )
+    }
+
+    /// The records pinning the ids of chunk items and the names of chunks,
+    /// e.g. read with
+    /// [read_chunking_records](super::records::read_chunking_records)
+    /// from the file written by a previous session. Recorded ids and names
+    /// take precedence over derived ones.
+    fn records(self: Vc<Self>) -> Vc<ChunkingRecords> {
+        ChunkingRecords::empty()
+    }
+
     async fn chunk_item_id_from_ident(
         self: Vc<Self>,
         ident: Vc<AssetIdent>,
     ) -> Result<Vc<ModuleId>> {
-        Ok(ModuleId::String(ident.to_string().await?.clone_value()).cell())
+        let ident = ident.to_string().await?;
+        if let Some(id) = self.records().await?.module_ids.get(ident.as_str()) {
+            return Ok(id.clone().cell());
+        }
+        Ok(ModuleId::String(ident.clone_value()).cell())
     }
 
     fn chunk_item_id(self: Vc<Self>, chunk_item: Vc<Box<dyn ChunkItem>>) -> Vc<ModuleId> {
@@ -0,0 +1,146 @@
+//! Chunk references for the manifest of a browser extension (Manifest V3).
+//!
+//! MV3 extensions can only run code that is part of the extension package, and
+//! content scripts and the background service worker can't load chunks on
+//! demand. Their chunk groups are built for an environment with a
+//! [crate::environment::BrowserExtensionContext] that loads all chunks upfront:
+//!
+//! * The chunks of a content script are listed in its `content_scripts` entry
+//!   of the manifest, see [content_script_resources].
+//! * The chunks of the background service worker are imported by a bootstrap
+//!   script, which is the `background.service_worker` of the manifest, see
+//!   [ServiceWorkerBootstrapAsset].
+//!
+//! The output root of the chunking context must be the root of the extension
+//! package, as manifest paths are relative to it.
+
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+
+use super::{availability_info::AvailabilityInfo, ChunkingContext, EvaluatableAssets};
+use crate::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+};
+
+/// The `js` and `css` fields of a `content_scripts` entry of the manifest.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+pub struct ContentScriptResources {
+    /// The JS chunks, in the order they must be loaded in.
+    pub js: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub css: Vec<String>,
+}
+
+/// The output assets of the evaluated chunk group of an extension entry.
+#[turbo_tasks::function]
+async fn extension_entry_assets(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ident: Vc<AssetIdent>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+) -> Result<Vc<OutputAssets>> {
+    Ok(chunking_context
+        .evaluated_chunk_group(
+            ident,
+            evaluatable_assets,
+            Value::new(AvailabilityInfo::Root),
+        )
+        .await?
+        .assets)
+}
+
+/// Computes the resources of a content script whose entries are
+/// `evaluatable_assets`.
+#[turbo_tasks::function]
+pub async fn content_script_resources(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ident: Vc<AssetIdent>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+) -> Result<Vc<ContentScriptResources>> {
+    let output_root = chunking_context.output_root().await?;
+    let assets = extension_entry_assets(chunking_context, ident, evaluatable_assets).await?;
+    let mut resources = ContentScriptResources::default();
+    for &asset in assets.iter() {
+        let path = asset.ident().path().await?;
+        let Some(path) = output_root.get_path_to(&path) else {
+            continue;
+        };
+        if path.ends_with(".js") {
+            resources.js.push(path.to_string());
+        } else if path.ends_with(".css") {
+            resources.css.push(path.to_string());
+        }
+    }
+    Ok(resources.cell())
+}
+
+/// The background service worker of an extension. It imports the chunks of
+/// the evaluated chunk group of its entries while the service worker is
+/// evaluated for the first time, which is the only time MV3 allows service
+/// workers to import scripts.
+#[turbo_tasks::value]
+pub struct ServiceWorkerBootstrapAsset {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl ServiceWorkerBootstrapAsset {
+    /// Creates a new bootstrap script that will be emitted at `path`.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Self> {
+        ServiceWorkerBootstrapAsset {
+            path,
+            chunking_context,
+            evaluatable_assets,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for ServiceWorkerBootstrapAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<OutputAssets> {
+        extension_entry_assets(
+            self.chunking_context,
+            AssetIdent::from_path(self.path),
+            self.evaluatable_assets,
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ServiceWorkerBootstrapAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let resources = content_script_resources(
+            self.chunking_context,
+            AssetIdent::from_path(self.path),
+            self.evaluatable_assets,
+        )
+        .await?;
+        // Paths starting with `/` are resolved against the root of the
+        // extension package, regardless of where the bootstrap script is placed.
+        let urls = resources
+            .js
+            .iter()
+            .map(|path| serde_json::to_string(&format!("/{path}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let code = format!("importScripts(\n  {}\n);\n", urls.join(",\n  "));
+        Ok(AssetContent::file(File::from(code).into()))
+    }
+}
@@ -0,0 +1,92 @@
+//! [Subresource Integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity)
+//! hashes of chunks, which browsers use to verify that a loaded chunk wasn't
+//! tampered with.
+
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use indexmap::IndexMap;
+use sha2::{Digest, Sha384};
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+
+use super::ChunkingContext;
+use crate::{
+    asset::{Asset, AssetContent},
+    output::{OutputAsset, OutputAssets},
+};
+
+/// Maps the paths of chunks, relative to the output root, to their SRI hashes.
+#[turbo_tasks::value(transparent)]
+pub struct ChunkIntegrityHashes(IndexMap<String, String>);
+
+/// Returns the SRI hash of `content`, e.g. `sha384-<base64 digest>`.
+pub fn sha384_integrity(content: &[u8]) -> String {
+    format!("sha384-{}", STANDARD.encode(Sha384::digest(content)))
+}
+
+/// Returns the SRI hash of the content of `asset`, or `None` if it has no
+/// file content.
+#[turbo_tasks::function]
+pub async fn asset_integrity(asset: Vc<Box<dyn OutputAsset>>) -> Result<Vc<Option<String>>> {
+    let AssetContent::File(file) = &*asset.content().await? else {
+        return Ok(Vc::cell(None));
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(Vc::cell(None));
+    };
+    Ok(Vc::cell(Some(sha384_integrity(
+        &file.content().to_bytes()?,
+    ))))
+}
+
+/// Returns the SRI hashes of those of `assets` that are in `output_root`.
+#[turbo_tasks::function]
+pub async fn chunk_integrity_hashes(
+    output_root: Vc<FileSystemPath>,
+    assets: Vc<OutputAssets>,
+) -> Result<Vc<ChunkIntegrityHashes>> {
+    let output_root = output_root.await?;
+    let hashes = assets
+        .await?
+        .iter()
+        .map(|&asset| {
+            let output_root = &output_root;
+            async move {
+                let path = asset.ident().path().await?;
+                let Some(path) = output_root.get_path_to(&path) else {
+                    return Ok(None);
+                };
+                let path = path.to_string();
+                Ok(asset_integrity(asset)
+                    .await?
+                    .clone_value()
+                    .map(|hash| (path, hash)))
+            }
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(hashes.into_iter().flatten().collect()))
+}
+
+/// Returns the SRI hashes of the assets of a chunk group, if the chunking
+/// context has [ChunkingContext::subresource_integrity] enabled.
+pub async fn chunk_group_integrity(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    assets: Vc<OutputAssets>,
+) -> Result<Option<Vc<ChunkIntegrityHashes>>> {
+    Ok((*chunking_context.subresource_integrity().await?)
+        .then(|| chunk_integrity_hashes(chunking_context.output_root(), assets)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sha384_integrity;
+
+    #[test]
+    fn sha384_integrity_of_content() {
+        assert_eq!(
+            sha384_integrity(b""),
+            "sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb"
+        );
+    }
+}
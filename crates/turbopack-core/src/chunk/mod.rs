@@ -1,13 +1,29 @@
+pub mod alias;
 pub mod availability_info;
 pub mod available_chunk_items;
+pub mod cache_group;
 pub mod chunk_group;
 pub mod chunking;
 pub(crate) mod chunking_context;
 pub(crate) mod containment_tree;
 pub(crate) mod data;
 pub(crate) mod evaluate;
+pub mod extension_manifest;
+pub mod integrity;
+pub(crate) mod module_id_strategy;
 pub mod optimize;
 pub(crate) mod passthrough_asset;
+pub(crate) mod peer_dependencies;
+pub mod plan;
+pub mod policy;
+pub mod precache;
+pub mod prefetch_manifest;
+pub mod records;
+pub mod size_guard;
+pub mod snapshot;
+pub mod tags;
+pub mod usage_profile;
+pub mod warm_up;
 
 use std::{
     collections::HashSet,
@@ -30,17 +46,23 @@ use turbo_tasks::{
 use turbo_tasks_fs::FileSystemPath;
 use turbo_tasks_hash::DeterministicHash;
 
-use self::availability_info::AvailabilityInfo;
+use self::{
+    availability_info::AvailabilityInfo, available_chunk_items::AvailableChunkItems,
+    chunking::is_app_code,
+};
 pub use self::{
     chunking_context::{ChunkGroupResult, ChunkingContext, ChunkingContextExt},
     data::{ChunkData, ChunkDataOption, ChunksData},
     evaluate::{EvaluatableAsset, EvaluatableAssetExt, EvaluatableAssets},
+    module_id_strategy::ModuleIdStrategy,
     passthrough_asset::PassthroughModule,
 };
 use crate::{
     asset::Asset,
     environment::ChunkLoading,
+    error::PrettyPrintError,
     ident::AssetIdent,
+    issue::{code_gen::CodeGenerationIssue, IssueExt, IssueSeverity, StyledString},
     module::Module,
     output::OutputAssets,
     reference::{ModuleReference, ModuleReferences},
@@ -179,6 +201,13 @@ pub trait ChunkableModuleReference: ModuleReference + ValueToString {
     fn chunking_type(self: Vc<Self>) -> Vc<ChunkingTypeOption> {
         Vc::cell(Some(ChunkingType::default()))
     }
+
+    /// The name of the chunk group the referenced module is placed into, for
+    /// [ChunkingType::Async] references, e.g. from a `turbopackChunkName`
+    /// comment.
+    fn chunk_group_name(self: Vc<Self>) -> Vc<Option<String>> {
+        Vc::cell(None)
+    }
 }
 
 type AsyncInfo = IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>>;
@@ -186,6 +215,9 @@ type AsyncInfo = IndexMap<Vc<Box<dyn ChunkItem>>, Vec<Vc<Box<dyn ChunkItem>>>>;
 pub struct ChunkContentResult {
     pub chunk_items: IndexSet<Vc<Box<dyn ChunkItem>>>,
     pub async_modules: IndexSet<Vc<Box<dyn ChunkableModule>>>,
+    /// The names of the chunk groups of [ChunkContentResult::async_modules]
+    /// that are referenced with a name.
+    pub async_chunk_group_names: IndexMap<Vc<Box<dyn ChunkableModule>>, String>,
     pub external_module_references: IndexSet<Vc<Box<dyn ModuleReference>>>,
     /// A map from local module to all children from which the async module
     /// status is inherited
@@ -196,6 +228,9 @@ pub struct ChunkContentResult {
     /// A map from already available async modules to all local parents that
     /// inherit the async module status
     pub available_async_modules_back_edges_inherit_async: AsyncInfo,
+    /// A map from split points to all local parents that wait for their chunk
+    /// group to be loaded
+    pub split_point_back_edges_inherit_async: AsyncInfo,
 }
 
 pub async fn chunk_content(
@@ -216,6 +251,9 @@ enum InheritAsyncEdge {
     /// not included in back edges at all since they don't influence the parent
     /// module in terms of being an async module.
     AvailableAsyncModule,
+    /// The chunk item is a split point, which is placed in its own chunk group
+    /// and loaded before the parent module is evaluated.
+    SplitPoint,
 }
 
 #[derive(Eq, PartialEq, Clone, Hash, Serialize, Deserialize, TraceRawVcs, Debug)]
@@ -238,6 +276,7 @@ enum ChunkContentGraphNode {
     // Async module that is referenced from the chunk group
     AsyncModule {
         module: Vc<Box<dyn ChunkableModule>>,
+        name: Option<String>,
     },
     // ModuleReferences that are not placed in the current chunk group
     ExternalModuleReference(Vc<Box<dyn ModuleReference>>),
@@ -271,23 +310,95 @@ struct ChunkGraphEdges(Vec<ChunkGraphEdge>);
 struct ChunkContentContext {
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     availability_info: AvailabilityInfo,
+    /// See [ChunkingContext::isolate_vendor_chunks].
+    isolate_vendor_chunks: bool,
 }
 
 #[turbo_tasks::value_impl]
 impl ChunkContentContext {
     #[turbo_tasks::function]
-    fn new(
+    async fn new(
         chunking_context: Vc<Box<dyn ChunkingContext>>,
         availability_info: Value<AvailabilityInfo>,
-    ) -> Vc<Self> {
-        Self {
+    ) -> Result<Vc<Self>> {
+        Ok(Self {
             chunking_context,
             availability_info: availability_info.into_value(),
+            isolate_vendor_chunks: *chunking_context.isolate_vendor_chunks().await?,
         }
-        .cell()
+        .cell())
     }
 }
 
+impl ChunkContentContext {
+    /// The chunk items that are already available for `module`, and aren't
+    /// placed into the chunk group again. Vendor modules ignore the
+    /// availability when vendor chunks are isolated, so that the vendor chunks
+    /// of a chunk group don't change with the app code of its parents.
+    async fn available_chunk_items(
+        &self,
+        module: Vc<Box<dyn Module>>,
+    ) -> Result<Option<Vc<AvailableChunkItems>>> {
+        let Some(available_chunk_items) = self.availability_info.available_chunk_items() else {
+            return Ok(None);
+        };
+        if self.isolate_vendor_chunks && !is_app_code(&module.ident().to_string().await?) {
+            return Ok(None);
+        }
+        Ok(Some(available_chunk_items))
+    }
+}
+
+/// Whether `module` is one of the [ChunkingContext::split_points].
+#[turbo_tasks::function]
+pub async fn is_split_point(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    module: Vc<Box<dyn Module>>,
+) -> Result<Vc<bool>> {
+    let module = module.resolve().await?;
+    for &split_point in chunking_context.split_points().await?.iter() {
+        if split_point.resolve().await? == module {
+            return Ok(Vc::cell(true));
+        }
+    }
+    Ok(Vc::cell(false))
+}
+
+/// Creates the chunk item for `module`. When the module fails to build, the
+/// chunking context can replace it with a stub chunk item, in which case the
+/// error is reported as an issue instead of failing the chunk group.
+async fn module_chunk_item(
+    module: Vc<Box<dyn ChunkableModule>>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+) -> Result<Vc<Box<dyn ChunkItem>>> {
+    let chunk_item = async {
+        let chunk_item = module.as_chunk_item(chunking_context).resolve().await?;
+        chunk_item.references().await?;
+        anyhow::Ok(chunk_item)
+    }
+    .await;
+    let error = match chunk_item {
+        Ok(chunk_item) => return Ok(chunk_item),
+        Err(error) => error,
+    };
+    let message = PrettyPrintError(&error).to_string();
+    let Some(stub) = *chunking_context
+        .failing_module_chunk_item(module, message.clone())
+        .await?
+    else {
+        return Err(error);
+    };
+    CodeGenerationIssue {
+        severity: IssueSeverity::Error.cell(),
+        path: module.ident().path(),
+        title: StyledString::Text("Module failed to build".to_string()).cell(),
+        message: StyledString::Text(message).cell(),
+    }
+    .cell()
+    .emit();
+    Ok(stub.resolve().await?)
+}
+
 #[turbo_tasks::function]
 async fn graph_node_to_referenced_nodes(
     node: ChunkGraphNodeToReferences,
@@ -357,13 +468,13 @@ async fn graph_node_to_referenced_nodes(
 
                     match chunking_type {
                         ChunkingType::Parallel => {
-                            let chunk_item = chunkable_module
-                                .as_chunk_item(chunk_content_context.chunking_context)
-                                .resolve()
-                                .await?;
-                            if let Some(available_chunk_items) = chunk_content_context
-                                .availability_info
-                                .available_chunk_items()
+                            let chunk_item = module_chunk_item(
+                                chunkable_module,
+                                chunk_content_context.chunking_context,
+                            )
+                            .await?;
+                            if let Some(available_chunk_items) =
+                                chunk_content_context.available_chunk_items(module).await?
                             {
                                 if available_chunk_items.get(chunk_item).await?.is_some() {
                                     return Ok((None, None));
@@ -382,13 +493,35 @@ async fn graph_node_to_referenced_nodes(
                             ))
                         }
                         ChunkingType::ParallelInheritAsync => {
-                            let chunk_item = chunkable_module
-                                .as_chunk_item(chunk_content_context.chunking_context)
-                                .resolve()
-                                .await?;
-                            if let Some(available_chunk_items) = chunk_content_context
-                                .availability_info
-                                .available_chunk_items()
+                            let chunk_item = module_chunk_item(
+                                chunkable_module,
+                                chunk_content_context.chunking_context,
+                            )
+                            .await?;
+                            if *is_split_point(chunk_content_context.chunking_context, module)
+                                .await?
+                                && !matches!(
+                                    *chunk_content_context
+                                        .chunking_context
+                                        .environment()
+                                        .chunk_loading()
+                                        .await?,
+                                    ChunkLoading::None
+                                )
+                            {
+                                return Ok((
+                                    Some(ChunkGraphEdge {
+                                        key: None,
+                                        node: ChunkContentGraphNode::AsyncModule {
+                                            module: chunkable_module,
+                                            name: None,
+                                        },
+                                    }),
+                                    Some((chunk_item, InheritAsyncEdge::SplitPoint)),
+                                ));
+                            }
+                            if let Some(available_chunk_items) =
+                                chunk_content_context.available_chunk_items(module).await?
                             {
                                 if let Some(info) = &*available_chunk_items.get(chunk_item).await? {
                                     if info.is_async {
@@ -415,10 +548,11 @@ async fn graph_node_to_referenced_nodes(
                             ))
                         }
                         ChunkingType::Passthrough => {
-                            let chunk_item = chunkable_module
-                                .as_chunk_item(chunk_content_context.chunking_context)
-                                .resolve()
-                                .await?;
+                            let chunk_item = module_chunk_item(
+                                chunkable_module,
+                                chunk_content_context.chunking_context,
+                            )
+                            .await?;
 
                             Ok((
                                 Some(ChunkGraphEdge {
@@ -437,13 +571,13 @@ async fn graph_node_to_referenced_nodes(
                                 .chunk_loading()
                                 .await?;
                             if matches!(*chunk_loading, ChunkLoading::None) {
-                                let chunk_item = chunkable_module
-                                    .as_chunk_item(chunk_content_context.chunking_context)
-                                    .resolve()
-                                    .await?;
-                                if let Some(available_chunk_items) = chunk_content_context
-                                    .availability_info
-                                    .available_chunk_items()
+                                let chunk_item = module_chunk_item(
+                                    chunkable_module,
+                                    chunk_content_context.chunking_context,
+                                )
+                                .await?;
+                                if let Some(available_chunk_items) =
+                                    chunk_content_context.available_chunk_items(module).await?
                                 {
                                     if available_chunk_items.get(chunk_item).await?.is_some() {
                                         return Ok((None, None));
@@ -465,6 +599,10 @@ async fn graph_node_to_referenced_nodes(
                                         key: None,
                                         node: ChunkContentGraphNode::AsyncModule {
                                             module: chunkable_module,
+                                            name: chunkable_module_reference
+                                                .chunk_group_name()
+                                                .await?
+                                                .clone_value(),
                                         },
                                     }),
                                     None,
@@ -591,10 +729,7 @@ async fn chunk_content_internal_parallel(
             Ok(Some(ChunkGraphEdge {
                 key: Some(entry),
                 node: ChunkContentGraphNode::ChunkItem {
-                    item: chunkable_module
-                        .as_chunk_item(chunking_context)
-                        .resolve()
-                        .await?,
+                    item: module_chunk_item(chunkable_module, chunking_context).await?,
                     ident: chunkable_module.ident().to_string().await?,
                 },
             }))
@@ -620,10 +755,12 @@ async fn chunk_content_internal_parallel(
 
     let mut chunk_items = IndexSet::new();
     let mut async_modules = IndexSet::new();
+    let mut async_chunk_group_names = IndexMap::new();
     let mut external_module_references = IndexSet::new();
     let mut forward_edges_inherit_async = IndexMap::new();
     let mut local_back_edges_inherit_async = IndexMap::new();
     let mut available_async_modules_back_edges_inherit_async = IndexMap::new();
+    let mut split_point_back_edges_inherit_async = IndexMap::new();
 
     for graph_node in graph_nodes {
         match graph_node {
@@ -632,9 +769,12 @@ async fn chunk_content_internal_parallel(
             ChunkContentGraphNode::ChunkItem { item, .. } => {
                 chunk_items.insert(item);
             }
-            ChunkContentGraphNode::AsyncModule { module } => {
+            ChunkContentGraphNode::AsyncModule { module, name } => {
                 let module = module.resolve().await?;
                 async_modules.insert(module);
+                if let Some(name) = name {
+                    async_chunk_group_names.entry(module).or_insert(name);
+                }
             }
             ChunkContentGraphNode::ExternalModuleReference(reference) => {
                 let reference = reference.resolve().await?;
@@ -653,6 +793,10 @@ async fn chunk_content_internal_parallel(
                                 .or_insert_with(Vec::new)
                                 .push(item)
                         }
+                        InheritAsyncEdge::SplitPoint => split_point_back_edges_inherit_async
+                            .entry(reference)
+                            .or_insert_with(Vec::new)
+                            .push(item),
                     }
                 }
                 forward_edges_inherit_async
@@ -666,10 +810,12 @@ async fn chunk_content_internal_parallel(
     Ok(ChunkContentResult {
         chunk_items,
         async_modules,
+        async_chunk_group_names,
         external_module_references,
         forward_edges_inherit_async,
         local_back_edges_inherit_async,
         available_async_modules_back_edges_inherit_async,
+        split_point_back_edges_inherit_async,
     })
 }
 
@@ -727,6 +873,9 @@ pub trait ChunkType: ValueToString {
 #[turbo_tasks::value(transparent)]
 pub struct ChunkItems(Vec<Vc<Box<dyn ChunkItem>>>);
 
+#[turbo_tasks::value(transparent)]
+pub struct OptionChunkItem(Option<Vc<Box<dyn ChunkItem>>>);
+
 #[turbo_tasks::value]
 pub struct AsyncModuleInfo {
     pub referenced_async_modules: AutoSet<Vc<Box<dyn ChunkItem>>>,
@@ -0,0 +1,108 @@
+use super::ModuleId;
+use crate::hash::HashAlgorithm;
+
+/// How the ids of chunk items are derived from their idents.
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum ModuleIdStrategy {
+    /// The ident itself, e.g. `[project]/src/index.js [client] (ecmascript)`,
+    /// which is readable when debugging.
+    #[default]
+    Named,
+    /// A number with at most `digits` (1 to 9) digits derived from a hash of
+    /// the ident, like the deterministic ids of production builds. Few digits
+    /// make collisions likely.
+    Numeric { digits: u8 },
+    /// The first `length` (1 to 16) hexadecimal characters of a hash of the
+    /// ident.
+    Hashed { length: u8 },
+}
+
+impl ModuleIdStrategy {
+    /// The id of the chunk item with the ident `ident`. Hashes of the ident
+    /// are computed with `hash_algorithm`.
+    pub fn module_id(&self, ident: &str, hash_algorithm: HashAlgorithm) -> ModuleId {
+        match *self {
+            ModuleIdStrategy::Named => ModuleId::String(ident.to_string()),
+            ModuleIdStrategy::Numeric { digits } => {
+                let modulus = 10u64.pow(digits.clamp(1, 9) as u32);
+                ModuleId::Number((hash_algorithm.hash_u64(ident) % modulus) as u32)
+            }
+            ModuleIdStrategy::Hashed { length } => {
+                let mut hash = hash_algorithm.hash_hex(ident);
+                hash.truncate(length.clamp(1, 16) as usize);
+                ModuleId::String(hash)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENT: &str = "[project]/src/index.js [client] (ecmascript)";
+
+    #[test]
+    fn test_named() {
+        assert_eq!(
+            ModuleIdStrategy::Named.module_id(IDENT, HashAlgorithm::Xxh3Hash64),
+            ModuleId::String(IDENT.to_string())
+        );
+    }
+
+    #[test]
+    fn test_numeric() {
+        for digits in 1..=9 {
+            let ModuleId::Number(id) =
+                ModuleIdStrategy::Numeric { digits }.module_id(IDENT, HashAlgorithm::Xxh3Hash64)
+            else {
+                panic!("numeric ids must be numbers");
+            };
+            assert!((id as u64) < 10u64.pow(digits as u32));
+        }
+        // Out of range lengths are clamped.
+        assert_eq!(
+            ModuleIdStrategy::Numeric { digits: 0 }.module_id(IDENT, HashAlgorithm::Xxh3Hash64),
+            ModuleIdStrategy::Numeric { digits: 1 }.module_id(IDENT, HashAlgorithm::Xxh3Hash64)
+        );
+    }
+
+    #[test]
+    fn test_hashed() {
+        let ModuleId::String(id) =
+            ModuleIdStrategy::Hashed { length: 6 }.module_id(IDENT, HashAlgorithm::Xxh3Hash64)
+        else {
+            panic!("hashed ids must be strings");
+        };
+        assert_eq!(id.len(), 6);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(
+            ModuleIdStrategy::Hashed { length: 6 }.module_id(IDENT, HashAlgorithm::Xxh3Hash64),
+            ModuleIdStrategy::Hashed { length: 6 }.module_id(IDENT, HashAlgorithm::Xxh3Hash64)
+        );
+        let ModuleId::String(id) =
+            ModuleIdStrategy::Hashed { length: 40 }.module_id(IDENT, HashAlgorithm::Xxh3Hash64)
+        else {
+            panic!("hashed ids must be strings");
+        };
+        assert_eq!(id.len(), 16);
+    }
+
+    #[test]
+    fn test_hash_algorithm() {
+        let strategy = ModuleIdStrategy::Hashed { length: 16 };
+        assert_ne!(
+            strategy.module_id(IDENT, HashAlgorithm::Xxh3Hash64),
+            strategy.module_id(IDENT, HashAlgorithm::Blake3)
+        );
+        let ModuleId::String(id) = strategy.module_id(IDENT, HashAlgorithm::Blake3) else {
+            panic!("hashed ids must be strings");
+        };
+        assert_eq!(id, HashAlgorithm::Blake3.hash_hex(IDENT)[..16]);
+        assert_eq!(
+            ModuleIdStrategy::Named.module_id(IDENT, HashAlgorithm::Blake3),
+            ModuleIdStrategy::Named.module_id(IDENT, HashAlgorithm::Xxh3Hash64)
+        );
+    }
+}
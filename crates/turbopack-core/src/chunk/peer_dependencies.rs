@@ -0,0 +1,201 @@
+//! Detects packages that have been placed into a chunk group together with a
+//! version of one of their peer dependencies that is outside of the range they
+//! declare.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use node_semver::{Range, Version};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use turbo_tasks::{Completion, TryFlatJoinIterExt, TryJoinIterExt, Vc};
+use turbo_tasks_fs::FileSystem;
+
+use super::{ChunkItem, ChunkItems};
+use crate::{
+    issue::{peer_dependency::PeerDependencyMismatchIssue, IssueExt},
+    module::Module,
+    package_json::read_package_json,
+    reference::ModuleReference,
+};
+
+/// A package in `node_modules`, identified by the path of its directory
+/// relative to the root of its file system.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PackageLocation {
+    dir: String,
+    name: String,
+}
+
+/// Returns the innermost package in `node_modules` that contains `path`.
+fn package_location(path: &str) -> Option<PackageLocation> {
+    static PACKAGE_REGEX: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?:^|/)node_modules/((?:@[^/]+/)?[^/]+)").unwrap());
+    let name = PACKAGE_REGEX.captures_iter(path).last()?.get(1)?;
+    Some(PackageLocation {
+        dir: path[..name.end()].to_string(),
+        name: name.as_str().to_string(),
+    })
+}
+
+/// Emits a [PeerDependencyMismatchIssue] for every package in `chunk_items`
+/// that imports a peer dependency whose version is outside of the range
+/// declared in its package.json. Only the imports of the packages that declare
+/// peer dependencies are resolved.
+#[turbo_tasks::function]
+pub async fn check_peer_dependencies(chunk_items: Vc<ChunkItems>) -> Result<Vc<Completion>> {
+    let chunk_items = chunk_items
+        .await?
+        .iter()
+        .map(|&chunk_item| async move {
+            let path = chunk_item.asset_ident().path().await?;
+            Ok(package_location(&path.path).map(|package| (package, chunk_item, path.fs)))
+        })
+        .try_flat_join()
+        .await?;
+
+    let mut packages = IndexMap::<_, (_, Vec<_>)>::new();
+    for (package, chunk_item, fs) in chunk_items {
+        packages
+            .entry(package)
+            .or_insert_with(|| (fs, Vec::new()))
+            .1
+            .push(chunk_item);
+    }
+
+    for (package, (fs, chunk_items)) in packages {
+        let package_json_path = fs.root().join(format!("{}/package.json", package.dir));
+        let Some(package_json) = &*read_package_json(package_json_path).await? else {
+            continue;
+        };
+        let Some(peer_dependencies) = package_json["peerDependencies"].as_object() else {
+            continue;
+        };
+        if peer_dependencies.is_empty() {
+            continue;
+        }
+        let package_version = package_json["version"].as_str().map(|v| v.to_string());
+
+        for (importer, resolved) in imported_packages(&chunk_items, &package).await? {
+            let Some(range) = peer_dependencies
+                .get(&resolved.name)
+                .and_then(|range| range.as_str())
+            else {
+                continue;
+            };
+            let Ok(parsed_range) = Range::parse(range) else {
+                continue;
+            };
+            let resolved_package_json_path =
+                fs.root().join(format!("{}/package.json", resolved.dir));
+            let Some(resolved_package_json) =
+                &*read_package_json(resolved_package_json_path).await?
+            else {
+                continue;
+            };
+            let Some(resolved_version) = resolved_package_json["version"].as_str() else {
+                continue;
+            };
+            let Ok(version) = Version::parse(resolved_version) else {
+                continue;
+            };
+            if satisfies(&parsed_range, &version) {
+                continue;
+            }
+            PeerDependencyMismatchIssue {
+                package_json_path,
+                package_name: package.name.clone(),
+                package_version: package_version.clone(),
+                peer_name: resolved.name,
+                peer_range: range.to_string(),
+                resolved_package_json_path,
+                resolved_version: resolved_version.to_string(),
+                importer,
+            }
+            .cell()
+            .emit();
+        }
+    }
+    Ok(Completion::new())
+}
+
+/// Whether `version` is in `range`. Unlike [Range::satisfies], prereleases of
+/// versions in the range satisfy it too, e.g. `19.0.0-rc.1` satisfies `>=18`,
+/// as package managers install them for such ranges when asked to.
+fn satisfies(range: &Range, version: &Version) -> bool {
+    if range.satisfies(version) {
+        return true;
+    }
+    if version.pre_release.is_empty() {
+        return false;
+    }
+    let release = Version {
+        pre_release: Vec::new(),
+        build: Vec::new(),
+        ..version.clone()
+    };
+    range.satisfies(&release)
+}
+
+/// The packages other than `package` that the `chunk_items` of `package`
+/// import, with the first chunk item importing them. Every imported package
+/// is only returned once per `package`, so a mismatch is reported once for
+/// every package that declares the peer dependency.
+async fn imported_packages(
+    chunk_items: &[Vc<Box<dyn ChunkItem>>],
+    package: &PackageLocation,
+) -> Result<Vec<(Vc<Box<dyn ChunkItem>>, PackageLocation)>> {
+    let imports = chunk_items
+        .iter()
+        .map(|&chunk_item| async move {
+            let references = chunk_item.references().await?;
+            let modules = references
+                .iter()
+                .map(|reference| reference.resolve_reference().primary_modules())
+                .try_join()
+                .await?;
+            modules
+                .iter()
+                .flat_map(|modules| modules.iter())
+                .map(|module| async move {
+                    let path = module.ident().path().await?;
+                    Ok(package_location(&path.path).map(|package| (chunk_item, package)))
+                })
+                .try_flat_join()
+                .await
+        })
+        .try_join()
+        .await?;
+    let mut seen = HashSet::new();
+    Ok(imports
+        .into_iter()
+        .flatten()
+        .filter(|(_, imported)| imported != package && seen.insert(imported.clone()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_location() {
+        let package = package_location("node_modules/a/node_modules/@scope/b/index.js").unwrap();
+        assert_eq!(package.name, "@scope/b");
+        assert_eq!(package.dir, "node_modules/a/node_modules/@scope/b");
+        assert!(package_location("src/index.js").is_none());
+    }
+
+    #[test]
+    fn test_satisfies_prereleases() {
+        let range = Range::parse(">=18").unwrap();
+        assert!(satisfies(&range, &Version::parse("18.2.0").unwrap()));
+        assert!(satisfies(&range, &Version::parse("19.0.0-rc.1").unwrap()));
+        assert!(!satisfies(&range, &Version::parse("17.0.2").unwrap()));
+        assert!(!satisfies(
+            &range,
+            &Version::parse("17.1.0-beta.0").unwrap()
+        ));
+    }
+}
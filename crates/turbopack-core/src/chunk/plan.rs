@@ -0,0 +1,175 @@
+//! Chunk plans, which describe the chunks of a chunk group without generating
+//! them.
+//!
+//! Chunking contexts in dry-run mode return a [ChunkGroupPlan] from
+//! `chunk_group` and `evaluated_chunk_group` instead of output assets. Only the
+//! chunk items are generated to determine their sizes, which the chunking is
+//! based on. The chunks are named after their output assets, but these aren't
+//! assembled, hashed, minified or emitted, which makes this much faster to
+//! compute, e.g. for analysis tools or to preview how a change affects the
+//! chunks.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    pin::Pin,
+};
+
+use anyhow::Result;
+use futures::Future;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{TryJoinIterExt, Value, ValueToString, Vc};
+
+use super::{
+    availability_info::{AvailabilityDump, AvailabilityInfo},
+    ChunkableModule, ChunkingContext,
+};
+
+/// A chunk that would be created for a chunk group.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct ChunkPlan {
+    /// The key of the split that created the chunk, e.g.
+    /// `ecmascript-vendors-react-dom`.
+    pub key: String,
+    /// The path of the output asset of the chunk, relative to the output root
+    /// of the chunking context. It's `None` while the chunk is being split,
+    /// see [super::ChunkingPolicy::should_split].
+    pub path: Option<String>,
+    /// The size of the chunk items in bytes. This is the size of the generated
+    /// code of the modules, before it is minified.
+    pub size: u64,
+    /// The idents of the modules included in the chunk.
+    pub modules: Vec<String>,
+}
+
+/// The chunks that would be created for a chunk group.
+#[turbo_tasks::value(shared)]
+pub struct ChunkGroupPlan {
+    /// The chunks of the chunk group, including the chunks of the loaders of
+    /// its async chunk groups.
+    pub chunks: Vec<ChunkPlan>,
+    /// The paths of the output assets referenced by the chunk group, e.g.
+    /// static assets, relative to the output root of the chunking context.
+    pub referenced_assets: Vec<String>,
+    /// The plans of the chunk groups loaded asynchronously from this chunk
+    /// group, keyed by the ident of the module they are loaded for.
+    pub async_chunk_groups: Vec<(String, Vc<ChunkGroupPlan>)>,
+    /// The modules that are assumed to be available from the chunk groups the
+    /// chunk group is loaded from, and that aren't included in its chunks.
+    pub availability_info: AvailabilityInfo,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkGroupPlan {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        ChunkGroupPlan {
+            chunks: Vec::new(),
+            referenced_assets: Vec::new(),
+            async_chunk_groups: Vec::new(),
+            availability_info: AvailabilityInfo::Untracked,
+        }
+        .cell()
+    }
+
+    /// The plan of the chunk group that would be loaded asynchronously for
+    /// `module`. When `chunking_context` isn't in dry-run mode, the plan is
+    /// empty.
+    #[turbo_tasks::function]
+    pub async fn async_chunk_group(
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Result<Vc<Self>> {
+        Ok(chunking_context
+            .chunk_group(module, availability_info)
+            .await?
+            .plan
+            .unwrap_or_else(ChunkGroupPlan::empty))
+    }
+}
+
+/// Creates the [ChunkGroupPlan::async_chunk_groups] for `async_modules`.
+pub async fn async_chunk_group_plans(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    async_modules: impl IntoIterator<Item = Vc<Box<dyn ChunkableModule>>>,
+    availability_info: AvailabilityInfo,
+) -> Result<Vec<(String, Vc<ChunkGroupPlan>)>> {
+    async_modules
+        .into_iter()
+        .map(|module| async move {
+            Ok((
+                module.ident().to_string().await?.clone_value(),
+                ChunkGroupPlan::async_chunk_group(
+                    chunking_context,
+                    module,
+                    Value::new(availability_info),
+                ),
+            ))
+        })
+        .try_join()
+        .await
+}
+
+/// A [ChunkGroupPlan] with all of its async chunk groups resolved, which can be
+/// serialized for analysis tools.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkGroupPlanTree {
+    pub chunks: Vec<ChunkPlan>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub referenced_assets: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub async_chunk_groups: BTreeMap<String, ChunkGroupPlanTree>,
+    /// The modules assumed to be available from the ancestor chunk groups,
+    /// when there are any. Modules that are missing at runtime in a nested
+    /// async chunk group were usually wrongly assumed to be available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available: Option<AvailabilityDump>,
+}
+
+impl ChunkGroupPlanTree {
+    /// Resolves `plan` and its async chunk groups. Async chunk groups that
+    /// would be loaded from within themselves are only included once.
+    pub async fn capture(plan: Vc<ChunkGroupPlan>) -> Result<Self> {
+        Self::capture_inner(plan, HashSet::new()).await
+    }
+
+    fn capture_inner(
+        plan: Vc<ChunkGroupPlan>,
+        mut ancestors: HashSet<Vc<ChunkGroupPlan>>,
+    ) -> Pin<Box<dyn Future<Output = Result<Self>> + Send>> {
+        Box::pin(async move {
+            let plan = plan.resolve().await?;
+            ancestors.insert(plan);
+            let plan_ref = plan.await?;
+            let mut async_chunk_groups = BTreeMap::new();
+            for (module, async_plan) in plan_ref.async_chunk_groups.iter() {
+                let async_plan = async_plan.resolve().await?;
+                if ancestors.contains(&async_plan) {
+                    continue;
+                }
+                async_chunk_groups.insert(
+                    module.clone(),
+                    Self::capture_inner(async_plan, ancestors.clone()).await?,
+                );
+            }
+            let available = plan_ref.availability_info.dump().await?;
+            Ok(ChunkGroupPlanTree {
+                chunks: plan_ref.chunks.clone(),
+                referenced_assets: plan_ref.referenced_assets.clone(),
+                async_chunk_groups,
+                available: (!available.levels.is_empty()).then_some(available),
+            })
+        })
+    }
+
+    /// The total size of the chunks of this chunk group, without its async
+    /// chunk groups.
+    pub fn size(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.size).sum()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
@@ -0,0 +1,36 @@
+use turbo_tasks::Vc;
+
+use super::plan::ChunkPlan;
+use crate::module::Module;
+
+/// Customizes how a chunking context assigns modules to chunks, e.g. to keep
+/// the packages of each team of a monorepo in chunks of their own.
+///
+/// Every hook may return `None` to fall back to the default behavior of the
+/// chunking context.
+#[turbo_tasks::value_trait]
+pub trait ChunkingPolicy {
+    /// Whether `module_a` and `module_b` may be placed into the same chunk.
+    fn can_be_in_same_chunk(
+        self: Vc<Self>,
+        _module_a: Vc<Box<dyn Module>>,
+        _module_b: Vc<Box<dyn Module>>,
+    ) -> Vc<Option<bool>> {
+        Vc::cell(None)
+    }
+
+    /// The name of the chunks `module` is placed into. Modules with the same
+    /// name are placed into the same chunks, apart from all other modules.
+    fn chunk_name_for(self: Vc<Self>, _module: Vc<Box<dyn Module>>) -> Vc<Option<String>> {
+        Vc::cell(None)
+    }
+
+    /// Whether the modules of `chunk` are split further into multiple chunks.
+    /// By default, chunks are split when they exceed the maximum chunk size.
+    fn should_split(self: Vc<Self>, _chunk: Vc<ChunkPlan>) -> Vc<Option<bool>> {
+        Vc::cell(None)
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionChunkingPolicy(Option<Vc<Box<dyn ChunkingPolicy>>>);
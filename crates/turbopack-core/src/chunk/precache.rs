@@ -0,0 +1,104 @@
+//! Precache manifests for service workers.
+//!
+//! A service worker caches the assets of an app when it's installed, so the
+//! app works offline. The precache manifest lists the URLs of these assets,
+//! relative to the manifest, together with a revision that changes whenever
+//! the content of an asset changes, so only changed assets are fetched again
+//! when a new service worker is installed.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+
+use crate::{
+    asset::{Asset, AssetContent},
+    hash::HashAlgorithm,
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+};
+
+/// An asset to precache.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct PrecacheEntry {
+    pub url: String,
+    /// The hash of the content, see [HashAlgorithm::hash_hex].
+    pub revision: String,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct PrecacheEntries(Vec<PrecacheEntry>);
+
+/// Returns the entries for `assets` and all assets they reference, ordered by
+/// URL. The URLs are relative to `base`. Source maps aren't precached, as
+/// they're only needed for debugging.
+#[turbo_tasks::function]
+pub async fn precache_entries(
+    base: Vc<FileSystemPath>,
+    assets: Vc<OutputAssets>,
+) -> Result<Vc<PrecacheEntries>> {
+    let base = base.await?;
+    let mut entries = BTreeMap::new();
+    for &asset in all_assets_from_entries(assets).await?.iter() {
+        let path = asset.ident().path().await?;
+        if path.extension_ref() == Some("map") {
+            continue;
+        }
+        let Some(url) = base.get_relative_path_to(&path) else {
+            continue;
+        };
+        let AssetContent::File(file) = &*asset.content().await? else {
+            continue;
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            continue;
+        };
+        let revision = HashAlgorithm::default().hash_hex(file.content());
+        entries.insert(url.clone(), PrecacheEntry { url, revision });
+    }
+    Ok(Vc::cell(entries.into_values().collect()))
+}
+
+/// The path of the [PrecacheManifestAsset] of the service worker at
+/// `service_worker_path`, e.g. `sw.precache.json` for `sw.js`.
+pub fn precache_manifest_path(service_worker_path: Vc<FileSystemPath>) -> Vc<FileSystemPath> {
+    service_worker_path.with_extension("precache.json".to_string())
+}
+
+/// A JSON asset containing the [PrecacheEntries] of a service worker, relative
+/// to the directory of the manifest.
+#[turbo_tasks::value]
+pub struct PrecacheManifestAsset {
+    path: Vc<FileSystemPath>,
+    assets: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl PrecacheManifestAsset {
+    /// Creates a new manifest that will be emitted at `path`.
+    #[turbo_tasks::function]
+    pub fn new(path: Vc<FileSystemPath>, assets: Vc<OutputAssets>) -> Vc<Self> {
+        PrecacheManifestAsset { path, assets }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for PrecacheManifestAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for PrecacheManifestAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let entries = precache_entries(self.path.parent(), self.assets).await?;
+        let json = serde_json::to_string_pretty(&*entries)?;
+        Ok(AssetContent::file(File::from(json).into()))
+    }
+}
@@ -0,0 +1,224 @@
+//! Prefetch manifests for client-side routers.
+//!
+//! A prefetch manifest maps the name of every route to the JS chunks, CSS
+//! chunks and critical assets (e.g. fonts and images referenced by the CSS)
+//! of the route's chunk group, so a router can prefetch them when a link to
+//! the route is hovered. The paths are relative to the output root, like the
+//! chunk paths the runtime loads relative to the chunk base path.
+//!
+//! The manifest is available as a JSON [PrefetchManifestAsset], which is
+//! derived from the chunk groups, so it stays current when a rebuild changes
+//! the chunks of a route. Routes can query it at runtime with the module of
+//! [prefetch_manifest_helper_source], which fetches the JSON asset. The helper
+//! doesn't embed the manifest, as routes importing it would otherwise depend
+//! on their own chunks.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use turbo_tasks::{TryJoinIterExt, Value, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+
+use super::{availability_info::AvailabilityInfo, ChunkableModule, ChunkingContext};
+use crate::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source::Source,
+    virtual_source::VirtualSource,
+};
+
+/// The routes of a prefetch manifest, mapping the name of a route (e.g. its
+/// pathname) to its entry module, which is loaded as a chunk group.
+#[turbo_tasks::value(transparent)]
+pub struct PrefetchRoutes(BTreeMap<String, Vc<Box<dyn ChunkableModule>>>);
+
+/// The resources to prefetch for a single route.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+pub struct RoutePrefetchResources {
+    pub js: Vec<String>,
+    pub css: Vec<String>,
+    /// Other assets referenced by the chunks, e.g. fonts and images.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assets: Vec<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct PrefetchManifest(BTreeMap<String, RoutePrefetchResources>);
+
+/// The output assets of the chunk groups of all routes.
+#[turbo_tasks::function]
+async fn route_chunk_group_assets(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    routes: Vc<PrefetchRoutes>,
+) -> Result<Vc<OutputAssets>> {
+    let assets = routes
+        .await?
+        .values()
+        .map(|&module| async move {
+            Ok(chunking_context
+                .chunk_group(module, Value::new(AvailabilityInfo::Root))
+                .await?
+                .assets
+                .await?)
+        })
+        .try_join()
+        .await?;
+    let assets: IndexSet<_> = assets.iter().flat_map(|a| a.iter().copied()).collect();
+    Ok(Vc::cell(assets.into_iter().collect()))
+}
+
+/// Computes the resources to prefetch for every route.
+#[turbo_tasks::function]
+pub async fn prefetch_manifest(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    routes: Vc<PrefetchRoutes>,
+) -> Result<Vc<PrefetchManifest>> {
+    let output_root = chunking_context.output_root().await?;
+    let routes = routes
+        .await?
+        .iter()
+        .map(|(name, &module)| {
+            let output_root = &output_root;
+            async move {
+                let assets = chunking_context
+                    .chunk_group(module, Value::new(AvailabilityInfo::Root))
+                    .await?
+                    .assets
+                    .await?;
+                let mut resources = RoutePrefetchResources::default();
+                let mut assets_set = IndexSet::new();
+                for &asset in assets.iter() {
+                    let path = asset.ident().path().await?;
+                    let Some(path) = output_root.get_path_to(&path) else {
+                        continue;
+                    };
+                    if path.ends_with(".js") {
+                        resources.js.push(path.to_string());
+                    } else if path.ends_with(".css") {
+                        resources.css.push(path.to_string());
+                    } else {
+                        continue;
+                    }
+                    for &reference in asset.references().await?.iter() {
+                        let reference_path = reference.ident().path().await?;
+                        let Some(reference_path) = output_root.get_path_to(&reference_path) else {
+                            continue;
+                        };
+                        // Chunks are listed on their own and source maps aren't
+                        // needed to render the route.
+                        if [".js", ".css", ".map"]
+                            .iter()
+                            .any(|extension| reference_path.ends_with(extension))
+                        {
+                            continue;
+                        }
+                        assets_set.insert(reference_path.to_string());
+                    }
+                }
+                resources.assets = assets_set.into_iter().collect();
+                Ok((name.clone(), resources))
+            }
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(routes.into_iter().collect()))
+}
+
+/// A JSON asset containing the [PrefetchManifest] of a set of routes.
+#[turbo_tasks::value]
+pub struct PrefetchManifestAsset {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    routes: Vc<PrefetchRoutes>,
+}
+
+#[turbo_tasks::value_impl]
+impl PrefetchManifestAsset {
+    /// Creates a new manifest that will be emitted at `path`.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        routes: Vc<PrefetchRoutes>,
+    ) -> Vc<Self> {
+        PrefetchManifestAsset {
+            path,
+            chunking_context,
+            routes,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for PrefetchManifestAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<OutputAssets> {
+        route_chunk_group_assets(self.chunking_context, self.routes)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for PrefetchManifestAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let manifest = prefetch_manifest(self.chunking_context, self.routes).await?;
+        let json = serde_json::to_string(&*manifest)?;
+        Ok(AssetContent::file(File::from(json).into()))
+    }
+}
+
+/// Returns an ecmascript source for a helper that exposes the
+/// [PrefetchManifest] at runtime.
+///
+/// The helper exports `loadPrefetchManifest()`, which fetches the
+/// [PrefetchManifestAsset] from `manifest_url` once, and an async
+/// `prefetchResources(route)` function that resolves to the resources of a
+/// route, or `undefined` when the route is unknown. It should be placed at
+/// `path` and processed with the asset context of the module graph that
+/// imports it. As it only depends on the URL of the manifest, routes can
+/// import it.
+#[turbo_tasks::function]
+pub fn prefetch_manifest_helper_source(
+    path: Vc<FileSystemPath>,
+    manifest_url: String,
+) -> Result<Vc<Box<dyn Source>>> {
+    let code = format!(
+        r#"const MANIFEST_URL = {manifest_url};
+
+let prefetchManifest;
+
+export function loadPrefetchManifest() {{
+    if (!prefetchManifest) {{
+        prefetchManifest = fetch(MANIFEST_URL).then((response) => {{
+            if (!response.ok) {{
+                throw new Error(`Failed to load the prefetch manifest from ${{MANIFEST_URL}}`);
+            }}
+            return response.json();
+        }});
+    }}
+    return prefetchManifest;
+}}
+
+export async function prefetchResources(route) {{
+    const manifest = await loadPrefetchManifest();
+    return Object.prototype.hasOwnProperty.call(manifest, route)
+        ? manifest[route]
+        : undefined;
+}}
+"#,
+        manifest_url = serde_json::to_string(&manifest_url)?,
+    );
+    Ok(Vc::upcast(VirtualSource::new(
+        path,
+        AssetContent::file(File::from(code).into()),
+    )))
+}
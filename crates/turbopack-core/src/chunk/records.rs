@@ -0,0 +1,256 @@
+//! Records of the ids of chunk items and the names of chunks, which pin them
+//! across restarts of a dev server.
+//!
+//! Browsers cache chunks by their URL, and a long-running HMR session refers
+//! to modules by their id. Both stay valid after a restart when the chunking
+//! context reads the records written by the previous session, see
+//! [ChunkingContext::records](super::ChunkingContext::records). Ids are
+//! derived from idents, so pinning them matters when they would be derived
+//! differently, e.g. after the [ModuleIdStrategy] changed, or when a derived
+//! id is already pinned for another module and a different one is chosen,
+//! see [unpinned_module_id].
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+};
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{FileContent, FileJsonContent, FileSystemPath};
+
+use super::{ModuleId, ModuleIdStrategy};
+use crate::{
+    hash::HashAlgorithm,
+    issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+};
+
+/// The pinned ids of chunk items and names of chunks.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkingRecords {
+    /// Maps the idents of chunk items to their ids.
+    #[serde(default)]
+    pub module_ids: BTreeMap<String, ModuleId>,
+    /// Maps the idents of chunks to their paths relative to the output root.
+    #[serde(default)]
+    pub chunk_names: BTreeMap<String, String>,
+}
+
+/// The module ids pinned by [ChunkingRecords].
+#[turbo_tasks::value(transparent)]
+pub struct PinnedModuleIds(HashSet<ModuleId>);
+
+#[turbo_tasks::value_impl]
+impl ChunkingRecords {
+    /// Records that don't pin anything.
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        ChunkingRecords::default().cell()
+    }
+
+    /// The ids of all chunk items with recorded ids.
+    #[turbo_tasks::function]
+    pub async fn pinned_module_ids(self: Vc<Self>) -> Result<Vc<PinnedModuleIds>> {
+        Ok(Vc::cell(self.await?.module_ids.values().cloned().collect()))
+    }
+}
+
+impl ChunkingRecords {
+    /// Adds the entries of `other`, keeping the entries of `self` for idents
+    /// recorded in both.
+    pub fn extend(&mut self, other: &ChunkingRecords) {
+        for (ident, id) in &other.module_ids {
+            self.module_ids
+                .entry(ident.clone())
+                .or_insert_with(|| id.clone());
+        }
+        for (ident, name) in &other.chunk_names {
+            self.chunk_names
+                .entry(ident.clone())
+                .or_insert_with(|| name.clone());
+        }
+    }
+
+    /// Serializes the records as pretty-printed JSON, with the idents sorted
+    /// so that the file diffs well.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// How many other ids are tried for a chunk item whose derived id is pinned
+/// for another chunk item.
+const MAX_MODULE_ID_ATTEMPTS: u32 = 100;
+
+/// Derives the id of the chunk item with `ident` that has no recorded id.
+/// When the id `strategy` derives is `pinned` for another chunk item, ids are
+/// derived from `ident` with a counter until one isn't pinned. The records of
+/// the session pin that id in turn.
+pub fn unpinned_module_id(
+    strategy: ModuleIdStrategy,
+    hash_algorithm: HashAlgorithm,
+    ident: &str,
+    pinned: &HashSet<ModuleId>,
+) -> ModuleId {
+    let id = strategy.module_id(ident, hash_algorithm);
+    if !pinned.contains(&id) {
+        return id;
+    }
+    (1..=MAX_MODULE_ID_ATTEMPTS)
+        .map(|attempt| strategy.module_id(&format!("{ident} ({attempt})"), hash_algorithm))
+        .find(|id| !pinned.contains(id))
+        // All ids are taken with very short ids. The collision is reported
+        // when the chunk item is chunked.
+        .unwrap_or(id)
+}
+
+/// Reads the records file at `path`. Missing files don't pin anything, so the
+/// first session starts without records. Invalid files are reported and
+/// ignored.
+#[turbo_tasks::function]
+pub async fn read_chunking_records(path: Vc<FileSystemPath>) -> Result<Vc<ChunkingRecords>> {
+    let error_message = match &*path.read_json().await? {
+        FileJsonContent::NotFound => return Ok(ChunkingRecords::empty()),
+        FileJsonContent::Content(json) => {
+            match serde_json::from_value::<ChunkingRecords>(json.clone()) {
+                Ok(records) => return Ok(records.cell()),
+                Err(err) => format!("invalid records: {err}"),
+            }
+        }
+        FileJsonContent::Unparseable(e) => {
+            let mut message = "invalid JSON: ".to_string();
+            if let FileContent::Content(content) = &*path.read().await? {
+                let text = content.content().to_str()?;
+                e.write_with_content(&mut message, &text)?;
+            } else {
+                write!(message, "{}", e)?;
+            }
+            message
+        }
+    };
+    ChunkingRecordsIssue {
+        path,
+        error_message,
+    }
+    .cell()
+    .emit();
+    Ok(ChunkingRecords::empty())
+}
+
+/// A records file can't be read, so module ids and chunk names aren't pinned.
+#[turbo_tasks::value(shared)]
+struct ChunkingRecordsIssue {
+    path: Vc<FileSystemPath>,
+    error_message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ChunkingRecordsIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Error parsing chunking records".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("parse".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(format!(
+                "{}\nModule ids and chunk names are derived from scratch, so caches of the \
+                 previous session are invalidated.",
+                self.error_message
+            ))
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extend_keeps_existing_entries() {
+        let mut records = ChunkingRecords::default();
+        records
+            .module_ids
+            .insert("a".to_string(), ModuleId::Number(1));
+        let mut other = ChunkingRecords::default();
+        other
+            .module_ids
+            .insert("a".to_string(), ModuleId::Number(2));
+        other
+            .module_ids
+            .insert("b".to_string(), ModuleId::Number(3));
+        other
+            .chunk_names
+            .insert("c".to_string(), "c.js".to_string());
+
+        records.extend(&other);
+        assert_eq!(records.module_ids["a"], ModuleId::Number(1));
+        assert_eq!(records.module_ids["b"], ModuleId::Number(3));
+        assert_eq!(records.chunk_names["c"], "c.js");
+    }
+
+    #[test]
+    fn test_unpinned_module_id_avoids_pinned_ids() {
+        let strategy = ModuleIdStrategy::Numeric { digits: 2 };
+        let derived = strategy.module_id("a", HashAlgorithm::default());
+        assert_eq!(
+            unpinned_module_id(strategy, HashAlgorithm::default(), "a", &HashSet::new()),
+            derived
+        );
+
+        let pinned = HashSet::from([derived.clone()]);
+        let id = unpinned_module_id(strategy, HashAlgorithm::default(), "a", &pinned);
+        assert_ne!(id, derived);
+        // The same id is chosen again, so that it's stable across restarts
+        // until it's pinned itself.
+        assert_eq!(
+            unpinned_module_id(strategy, HashAlgorithm::default(), "a", &pinned),
+            id
+        );
+    }
+
+    #[test]
+    fn test_unpinned_module_id_gives_up_when_all_ids_are_pinned() {
+        let strategy = ModuleIdStrategy::Numeric { digits: 1 };
+        let pinned = (0..10).map(ModuleId::Number).collect();
+        assert_eq!(
+            unpinned_module_id(strategy, HashAlgorithm::default(), "a", &pinned),
+            strategy.module_id("a", HashAlgorithm::default())
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut records = ChunkingRecords::default();
+        records
+            .module_ids
+            .insert("a".to_string(), ModuleId::String("x".to_string()));
+        records
+            .module_ids
+            .insert("b".to_string(), ModuleId::Number(42));
+        let json = records.to_json().unwrap();
+        assert!(json.contains("\"moduleIds\""));
+        let parsed: ChunkingRecords = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.module_ids, records.module_ids);
+        assert!(parsed.chunk_names.is_empty());
+    }
+}
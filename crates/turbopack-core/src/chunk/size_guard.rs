@@ -0,0 +1,225 @@
+//! Guards against chunk group size regressions.
+//!
+//! The sizes of named chunk groups are compared against a baseline file, which
+//! is committed to the repository. Chunk groups that grew beyond the
+//! configured thresholds are reported as error [SizeRegressionIssue]s, so the
+//! build fails in the change that introduced the regression. When the growth
+//! is intended, the baseline is updated with [update_size_baseline].
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{Completion, TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+
+use super::snapshot::ChunkGroupSnapshot;
+use crate::{
+    issue::{size_regression::SizeRegressionIssue, IssueExt},
+    output::OutputAssets,
+};
+
+/// Named chunk groups whose sizes are guarded, e.g. one per entry or route.
+#[turbo_tasks::value(transparent)]
+pub struct NamedChunkGroups(BTreeMap<String, Vc<OutputAssets>>);
+
+/// How much a chunk group may grow compared to the baseline. A chunk group
+/// regresses when it exceeds any of the limits.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SizeThreshold {
+    /// The growth in bytes.
+    pub max_growth: Option<u64>,
+    /// The growth in percent of the baseline size.
+    pub max_growth_percent: Option<u32>,
+}
+
+impl SizeThreshold {
+    fn is_exceeded(&self, before: u64, after: u64) -> bool {
+        let Some(growth) = after.checked_sub(before) else {
+            return false;
+        };
+        self.max_growth.map_or(false, |max| growth > max)
+            || self
+                .max_growth_percent
+                .map_or(false, |max| growth * 100 > before * max as u64)
+    }
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+pub struct SizeGuardConfig {
+    /// The threshold of chunk groups without a threshold of their own.
+    pub default: SizeThreshold,
+    /// Thresholds of specific chunk groups, by name.
+    pub chunk_groups: BTreeMap<String, SizeThreshold>,
+}
+
+/// The contents of a baseline file, mapping the names of chunk groups to
+/// their size in bytes.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeBaseline {
+    pub chunk_groups: BTreeMap<String, u64>,
+}
+
+/// A chunk group that grew beyond its [SizeThreshold].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeRegression {
+    pub chunk_group: String,
+    pub before: u64,
+    pub after: u64,
+    pub threshold: SizeThreshold,
+}
+
+impl SizeBaseline {
+    /// Measures the total size of the output assets of every chunk group.
+    pub async fn capture(chunk_groups: Vc<NamedChunkGroups>) -> Result<Self> {
+        let chunk_groups = chunk_groups
+            .await?
+            .iter()
+            .map(|(name, &assets)| async move {
+                Ok((
+                    name.clone(),
+                    ChunkGroupSnapshot::capture(assets).await?.size(),
+                ))
+            })
+            .try_join()
+            .await?;
+        Ok(SizeBaseline {
+            chunk_groups: chunk_groups.into_iter().collect(),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The chunk groups of `current` that grew beyond their threshold compared
+    /// to `self`. Chunk groups that aren't in the baseline are not checked.
+    pub fn regressions(
+        &self,
+        current: &SizeBaseline,
+        config: &SizeGuardConfig,
+    ) -> Vec<SizeRegression> {
+        current
+            .chunk_groups
+            .iter()
+            .filter_map(|(name, &after)| {
+                let &before = self.chunk_groups.get(name)?;
+                let threshold = *config.chunk_groups.get(name).unwrap_or(&config.default);
+                threshold
+                    .is_exceeded(before, after)
+                    .then(|| SizeRegression {
+                        chunk_group: name.clone(),
+                        before,
+                        after,
+                        threshold,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Compares the sizes of `chunk_groups` against the baseline file at
+/// `baseline_path` and emits a [SizeRegressionIssue] for every chunk group that
+/// grew beyond its threshold. Nothing is checked when the baseline file
+/// doesn't exist yet.
+#[turbo_tasks::function]
+pub async fn check_size_regressions(
+    baseline_path: Vc<FileSystemPath>,
+    chunk_groups: Vc<NamedChunkGroups>,
+    config: Vc<SizeGuardConfig>,
+) -> Result<Vc<Completion>> {
+    let FileContent::Content(file) = &*baseline_path.read().await? else {
+        return Ok(Completion::immutable());
+    };
+    let baseline_path_str = baseline_path.to_string().await?;
+    let baseline = SizeBaseline::from_json(&file.content().to_str()?)
+        .with_context(|| format!("failed to parse the size baseline {}", baseline_path_str))?;
+    let current = SizeBaseline::capture(chunk_groups).await?;
+    for regression in baseline.regressions(&current, &*config.await?) {
+        SizeRegressionIssue {
+            baseline_path,
+            chunk_group: regression.chunk_group,
+            before: regression.before,
+            after: regression.after,
+            max_growth: regression.threshold.max_growth,
+            max_growth_percent: regression.threshold.max_growth_percent,
+        }
+        .cell()
+        .emit();
+    }
+    Ok(Completion::new())
+}
+
+/// Writes the current sizes of `chunk_groups` to the baseline file at
+/// `baseline_path`.
+#[turbo_tasks::function]
+pub async fn update_size_baseline(
+    baseline_path: Vc<FileSystemPath>,
+    chunk_groups: Vc<NamedChunkGroups>,
+) -> Result<Vc<Completion>> {
+    let baseline = SizeBaseline::capture(chunk_groups).await?;
+    Ok(baseline_path.write(FileContent::Content(File::from(baseline.to_json()?)).cell()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline(chunk_groups: &[(&str, u64)]) -> SizeBaseline {
+        SizeBaseline {
+            chunk_groups: chunk_groups
+                .iter()
+                .map(|&(name, size)| (name.to_string(), size))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_regressions() {
+        let before = baseline(&[("main", 1000), ("about", 1000), ("shrunk", 1000)]);
+        let after = baseline(&[
+            ("main", 1200),
+            ("about", 1050),
+            ("shrunk", 500),
+            ("added", 100_000),
+        ]);
+        let config = SizeGuardConfig {
+            default: SizeThreshold {
+                max_growth: Some(100),
+                max_growth_percent: None,
+            },
+            chunk_groups: BTreeMap::from([(
+                "about".to_string(),
+                SizeThreshold {
+                    max_growth: None,
+                    max_growth_percent: Some(4),
+                },
+            )]),
+        };
+
+        let regressions = before.regressions(&after, &config);
+        assert_eq!(
+            regressions
+                .iter()
+                .map(|r| (r.chunk_group.as_str(), r.before, r.after))
+                .collect::<Vec<_>>(),
+            vec![("about", 1000, 1050), ("main", 1000, 1200)]
+        );
+        assert!(before.regressions(&before, &config).is_empty());
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let before = baseline(&[("main", 1000)]);
+        assert_eq!(
+            SizeBaseline::from_json(&before.to_json().unwrap()).unwrap(),
+            before
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Snapshots of chunk groups with their sizes and modules, and diffs between
+//! two snapshots.
+//!
+//! A snapshot is a compact, serializable artifact meant to be stored (e.g. per
+//! commit in CI) and compared against a later snapshot to review how a change
+//! affects the output: which modules were added or removed, how the size of
+//! every chunk group changed, and which packages are newly duplicated.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileContent;
+
+use super::OutputChunk;
+use crate::{
+    asset::{Asset, AssetContent},
+    output::OutputAssets,
+};
+
+/// A snapshot of a set of named chunk groups.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkGraphSnapshot {
+    pub chunk_groups: BTreeMap<String, ChunkGroupSnapshot>,
+}
+
+/// A snapshot of a single chunk group.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkGroupSnapshot {
+    /// Maps the paths of the output assets of the chunk group to their
+    /// snapshots.
+    pub chunks: BTreeMap<String, ChunkSnapshot>,
+}
+
+/// A snapshot of a single output asset.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkSnapshot {
+    /// The size of the content in bytes.
+    pub size: u64,
+    /// The ids of the modules included in the chunk, if it is an
+    /// [OutputChunk].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<String>,
+}
+
+impl ChunkGroupSnapshot {
+    /// Captures the sizes and modules of the output assets of a chunk group,
+    /// e.g. [ChunkGroupResult::assets](super::ChunkGroupResult).
+    pub async fn capture(assets: Vc<OutputAssets>) -> Result<Self> {
+        let chunks = assets
+            .await?
+            .iter()
+            .map(|&asset| async move {
+                let path = asset.ident().path().to_string().await?.clone_value();
+                let size = match &*asset.content().await? {
+                    AssetContent::File(file) => match &*file.await? {
+                        FileContent::Content(file) => file.content().len() as u64,
+                        FileContent::NotFound => 0,
+                    },
+                    AssetContent::Redirect { .. } => 0,
+                };
+                let mut modules = Vec::new();
+                if let Some(chunk) = Vc::try_resolve_sidecast::<Box<dyn OutputChunk>>(asset).await?
+                {
+                    if let Some(included_ids) = chunk.runtime_info().await?.included_ids {
+                        for id in included_ids.await?.iter() {
+                            modules.push(format!("{}", &*id.await?));
+                        }
+                    }
+                }
+                modules.sort();
+                Ok((path, ChunkSnapshot { size, modules }))
+            })
+            .try_join()
+            .await?;
+        Ok(ChunkGroupSnapshot {
+            chunks: chunks.into_iter().collect(),
+        })
+    }
+
+    /// The total size of all output assets in bytes.
+    pub fn size(&self) -> u64 {
+        self.chunks.values().map(|chunk| chunk.size).sum()
+    }
+
+    /// The ids of all modules included in the chunk group.
+    pub fn modules(&self) -> BTreeSet<&str> {
+        self.chunks
+            .values()
+            .flat_map(|chunk| chunk.modules.iter().map(|id| id.as_str()))
+            .collect()
+    }
+}
+
+impl ChunkGraphSnapshot {
+    /// Captures snapshots of named chunk groups, see
+    /// [ChunkGroupSnapshot::capture].
+    pub async fn capture(
+        chunk_groups: impl IntoIterator<Item = (String, Vc<OutputAssets>)>,
+    ) -> Result<Self> {
+        let chunk_groups =
+            chunk_groups
+                .into_iter()
+                .map(|(name, assets)| async move {
+                    Ok((name, ChunkGroupSnapshot::capture(assets).await?))
+                })
+                .try_join()
+                .await?;
+        Ok(ChunkGraphSnapshot {
+            chunk_groups: chunk_groups.into_iter().collect(),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// The ids of all modules included in any chunk group.
+    pub fn modules(&self) -> BTreeSet<&str> {
+        self.chunk_groups
+            .values()
+            .flat_map(|chunk_group| chunk_group.modules())
+            .collect()
+    }
+
+    /// Maps the names of packages that are included from more than one
+    /// `node_modules` directory to these directories.
+    pub fn duplicate_packages(&self) -> BTreeMap<String, BTreeSet<String>> {
+        let mut packages = BTreeMap::<String, BTreeSet<String>>::new();
+        for module in self.modules() {
+            if let Some((name, directory)) = package_of_module(module) {
+                packages
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert(directory.to_string());
+            }
+        }
+        packages.retain(|_, directories| directories.len() > 1);
+        packages
+    }
+
+    /// Computes the changes from `self` to `other`.
+    pub fn diff(&self, other: &ChunkGraphSnapshot) -> ChunkGraphSnapshotDiff {
+        let modules = self.modules();
+        let other_modules = other.modules();
+
+        let chunk_group_names = self
+            .chunk_groups
+            .keys()
+            .chain(other.chunk_groups.keys())
+            .collect::<BTreeSet<_>>();
+        let chunk_groups = chunk_group_names
+            .into_iter()
+            .filter_map(|name| {
+                let before = self.chunk_groups.get(name).map(|group| group.size());
+                let after = other.chunk_groups.get(name).map(|group| group.size());
+                (before != after).then(|| (name.clone(), SizeDelta { before, after }))
+            })
+            .collect();
+
+        let duplicate_packages = self.duplicate_packages();
+        let new_duplicate_packages = other
+            .duplicate_packages()
+            .into_iter()
+            .filter(|(name, directories)| {
+                duplicate_packages
+                    .get(name)
+                    .map_or(true, |previous| !directories.is_subset(previous))
+            })
+            .collect();
+
+        ChunkGraphSnapshotDiff {
+            added_modules: other_modules
+                .difference(&modules)
+                .map(|id| id.to_string())
+                .collect(),
+            removed_modules: modules
+                .difference(&other_modules)
+                .map(|id| id.to_string())
+                .collect(),
+            chunk_groups,
+            new_duplicate_packages,
+        }
+    }
+}
+
+/// The changes between two [ChunkGraphSnapshot]s.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkGraphSnapshotDiff {
+    pub added_modules: Vec<String>,
+    pub removed_modules: Vec<String>,
+    /// The size changes of chunk groups, for chunk groups whose size changed.
+    pub chunk_groups: BTreeMap<String, SizeDelta>,
+    /// Packages that are duplicated in the new snapshot, but weren't (or in
+    /// fewer directories) in the old one.
+    pub new_duplicate_packages: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl ChunkGraphSnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_modules.is_empty()
+            && self.removed_modules.is_empty()
+            && self.chunk_groups.is_empty()
+            && self.new_duplicate_packages.is_empty()
+    }
+}
+
+/// The size of a chunk group in bytes before and after a change. `None` when
+/// the chunk group doesn't exist on that side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeDelta {
+    pub before: Option<u64>,
+    pub after: Option<u64>,
+}
+
+impl SizeDelta {
+    pub fn delta(&self) -> i64 {
+        self.after.unwrap_or_default() as i64 - self.before.unwrap_or_default() as i64
+    }
+}
+
+/// Extracts the package name and the directory it is installed in from a
+/// module id, e.g. `lodash` and `[project]/node_modules/lodash` from
+/// `[project]/node_modules/lodash/get.js [client] (ecmascript)`.
+fn package_of_module(module: &str) -> Option<(&str, &str)> {
+    const NODE_MODULES: &str = "node_modules/";
+    let start = module.rfind(NODE_MODULES)? + NODE_MODULES.len();
+    let rest = &module[start..];
+    let segments = if rest.starts_with('@') { 2 } else { 1 };
+    let mut end = 0;
+    for _ in 0..segments {
+        let segment_end = rest[end..].find(&['/', ' '][..])?;
+        end += segment_end + 1;
+    }
+    let name = &rest[..end - 1];
+    Some((name, &module[..start + end - 1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(chunk_groups: &[(&str, &[(&str, u64, &[&str])])]) -> ChunkGraphSnapshot {
+        ChunkGraphSnapshot {
+            chunk_groups: chunk_groups
+                .iter()
+                .map(|(name, chunks)| {
+                    (
+                        name.to_string(),
+                        ChunkGroupSnapshot {
+                            chunks: chunks
+                                .iter()
+                                .map(|(path, size, modules)| {
+                                    (
+                                        path.to_string(),
+                                        ChunkSnapshot {
+                                            size: *size,
+                                            modules: modules
+                                                .iter()
+                                                .map(|m| m.to_string())
+                                                .collect(),
+                                        },
+                                    )
+                                })
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_package_of_module() {
+        assert_eq!(
+            package_of_module("[project]/node_modules/lodash/get.js [client] (ecmascript)"),
+            Some(("lodash", "[project]/node_modules/lodash"))
+        );
+        assert_eq!(
+            package_of_module("[project]/node_modules/a/node_modules/@scope/pkg/index.js"),
+            Some((
+                "@scope/pkg",
+                "[project]/node_modules/a/node_modules/@scope/pkg"
+            ))
+        );
+        assert_eq!(package_of_module("[project]/src/index.js"), None);
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = snapshot(&[
+            ("main", &[("main.js", 100, &["[project]/src/a.js"])]),
+            ("removed", &[("removed.js", 10, &[])]),
+        ]);
+        let after = snapshot(&[
+            (
+                "main",
+                &[
+                    (
+                        "main.js",
+                        120,
+                        &["[project]/src/b.js", "[project]/node_modules/lodash/get.js"],
+                    ),
+                    (
+                        "vendor.js",
+                        50,
+                        &["[project]/node_modules/a/node_modules/lodash/get.js"],
+                    ),
+                ],
+            ),
+            ("added", &[("added.js", 5, &[])]),
+        ]);
+
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff.added_modules,
+            vec![
+                "[project]/node_modules/a/node_modules/lodash/get.js",
+                "[project]/node_modules/lodash/get.js",
+                "[project]/src/b.js",
+            ]
+        );
+        assert_eq!(diff.removed_modules, vec!["[project]/src/a.js"]);
+        assert_eq!(diff.chunk_groups["main"].delta(), 70);
+        assert_eq!(
+            diff.chunk_groups["added"],
+            SizeDelta {
+                before: None,
+                after: Some(5)
+            }
+        );
+        assert_eq!(diff.chunk_groups["removed"].delta(), -10);
+        assert_eq!(
+            diff.new_duplicate_packages["lodash"],
+            BTreeSet::from([
+                "[project]/node_modules/a/node_modules/lodash".to_string(),
+                "[project]/node_modules/lodash".to_string(),
+            ])
+        );
+        assert!(before.diff(&before).is_empty());
+    }
+}
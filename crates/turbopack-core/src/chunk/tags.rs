@@ -0,0 +1,68 @@
+//! Tags of chunk groups, e.g. `admin` or `public`.
+//!
+//! Chunk groups are tagged when they are created, by wrapping the
+//! [ChunkGroupResult] into a [TaggedChunkGroup]. The output assets of the
+//! chunk groups with certain tags can then be selected with
+//! [tagged_chunk_group_assets], so that one build graph produces several
+//! output sets that are deployed separately. Chunks shared by chunk groups
+//! with different tags are part of every output set that needs them.
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use turbo_tasks::Vc;
+
+use super::ChunkGroupResult;
+use crate::output::OutputAssets;
+
+/// A chunk group with tags. A chunk group without tags is selected by every
+/// set of tags, e.g. for a shared error page.
+#[turbo_tasks::value(shared)]
+pub struct TaggedChunkGroup {
+    pub chunk_group: Vc<ChunkGroupResult>,
+    pub tags: BTreeSet<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl TaggedChunkGroup {
+    #[turbo_tasks::function]
+    pub fn new(chunk_group: Vc<ChunkGroupResult>, tags: Vec<String>) -> Vc<Self> {
+        TaggedChunkGroup {
+            chunk_group,
+            tags: tags.into_iter().collect(),
+        }
+        .cell()
+    }
+}
+
+impl TaggedChunkGroup {
+    /// Whether the chunk group is selected by `tags`, i.e. it has any of them
+    /// or no tags at all.
+    pub fn is_selected_by(&self, tags: &[String]) -> bool {
+        self.tags.is_empty() || tags.iter().any(|tag| self.tags.contains(tag))
+    }
+}
+
+/// A list of [TaggedChunkGroup]s.
+#[turbo_tasks::value(transparent)]
+pub struct TaggedChunkGroups(Vec<Vc<TaggedChunkGroup>>);
+
+/// The output assets of the chunk groups of `chunk_groups` that are selected
+/// by `tags`, see [TaggedChunkGroup::is_selected_by]. Assets they reference
+/// (e.g. the chunks loaded by the runtime) aren't included, but are emitted
+/// along with them.
+#[turbo_tasks::function]
+pub async fn tagged_chunk_group_assets(
+    chunk_groups: Vc<TaggedChunkGroups>,
+    tags: Vec<String>,
+) -> Result<Vc<OutputAssets>> {
+    let mut assets = IndexSet::new();
+    for &chunk_group in chunk_groups.await?.iter() {
+        let chunk_group = chunk_group.await?;
+        if chunk_group.is_selected_by(&tags) {
+            assets.extend(chunk_group.chunk_group.await?.assets.await?.iter().copied());
+        }
+    }
+    Ok(Vc::cell(assets.into_iter().collect()))
+}
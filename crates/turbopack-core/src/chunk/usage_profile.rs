@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use turbo_tasks::Vc;
+
+use super::ModuleId;
+
+/// The modules that have been executed during a session, as exported by the
+/// development runtime with `TURBOPACK_USAGE_PROFILE()`.
+///
+/// Chunking places the executed ("hot") modules of a chunk group into its
+/// first chunks and the other ("cold") modules into separate deferred chunks
+/// after them, so that changes to cold modules don't invalidate hot chunks.
+/// Runtimes that support it load the deferred chunks only after the entries of
+/// the chunk group have been evaluated, see
+/// [super::chunking::MakeChunksResult::deferred_chunks]. A cold module that is
+/// required before its chunk has been loaded fails to instantiate, so the
+/// profile should cover all code paths taken at startup.
+#[turbo_tasks::value(shared)]
+pub struct UsageProfile {
+    executed_modules: HashSet<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl UsageProfile {
+    #[turbo_tasks::function]
+    pub fn new(executed_modules: Vec<String>) -> Vc<Self> {
+        UsageProfile {
+            executed_modules: executed_modules.into_iter().collect(),
+        }
+        .cell()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageProfileJson {
+    executed_modules: Vec<serde_json::Value>,
+}
+
+impl UsageProfile {
+    /// Parses a profile exported by the runtime and returns the ids of the
+    /// executed modules.
+    pub fn parse_executed_modules(json: &str) -> Result<Vec<String>> {
+        let profile: UsageProfileJson =
+            serde_json::from_str(json).context("invalid usage profile")?;
+        Ok(profile
+            .executed_modules
+            .into_iter()
+            .map(|id| match id {
+                serde_json::Value::String(id) => id,
+                id => id.to_string(),
+            })
+            .collect())
+    }
+
+    pub fn is_executed(&self, id: &ModuleId) -> bool {
+        self.executed_modules.contains(&id.to_string())
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionUsageProfile(Option<Vc<UsageProfile>>);
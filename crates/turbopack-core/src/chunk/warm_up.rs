@@ -0,0 +1,97 @@
+//! Precomputes the chunk groups of entries, e.g. at server start, so that the
+//! first request of each entry doesn't have to wait for the analysis of its
+//! modules.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Value, ValueToString, Vc};
+
+use super::{
+    availability_info::AvailabilityInfo, ChunkGroupResult, ChunkableModule, ChunkingContext,
+    EvaluatableAssets,
+};
+use crate::{ident::AssetIdent, module::Module, output::OutputAsset};
+
+/// An entry to warm up.
+#[derive(Clone, Copy, Debug)]
+pub enum WarmUpEntry {
+    /// The chunk group of a module, see [ChunkingContext::chunk_group].
+    Module(Vc<Box<dyn ChunkableModule>>),
+    /// An evaluated chunk group, see [ChunkingContext::evaluated_chunk_group].
+    Evaluated {
+        ident: Vc<AssetIdent>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    },
+}
+
+impl WarmUpEntry {
+    fn chunk_group(self, chunking_context: Vc<Box<dyn ChunkingContext>>) -> Vc<ChunkGroupResult> {
+        let availability_info = Value::new(AvailabilityInfo::Root);
+        match self {
+            WarmUpEntry::Module(module) => chunking_context.chunk_group(module, availability_info),
+            WarmUpEntry::Evaluated {
+                ident,
+                evaluatable_assets,
+            } => {
+                chunking_context.evaluated_chunk_group(ident, evaluatable_assets, availability_info)
+            }
+        }
+    }
+
+    fn ident(self) -> Vc<AssetIdent> {
+        match self {
+            WarmUpEntry::Module(module) => module.ident(),
+            WarmUpEntry::Evaluated { ident, .. } => ident,
+        }
+    }
+}
+
+/// Reported by [warm_up] whenever the chunk group of an entry is computed.
+#[derive(Clone, Debug)]
+pub struct WarmUpProgress {
+    /// The entry that was warmed up.
+    pub entry: String,
+    /// The number of entries that were warmed up so far.
+    pub completed: usize,
+    /// The number of entries to warm up.
+    pub total: usize,
+}
+
+/// Computes the chunk groups of `entries` concurrently, which analyzes all
+/// modules they reference and plans their chunks. The content of the chunks is
+/// only generated when they are emitted or served.
+///
+/// The results are cached like any other task, so the chunk groups are reused
+/// when the entries are requested later. With a dry run chunking context, only
+/// the chunk plans are computed.
+pub async fn warm_up(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: Vec<WarmUpEntry>,
+    on_progress: &(dyn Fn(WarmUpProgress) + Sync),
+) -> Result<()> {
+    let total = entries.len();
+    let completed = AtomicUsize::new(0);
+    let completed = &completed;
+    entries
+        .into_iter()
+        .map(|entry| async move {
+            let chunk_group = entry.chunk_group(chunking_context).await?;
+            chunk_group
+                .assets
+                .await?
+                .iter()
+                .map(|asset| asset.ident().resolve())
+                .try_join()
+                .await?;
+            on_progress(WarmUpProgress {
+                entry: entry.ident().to_string().await?.clone_value(),
+                completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                total,
+            });
+            Ok(())
+        })
+        .try_join()
+        .await?;
+    Ok(())
+}
@@ -5,12 +5,19 @@ use std::{
 };
 
 use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use turbo_tasks::Vc;
-use turbo_tasks_fs::rope::{Rope, RopeBuilder};
+use turbo_tasks_fs::{
+    rope::{Rope, RopeBuilder},
+    FileSystemPath,
+};
 use turbo_tasks_hash::hash_xxh3_hash64;
 
 use crate::{
-    source_map::{GenerateSourceMap, OptionSourceMap, SourceMap, SourceMapSection},
+    source_map::{
+        redact_source_map, GenerateSourceMap, OptionSourceMap, OptionSourceMapPrivacy, SourceMap,
+        SourceMapMode, SourceMapSection,
+    },
     source_pos::SourcePos,
 };
 
@@ -197,3 +204,139 @@ impl Code {
         Ok(Vc::cell(hash))
     }
 }
+
+/// Appends the comment that references the source map of `code`, the code of
+/// the chunk at `path`, as `mode` says: the URL of the `.map` file next to the
+/// chunk, or a data URL embedding the source map redacted by `privacy`.
+/// `comment` formats the comment from the URL, which differs between
+/// JavaScript and CSS. Code without a source map is returned as is.
+pub async fn append_source_map_url(
+    code: Vc<Code>,
+    path: &FileSystemPath,
+    mode: SourceMapMode,
+    privacy: Vc<OptionSourceMapPrivacy>,
+    comment: impl FnOnce(&str) -> String,
+) -> Result<Vc<Code>> {
+    let prebuilt = code.await?;
+    if !prebuilt.has_source_map() {
+        return Ok(code);
+    }
+    let url = match mode {
+        SourceMapMode::External => format!("{}.map", path.file_name()),
+        SourceMapMode::Inline => {
+            let source_map = redact_source_map(code.generate_source_map(), privacy);
+            let Some(source_map) = *source_map.await? else {
+                return Ok(code);
+            };
+            let json = source_map.to_rope().await?;
+            format!(
+                "data:application/json;charset=utf-8;base64,{}",
+                STANDARD.encode(json.to_bytes()?)
+            )
+        }
+        SourceMapMode::None => return Ok(code),
+    };
+    let mut builder = CodeBuilder::default();
+    builder.push_code(&prebuilt);
+    write!(builder, "{}", comment(&url))?;
+    Ok(builder.build().cell())
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks_fs::{FileSystem, VirtualFileSystem};
+
+    use super::*;
+
+    fn line_comment(url: &str) -> String {
+        format!("\n//# sourceMappingURL={url}")
+    }
+
+    async fn source_code(code: Vc<Code>) -> Result<String> {
+        Ok(code.await?.source_code().to_str()?.into_owned())
+    }
+
+    #[tokio::test]
+    async fn append_source_map_url_modes() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let path = VirtualFileSystem::new()
+                .root()
+                .join("dist/index.js".to_string())
+                .await?;
+            let mut builder = CodeBuilder::default();
+            builder.push_source(&Rope::from("a;"), Some(Vc::upcast(SourceMap::empty())));
+            let code = builder.build().cell();
+
+            let external = append_source_map_url(
+                code,
+                &path,
+                SourceMapMode::External,
+                Vc::cell(None),
+                line_comment,
+            )
+            .await?;
+            assert_eq!(
+                source_code(external).await?,
+                "a;\n//# sourceMappingURL=index.js.map"
+            );
+
+            let none = append_source_map_url(
+                code,
+                &path,
+                SourceMapMode::None,
+                Vc::cell(None),
+                line_comment,
+            )
+            .await?;
+            assert_eq!(source_code(none).await?, "a;");
+
+            let inline = append_source_map_url(
+                code,
+                &path,
+                SourceMapMode::Inline,
+                Vc::cell(None),
+                line_comment,
+            )
+            .await?;
+            let inline = source_code(inline).await?;
+            let data = inline
+                .strip_prefix(
+                    "a;\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,",
+                )
+                .expect("inline source map comment");
+            let json: serde_json::Value = serde_json::from_slice(&STANDARD.decode(data)?)?;
+            assert_eq!(json["version"], 3);
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn append_source_map_url_without_source_map() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let path = VirtualFileSystem::new()
+                .root()
+                .join("dist/index.js".to_string())
+                .await?;
+            let mut builder = CodeBuilder::default();
+            builder += "a;";
+            let code = builder.build().cell();
+
+            for mode in [SourceMapMode::External, SourceMapMode::Inline] {
+                let appended =
+                    append_source_map_url(code, &path, mode, Vc::cell(None), line_comment).await?;
+                assert_eq!(source_code(appended).await?, "a;");
+            }
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+}
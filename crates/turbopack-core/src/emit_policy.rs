@@ -0,0 +1,208 @@
+use anyhow::{bail, Result};
+use turbo_tasks::{TryFlatJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+
+use crate::{
+    asset::{Asset, AssetContent},
+    hash::HashAlgorithm,
+    output::OutputAsset,
+};
+
+/// An [OutputAsset] that is about to be emitted.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct EmittedAsset {
+    pub path: Vc<FileSystemPath>,
+    /// The size of the content in bytes.
+    pub size: u64,
+    /// The hash of the content, see [HashAlgorithm::hash_hex].
+    pub content_hash: String,
+}
+
+#[turbo_tasks::value_impl]
+impl EmittedAsset {
+    #[turbo_tasks::function]
+    pub async fn new(asset: Vc<Box<dyn OutputAsset>>) -> Result<Vc<Self>> {
+        let (size, content_hash) = match &*asset.content().await? {
+            AssetContent::File(file) => match &*file.await? {
+                FileContent::Content(file) => (
+                    file.content().len() as u64,
+                    HashAlgorithm::default().hash_hex(file.content()),
+                ),
+                FileContent::NotFound => (0, String::new()),
+            },
+            AssetContent::Redirect { .. } => (0, String::new()),
+        };
+        Ok(EmittedAsset {
+            path: asset.ident().path(),
+            size,
+            content_hash,
+        }
+        .cell())
+    }
+}
+
+/// Decides whether an [OutputAsset] may be emitted, e.g. to enforce that no
+/// source maps are emitted into a production output root.
+///
+/// A veto fails the emitting with the returned reason.
+#[turbo_tasks::value_trait]
+pub trait EmitPolicy {
+    /// Returns the reason to veto emitting `asset`, or `None` to allow it.
+    fn veto(self: Vc<Self>, asset: Vc<EmittedAsset>) -> Vc<Option<String>>;
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionEmitPolicy(Option<Vc<Box<dyn EmitPolicy>>>);
+
+/// Fails if `policy` vetoes emitting any of `assets`. All vetoes are
+/// reported, so they can be fixed at once.
+///
+/// This is meant to be called before the first asset is written, so a veto
+/// doesn't leave a partially emitted output behind.
+pub async fn check_emit_policy(
+    assets: &[Vc<Box<dyn OutputAsset>>],
+    policy: Vc<Box<dyn EmitPolicy>>,
+) -> Result<()> {
+    let vetoes = assets
+        .iter()
+        .map(|&asset| async move {
+            let Some(reason) = policy.veto(EmittedAsset::new(asset)).await?.clone_value() else {
+                return Ok(None);
+            };
+            Ok(Some(format!(
+                "emitting {} was vetoed: {}",
+                asset.ident().path().to_string().await?,
+                reason
+            )))
+        })
+        .try_flat_join()
+        .await?;
+    if !vetoes.is_empty() {
+        bail!("{}", vetoes.join("\n"));
+    }
+    Ok(())
+}
+
+/// Vetoes source maps (`.map` files).
+#[turbo_tasks::value]
+pub struct NoSourceMapsPolicy;
+
+#[turbo_tasks::value_impl]
+impl NoSourceMapsPolicy {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        NoSourceMapsPolicy.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EmitPolicy for NoSourceMapsPolicy {
+    #[turbo_tasks::function]
+    async fn veto(&self, asset: Vc<EmittedAsset>) -> Result<Vc<Option<String>>> {
+        let path = asset.await?.path.await?;
+        Ok(Vc::cell(
+            (path.extension_ref() == Some("map"))
+                .then(|| "source maps must not be emitted".to_string()),
+        ))
+    }
+}
+
+/// Vetoes an asset if any of the policies vetoes it.
+#[turbo_tasks::value]
+pub struct EmitPolicies(Vec<Vc<Box<dyn EmitPolicy>>>);
+
+#[turbo_tasks::value_impl]
+impl EmitPolicies {
+    #[turbo_tasks::function]
+    pub fn new(policies: Vec<Vc<Box<dyn EmitPolicy>>>) -> Vc<Self> {
+        EmitPolicies(policies).cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EmitPolicy for EmitPolicies {
+    #[turbo_tasks::function]
+    async fn veto(&self, asset: Vc<EmittedAsset>) -> Result<Vc<Option<String>>> {
+        for policy in self.0.iter() {
+            let veto = policy.veto(asset).await?;
+            if veto.is_some() {
+                return Ok(Vc::cell(veto.clone_value()));
+            }
+        }
+        Ok(Vc::cell(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks_fs::{File, VirtualFileSystem};
+
+    use super::*;
+    use crate::virtual_output::VirtualOutputAsset;
+
+    fn asset(path: &str, content: &str) -> Vc<Box<dyn OutputAsset>> {
+        let path = VirtualFileSystem::new().root().join(path.to_string());
+        let content = FileContent::Content(File::from(content));
+        Vc::upcast(VirtualOutputAsset::new(
+            path,
+            AssetContent::file(content.cell()),
+        ))
+    }
+
+    #[tokio::test]
+    async fn no_source_maps_policy_vetoes_source_maps() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let policy = Vc::upcast(NoSourceMapsPolicy::new());
+            check_emit_policy(&[asset("dist/index.js", "a;")], policy).await?;
+
+            let error = check_emit_policy(
+                &[
+                    asset("dist/index.js", "a;"),
+                    asset("dist/index.js.map", "{}"),
+                    asset("dist/other.js.map", "{}"),
+                ],
+                policy,
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+            assert!(error.contains("dist/index.js.map"));
+            assert!(error.contains("dist/other.js.map"));
+            assert!(!error.contains("dist/index.js was vetoed"));
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn emit_policies_veto_if_any_policy_vetoes() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let policy = Vc::upcast(EmitPolicies::new(vec![Vc::upcast(
+                NoSourceMapsPolicy::new(),
+            )]));
+            let emitted = EmittedAsset::new(asset("dist/index.js.map", "{}"));
+            assert_eq!(
+                policy.veto(emitted).await?.clone_value(),
+                Some("source maps must not be emitted".to_string())
+            );
+
+            let emitted = EmittedAsset::new(asset("dist/index.js", "a;"));
+            assert_eq!(emitted.await?.size, 2);
+            assert_eq!(policy.veto(emitted).await?.clone_value(), None);
+
+            let policy: Vc<Box<dyn EmitPolicy>> = Vc::upcast(EmitPolicies::new(vec![]));
+            assert_eq!(policy.veto(emitted).await?.clone_value(), None);
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+}
@@ -175,6 +175,11 @@ pub enum ExecutionEnvironment {
     NodeJsLambda(Vc<NodeJsEnvironment>),
     EdgeWorker(Vc<EdgeWorkerEnvironment>),
     Browser(Vc<BrowserEnvironment>),
+    BrowserExtension(Vc<BrowserExtensionEnvironment>),
+    /// The main process of an Electron app, which is a Node.js environment
+    /// that can import `electron` as an external.
+    ElectronMain(Vc<NodeJsEnvironment>),
+    ElectronRenderer(Vc<ElectronRendererEnvironment>),
     // TODO allow custom trait here
     Custom(u8),
 }
@@ -186,8 +191,11 @@ impl Environment {
         let this = self.await?;
         Ok(match this.execution {
             ExecutionEnvironment::NodeJsBuildTime(node_env, ..)
-            | ExecutionEnvironment::NodeJsLambda(node_env) => node_env.await?.compile_target,
-            ExecutionEnvironment::Browser(_) => CompileTarget::unknown(),
+            | ExecutionEnvironment::NodeJsLambda(node_env)
+            | ExecutionEnvironment::ElectronMain(node_env) => node_env.await?.compile_target,
+            ExecutionEnvironment::Browser(_)
+            | ExecutionEnvironment::BrowserExtension(_)
+            | ExecutionEnvironment::ElectronRenderer(_) => CompileTarget::unknown(),
             ExecutionEnvironment::EdgeWorker(_) => CompileTarget::unknown(),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
@@ -198,13 +206,26 @@ impl Environment {
         let this = self.await?;
         Ok(match this.execution {
             ExecutionEnvironment::NodeJsBuildTime(node_env, ..)
-            | ExecutionEnvironment::NodeJsLambda(node_env) => node_env.runtime_versions(),
+            | ExecutionEnvironment::NodeJsLambda(node_env)
+            | ExecutionEnvironment::ElectronMain(node_env) => node_env.runtime_versions(),
             ExecutionEnvironment::Browser(browser_env) => {
                 Vc::cell(Versions::parse_versions(browserslist::resolve(
                     browser_env.await?.browserslist_query.split(','),
                     &browserslist::Opts::new(),
                 )?)?)
             }
+            ExecutionEnvironment::BrowserExtension(extension_env) => {
+                Vc::cell(Versions::parse_versions(browserslist::resolve(
+                    extension_env.await?.browserslist_query.split(','),
+                    &browserslist::Opts::new(),
+                )?)?)
+            }
+            ExecutionEnvironment::ElectronRenderer(renderer_env) => {
+                Vc::cell(Versions::parse_versions(browserslist::resolve(
+                    renderer_env.await?.browserslist_query.split(','),
+                    &browserslist::Opts::new(),
+                )?)?)
+            }
             ExecutionEnvironment::EdgeWorker(_) => todo!(),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
@@ -214,10 +235,15 @@ impl Environment {
     pub async fn node_externals(self: Vc<Self>) -> Result<Vc<bool>> {
         let this = self.await?;
         Ok(match this.execution {
-            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
-                Vc::cell(true)
+            ExecutionEnvironment::NodeJsBuildTime(..)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::ElectronMain(_) => Vc::cell(true),
+            ExecutionEnvironment::Browser(_) | ExecutionEnvironment::BrowserExtension(_) => {
+                Vc::cell(false)
+            }
+            ExecutionEnvironment::ElectronRenderer(renderer_env) => {
+                Vc::cell(renderer_env.await?.node_integration)
             }
-            ExecutionEnvironment::Browser(_) => Vc::cell(false),
             ExecutionEnvironment::EdgeWorker(_) => Vc::cell(false),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
@@ -227,10 +253,15 @@ impl Environment {
     pub async fn supports_commonjs_externals(self: Vc<Self>) -> Result<Vc<bool>> {
         let this = self.await?;
         Ok(match this.execution {
-            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
-                Vc::cell(true)
+            ExecutionEnvironment::NodeJsBuildTime(..)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::ElectronMain(_) => Vc::cell(true),
+            ExecutionEnvironment::Browser(_) | ExecutionEnvironment::BrowserExtension(_) => {
+                Vc::cell(false)
+            }
+            ExecutionEnvironment::ElectronRenderer(renderer_env) => {
+                Vc::cell(renderer_env.await?.node_integration)
             }
-            ExecutionEnvironment::Browser(_) => Vc::cell(false),
             ExecutionEnvironment::EdgeWorker(_) => Vc::cell(true),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
@@ -240,10 +271,12 @@ impl Environment {
     pub async fn supports_wasm(self: Vc<Self>) -> Result<Vc<bool>> {
         let this = self.await?;
         Ok(match this.execution {
-            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
-                Vc::cell(true)
-            }
-            ExecutionEnvironment::Browser(_) => Vc::cell(false),
+            ExecutionEnvironment::NodeJsBuildTime(..)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::ElectronMain(_) => Vc::cell(true),
+            ExecutionEnvironment::Browser(_)
+            | ExecutionEnvironment::BrowserExtension(_)
+            | ExecutionEnvironment::ElectronRenderer(_) => Vc::cell(false),
             ExecutionEnvironment::EdgeWorker(_) => Vc::cell(false),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
@@ -253,16 +286,17 @@ impl Environment {
     pub async fn resolve_extensions(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
         let env = self.await?;
         Ok(match env.execution {
-            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
-                Vc::cell(vec![
-                    ".js".to_string(),
-                    ".node".to_string(),
-                    ".json".to_string(),
-                ])
-            }
-            ExecutionEnvironment::EdgeWorker(_) | ExecutionEnvironment::Browser(_) => {
-                Vc::<Vec<String>>::default()
-            }
+            ExecutionEnvironment::NodeJsBuildTime(..)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::ElectronMain(_) => Vc::cell(vec![
+                ".js".to_string(),
+                ".node".to_string(),
+                ".json".to_string(),
+            ]),
+            ExecutionEnvironment::EdgeWorker(_)
+            | ExecutionEnvironment::Browser(_)
+            | ExecutionEnvironment::BrowserExtension(_)
+            | ExecutionEnvironment::ElectronRenderer(_) => Vc::<Vec<String>>::default(),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -271,12 +305,13 @@ impl Environment {
     pub async fn resolve_node_modules(self: Vc<Self>) -> Result<Vc<bool>> {
         let env = self.await?;
         Ok(match env.execution {
-            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
-                Vc::cell(true)
-            }
-            ExecutionEnvironment::EdgeWorker(_) | ExecutionEnvironment::Browser(_) => {
-                Vc::cell(false)
-            }
+            ExecutionEnvironment::NodeJsBuildTime(..)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::ElectronMain(_) => Vc::cell(true),
+            ExecutionEnvironment::EdgeWorker(_)
+            | ExecutionEnvironment::Browser(_)
+            | ExecutionEnvironment::BrowserExtension(_)
+            | ExecutionEnvironment::ElectronRenderer(_) => Vc::cell(false),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
     }
@@ -285,10 +320,12 @@ impl Environment {
     pub async fn resolve_conditions(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
         let env = self.await?;
         Ok(match env.execution {
-            ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_) => {
-                Vc::cell(vec!["node".to_string()])
-            }
-            ExecutionEnvironment::Browser(_) => Vc::<Vec<String>>::default(),
+            ExecutionEnvironment::NodeJsBuildTime(..)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::ElectronMain(_) => Vc::cell(vec!["node".to_string()]),
+            ExecutionEnvironment::Browser(_)
+            | ExecutionEnvironment::BrowserExtension(_)
+            | ExecutionEnvironment::ElectronRenderer(_) => Vc::<Vec<String>>::default(),
             ExecutionEnvironment::EdgeWorker(_) => Vc::cell(vec!["edge-worker".to_string()]),
             ExecutionEnvironment::Custom(_) => todo!(),
         })
@@ -299,7 +336,8 @@ impl Environment {
         let env = self.await?;
         Ok(match env.execution {
             ExecutionEnvironment::NodeJsBuildTime(env)
-            | ExecutionEnvironment::NodeJsLambda(env) => env.await?.cwd,
+            | ExecutionEnvironment::NodeJsLambda(env)
+            | ExecutionEnvironment::ElectronMain(env) => env.await?.cwd,
             _ => Vc::cell(None),
         })
     }
@@ -315,20 +353,46 @@ impl Environment {
             ExecutionEnvironment::EdgeWorker(env) => {
                 Rendering::Server(env.await?.server_addr).cell()
             }
-            ExecutionEnvironment::Browser(_) => Rendering::Client.cell(),
+            ExecutionEnvironment::Browser(_)
+            | ExecutionEnvironment::BrowserExtension(_)
+            | ExecutionEnvironment::ElectronRenderer(_) => Rendering::Client.cell(),
             _ => Rendering::None.cell(),
         })
     }
 
+    /// Whether asset URLs are relative to the document instead of the origin
+    /// when the chunking context has no asset base path. Electron renderers
+    /// may be loaded with the `file:` protocol, where the origin is the root
+    /// of the file system.
+    #[turbo_tasks::function]
+    pub async fn document_relative_urls(self: Vc<Self>) -> Result<Vc<bool>> {
+        let env = self.await?;
+        Ok(Vc::cell(matches!(
+            env.execution,
+            ExecutionEnvironment::ElectronRenderer(_)
+        )))
+    }
+
     #[turbo_tasks::function]
     pub async fn chunk_loading(self: Vc<Self>) -> Result<Vc<ChunkLoading>> {
         let env = self.await?;
         Ok(match env.execution {
-            ExecutionEnvironment::NodeJsBuildTime(_) | ExecutionEnvironment::NodeJsLambda(_) => {
-                ChunkLoading::NodeJs.cell()
-            }
+            ExecutionEnvironment::NodeJsBuildTime(_)
+            | ExecutionEnvironment::NodeJsLambda(_)
+            | ExecutionEnvironment::ElectronMain(_) => ChunkLoading::NodeJs.cell(),
             ExecutionEnvironment::EdgeWorker(_) => ChunkLoading::None.cell(),
-            ExecutionEnvironment::Browser(_) => ChunkLoading::Dom.cell(),
+            ExecutionEnvironment::Browser(_) | ExecutionEnvironment::ElectronRenderer(_) => {
+                ChunkLoading::Dom.cell()
+            }
+            ExecutionEnvironment::BrowserExtension(extension_env) => {
+                match extension_env.await?.context {
+                    // Neither can load scripts on demand, so all chunks of a
+                    // chunk group are loaded upfront.
+                    BrowserExtensionContext::ContentScript
+                    | BrowserExtensionContext::BackgroundServiceWorker => ChunkLoading::None.cell(),
+                    BrowserExtensionContext::Page => ChunkLoading::Dom.cell(),
+                }
+            }
             _ => ChunkLoading::None.cell(),
         })
     }
@@ -407,6 +471,44 @@ pub struct BrowserEnvironment {
     pub browserslist_query: String,
 }
 
+/// The context code of a browser extension (Manifest V3) runs in.
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum BrowserExtensionContext {
+    /// A content script, which runs in an isolated world of a web page. It
+    /// can't inject scripts into the page to load chunks.
+    ContentScript,
+    /// The background service worker, which can't import scripts after it
+    /// has been installed.
+    BackgroundServiceWorker,
+    /// An extension page, e.g. a popup or the options page, which loads chunks
+    /// from the extension package with script tags.
+    Page,
+}
+
+#[turbo_tasks::value(shared)]
+pub struct BrowserExtensionEnvironment {
+    pub context: BrowserExtensionContext,
+    pub browserslist_query: String,
+}
+
+/// The renderer process of an Electron app. Its main process is the
+/// [ExecutionEnvironment::ElectronMain] environment.
+///
+/// Renderers load chunks with script tags like browsers. Chunk and asset URLs
+/// are relative to the document when the chunking context has no chunk or
+/// asset base path, see [Environment::document_relative_urls]. This also works
+/// when the app is loaded with the `file:` protocol, as long as the HTML is
+/// placed at the output root. The main process and renderers can share the
+/// same asset root, so static assets used by both are only emitted once.
+#[turbo_tasks::value(shared)]
+pub struct ElectronRendererEnvironment {
+    /// Whether the renderer has `nodeIntegration` enabled, which allows it to
+    /// require Node.js and Electron modules as externals.
+    pub node_integration: bool,
+    pub browserslist_query: String,
+}
+
 #[turbo_tasks::value(shared)]
 pub struct EdgeWorkerEnvironment {
     pub server_addr: Vc<ServerAddr>,
@@ -0,0 +1,231 @@
+//! Build-time feature flags.
+//!
+//! A project declares boolean feature flags, which modules read as
+//! `FEATURE_FLAGS.<name>`. Every combination of flag values is a variant that
+//! is compiled separately, so code behind disabled flags is removed like any
+//! other dead branch. The chunking context of a variant encodes its flags in
+//! the chunk paths, so the chunks of different variants can be served side by
+//! side.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use turbo_tasks::Vc;
+
+use crate::compile_time_info::{CompileTimeDefineValue, CompileTimeDefines, CompileTimeInfo};
+
+/// The global that modules read feature flags from.
+pub const FEATURE_FLAGS_GLOBAL: &str = "FEATURE_FLAGS";
+
+/// The number of flags up to which [FeatureFlags::variants] enumerates all
+/// combinations.
+const MAX_ENUMERATED_FLAGS: usize = 10;
+
+/// The feature flags of a project, mapped to their default values.
+#[turbo_tasks::value(transparent)]
+pub struct FeatureFlags(IndexMap<String, bool>);
+
+fn validate_flag_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        bail!(
+            "invalid feature flag name {:?}, only letters, digits and underscores are allowed",
+            name
+        );
+    }
+    Ok(())
+}
+
+#[turbo_tasks::value_impl]
+impl FeatureFlags {
+    /// The variant with every flag set to its default value.
+    #[turbo_tasks::function]
+    pub async fn defaults(self: Vc<Self>) -> Result<Vc<FeatureFlagSet>> {
+        let flags = self.await?;
+        for name in flags.keys() {
+            validate_flag_name(name)?;
+        }
+        Ok(Vc::cell(
+            flags
+                .iter()
+                .map(|(name, &value)| (name.clone(), value))
+                .collect(),
+        ))
+    }
+
+    /// The variant with exactly the `enabled` flags enabled. Fails if any of
+    /// them isn't declared.
+    #[turbo_tasks::function]
+    pub async fn variant(self: Vc<Self>, enabled: Vec<String>) -> Result<Vc<FeatureFlagSet>> {
+        let flags = self.await?;
+        for name in enabled.iter() {
+            if !flags.contains_key(name) {
+                bail!("the feature flag {:?} is not declared", name);
+            }
+        }
+        for name in flags.keys() {
+            validate_flag_name(name)?;
+        }
+        Ok(Vc::cell(
+            flags
+                .keys()
+                .map(|name| (name.clone(), enabled.contains(name)))
+                .collect(),
+        ))
+    }
+
+    /// Every combination of flag values, starting with the defaults.
+    #[turbo_tasks::function]
+    pub async fn variants(self: Vc<Self>) -> Result<Vc<FeatureFlagSets>> {
+        let flags = self.await?;
+        if flags.len() > MAX_ENUMERATED_FLAGS {
+            bail!(
+                "{} feature flags have {} combinations, select the variants to build with \
+                 FeatureFlags::variant instead",
+                flags.len(),
+                1u64 << flags.len()
+            );
+        }
+        for name in flags.keys() {
+            validate_flag_name(name)?;
+        }
+        // Toggling the flags that are set in `mask` enumerates every combination,
+        // and the empty mask yields the defaults.
+        let variants: Vec<Vc<FeatureFlagSet>> = (0u32..1 << flags.len())
+            .map(|mask| {
+                Vc::cell(
+                    flags
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (name, &default))| {
+                            (name.clone(), default ^ (mask & (1 << i) != 0))
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        Ok(Vc::cell(variants))
+    }
+}
+
+/// The values of all feature flags of a variant.
+#[turbo_tasks::value(transparent)]
+pub struct FeatureFlagSet(BTreeMap<String, bool>);
+
+#[turbo_tasks::value_impl]
+impl FeatureFlagSet {
+    /// A name for the variant that can be used in paths: the enabled flags
+    /// joined by `+`, or `none`.
+    #[turbo_tasks::function]
+    pub async fn key(self: Vc<Self>) -> Result<Vc<String>> {
+        let enabled = self
+            .await?
+            .iter()
+            .filter(|(_, value)| **value)
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        Ok(Vc::cell(if enabled.is_empty() {
+            "none".to_string()
+        } else {
+            enabled.join("+")
+        }))
+    }
+
+    /// Defines `FEATURE_FLAGS.<name>` for every flag.
+    #[turbo_tasks::function]
+    pub async fn defines(self: Vc<Self>) -> Result<Vc<CompileTimeDefines>> {
+        Ok(Vc::cell(
+            self.await?
+                .iter()
+                .map(|(name, &value)| {
+                    (
+                        vec![FEATURE_FLAGS_GLOBAL.to_string(), name.clone()],
+                        CompileTimeDefineValue::Bool(value),
+                    )
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct FeatureFlagSets(Vec<Vc<FeatureFlagSet>>);
+
+/// Adds the defines of the `feature_flags` variant to `compile_time_info`.
+#[turbo_tasks::function]
+pub async fn compile_time_info_with_feature_flags(
+    compile_time_info: Vc<CompileTimeInfo>,
+    feature_flags: Vc<FeatureFlagSet>,
+) -> Result<Vc<CompileTimeInfo>> {
+    let compile_time_info = compile_time_info.await?;
+    let mut defines = compile_time_info.defines.await?.clone_value();
+    defines.extend(feature_flags.defines().await?.clone_value());
+    Ok(CompileTimeInfo {
+        environment: compile_time_info.environment,
+        defines: Vc::cell(defines),
+        free_var_references: compile_time_info.free_var_references,
+    }
+    .cell())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature_flags(flags: &[(&str, bool)]) -> Vc<FeatureFlags> {
+        Vc::cell(
+            flags
+                .iter()
+                .map(|&(name, default)| (name.to_string(), default))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn variants() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let flags = feature_flags(&[("a", false), ("b", true)]);
+            let mut keys = Vec::new();
+            for variant in flags.variants().await?.iter() {
+                keys.push(variant.key().await?.clone_value());
+            }
+            // The defaults come first.
+            assert_eq!(keys, vec!["b", "a+b", "none", "a"]);
+
+            let too_many = feature_flags(
+                &["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"].map(|name| (name, false)),
+            );
+            assert!(too_many.variants().await.is_err());
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn variant() {
+        crate::register();
+
+        turbo_tasks_testing::VcStorage::with(async {
+            let flags = feature_flags(&[("a", false), ("b", true)]);
+            let variant = flags.variant(vec!["a".to_string()]).await?;
+            assert_eq!(variant.get("a"), Some(&true));
+            assert_eq!(variant.get("b"), Some(&false));
+            assert_eq!(*flags.variant(vec![]).key().await?, "none");
+            assert!(flags.variant(vec!["c".to_string()]).await.is_err());
+
+            let invalid = feature_flags(&[("not-an-identifier", false)]);
+            assert!(invalid.variant(vec![]).await.is_err());
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+}
@@ -0,0 +1,94 @@
+use turbo_tasks_hash::{
+    encode_hex, Blake3Hasher, DeterministicHash, DeterministicHasher, Xxh3Hash64Hasher,
+};
+
+/// The hash function used for content hashes and chunk idents.
+///
+/// The default is the fast, non-cryptographic xxh3. Embedders that rely on
+/// hashes to be collision resistant (e.g. when content hashes are used for
+/// integrity or to address content in a shared cache) can opt into BLAKE3.
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum HashAlgorithm {
+    #[default]
+    Xxh3Hash64,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// A stable identifier of the algorithm and its output format. It changes
+    /// whenever the hashes produced for the same input change, so it should be
+    /// part of the key of any cache that persists hashes across runs.
+    pub fn version(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Xxh3Hash64 => "xxh3-64@1",
+            HashAlgorithm::Blake3 => "blake3@1",
+        }
+    }
+
+    pub fn hasher(&self) -> AlgorithmHasher {
+        match self {
+            HashAlgorithm::Xxh3Hash64 => AlgorithmHasher::Xxh3Hash64(Xxh3Hash64Hasher::new()),
+            HashAlgorithm::Blake3 => AlgorithmHasher::Blake3(Blake3Hasher::new()),
+        }
+    }
+
+    /// Hashes `input` and encodes the full digest as a hexadecimal string.
+    pub fn hash_hex<T: DeterministicHash>(&self, input: T) -> String {
+        let mut hasher = self.hasher();
+        hasher.write_value(input);
+        hasher.finish_hex()
+    }
+
+    /// Hashes `input` to a number, e.g. to derive numeric ids.
+    pub fn hash_u64<T: DeterministicHash>(&self, input: T) -> u64 {
+        let mut hasher = self.hasher();
+        hasher.write_value(input);
+        hasher.finish()
+    }
+}
+
+/// A hasher for a [HashAlgorithm].
+pub enum AlgorithmHasher {
+    Xxh3Hash64(Xxh3Hash64Hasher),
+    Blake3(Blake3Hasher),
+}
+
+impl AlgorithmHasher {
+    /// Uses the DeterministicHash trait to hash the input in a
+    /// cross-platform way.
+    pub fn write_value<T: DeterministicHash>(&mut self, input: T) {
+        input.deterministic_hash(self);
+    }
+
+    /// Uses the DeterministicHash trait to hash the input in a
+    /// cross-platform way.
+    pub fn write_ref<T: DeterministicHash>(&mut self, input: &T) {
+        input.deterministic_hash(self);
+    }
+
+    /// Finish the hash computation and return the full digest as a
+    /// hexadecimal string: 16 characters for xxh3, 64 for BLAKE3.
+    pub fn finish_hex(&self) -> String {
+        match self {
+            AlgorithmHasher::Xxh3Hash64(hasher) => encode_hex(hasher.finish()),
+            AlgorithmHasher::Blake3(hasher) => hasher.finish_hex(),
+        }
+    }
+}
+
+impl DeterministicHasher for AlgorithmHasher {
+    fn finish(&self) -> u64 {
+        match self {
+            AlgorithmHasher::Xxh3Hash64(hasher) => hasher.finish(),
+            AlgorithmHasher::Blake3(hasher) => DeterministicHasher::finish(hasher),
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        match self {
+            AlgorithmHasher::Xxh3Hash64(hasher) => hasher.write_bytes(bytes),
+            AlgorithmHasher::Blake3(hasher) => hasher.write_bytes(bytes),
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use std::fmt::Write;
 
 use anyhow::Result;
-use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks::{ReadRef, Value, ValueToString, Vc};
 use turbo_tasks_fs::FileSystemPath;
-use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64, DeterministicHash, Xxh3Hash64Hasher};
+use turbo_tasks_hash::DeterministicHash;
 
-use crate::resolve::ModulePart;
+use crate::{hash::HashAlgorithm, resolve::ModulePart};
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Clone, Debug, PartialOrd, Ord, Hash)]
@@ -168,12 +168,17 @@ impl AssetIdent {
     /// TODO(alexkirsz) This is `turbopack-dev` specific, as `turbopack-build`
     /// would use a content hash instead. But for now both are using the same
     /// name generation logic.
+    ///
+    /// The hashes included in the name are computed with `hash_algorithm`.
     #[turbo_tasks::function]
     pub async fn output_name(
         &self,
         context_path: Vc<FileSystemPath>,
         expected_extension: String,
+        hash_algorithm: Vc<HashAlgorithm>,
     ) -> Result<Vc<String>> {
+        let hash_algorithm = *hash_algorithm.await?;
+
         // TODO(PACK-2140): restrict character set to A–Za–z0–9-_.~'()
         // to be compatible with all operating systems + URLs.
 
@@ -199,7 +204,7 @@ impl AssetIdent {
             _ => None,
         };
 
-        let mut hasher = Xxh3Hash64Hasher::new();
+        let mut hasher = hash_algorithm.hasher();
         let mut has_hash = false;
         let AssetIdent {
             path: _,
@@ -284,7 +289,7 @@ impl AssetIdent {
         }
 
         if has_hash {
-            let hash = encode_hex(hasher.finish());
+            let hash = hasher.finish_hex();
             let truncated_hash = &hash[..6];
             write!(name, "_{}", truncated_hash)?;
         }
@@ -306,7 +311,7 @@ impl AssetIdent {
             }
         }
         if i > 0 {
-            let hash = encode_hex(hash_xxh3_hash64(name[..i].as_bytes()));
+            let hash = hash_algorithm.hash_hex(name[..i].as_bytes());
             let truncated_hash = &hash[..5];
             name = format!("{}_{}", truncated_hash, &name[i..]);
         }
@@ -321,6 +326,30 @@ impl AssetIdent {
     }
 }
 
+/// The layer shared by all `idents`, e.g. for the ident of a chunk of the
+/// modules with these idents. Returns `None` if any of them has a different or
+/// no layer.
+pub async fn common_layer(
+    idents: impl IntoIterator<Item = Vc<AssetIdent>>,
+) -> Result<Option<Vc<String>>> {
+    let mut common_layer: Option<(Vc<String>, ReadRef<String>)> = None;
+    for ident in idents {
+        let Some(layer) = ident.await?.layer else {
+            return Ok(None);
+        };
+        let layer_ref = layer.await?;
+        match &common_layer {
+            Some((_, common_layer_ref)) => {
+                if **common_layer_ref != *layer_ref {
+                    return Ok(None);
+                }
+            }
+            None => common_layer = Some((layer, layer_ref)),
+        }
+    }
+    Ok(common_layer.map(|(layer, _)| layer))
+}
+
 fn clean_separators(s: &str) -> String {
     s.replace('/', "_")
 }
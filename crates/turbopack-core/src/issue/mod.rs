@@ -1,7 +1,11 @@
 pub mod analyze;
 pub mod code_gen;
+pub mod oversized_module;
+pub mod peer_dependency;
 pub mod resolve;
+pub mod size_regression;
 pub mod unsupported_module;
+pub mod usage_profile;
 
 use std::{
     cmp::{min, Ordering},
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use turbo_tasks::{TryFlatJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+
+use super::{Issue, IssueSeverity, OptionStyledString, StyledString};
+use crate::chunk::{Chunk, ChunkItem, ChunkItems};
+
+/// The generated code of a single module exceeds the configured size, which
+/// usually means that data has been bundled by accident.
+#[turbo_tasks::value(shared)]
+pub struct OversizedModuleIssue {
+    pub chunk_item: Vc<Box<dyn ChunkItem>>,
+    pub size: u64,
+    pub limit: u64,
+    /// The chunk the module has been placed in.
+    pub chunk: Vc<Box<dyn Chunk>>,
+    /// The chunk items of the chunk group, which are searched for importers.
+    pub chunk_items: Vc<ChunkItems>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for OversizedModuleIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Module is too large".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.chunk_item.asset_ident().path()
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<OptionStyledString>> {
+        let mut lines = vec![
+            StyledString::Line(vec![
+                StyledString::Text(format!(
+                    "The generated code of this module has {} bytes, which exceeds the limit of \
+                     {} bytes. It has been placed in the chunk ",
+                    self.size, self.limit
+                )),
+                StyledString::Code(self.chunk.path().to_string().await?.clone_value()),
+                StyledString::Text(".".to_string()),
+            ]),
+            StyledString::Text(
+                "Large modules are often data that has been imported by accident.".to_string(),
+            ),
+        ];
+        let importers = importers(self.chunk_items, self.chunk_item).await?;
+        if !importers.is_empty() {
+            lines.push(StyledString::Text("It is imported by:".to_string()));
+            lines.extend(
+                importers
+                    .iter()
+                    .map(|importer| StyledString::Code(format!("  {}", importer))),
+            );
+        }
+        Ok(Vc::cell(Some(StyledString::Stack(lines).cell())))
+    }
+}
+
+/// The idents of the chunk items in `chunk_items` that reference the module of
+/// `chunk_item`.
+#[turbo_tasks::function]
+async fn importers(
+    chunk_items: Vc<ChunkItems>,
+    chunk_item: Vc<Box<dyn ChunkItem>>,
+) -> Result<Vc<Vec<String>>> {
+    let module = chunk_item.module().resolve().await?;
+    let importers = chunk_items
+        .await?
+        .iter()
+        .map(|&item| async move {
+            for &reference in item.references().await?.iter() {
+                for &referenced in reference
+                    .resolve_reference()
+                    .primary_modules()
+                    .await?
+                    .iter()
+                {
+                    if referenced.resolve().await? == module {
+                        return Ok(Some(item.asset_ident().to_string().await?.clone_value()));
+                    }
+                }
+            }
+            Ok(None)
+        })
+        .try_flat_join()
+        .await?;
+    Ok(Vc::cell(importers))
+}
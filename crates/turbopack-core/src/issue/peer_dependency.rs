@@ -0,0 +1,97 @@
+use anyhow::Result;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+
+use super::{Issue, IssueSeverity, OptionStyledString, StyledString};
+use crate::chunk::ChunkItem;
+
+/// A package has been placed into a chunk together with a version of one of
+/// its peer dependencies that is outside of the range it declares. This
+/// usually happens when packages are hoisted differently than expected, and
+/// shows up as errors at runtime that are hard to trace back.
+#[turbo_tasks::value(shared)]
+pub struct PeerDependencyMismatchIssue {
+    /// The package.json of the package that declares the peer dependency.
+    pub package_json_path: Vc<FileSystemPath>,
+    pub package_name: String,
+    pub package_version: Option<String>,
+    pub peer_name: String,
+    /// The range declared in `peerDependencies`.
+    pub peer_range: String,
+    /// The package.json of the package the peer dependency resolved to.
+    pub resolved_package_json_path: Vc<FileSystemPath>,
+    pub resolved_version: String,
+    /// A module of the package that imports the peer dependency.
+    pub importer: Vc<Box<dyn ChunkItem>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for PeerDependencyMismatchIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(format!(
+            "Peer dependency {} of {} is resolved outside of its declared range",
+            self.peer_name, self.package_name
+        ))
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.package_json_path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<OptionStyledString>> {
+        let package = match &self.package_version {
+            Some(version) => format!("{}@{}", self.package_name, version),
+            None => self.package_name.clone(),
+        };
+        Ok(Vc::cell(Some(
+            StyledString::Stack(vec![
+                StyledString::Line(vec![
+                    StyledString::Code(package),
+                    StyledString::Text(" declares a peer dependency on ".to_string()),
+                    StyledString::Code(format!("{}@{}", self.peer_name, self.peer_range)),
+                    StyledString::Text(", but it resolves to ".to_string()),
+                    StyledString::Code(format!("{}@{}", self.peer_name, self.resolved_version)),
+                    StyledString::Text(" at ".to_string()),
+                    StyledString::Code(
+                        self.resolved_package_json_path
+                            .parent()
+                            .to_string()
+                            .await?
+                            .clone_value(),
+                    ),
+                    StyledString::Text(".".to_string()),
+                ]),
+                StyledString::Line(vec![
+                    StyledString::Text("It is imported by ".to_string()),
+                    StyledString::Code(
+                        self.importer.asset_ident().to_string().await?.clone_value(),
+                    ),
+                    StyledString::Text(".".to_string()),
+                ]),
+                StyledString::Text(
+                    "This usually means that the package manager hoisted another version of the \
+                     peer dependency next to the package. Both versions may end up in the same \
+                     chunks, which causes errors at runtime like hooks or context not being \
+                     shared. Install a version within the declared range, or deduplicate the \
+                     dependency."
+                        .to_string(),
+                ),
+            ])
+            .cell(),
+        )))
+    }
+}
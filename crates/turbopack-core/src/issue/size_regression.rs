@@ -0,0 +1,67 @@
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+
+use super::{Issue, IssueSeverity, OptionStyledString, StyledString};
+
+/// A chunk group grew beyond its threshold compared to the size baseline.
+#[turbo_tasks::value(shared)]
+pub struct SizeRegressionIssue {
+    pub baseline_path: Vc<FileSystemPath>,
+    pub chunk_group: String,
+    pub before: u64,
+    pub after: u64,
+    pub max_growth: Option<u64>,
+    pub max_growth_percent: Option<u32>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SizeRegressionIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(format!(
+            "Chunk group {} grew beyond its size threshold",
+            self.chunk_group
+        ))
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.baseline_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        let growth = self.after - self.before;
+        let limits = self
+            .max_growth
+            .map(|max| format!("{} bytes", max))
+            .into_iter()
+            .chain(self.max_growth_percent.map(|max| format!("{}%", max)))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        Vc::cell(Some(
+            StyledString::Stack(vec![
+                StyledString::Text(format!(
+                    "The chunk group grew from {} to {} bytes (+{} bytes), which exceeds the \
+                     allowed growth of {}.",
+                    self.before, self.after, growth, limits
+                )),
+                StyledString::Text(
+                    "If the growth is intended, update the size baseline.".to_string(),
+                ),
+            ])
+            .cell(),
+        ))
+    }
+}
@@ -0,0 +1,92 @@
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+
+use super::{Issue, IssueSeverity, OptionStyledString, StyledString};
+
+/// Reports how a usage profile changed the chunks of a chunk group compared to
+/// the default chunking, summed over all chunk types.
+#[turbo_tasks::value(shared)]
+pub struct UsageProfileChunkingIssue {
+    pub path: Vc<FileSystemPath>,
+    pub default_chunks: usize,
+    pub hot_chunks: usize,
+    pub hot_modules: usize,
+    pub hot_size: usize,
+    pub cold_chunks: usize,
+    /// The idents of the modules that haven't been executed.
+    pub cold_modules: Vec<String>,
+    pub cold_size: usize,
+}
+
+/// Only so many deferred modules are listed in the description.
+const MAX_LISTED_MODULES: usize = 20;
+
+impl UsageProfileChunkingIssue {
+    /// Adds the changes to the chunks of another chunk type.
+    pub fn extend(&mut self, other: Self) {
+        self.default_chunks += other.default_chunks;
+        self.hot_chunks += other.hot_chunks;
+        self.hot_modules += other.hot_modules;
+        self.hot_size += other.hot_size;
+        self.cold_chunks += other.cold_chunks;
+        self.cold_modules.extend(other.cold_modules);
+        self.cold_size += other.cold_size;
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UsageProfileChunkingIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Info.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Chunking has been adjusted by the usage profile".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        let mut lines = vec![
+            StyledString::Text(format!(
+                "{} executed modules ({} bytes) have been placed into {} early chunks.",
+                self.hot_modules, self.hot_size, self.hot_chunks
+            )),
+            StyledString::Text(format!(
+                "{} modules that haven't been executed ({} bytes) have been deferred into {} \
+                 chunks, which are loaded after the entries have been evaluated:",
+                self.cold_modules.len(),
+                self.cold_size,
+                self.cold_chunks
+            )),
+        ];
+        lines.extend(
+            self.cold_modules
+                .iter()
+                .take(MAX_LISTED_MODULES)
+                .map(|module| StyledString::Code(format!("  {}", module))),
+        );
+        if self.cold_modules.len() > MAX_LISTED_MODULES {
+            lines.push(StyledString::Text(format!(
+                "  and {} more",
+                self.cold_modules.len() - MAX_LISTED_MODULES
+            )));
+        }
+        lines.push(StyledString::Text(format!(
+            "Without the usage profile, the modules would have been placed into {} chunks.",
+            self.default_chunks
+        )));
+        Vc::cell(Some(StyledString::Stack(lines).cell()))
+    }
+}
@@ -14,9 +14,12 @@ pub mod code_builder;
 pub mod compile_time_info;
 pub mod context;
 pub mod diagnostics;
+pub mod emit_policy;
 pub mod environment;
 pub mod error;
+pub mod feature_flags;
 pub mod file_source;
+pub mod hash;
 pub mod ident;
 pub mod introspect;
 pub mod issue;
@@ -35,8 +38,10 @@ pub mod source_map;
 pub mod source_pos;
 pub mod source_transform;
 pub mod target;
+pub mod unused_files;
 mod utils;
 pub mod version;
+pub mod virtual_entry;
 pub mod virtual_output;
 pub mod virtual_source;
 
@@ -24,6 +24,9 @@ pub struct OptionModule(Option<Vc<Box<dyn Module>>>);
 #[turbo_tasks::value(transparent)]
 pub struct Modules(Vec<Vc<Box<dyn Module>>>);
 
+#[turbo_tasks::value(transparent)]
+pub struct OptionModules(Option<Vc<Modules>>);
+
 #[turbo_tasks::value_impl]
 impl Modules {
     #[turbo_tasks::function]
@@ -17,8 +17,82 @@ pub trait OutputAsset: Asset {
     fn references(self: Vc<Self>) -> Vc<OutputAssets> {
         OutputAssets::empty()
     }
+
+    /// Hints for serving the [OutputAsset], e.g. that it can be cached forever
+    /// because its name contains a hash of its content.
+    fn serving_hints(self: Vc<Self>) -> Vc<ServingHints> {
+        ServingHints::empty()
+    }
+}
+
+/// How long an [OutputAsset] may be cached.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum CacheControl {
+    /// The content never changes for the same path, e.g. because the path
+    /// contains a hash of the content.
+    Immutable,
+    /// The content may change at any time and must be revalidated.
+    MustRevalidate,
+}
+
+impl CacheControl {
+    /// The value of the `cache-control` header.
+    pub fn header_value(&self) -> &'static str {
+        match self {
+            CacheControl::Immutable => "public, max-age=31536000, immutable",
+            CacheControl::MustRevalidate => "must-revalidate",
+        }
+    }
+}
+
+/// Hints for serving an [OutputAsset], used by the dev server and exported to
+/// the build manifest for CDNs.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct ServingHints {
+    /// The caching policy, if it differs from the server's default.
+    pub cache_control: Option<CacheControl>,
+    /// Overrides the content type guessed from the path.
+    pub content_type: Option<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl ServingHints {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        ServingHints::default().cell()
+    }
+
+    #[turbo_tasks::function]
+    pub fn immutable() -> Vc<Self> {
+        ServingHints {
+            cache_control: Some(CacheControl::Immutable),
+            content_type: None,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    pub fn must_revalidate() -> Vc<Self> {
+        ServingHints {
+            cache_control: Some(CacheControl::MustRevalidate),
+            content_type: None,
+        }
+        .cell()
+    }
+}
+
+impl ServingHints {
+    /// Whether the hints don't change how the asset is served.
+    pub fn is_empty(&self) -> bool {
+        self.cache_control.is_none() && self.content_type.is_none()
+    }
 }
 
+#[turbo_tasks::value(transparent)]
+pub struct OptionOutputAsset(Option<Vc<Box<dyn OutputAsset>>>);
+
 #[turbo_tasks::value(transparent)]
 pub struct OutputAssets(Vec<Vc<Box<dyn OutputAsset>>>);
 
@@ -45,7 +45,7 @@ pub enum EcmaScriptModulesReferenceSubType {
 }
 
 /// The individual set of conditions present on this module through `@import`
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[turbo_tasks::value(shared)]
 pub struct ImportAttributes {
     pub layer: Option<String>,
@@ -58,21 +58,16 @@ pub struct ImportAttributes {
 #[derive(Debug, Default)]
 #[turbo_tasks::value]
 pub struct ImportContext {
-    pub layers: Vec<String>,
-    pub supports: Vec<String>,
-    pub media: Vec<String>,
+    /// The conditions of every `@import` on the import path, outermost first,
+    /// which preserves the order in which they apply.
+    pub conditions: Vec<ImportAttributes>,
 }
 
 #[turbo_tasks::value_impl]
 impl ImportContext {
     #[turbo_tasks::function]
-    pub fn new(layers: Vec<String>, media: Vec<String>, supports: Vec<String>) -> Vc<Self> {
-        ImportContext {
-            layers,
-            media,
-            supports,
-        }
-        .cell()
+    pub fn new() -> Vc<Self> {
+        ImportContext::default().cell()
     }
 
     #[turbo_tasks::function]
@@ -82,39 +77,15 @@ impl ImportContext {
         attr_media: Option<String>,
         attr_supports: Option<String>,
     ) -> Result<Vc<Self>> {
-        let this = &*self.await?;
-
-        let layers = {
-            let mut layers = this.layers.clone();
-            if let Some(attr_layer) = attr_layer {
-                if !layers.contains(&attr_layer) {
-                    layers.push(attr_layer.to_owned());
-                }
-            }
-            layers
-        };
-
-        let media = {
-            let mut media = this.media.clone();
-            if let Some(attr_media) = attr_media {
-                if !media.contains(&attr_media) {
-                    media.push(attr_media.to_owned());
-                }
-            }
-            media
-        };
-
-        let supports = {
-            let mut supports = this.supports.clone();
-            if let Some(attr_supports) = attr_supports {
-                if !supports.contains(&attr_supports) {
-                    supports.push(attr_supports.to_owned());
-                }
-            }
-            supports
-        };
-
-        Ok(ImportContext::new(layers, media, supports))
+        let mut conditions = self.await?.conditions.clone();
+        if attr_layer.is_some() || attr_media.is_some() || attr_supports.is_some() {
+            conditions.push(ImportAttributes {
+                layer: attr_layer,
+                supports: attr_supports,
+                media: attr_media,
+            });
+        }
+        Ok(ImportContext { conditions }.cell())
     }
 }
 
@@ -137,6 +108,7 @@ pub enum CssReferenceSubType {
 #[derive(Debug, Clone, PartialOrd, Ord, Hash)]
 pub enum UrlReferenceSubType {
     EcmaScriptNewUrl,
+    ImportMetaResolve,
     CssUrl,
     Custom(u8),
     Undefined,
@@ -163,6 +135,8 @@ pub enum EntryReferenceSubType {
     Middleware,
     Instrumentation,
     Runtime,
+    Worklet,
+    Worker,
     Custom(u8),
     Undefined,
 }
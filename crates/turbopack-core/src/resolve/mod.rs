@@ -1787,6 +1787,33 @@ async fn resolve_alias_field_result(
     Ok(ResolveResult::unresolveable_with_affecting_sources(refs).cell())
 }
 
+/// Looks up the alias field entry for the file at `rel_path` (e.g.
+/// `./lib/server.js`) in a package. Like other bundlers, the keys may omit the
+/// leading `./` and the extension of the file.
+fn alias_field_entry<'a>(
+    field_value: &'a serde_json::Map<String, JsonValue>,
+    rel_path: &str,
+) -> Option<(&'a str, &'a JsonValue)> {
+    let without_dot = rel_path.strip_prefix("./").unwrap_or(rel_path);
+    let without_extension = match without_dot.rsplit_once('.') {
+        Some((stem, extension))
+            if !extension.contains('/') && !stem.is_empty() && !stem.ends_with('/') =>
+        {
+            Some(stem)
+        }
+        _ => None,
+    };
+    let mut candidates = vec![rel_path.to_string(), without_dot.to_string()];
+    if let Some(stem) = without_extension {
+        candidates.push(format!("./{stem}"));
+        candidates.push(stem.to_string());
+    }
+    candidates
+        .iter()
+        .find_map(|candidate| field_value.get_key_value(candidate.as_str()))
+        .map(|(key, value)| (key.as_str(), value))
+}
+
 #[tracing::instrument(level = Level::TRACE, skip_all)]
 async fn resolved(
     fs_path: Vc<FileSystemPath>,
@@ -1828,7 +1855,7 @@ async fn resolved(
             continue;
         };
 
-        let Some(value) = field_value.get(&rel_path) else {
+        let Some((key, value)) = alias_field_entry(field_value, &rel_path) else {
             continue;
         };
 
@@ -1838,7 +1865,7 @@ async fn resolved(
             package_path,
             options,
             *package_json_path,
-            &rel_path,
+            key,
             field,
             query,
         )
@@ -7,11 +7,12 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sourcemap::{DecodedMap, SourceMap as RegularMap, SourceMapBuilder, SourceMapIndex};
 use turbo_tasks::{TryJoinIterExt, Vc};
 use turbo_tasks_fs::{
+    glob::Glob,
     rope::{Rope, RopeBuilder},
     FileSystemPath,
 };
 
-use crate::source_pos::SourcePos;
+use crate::{source_pos::SourcePos, SOURCE_MAP_ROOT_NAME};
 
 pub(crate) mod source_map_asset;
 
@@ -55,6 +56,53 @@ pub struct SectionMapping(IndexMap<String, Vc<Box<dyn GenerateSourceMap>>>);
 #[turbo_tasks::value(transparent)]
 pub struct OptionSourceMap(Option<Vc<SourceMap>>);
 
+/// Controls which sources are redacted from source maps before they are
+/// exposed, e.g. to keep third-party code out of maps served to clients while
+/// keeping full maps for first-party code.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct SourceMapPrivacy {
+    /// Redacts sources that are located in a `node_modules` directory.
+    pub exclude_node_modules: bool,
+    /// Redacts sources whose path (relative to the project root) matches any
+    /// of these globs.
+    pub exclude_globs: Vec<String>,
+    /// Also removes the mappings into redacted sources, instead of only
+    /// removing their contents.
+    pub strip_mappings: bool,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionSourceMapPrivacy(Option<Vc<SourceMapPrivacy>>);
+
+/// Applies `privacy` to `source_map`, if both are present.
+#[turbo_tasks::function]
+pub async fn redact_source_map(
+    source_map: Vc<OptionSourceMap>,
+    privacy: Vc<OptionSourceMapPrivacy>,
+) -> Result<Vc<OptionSourceMap>> {
+    let (Some(map), Some(privacy)) = (*source_map.await?, *privacy.await?) else {
+        return Ok(source_map);
+    };
+    Ok(Vc::cell(Some(map.redact(privacy))))
+}
+
+/// How chunks reference their source maps, see
+/// [ChunkingContext::reference_chunk_source_maps](crate::chunk::ChunkingContext::reference_chunk_source_maps).
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum SourceMapMode {
+    /// The source map is emitted as a `.map` file next to the chunk.
+    #[default]
+    External,
+    /// The source map is embedded into the chunk as a data URL, for
+    /// environments that can't fetch the `.map` files next to chunks, e.g.
+    /// some embedded webviews.
+    Inline,
+    /// The chunk doesn't reference a source map.
+    None,
+}
+
 #[turbo_tasks::value(transparent)]
 #[derive(Clone, Debug)]
 pub struct Tokens(Vec<Token>);
@@ -361,6 +409,99 @@ impl GenerateSourceMap for SourceMap {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl SourceMap {
+    /// Returns a copy of this source map with the sources selected by
+    /// `privacy` redacted.
+    #[turbo_tasks::function]
+    pub async fn redact(self: Vc<Self>, privacy: Vc<SourceMapPrivacy>) -> Result<Vc<Self>> {
+        Ok(match &*self.await? {
+            SourceMap::Decoded(map) => {
+                let privacy = privacy.await?;
+                let globs = privacy
+                    .exclude_globs
+                    .iter()
+                    .map(|glob| Glob::try_from(glob.as_str()))
+                    .collect::<Result<Vec<_>>>()?;
+                let Some(map) = map.as_regular_source_map() else {
+                    return Ok(self);
+                };
+                SourceMap::new_regular(redact_regular_map(&map, privacy.strip_mappings, |source| {
+                    is_private_source(source, privacy.exclude_node_modules, &globs)
+                }))
+                .cell()
+            }
+            SourceMap::Sectioned(map) => SourceMap::new_sectioned(
+                map.sections
+                    .iter()
+                    .map(|section| {
+                        SourceMapSection::new(section.offset, section.map.redact(privacy))
+                    })
+                    .collect(),
+            )
+            .cell(),
+        })
+    }
+}
+
+/// Returns `true` if the source map source `source` is selected for redaction
+/// by the `node_modules` option or by one of `globs`, which match paths
+/// relative to the project root.
+fn is_private_source(source: &str, exclude_node_modules: bool, globs: &[Glob]) -> bool {
+    let path = source
+        .strip_prefix(&format!("/{SOURCE_MAP_ROOT_NAME}/"))
+        .unwrap_or(source);
+    let path = path.strip_prefix("[project]/").unwrap_or(path);
+    (exclude_node_modules && (path.starts_with("node_modules/") || path.contains("/node_modules/")))
+        || globs.iter().any(|glob| glob.execute(path))
+}
+
+/// Rebuilds `map` without the contents of the sources for which `is_redacted`
+/// returns true. When `strip_mappings` is set, these sources and all mappings
+/// into them are removed as well.
+fn redact_regular_map(
+    map: &RegularMap,
+    strip_mappings: bool,
+    is_redacted: impl Fn(&str) -> bool,
+) -> RegularMap {
+    let mut builder = SourceMapBuilder::new(map.get_file());
+    let source_ids = map
+        .sources()
+        .enumerate()
+        .map(|(id, source)| {
+            let redacted = is_redacted(source);
+            if redacted && strip_mappings {
+                return None;
+            }
+            let new_id = builder.add_source(source);
+            if !redacted {
+                builder.set_source_contents(new_id, map.get_source_contents(id as u32));
+            }
+            Some(new_id)
+        })
+        .collect::<Vec<_>>();
+    for token in map.tokens() {
+        let raw = token.get_raw_token();
+        match source_ids.get(raw.src_id as usize).copied().flatten() {
+            Some(src_id) => {
+                let name_id = token.get_name().map(|name| builder.add_name(name));
+                builder.add_raw(
+                    raw.dst_line,
+                    raw.dst_col,
+                    raw.src_line,
+                    raw.src_col,
+                    Some(src_id),
+                    name_id,
+                );
+            }
+            None => {
+                builder.add_raw(raw.dst_line, raw.dst_col, 0, 0, None, None);
+            }
+        }
+    }
+    builder.into_sourcemap()
+}
+
 /// A regular source map covers an entire file.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InnerSourceMap {
@@ -489,3 +630,60 @@ impl SourceMapSection {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APP: &str = "/turbopack/[project]/src/app.js";
+    const LIB: &str = "/turbopack/[project]/node_modules/lib/index.js";
+
+    /// A map of two lines, the first mapped into `APP` and the second into
+    /// `LIB`.
+    fn map() -> RegularMap {
+        let mut builder = SourceMapBuilder::new(Some("chunk.js"));
+        for (line, source) in [APP, LIB].into_iter().enumerate() {
+            let id = builder.add_source(source);
+            builder.set_source_contents(id, Some(source));
+            builder.add_raw(line as u32, 0, 0, 0, Some(id), None);
+        }
+        builder.into_sourcemap()
+    }
+
+    fn sources(map: &RegularMap) -> Vec<(&str, Option<&str>)> {
+        map.sources()
+            .enumerate()
+            .map(|(id, source)| (source, map.get_source_contents(id as u32)))
+            .collect()
+    }
+
+    #[test]
+    fn selects_node_modules_and_glob_matches() {
+        let globs = [Glob::try_from("src/secret/**").unwrap()];
+        assert!(is_private_source(LIB, true, &[]));
+        assert!(!is_private_source(LIB, false, &[]));
+        assert!(!is_private_source(APP, true, &globs));
+        assert!(is_private_source(
+            "/turbopack/[project]/src/secret/key.js",
+            false,
+            &globs
+        ));
+    }
+
+    #[test]
+    fn redacts_the_contents_of_sources() {
+        let map = redact_regular_map(&map(), false, |source| source == LIB);
+
+        assert_eq!(sources(&map), vec![(APP, Some(APP)), (LIB, None)]);
+        assert_eq!(map.get_token_count(), 2);
+    }
+
+    #[test]
+    fn strips_the_mappings_into_sources() {
+        let map = redact_regular_map(&map(), true, |source| source == LIB);
+
+        assert_eq!(sources(&map), vec![(APP, Some(APP))]);
+        assert_eq!(map.get_token_count(), 1);
+        assert_eq!(map.get_token(0).unwrap().get_source(), Some(APP));
+    }
+}
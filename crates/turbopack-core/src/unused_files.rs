@@ -0,0 +1,138 @@
+//! Detection of source files that aren't referenced by any entry, e.g. to
+//! find dead code that can be deleted.
+//!
+//! TypeScript imports that only import types are removed by the TypeScript
+//! transform before references are analyzed. The entries should be processed
+//! with an asset context that keeps them, i.e. with types enabled and without
+//! the TypeScript transform, otherwise files that are only imported for their
+//! types are reported.
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{glob::Glob, DirectoryEntry, FileSystem, FileSystemPath};
+
+use crate::{
+    module::{Module, Modules},
+    reference::all_modules,
+};
+
+/// Which files [unused_files] checks. All globs and directories are relative
+/// to the context path.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct UnusedFilesOptions {
+    /// The files to check, e.g. `src/**/*.{js,jsx,ts,tsx}`.
+    pub include: Vec<String>,
+    /// Files that are never reported, e.g. tests or config files that are
+    /// loaded by other tools.
+    pub exclude: Vec<String>,
+    /// Directories that are served as they are, e.g. `public`. Their files are
+    /// never reported, as they are referenced by URL.
+    pub public_dirs: Vec<String>,
+    /// The patterns that transforms rename files with, e.g. the `rename_as`
+    /// of webpack loader rules like `*.js`. The `*` stands for the path of the
+    /// original file, which is used when a module has been renamed.
+    pub rename_patterns: Vec<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct UnusedFiles(Vec<Vc<FileSystemPath>>);
+
+/// Returns the files under `context_path` that match the options and aren't
+/// the source of any module in the module graphs of `entries`, ordered by
+/// path.
+#[turbo_tasks::function]
+pub async fn unused_files(
+    context_path: Vc<FileSystemPath>,
+    entries: Vc<Modules>,
+    options: Vc<UnusedFilesOptions>,
+) -> Result<Vc<UnusedFiles>> {
+    let options = options.await?;
+    let context_path_ref = context_path.await?;
+
+    let mut modules = HashSet::new();
+    for &entry in entries.await?.iter() {
+        modules.extend(all_modules(entry).await?.iter().copied());
+    }
+    let mut used_paths = HashSet::new();
+    for module in modules {
+        let path = module.ident().path().await?;
+        for pattern in options.rename_patterns.iter() {
+            if let Some(original) = original_path(&path.path, pattern) {
+                let original = path.fs.root().join(original.to_string()).await?;
+                if let Some(original) = context_path_ref.get_path_to(&original) {
+                    used_paths.insert(original.to_string());
+                }
+            }
+        }
+        if let Some(path) = context_path_ref.get_path_to(&path) {
+            used_paths.insert(path.to_string());
+        }
+    }
+
+    let exclude = options
+        .exclude
+        .iter()
+        .map(|glob| Glob::parse(glob))
+        .collect::<Result<Vec<_>>>()?;
+    let is_in_public_dir = |path: &str| {
+        options.public_dirs.iter().any(|dir| {
+            path.strip_prefix(dir.trim_end_matches('/'))
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    };
+
+    let mut unused = BTreeMap::new();
+    for include in options.include.iter() {
+        let mut queue = vec![context_path.read_glob(Glob::new(include.clone()), false)];
+        while let Some(result) = queue.pop() {
+            let result = result.await?;
+            for (path, entry) in result.results.iter() {
+                let DirectoryEntry::File(file) = entry else {
+                    continue;
+                };
+                if used_paths.contains(path)
+                    || exclude.iter().any(|glob| glob.execute(path))
+                    || is_in_public_dir(path)
+                {
+                    continue;
+                }
+                unused.insert(path.clone(), *file);
+            }
+            queue.extend(result.inner.values().copied());
+        }
+    }
+
+    Ok(Vc::cell(unused.into_values().collect()))
+}
+
+/// The path of the file that has been renamed to `path` with `pattern`, see
+/// [UnusedFilesOptions::rename_patterns].
+fn original_path<'a>(path: &'a str, pattern: &str) -> Option<&'a str> {
+    let (prefix, suffix) = pattern.split_once('*')?;
+    path.strip_prefix(prefix)?
+        .strip_suffix(suffix)
+        .filter(|original| !original.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_original_path() {
+        assert_eq!(
+            original_path("src/logo.svg.js", "*.js"),
+            Some("src/logo.svg")
+        );
+        assert_eq!(
+            original_path("generated/src/schema.graphql.ts", "generated/*.ts"),
+            Some("src/schema.graphql")
+        );
+        assert_eq!(original_path("src/index.ts", "*.js"), None);
+        assert_eq!(original_path(".js", "*.js"), None);
+        assert_eq!(original_path("src/index.js", "index.js"), None);
+    }
+}
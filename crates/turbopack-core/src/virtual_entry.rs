@@ -0,0 +1,84 @@
+use anyhow::Result;
+use turbo_tasks::{State, Value, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+
+use crate::{
+    asset::AssetContent,
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkingContext, ChunkingContextExt,
+        EvaluatableAssetExt, EvaluatableAssets,
+    },
+    context::AssetContext,
+    output::OutputAssets,
+    source::Source,
+    virtual_source::VirtualSource,
+};
+
+/// An entry whose code is kept in memory instead of being read from a file,
+/// e.g. the code of a REPL or playground.
+///
+/// The code can be replaced with [VirtualEntry::set_code]. This invalidates
+/// everything that was computed from it, like a change to a file on disk
+/// would, so clients of the chunk group receive the change as a hot update.
+/// The entry is located at `path`, which is only used to resolve its imports
+/// and to name its module. Nothing needs to exist at that path.
+#[turbo_tasks::value(serialization = "none", eq = "manual", cell = "new")]
+pub struct VirtualEntry {
+    path: Vc<FileSystemPath>,
+    code: State<String>,
+}
+
+impl VirtualEntry {
+    /// Creates a new entry. It must be created outside of turbo tasks
+    /// functions, as its code would be reset every time such a function is
+    /// executed again.
+    pub fn new(path: Vc<FileSystemPath>, code: String) -> Vc<Self> {
+        VirtualEntry {
+            path,
+            code: State::new(code),
+        }
+        .cell()
+    }
+
+    /// Replaces the code of the entry. Nothing is invalidated when the code
+    /// didn't change.
+    pub async fn set_code(self: Vc<Self>, code: String) -> Result<()> {
+        let this = self.await?;
+        this.code.set(code);
+        Ok(())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl VirtualEntry {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        let code = this.code.get().clone();
+        Ok(AssetContent::file(File::from(code).into()))
+    }
+
+    /// The source of the entry, with the current code.
+    #[turbo_tasks::function]
+    pub async fn source(self: Vc<Self>) -> Result<Vc<VirtualSource>> {
+        let this = self.await?;
+        Ok(VirtualSource::new(this.path, self.content()))
+    }
+
+    /// The output assets of the evaluated chunk group of the entry, which
+    /// evaluates `runtime_entries` before the entry.
+    #[turbo_tasks::function]
+    pub async fn evaluated_chunk_group_assets(
+        self: Vc<Self>,
+        asset_context: Vc<Box<dyn AssetContext>>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        runtime_entries: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<OutputAssets>> {
+        let source = self.source();
+        Ok(chunking_context.evaluated_chunk_group_assets(
+            source.ident(),
+            runtime_entries.with_entry(source.to_evaluatable(asset_context)),
+            Value::new(AvailabilityInfo::Root),
+        ))
+    }
+}
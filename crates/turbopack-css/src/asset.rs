@@ -75,8 +75,7 @@ impl ParseCss for CssModuleAsset {
         Ok(parse_css(
             this.source,
             Vc::upcast(self),
-            this.import_context
-                .unwrap_or_else(|| ImportContext::new(vec![], vec![], vec![])),
+            this.import_context.unwrap_or_else(ImportContext::new),
             this.ty,
             this.use_lightningcss,
         ))
@@ -0,0 +1,85 @@
+use turbo_tasks::Vc;
+use turbopack_core::{
+    chunk::{ChunkItem, ChunkType, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    reference::ModuleReferences,
+};
+
+use super::{CssChunkItem, CssChunkItemContent, CssChunkPlaceable, CssChunkType};
+
+/// Replaces a CSS module that failed to build. CSS can't throw, so the stub
+/// has no rules and only carries the build error as a comment. The rest of the
+/// chunk group keeps working.
+#[turbo_tasks::value]
+pub struct FailingCssModuleChunkItem {
+    module: Vc<Box<dyn CssChunkPlaceable>>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    error: String,
+}
+
+#[turbo_tasks::value_impl]
+impl FailingCssModuleChunkItem {
+    #[turbo_tasks::function]
+    pub fn new(
+        module: Vc<Box<dyn CssChunkPlaceable>>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        error: String,
+    ) -> Vc<Self> {
+        Self::cell(FailingCssModuleChunkItem {
+            module,
+            chunking_context,
+            error,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for FailingCssModuleChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        // Same ident as the module, so that importers use the id of the stub.
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        ModuleReferences::empty()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    fn ty(&self) -> Vc<Box<dyn ChunkType>> {
+        Vc::upcast(Vc::<CssChunkType>::default())
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CssChunkItem for FailingCssModuleChunkItem {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<CssChunkItemContent> {
+        // The error must not end the comment early.
+        let message = self.error.replace("*/", "* /");
+        CssChunkItemContent {
+            import_context: None,
+            imports: vec![],
+            inner_code: format!("/* Module failed to build:\n{message}\n*/\n").into(),
+            source_map: None,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        self.chunking_context
+    }
+}
@@ -1,3 +1,4 @@
+pub(crate) mod failing_item;
 pub(crate) mod single_item_chunk;
 pub mod source_map;
 
@@ -13,20 +14,21 @@ use turbopack_core::{
         AsyncModuleInfo, Chunk, ChunkItem, ChunkItemWithAsyncModuleInfo, ChunkType,
         ChunkableModule, ChunkingContext, ModuleId, OutputChunk, OutputChunkRuntimeInfo,
     },
-    code_builder::{Code, CodeBuilder},
-    ident::AssetIdent,
+    code_builder::{append_source_map_url, Code, CodeBuilder},
+    ident::{common_layer, AssetIdent},
     introspect::{
         module::IntrospectableModule,
         utils::{children_from_output_assets, content_to_details},
         Introspectable, IntrospectableChildren,
     },
     module::Module,
-    output::{OutputAsset, OutputAssets},
+    output::{OutputAsset, OutputAssets, ServingHints},
     reference_type::ImportContext,
     server_fs::ServerFileSystem,
-    source_map::{GenerateSourceMap, OptionSourceMap},
+    source_map::{redact_source_map, GenerateSourceMap, OptionSourceMap, SourceMapMode},
 };
 
+pub use self::failing_item::FailingCssModuleChunkItem;
 use self::{single_item_chunk::chunk::SingleItemCssChunk, source_map::CssChunkSourceMapAsset};
 use crate::{process::ParseCssResultSourceMap, util::stringify_js, ImportAssetReference};
 
@@ -80,22 +82,31 @@ impl CssChunk {
             writeln!(body, "/* {} */", id)?;
             let mut close: Vec<String> = vec![];
             if let Some(import_context) = content.import_context {
-                let import_context = &*import_context.await?;
-                if !&import_context.layers.is_empty() {
-                    writeln!(body, "@layer {} {{", import_context.layers.join("."))?;
-                    close.push("}\n".to_owned());
-                }
-                if !&import_context.media.is_empty() {
-                    writeln!(body, "@media {} {{", import_context.media.join(" and "))?;
-                    close.push("}\n".to_owned());
-                }
-                if !&import_context.supports.is_empty() {
-                    writeln!(
-                        body,
-                        "@supports {} {{",
-                        import_context.supports.join(" and ")
-                    )?;
-                    close.push("}\n".to_owned());
+                // The conditions of the outermost `@import` are written first.
+                // Within an `@import url(...) layer(...) supports(...) media`, the
+                // layer only applies when the import conditions match, so it's
+                // written as `@media ... { @supports ... { @layer ... { } } }`.
+                // Otherwise the layer would be declared even when the conditions
+                // don't match, which changes the layer order. Nesting the
+                // conditions instead of combining them keeps media query lists
+                // intact.
+                for condition in import_context.await?.conditions.iter() {
+                    if let Some(media) = &condition.media {
+                        writeln!(body, "@media {} {{", media)?;
+                        close.push("}\n".to_owned());
+                    }
+                    if let Some(supports) = &condition.supports {
+                        writeln!(body, "@supports {} {{", supports)?;
+                        close.push("}\n".to_owned());
+                    }
+                    if let Some(layer) = &condition.layer {
+                        if layer.is_empty() {
+                            writeln!(body, "@layer {{")?;
+                        } else {
+                            writeln!(body, "@layer {} {{", layer)?;
+                        }
+                        close.push("}\n".to_owned());
+                    }
                 }
             }
 
@@ -115,22 +126,18 @@ impl CssChunk {
         let built = &body.build();
         code.push_code(built);
 
-        if *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?
-            && code.has_source_map()
-        {
-            let chunk_path = self.path().await?;
-            writeln!(
-                code,
-                "/*# sourceMappingURL={}.map*/",
-                chunk_path.file_name()
-            )?;
-        }
-
-        let c = code.build().cell();
-        Ok(c)
+        let chunk_path = self.path().await?;
+        append_source_map_url(
+            code.build().cell(),
+            &chunk_path,
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            this.chunking_context.source_map_privacy(),
+            |url| format!("/*# sourceMappingURL={url}*/\n"),
+        )
+        .await
     }
 
     #[turbo_tasks::function]
@@ -140,6 +147,31 @@ impl CssChunk {
             File::from(code.source_code().clone()).into(),
         ))
     }
+
+    /// A hash of the code of the chunk items and of the conditions they're
+    /// imported with, which doesn't depend on the path of the chunk.
+    #[turbo_tasks::function]
+    async fn content_hash(&self) -> Result<Vc<String>> {
+        let mut hasher = self.chunking_context.hash_algorithm().await?.hasher();
+        for css_item in &self.content.await?.chunk_items {
+            hasher.write_ref(&css_item.id().await?.to_string());
+            let content = css_item.content().await?;
+            for import in &content.imports {
+                if let CssImport::External(external_import) = import {
+                    hasher.write_ref(&*external_import.await?);
+                }
+            }
+            if let Some(import_context) = content.import_context {
+                for condition in import_context.await?.conditions.iter() {
+                    hasher.write_ref(&condition.media);
+                    hasher.write_ref(&condition.supports);
+                    hasher.write_ref(&condition.layer);
+                }
+            }
+            hasher.write_ref(&content.inner_code);
+        }
+        Ok(Vc::cell(hasher.finish_hex()))
+    }
 }
 
 #[turbo_tasks::value]
@@ -264,6 +296,17 @@ impl OutputAsset for CssChunk {
             *ident = ident.resolve().await?;
         }
 
+        let layer = if *this.chunking_context.layered_chunks().await? {
+            common_layer(
+                chunk_items
+                    .iter()
+                    .map(|chunk_item| chunk_item.asset_ident()),
+            )
+            .await?
+        } else {
+            None
+        };
+
         let ident = AssetIdent {
             path: if let Some((common_path, _)) = common_path {
                 common_path
@@ -275,13 +318,31 @@ impl OutputAsset for CssChunk {
             assets,
             modifiers: Vec::new(),
             part: None,
-            layer: None,
+            layer,
         };
 
-        Ok(AssetIdent::from_path(this.chunking_context.chunk_path(
-            AssetIdent::new(Value::new(ident)),
-            ".css".to_string(),
-        )))
+        let ident = AssetIdent::new(Value::new(ident));
+        let path = if *this.chunking_context.content_hash_chunk_names().await? {
+            this.chunking_context.content_hashed_chunk_path(
+                ident,
+                self.content_hash(),
+                ".css".to_string(),
+            )
+        } else {
+            this.chunking_context.chunk_path(ident, ".css".to_string())
+        };
+        Ok(AssetIdent::from_path(path))
+    }
+
+    #[turbo_tasks::function]
+    async fn serving_hints(&self) -> Result<Vc<ServingHints>> {
+        Ok(
+            if *self.chunking_context.content_hash_chunk_names().await? {
+                ServingHints::immutable()
+            } else {
+                ServingHints::empty()
+            },
+        )
     }
 
     #[turbo_tasks::function]
@@ -295,11 +356,13 @@ impl OutputAsset for CssChunk {
                 *item,
             )));
         }
-        if *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?
-        {
+        if matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        ) {
             references.push(Vc::upcast(CssChunkSourceMapAsset::new(self)));
         }
         Ok(Vc::cell(references))
@@ -317,8 +380,11 @@ impl Asset for CssChunk {
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for CssChunk {
     #[turbo_tasks::function]
-    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
-        self.code().generate_source_map()
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(redact_source_map(
+            self.code().generate_source_map(),
+            self.await?.chunking_context.source_map_privacy(),
+        ))
     }
 }
 
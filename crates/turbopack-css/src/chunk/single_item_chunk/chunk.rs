@@ -6,11 +6,11 @@ use turbo_tasks_fs::File;
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{Chunk, ChunkItem, ChunkingContext},
-    code_builder::{Code, CodeBuilder},
+    code_builder::{append_source_map_url, Code, CodeBuilder},
     ident::AssetIdent,
     introspect::Introspectable,
     output::{OutputAsset, OutputAssets},
-    source_map::{GenerateSourceMap, OptionSourceMap},
+    source_map::{redact_source_map, GenerateSourceMap, OptionSourceMap, SourceMapMode},
 };
 
 use super::source_map::SingleItemCssChunkSourceMapAsset;
@@ -56,22 +56,18 @@ impl SingleItemCssChunk {
         let content = this.item.content().await?;
         code.push_source(&content.inner_code, content.source_map.map(Vc::upcast));
 
-        if *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?
-            && code.has_source_map()
-        {
-            let chunk_path = self.path().await?;
-            write!(
-                code,
-                "\n/*# sourceMappingURL={}.map*/",
-                chunk_path.file_name()
-            )?;
-        }
-
-        let c = code.build().cell();
-        Ok(c)
+        let chunk_path = self.path().await?;
+        append_source_map_url(
+            code.build().cell(),
+            &chunk_path,
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            this.chunking_context.source_map_privacy(),
+            |url| format!("\n/*# sourceMappingURL={url}*/"),
+        )
+        .await
     }
 }
 
@@ -112,11 +108,13 @@ impl OutputAsset for SingleItemCssChunk {
     async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
         let mut references = Vec::new();
-        if *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?
-        {
+        if matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        ) {
             references.push(Vc::upcast(SingleItemCssChunkSourceMapAsset::new(self)));
         }
         Ok(Vc::cell(references))
@@ -137,8 +135,11 @@ impl Asset for SingleItemCssChunk {
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for SingleItemCssChunk {
     #[turbo_tasks::function]
-    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
-        self.code().generate_source_map()
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(redact_source_map(
+            self.code().generate_source_map(),
+            self.await?.chunking_context.source_map_privacy(),
+        ))
     }
 }
 
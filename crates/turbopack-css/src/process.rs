@@ -434,7 +434,8 @@ pub async fn parse_css(
 ) -> Result<Vc<ParseCssResult>> {
     let span = {
         let name = source.ident().to_string().await?;
-        tracing::info_span!("parse css", name = *name)
+        let path = source.ident().path().to_string().await?;
+        tracing::info_span!("parse css", name = *name, path = *path)
     };
     async move {
         let content = source.content();
@@ -154,7 +154,9 @@ impl ImportAttributes {
                 supports: supports
                     .as_ref()
                     .map(|s| s.to_css_string(Default::default()).unwrap()),
-                media: Some(media.to_css_string(Default::default()).unwrap()),
+                // An empty media list applies to all media
+                media: Some(media.to_css_string(Default::default()).unwrap())
+                    .filter(|media| !media.is_empty()),
             },
             ImportAttributes::Swc {
                 layer_name,
@@ -163,9 +165,13 @@ impl ImportAttributes {
             } => turbopack_core::reference_type::ImportAttributes {
                 layer: layer_name.as_ref().map(gen_swc_node),
                 supports: supports.as_ref().map(gen_swc_node),
-                media: media
-                    .as_ref()
-                    .map(|queries| queries.iter().map(gen_swc_node).collect()),
+                media: media.as_ref().map(|queries| {
+                    queries
+                        .iter()
+                        .map(gen_swc_node)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }),
             },
         }
     }
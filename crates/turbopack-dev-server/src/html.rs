@@ -1,13 +1,13 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use mime_guess::mime::TEXT_HTML_UTF_8;
 use turbo_tasks::{ReadRef, TryJoinIterExt, Value, Vc};
-use turbo_tasks_fs::{File, FileSystemPath};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
 use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
-        availability_info::AvailabilityInfo, ChunkableModule, ChunkingContext, ChunkingContextExt,
-        EvaluatableAssets,
+        availability_info::AvailabilityInfo, warm_up::WarmUpEntry, ChunkableModule,
+        ChunkingContext, ChunkingContextExt, EvaluatableAssets, OutputChunk,
     },
     ident::AssetIdent,
     module::Module,
@@ -32,8 +32,24 @@ pub struct DevHtmlAsset {
     path: Vc<FileSystemPath>,
     entries: Vec<DevHtmlEntry>,
     body: Option<String>,
+    /// Inline the evaluate chunks of evaluated entries into the HTML instead of
+    /// referencing them, which saves a request when bootstrapping the page.
+    inline_evaluate_chunk: bool,
+    /// Also inline the chunk lists of evaluated entries.
+    inline_chunk_list: bool,
+    /// The URL of a script that is loaded before all chunks, e.g. the config
+    /// script served by a [BasePathsContentSource].
+    ///
+    /// [BasePathsContentSource]: crate::source::base_paths::BasePathsContentSource
+    config_script: Option<String>,
 }
 
+/// The output assets of the chunk group of each entry, whether that chunk
+/// group is evaluated, and whether it only contains static assets (see
+/// [turbopack_core::chunk::ChunkGroupResult::static_only]).
+#[turbo_tasks::value(transparent)]
+struct DevHtmlChunkGroups(Vec<(Vc<OutputAssets>, bool, bool)>);
+
 #[turbo_tasks::function]
 fn dev_html_chunk_reference_description() -> Vc<String> {
     Vc::cell("dev html chunk".to_string())
@@ -72,6 +88,9 @@ impl DevHtmlAsset {
             path,
             entries,
             body: None,
+            inline_evaluate_chunk: false,
+            inline_chunk_list: false,
+            config_script: None,
         }
         .cell()
     }
@@ -86,9 +105,37 @@ impl DevHtmlAsset {
             path,
             entries,
             body: Some(body),
+            inline_evaluate_chunk: false,
+            inline_chunk_list: false,
+            config_script: None,
         }
         .cell()
     }
+
+    /// The entries of the page with their chunking contexts, whose chunk
+    /// groups can be computed before the page is requested with
+    /// [warm_up](turbopack_core::chunk::warm_up::warm_up).
+    pub async fn warm_up_entries(
+        self: Vc<Self>,
+    ) -> Result<Vec<(Vc<Box<dyn ChunkingContext>>, WarmUpEntry)>> {
+        self.await?
+            .entries
+            .iter()
+            .map(|entry| async move {
+                let &(chunkable_module, chunking_context, runtime_entries) = entry;
+                let entry = match runtime_entries {
+                    Some(runtime_entries) => WarmUpEntry::Evaluated {
+                        ident: chunkable_module.ident(),
+                        evaluatable_assets: evaluatable_assets(chunkable_module, runtime_entries)
+                            .await?,
+                    },
+                    None => WarmUpEntry::Module(chunkable_module),
+                };
+                Ok((chunking_context, entry))
+            })
+            .try_join()
+            .await
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -106,6 +153,28 @@ impl DevHtmlAsset {
         html.body = Some(body);
         Ok(html.cell())
     }
+
+    /// Inlines the evaluate chunks (and when `inline_chunk_list` is set, the
+    /// chunk lists) of evaluated entries into the HTML, so bootstrapping the
+    /// page doesn't require additional requests for them.
+    #[turbo_tasks::function]
+    pub async fn with_inlined_evaluate_chunk(
+        self: Vc<Self>,
+        inline_chunk_list: bool,
+    ) -> Result<Vc<Self>> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.inline_evaluate_chunk = true;
+        html.inline_chunk_list = inline_chunk_list;
+        Ok(html.cell())
+    }
+
+    /// Loads the script at `url` before all chunks of the page.
+    #[turbo_tasks::function]
+    pub async fn with_config_script(self: Vc<Self>, url: String) -> Result<Vc<Self>> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.config_script = Some(url);
+        Ok(html.cell())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -114,66 +183,165 @@ impl DevHtmlAsset {
     async fn html_content(self: Vc<Self>) -> Result<Vc<DevHtmlAssetContent>> {
         let this = self.await?;
         let context_path = this.path.parent().await?;
-        let mut chunk_paths = vec![];
-        for chunk in &*self.chunks().await? {
-            let chunk_path = &*chunk.ident().path().await?;
-            if let Some(relative_path) = context_path.get_path_to(chunk_path) {
-                chunk_paths.push(format!("/{relative_path}"));
+        let mut chunks = vec![];
+        let chunk_groups = self.chunk_groups().await?;
+        for (&(assets, evaluated, _), &(_, chunking_context, _)) in
+            chunk_groups.iter().zip(this.entries.iter())
+        {
+            let esm = *chunking_context.esm_chunks().await?;
+            let assets = assets.await?;
+            for (index, &chunk) in assets.iter().enumerate() {
+                let chunk_path = &*chunk.ident().path().await?;
+                let Some(relative_path) = context_path.get_path_to(chunk_path) else {
+                    continue;
+                };
+                let url = format!("/{relative_path}");
+                // The bootstrap assets of an evaluated chunk group (its chunk list and
+                // evaluate chunk) are the only scripts that aren't output chunks. The
+                // evaluate chunk always comes last.
+                let inline = evaluated
+                    && (this.inline_evaluate_chunk || this.inline_chunk_list)
+                    && relative_path.ends_with(".js")
+                    && Vc::try_resolve_sidecast::<Box<dyn OutputChunk>>(chunk)
+                        .await?
+                        .is_none()
+                    && if index == assets.len() - 1 {
+                        this.inline_evaluate_chunk
+                    } else {
+                        this.inline_chunk_list
+                    };
+                let code = if inline {
+                    Some(inline_script(chunk, &url).await?)
+                } else {
+                    None
+                };
+                chunks.push((url, code, esm));
             }
         }
 
-        Ok(DevHtmlAssetContent::new(chunk_paths, this.body.clone()))
+        // Pages without JavaScript don't need the base paths of chunks.
+        let config_script = if chunk_groups.iter().all(|&(_, _, static_only)| static_only) {
+            None
+        } else {
+            this.config_script.clone()
+        };
+
+        Ok(DevHtmlAssetContent::new(
+            chunks,
+            this.body.clone(),
+            config_script,
+        ))
     }
 
     #[turbo_tasks::function]
     async fn chunks(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let mut all_assets = vec![];
+        for (assets, ..) in &*self.chunk_groups().await? {
+            all_assets.extend(assets.await?.iter().copied());
+        }
+        Ok(Vc::cell(all_assets))
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_groups(self: Vc<Self>) -> Result<Vc<DevHtmlChunkGroups>> {
         let this = self.await?;
 
-        let all_assets = this
+        let chunk_groups = this
             .entries
             .iter()
             .map(|entry| async move {
                 let &(chunkable_module, chunking_context, runtime_entries) = entry;
 
-                let assets = if let Some(runtime_entries) = runtime_entries {
-                    let runtime_entries = if let Some(evaluatable) =
-                        Vc::try_resolve_downcast(chunkable_module).await?
-                    {
-                        runtime_entries.with_entry(evaluatable)
-                    } else {
-                        runtime_entries
-                    };
-                    chunking_context.evaluated_chunk_group_assets(
-                        chunkable_module.ident(),
-                        runtime_entries,
-                        Value::new(AvailabilityInfo::Root),
+                Ok(if let Some(runtime_entries) = runtime_entries {
+                    let runtime_entries =
+                        evaluatable_assets(chunkable_module, runtime_entries).await?;
+                    let chunk_group = chunking_context
+                        .evaluated_chunk_group(
+                            chunkable_module.ident(),
+                            runtime_entries,
+                            Value::new(AvailabilityInfo::Root),
+                        )
+                        .await?;
+                    // A static chunk group has no bootstrap assets.
+                    (
+                        chunk_group.assets,
+                        !chunk_group.static_only,
+                        chunk_group.static_only,
                     )
                 } else {
-                    chunking_context.root_chunk_group_assets(Vc::upcast(chunkable_module))
-                };
-
-                assets.await
+                    let assets =
+                        chunking_context.root_chunk_group_assets(Vc::upcast(chunkable_module));
+                    (assets, false, false)
+                })
             })
             .try_join()
-            .await?
-            .iter()
-            .flatten()
-            .copied()
-            .collect();
+            .await?;
 
-        Ok(Vc::cell(all_assets))
+        Ok(Vc::cell(chunk_groups))
     }
 }
 
+/// The assets evaluated by the chunk group of an entry of the page, which
+/// include the entry itself when it can be evaluated.
+async fn evaluatable_assets(
+    chunkable_module: Vc<Box<dyn ChunkableModule>>,
+    runtime_entries: Vc<EvaluatableAssets>,
+) -> Result<Vc<EvaluatableAssets>> {
+    Ok(
+        if let Some(evaluatable) = Vc::try_resolve_downcast(chunkable_module).await? {
+            runtime_entries.with_entry(evaluatable)
+        } else {
+            runtime_entries
+        },
+    )
+}
+
+/// Returns the code of `chunk` in a form that can be inlined into a `<script>`
+/// tag of an HTML page, where `url` is the URL the chunk would otherwise be
+/// loaded from.
+async fn inline_script(chunk: Vc<Box<dyn OutputAsset>>, url: &str) -> Result<String> {
+    let AssetContent::File(file) = &*chunk.content().await? else {
+        bail!("chunk {url} can't be inlined as it is not a file");
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        bail!("chunk {url} can't be inlined as it doesn't exist");
+    };
+    let code = file.content().to_str()?;
+    let mut code = match code.rfind("\n//# sourceMappingURL=") {
+        // Inline source maps keep working when the chunk is inlined.
+        Some(index) if code[index..].starts_with("\n//# sourceMappingURL=data:") => {
+            code.into_owned()
+        }
+        // The source map is referenced relative to the chunk, which doesn't work
+        // anymore when it's inlined.
+        Some(index) => format!("{}\n//# sourceMappingURL={url}.map", &code[..index]),
+        None => code.into_owned(),
+    };
+    code.push_str(&format!("\n//# sourceURL={url}"));
+    Ok(code.replace("</script", "<\\/script"))
+}
+
 #[turbo_tasks::value]
 struct DevHtmlAssetContent {
-    chunk_paths: Vec<String>,
+    /// The URLs of the chunks of the page, with the code of the chunks that
+    /// are inlined, and whether they are loaded as ES modules.
+    chunks: Vec<(String, Option<String>, bool)>,
     body: Option<String>,
+    config_script: Option<String>,
 }
 
 impl DevHtmlAssetContent {
-    fn new(chunk_paths: Vec<String>, body: Option<String>) -> Vc<Self> {
-        DevHtmlAssetContent { chunk_paths, body }.cell()
+    fn new(
+        chunks: Vec<(String, Option<String>, bool)>,
+        body: Option<String>,
+        config_script: Option<String>,
+    ) -> Vc<Self> {
+        DevHtmlAssetContent {
+            chunks,
+            body,
+            config_script,
+        }
+        .cell()
     }
 }
 
@@ -186,9 +354,23 @@ impl DevHtmlAssetContent {
         let mut scripts = Vec::new();
         let mut stylesheets = Vec::new();
 
-        for relative_path in &*this.chunk_paths {
-            if relative_path.ends_with(".js") {
-                scripts.push(format!("<script src=\"{}\"></script>", relative_path));
+        if let Some(config_script) = &this.config_script {
+            scripts.push(format!("<script src=\"{}\"></script>", config_script));
+        }
+
+        for (relative_path, code, esm) in &*this.chunks {
+            // Module scripts are deferred, but still evaluated in order.
+            let script_type = if *esm { " type=\"module\"" } else { "" };
+            if let Some(code) = code {
+                scripts.push(format!(
+                    "<script{} data-turbopack-src=\"{}\">\n{}\n</script>",
+                    script_type, relative_path, code
+                ));
+            } else if relative_path.ends_with(".js") {
+                scripts.push(format!(
+                    "<script{} src=\"{}\"></script>",
+                    script_type, relative_path
+                ));
             } else if relative_path.ends_with(".css") {
                 stylesheets.push(format!(
                     "<link data-turbopack rel=\"stylesheet\" href=\"{}\">",
@@ -246,12 +428,15 @@ impl Version for DevHtmlAssetVersion {
     #[turbo_tasks::function]
     async fn id(&self) -> Result<Vc<String>> {
         let mut hasher = Xxh3Hash64Hasher::new();
-        for relative_path in &*self.content.chunk_paths {
+        for (relative_path, code, esm) in &*self.content.chunks {
             hasher.write_ref(relative_path);
+            hasher.write_ref(code);
+            hasher.write_ref(esm);
         }
         if let Some(body) = &self.content.body {
             hasher.write_ref(body);
         }
+        hasher.write_ref(&self.content.config_script);
         let hash = hasher.finish();
         let hex_hash = encode_hex(hash);
         Ok(Vc::cell(hex_hash))
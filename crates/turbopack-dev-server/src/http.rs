@@ -4,7 +4,7 @@ use anyhow::{anyhow, Result};
 use auto_hash_map::AutoSet;
 use futures::{StreamExt, TryStreamExt};
 use hyper::{
-    header::{HeaderName, CONTENT_ENCODING, CONTENT_LENGTH},
+    header::{HeaderName, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE},
     http::HeaderValue,
     Request, Response,
 };
@@ -20,14 +20,17 @@ use turbopack_core::{
     version::VersionedContent,
 };
 
-use crate::source::{
-    request::SourceRequest,
-    resolve::{resolve_source_request, ResolveSourceRequestResult},
-    Body, ContentSource, ContentSourceSideEffect, HeaderList, ProxyResult,
+use crate::{
+    source::{
+        request::SourceRequest,
+        resolve::{resolve_source_request, ResolveSourceRequestResult},
+        Body, ContentSource, ContentSourceSideEffect, HeaderList, ProxyResult,
+    },
+    stale::{build_id_from_uri, StaleContentCache},
 };
 
 #[turbo_tasks::value(serialization = "none")]
-enum GetFromSourceResult {
+pub(crate) enum GetFromSourceResult {
     Static {
         content: ReadRef<FileContent>,
         status_code: u16,
@@ -41,7 +44,7 @@ enum GetFromSourceResult {
 /// Resolves a [SourceRequest] within a [super::ContentSource], returning the
 /// corresponding content as a
 #[turbo_tasks::function]
-async fn get_from_source(
+pub(crate) async fn get_from_source(
     source: Vc<Box<dyn ContentSource>>,
     request: TransientInstance<SourceRequest>,
 ) -> Result<Vc<GetFromSourceResult>> {
@@ -69,15 +72,20 @@ async fn get_from_source(
 
 /// Processes an HTTP request within a given content source and returns the
 /// response.
+///
+/// Content that is no longer available from the content source is served from
+/// `stale_content` if it was served for a previous build.
 pub async fn process_request_with_content_source(
     source: Vc<Box<dyn ContentSource>>,
     request: Request<hyper::Body>,
     issue_reporter: Vc<Box<dyn IssueReporter>>,
+    stale_content: &StaleContentCache,
 ) -> Result<(
     Response<hyper::Body>,
     AutoSet<Vc<Box<dyn ContentSourceSideEffect>>>,
 )> {
     let original_path = request.uri().path().to_string();
+    let build_id = build_id_from_uri(request.uri());
     let request = http_request_to_source_request(request).await?;
     let result = get_from_source(source, TransientInstance::new(request));
     let resolved_result = result.resolve_strongly_consistent().await?;
@@ -90,7 +98,15 @@ pub async fn process_request_with_content_source(
         Some("get_from_source"),
     )
     .await?;
-    match &*resolved_result.await? {
+    let mut resolved_result = resolved_result.await?;
+    if let GetFromSourceResult::NotFound = &*resolved_result {
+        if let Some(stale) = stale_content.get(build_id.as_deref(), &original_path) {
+            resolved_result = stale;
+        }
+    } else if let Some(build_id) = &build_id {
+        stale_content.record(build_id, &original_path, resolved_result.clone());
+    }
+    match &*resolved_result {
         GetFromSourceResult::Static {
             content,
             status_code,
@@ -132,7 +148,15 @@ pub async fn process_request_with_content_source(
                     )
                 };
 
-                if let Some(content_type) = file.content_type() {
+                if let Some(content_type) = header_map
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<Mime>().ok())
+                {
+                    // The content type was overridden, e.g. by the serving hints of an
+                    // output asset.
+                    should_compress = should_compress_predicate(&content_type);
+                } else if let Some(content_type) = file.content_type() {
                     header_map.append(
                         "content-type",
                         hyper::header::HeaderValue::try_from(content_type.to_string())?,
@@ -9,9 +9,14 @@ pub mod html;
 mod http;
 pub mod introspect;
 mod invalidation;
+mod precompile;
 pub mod source;
+pub mod source_map_access;
+mod stale;
 pub mod update;
 
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
 use std::{
     collections::VecDeque,
     future::Future,
@@ -39,10 +44,23 @@ use turbopack_core::{
     issue::{handle_issues, IssueReporter, IssueSeverity},
 };
 
-use self::{source::ContentSource, update::UpdateServer};
+use self::{
+    source::ContentSource,
+    update::{
+        long_polling::{LongPollingSessions, LONG_POLLING_PATH_PREFIX},
+        transport::{
+            update_channel, UpdateChannelClient, UpdateChannelConnection, UpdateConnection,
+            WebSocketConnection,
+        },
+        UpdateServer,
+    },
+};
 use crate::{
     invalidation::{ServerRequest, ServerRequestSideEffects},
+    precompile::Precompiler,
     source::ContentSourceSideEffect,
+    source_map_access::{SourceMapAccess, SourceMapAccessPolicy},
+    stale::StaleContentCache,
 };
 
 pub trait SourceProvider: Send + Clone + 'static {
@@ -65,6 +83,9 @@ pub struct DevServerBuilder {
     pub addr: SocketAddr,
     #[turbo_tasks(trace_ignore)]
     server: Builder<AddrIncoming>,
+    handler: DevServerHandlerBuilder,
+    #[cfg(unix)]
+    update_socket: Option<PathBuf>,
 }
 
 #[derive(TraceRawVcs)]
@@ -73,9 +94,27 @@ pub struct DevServer {
     pub addr: SocketAddr,
     #[turbo_tasks(trace_ignore)]
     pub future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
+    #[turbo_tasks(trace_ignore)]
+    precompiler: Arc<Precompiler>,
+    #[turbo_tasks(trace_ignore)]
+    connect_update_client: Arc<dyn Fn(UpdateChannelConnection) + Send + Sync>,
 }
 
 impl DevServer {
+    /// Hints that `paths` (e.g. routes linked from the current page) are
+    /// likely to be requested soon. Their content is precompiled while the
+    /// server is idle.
+    pub fn precompile(&self, paths: impl IntoIterator<Item = String>) {
+        self.precompiler.hint(paths);
+    }
+
+    /// See [DevServerHandler::update_channel].
+    pub fn update_channel(&self) -> UpdateChannelClient {
+        let (connection, client) = update_channel();
+        (self.connect_update_client)(connection);
+        client
+    }
+
     pub fn listen(addr: SocketAddr) -> Result<DevServerBuilder, anyhow::Error> {
         // This is annoying. The hyper::Server doesn't allow us to know which port was
         // bound (until we build it with a request handler) when using the standard
@@ -101,180 +140,84 @@ impl DevServer {
             .context("not able to bind address")?;
         socket.listen(128).context("not able to listen on socket")?;
 
-        let listener: TcpListener = socket.into();
+        Self::from_listener(socket.into())
+    }
+
+    /// Serves on a listener that was bound by someone else, e.g. a socket
+    /// passed by a parent process or by systemd socket activation, which owns
+    /// the socket and its lifecycle.
+    pub fn from_listener(listener: TcpListener) -> Result<DevServerBuilder, anyhow::Error> {
         let addr = listener
             .local_addr()
             .context("not able to get bound address")?;
+        listener
+            .set_nonblocking(true)
+            .context("not able to make the listener non-blocking")?;
         let server = Server::from_tcp(listener).context("Not able to start server")?;
-        Ok(DevServerBuilder { addr, server })
+        Ok(DevServerBuilder {
+            addr,
+            server,
+            handler: DevServerHandlerBuilder::default(),
+            #[cfg(unix)]
+            update_socket: None,
+        })
     }
 }
 
 impl DevServerBuilder {
-    pub fn serve(
+    /// Keeps the content served for the last `builds` builds addressable, so
+    /// clients still running a previous build (as identified by the build id
+    /// the runtime sends along with chunk requests) can load chunks that were
+    /// replaced in the meantime. Disabled by default.
+    pub fn retain_stale_builds(mut self, builds: usize) -> Self {
+        self.handler = self.handler.retain_stale_builds(builds);
+        self
+    }
+
+    /// Only serves source maps to requests allowed by `policy`, e.g. requests
+    /// with a specific `authorization` header. Other requests for source maps
+    /// are answered with a 403. Chunks still reference their source maps.
+    pub fn source_map_access(mut self, policy: impl SourceMapAccessPolicy) -> Self {
+        self.handler = self.handler.source_map_access(policy);
+        self
+    }
+
+    /// Also serves hot updates on a Unix domain socket at `path`, see
+    /// [DevServerHandler::serve_updates_on_unix_socket].
+    #[cfg(unix)]
+    pub fn update_socket(mut self, path: PathBuf) -> Self {
+        self.update_socket = Some(path);
+        self
+    }
+
+    pub fn serve<P: SourceProvider + Sync>(
         self,
         turbo_tasks: Arc<dyn TurboTasksApi>,
-        source_provider: impl SourceProvider + Clone + Send + Sync,
+        source_provider: P,
         get_issue_reporter: Arc<dyn Fn() -> Vc<Box<dyn IssueReporter>> + Send + Sync>,
     ) -> DevServer {
-        let ongoing_side_effects = Arc::new(Mutex::new(VecDeque::<
-            Arc<tokio::sync::Mutex<Option<JoinHandle<Result<()>>>>>,
-        >::with_capacity(16)));
+        let handler = self
+            .handler
+            .build(turbo_tasks, source_provider, get_issue_reporter);
+        let precompiler = handler.precompiler.clone();
+        let connect_update_client = {
+            let handler = handler.clone();
+            Arc::new(move |connection: UpdateChannelConnection| {
+                handler.connect_update_client(connection)
+            })
+        };
+        #[cfg(unix)]
+        let update_socket = self.update_socket.map(|path| {
+            let handler = handler.clone();
+            async move { handler.serve_updates_on_unix_socket(&path)?.await }
+        });
         let make_svc = make_service_fn(move |_| {
-            let tt = turbo_tasks.clone();
-            let source_provider = source_provider.clone();
-            let get_issue_reporter = get_issue_reporter.clone();
-            let ongoing_side_effects = ongoing_side_effects.clone();
+            let handler = handler.clone();
             async move {
-                let handler = move |request: Request<hyper::Body>| {
-                    let request_span = info_span!(parent: None, "request", name = ?request.uri());
-                    let start = Instant::now();
-                    let tt = tt.clone();
-                    let get_issue_reporter = get_issue_reporter.clone();
-                    let ongoing_side_effects = ongoing_side_effects.clone();
-                    let source_provider = source_provider.clone();
-                    let future = async move {
-                        event!(parent: Span::current(), Level::DEBUG, "request start");
-                        // Wait until all ongoing side effects are completed
-                        // We only need to wait for the ongoing side effects that were started
-                        // before this request. Later added side effects are not relevant for this.
-                        let current_ongoing_side_effects = {
-                            // Cleanup the ongoing_side_effects list
-                            let mut guard = ongoing_side_effects.lock();
-                            while let Some(front) = guard.front() {
-                                let Ok(front_guard) = front.try_lock() else {
-                                    break;
-                                };
-                                if front_guard.is_some() {
-                                    break;
-                                }
-                                drop(front_guard);
-                                guard.pop_front();
-                            }
-                            // Get a clone of the remaining list
-                            (*guard).clone()
-                        };
-                        // Wait for the side effects to complete
-                        for side_effect_mutex in current_ongoing_side_effects {
-                            let mut guard = side_effect_mutex.lock().await;
-                            if let Some(join_handle) = guard.take() {
-                                join_handle.await??;
-                            }
-                            drop(guard);
-                        }
-                        let reason = ServerRequest {
-                            method: request.method().clone(),
-                            uri: request.uri().clone(),
-                        };
-                        let side_effects_reason = ServerRequestSideEffects {
-                            method: request.method().clone(),
-                            uri: request.uri().clone(),
-                        };
-                        run_once_with_reason(tt.clone(), reason, async move {
-                            let issue_reporter = get_issue_reporter();
-
-                            if hyper_tungstenite::is_upgrade_request(&request) {
-                                let uri = request.uri();
-                                let path = uri.path();
-
-                                if path == "/turbopack-hmr" {
-                                    let (response, websocket) =
-                                        hyper_tungstenite::upgrade(request, None)?;
-                                    let update_server =
-                                        UpdateServer::new(source_provider, issue_reporter);
-                                    update_server.run(&*tt, websocket);
-                                    return Ok(response);
-                                }
-
-                                println!("[404] {} (WebSocket)", path);
-                                if path == "/_next/webpack-hmr" {
-                                    // Special-case requests to webpack-hmr as these are made by
-                                    // Next.js clients built
-                                    // without turbopack, which may be making requests in
-                                    // development.
-                                    println!(
-                                        "A non-turbopack next.js client is trying to connect."
-                                    );
-                                    println!(
-                                        "Make sure to reload/close any browser window which has \
-                                         been opened without --turbo."
-                                    );
-                                }
-
-                                return Ok(Response::builder()
-                                    .status(404)
-                                    .body(hyper::Body::empty())?);
-                            }
-
-                            let uri = request.uri();
-                            let path = uri.path().to_string();
-                            let source = source_provider.get_source();
-                            let resolved_source = source.resolve_strongly_consistent().await?;
-                            handle_issues(
-                                source,
-                                issue_reporter,
-                                IssueSeverity::Fatal.cell(),
-                                Some(&path),
-                                Some("get source"),
-                            )
-                            .await?;
-                            let (response, side_effects) =
-                                http::process_request_with_content_source(
-                                    resolved_source,
-                                    request,
-                                    issue_reporter,
-                                )
-                                .await?;
-                            let status = response.status().as_u16();
-                            let is_error = response.status().is_client_error()
-                                || response.status().is_server_error();
-                            let elapsed = start.elapsed();
-                            if is_error
-                                || (cfg!(feature = "log_request_stats")
-                                    && elapsed > Duration::from_secs(1))
-                            {
-                                println!(
-                                    "[{status}] {path} ({duration})",
-                                    duration = FormatDuration(elapsed)
-                                );
-                            }
-                            if !side_effects.is_empty() {
-                                let join_handle = tokio::spawn(run_once_with_reason(
-                                    tt.clone(),
-                                    side_effects_reason,
-                                    async move {
-                                        for side_effect in side_effects {
-                                            side_effect.apply().await?;
-                                        }
-                                        Ok(())
-                                    },
-                                ));
-                                ongoing_side_effects.lock().push_back(Arc::new(
-                                    tokio::sync::Mutex::new(Some(join_handle)),
-                                ));
-                            }
-                            Ok(response)
-                        })
-                        .await
-                    };
-                    async move {
-                        match future.await {
-                            Ok(r) => Ok::<_, hyper::http::Error>(r),
-                            Err(e) => {
-                                println!(
-                                    "[500] error ({}): {}",
-                                    FormatDuration(start.elapsed()),
-                                    PrettyPrintError(&e),
-                                );
-                                Ok(Response::builder()
-                                    .status(500)
-                                    .body(hyper::Body::from(format!("{}", PrettyPrintError(&e))))?)
-                            }
-                        }
-                    }
-                    .instrument(request_span)
-                };
-                anyhow::Ok(service_fn(handler))
+                anyhow::Ok(service_fn(move |request| {
+                    let handler = handler.clone();
+                    async move { handler.handle(request).await }
+                }))
             }
         });
         let server = self.server.serve(make_svc);
@@ -282,9 +225,378 @@ impl DevServerBuilder {
         DevServer {
             addr: self.addr,
             future: Box::pin(async move {
+                #[cfg(unix)]
+                if let Some(update_socket) = update_socket {
+                    tokio::select! {
+                        result = server => result?,
+                        result = update_socket => result?,
+                    }
+                    return Ok(());
+                }
                 server.await?;
                 Ok(())
             }),
+            precompiler,
+            connect_update_client,
+        }
+    }
+}
+
+/// Builds a [DevServerHandler], for embedding the dev server into a server of
+/// a parent process. Use [DevServerHandlerBuilder::default] to create one.
+#[derive(TraceRawVcs, Debug, Default)]
+pub struct DevServerHandlerBuilder {
+    stale_builds: usize,
+    #[turbo_tasks(trace_ignore)]
+    source_map_access: Option<SourceMapAccess>,
+}
+
+impl DevServerHandlerBuilder {
+    /// See [DevServerBuilder::retain_stale_builds].
+    pub fn retain_stale_builds(mut self, builds: usize) -> Self {
+        self.stale_builds = builds;
+        self
+    }
+
+    /// See [DevServerBuilder::source_map_access].
+    pub fn source_map_access(mut self, policy: impl SourceMapAccessPolicy) -> Self {
+        self.source_map_access = Some(SourceMapAccess::new(policy));
+        self
+    }
+
+    pub fn build<P: SourceProvider + Sync>(
+        self,
+        turbo_tasks: Arc<dyn TurboTasksApi>,
+        source_provider: P,
+        get_issue_reporter: Arc<dyn Fn() -> Vc<Box<dyn IssueReporter>> + Send + Sync>,
+    ) -> DevServerHandler<P> {
+        let precompiler = Arc::new(Precompiler::new(turbo_tasks.clone(), {
+            let source_provider = source_provider.clone();
+            Arc::new(move || source_provider.get_source())
+        }));
+        DevServerHandler {
+            turbo_tasks,
+            source_provider,
+            get_issue_reporter,
+            ongoing_side_effects: Arc::new(Mutex::new(VecDeque::with_capacity(16))),
+            stale_content: Arc::new(StaleContentCache::new(self.stale_builds)),
+            source_map_access: self.source_map_access,
+            precompiler,
+            long_polling: Default::default(),
+        }
+    }
+}
+
+type OngoingSideEffects = Mutex<VecDeque<Arc<tokio::sync::Mutex<Option<JoinHandle<Result<()>>>>>>>;
+
+/// Handles the requests of a dev server: serves the content source, including
+/// chunks, and the HMR WebSocket at `/turbopack-hmr`, with a long-polling
+/// fallback for networks that block WebSockets (see [update::long_polling]).
+/// Hot updates can also be served over other transports, see
+/// [update::transport].
+///
+/// A [DevServer] creates one for its own socket. It can also be embedded into
+/// a hyper server of a parent process (e.g. a framework CLI or an IDE plugin)
+/// that owns the socket and the lifecycle of the server, by passing requests
+/// to [DevServerHandler::handle].
+#[derive(Clone)]
+pub struct DevServerHandler<P: SourceProvider> {
+    turbo_tasks: Arc<dyn TurboTasksApi>,
+    source_provider: P,
+    get_issue_reporter: Arc<dyn Fn() -> Vc<Box<dyn IssueReporter>> + Send + Sync>,
+    ongoing_side_effects: Arc<OngoingSideEffects>,
+    stale_content: Arc<StaleContentCache>,
+    source_map_access: Option<SourceMapAccess>,
+    precompiler: Arc<Precompiler>,
+    long_polling: Arc<LongPollingSessions>,
+}
+
+impl<P: SourceProvider + Sync> DevServerHandler<P> {
+    /// See [DevServer::precompile].
+    pub fn precompile(&self, paths: impl IntoIterator<Item = String>) {
+        self.precompiler.hint(paths);
+    }
+
+    /// Serves hot updates to the client connected by `connection` instead of
+    /// the `/turbopack-hmr` WebSocket, until it disconnects. The client is
+    /// trusted to receive source maps, as it's connected by the embedder.
+    pub fn connect_update_client(&self, connection: impl UpdateConnection) {
+        self.connect_update_client_with_access(connection, true)
+    }
+
+    fn connect_update_client_with_access(
+        &self,
+        connection: impl UpdateConnection,
+        source_maps_allowed: bool,
+    ) {
+        let DevServerHandler {
+            turbo_tasks: tt,
+            source_provider,
+            get_issue_reporter,
+            precompiler,
+            ..
+        } = self.clone();
+        self.turbo_tasks.run_once_process(Box::pin(async move {
+            let update_server = UpdateServer::new(
+                source_provider,
+                get_issue_reporter(),
+                precompiler,
+                source_maps_allowed,
+            );
+            update_server.run(&*tt, async move { Ok(connection) });
+            Ok(())
+        }));
+    }
+
+    /// Connects an in-process update client, e.g. for an embedded WebView that
+    /// forwards the messages to its page, or for a test harness.
+    pub fn update_channel(&self) -> UpdateChannelClient {
+        let (connection, client) = update_channel();
+        self.connect_update_client(connection);
+        client
+    }
+
+    /// Binds a Unix domain socket at `path` and returns a future that serves
+    /// hot updates to every client connecting to it. The messages are
+    /// delimited by newlines. A socket file left over by a previous server is
+    /// replaced, and the socket file is removed when the future is dropped.
+    #[cfg(unix)]
+    pub fn serve_updates_on_unix_socket(
+        &self,
+        path: &Path,
+    ) -> Result<impl Future<Output = Result<()>> + Send + 'static> {
+        /// Removes the socket file when dropped.
+        struct SocketFile(PathBuf);
+
+        impl Drop for SocketFile {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_file(&self.0);
+            }
+        }
+
+        if path.exists() {
+            // Nobody listens on a socket left over by a server that exited.
+            match std::os::unix::net::UnixStream::connect(path) {
+                Ok(_) => anyhow::bail!("{} is used by another dev server", path.display()),
+                Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused => {
+                    std::fs::remove_file(path)
+                        .with_context(|| format!("not able to remove {}", path.display()))?;
+                }
+                Err(_) => {}
+            }
+        }
+        let listener = tokio::net::UnixListener::bind(path)
+            .with_context(|| format!("not able to bind {}", path.display()))?;
+        let socket_file = SocketFile(path.to_path_buf());
+        let handler = self.clone();
+        Ok(async move {
+            let _socket_file = socket_file;
+            update::transport::accept_unix_socket_clients(listener, |connection| {
+                handler.connect_update_client(connection)
+            })
+            .await
+        })
+    }
+
+    /// Handles a single request. Errors are answered with a 500.
+    pub async fn handle(
+        &self,
+        request: Request<hyper::Body>,
+    ) -> Result<Response<hyper::Body>, hyper::http::Error> {
+        if request.uri().path().starts_with(LONG_POLLING_PATH_PREFIX) {
+            return self.handle_long_polling(request).await;
+        }
+        let request_span = info_span!(parent: None, "request", name = ?request.uri());
+        let start = Instant::now();
+        let DevServerHandler {
+            turbo_tasks: tt,
+            source_provider,
+            get_issue_reporter,
+            ongoing_side_effects,
+            stale_content,
+            source_map_access,
+            precompiler,
+            long_polling: _,
+        } = self.clone();
+        let future = async move {
+            let _active_request = precompiler.request_started();
+            event!(parent: Span::current(), Level::DEBUG, "request start");
+            // Wait until all ongoing side effects are completed
+            // We only need to wait for the ongoing side effects that were started
+            // before this request. Later added side effects are not relevant for this.
+            let current_ongoing_side_effects = {
+                // Cleanup the ongoing_side_effects list
+                let mut guard = ongoing_side_effects.lock();
+                while let Some(front) = guard.front() {
+                    let Ok(front_guard) = front.try_lock() else {
+                        break;
+                    };
+                    if front_guard.is_some() {
+                        break;
+                    }
+                    drop(front_guard);
+                    guard.pop_front();
+                }
+                // Get a clone of the remaining list
+                (*guard).clone()
+            };
+            // Wait for the side effects to complete
+            for side_effect_mutex in current_ongoing_side_effects {
+                let mut guard = side_effect_mutex.lock().await;
+                if let Some(join_handle) = guard.take() {
+                    join_handle.await??;
+                }
+                drop(guard);
+            }
+            let reason = ServerRequest {
+                method: request.method().clone(),
+                uri: request.uri().clone(),
+            };
+            let side_effects_reason = ServerRequestSideEffects {
+                method: request.method().clone(),
+                uri: request.uri().clone(),
+            };
+            let update_precompiler = precompiler.clone();
+            run_once_with_reason(tt.clone(), reason, async move {
+                let issue_reporter = get_issue_reporter();
+
+                if hyper_tungstenite::is_upgrade_request(&request) {
+                    let uri = request.uri();
+                    let path = uri.path();
+
+                    if path == "/turbopack-hmr" {
+                        let source_maps_allowed = source_map_access
+                            .as_ref()
+                            .map_or(true, |access| access.allows_source_maps(&request));
+                        let (response, websocket) = hyper_tungstenite::upgrade(request, None)?;
+                        let update_server = UpdateServer::new(
+                            source_provider,
+                            issue_reporter,
+                            update_precompiler,
+                            source_maps_allowed,
+                        );
+                        update_server.run(&*tt, async move {
+                            Ok(WebSocketConnection::from(websocket.await?))
+                        });
+                        return Ok(response);
+                    }
+
+                    println!("[404] {} (WebSocket)", path);
+                    if path == "/_next/webpack-hmr" {
+                        // Special-case requests to webpack-hmr as these are made by
+                        // Next.js clients built
+                        // without turbopack, which may be making requests in
+                        // development.
+                        println!("A non-turbopack next.js client is trying to connect.");
+                        println!(
+                            "Make sure to reload/close any browser window which has been opened \
+                             without --turbo."
+                        );
+                    }
+
+                    return Ok(Response::builder().status(404).body(hyper::Body::empty())?);
+                }
+
+                let uri = request.uri();
+                let path = uri.path().to_string();
+                if let Some(source_map_access) = &source_map_access {
+                    if !source_map_access.is_allowed(&request) {
+                        println!("[403] {path} (source map access denied)");
+                        return Ok(Response::builder().status(403).body(hyper::Body::empty())?);
+                    }
+                }
+                let source = source_provider.get_source();
+                let resolved_source = source.resolve_strongly_consistent().await?;
+                handle_issues(
+                    source,
+                    issue_reporter,
+                    IssueSeverity::Fatal.cell(),
+                    Some(&path),
+                    Some("get source"),
+                )
+                .await?;
+                let (response, side_effects) = http::process_request_with_content_source(
+                    resolved_source,
+                    request,
+                    issue_reporter,
+                    &stale_content,
+                )
+                .await?;
+                let status = response.status().as_u16();
+                let is_error =
+                    response.status().is_client_error() || response.status().is_server_error();
+                let elapsed = start.elapsed();
+                if is_error
+                    || (cfg!(feature = "log_request_stats") && elapsed > Duration::from_secs(1))
+                {
+                    println!(
+                        "[{status}] {path} ({duration})",
+                        duration = FormatDuration(elapsed)
+                    );
+                }
+                if !side_effects.is_empty() {
+                    let join_handle = tokio::spawn(run_once_with_reason(
+                        tt.clone(),
+                        side_effects_reason,
+                        async move {
+                            for side_effect in side_effects {
+                                side_effect.apply().await?;
+                            }
+                            Ok(())
+                        },
+                    ));
+                    ongoing_side_effects
+                        .lock()
+                        .push_back(Arc::new(tokio::sync::Mutex::new(Some(join_handle))));
+                }
+                Ok(response)
+            })
+            .await
+        };
+        async move {
+            match future.await {
+                Ok(r) => Ok::<_, hyper::http::Error>(r),
+                Err(e) => {
+                    println!(
+                        "[500] error ({}): {}",
+                        FormatDuration(start.elapsed()),
+                        PrettyPrintError(&e),
+                    );
+                    Ok(Response::builder()
+                        .status(500)
+                        .body(hyper::Body::from(format!("{}", PrettyPrintError(&e))))?)
+                }
+            }
+        }
+        .instrument(request_span)
+        .await
+    }
+
+    /// Handles a request of the long-polling fallback of the HMR WebSocket.
+    /// Polls are held open until there are updates, so they don't wait for
+    /// ongoing side effects like other requests.
+    async fn handle_long_polling(
+        &self,
+        request: Request<hyper::Body>,
+    ) -> Result<Response<hyper::Body>, hyper::http::Error> {
+        let source_maps_allowed = self
+            .source_map_access
+            .as_ref()
+            .map_or(true, |access| access.allows_source_maps(&request));
+        match self
+            .long_polling
+            .handle(request, |connection| {
+                self.connect_update_client_with_access(connection, source_maps_allowed)
+            })
+            .await
+        {
+            Ok(response) => Ok(response),
+            Err(e) => {
+                println!("[500] error (long polling): {}", PrettyPrintError(&e));
+                Response::builder()
+                    .status(500)
+                    .body(hyper::Body::from(format!("{}", PrettyPrintError(&e))))
+            }
         }
     }
 }
@@ -298,3 +610,92 @@ pub fn register() {
     turbopack_ecmascript::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use turbo_tasks::{TransientInstance, TurboTasks};
+    use turbo_tasks_fs::{DiskFileSystem, FileSystem};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
+
+    use super::*;
+    use crate::source::static_assets::StaticAssetsContentSource;
+
+    /// Serves the files of `dir` at `/static`.
+    fn source_provider(dir: &Path) -> impl SourceProvider + Sync {
+        let root = dir.to_string_lossy().to_string();
+        move || {
+            let fs = DiskFileSystem::new("project".to_string(), root.clone());
+            Vc::upcast(StaticAssetsContentSource::new(
+                "static".to_string(),
+                fs.root(),
+            ))
+        }
+    }
+
+    fn issue_reporter(dir: &Path) -> Arc<dyn Fn() -> Vc<Box<dyn IssueReporter>> + Send + Sync> {
+        let log_options = TransientInstance::new(LogOptions {
+            current_dir: dir.to_path_buf(),
+            project_dir: dir.to_path_buf(),
+            show_all: false,
+            log_detail: false,
+            log_level: IssueSeverity::Error,
+        });
+        Arc::new(move || Vc::upcast(ConsoleUi::new(log_options.clone())))
+    }
+
+    fn get(uri: &str) -> Request<hyper::Body> {
+        Request::get(uri).body(hyper::Body::empty()).unwrap()
+    }
+
+    async fn text(response: Response<hyper::Body>) -> String {
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn handles_requests_of_an_embedding_server() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hello.txt"), "Hello").unwrap();
+        register();
+        let handler = DevServerHandlerBuilder::default().build(
+            TurboTasks::new(MemoryBackend::default()),
+            source_provider(dir.path()),
+            issue_reporter(dir.path()),
+        );
+
+        let response = handler.handle(get("/static/hello.txt")).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(text(response).await, "Hello");
+
+        let response = handler.handle(get("/static/missing.txt")).await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn serves_on_a_pre_bound_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hello.txt"), "Hello").unwrap();
+        register();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let builder = DevServer::from_listener(listener).unwrap();
+        assert_eq!(builder.addr, addr);
+        let server = builder.serve(
+            TurboTasks::new(MemoryBackend::default()),
+            source_provider(dir.path()),
+            issue_reporter(dir.path()),
+        );
+        tokio::spawn(server.future);
+
+        let response = hyper::Client::new()
+            .get(format!("http://{addr}/static/hello.txt").parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(text(response).await, "Hello");
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use hyper::HeaderMap;
+use parking_lot::Mutex;
+use turbo_tasks::{run_once, TransientInstance, TurboTasksApi, Vc};
+use turbopack_core::error::PrettyPrintError;
+
+use crate::{
+    http::get_from_source,
+    source::{request::SourceRequest, Body, ContentSource},
+};
+
+/// How long the server needs to be idle before the next hinted path is
+/// precompiled.
+const IDLE_DELAY: Duration = Duration::from_millis(100);
+
+/// Precompiles the content of paths that are likely to be requested next
+/// (e.g. routes linked from the current page), so that navigating to them is
+/// instant.
+///
+/// Hinted paths are processed one at a time, and only while no other requests
+/// are being handled, so precompilation never competes with actual requests.
+pub(crate) struct Precompiler {
+    turbo_tasks: Arc<dyn TurboTasksApi>,
+    get_source: Arc<dyn Fn() -> Vc<Box<dyn ContentSource>> + Send + Sync>,
+    active_requests: AtomicUsize,
+    state: Mutex<PrecompilerState>,
+}
+
+#[derive(Default)]
+struct PrecompilerState {
+    queue: VecDeque<String>,
+    /// Paths that have already been queued. Once precompiled, content is kept
+    /// up to date by turbo-tasks, so hinting a path again is a no-op.
+    seen: HashSet<String>,
+    running: bool,
+}
+
+impl PrecompilerState {
+    /// Queues the paths that haven't been queued yet. Returns `true` if a
+    /// precompilation loop needs to be started for them.
+    fn push(&mut self, paths: impl IntoIterator<Item = String>) -> bool {
+        for path in paths {
+            let path = if path.starts_with('/') {
+                path
+            } else {
+                format!("/{path}")
+            };
+            if self.seen.insert(path.clone()) {
+                self.queue.push_back(path);
+            }
+        }
+        if self.running || self.queue.is_empty() {
+            return false;
+        }
+        self.running = true;
+        true
+    }
+
+    /// Takes the next path to precompile. The precompilation loop stops once
+    /// this returns `None`.
+    fn pop(&mut self) -> Option<String> {
+        let path = self.queue.pop_front();
+        if path.is_none() {
+            self.running = false;
+        }
+        path
+    }
+}
+
+/// Marks a request as being handled for as long as it is alive.
+pub(crate) struct ActiveRequestGuard<'a>(&'a Precompiler);
+
+impl Drop for ActiveRequestGuard<'_> {
+    fn drop(&mut self) {
+        self.0.active_requests.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Precompiler {
+    pub fn new(
+        turbo_tasks: Arc<dyn TurboTasksApi>,
+        get_source: Arc<dyn Fn() -> Vc<Box<dyn ContentSource>> + Send + Sync>,
+    ) -> Self {
+        Self {
+            turbo_tasks,
+            get_source,
+            active_requests: AtomicUsize::new(0),
+            state: Default::default(),
+        }
+    }
+
+    /// Marks the start of a request. Precompilation is paused until the
+    /// returned guard is dropped.
+    pub fn request_started(&self) -> ActiveRequestGuard<'_> {
+        self.active_requests.fetch_add(1, Ordering::SeqCst);
+        ActiveRequestGuard(self)
+    }
+
+    /// Queues `paths` to be precompiled when the server is idle.
+    pub fn hint(self: &Arc<Self>, paths: impl IntoIterator<Item = String>) {
+        if self.state.lock().push(paths) {
+            tokio::spawn(self.clone().run());
+        }
+    }
+
+    async fn run(self: Arc<Self>) {
+        loop {
+            while self.active_requests.load(Ordering::SeqCst) > 0 {
+                tokio::time::sleep(IDLE_DELAY).await;
+            }
+            tokio::time::sleep(IDLE_DELAY).await;
+            if self.active_requests.load(Ordering::SeqCst) > 0 {
+                continue;
+            }
+
+            let Some(path) = self.state.lock().pop() else {
+                return;
+            };
+
+            let get_source = self.get_source.clone();
+            let result = run_once(self.turbo_tasks.clone(), {
+                let path = path.clone();
+                async move {
+                    let request = SourceRequest {
+                        method: "GET".to_string(),
+                        uri: path.parse()?,
+                        headers: HeaderMap::new(),
+                        body: Body::new(vec![]),
+                    };
+                    get_from_source(get_source(), TransientInstance::new(request))
+                        .resolve_strongly_consistent()
+                        .await?;
+                    Ok(())
+                }
+            })
+            .await;
+            if let Err(err) = result {
+                eprintln!("Failed to precompile {path}: {}", PrettyPrintError(&err));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn queues_each_path_once() {
+        let mut state = PrecompilerState::default();
+
+        assert!(state.push(paths(&["a", "/b", "/a"])));
+        assert!(!state.push(paths(&["b", "/c"])));
+
+        assert_eq!(state.queue, paths(&["/a", "/b", "/c"]));
+    }
+
+    #[test]
+    fn restarts_once_the_queue_is_drained() {
+        let mut state = PrecompilerState::default();
+
+        assert!(!state.push(vec![]));
+        assert!(state.push(paths(&["/a"])));
+        assert_eq!(state.pop().as_deref(), Some("/a"));
+        assert_eq!(state.pop(), None);
+
+        assert!(!state.push(paths(&["/a"])), "/a was precompiled already");
+        assert!(state.push(paths(&["/b"])));
+    }
+}
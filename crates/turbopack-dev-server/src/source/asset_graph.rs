@@ -266,8 +266,26 @@ impl GetContentSourceContent for AssetGraphGetContentSourceContent {
     ) -> Result<Vc<ContentSourceContent>> {
         let this = self.await?;
         turbo_tasks::emit(Vc::upcast::<Box<dyn ContentSourceSideEffect>>(self));
-        Ok(ContentSourceContent::static_content(
+        let serving_hints = this.asset.serving_hints().await?;
+        if serving_hints.is_empty() {
+            return Ok(ContentSourceContent::static_content(
+                this.asset.versioned_content(),
+            ));
+        }
+        let mut headers = Vec::new();
+        if let Some(cache_control) = serving_hints.cache_control {
+            headers.push((
+                "cache-control".to_string(),
+                cache_control.header_value().to_string(),
+            ));
+        }
+        if let Some(content_type) = &serving_hints.content_type {
+            headers.push(("content-type".to_string(), content_type.clone()));
+        }
+        Ok(ContentSourceContent::static_with_headers(
             this.asset.versioned_content(),
+            200,
+            Vc::cell(headers),
         ))
     }
 }
@@ -0,0 +1,198 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileJsonContent, FileSystemPath};
+use turbopack_core::{asset::AssetContent, version::VersionedContentExt};
+use turbopack_ecmascript::chunk::base_paths_global;
+
+use super::{
+    headers::HeaderValue,
+    route_tree::{BaseSegment, RouteTree, RouteType},
+    ContentSource, ContentSourceContent, ContentSourceData, ContentSourceDataFilter,
+    ContentSourceDataVary, GetContentSourceContent, HeaderList,
+};
+
+/// The base paths chunks and static assets are loaded from. `None` keeps the
+/// base path the chunks were built with.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BasePaths {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_base_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asset_base_path: Option<String>,
+}
+
+/// The [BasePaths] of each host, keyed by the full host name (`a.example.com`)
+/// or by its first label (`a`).
+#[turbo_tasks::value(transparent)]
+pub struct HostBasePaths(IndexMap<String, BasePaths>);
+
+/// Reads the [HostBasePaths] from the JSON file at `path`, e.g.
+///
+/// ```json
+/// {
+///   "tenant-a": { "chunkBasePath": "https://tenant-a.cdn.example.com/" }
+/// }
+/// ```
+#[turbo_tasks::function]
+pub async fn read_host_base_paths(path: Vc<FileSystemPath>) -> Result<Vc<HostBasePaths>> {
+    let path_str = path.to_string().await?;
+    let FileJsonContent::Content(json) = &*path.read_json().await? else {
+        bail!("unable to read the base paths from {}", path_str);
+    };
+    let hosts = serde_json::from_value(json.clone())
+        .with_context(|| format!("invalid base paths in {}", path_str))?;
+    Ok(Vc::cell(hosts))
+}
+
+/// Looks up the [BasePaths] of the `host` header of a request, by the full
+/// host name first and by its first label otherwise.
+fn base_paths_of_host<'a>(
+    hosts: &'a IndexMap<String, BasePaths>,
+    host: &str,
+) -> Option<&'a BasePaths> {
+    // Strip the port.
+    let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
+    hosts
+        .get(host)
+        .or_else(|| host.split_once('.').and_then(|(label, _)| hosts.get(label)))
+}
+
+/// Serves the config script setting the base paths for the host of the
+/// request, which are read by the chunks registering with the chunk loading
+/// global `chunk_loading_global` when their base paths are resolved at
+/// runtime. The script must be loaded before any chunk.
+#[turbo_tasks::value(shared)]
+pub struct BasePathsContentSource {
+    pathname: String,
+    chunk_loading_global: String,
+    hosts: Vc<HostBasePaths>,
+    default: Vc<BasePaths>,
+}
+
+#[turbo_tasks::value_impl]
+impl BasePathsContentSource {
+    #[turbo_tasks::function]
+    pub fn new(
+        pathname: String,
+        chunk_loading_global: String,
+        hosts: Vc<HostBasePaths>,
+        default: Vc<BasePaths>,
+    ) -> Vc<Self> {
+        BasePathsContentSource {
+            pathname,
+            chunk_loading_global,
+            hosts,
+            default,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ContentSource for BasePathsContentSource {
+    #[turbo_tasks::function]
+    async fn get_routes(self: Vc<Self>) -> Result<Vc<RouteTree>> {
+        Ok(RouteTree::new_route(
+            BaseSegment::from_static_pathname(&self.await?.pathname).collect(),
+            RouteType::Exact,
+            Vc::upcast(self),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GetContentSourceContent for BasePathsContentSource {
+    #[turbo_tasks::function]
+    fn vary(&self) -> Vc<ContentSourceDataVary> {
+        ContentSourceDataVary {
+            headers: Some(ContentSourceDataFilter::Subset(BTreeSet::from([
+                "host".to_string()
+            ]))),
+            ..Default::default()
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn get(
+        &self,
+        _path: String,
+        data: Value<ContentSourceData>,
+    ) -> Result<Vc<ContentSourceContent>> {
+        let host = match data
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get("host"))
+        {
+            Some(HeaderValue::SingleString(host)) => host.as_str(),
+            _ => "",
+        };
+        let hosts = self.hosts.await?;
+        let default = self.default.await?;
+        let base_paths = base_paths_of_host(&hosts, host).unwrap_or(&default);
+
+        let code = format!(
+            "globalThis.{} = {};\n",
+            base_paths_global(&self.chunk_loading_global),
+            serde_json::to_string(base_paths)?
+        );
+        Ok(ContentSourceContent::static_with_headers(
+            AssetContent::file(
+                File::from(code)
+                    .with_content_type(mime::APPLICATION_JAVASCRIPT_UTF_8)
+                    .into(),
+            )
+            .versioned(),
+            200,
+            // The script differs per host, so it must not be cached by proxies
+            // in front of the server.
+            HeaderList::new(vec![("cache-control".to_string(), "no-store".to_string())]),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_paths(chunk_base_path: &str) -> BasePaths {
+        BasePaths {
+            chunk_base_path: Some(chunk_base_path.to_string()),
+            asset_base_path: None,
+        }
+    }
+
+    #[test]
+    fn looks_up_hosts_by_name_and_first_label() {
+        let hosts = IndexMap::from([
+            ("a.example.com".to_string(), base_paths("https://a.cdn/")),
+            ("a".to_string(), base_paths("https://a-label.cdn/")),
+            ("b".to_string(), base_paths("https://b.cdn/")),
+        ]);
+        let lookup = |host| {
+            base_paths_of_host(&hosts, host)
+                .and_then(|base_paths| base_paths.chunk_base_path.as_deref())
+        };
+
+        assert_eq!(lookup("a.example.com"), Some("https://a.cdn/"));
+        assert_eq!(lookup("a.example.com:3000"), Some("https://a.cdn/"));
+        assert_eq!(lookup("a.example.org"), Some("https://a-label.cdn/"));
+        assert_eq!(lookup("b.example.com:3000"), Some("https://b.cdn/"));
+        assert_eq!(lookup("c.example.com"), None);
+        assert_eq!(lookup(""), None);
+    }
+
+    #[test]
+    fn serializes_only_the_configured_base_paths() {
+        assert_eq!(
+            serde_json::to_string(&base_paths("https://a.cdn/")).unwrap(),
+            r#"{"chunkBasePath":"https://a.cdn/"}"#
+        );
+        assert_eq!(serde_json::to_string(&BasePaths::default()).unwrap(), "{}");
+    }
+}
@@ -1,4 +1,5 @@
 pub mod asset_graph;
+pub mod base_paths;
 pub mod combined;
 pub mod conditional;
 pub mod headers;
@@ -158,7 +159,7 @@ impl ContentSourceContent {
 
 /// A list of headers arranged as contiguous (name, value) pairs.
 #[turbo_tasks::value(transparent)]
-pub struct HeaderList(Vec<(String, String)>);
+pub struct HeaderList(pub(crate) Vec<(String, String)>);
 
 #[turbo_tasks::value_impl]
 impl HeaderList {
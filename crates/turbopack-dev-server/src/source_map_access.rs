@@ -0,0 +1,188 @@
+use std::{fmt, sync::Arc};
+
+use hyper::{header::HeaderName, Request, Uri};
+
+/// Decides whether the dev server serves a source map (a `.map` file) for a
+/// request.
+///
+/// Chunks still reference their source maps, so that authorized clients can
+/// load them, but other clients (e.g. stakeholders looking at a deployed dev
+/// build) can't download the original sources.
+pub trait SourceMapAccessPolicy: Send + Sync + 'static {
+    fn is_allowed(&self, request: &Request<hyper::Body>) -> bool;
+}
+
+impl<F> SourceMapAccessPolicy for F
+where
+    F: Fn(&Request<hyper::Body>) -> bool + Send + Sync + 'static,
+{
+    fn is_allowed(&self, request: &Request<hyper::Body>) -> bool {
+        self(request)
+    }
+}
+
+/// Allows requests with a header of the given value, e.g. an
+/// `authorization` header with a token.
+pub struct RequireHeader {
+    name: HeaderName,
+    value: String,
+}
+
+impl RequireHeader {
+    pub fn new(name: HeaderName, value: impl Into<String>) -> Self {
+        Self {
+            name,
+            value: value.into(),
+        }
+    }
+}
+
+impl SourceMapAccessPolicy for RequireHeader {
+    fn is_allowed(&self, request: &Request<hyper::Body>) -> bool {
+        request
+            .headers()
+            .get_all(&self.name)
+            .iter()
+            .any(|value| value.as_bytes() == self.value.as_bytes())
+    }
+}
+
+/// Allows requests whose `referer` header has one of the given origins, e.g.
+/// requests made by devtools opened on an internal host. Requests without a
+/// `referer` header, like WebSocket upgrades, are checked by their `origin`
+/// header instead.
+pub struct RequireReferer {
+    origins: Vec<String>,
+}
+
+impl RequireReferer {
+    /// Origins are given as URLs, e.g. `https://internal.example.com`. Their
+    /// paths are ignored.
+    pub fn new(origins: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            origins: origins
+                .into_iter()
+                .filter_map(|origin| origin_of(&origin))
+                .collect(),
+        }
+    }
+}
+
+impl SourceMapAccessPolicy for RequireReferer {
+    fn is_allowed(&self, request: &Request<hyper::Body>) -> bool {
+        let headers = request.headers();
+        let Some(url) = headers
+            .get(hyper::header::REFERER)
+            .or_else(|| headers.get(hyper::header::ORIGIN))
+            .and_then(|url| url.to_str().ok())
+        else {
+            return false;
+        };
+        let Some(origin) = origin_of(url) else {
+            return false;
+        };
+        self.origins.iter().any(|allowed| *allowed == origin)
+    }
+}
+
+/// Returns the origin of `url`, i.e. its lowercased scheme, host and port, or
+/// `None` if it's not an absolute URL.
+fn origin_of(url: &str) -> Option<String> {
+    let uri = url.parse::<Uri>().ok()?;
+    let scheme = uri.scheme_str()?.to_ascii_lowercase();
+    let authority = uri.authority()?;
+    let host = authority.host().to_ascii_lowercase();
+    Some(match authority.port_u16() {
+        Some(port) => format!("{scheme}://{host}:{port}"),
+        None => format!("{scheme}://{host}"),
+    })
+}
+
+/// Whether the request path `path` is for a source map. The path is decoded
+/// first, like it is when it's resolved, so encoded paths like `/a.js.m%61p`
+/// are detected as well.
+pub(crate) fn is_source_map_path(path: &str) -> bool {
+    match urlencoding::decode(path) {
+        Ok(path) => path.to_ascii_lowercase().ends_with(".map"),
+        // Paths that can't be decoded can't be resolved either.
+        Err(_) => true,
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct SourceMapAccess(Arc<dyn SourceMapAccessPolicy>);
+
+impl SourceMapAccess {
+    pub fn new(policy: impl SourceMapAccessPolicy) -> Self {
+        Self(Arc::new(policy))
+    }
+
+    /// Returns `false` if `request` is for a source map and the policy doesn't
+    /// allow it.
+    pub fn is_allowed(&self, request: &Request<hyper::Body>) -> bool {
+        !is_source_map_path(request.uri().path()) || self.0.is_allowed(request)
+    }
+
+    /// Whether the client making `request` may receive source maps, e.g. with
+    /// the hot updates sent to the WebSocket it's upgraded to.
+    pub fn allows_source_maps(&self, request: &Request<hyper::Body>) -> bool {
+        self.0.is_allowed(request)
+    }
+}
+
+impl fmt::Debug for SourceMapAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SourceMapAccess")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str, headers: &[(&str, &str)]) -> Request<hyper::Body> {
+        let mut builder = Request::builder().uri(uri);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(hyper::Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn detects_encoded_source_map_paths() {
+        assert!(is_source_map_path("/chunks/a.js.map"));
+        assert!(is_source_map_path("/chunks/a.js.m%61p"));
+        assert!(is_source_map_path("/chunks/a.js.MAP"));
+        assert!(!is_source_map_path("/chunks/a.js"));
+        assert!(!is_source_map_path("/chunks/map.js"));
+    }
+
+    #[test]
+    fn denies_encoded_source_map_requests() {
+        let access = SourceMapAccess::new(|_: &Request<hyper::Body>| false);
+        assert!(!access.is_allowed(&request("/a.js.m%61p", &[])));
+        assert!(!access.is_allowed(&request("/a.js.map?id=1", &[])));
+        assert!(access.is_allowed(&request("/a.js", &[])));
+    }
+
+    #[test]
+    fn referer_must_match_an_origin_exactly() {
+        let policy = RequireReferer::new(["https://a.com".to_string()]);
+        let allowed = |headers: &[(&str, &str)]| policy.is_allowed(&request("/a.js.map", headers));
+        assert!(allowed(&[("referer", "https://a.com/page")]));
+        assert!(allowed(&[("referer", "https://A.com")]));
+        assert!(allowed(&[("origin", "https://a.com")]));
+        assert!(!allowed(&[("referer", "https://a.com.evil.com/page")]));
+        assert!(!allowed(&[("referer", "https://a.com:8080/page")]));
+        assert!(!allowed(&[("referer", "http://a.com/page")]));
+        assert!(!allowed(&[("referer", "https://user@a.com.evil.com")]));
+        assert!(!allowed(&[]));
+    }
+
+    #[test]
+    fn require_header() {
+        let policy = RequireHeader::new(hyper::header::AUTHORIZATION, "token");
+        assert!(policy.is_allowed(&request("/a.js.map", &[("authorization", "token")])));
+        assert!(!policy.is_allowed(&request("/a.js.map", &[("authorization", "other")])));
+    }
+}
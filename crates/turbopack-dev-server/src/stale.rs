@@ -0,0 +1,161 @@
+use std::collections::{HashMap, VecDeque};
+
+use hyper::Uri;
+use parking_lot::Mutex;
+use turbo_tasks::ReadRef;
+
+use crate::http::GetFromSourceResult;
+
+/// The query parameter the runtime uses to tell the server which build a chunk
+/// is requested for. Must be kept in sync with `BUILD_ID_QUERY_PARAM` in the
+/// dev runtime.
+pub(crate) const BUILD_ID_QUERY_PARAM: &str = "turbopack-build";
+
+/// Extracts the build id from the query of a request URI, if any.
+pub(crate) fn build_id_from_uri(uri: &Uri) -> Option<String> {
+    uri.query()?.split('&').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        (name == BUILD_ID_QUERY_PARAM)
+            .then(|| urlencoding::decode(value).ok())
+            .flatten()
+            .map(|value| value.into_owned())
+    })
+}
+
+/// Keeps the content served for the last few builds addressable.
+///
+/// Every successful response to a request that carries a build id is recorded
+/// for that build. When a later request can't be served from the current
+/// content source anymore (e.g. because chunk names contain content hashes and
+/// a rebuild replaced the chunk), the recorded content is served instead. This
+/// prevents clients still running an older build from breaking while a new
+/// build is rolled out.
+pub(crate) struct StaleContentCache {
+    max_builds: usize,
+    builds: Mutex<VecDeque<(String, HashMap<String, ReadRef<GetFromSourceResult>>)>>,
+}
+
+impl StaleContentCache {
+    pub fn new(max_builds: usize) -> Self {
+        Self {
+            max_builds,
+            builds: Mutex::new(VecDeque::with_capacity(max_builds)),
+        }
+    }
+
+    /// Records the content served for `path` in the build `build_id`.
+    pub fn record(&self, build_id: &str, path: &str, result: ReadRef<GetFromSourceResult>) {
+        if self.max_builds == 0 || !matches!(&*result, GetFromSourceResult::Static { .. }) {
+            return;
+        }
+        let mut builds = self.builds.lock();
+        let index = match builds.iter().position(|(id, _)| id == build_id) {
+            Some(index) => index,
+            None => {
+                if builds.len() == self.max_builds {
+                    builds.pop_front();
+                }
+                builds.push_back((build_id.to_string(), HashMap::new()));
+                builds.len() - 1
+            }
+        };
+        builds[index].1.insert(path.to_string(), result);
+    }
+
+    /// Looks up content previously served for `path`. Content recorded for
+    /// `build_id` is preferred, otherwise the most recent build that served
+    /// `path` is used.
+    pub fn get(&self, build_id: Option<&str>, path: &str) -> Option<ReadRef<GetFromSourceResult>> {
+        let builds = self.builds.lock();
+        if let Some(build_id) = build_id {
+            if let Some(result) = builds
+                .iter()
+                .find(|(id, _)| id == build_id)
+                .and_then(|(_, contents)| contents.get(path))
+            {
+                return Some(result.clone());
+            }
+        }
+        builds
+            .iter()
+            .rev()
+            .find_map(|(_, contents)| contents.get(path).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use turbo_tasks_fs::FileContent;
+
+    use super::*;
+    use crate::source::HeaderList;
+
+    fn response(status_code: u16) -> ReadRef<GetFromSourceResult> {
+        let headers = ReadRef::new(Arc::new(HeaderList(vec![])));
+        ReadRef::new(Arc::new(GetFromSourceResult::Static {
+            content: ReadRef::new(Arc::new(FileContent::NotFound)),
+            status_code,
+            headers: headers.clone(),
+            header_overwrites: headers,
+        }))
+    }
+
+    fn status_code(result: Option<ReadRef<GetFromSourceResult>>) -> Option<u16> {
+        match &*result? {
+            GetFromSourceResult::Static { status_code, .. } => Some(*status_code),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn reads_the_build_id_from_the_query() {
+        let build_id = |uri: &str| build_id_from_uri(&uri.parse().unwrap());
+        assert_eq!(
+            build_id("/a.js?v=1&turbopack-build=b%201").as_deref(),
+            Some("b 1")
+        );
+        assert_eq!(build_id("/a.js?v=1"), None);
+        assert_eq!(build_id("/a.js"), None);
+    }
+
+    #[test]
+    fn serves_the_content_of_the_requested_build() {
+        let cache = StaleContentCache::new(2);
+        cache.record("b1", "/a.js", response(201));
+        cache.record("b2", "/a.js", response(202));
+
+        assert_eq!(status_code(cache.get(Some("b1"), "/a.js")), Some(201));
+        assert_eq!(status_code(cache.get(Some("b2"), "/a.js")), Some(202));
+        assert_eq!(status_code(cache.get(Some("b3"), "/a.js")), Some(202));
+        assert_eq!(status_code(cache.get(None, "/a.js")), Some(202));
+        assert_eq!(status_code(cache.get(Some("b1"), "/b.js")), None);
+    }
+
+    #[test]
+    fn forgets_the_oldest_build() {
+        let cache = StaleContentCache::new(2);
+        cache.record("b1", "/a.js", response(201));
+        cache.record("b2", "/b.js", response(202));
+        cache.record("b3", "/b.js", response(203));
+
+        assert_eq!(status_code(cache.get(Some("b1"), "/a.js")), None);
+        assert_eq!(status_code(cache.get(Some("b2"), "/b.js")), Some(202));
+    }
+
+    #[test]
+    fn only_records_static_content() {
+        let cache = StaleContentCache::new(2);
+        cache.record(
+            "b1",
+            "/a.js",
+            ReadRef::new(Arc::new(GetFromSourceResult::NotFound)),
+        );
+        assert!(cache.get(Some("b1"), "/a.js").is_none());
+
+        let disabled = StaleContentCache::new(0);
+        disabled.record("b1", "/a.js", response(200));
+        assert!(disabled.get(Some("b1"), "/a.js").is_none());
+    }
+}
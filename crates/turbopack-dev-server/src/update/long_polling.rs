@@ -0,0 +1,290 @@
+//! A long-polling fallback for the `/turbopack-hmr` WebSocket, for networks
+//! that block WebSockets, e.g. behind corporate proxies. The runtime falls
+//! back to it when the WebSocket can't be opened.
+//!
+//! * `POST /turbopack-hmr/connect` starts a session and answers `{"session":
+//!   "<id>"}`.
+//! * `POST /turbopack-hmr/send?session=<id>` sends the JSON message in the body
+//!   to the dev server.
+//! * `GET /turbopack-hmr/poll?session=<id>` waits for messages of the dev
+//!   server and answers them as a JSON array of message texts, or an empty
+//!   array after [POLL_TIMEOUT].
+//!
+//! Sessions that aren't polled for [SESSION_TIMEOUT] are closed, which ends
+//! their update channels. Requests for unknown sessions are answered with a
+//! 404, upon which the runtime starts a new session and subscribes again.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use futures::{channel::mpsc, StreamExt};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use parking_lot::Mutex;
+use serde::Deserialize;
+
+use super::transport::{update_channel, UpdateChannelClient, UpdateChannelConnection};
+
+/// The path prefix of the long-polling endpoints.
+pub(crate) const LONG_POLLING_PATH_PREFIX: &str = "/turbopack-hmr/";
+
+/// How long a poll waits for messages before it's answered with no messages.
+/// This is below the idle timeout of common proxies.
+pub const POLL_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// How long a session is kept without being polled.
+pub const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+struct Session {
+    sender: mpsc::UnboundedSender<String>,
+    /// Locked while the session is polled.
+    receiver: tokio::sync::Mutex<mpsc::UnboundedReceiver<String>>,
+    last_poll: Mutex<Instant>,
+}
+
+#[derive(Deserialize)]
+struct SessionQuery {
+    session: String,
+}
+
+/// The long-polling sessions of a dev server.
+#[derive(Default)]
+pub(crate) struct LongPollingSessions {
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+    /// Whether the task closing expired sessions has been started.
+    closing_expired_sessions: AtomicBool,
+}
+
+impl LongPollingSessions {
+    /// Handles a request to one of the long-polling endpoints. `connect` is
+    /// called with the dev server's end of the update channel of every new
+    /// session.
+    pub(crate) async fn handle(
+        self: &Arc<Self>,
+        request: Request<Body>,
+        connect: impl FnOnce(UpdateChannelConnection),
+    ) -> Result<Response<Body>> {
+        let endpoint = request
+            .uri()
+            .path()
+            .strip_prefix(LONG_POLLING_PATH_PREFIX)
+            .unwrap_or_default()
+            .to_string();
+        match (request.method(), endpoint.as_str()) {
+            (&Method::POST, "connect") => {
+                if !self.closing_expired_sessions.swap(true, Ordering::Relaxed) {
+                    tokio::spawn(close_expired_sessions_periodically(Arc::downgrade(self)));
+                }
+                let id = new_session_id();
+                let (connection, UpdateChannelClient { sender, receiver }) = update_channel();
+                connect(connection);
+                self.sessions.lock().insert(
+                    id.clone(),
+                    Arc::new(Session {
+                        sender,
+                        receiver: tokio::sync::Mutex::new(receiver),
+                        last_poll: Mutex::new(Instant::now()),
+                    }),
+                );
+                json_response(&serde_json::json!({ "session": id }))
+            }
+            (&Method::POST, "send") => {
+                let Some((_, session)) = self.session(&request) else {
+                    return not_found();
+                };
+                let body = hyper::body::to_bytes(request.into_body())
+                    .await
+                    .context("reading the message")?;
+                let message = String::from_utf8(body.to_vec()).context("invalid message")?;
+                // Sending only fails when the update server stopped, in which
+                // case the next poll ends the session.
+                let _ = session.sender.unbounded_send(message);
+                Ok(Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .body(Body::empty())?)
+            }
+            (&Method::GET, "poll") => {
+                let Some((id, session)) = self.session(&request) else {
+                    return not_found();
+                };
+                let mut receiver = session.receiver.lock().await;
+                *session.last_poll.lock() = Instant::now();
+                let mut messages = Vec::new();
+                match tokio::time::timeout(POLL_TIMEOUT, receiver.next()).await {
+                    Ok(Some(message)) => messages.push(message),
+                    // The update server stopped.
+                    Ok(None) => {
+                        self.sessions.lock().remove(&id);
+                        return not_found();
+                    }
+                    Err(_) => {}
+                }
+                while let Ok(Some(message)) = receiver.try_next() {
+                    messages.push(message);
+                }
+                *session.last_poll.lock() = Instant::now();
+                json_response(&messages)
+            }
+            _ => not_found(),
+        }
+    }
+
+    fn session(&self, request: &Request<Body>) -> Option<(String, Arc<Session>)> {
+        let query: SessionQuery = serde_qs::from_str(request.uri().query()?).ok()?;
+        let session = self.sessions.lock().get(&query.session)?.clone();
+        Some((query.session, session))
+    }
+
+    /// Closes the sessions that haven't been polled for [SESSION_TIMEOUT],
+    /// which ends their update channels, so their update servers stop.
+    fn close_expired_sessions(&self) {
+        self.sessions.lock().retain(|_, session| {
+            let expired = session.last_poll.lock().elapsed() >= SESSION_TIMEOUT
+                && session.receiver.try_lock().is_ok();
+            if expired {
+                session.sender.close_channel();
+            }
+            !expired
+        });
+    }
+}
+
+/// Closes expired sessions until the sessions are dropped.
+async fn close_expired_sessions_periodically(sessions: Weak<LongPollingSessions>) {
+    let mut interval = tokio::time::interval(SESSION_TIMEOUT / 2);
+    loop {
+        interval.tick().await;
+        let Some(sessions) = sessions.upgrade() else {
+            return;
+        };
+        sessions.close_expired_sessions();
+    }
+}
+
+/// Creates a random id for a new session, which can't be guessed to take over
+/// the session.
+fn new_session_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+fn json_response(value: &impl serde::Serialize) -> Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "application/json")
+        .header("cache-control", "no-store")
+        .body(Body::from(serde_json::to_string(value)?))?)
+}
+
+fn not_found() -> Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::empty())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, endpoint: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(format!("{LONG_POLLING_PATH_PREFIX}{endpoint}"))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn body(response: Response<Body>) -> serde_json::Value {
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    async fn connect(sessions: &Arc<LongPollingSessions>) -> (String, UpdateChannelConnection) {
+        let mut connection = None;
+        let response = sessions
+            .handle(request(Method::POST, "connect", ""), |c| {
+                connection = Some(c)
+            })
+            .await
+            .unwrap();
+        let id = body(response).await["session"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        (id, connection.unwrap())
+    }
+
+    #[tokio::test]
+    async fn exchanges_messages() {
+        let sessions = Arc::new(LongPollingSessions::default());
+        let (id, mut connection) = connect(&sessions).await;
+
+        let response = sessions
+            .handle(
+                request(Method::POST, &format!("send?session={id}"), "subscribe"),
+                |_| unreachable!(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(connection.next().await.unwrap().unwrap(), "subscribe");
+
+        futures::SinkExt::send(&mut connection, "a".to_string())
+            .await
+            .unwrap();
+        futures::SinkExt::send(&mut connection, "b".to_string())
+            .await
+            .unwrap();
+        let response = sessions
+            .handle(
+                request(Method::GET, &format!("poll?session={id}"), ""),
+                |_| unreachable!(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(body(response).await, serde_json::json!(["a", "b"]));
+    }
+
+    #[tokio::test]
+    async fn unknown_sessions_are_not_found() {
+        let sessions = Arc::new(LongPollingSessions::default());
+        let response = sessions
+            .handle(
+                request(Method::GET, "poll?session=unknown", ""),
+                |_| unreachable!(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn session_ids_are_random() {
+        let sessions = Arc::new(LongPollingSessions::default());
+        let (a, _a) = connect(&sessions).await;
+        let (b, _b) = connect(&sessions).await;
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 32);
+    }
+
+    #[tokio::test]
+    async fn expired_sessions_are_closed() {
+        let sessions = Arc::new(LongPollingSessions::default());
+        let (expired, mut expired_connection) = connect(&sessions).await;
+        let (active, _active_connection) = connect(&sessions).await;
+
+        let long_ago = Instant::now().checked_sub(SESSION_TIMEOUT * 2).unwrap();
+        *sessions.sessions.lock()[&expired].last_poll.lock() = long_ago;
+        sessions.close_expired_sessions();
+
+        assert!(expired_connection.next().await.is_none());
+        let remaining = sessions.sessions.lock();
+        assert!(!remaining.contains_key(&expired));
+        assert!(remaining.contains_key(&active));
+    }
+}
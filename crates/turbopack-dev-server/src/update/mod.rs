@@ -1,4 +1,6 @@
+pub mod long_polling;
 pub mod server;
 pub mod stream;
+pub mod transport;
 
 pub(super) use server::UpdateServer;
@@ -1,13 +1,12 @@
 use std::{
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
 use anyhow::{Context as _, Error, Result};
 use futures::{prelude::*, ready, stream::FusedStream, SinkExt};
-use hyper::{upgrade::Upgraded, HeaderMap, Uri};
-use hyper_tungstenite::{tungstenite::Message, HyperWebsocket, WebSocketStream};
-use pin_project_lite::pin_project;
+use hyper::{HeaderMap, Uri};
 use tokio::select;
 use tokio_stream::StreamMap;
 use tracing::{instrument, Level};
@@ -18,9 +17,11 @@ use turbopack_ecmascript_hmr_protocol::{
     ClientMessage, ClientUpdateInstruction, Issue, ResourceIdentifier,
 };
 
-use super::stream::UpdateStream;
+use super::{stream::UpdateStream, transport::UpdateConnection};
 use crate::{
+    precompile::Precompiler,
     source::{request::SourceRequest, resolve::resolve_source_request, Body},
+    source_map_access::is_source_map_path,
     update::stream::UpdateStreamItem,
     SourceProvider,
 };
@@ -30,21 +31,37 @@ pub(crate) struct UpdateServer<P: SourceProvider> {
     source_provider: P,
     #[allow(dead_code)]
     issue_reporter: Vc<Box<dyn IssueReporter>>,
+    precompiler: Arc<Precompiler>,
+    /// Whether the client may receive source maps, see
+    /// [SourceMapAccess](crate::source_map_access::SourceMapAccess).
+    source_maps_allowed: bool,
 }
 
 impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
     /// Create a new update server with the given websocket and content source.
-    pub fn new(source_provider: P, issue_reporter: Vc<Box<dyn IssueReporter>>) -> Self {
+    pub fn new(
+        source_provider: P,
+        issue_reporter: Vc<Box<dyn IssueReporter>>,
+        precompiler: Arc<Precompiler>,
+        source_maps_allowed: bool,
+    ) -> Self {
         Self {
             source_provider,
             issue_reporter,
+            precompiler,
+            source_maps_allowed,
         }
     }
 
-    /// Run the update server loop.
-    pub fn run(self, tt: &dyn TurboTasksApi, ws: HyperWebsocket) {
+    /// Run the update server loop for the client that connects with
+    /// `connection`, e.g. once a WebSocket upgrade completes.
+    pub fn run<C: UpdateConnection>(
+        self,
+        tt: &dyn TurboTasksApi,
+        connection: impl Future<Output = Result<C>> + Send + 'static,
+    ) {
         tt.run_once_process(Box::pin(async move {
-            if let Err(err) = self.run_internal(ws).await {
+            if let Err(err) = self.run_internal(connection).await {
                 println!("[UpdateServer]: error {:#}", err);
             }
             Ok(())
@@ -52,8 +69,11 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
     }
 
     #[instrument(level = Level::TRACE, skip_all, name = "UpdateServer::run_internal")]
-    async fn run_internal(self, ws: HyperWebsocket) -> Result<()> {
-        let mut client: UpdateClient = ws.await?.into();
+    async fn run_internal<C: UpdateConnection>(
+        self,
+        connection: impl Future<Output = Result<C>>,
+    ) -> Result<()> {
+        let mut client = UpdateClient::new(connection.await?);
 
         let mut streams = StreamMap::new();
 
@@ -62,6 +82,13 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                 message = client.try_next() => {
                     match message? {
                         Some(ClientMessage::Subscribe { resource }) => {
+                            if !self.source_maps_allowed && is_source_map_path(&resource.path) {
+                                println!("[403] {} (source map access denied)", resource.path);
+                                client
+                                    .send(ClientUpdateInstruction::not_found(&resource))
+                                    .await?;
+                                continue;
+                            }
                             let get_content = {
                                 let source_provider = self.source_provider.clone();
                                 let request = resource_to_request(&resource)?;
@@ -89,8 +116,11 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                         Some(ClientMessage::Unsubscribe { resource }) => {
                             streams.remove(&resource);
                         }
+                        Some(ClientMessage::Precompile { paths }) => {
+                            self.precompiler.hint(paths);
+                        }
                         None => {
-                            // WebSocket was closed, stop sending updates
+                            // The client disconnected, stop sending updates
                             break;
                         }
                     }
@@ -98,7 +128,14 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                 Some((resource, update)) = streams.next() => {
                     match update {
                         Ok(update) => {
-                            Self::send_update(&mut client, &mut streams, resource, &update).await?;
+                            Self::send_update(
+                                &mut client,
+                                &mut streams,
+                                resource,
+                                &update,
+                                self.source_maps_allowed,
+                            )
+                            .await?;
                         }
                         Err(err) => {
                             eprintln!("Failed to get update for {resource}: {}", PrettyPrintError(&err));
@@ -117,6 +154,7 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
         streams: &mut StreamMap<ResourceIdentifier, UpdateStream>,
         resource: ResourceIdentifier,
         item: &UpdateStreamItem,
+        source_maps_allowed: bool,
     ) -> Result<()> {
         match item {
             UpdateStreamItem::NotFound => {
@@ -134,11 +172,14 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
                     .collect::<Vec<Issue<'_>>>();
                 match &**update {
                     Update::Partial(partial) => {
-                        let partial_instruction = &partial.instruction;
+                        let mut partial_instruction = partial.instruction.clone();
+                        if !source_maps_allowed {
+                            strip_source_map_urls(Arc::make_mut(&mut partial_instruction));
+                        }
                         client
                             .send(ClientUpdateInstruction::partial(
                                 &resource,
-                                partial_instruction,
+                                &partial_instruction,
                                 &issues,
                             ))
                             .await?;
@@ -161,6 +202,31 @@ impl<P: SourceProvider + Clone + Send + Sync> UpdateServer<P> {
     }
 }
 
+/// Removes the source map URLs of the module entries of an update instruction,
+/// so that a client that may not receive source maps doesn't reference them.
+fn strip_source_map_urls(instruction: &mut serde_json::Value) {
+    match instruction {
+        serde_json::Value::Object(object) => {
+            if let Some(serde_json::Value::Object(entries)) = object.get_mut("entries") {
+                for entry in entries.values_mut() {
+                    if let serde_json::Value::Object(entry) = entry {
+                        entry.remove("map");
+                    }
+                }
+            }
+            for value in object.values_mut() {
+                strip_source_map_urls(value);
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for value in values {
+                strip_source_map_urls(value);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn resource_to_request(resource: &ResourceIdentifier) -> Result<SourceRequest> {
     let mut headers = HeaderMap::new();
 
@@ -181,43 +247,44 @@ fn resource_to_request(resource: &ResourceIdentifier) -> Result<SourceRequest> {
     })
 }
 
-pin_project! {
-    struct UpdateClient {
-        #[pin]
-        ws: WebSocketStream<Upgraded>,
-        ended: bool,
+struct UpdateClient {
+    connection: Pin<Box<dyn UpdateConnection>>,
+    ended: bool,
+}
+
+impl UpdateClient {
+    fn new(connection: impl UpdateConnection) -> Self {
+        Self {
+            connection: Box::pin(connection),
+            ended: false,
+        }
     }
 }
 
 impl Stream for UpdateClient {
     type Item = Result<ClientMessage>;
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         if self.ended {
             return Poll::Ready(None);
         }
 
-        let this = self.project();
-        let item = ready!(this.ws.poll_next(cx));
-
-        let msg = match item {
-            Some(Ok(Message::Text(msg))) => msg,
+        let msg = match ready!(self.connection.as_mut().poll_next(cx)) {
+            Some(Ok(msg)) => msg,
             Some(Err(err)) => {
-                *this.ended = true;
-
-                let err = Error::new(err).context("reading from websocket");
+                self.ended = true;
                 return Poll::Ready(Some(Err(err)));
             }
-            _ => {
-                *this.ended = true;
+            None => {
+                self.ended = true;
                 return Poll::Ready(None);
             }
         };
 
-        match parse_json_with_source_context(&msg).context("deserializing websocket message") {
+        match parse_json_with_source_context(&msg).context("deserializing update message") {
             Ok(msg) => Poll::Ready(Some(Ok(msg))),
             Err(err) => {
-                *this.ended = true;
+                self.ended = true;
 
                 Poll::Ready(Some(Err(err)))
             }
@@ -227,7 +294,7 @@ impl Stream for UpdateClient {
 
 impl FusedStream for UpdateClient {
     fn is_terminated(&self) -> bool {
-        self.ended || self.ws.is_terminated()
+        self.ended
     }
 }
 
@@ -235,50 +302,56 @@ impl<'a> Sink<ClientUpdateInstruction<'a>> for UpdateClient {
     type Error = Error;
 
     fn poll_ready(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<std::result::Result<(), Self::Error>> {
-        self.project()
-            .ws
-            .poll_ready(cx)
-            .map(|res| res.context("polling WebSocket ready"))
+        self.connection.as_mut().poll_ready(cx)
     }
 
     fn start_send(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         item: ClientUpdateInstruction<'a>,
     ) -> std::result::Result<(), Self::Error> {
-        let msg = Message::text(serde_json::to_string(&item)?);
-
-        self.project()
-            .ws
-            .start_send(msg)
-            .context("sending to WebSocket")
+        let msg = serde_json::to_string(&item)?;
+        self.connection.as_mut().start_send(msg)
     }
 
     fn poll_flush(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<std::result::Result<(), Self::Error>> {
-        self.project()
-            .ws
-            .poll_flush(cx)
-            .map(|res| res.context("flushing WebSocket"))
+        self.connection.as_mut().poll_flush(cx)
     }
 
     fn poll_close(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<std::result::Result<(), Self::Error>> {
-        self.project()
-            .ws
-            .poll_close(cx)
-            .map(|res| res.context("closing WebSocket"))
+        self.connection.as_mut().poll_close(cx)
     }
 }
 
-impl From<WebSocketStream<Upgraded>> for UpdateClient {
-    fn from(ws: WebSocketStream<Upgraded>) -> Self {
-        Self { ws, ended: false }
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn strips_source_map_urls_of_nested_entries() {
+        let mut instruction = json!({
+            "type": "ChunkListUpdate",
+            "merged": [{
+                "type": "EcmascriptMergedUpdate",
+                "entries": {
+                    "1": { "code": "a", "url": "/a.js?id=1", "map": "/a.js.map?id=1" },
+                },
+            }],
+        });
+        strip_source_map_urls(&mut instruction);
+        assert_eq!(
+            instruction["merged"][0]["entries"]["1"],
+            json!({ "code": "a", "url": "/a.js?id=1" })
+        );
     }
 }
@@ -0,0 +1,287 @@
+//! Transports for the update channel between the dev server and HMR clients.
+//!
+//! Browsers connect over the `/turbopack-hmr` WebSocket. Embedders can connect
+//! other clients over an in-memory channel (e.g. an embedded WebView that
+//! forwards the messages to the page, or a test harness) or over a Unix domain
+//! socket. Either way, the messages of the HMR protocol are exchanged as JSON
+//! text.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use anyhow::{Context as _, Error, Result};
+use futures::{channel::mpsc, ready, Sink, Stream, StreamExt};
+use hyper::upgrade::Upgraded;
+use hyper_tungstenite::{tungstenite::Message, WebSocketStream};
+use pin_project_lite::pin_project;
+
+/// A connection to an HMR client, which sends and receives the JSON messages
+/// of the HMR protocol as text. The client disconnected when the stream ends.
+pub trait UpdateConnection:
+    Stream<Item = Result<String>> + Sink<String, Error = Error> + Send + 'static
+{
+}
+
+impl<T> UpdateConnection for T where
+    T: Stream<Item = Result<String>> + Sink<String, Error = Error> + Send + 'static
+{
+}
+
+pin_project! {
+    /// A connection over the `/turbopack-hmr` WebSocket.
+    pub(crate) struct WebSocketConnection {
+        #[pin]
+        ws: WebSocketStream<Upgraded>,
+    }
+}
+
+impl From<WebSocketStream<Upgraded>> for WebSocketConnection {
+    fn from(ws: WebSocketStream<Upgraded>) -> Self {
+        Self { ws }
+    }
+}
+
+impl Stream for WebSocketConnection {
+    type Item = Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(match ready!(self.project().ws.poll_next(cx)) {
+            Some(Ok(Message::Text(msg))) => Some(Ok(msg)),
+            Some(Err(err)) => Some(Err(Error::new(err).context("reading from websocket"))),
+            _ => None,
+        })
+    }
+}
+
+impl Sink<String> for WebSocketConnection {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project()
+            .ws
+            .poll_ready(cx)
+            .map(|res| res.context("polling WebSocket ready"))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: String) -> Result<()> {
+        self.project()
+            .ws
+            .start_send(Message::text(item))
+            .context("sending to WebSocket")
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project()
+            .ws
+            .poll_flush(cx)
+            .map(|res| res.context("flushing WebSocket"))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project()
+            .ws
+            .poll_close(cx)
+            .map(|res| res.context("closing WebSocket"))
+    }
+}
+
+/// Creates an in-memory update channel. The connection is passed to the dev
+/// server, while the client end is used to exchange messages with it.
+pub fn update_channel() -> (UpdateChannelConnection, UpdateChannelClient) {
+    let (client_sender, server_receiver) = mpsc::unbounded();
+    let (server_sender, client_receiver) = mpsc::unbounded();
+    (
+        UpdateChannelConnection {
+            receiver: server_receiver,
+            sender: server_sender,
+        },
+        UpdateChannelClient {
+            sender: client_sender,
+            receiver: client_receiver,
+        },
+    )
+}
+
+pin_project! {
+    /// The dev server's end of an in-memory update channel, see
+    /// [update_channel].
+    pub struct UpdateChannelConnection {
+        #[pin]
+        receiver: mpsc::UnboundedReceiver<String>,
+        #[pin]
+        sender: mpsc::UnboundedSender<String>,
+    }
+}
+
+impl Stream for UpdateChannelConnection {
+    type Item = Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().receiver.poll_next(cx).map(|msg| msg.map(Ok))
+    }
+}
+
+impl Sink<String> for UpdateChannelConnection {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project()
+            .sender
+            .poll_ready(cx)
+            .map(|res| res.context("the update client disconnected"))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: String) -> Result<()> {
+        self.project()
+            .sender
+            .start_send(item)
+            .context("the update client disconnected")
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project()
+            .sender
+            .poll_flush(cx)
+            .map(|res| res.context("the update client disconnected"))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.project()
+            .sender
+            .poll_close(cx)
+            .map(|res| res.context("the update client disconnected"))
+    }
+}
+
+/// The client end of an in-memory update channel, see [update_channel].
+/// Dropping it disconnects the client.
+pub struct UpdateChannelClient {
+    pub(super) sender: mpsc::UnboundedSender<String>,
+    pub(super) receiver: mpsc::UnboundedReceiver<String>,
+}
+
+impl UpdateChannelClient {
+    /// Sends a JSON message of the HMR protocol to the dev server, e.g. to
+    /// subscribe to the updates of a resource.
+    pub fn send(&self, message: String) -> Result<()> {
+        self.sender
+            .unbounded_send(message)
+            .context("the dev server closed the update channel")
+    }
+
+    /// Receives the next JSON message from the dev server, or `None` when it
+    /// closed the update channel.
+    pub async fn recv(&mut self) -> Option<String> {
+        self.receiver.next().await
+    }
+}
+
+/// Accepts update clients on a Unix domain socket and passes a connection for
+/// every client to `connect`. Messages are delimited by newlines on the socket.
+/// The connection ends when the client hangs up.
+#[cfg(unix)]
+pub async fn accept_unix_socket_clients(
+    listener: tokio::net::UnixListener,
+    connect: impl Fn(UpdateChannelConnection),
+) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .context("accepting an update client")?;
+        let (read, mut write) = stream.into_split();
+        let (
+            connection,
+            UpdateChannelClient {
+                sender,
+                mut receiver,
+            },
+        ) = update_channel();
+        // The reader owns the only sender, so the connection ends when the
+        // client hangs up.
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if sender.unbounded_send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        tokio::spawn(async move {
+            while let Some(message) = receiver.next().await {
+                if write.write_all(message.as_bytes()).await.is_err()
+                    || write.write_all(b"\n").await.is_err()
+                {
+                    break;
+                }
+            }
+        });
+        connect(connection);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::SinkExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn update_channel_exchanges_messages() {
+        let (mut connection, mut client) = update_channel();
+
+        client.send("subscribe".to_string()).unwrap();
+        assert_eq!(connection.next().await.unwrap().unwrap(), "subscribe");
+
+        connection.send("update".to_string()).await.unwrap();
+        assert_eq!(client.recv().await.as_deref(), Some("update"));
+
+        drop(client);
+        assert!(connection.next().await.is_none());
+        assert!(connection.send("update".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn update_channel_client_sees_closed_connection() {
+        let (connection, mut client) = update_channel();
+        drop(connection);
+        assert!(client.recv().await.is_none());
+        assert!(client.send("subscribe".to_string()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_connection_ends_when_the_client_hangs_up() {
+        use tokio::{
+            io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+            net::{UnixListener, UnixStream},
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hmr.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let (connections, mut accepted) = mpsc::unbounded();
+        tokio::spawn(accept_unix_socket_clients(listener, move |connection| {
+            connections.unbounded_send(connection).unwrap();
+        }));
+
+        let stream = UnixStream::connect(&path).await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut connection = accepted.next().await.unwrap();
+
+        write.write_all(b"subscribe\n").await.unwrap();
+        assert_eq!(connection.next().await.unwrap().unwrap(), "subscribe");
+
+        connection.send("update".to_string()).await.unwrap();
+        let mut lines = BufReader::new(read).lines();
+        assert_eq!(lines.next_line().await.unwrap().as_deref(), Some("update"));
+
+        drop(write);
+        drop(lines);
+        assert!(connection.next().await.is_none());
+    }
+}
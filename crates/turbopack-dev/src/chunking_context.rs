@@ -1,31 +1,80 @@
+use std::time::Duration;
+
 use anyhow::{bail, Context, Result};
+use indexmap::IndexSet;
 use tracing::Instrument;
 use turbo_tasks::{Value, ValueToString, Vc};
-use turbo_tasks_fs::FileSystemPath;
+use turbo_tasks_fs::{glob::Glob, FileSystemPath};
 use turbopack_core::{
     chunk::{
         availability_info::AvailabilityInfo,
-        chunk_group::{make_chunk_group, MakeChunkGroupResult},
-        Chunk, ChunkGroupResult, ChunkItem, ChunkableModule, ChunkingContext, EvaluatableAssets,
-        ModuleId,
+        cache_group::{ChunkCacheGroup, ChunkCacheGroups},
+        chunk_group::{
+            make_chunk_group, plan_chunk_group, MakeChunkGroupResult, PlanChunkGroupResult,
+        },
+        integrity::chunk_group_integrity,
+        policy::{ChunkingPolicy, OptionChunkingPolicy},
+        precache::{precache_manifest_path, PrecacheManifestAsset},
+        records::{read_chunking_records, unpinned_module_id, ChunkingRecords},
+        usage_profile::{OptionUsageProfile, UsageProfile},
+        Chunk, ChunkGroupResult, ChunkItem, ChunkableModule, ChunkingContext, EvaluatableAsset,
+        EvaluatableAssets, ModuleId, ModuleIdStrategy, OptionChunkItem,
     },
-    environment::Environment,
+    environment::{ChunkLoading, Environment},
+    feature_flags::FeatureFlagSet,
+    hash::HashAlgorithm,
     ident::AssetIdent,
-    module::Module,
+    module::{Module, ModulesSet, OptionModules},
     output::{OutputAsset, OutputAssets},
+    source_map::{OptionSourceMapPrivacy, SourceMapMode, SourceMapPrivacy},
 };
+use turbopack_css::chunk::{CssChunkPlaceable, FailingCssModuleChunkItem};
 use turbopack_ecmascript::{
-    chunk::{EcmascriptChunk, EcmascriptChunkingContext},
+    chunk::{
+        base_paths_global, check_strict_esm, runtime_base_path_expr, EcmascriptChunk,
+        EcmascriptChunkingContext, FailingModuleChunkItem, StrictEsm,
+    },
+    federation::is_identifier,
     manifest::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem},
+    utils::StringifyJs,
+    EcmascriptModuleAsset,
 };
-use turbopack_ecmascript_runtime::RuntimeType;
+use turbopack_ecmascript_runtime::{CrossOrigin, RuntimeType};
 
 use crate::ecmascript::{
     chunk::EcmascriptDevChunk,
+    content_entry::check_module_id_collisions,
     evaluate::chunk::EcmascriptDevEvaluateChunk,
     list::asset::{EcmascriptDevChunkList, EcmascriptDevChunkListSource},
+    worklet::EcmascriptDevWorkletChunk,
 };
 
+/// How a [DevChunkingContext] resolves the base paths chunks and static assets
+/// are loaded from.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum BasePathMode {
+    /// The configured base paths are embedded in the chunks.
+    #[default]
+    Static,
+    /// The base paths are read at runtime from the [base_paths_global] of the
+    /// chunk loading global, so that they can differ per request, e.g. per
+    /// tenant. It's set by a config script loaded before the chunks. The
+    /// configured base paths are used when it isn't set.
+    Runtime,
+    /// Like [BasePathMode::Runtime], but when the global isn't set, the
+    /// evaluated chunks set it to the URL they are loaded from, derived from
+    /// `document.currentScript` (or `import.meta.url` with ES module chunks).
+    /// This allows to load the chunks from hosts or path prefixes that aren't
+    /// known at build time, e.g. when they are embedded into third-party pages
+    /// or served behind a reverse proxy.
+    Auto,
+}
+
+/// The global variable chunks register with, unless another one is configured
+/// with [DevChunkingContextBuilder::chunk_loading_global].
+pub(crate) const DEFAULT_CHUNK_LOADING_GLOBAL: &str = "TURBOPACK";
+
 pub struct DevChunkingContextBuilder {
     chunking_context: DevChunkingContext,
 }
@@ -41,26 +90,373 @@ impl DevChunkingContextBuilder {
         self
     }
 
+    /// Names static assets by their content hash only, so that identical files
+    /// referenced from different source paths (e.g. copied assets or fonts
+    /// duplicated across packages) are emitted once, and all references point
+    /// to the same output.
+    pub fn dedupe_assets_by_content(mut self) -> Self {
+        self.chunking_context.dedupe_assets_by_content = true;
+        self
+    }
+
+    /// Includes a hash of the content in the names of JavaScript and CSS
+    /// chunks, so that a caching proxy in front of the dev server never serves
+    /// stale chunks after a rebuild. The chunk lists and entry chunks keep
+    /// their names, so they must not be cached.
+    pub fn content_hash_chunk_names(mut self) -> Self {
+        self.chunking_context.content_hash_chunk_names = true;
+        self
+    }
+
+    /// Names chunks by `template` instead of the flattened path of their
+    /// entry, e.g. `[dir]/[name].[hash8].[ext]`. The placeholders are:
+    ///
+    /// * `[dir]`: the directory of the entry, relative to the context path
+    /// * `[name]`: the file name of the entry without its extension
+    /// * `[hash]`, `[hash8]`: the hash of the chunk ident, or of the chunk
+    ///   content with [Self::content_hash_chunk_names], and its first 8
+    ///   characters
+    /// * `[ext]`: the extension of the chunk, without the leading dot
+    ///
+    /// Different chunks of the same entry are only named differently by the
+    /// hash, so templates should include `[hash]` or `[hash8]`.
+    pub fn chunk_filename_template(mut self, template: String) -> Self {
+        self.chunking_context.chunk_filename_template = Some(template);
+        self
+    }
+
+    pub fn hash_algorithm(mut self, hash_algorithm: HashAlgorithm) -> Self {
+        self.chunking_context.hash_algorithm = hash_algorithm;
+        self
+    }
+
     pub fn chunk_base_path(mut self, chunk_base_path: Vc<Option<String>>) -> Self {
         self.chunking_context.chunk_base_path = chunk_base_path;
         self
     }
 
-    pub fn reference_chunk_source_maps(mut self, source_maps: bool) -> Self {
+    /// Sets how the chunk and asset base paths are resolved, see
+    /// [BasePathMode].
+    pub fn base_path_mode(mut self, base_path_mode: BasePathMode) -> Self {
+        self.chunking_context.base_path_mode = base_path_mode;
+        self
+    }
+
+    /// Computes Subresource Integrity hashes of the chunks, which the runtime
+    /// sets on the `<script>` and `<link>` tags it injects to load them. The
+    /// hashes are also exposed on [ChunkGroupResult::integrity], e.g. for
+    /// tags in server-rendered HTML.
+    pub fn subresource_integrity(mut self) -> Self {
+        self.chunking_context.subresource_integrity = true;
+        self
+    }
+
+    /// Loads chunks as ES modules, with `<script type="module">` tags in HTML
+    /// pages and `import()` in the runtime, so they are debugged with native
+    /// module semantics. Chunks are evaluated in strict mode, and loaded in
+    /// order like deferred scripts. Entry chunks use top-level await to wait
+    /// for asynchronous entries, and read their URL from `import.meta.url`.
+    /// Web workers still load chunks as classic scripts.
+    pub fn esm_chunks(mut self) -> Self {
+        self.chunking_context.esm_chunks = true;
+        self
+    }
+
+    pub fn build_id(mut self, build_id: Vc<Option<String>>) -> Self {
+        self.chunking_context.build_id = build_id;
+        self
+    }
+
+    /// Places the chunks of chunk groups whose entry path (relative to the
+    /// context path) matches `glob` into `subdirectory` of the chunk root path.
+    /// The first matching mapping wins. Chunk groups that don't match any
+    /// mapping inherit the subdirectory of their parent chunk group.
+    pub fn chunk_group_output_dir(mut self, glob: String, subdirectory: String) -> Self {
+        self.chunking_context
+            .chunk_group_output_dirs
+            .push((glob, subdirectory));
+        self
+    }
+
+    /// Places the chunks of modules in `layer` (see [AssetIdent::layer]) at
+    /// `chunk_root_path` instead of the default chunk root path, e.g. to emit
+    /// server and client chunks from the same chunking context into different
+    /// directories. Chunks of modules from different layers use the default.
+    pub fn layer_chunk_root_path(
+        mut self,
+        layer: String,
+        chunk_root_path: Vc<FileSystemPath>,
+    ) -> Self {
+        self.chunking_context
+            .layer_chunk_root_paths
+            .push((layer, chunk_root_path));
+        self
+    }
+
+    /// Registers a module (e.g. a router, refresh runtime or telemetry client)
+    /// that is evaluated before the entries of every evaluated chunk group.
+    /// Runtime globals are evaluated in the order they are registered.
+    pub fn runtime_global(mut self, module: Vc<Box<dyn EvaluatableAsset>>) -> Self {
+        self.chunking_context.runtime_globals.push(module);
+        self
+    }
+
+    pub fn reference_chunk_source_maps(mut self, source_maps: SourceMapMode) -> Self {
         self.chunking_context.reference_chunk_source_maps = source_maps;
         self
     }
 
-    pub fn reference_css_chunk_source_maps(mut self, source_maps: bool) -> Self {
+    pub fn reference_css_chunk_source_maps(mut self, source_maps: SourceMapMode) -> Self {
         self.chunking_context.reference_css_chunk_source_maps = source_maps;
         self
     }
 
+    pub fn source_map_privacy(mut self, privacy: Vc<SourceMapPrivacy>) -> Self {
+        self.chunking_context.source_map_privacy = Some(privacy);
+        self
+    }
+
     pub fn runtime_type(mut self, runtime_type: RuntimeType) -> Self {
         self.chunking_context.runtime_type = runtime_type;
         self
     }
 
+    /// Replaces modules that fail to build with stubs that throw the error at
+    /// runtime, instead of failing the whole chunk group. CSS modules are
+    /// replaced with empty stylesheets.
+    pub fn stub_failing_modules(mut self, stub_failing_modules: bool) -> Self {
+        self.chunking_context.stub_failing_modules = stub_failing_modules;
+        self
+    }
+
+    /// Reports modules whose generated code exceeds `threshold` bytes as
+    /// issues.
+    pub fn module_size_warning_threshold(mut self, threshold: u64) -> Self {
+        self.chunking_context.module_size_warning_threshold = Some(threshold);
+        self
+    }
+
+    /// Reports packages that are placed into a chunk group together with a
+    /// version of one of their peer dependencies outside of the declared
+    /// range.
+    pub fn check_peer_dependencies(mut self) -> Self {
+        self.chunking_context.check_peer_dependencies = true;
+        self
+    }
+
+    /// Merges chunks smaller than `bytes` with other chunks when possible.
+    /// Must not be larger than the [max_chunk_size](Self::max_chunk_size).
+    pub fn min_chunk_size(mut self, bytes: u64) -> Self {
+        self.chunking_context.min_chunk_size = Some(bytes);
+        self
+    }
+
+    /// Splits chunks of at least `bytes` further when possible. Single
+    /// modules are never split. Must not be smaller than the
+    /// [min_chunk_size](Self::min_chunk_size).
+    pub fn max_chunk_size(mut self, bytes: u64) -> Self {
+        self.chunking_context.max_chunk_size = Some(bytes);
+        self
+    }
+
+    /// Whether modules in `node_modules` are placed into separate chunks,
+    /// apart from app code. Defaults to `true`. Disabling it results in
+    /// fewer, larger chunks, which can load faster when each request has a
+    /// high latency, e.g. in remote development containers.
+    pub fn split_node_modules(mut self, split_node_modules: bool) -> Self {
+        self.chunking_context.split_node_modules = split_node_modules;
+        self
+    }
+
+    /// Keeps the content of vendor chunks independent of app code, so that
+    /// the browser can cache large vendor chunks for the whole dev session.
+    /// Vendor modules can then be loaded by several chunk groups.
+    pub fn isolate_vendor_chunks(mut self, isolate_vendor_chunks: bool) -> Self {
+        self.chunking_context.isolate_vendor_chunks = isolate_vendor_chunks;
+        self
+    }
+
+    /// Whether JavaScript chunks are minified, e.g. to serve dev builds over
+    /// slow connections. Only local names are mangled and whitespace is
+    /// removed, which is fast, and source maps map to the original code. HMR
+    /// updates aren't minified. Defaults to `false`.
+    pub fn minify(mut self, minify: bool) -> Self {
+        self.chunking_context.minify = minify;
+        self
+    }
+
+    /// Transforms the code of JavaScript chunks so that it runs in
+    /// `environment`, e.g. to debug a dev build in a legacy browser. Modules
+    /// are still compiled for the environment of the chunking context, and
+    /// HMR updates aren't transformed.
+    pub fn downlevel(mut self, environment: Vc<Environment>) -> Self {
+        self.chunking_context.downlevel_environment = Some(environment);
+        self
+    }
+
+    /// Limits the number of chunks of each chunk group to
+    /// `max_parallel_requests` by merging chunks, so that a chunk group
+    /// doesn't need hundreds of requests to load.
+    pub fn max_parallel_requests(mut self, max_parallel_requests: u64) -> Self {
+        self.chunking_context.max_chunks_per_chunk_group = Some(max_parallel_requests);
+        self
+    }
+
+    /// Customizes how modules are assigned to chunks with `policy`, which takes
+    /// precedence over the cache groups and the default heuristics.
+    pub fn chunking_policy(mut self, policy: Vc<Box<dyn ChunkingPolicy>>) -> Self {
+        self.chunking_context.chunking_policy = Some(policy);
+        self
+    }
+
+    /// Places the modules matching `cache_group` into chunks of their own,
+    /// named after the group, e.g. to keep rarely changing framework code
+    /// apart from other vendor code. A module belongs to the first group it
+    /// matches, in the order the groups are registered.
+    pub fn cache_group(mut self, cache_group: ChunkCacheGroup) -> Self {
+        self.chunking_context.cache_groups.push(cache_group);
+        self
+    }
+
+    /// Places the modules executed according to `usage_profile` into the
+    /// first chunks of chunk groups.
+    pub fn usage_profile(mut self, usage_profile: Vc<UsageProfile>) -> Self {
+        self.chunking_context.usage_profile = Some(usage_profile);
+        self
+    }
+
+    /// Places `split_points` into their own async chunk groups, even when they
+    /// are imported statically.
+    pub fn split_points(mut self, split_points: Vc<ModulesSet>) -> Self {
+        self.chunking_context.split_points = split_points;
+        self
+    }
+
+    /// Makes `chunk_group` and `evaluated_chunk_group` return a plan of the
+    /// chunks they would create, see [turbopack_core::chunk::plan], without
+    /// generating any assets.
+    pub fn dry_run(mut self) -> Self {
+        self.chunking_context.dry_run = true;
+        self
+    }
+
+    /// Marks the chunks as belonging to the `feature_flags` variant of the
+    /// project, by including the enabled flags in their paths. This lets the
+    /// chunks of several variants be emitted to the same output root.
+    pub fn feature_flags(mut self, feature_flags: Vc<FeatureFlagSet>) -> Self {
+        self.chunking_context.feature_flags = Some(feature_flags);
+        self
+    }
+
+    /// Makes the runtime never evaluate code with `eval` or `new Function`,
+    /// which is required by edge runtimes and by strict Content Security
+    /// Policies (e.g. in browser extensions). The runtime then doesn't contain
+    /// such code at all, so it also passes static checks for it. Hot updates
+    /// that add or change modules reload the page instead of being applied in
+    /// place.
+    pub fn disallow_eval(mut self) -> Self {
+        self.chunking_context.allow_eval = false;
+        self
+    }
+
+    /// Makes the runtime set the nonce read from the global variable `name`
+    /// (e.g. `__webpack_nonce__`) on every `<script>` and `<link>` tag it
+    /// injects, so that chunks load under a nonce-based Content Security
+    /// Policy. The variable is read whenever a tag is injected, so it can be
+    /// set after the runtime is loaded.
+    pub fn csp_nonce_var(mut self, name: String) -> Self {
+        self.chunking_context.csp_nonce_var = Some(name);
+        self
+    }
+
+    /// Sets the `crossorigin` attribute of the `<script>` and `<link>` tags the
+    /// runtime injects, e.g. when the chunks are served from a different
+    /// origin than the page. This makes the browser send credentials as
+    /// configured and report the actual errors of cross-origin scripts
+    /// instead of "Script error".
+    pub fn cross_origin(mut self, cross_origin: CrossOrigin) -> Self {
+        self.chunking_context.cross_origin = Some(cross_origin);
+        self
+    }
+
+    /// Makes the runtime retry loading a chunk up to `retries` times when it
+    /// fails to load, e.g. because the dev server was briefly unreachable,
+    /// instead of failing the import with a `ChunkLoadError` until the page is
+    /// reloaded.
+    pub fn chunk_load_retries(mut self, retries: u32) -> Self {
+        self.chunking_context.chunk_load_retries = retries;
+        self
+    }
+
+    /// The delay before the first retry of loading a chunk, see
+    /// [Self::chunk_load_retries]. It doubles with every further retry.
+    /// Defaults to 500ms.
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.chunking_context.retry_delay = delay;
+        self
+    }
+
+    /// Sets how the ids of modules are derived from their idents. The default
+    /// [ModuleIdStrategy::Named] ids are readable, while numeric and hashed
+    /// ids behave like production ids, e.g. to test id collisions, and make
+    /// chunks smaller.
+    pub fn module_id_strategy(mut self, strategy: ModuleIdStrategy) -> Self {
+        self.chunking_context.module_id_strategy = strategy;
+        self
+    }
+
+    /// Pins the ids of modules and the names of chunks recorded in the JSON
+    /// file at `path`, so that browser caches and running HMR sessions stay
+    /// valid after a restart of the dev server. The file is written by a
+    /// [ChunkingRecordsAsset](crate::records::ChunkingRecordsAsset), and
+    /// isn't required to exist.
+    pub fn records(mut self, path: Vc<FileSystemPath>) -> Self {
+        self.chunking_context.records_path = Some(path);
+        self
+    }
+
+    /// Inlines workers whose code is at most `max_size` bytes into the chunk
+    /// that starts them, so they are started from a blob URL instead of a
+    /// chunk URL, which must be same-origin. Inlined workers can't use
+    /// `import()`.
+    pub fn inline_workers(mut self, max_size: u64) -> Self {
+        self.chunking_context.inline_worker_size_limit = Some(max_size);
+        self
+    }
+
+    /// Makes `evaluated_chunk_group` emit only the imported CSS (or other
+    /// static assets) when all entries consist of imports of CSS only, e.g.
+    /// for a marketing page. No runtime is emitted for such chunk groups, so
+    /// their CSS isn't hot reloaded.
+    pub fn static_chunk_groups(mut self) -> Self {
+        self.chunking_context.static_chunk_groups = true;
+        self
+    }
+
+    /// Reports the modules of chunk groups that use CommonJS, with the chain
+    /// of modules importing them, e.g. to complete a migration to ES modules.
+    /// With [StrictEsm::Error], such modules fail the build.
+    pub fn strict_esm(mut self, mode: StrictEsm) -> Self {
+        self.chunking_context.strict_esm = mode;
+        self
+    }
+
+    /// Sets the global variable chunks register with, which defaults to
+    /// `TURBOPACK`. Apps loaded into the same page, e.g. a host and the remote
+    /// entries it consumes with module federation, need distinct globals so
+    /// that each of their runtimes only registers its own chunks. Fails if
+    /// `name` isn't a valid JavaScript identifier.
+    pub fn chunk_loading_global(mut self, name: String) -> Result<Self> {
+        if !is_identifier(&name) {
+            bail!(
+                "the chunk loading global {} is not a valid JavaScript identifier",
+                name
+            );
+        }
+        self.chunking_context.chunk_loading_global = name;
+        Ok(self)
+    }
+
     pub fn build(self) -> Vc<DevChunkingContext> {
         DevChunkingContext::new(Value::new(self.chunking_context))
     }
@@ -81,21 +477,115 @@ pub struct DevChunkingContext {
     output_root: Vc<FileSystemPath>,
     /// Chunks are placed at this path
     chunk_root_path: Vc<FileSystemPath>,
-    /// Chunks reference source maps assets
-    reference_chunk_source_maps: bool,
-    /// Css chunks reference source maps assets
-    reference_css_chunk_source_maps: bool,
+    /// Chunks of modules in these layers are placed at these paths instead
+    layer_chunk_root_paths: Vec<(String, Vc<FileSystemPath>)>,
+    /// Maps globs matching the entries of chunk groups to the subdirectory of
+    /// `chunk_root_path` their chunks are placed in.
+    chunk_group_output_dirs: Vec<(String, String)>,
+    /// The subdirectory of `chunk_root_path` the chunks of the current chunk
+    /// group are placed in.
+    chunk_group_output_dir: Option<String>,
+    /// The name of the current chunk group, which the chunks are named after.
+    chunk_group_name: Option<String>,
+    /// How chunks reference their source maps
+    reference_chunk_source_maps: SourceMapMode,
+    /// How Css chunks reference their source maps
+    reference_css_chunk_source_maps: SourceMapMode,
+    /// Which sources to redact from source maps
+    source_map_privacy: Option<Vc<SourceMapPrivacy>>,
     /// Static assets are placed at this path
     asset_root_path: Vc<FileSystemPath>,
+    /// Whether static assets are named by their content hash only
+    dedupe_assets_by_content: bool,
+    /// Whether the names of chunks include a hash of their content
+    content_hash_chunk_names: bool,
+    /// Whether JavaScript chunks are minified
+    minify: bool,
+    /// The environment the code of JavaScript chunks is transformed for
+    downlevel_environment: Option<Vc<Environment>>,
+    /// The template chunks are named by
+    chunk_filename_template: Option<String>,
+    /// The hash function used for content hashes and chunk idents
+    hash_algorithm: HashAlgorithm,
     /// Base path that will be prepended to all chunk URLs when loading them.
     /// This path will not appear in chunk paths or chunk data.
     chunk_base_path: Vc<Option<String>>,
     /// URL prefix that will be prepended to all static asset URLs when loading
     /// them.
     asset_base_path: Vc<Option<String>>,
+    /// How the base paths are resolved
+    base_path_mode: BasePathMode,
+    /// Whether Subresource Integrity hashes are computed for chunks
+    subresource_integrity: bool,
+    /// Whether chunks are loaded as ES modules
+    esm_chunks: bool,
+    /// An identifier of the current build. It is embedded in chunk lists,
+    /// evaluate chunks and HMR updates so the runtime can detect that it is
+    /// talking to a different build (e.g. stale HTML referencing old chunks)
+    /// and reload.
+    build_id: Vc<Option<String>>,
+    /// Modules that are evaluated before the entries of every evaluated chunk
+    /// group.
+    runtime_globals: Vec<Vc<Box<dyn EvaluatableAsset>>>,
     /// Enable HMR for this chunking
     enable_hot_module_replacement: bool,
-    /// The environment chunks will be evaluated in.
+    /// Replace modules that fail to build with stubs throwing the error
+    stub_failing_modules: bool,
+    /// Report modules whose generated code exceeds this size in bytes
+    module_size_warning_threshold: Option<u64>,
+    /// Report peer dependencies outside of the range declared by packages
+    check_peer_dependencies: bool,
+    /// Merge chunks smaller than this size in bytes
+    min_chunk_size: Option<u64>,
+    /// Split chunks of at least this size in bytes
+    max_chunk_size: Option<u64>,
+    /// Whether node_modules are placed into separate chunks
+    split_node_modules: bool,
+    /// Whether vendor chunks are independent of app code
+    isolate_vendor_chunks: bool,
+    /// The maximum number of chunks of a chunk group
+    max_chunks_per_chunk_group: Option<u64>,
+    /// Named groups of modules that are placed into their own chunks
+    cache_groups: Vec<ChunkCacheGroup>,
+    /// Customizes how modules are assigned to chunks
+    chunking_policy: Option<Vc<Box<dyn ChunkingPolicy>>>,
+    /// The modules executed during a session, which are placed into the first
+    /// chunks of chunk groups
+    usage_profile: Option<Vc<UsageProfile>>,
+    /// Modules that are placed in their own async chunk groups
+    split_points: Vc<ModulesSet>,
+    /// Only plan the chunks of chunk groups instead of generating them
+    dry_run: bool,
+    /// Whether the runtime may evaluate hot updates with `eval`
+    allow_eval: bool,
+    /// The global variable the runtime reads the CSP nonce of injected tags
+    /// from
+    csp_nonce_var: Option<String>,
+    /// The `crossorigin` attribute of the tags the runtime injects
+    cross_origin: Option<CrossOrigin>,
+    /// How often the runtime retries loading a chunk
+    chunk_load_retries: u32,
+    /// The delay before the first retry of loading a chunk
+    retry_delay: Duration,
+    /// Whether chunk groups of entries that only import CSS are emitted
+    /// without JavaScript
+    static_chunk_groups: bool,
+    /// Workers up to this size in bytes are inlined into the chunk starting
+    /// them
+    inline_worker_size_limit: Option<u64>,
+    /// How the ids of modules are derived from their idents
+    module_id_strategy: ModuleIdStrategy,
+    /// The file pinning module ids and chunk names across restarts
+    records_path: Option<Vc<FileSystemPath>>,
+    /// The feature flag variant the chunks belong to
+    feature_flags: Option<Vc<FeatureFlagSet>>,
+    /// How CommonJS modules in chunk groups are reported
+    strict_esm: StrictEsm,
+    /// The global variable chunks register with
+    chunk_loading_global: String,
+    /// The environment chunks will be evaluated in. In a Node.js environment,
+    /// evaluated chunks are CommonJS modules that can be loaded with
+    /// `require()`, e.g. for server-side rendering in development.
     environment: Vc<Environment>,
     /// The kind of runtime to include in the output.
     runtime_type: RuntimeType,
@@ -114,12 +604,53 @@ impl DevChunkingContext {
                 context_path,
                 output_root,
                 chunk_root_path,
-                reference_chunk_source_maps: true,
-                reference_css_chunk_source_maps: true,
+                layer_chunk_root_paths: Vec::new(),
+                chunk_group_output_dirs: Vec::new(),
+                chunk_group_output_dir: None,
+                chunk_group_name: None,
+                reference_chunk_source_maps: SourceMapMode::External,
+                reference_css_chunk_source_maps: SourceMapMode::External,
+                source_map_privacy: None,
                 asset_root_path,
                 chunk_base_path: Default::default(),
                 asset_base_path: Default::default(),
+                base_path_mode: Default::default(),
+                subresource_integrity: false,
+                esm_chunks: false,
+                dedupe_assets_by_content: false,
+                content_hash_chunk_names: false,
+                minify: false,
+                downlevel_environment: None,
+                chunk_filename_template: None,
+                hash_algorithm: Default::default(),
+                build_id: Default::default(),
+                runtime_globals: Vec::new(),
                 enable_hot_module_replacement: false,
+                stub_failing_modules: false,
+                module_size_warning_threshold: None,
+                check_peer_dependencies: false,
+                min_chunk_size: None,
+                max_chunk_size: None,
+                split_node_modules: true,
+                isolate_vendor_chunks: false,
+                max_chunks_per_chunk_group: None,
+                cache_groups: Vec::new(),
+                chunking_policy: None,
+                usage_profile: None,
+                split_points: ModulesSet::empty(),
+                dry_run: false,
+                allow_eval: true,
+                csp_nonce_var: None,
+                cross_origin: None,
+                chunk_load_retries: 0,
+                retry_delay: Duration::from_millis(500),
+                static_chunk_groups: false,
+                inline_worker_size_limit: None,
+                module_id_strategy: ModuleIdStrategy::Named,
+                records_path: None,
+                feature_flags: None,
+                strict_esm: StrictEsm::Off,
+                chunk_loading_global: DEFAULT_CHUNK_LOADING_GLOBAL.to_string(),
                 environment,
                 runtime_type: Default::default(),
             },
@@ -140,6 +671,87 @@ impl DevChunkingContext {
     pub fn chunk_base_path(&self) -> Vc<Option<String>> {
         self.chunk_base_path
     }
+
+    /// How the base paths are resolved.
+    pub fn base_path_mode(&self) -> BasePathMode {
+        self.base_path_mode
+    }
+
+    /// Returns the build id, if any.
+    pub fn build_id(&self) -> Vc<Option<String>> {
+        self.build_id
+    }
+
+    /// Whether the runtime may evaluate code with `eval`.
+    pub fn allow_eval(&self) -> bool {
+        self.allow_eval
+    }
+
+    /// The global variable the runtime reads the CSP nonce from, if any.
+    pub fn csp_nonce_var(&self) -> Option<String> {
+        self.csp_nonce_var.clone()
+    }
+
+    /// The `crossorigin` attribute of the tags the runtime injects, if any.
+    pub fn cross_origin(&self) -> Option<CrossOrigin> {
+        self.cross_origin
+    }
+
+    /// How often the runtime retries loading a chunk.
+    pub fn chunk_load_retries(&self) -> u32 {
+        self.chunk_load_retries
+    }
+
+    /// The delay before the first retry of loading a chunk.
+    pub fn retry_delay(&self) -> Duration {
+        self.retry_delay
+    }
+
+    /// Whether JavaScript chunks are minified.
+    pub fn minify(&self) -> bool {
+        self.minify
+    }
+
+    /// The environment the code of JavaScript chunks is transformed for, if
+    /// any.
+    pub fn downlevel_environment(&self) -> Option<Vc<Environment>> {
+        self.downlevel_environment
+    }
+
+    /// The global variable chunks register with.
+    pub fn chunk_loading_global(&self) -> &str {
+        &self.chunk_loading_global
+    }
+
+    /// The file pinning module ids and chunk names across restarts, if any.
+    pub fn records_path(&self) -> Option<Vc<FileSystemPath>> {
+        self.records_path
+    }
+
+    /// Whether different modules can get the same id, which is the case when
+    /// ids are shortened or pinned by records.
+    pub fn module_ids_can_collide(&self) -> bool {
+        !matches!(self.module_id_strategy, ModuleIdStrategy::Named) || self.records_path.is_some()
+    }
+
+    /// The directory the chunk with `ident` is placed in.
+    async fn chunk_dir(&self, ident: Vc<AssetIdent>) -> Result<Vc<FileSystemPath>> {
+        let mut chunk_root_path = self.chunk_root_path;
+        if let Some(layer) = ident.await?.layer {
+            let layer = layer.await?;
+            if let Some((_, layer_chunk_root_path)) = self
+                .layer_chunk_root_paths
+                .iter()
+                .find(|(name, _)| *name == *layer)
+            {
+                chunk_root_path = *layer_chunk_root_path;
+            }
+        }
+        Ok(match &self.chunk_group_output_dir {
+            Some(subdirectory) => chunk_root_path.join(subdirectory.clone()),
+            None => chunk_root_path,
+        })
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -149,18 +761,262 @@ impl DevChunkingContext {
         this.into_value().cell()
     }
 
+    /// Returns the URL of the static asset with `ident` relative to the asset
+    /// base path.
+    #[turbo_tasks::function]
+    async fn asset_url_path(&self, ident: Vc<AssetIdent>) -> Result<Vc<String>> {
+        let asset_path = ident.path().await?.to_string();
+        let asset_path = asset_path
+            .strip_prefix(&format!("{}/", self.output_root.await?.path))
+            .context("expected output_root to contain asset path")?;
+        Ok(Vc::cell(asset_path.to_string()))
+    }
+
+    /// Prepends the runtime globals to `evaluatable_assets`, so they are
+    /// initialized before any of the entries. Entries that are runtime globals
+    /// themselves are only evaluated once.
+    #[turbo_tasks::function]
+    async fn with_runtime_globals(
+        &self,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<EvaluatableAssets>> {
+        if self.runtime_globals.is_empty() {
+            return Ok(evaluatable_assets);
+        }
+        let mut assets = IndexSet::new();
+        for &asset in self
+            .runtime_globals
+            .iter()
+            .chain(evaluatable_assets.await?.iter())
+        {
+            assets.insert(asset.resolve().await?);
+        }
+        Ok(EvaluatableAssets::many(assets.into_iter().collect()))
+    }
+
+    /// The modules imported by `evaluatable_assets` when the chunk group of
+    /// them can be emitted without JavaScript, see
+    /// [EcmascriptModuleAsset::static_imports].
+    #[turbo_tasks::function]
+    async fn static_entry_imports(
+        &self,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Result<Vc<OptionModules>> {
+        if !self.static_chunk_groups || self.dry_run {
+            return Ok(Vc::cell(None));
+        }
+        let mut modules = Vec::new();
+        for &evaluatable in evaluatable_assets.await?.iter() {
+            let Some(module) =
+                Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(evaluatable).await?
+            else {
+                return Ok(Vc::cell(None));
+            };
+            let Some(imports) = *module.static_imports().await? else {
+                return Ok(Vc::cell(None));
+            };
+            modules.extend(imports.await?.iter().copied());
+        }
+        Ok(Vc::cell(Some(Vc::cell(modules))))
+    }
+
+    /// The chunk group of `module`. When `name` is given, the chunks are named
+    /// after it.
+    #[turbo_tasks::function]
+    async fn chunk_group_with_name(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+        name: Option<String>,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        let chunking_context = self.for_chunk_group(module.ident()).resolve().await?;
+        if chunking_context != self {
+            return Ok(chunking_context.chunk_group_with_name(module, availability_info, name));
+        }
+        let span = tracing::info_span!("chunking", module = *module.ident().to_string().await?);
+        async move {
+            // Only the output assets are named, the chunk items are still
+            // created by this chunking context to dedupe them with the
+            // available chunk items
+            let output_chunking_context = match name {
+                Some(name) => self.with_chunk_group_name(name),
+                None => self,
+            };
+
+            if self.await?.dry_run {
+                let PlanChunkGroupResult {
+                    plan,
+                    availability_info,
+                } = plan_chunk_group(
+                    Vc::upcast(self),
+                    [Vc::upcast(module)],
+                    availability_info.into_value(),
+                    |chunk| output_chunking_context.generate_chunk(chunk),
+                )
+                .await?;
+                return Ok(ChunkGroupResult {
+                    assets: OutputAssets::empty(),
+                    availability_info,
+                    plan: Some(plan),
+                    static_only: false,
+                    integrity: None,
+                }
+                .cell());
+            }
+
+            let MakeChunkGroupResult {
+                chunks,
+                availability_info,
+                ..
+            } = make_chunk_group(
+                Vc::upcast(self),
+                [Vc::upcast(module)],
+                availability_info.into_value(),
+            )
+            .await?;
+            check_module_id_collisions(self, &chunks).await?;
+
+            if self.await?.strict_esm != StrictEsm::Off {
+                check_strict_esm(Vc::upcast(self), Vc::cell(vec![Vc::upcast(module)])).await?;
+            }
+
+            let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
+                .iter()
+                .map(|chunk| output_chunking_context.generate_chunk(*chunk))
+                .collect();
+
+            assets.push(self.generate_chunk_list_register_chunk(
+                module.ident(),
+                EvaluatableAssets::empty(),
+                Vc::cell(assets.clone()),
+                Value::new(EcmascriptDevChunkListSource::Dynamic),
+            ));
+
+            // Resolve assets
+            for asset in assets.iter_mut() {
+                *asset = asset.resolve().await?;
+            }
+
+            let assets = Vc::cell(assets);
+            Ok(ChunkGroupResult {
+                assets,
+                availability_info,
+                plan: None,
+                static_only: false,
+                integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
+            }
+            .cell())
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// The ident chunks with `ident` are named after, which includes the
+    /// feature flag variant of the chunk. Chunk names are recorded by this
+    /// ident.
+    #[turbo_tasks::function]
+    pub(crate) async fn chunk_ident(&self, ident: Vc<AssetIdent>) -> Result<Vc<AssetIdent>> {
+        Ok(match self.feature_flags {
+            Some(feature_flags) => ident.with_modifier(Vc::cell(format!(
+                "feature flags {}",
+                feature_flags.key().await?
+            ))),
+            None => ident,
+        })
+    }
+
+    /// The path of a chunk named by the filename template. The hash of the
+    /// ident is used for `[hash]` unless a `hash` is given.
+    #[turbo_tasks::function]
+    async fn templated_chunk_path(
+        &self,
+        ident: Vc<AssetIdent>,
+        extension: String,
+        hash: Option<Vc<String>>,
+    ) -> Result<Vc<FileSystemPath>> {
+        let Some(template) = &self.chunk_filename_template else {
+            bail!("chunk filename template is not configured");
+        };
+        let path = ident.path().await?;
+        let context_path = self.context_path.await?;
+        let relative_path = context_path
+            .get_path_to(&path)
+            .unwrap_or(path.path.as_str());
+        let (dir, file_name) = relative_path
+            .rsplit_once('/')
+            .unwrap_or(("", relative_path));
+        let stem = file_name
+            .split_once('.')
+            .map_or(file_name, |(stem, _)| stem);
+        let name = match &self.chunk_group_name {
+            Some(chunk_group_name) => format!("{chunk_group_name}-{stem}"),
+            None => stem.to_string(),
+        };
+        let hash = match hash {
+            Some(hash) => hash.await?.clone_value(),
+            None => self
+                .hash_algorithm
+                .hash_hex(ident.to_string().await?.as_str()),
+        };
+        let root_path = self.chunk_dir(ident).await?;
+        Ok(root_path.join(apply_chunk_filename_template(
+            template, dir, &name, &hash, &extension,
+        )))
+    }
+
+    /// This chunking context, but naming chunks after `name`. The name comes
+    /// from the source code, so it's reduced to a file name, see
+    /// [chunk_group_name_slug].
+    #[turbo_tasks::function]
+    async fn with_chunk_group_name(self: Vc<Self>, name: String) -> Result<Vc<Self>> {
+        let mut chunking_context = self.await?.clone_value();
+        chunking_context.chunk_group_name = Some(chunk_group_name_slug(&name));
+        Ok(chunking_context.cell())
+    }
+
+    /// Returns the chunking context to use for the chunk group with the entry
+    /// `ident`, which places chunks into the subdirectory configured for that
+    /// entry.
+    #[turbo_tasks::function]
+    async fn for_chunk_group(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<Self>> {
+        let this = self.await?;
+        if this.chunk_group_output_dirs.is_empty() {
+            return Ok(self);
+        }
+        let context_path = this.context_path.await?;
+        let entry_path = ident.path().await?;
+        let Some(entry_path) = context_path.get_path_to(&entry_path) else {
+            return Ok(self);
+        };
+        for (glob, subdirectory) in &this.chunk_group_output_dirs {
+            if !Glob::new(glob.clone()).await?.execute(entry_path) {
+                continue;
+            }
+            if this.chunk_group_output_dir.as_ref() == Some(subdirectory) {
+                return Ok(self);
+            }
+            let mut chunking_context = this.clone_value();
+            chunking_context.chunk_group_output_dir = Some(subdirectory.clone());
+            return Ok(chunking_context.cell());
+        }
+        Ok(self)
+    }
+
     #[turbo_tasks::function]
     fn generate_evaluate_chunk(
         self: Vc<Self>,
         ident: Vc<AssetIdent>,
         other_chunks: Vc<OutputAssets>,
+        deferred_chunks: Vc<OutputAssets>,
         evaluatable_assets: Vc<EvaluatableAssets>,
     ) -> Vc<Box<dyn OutputAsset>> {
         Vc::upcast(EcmascriptDevEvaluateChunk::new(
             self,
             ident,
             other_chunks,
+            deferred_chunks,
             evaluatable_assets,
+            false,
         ))
     }
 
@@ -221,31 +1077,84 @@ impl ChunkingContext for DevChunkingContext {
 
     #[turbo_tasks::function]
     async fn chunk_path(
-        &self,
+        self: Vc<Self>,
         ident: Vc<AssetIdent>,
         extension: String,
     ) -> Result<Vc<FileSystemPath>> {
-        let root_path = self.chunk_root_path;
-        let name = ident.output_name(self.context_path, extension).await?;
-        Ok(root_path.join(name.clone_value()))
-    }
-
-    #[turbo_tasks::function]
-    async fn asset_url(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<String>> {
         let this = self.await?;
-        let asset_path = ident.path().await?.to_string();
-        let asset_path = asset_path
-            .strip_prefix(&format!("{}/", this.output_root.await?.path))
-            .context("expected output_root to contain asset path")?;
+        let ident = self.chunk_ident(ident).resolve().await?;
+        if let Some(name) = self
+            .records()
+            .await?
+            .chunk_names
+            .get(ident.to_string().await?.as_str())
+        {
+            // The records file can be edited, so names leaving the output root
+            // are ignored.
+            if let Some(path) = *this.output_root.try_join(name.clone()).await? {
+                if path.await?.is_inside_ref(&*this.output_root.await?) {
+                    return Ok(path);
+                }
+            }
+        }
+        if this.chunk_filename_template.is_some() {
+            return Ok(self.templated_chunk_path(ident, extension, None));
+        }
+        let root_path = this.chunk_dir(ident).await?;
+        let name = ident
+            .output_name(this.context_path, extension, this.hash_algorithm.cell())
+            .await?;
+        Ok(match &this.chunk_group_name {
+            Some(chunk_group_name) => root_path.join(format!("{chunk_group_name}-{name}")),
+            None => root_path.join(name.clone_value()),
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn content_hash_chunk_names(&self) -> Vc<bool> {
+        Vc::cell(self.content_hash_chunk_names)
+    }
+
+    /// Like [ChunkingContext::chunk_path], but with the first characters of
+    /// `content_hash` before the extension, e.g. `src_index_ts._.1a2b3c4d.js`,
+    /// or in place of `[hash]` in the filename template.
+    #[turbo_tasks::function]
+    async fn content_hashed_chunk_path(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        content_hash: Vc<String>,
+        extension: String,
+    ) -> Result<Vc<FileSystemPath>> {
+        if self.await?.chunk_filename_template.is_some() {
+            return Ok(self.templated_chunk_path(ident, extension, Some(content_hash)));
+        }
+        let path = self.chunk_path(ident, extension.clone());
+        let path_ref = path.await?;
+        let name = path_ref.file_name();
+        let stem = name.strip_suffix(extension.as_str()).unwrap_or(name);
+        let content_hash = content_hash.await?;
+        Ok(path.parent().join(format!(
+            "{stem}.{content_hash}{extension}",
+            content_hash = &content_hash[..8]
+        )))
+    }
 
+    #[turbo_tasks::function]
+    async fn asset_url(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<String>> {
+        let this = self.await?;
+        let default_base_path = if *this.environment.document_relative_urls().await? {
+            ""
+        } else {
+            "/"
+        };
         Ok(Vc::cell(format!(
             "{}{}",
             this.asset_base_path
                 .await?
                 .as_ref()
                 .map(|s| s.as_str())
-                .unwrap_or("/"),
-            asset_path
+                .unwrap_or(default_base_path),
+            self.asset_url_path(ident).await?
         )))
     }
 
@@ -253,31 +1162,68 @@ impl ChunkingContext for DevChunkingContext {
     async fn reference_chunk_source_maps(
         &self,
         chunk: Vc<Box<dyn OutputAsset>>,
-    ) -> Result<Vc<bool>> {
-        let mut source_maps = self.reference_chunk_source_maps;
+    ) -> Result<Vc<SourceMapMode>> {
         let path = chunk.ident().path().await?;
-        let extension = path.extension_ref().unwrap_or_default();
-        #[allow(clippy::single_match, reason = "future extensions")]
-        match extension {
-            ".css" => {
-                source_maps = self.reference_css_chunk_source_maps;
-            }
-            _ => {}
-        }
-        Ok(Vc::cell(source_maps))
+        Ok(chunk_source_map_mode(
+            path.extension_ref().unwrap_or_default(),
+            self.reference_chunk_source_maps,
+            self.reference_css_chunk_source_maps,
+        )
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    fn source_map_privacy(&self) -> Vc<OptionSourceMapPrivacy> {
+        Vc::cell(self.source_map_privacy)
+    }
+
+    #[turbo_tasks::function]
+    fn hash_algorithm(&self) -> Vc<HashAlgorithm> {
+        self.hash_algorithm.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn subresource_integrity(&self) -> Vc<bool> {
+        Vc::cell(self.subresource_integrity)
+    }
+
+    #[turbo_tasks::function]
+    fn esm_chunks(&self) -> Vc<bool> {
+        Vc::cell(self.esm_chunks)
     }
 
     #[turbo_tasks::function]
     async fn can_be_in_same_chunk(
-        &self,
+        self: Vc<Self>,
         asset_a: Vc<Box<dyn Module>>,
         asset_b: Vc<Box<dyn Module>>,
     ) -> Result<Vc<bool>> {
+        let this = self.await?;
+        if let Some(policy) = this.chunking_policy {
+            if let Some(result) = *policy.can_be_in_same_chunk(asset_a, asset_b).await? {
+                return Ok(Vc::cell(result));
+            }
+        }
+
+        if !this.cache_groups.is_empty() {
+            // The cache groups cell of the context is shared by all calls, so the
+            // group of each module is only computed once.
+            let cache_groups = self.cache_groups();
+            let group_a = cache_groups.group_name(this.context_path, asset_a.ident());
+            let group_b = cache_groups.group_name(this.context_path, asset_b.ident());
+            let (group_a, group_b) = (group_a.await?, group_b.await?);
+            if group_a.is_some() || group_b.is_some() {
+                return Ok(Vc::cell(*group_a == *group_b));
+            }
+        }
+
         let parent_dir = asset_a.ident().path().parent().await?;
 
         let path = asset_b.ident().path().await?;
         if let Some(rel_path) = parent_dir.get_path_to(&path) {
-            if !rel_path.starts_with("node_modules/") && !rel_path.contains("/node_modules/") {
+            if !this.split_node_modules
+                || (!rel_path.starts_with("node_modules/") && !rel_path.contains("/node_modules/"))
+            {
                 return Ok(Vc::cell(true));
             }
         }
@@ -292,6 +1238,13 @@ impl ChunkingContext for DevChunkingContext {
         original_asset_ident: Vc<AssetIdent>,
     ) -> Result<Vc<FileSystemPath>> {
         let source_path = original_asset_ident.path().await?;
+        if self.dedupe_assets_by_content {
+            let asset_path = match source_path.extension_ref() {
+                Some(ext) => format!("{content_hash}.{ext}"),
+                None => content_hash,
+            };
+            return Ok(self.asset_root_path.join(asset_path));
+        }
         let basename = source_path.file_name();
         let asset_path = match source_path.extension_ref() {
             Some(ext) => format!(
@@ -313,33 +1266,216 @@ impl ChunkingContext for DevChunkingContext {
     }
 
     #[turbo_tasks::function]
-    async fn chunk_group(
+    fn layered_chunks(&self) -> Vc<bool> {
+        Vc::cell(!self.layer_chunk_root_paths.is_empty())
+    }
+
+    #[turbo_tasks::function]
+    fn usage_profile(&self) -> Vc<OptionUsageProfile> {
+        Vc::cell(self.usage_profile)
+    }
+
+    #[turbo_tasks::function]
+    fn split_points(&self) -> Vc<ModulesSet> {
+        self.split_points
+    }
+
+    #[turbo_tasks::function]
+    fn module_size_warning_threshold(&self) -> Vc<Option<u64>> {
+        Vc::cell(self.module_size_warning_threshold)
+    }
+
+    #[turbo_tasks::function]
+    fn check_peer_dependencies(&self) -> Vc<bool> {
+        Vc::cell(self.check_peer_dependencies)
+    }
+
+    #[turbo_tasks::function]
+    fn min_chunk_size(&self) -> Vc<Option<u64>> {
+        Vc::cell(self.min_chunk_size)
+    }
+
+    #[turbo_tasks::function]
+    fn max_chunk_size(&self) -> Vc<Option<u64>> {
+        Vc::cell(self.max_chunk_size)
+    }
+
+    #[turbo_tasks::function]
+    fn split_node_modules(&self) -> Vc<bool> {
+        Vc::cell(self.split_node_modules)
+    }
+
+    #[turbo_tasks::function]
+    fn isolate_vendor_chunks(&self) -> Vc<bool> {
+        Vc::cell(self.isolate_vendor_chunks)
+    }
+
+    #[turbo_tasks::function]
+    fn max_chunks_per_chunk_group(&self) -> Vc<Option<u64>> {
+        Vc::cell(self.max_chunks_per_chunk_group)
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_policy(&self) -> Vc<OptionChunkingPolicy> {
+        Vc::cell(self.chunking_policy)
+    }
+
+    #[turbo_tasks::function]
+    fn cache_groups(&self) -> Vc<ChunkCacheGroups> {
+        Vc::cell(self.cache_groups.clone())
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_group(
         self: Vc<Self>,
         module: Vc<Box<dyn ChunkableModule>>,
         availability_info: Value<AvailabilityInfo>,
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group_with_name(module, availability_info, None)
+    }
+
+    #[turbo_tasks::function]
+    fn named_chunk_group(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+        name: String,
+    ) -> Vc<ChunkGroupResult> {
+        self.chunk_group_with_name(module, availability_info, Some(name))
+    }
+
+    #[turbo_tasks::function]
+    async fn evaluated_chunk_group(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        availability_info: Value<AvailabilityInfo>,
     ) -> Result<Vc<ChunkGroupResult>> {
-        let span = tracing::info_span!("chunking", module = *module.ident().to_string().await?);
+        let chunking_context = self.for_chunk_group(ident).resolve().await?;
+        if chunking_context != self {
+            return Ok(chunking_context.evaluated_chunk_group(
+                ident,
+                evaluatable_assets,
+                availability_info,
+            ));
+        }
+        let span = {
+            let ident = ident.to_string().await?;
+            tracing::info_span!("chunking", chunking_type = "evaluated", ident = *ident)
+        };
         async move {
+            let availability_info = availability_info.into_value();
+
+            if let Some(modules) = *self.static_entry_imports(evaluatable_assets).await? {
+                let MakeChunkGroupResult {
+                    chunks,
+                    availability_info,
+                    ..
+                } = make_chunk_group(
+                    Vc::upcast(self),
+                    modules.await?.iter().copied(),
+                    availability_info,
+                )
+                .await?;
+
+                let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = Vec::new();
+                for &chunk in chunks.iter() {
+                    assets.push(self.generate_chunk(chunk).resolve().await?);
+                }
+
+                let assets = Vc::cell(assets);
+                return Ok(ChunkGroupResult {
+                    assets,
+                    availability_info,
+                    plan: None,
+                    static_only: true,
+                    integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
+                }
+                .cell());
+            }
+
+            let evaluatable_assets = self.with_runtime_globals(evaluatable_assets);
+            let evaluatable_assets_ref = evaluatable_assets.await?;
+
+            // TODO this collect is unnecessary, but it hits a compiler bug when it's not
+            // used
+            let entries = evaluatable_assets_ref
+                .iter()
+                .map(|&evaluatable| Vc::upcast(evaluatable))
+                .collect::<Vec<_>>();
+
+            if self.await?.dry_run {
+                let PlanChunkGroupResult {
+                    plan,
+                    availability_info,
+                } = plan_chunk_group(Vc::upcast(self), entries, availability_info, |chunk| {
+                    self.generate_chunk(chunk)
+                })
+                .await?;
+                return Ok(ChunkGroupResult {
+                    assets: OutputAssets::empty(),
+                    availability_info,
+                    plan: Some(plan),
+                    static_only: false,
+                    integrity: None,
+                }
+                .cell());
+            }
+
+            if self.await?.strict_esm != StrictEsm::Off {
+                check_strict_esm(Vc::upcast(self), Vc::cell(entries.clone())).await?;
+            }
+
             let MakeChunkGroupResult {
                 chunks,
+                deferred_chunks,
                 availability_info,
-            } = make_chunk_group(
-                Vc::upcast(self),
-                [Vc::upcast(module)],
-                availability_info.into_value(),
+            } = make_chunk_group(Vc::upcast(self), entries, availability_info).await?;
+            check_module_id_collisions(
+                self,
+                &chunks
+                    .iter()
+                    .chain(&deferred_chunks)
+                    .copied()
+                    .collect::<Vec<_>>(),
             )
             .await?;
 
-            let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
-                .iter()
-                .map(|chunk| self.generate_chunk(*chunk))
-                .collect();
+            // Only the DOM runtime loads deferred chunks after the entries have
+            // been evaluated. They are loaded by the runtime instead of being
+            // part of the chunk group, e.g. as script tags in HTML.
+            let defer = !deferred_chunks.is_empty()
+                && matches!(
+                    *self.environment().chunk_loading().await?,
+                    ChunkLoading::Dom
+                );
+            let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = Vec::new();
+            let mut deferred_assets: Vec<Vc<Box<dyn OutputAsset>>> = Vec::new();
+            for chunk in chunks {
+                let asset = self.generate_chunk(chunk);
+                if defer && deferred_chunks.contains(&chunk) {
+                    deferred_assets.push(asset);
+                } else {
+                    assets.push(asset);
+                }
+            }
+
+            let other_assets = Vc::cell(assets.clone());
+            // Deferred chunks receive hot updates once they have been loaded.
+            let listed_assets = Vc::cell(assets.iter().chain(&deferred_assets).copied().collect());
 
             assets.push(self.generate_chunk_list_register_chunk(
-                module.ident(),
-                EvaluatableAssets::empty(),
-                Vc::cell(assets.clone()),
-                Value::new(EcmascriptDevChunkListSource::Dynamic),
+                ident,
+                evaluatable_assets,
+                listed_assets,
+                Value::new(EcmascriptDevChunkListSource::Entry),
+            ));
+
+            assets.push(self.generate_evaluate_chunk(
+                ident,
+                other_assets,
+                Vc::cell(deferred_assets),
+                evaluatable_assets,
             ));
 
             // Resolve assets
@@ -347,9 +1483,13 @@ impl ChunkingContext for DevChunkingContext {
                 *asset = asset.resolve().await?;
             }
 
+            let assets = Vc::cell(assets);
             Ok(ChunkGroupResult {
-                assets: Vc::cell(assets),
+                assets,
                 availability_info,
+                plan: None,
+                static_only: false,
+                integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
             }
             .cell())
         }
@@ -358,57 +1498,76 @@ impl ChunkingContext for DevChunkingContext {
     }
 
     #[turbo_tasks::function]
-    async fn evaluated_chunk_group(
+    async fn worker_chunk_group(
         self: Vc<Self>,
         ident: Vc<AssetIdent>,
         evaluatable_assets: Vc<EvaluatableAssets>,
-        availability_info: Value<AvailabilityInfo>,
     ) -> Result<Vc<ChunkGroupResult>> {
         let span = {
             let ident = ident.to_string().await?;
-            tracing::info_span!("chunking", chunking_type = "evaluated", ident = *ident)
+            tracing::info_span!("chunking", chunking_type = "worker", ident = *ident)
         };
         async move {
-            let availability_info = availability_info.into_value();
-
-            let evaluatable_assets_ref = evaluatable_assets.await?;
-
-            // TODO this collect is unnecessary, but it hits a compiler bug when it's not
-            // used
-            let entries = evaluatable_assets_ref
+            // The runtime globals are set up for the page, so they aren't
+            // evaluated in workers.
+            let entries = evaluatable_assets
+                .await?
                 .iter()
                 .map(|&evaluatable| Vc::upcast(evaluatable))
                 .collect::<Vec<_>>();
 
+            if self.await?.dry_run {
+                let PlanChunkGroupResult {
+                    plan,
+                    availability_info,
+                } = plan_chunk_group(Vc::upcast(self), entries, AvailabilityInfo::Root, |chunk| {
+                    self.generate_chunk(chunk)
+                })
+                .await?;
+                return Ok(ChunkGroupResult {
+                    assets: OutputAssets::empty(),
+                    availability_info,
+                    plan: Some(plan),
+                    static_only: false,
+                    integrity: None,
+                }
+                .cell());
+            }
+
             let MakeChunkGroupResult {
                 chunks,
                 availability_info,
-            } = make_chunk_group(Vc::upcast(self), entries, availability_info).await?;
+                ..
+            } = make_chunk_group(Vc::upcast(self), entries, AvailabilityInfo::Root).await?;
+            check_module_id_collisions(self, &chunks).await?;
 
             let mut assets: Vec<Vc<Box<dyn OutputAsset>>> = chunks
                 .iter()
                 .map(|chunk| self.generate_chunk(*chunk))
                 .collect();
 
-            let other_assets = Vc::cell(assets.clone());
-
-            assets.push(self.generate_chunk_list_register_chunk(
+            // Workers don't receive hot updates, so there's no chunk list to
+            // register. The entry chunk must be the last asset.
+            assets.push(Vc::upcast(EcmascriptDevEvaluateChunk::new(
+                self,
                 ident,
+                Vc::cell(assets.clone()),
+                OutputAssets::empty(),
                 evaluatable_assets,
-                other_assets,
-                Value::new(EcmascriptDevChunkListSource::Entry),
-            ));
-
-            assets.push(self.generate_evaluate_chunk(ident, other_assets, evaluatable_assets));
+                true,
+            )));
 
-            // Resolve assets
             for asset in assets.iter_mut() {
                 *asset = asset.resolve().await?;
             }
 
+            let assets = Vc::cell(assets);
             Ok(ChunkGroupResult {
-                assets: Vc::cell(assets),
+                assets,
                 availability_info,
+                plan: None,
+                static_only: false,
+                integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
             }
             .cell())
         }
@@ -416,6 +1575,53 @@ impl ChunkingContext for DevChunkingContext {
         .await
     }
 
+    #[turbo_tasks::function]
+    fn inline_worker_size_limit(&self) -> Vc<Option<u64>> {
+        Vc::cell(self.inline_worker_size_limit)
+    }
+
+    #[turbo_tasks::function]
+    async fn service_worker_chunk_group(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        precache: Vc<OutputAssets>,
+    ) -> Result<Vc<ChunkGroupResult>> {
+        let assets: Vc<OutputAssets> = Vc::cell(vec![
+            Vc::upcast(PrecacheManifestAsset::new(
+                precache_manifest_path(path),
+                precache,
+            )),
+            Vc::upcast(EcmascriptDevWorkletChunk::service_worker(
+                self,
+                path,
+                evaluatable_assets,
+                precache,
+            )),
+        ]);
+        Ok(ChunkGroupResult {
+            assets,
+            availability_info: AvailabilityInfo::Root,
+            plan: None,
+            static_only: false,
+            integrity: chunk_group_integrity(Vc::upcast(self), assets).await?,
+        }
+        .cell())
+    }
+
+    #[turbo_tasks::function]
+    fn worklet_chunk(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Box<dyn OutputAsset>> {
+        Vc::upcast(EcmascriptDevWorkletChunk::new(
+            self,
+            ident,
+            evaluatable_assets,
+        ))
+    }
+
     #[turbo_tasks::function]
     fn async_loader_chunk_item(
         self: Vc<Self>,
@@ -429,6 +1635,49 @@ impl ChunkingContext for DevChunkingContext {
         ))
     }
 
+    #[turbo_tasks::function]
+    fn named_async_loader_chunk_item(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        availability_info: Value<AvailabilityInfo>,
+        name: String,
+    ) -> Vc<Box<dyn ChunkItem>> {
+        let manifest_asset =
+            ManifestAsyncModule::new_named(module, Vc::upcast(self), availability_info, name);
+        Vc::upcast(ManifestLoaderChunkItem::new(
+            manifest_asset,
+            Vc::upcast(self),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn records(&self) -> Vc<ChunkingRecords> {
+        match self.records_path {
+            Some(path) => read_chunking_records(path),
+            None => ChunkingRecords::empty(),
+        }
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_item_id_from_ident(
+        self: Vc<Self>,
+        ident: Vc<AssetIdent>,
+    ) -> Result<Vc<ModuleId>> {
+        let ident = ident.to_string().await?;
+        let records = self.records();
+        if let Some(id) = records.await?.module_ids.get(ident.as_str()) {
+            return Ok(id.clone().cell());
+        }
+        let this = self.await?;
+        Ok(unpinned_module_id(
+            this.module_id_strategy,
+            this.hash_algorithm,
+            &ident,
+            &*records.pinned_module_ids().await?,
+        )
+        .cell())
+    }
+
     #[turbo_tasks::function]
     fn async_loader_chunk_item_id(
         self: Vc<Self>,
@@ -436,6 +1685,31 @@ impl ChunkingContext for DevChunkingContext {
     ) -> Vc<ModuleId> {
         self.chunk_item_id_from_ident(ManifestLoaderChunkItem::asset_ident_for(module))
     }
+
+    #[turbo_tasks::function]
+    async fn failing_module_chunk_item(
+        self: Vc<Self>,
+        module: Vc<Box<dyn ChunkableModule>>,
+        error: String,
+    ) -> Result<Vc<OptionChunkItem>> {
+        if !self.await?.stub_failing_modules {
+            return Ok(Vc::cell(None));
+        }
+        if let Some(css_module) =
+            Vc::try_resolve_sidecast::<Box<dyn CssChunkPlaceable>>(module).await?
+        {
+            return Ok(Vc::cell(Some(Vc::upcast(FailingCssModuleChunkItem::new(
+                css_module,
+                Vc::upcast(self),
+                error,
+            )))));
+        }
+        Ok(Vc::cell(Some(Vc::upcast(FailingModuleChunkItem::new(
+            module,
+            Vc::upcast(self),
+            error,
+        )))))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -444,4 +1718,161 @@ impl EcmascriptChunkingContext for DevChunkingContext {
     fn has_react_refresh(&self) -> Vc<bool> {
         Vc::cell(true)
     }
+
+    #[turbo_tasks::function]
+    async fn asset_url_expr(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<String>> {
+        let this = self.await?;
+        if this.base_path_mode == BasePathMode::Static {
+            return Ok(Vc::cell(
+                StringifyJs(&*self.asset_url(ident).await?).to_string(),
+            ));
+        }
+        let asset_base_path = this.asset_base_path.await?;
+        Ok(Vc::cell(format!(
+            "{} + {}",
+            runtime_base_path_expr(
+                &base_paths_global(&this.chunk_loading_global),
+                "assetBasePath",
+                asset_base_path.as_deref().unwrap_or("/")
+            ),
+            StringifyJs(&*self.asset_url_path(ident).await?)
+        )))
+    }
+
+    #[turbo_tasks::function]
+    fn strict_esm(&self) -> Vc<StrictEsm> {
+        self.strict_esm.cell()
+    }
+}
+
+/// How a chunk with the file extension `extension` (without a leading `.`)
+/// references its source map, given the modes configured for JavaScript and CSS
+/// chunks.
+fn chunk_source_map_mode(
+    extension: &str,
+    chunks: SourceMapMode,
+    css_chunks: SourceMapMode,
+) -> SourceMapMode {
+    match extension {
+        "css" => css_chunks,
+        _ => chunks,
+    }
+}
+
+/// Reduces the name of a chunk group, e.g. from a `turbopackChunkName` magic
+/// comment, to characters that are safe in a file name, so it can't add path
+/// segments to the chunk path.
+fn chunk_group_name_slug(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Replaces the placeholders of a chunk filename template, see
+/// [DevChunkingContextBuilder::chunk_filename_template]. The template is
+/// parsed in a single pass, so placeholders in the substituted values, e.g. in
+/// `app/[name]/page.tsx`, are kept as they are.
+fn apply_chunk_filename_template(
+    template: &str,
+    dir: &str,
+    name: &str,
+    hash: &str,
+    extension: &str,
+) -> String {
+    let mut file_name = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('[') {
+        file_name.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        match &rest[1..end] {
+            "dir" => file_name.push_str(dir),
+            "name" => file_name.push_str(name),
+            "hash8" => file_name.push_str(&hash[..hash.len().min(8)]),
+            "hash" => file_name.push_str(hash),
+            "ext" => file_name.push_str(extension.trim_start_matches('.')),
+            _ => {
+                // Not a placeholder, keep the `[` and look for the next one.
+                file_name.push('[');
+                rest = &rest[1..];
+                continue;
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    file_name.push_str(rest);
+    // Entries in the context path have an empty `[dir]`, which would leave an
+    // empty path segment.
+    file_name
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn css_chunks_use_the_css_source_map_mode() {
+        let mode = |extension| {
+            chunk_source_map_mode(extension, SourceMapMode::External, SourceMapMode::Inline)
+        };
+        assert_eq!(mode("css"), SourceMapMode::Inline);
+        assert_eq!(mode("js"), SourceMapMode::External);
+        assert_eq!(mode(""), SourceMapMode::External);
+    }
+
+    #[test]
+    fn chunk_group_names_are_file_names() {
+        assert_eq!(chunk_group_name_slug("settings-page_2"), "settings-page_2");
+        assert_eq!(chunk_group_name_slug("../x"), "___x");
+        assert_eq!(chunk_group_name_slug("/etc/passwd"), "_etc_passwd");
+        assert_eq!(chunk_group_name_slug("a\\b c"), "a_b_c");
+    }
+
+    #[test]
+    fn chunk_filename_template_placeholders() {
+        assert_eq!(
+            apply_chunk_filename_template(
+                "[dir]/[name].[hash8].[ext]",
+                "src/pages",
+                "index",
+                "0123456789abcdef",
+                ".js"
+            ),
+            "src/pages/index.01234567.js"
+        );
+        assert_eq!(
+            apply_chunk_filename_template("[dir]/[name]-[hash].[ext]", "", "main", "abc", "css"),
+            "main-abc.css"
+        );
+    }
+
+    #[test]
+    fn chunk_filename_template_keeps_placeholders_in_values() {
+        assert_eq!(
+            apply_chunk_filename_template(
+                "[dir]/[name].[hash].[ext]",
+                "app/[name]",
+                "[hash]",
+                "abc",
+                "js"
+            ),
+            "app/[name]/[hash].abc.js"
+        );
+        assert_eq!(
+            apply_chunk_filename_template("[id]/[name]_[ext", "", "a", "abc", "js"),
+            "[id]/a_[ext"
+        );
+    }
 }
@@ -0,0 +1,154 @@
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks_fs::{rope::Rope, File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{availability_info::AvailabilityInfo, ChunkingContext, EvaluatableAssets},
+    code_builder::{Code, CodeBuilder},
+    environment::ChunkLoading,
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+};
+
+use crate::DevChunkingContext;
+
+/// An Ecmascript bundle that contains all chunks of an evaluated chunk group,
+/// including the runtime, in a single file, e.g. for React Native.
+///
+/// Modules are registered as factories and are only evaluated when they are
+/// required for the first time, like in a RAM bundle. The environment of the
+/// chunking context must not load chunks ([ChunkLoading::None]), so async
+/// chunk groups are included in the bundle as well. When the chunking context
+/// also disallows `eval`, the bundle can be compiled to Hermes bytecode.
+#[turbo_tasks::value(shared)]
+pub struct EcmascriptDevBundle {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<DevChunkingContext>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptDevBundle {
+    /// Creates a new bundle that will be emitted at `path`.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<DevChunkingContext>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Self> {
+        EcmascriptDevBundle {
+            path,
+            chunking_context,
+            evaluatable_assets,
+        }
+        .cell()
+    }
+
+    /// The output assets of the chunk group that is bundled.
+    #[turbo_tasks::function]
+    async fn chunk_group_assets(&self) -> Result<Vc<OutputAssets>> {
+        Ok(self
+            .chunking_context
+            .evaluated_chunk_group(
+                AssetIdent::from_path(self.path),
+                self.evaluatable_assets,
+                Value::new(AvailabilityInfo::Root),
+            )
+            .await?
+            .assets)
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let chunk_loading = this.chunking_context.environment().chunk_loading().await?;
+        if !matches!(*chunk_loading, ChunkLoading::None) {
+            bail!(
+                "cannot bundle {} into a single file, as its environment loads chunks on demand",
+                this.path.to_string().await?
+            );
+        }
+
+        let mut code = CodeBuilder::default();
+        for &asset in self.chunk_group_assets().await?.iter() {
+            if asset.ident().path().await?.extension_ref() != Some("js") {
+                continue;
+            }
+            let content = asset.content().file_content().await?;
+            let FileContent::Content(file) = &*content else {
+                continue;
+            };
+            let source = file.content().to_str()?;
+            // The bundle references a single source map for all of its chunks.
+            let source = source
+                .rsplit_once("\n\n//# sourceMappingURL=")
+                .map_or(&*source, |(source, _)| source);
+            let map = Vc::try_resolve_sidecast::<Box<dyn GenerateSourceMap>>(asset).await?;
+            code.push_source(&Rope::from(source.to_string()), map);
+            writeln!(code)?;
+        }
+
+        if code.has_source_map() {
+            let filename = this.path.await?.file_name().to_string();
+            write!(code, "\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        Ok(Code::cell(code.build()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptDevBundle {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let mut references = IndexSet::new();
+        references.insert(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+
+        // The chunks are part of the bundle, but the assets they reference (e.g.
+        // images and CSS chunks) are still emitted on their own.
+        for &asset in self.chunk_group_assets().await?.iter() {
+            if asset.ident().path().await?.extension_ref() != Some("js") {
+                references.insert(asset);
+                continue;
+            }
+            for &reference in asset.references().await?.iter() {
+                if !matches!(
+                    reference.ident().path().await?.extension_ref(),
+                    Some("js" | "map")
+                ) {
+                    references.insert(reference);
+                }
+            }
+        }
+
+        Ok(Vc::cell(references.into_iter().collect()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptDevBundle {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptDevBundle {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
@@ -3,16 +3,24 @@ use indexmap::IndexSet;
 use turbo_tasks::{ValueToString, Vc};
 use turbopack_core::{
     asset::{Asset, AssetContent},
-    chunk::{Chunk, ChunkingContext, OutputChunk, OutputChunkRuntimeInfo},
+    chunk::{
+        records::ChunkingRecords, Chunk, ChunkItemExt, ChunkingContext, OutputChunk,
+        OutputChunkRuntimeInfo,
+    },
     ident::AssetIdent,
     introspect::{Introspectable, IntrospectableChildren},
-    output::{OutputAsset, OutputAssets},
-    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+    output::{OutputAsset, OutputAssets, ServingHints},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset, SourceMapMode},
     version::VersionedContent,
 };
 use turbopack_ecmascript::chunk::EcmascriptChunk;
 
-use crate::{ecmascript::content::EcmascriptDevChunkContent, DevChunkingContext};
+use crate::{
+    ecmascript::{
+        content::EcmascriptDevChunkContent, content_entry::EcmascriptDevChunkContentEntries,
+    },
+    DevChunkingContext,
+};
 
 /// Development Ecmascript chunk.
 #[turbo_tasks::value(shared)]
@@ -70,24 +78,87 @@ impl EcmascriptDevChunk {
             this.chunk.chunk_content(),
         ))
     }
+
+    /// A hash of the code of the chunk items, which doesn't depend on the path
+    /// of the chunk.
+    #[turbo_tasks::function]
+    async fn content_hash(&self) -> Result<Vc<String>> {
+        let entries = EcmascriptDevChunkContentEntries::new(self.chunk.chunk_content()).await?;
+        let mut hasher = self.chunking_context.hash_algorithm().await?.hasher();
+        for (id, entry) in entries.iter() {
+            hasher.write_ref(&id.to_string());
+            hasher.write_value(*entry.hash.await?);
+        }
+        Ok(Vc::cell(hasher.finish_hex()))
+    }
+
+    /// The ids of the chunk items of this chunk, and the name of this chunk
+    /// unless it's named by its content hash, to be pinned across restarts.
+    #[turbo_tasks::function]
+    pub(crate) async fn records(self: Vc<Self>) -> Result<Vc<ChunkingRecords>> {
+        let this = self.await?;
+        let mut records = ChunkingRecords::default();
+        for &(chunk_item, _) in this.chunk.chunk_content().await?.chunk_items.iter() {
+            records.module_ids.insert(
+                chunk_item.asset_ident().to_string().await?.clone_value(),
+                chunk_item.id().await?.clone_value(),
+            );
+        }
+        if !*this.chunking_context.content_hash_chunk_names().await? {
+            let ident = this
+                .chunking_context
+                .chunk_ident(this.chunk.ident().with_modifier(modifier()));
+            let output_root = this.chunking_context.output_root().await?;
+            if let Some(name) = output_root.get_path_to(&*self.ident().path().await?) {
+                records
+                    .chunk_names
+                    .insert(ident.to_string().await?.clone_value(), name.to_string());
+            }
+        }
+        Ok(records.cell())
+    }
 }
 
 #[turbo_tasks::value_impl]
 impl OutputAsset for EcmascriptDevChunk {
     #[turbo_tasks::function]
-    fn ident(&self) -> Vc<AssetIdent> {
-        let ident = self.chunk.ident().with_modifier(modifier());
-        AssetIdent::from_path(self.chunking_context.chunk_path(ident, ".js".to_string()))
+    async fn ident(self: Vc<Self>) -> Result<Vc<AssetIdent>> {
+        let this = self.await?;
+        let ident = this.chunk.ident().with_modifier(modifier());
+        let path = if *this.chunking_context.content_hash_chunk_names().await? {
+            this.chunking_context.content_hashed_chunk_path(
+                ident,
+                self.content_hash(),
+                ".js".to_string(),
+            )
+        } else {
+            this.chunking_context.chunk_path(ident, ".js".to_string())
+        };
+        Ok(AssetIdent::from_path(path))
+    }
+
+    #[turbo_tasks::function]
+    async fn serving_hints(&self) -> Result<Vc<ServingHints>> {
+        Ok(
+            if *self.chunking_context.content_hash_chunk_names().await? {
+                ServingHints::immutable()
+            } else {
+                ServingHints::empty()
+            },
+        )
     }
 
     #[turbo_tasks::function]
     async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
         let chunk_references = this.chunk.references().await?;
-        let include_source_map = *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?;
+        let include_source_map = matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        );
         let mut references =
             Vec::with_capacity(chunk_references.len() + if include_source_map { 1 } else { 0 });
 
@@ -7,12 +7,14 @@ use turbo_tasks_fs::File;
 use turbopack_core::{
     asset::AssetContent,
     chunk::{ChunkingContext, ModuleId},
-    code_builder::{Code, CodeBuilder},
+    code_builder::{append_source_map_url, Code, CodeBuilder},
     output::OutputAsset,
-    source_map::{GenerateSourceMap, OptionSourceMap},
+    source_map::{redact_source_map, GenerateSourceMap, OptionSourceMap},
     version::{MergeableVersionedContent, Version, VersionedContent, VersionedContentMerger},
 };
-use turbopack_ecmascript::{chunk::EcmascriptChunkContent, utils::StringifyJs};
+use turbopack_ecmascript::{
+    chunk::EcmascriptChunkContent, downlevel::downlevel, minify::minify, utils::StringifyJs,
+};
 
 use super::{
     chunk::EcmascriptDevChunk, content_entry::EcmascriptDevChunkContentEntries,
@@ -62,6 +64,7 @@ impl EcmascriptDevChunkContent {
     #[turbo_tasks::function]
     async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
         let this = self.await?;
+        let chunking_context = this.chunking_context.await?;
         let output_root = this.chunking_context.output_root().await?;
         let chunk_path = this.chunk.ident().path().await?;
         let chunk_server_path = if let Some(path) = output_root.get_path_to(&chunk_path) {
@@ -77,7 +80,7 @@ impl EcmascriptDevChunkContent {
 
         // When a chunk is executed, it will either register itself with the current
         // instance of the runtime, or it will push itself onto the list of pending
-        // chunks (`self.TURBOPACK`).
+        // chunks (`self.TURBOPACK`, or the configured chunk loading global).
         //
         // When the runtime executes (see the `evaluate` module), it will pick up and
         // register all pending chunks, and replace the list of pending chunks
@@ -85,9 +88,10 @@ impl EcmascriptDevChunkContent {
         writedoc!(
             code,
             r#"
-                (globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([{chunk_path}, {{
+                (globalThis.{global} = globalThis.{global} || []).push([{chunk_path}, {{
             "#,
-            chunk_path = StringifyJs(chunk_server_path)
+            global = chunking_context.chunk_loading_global(),
+            chunk_path = StringifyJs(chunk_server_path),
         )?;
 
         for (id, entry) in this.entries.await?.iter() {
@@ -98,12 +102,25 @@ impl EcmascriptDevChunkContent {
 
         write!(code, "\n}}]);")?;
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        let mut code = code.build().cell();
+        if let Some(environment) = chunking_context.downlevel_environment() {
+            code = downlevel(this.chunk.ident().path(), code, environment);
+        }
+        if chunking_context.minify() {
+            code = minify(this.chunk.ident().path(), code, false);
         }
 
-        Ok(code.build().cell())
+        append_source_map_url(
+            code,
+            &chunk_path,
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(this.chunk))
+                .await?,
+            this.chunking_context.source_map_privacy(),
+            |url| format!("\n\n//# sourceMappingURL={url}"),
+        )
+        .await
     }
 }
 
@@ -134,8 +151,11 @@ impl MergeableVersionedContent for EcmascriptDevChunkContent {
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for EcmascriptDevChunkContent {
     #[turbo_tasks::function]
-    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
-        self.code().generate_source_map()
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(redact_source_map(
+            self.code().generate_source_map(),
+            self.await?.chunking_context.source_map_privacy(),
+        ))
     }
 
     #[turbo_tasks::function]
@@ -146,7 +166,10 @@ impl GenerateSourceMap for EcmascriptDevChunkContent {
             for (entry_id, entry) in self.entries.await?.iter() {
                 if id == **entry_id {
                     let sm = entry.code.generate_source_map();
-                    return Ok(sm);
+                    return Ok(redact_source_map(
+                        sm,
+                        self.chunking_context.source_map_privacy(),
+                    ));
                 }
             }
         }
@@ -1,19 +1,24 @@
-use std::io::Write as _;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    io::Write as _,
+};
 
 use anyhow::Result;
 use indexmap::IndexMap;
 use tracing::{info_span, Instrument};
 use turbo_tasks::{ReadRef, TryJoinIterExt, ValueToString, Vc};
 use turbopack_core::{
-    chunk::{AsyncModuleInfo, ChunkItem, ChunkItemExt, ModuleId},
+    chunk::{AsyncModuleInfo, Chunk, ChunkItem, ChunkItemExt, ModuleId},
     code_builder::{Code, CodeBuilder},
     error::PrettyPrintError,
     issue::{code_gen::CodeGenerationIssue, IssueExt, IssueSeverity, StyledString},
 };
 use turbopack_ecmascript::chunk::{
-    EcmascriptChunkContent, EcmascriptChunkItem, EcmascriptChunkItemExt,
+    EcmascriptChunk, EcmascriptChunkContent, EcmascriptChunkItem, EcmascriptChunkItemExt,
 };
 
+use crate::DevChunkingContext;
+
 /// A chunk item's content entry.
 ///
 /// Instead of storing the [`Vc<Box<dyn EcmascriptChunkItem>>`] itself from
@@ -53,7 +58,7 @@ impl EcmascriptDevChunkContentEntries {
     ) -> Result<Vc<EcmascriptDevChunkContentEntries>> {
         let chunk_content = chunk_content.await?;
 
-        let entries: IndexMap<_, _> = chunk_content
+        let items = chunk_content
             .chunk_items
             .iter()
             .map(|&(chunk_item, async_module_info)| async move {
@@ -70,12 +75,57 @@ impl EcmascriptDevChunkContentEntries {
                 .await
             })
             .try_join()
-            .await?
-            .into_iter()
-            .collect();
+            .await?;
+
+        Ok(Vc::cell(items.into_iter().collect()))
+    }
+}
 
-        Ok(Vc::cell(entries))
+/// Reports different modules with the same id in the `chunks` of a chunk
+/// group. Short numeric or hashed module ids, and ids pinned by records, can
+/// collide. The runtime keeps a single map of module factories, so only one
+/// of the modules would be available.
+pub(crate) async fn check_module_id_collisions(
+    chunking_context: Vc<DevChunkingContext>,
+    chunks: &[Vc<Box<dyn Chunk>>],
+) -> Result<()> {
+    if !chunking_context.await?.module_ids_can_collide() {
+        return Ok(());
+    }
+    let mut idents_by_id = HashMap::new();
+    for &chunk in chunks {
+        let Some(chunk) = Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk).await? else {
+            continue;
+        };
+        for &(chunk_item, _) in chunk.chunk_content().await?.chunk_items.iter() {
+            let ident = chunk_item.asset_ident().to_string().await?;
+            match idents_by_id.entry(chunk_item.id().await?) {
+                Entry::Vacant(entry) => {
+                    entry.insert(ident);
+                }
+                Entry::Occupied(entry) => {
+                    if **entry.get() == *ident {
+                        continue;
+                    }
+                    CodeGenerationIssue {
+                        severity: IssueSeverity::Error.cell(),
+                        path: chunk_item.asset_ident().path(),
+                        title: StyledString::Text("Module id collision".to_string()).cell(),
+                        message: StyledString::Text(format!(
+                            "The module id {} of {} is also the id of {}. Use longer module ids.",
+                            &**entry.key(),
+                            ident,
+                            entry.get()
+                        ))
+                        .cell(),
+                    }
+                    .cell()
+                    .emit();
+                }
+            }
+        }
     }
+    Ok(())
 }
 
 #[turbo_tasks::function]
@@ -1,6 +1,7 @@
 use std::io::Write;
 
 use anyhow::{bail, Result};
+use indexmap::IndexMap;
 use indoc::writedoc;
 use serde::Serialize;
 use turbo_tasks::{ReadRef, TryJoinIterExt, Value, ValueToString, Vc};
@@ -8,32 +9,49 @@ use turbo_tasks_fs::File;
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
+        integrity::{chunk_integrity_hashes, ChunkIntegrityHashes},
         ChunkData, ChunkItemExt, ChunkableModule, ChunkingContext, ChunksData, EvaluatableAssets,
         ModuleId,
     },
-    code_builder::{Code, CodeBuilder},
+    code_builder::{append_source_map_url, Code, CodeBuilder},
+    environment::ChunkLoading,
     ident::AssetIdent,
     module::Module,
     output::{OutputAsset, OutputAssets},
-    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+    reference::all_assets_from_entries,
+    source_map::{
+        redact_source_map, GenerateSourceMap, OptionSourceMap, SourceMapAsset, SourceMapMode,
+    },
 };
 use turbopack_ecmascript::{
-    chunk::{EcmascriptChunkData, EcmascriptChunkPlaceable},
+    chunk::{
+        auto_base_paths_code, base_paths_global, EcmascriptChunkData, EcmascriptChunkPlaceable,
+    },
+    downlevel::downlevel,
     utils::StringifyJs,
 };
-use turbopack_ecmascript_runtime::RuntimeType;
+use turbopack_ecmascript_runtime::{DevRuntimeOptions, RuntimeType};
 
-use crate::DevChunkingContext;
+use crate::{BasePathMode, DevChunkingContext};
 
 /// An Ecmascript chunk that:
 /// * Contains the Turbopack dev runtime code; and
 /// * Evaluates a list of runtime entries.
+///
+/// The entry chunk of a web worker contains a runtime that loads the other
+/// chunks with `importScripts`.
+///
+/// In a Node.js environment, the chunk can be loaded with `require()`, and
+/// exports the exports of the last runtime entry.
 #[turbo_tasks::value(shared)]
 pub(crate) struct EcmascriptDevEvaluateChunk {
     chunking_context: Vc<DevChunkingContext>,
     ident: Vc<AssetIdent>,
     other_chunks: Vc<OutputAssets>,
+    /// Chunks that are loaded after the runtime entries have been evaluated.
+    deferred_chunks: Vc<OutputAssets>,
     evaluatable_assets: Vc<EvaluatableAssets>,
+    worker: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -44,13 +62,17 @@ impl EcmascriptDevEvaluateChunk {
         chunking_context: Vc<DevChunkingContext>,
         ident: Vc<AssetIdent>,
         other_chunks: Vc<OutputAssets>,
+        deferred_chunks: Vc<OutputAssets>,
         evaluatable_assets: Vc<EvaluatableAssets>,
+        worker: bool,
     ) -> Vc<Self> {
         EcmascriptDevEvaluateChunk {
             chunking_context,
             ident,
             other_chunks,
+            deferred_chunks,
             evaluatable_assets,
+            worker,
         }
         .cell()
     }
@@ -64,6 +86,47 @@ impl EcmascriptDevEvaluateChunk {
         ))
     }
 
+    #[turbo_tasks::function]
+    async fn deferred_chunks_data(self: Vc<Self>) -> Result<Vc<ChunksData>> {
+        let this = self.await?;
+        Ok(ChunkData::from_assets(
+            this.chunking_context.output_root(),
+            this.deferred_chunks,
+        ))
+    }
+
+    /// The Subresource Integrity hashes of the chunks the runtime may load
+    /// with a tag: the other chunks and all chunks reachable from them, which
+    /// includes the manifest chunks and chunks of async chunk groups. The
+    /// hashes are collected here, as the loaders of async chunk groups can't
+    /// embed the hashes of manifest chunks without creating cycles for
+    /// circular imports.
+    #[turbo_tasks::function]
+    async fn integrity(self: Vc<Self>) -> Result<Vc<ChunkIntegrityHashes>> {
+        let this = self.await?;
+        let mut chunks = Vec::new();
+        for &asset in &*all_assets_from_entries(this.other_chunks).await? {
+            // Entry chunks of workers aren't loaded with a tag, and a worker that
+            // starts itself would make its entry chunk depend on its own hash.
+            if Vc::try_resolve_downcast_type::<EcmascriptDevEvaluateChunk>(asset)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            if matches!(
+                asset.ident().path().await?.extension_ref(),
+                Some("js" | "css")
+            ) {
+                chunks.push(asset);
+            }
+        }
+        Ok(chunk_integrity_hashes(
+            this.chunking_context.output_root(),
+            Vc::cell(chunks),
+        ))
+    }
+
     #[turbo_tasks::function]
     async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
         let this = self.await?;
@@ -88,6 +151,12 @@ impl EcmascriptDevEvaluateChunk {
             .iter()
             .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
             .collect();
+        let deferred_chunks_data = self.deferred_chunks_data().await?;
+        let deferred_chunks_data = deferred_chunks_data.iter().try_join().await?;
+        let deferred_chunks_data: Vec<_> = deferred_chunks_data
+            .iter()
+            .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
+            .collect();
 
         let runtime_module_ids = this
             .evaluatable_assets
@@ -117,35 +186,85 @@ impl EcmascriptDevEvaluateChunk {
             .flatten()
             .collect();
 
+        let build_id = chunking_context.build_id().await?;
+        // Workers load chunks with `importScripts`, which only supports classic
+        // scripts.
+        let esm_chunks = *this.chunking_context.esm_chunks().await?
+            && !this.worker
+            && matches!(*environment.chunk_loading().await?, ChunkLoading::Dom);
+        let integrity = if *this.chunking_context.subresource_integrity().await? {
+            Some(self.integrity().await?)
+        } else {
+            None
+        };
         let params = EcmascriptDevChunkRuntimeParams {
             other_chunks: &other_chunks_data,
+            deferred_chunks: &deferred_chunks_data,
             runtime_module_ids,
+            build_id: build_id.as_deref(),
+            integrity: integrity.as_deref(),
         };
 
         let mut code = CodeBuilder::default();
 
-        // We still use the `TURBOPACK` global variable to store the chunk here,
-        // as there may be another runtime already loaded in the page.
-        // This is the case in integration tests.
-        writedoc!(
-            code,
-            r#"
-                (globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
-                    {},
-                    {{}},
-                    {}
-                ]);
-            "#,
-            StringifyJs(&chunk_public_path),
-            StringifyJs(&params),
-        )?;
+        let global = chunking_context.chunk_loading_global();
+        let registration = format!(
+            "[\n    {path},\n    {{}},\n    {params}\n]",
+            path = StringifyJs(&chunk_public_path),
+            params = StringifyJs(&params),
+        );
+
+        if esm_chunks {
+            // The chunk registers itself after the runtime has been set up (see
+            // below), so the runtime only needs the list of pending chunks.
+            writeln!(code, "globalThis.{global} = globalThis.{global} || [];")?;
+        } else {
+            // We still use the `TURBOPACK` global variable (or the configured chunk
+            // loading global) to store the chunk here, as there may be another
+            // runtime already loaded in the page. This is the case in integration
+            // tests.
+            writeln!(
+                code,
+                "(globalThis.{global} = globalThis.{global} || []).push({registration});"
+            )?;
+        }
+
+        // Workers resolve relative URLs against their own script, so they
+        // always load chunks relative to it.
+        let base_path_mode = if this.worker {
+            BasePathMode::Auto
+        } else {
+            chunking_context.base_path_mode()
+        };
+        if base_path_mode == BasePathMode::Auto {
+            // Must run before the runtime reads the base paths, while the
+            // current script is still this chunk.
+            write!(
+                code,
+                "{}",
+                auto_base_paths_code(&base_paths_global(global), chunk_public_path, esm_chunks)
+            )?;
+        }
 
         match chunking_context.runtime_type() {
             RuntimeType::Default => {
                 let runtime_code = turbopack_ecmascript_runtime::get_dev_runtime_code(
                     environment,
-                    chunking_context.chunk_base_path(),
-                    Vc::cell(output_root.to_string()),
+                    DevRuntimeOptions {
+                        worker: this.worker,
+                        esm_chunks,
+                        chunk_base_path: chunking_context.chunk_base_path(),
+                        runtime_base_paths: base_path_mode != BasePathMode::Static,
+                        build_id: chunking_context.build_id(),
+                        allow_eval: chunking_context.allow_eval(),
+                        csp_nonce_var: chunking_context.csp_nonce_var(),
+                        cross_origin: chunking_context.cross_origin(),
+                        chunk_load_retries: chunking_context.chunk_load_retries(),
+                        retry_delay_ms: chunking_context.retry_delay().as_millis() as u64,
+                        output_root: Vc::cell(output_root.to_string()),
+                        chunk_loading_global: chunking_context.chunk_loading_global().to_string(),
+                    }
+                    .cell(),
                 );
                 code.push_code(&*runtime_code.await?);
             }
@@ -156,12 +275,45 @@ impl EcmascriptDevEvaluateChunk {
             }
         }
 
-        if code.has_source_map() {
-            let filename = chunk_path.file_name();
-            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        if esm_chunks {
+            // Like a native ES module, the chunk only finishes evaluating once
+            // its asynchronous entries have been evaluated, so top-level await
+            // in the entries delays the `load` of its `<script type="module">`
+            // and the `import()` of it.
+            writeln!(code, "\nawait globalThis.{global}.push({registration});")?;
+        }
+
+        if !this.worker && matches!(*environment.chunk_loading().await?, ChunkLoading::NodeJs) {
+            // Other chunks are evaluated by the runtime instead of being
+            // required, so `module` isn't always defined.
+            writedoc!(
+                code,
+                r#"
+
+                    if (typeof module !== "undefined") {{
+                        module.exports = globalThis.TURBOPACK_CHUNK_EXPORTS?.[{}];
+                    }}
+                "#,
+                StringifyJs(&chunk_public_path),
+            )?;
+        }
+
+        let mut code = code.build().cell();
+        if let Some(environment) = chunking_context.downlevel_environment() {
+            code = downlevel(self.ident().path(), code, environment);
         }
 
-        Ok(Code::cell(code.build()))
+        append_source_map_url(
+            code,
+            &chunk_path,
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            this.chunking_context.source_map_privacy(),
+            |url| format!("\n\n//# sourceMappingURL={url}"),
+        )
+        .await
     }
 }
 
@@ -178,13 +330,22 @@ fn modifier() -> Vc<String> {
     Vc::cell("ecmascript dev evaluate chunk".to_string())
 }
 
+#[turbo_tasks::function]
+fn worker_modifier() -> Vc<String> {
+    Vc::cell("ecmascript dev worker evaluate chunk".to_string())
+}
+
 #[turbo_tasks::value_impl]
 impl OutputAsset for EcmascriptDevEvaluateChunk {
     #[turbo_tasks::function]
     async fn ident(&self) -> Result<Vc<AssetIdent>> {
         let mut ident = self.ident.await?.clone_value();
 
-        ident.add_modifier(modifier());
+        ident.add_modifier(if self.worker {
+            worker_modifier()
+        } else {
+            modifier()
+        });
 
         let evaluatable_assets = self.evaluatable_assets.await?;
         ident.modifiers.extend(
@@ -208,10 +369,13 @@ impl OutputAsset for EcmascriptDevEvaluateChunk {
         let this = self.await?;
         let mut references = Vec::new();
 
-        let include_source_map = *this
-            .chunking_context
-            .reference_chunk_source_maps(Vc::upcast(self))
-            .await?;
+        let include_source_map = matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        );
 
         if include_source_map {
             references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
@@ -221,6 +385,13 @@ impl OutputAsset for EcmascriptDevEvaluateChunk {
             references.extend(chunk_data.references().await?.iter().copied());
         }
 
+        // Deferred chunks aren't part of the chunk group, so they are only
+        // emitted as references of this chunk.
+        references.extend(this.deferred_chunks.await?.iter().copied());
+        for chunk_data in &*self.deferred_chunks_data().await? {
+            references.extend(chunk_data.references().await?.iter().copied());
+        }
+
         Ok(Vc::cell(references))
     }
 }
@@ -239,8 +410,11 @@ impl Asset for EcmascriptDevEvaluateChunk {
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for EcmascriptDevEvaluateChunk {
     #[turbo_tasks::function]
-    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
-        self.code().generate_source_map()
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(redact_source_map(
+            self.code().generate_source_map(),
+            self.await?.chunking_context.source_map_privacy(),
+        ))
     }
 }
 
@@ -253,6 +427,16 @@ struct EcmascriptDevChunkRuntimeParams<'a, T> {
     /// These chunks must be loaed before the runtime modules can be
     /// instantiated.
     other_chunks: &'a [T],
+    /// Chunks of the chunk group that are loaded after the runtime modules
+    /// have been instantiated.
+    #[serde(skip_serializing_if = "<[T]>::is_empty")]
+    deferred_chunks: &'a [T],
     /// List of module IDs that this chunk should instantiate when executed.
     runtime_module_ids: Vec<ReadRef<ModuleId>>,
+    /// The build this chunk belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_id: Option<&'a str>,
+    /// The Subresource Integrity hashes of the chunks the runtime may load.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    integrity: Option<&'a IndexMap<String, String>>,
 }
@@ -29,6 +29,8 @@ pub(super) struct EcmascriptDevChunkListContent {
     chunk_list_path: String,
     pub(super) chunks_contents: IndexMap<String, Vc<Box<dyn VersionedContent>>>,
     source: EcmascriptDevChunkListSource,
+    pub(super) build_id: Option<String>,
+    chunk_loading_global: String,
 }
 
 #[turbo_tasks::value_impl]
@@ -64,6 +66,17 @@ impl EcmascriptDevChunkListContent {
                 .filter_map(|(path, content)| path.map(|path| (path, content)))
                 .collect(),
             source: chunk_list_ref.source,
+            build_id: chunk_list_ref
+                .chunking_context
+                .await?
+                .build_id()
+                .await?
+                .clone_value(),
+            chunk_loading_global: chunk_list_ref
+                .chunking_context
+                .await?
+                .chunk_loading_global()
+                .to_string(),
         }
         .cell())
     }
@@ -108,7 +121,12 @@ impl EcmascriptDevChunkListContent {
             .into_iter()
             .collect();
 
-        Ok(EcmascriptDevChunkListVersion { by_path, by_merger }.cell())
+        Ok(EcmascriptDevChunkListVersion {
+            by_path,
+            by_merger,
+            build_id: this.build_id.clone(),
+        }
+        .cell())
     }
 
     #[turbo_tasks::function]
@@ -119,24 +137,26 @@ impl EcmascriptDevChunkListContent {
             path: &this.chunk_list_path,
             chunks: this.chunks_contents.keys().map(|s| s.as_str()).collect(),
             source: this.source,
+            build_id: this.build_id.as_deref(),
         };
 
         let mut code = CodeBuilder::default();
 
         // When loaded, JS chunks must register themselves with the `TURBOPACK` global
-        // variable. Similarly, we register the chunk list with the
-        // `TURBOPACK_CHUNK_LISTS` global variable.
+        // variable (or the configured chunk loading global). Similarly, we register
+        // the chunk list with the `TURBOPACK_CHUNK_LISTS` global variable.
         writedoc!(
             code,
             r#"
-                (globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
-                    {},
+                (globalThis.{global} = globalThis.{global} || []).push([
+                    {path},
                     {{}},
                 ]);
-                (globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({:#});
+                (globalThis.{global}_CHUNK_LISTS = globalThis.{global}_CHUNK_LISTS || []).push({params:#});
             "#,
-            StringifyJs(&this.chunk_list_path),
-            StringifyJs(&params),
+            global = this.chunk_loading_global,
+            path = StringifyJs(&this.chunk_list_path),
+            params = StringifyJs(&params),
         )?;
 
         Ok(Code::cell(code.build()))
@@ -173,4 +193,7 @@ struct EcmascriptDevChunkListParams<'a> {
     chunks: Vec<&'a str>,
     /// Where this chunk list is from.
     source: EcmascriptDevChunkListSource,
+    /// The build this chunk list belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_id: Option<&'a str>,
 }
@@ -22,6 +22,9 @@ struct ChunkListUpdate<'a> {
     /// List of merged updates since the last version.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     merged: Vec<Arc<serde_json::Value>>,
+    /// The build the updated chunk list belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    build_id: Option<&'a str>,
 }
 
 /// Update of a chunk from one version to another.
@@ -30,11 +33,15 @@ struct ChunkListUpdate<'a> {
 #[serde(rename_all = "camelCase")]
 enum ChunkUpdate {
     /// The chunk was updated and must be reloaded.
-    Total,
+    ///
+    /// `version` identifies the new content of the chunk. The runtime appends
+    /// it to the chunk URL, so caches between the client and the server never
+    /// serve the previous content.
+    Total { version: String },
     /// The chunk was updated and can be merged with the previous version.
     Partial { instruction: Arc<serde_json::Value> },
     /// The chunk was added.
-    Added,
+    Added { version: String },
     /// The chunk was deleted.
     Deleted,
 }
@@ -42,7 +49,7 @@ enum ChunkUpdate {
 impl<'a> ChunkListUpdate<'a> {
     /// Returns `true` if this update is empty.
     fn is_empty(&self) -> bool {
-        let ChunkListUpdate { chunks, merged } = self;
+        let ChunkListUpdate { chunks, merged, .. } = self;
         chunks.is_empty() && merged.is_empty()
     }
 }
@@ -79,6 +86,17 @@ pub(super) async fn update_chunk_list(
         return Ok(Update::None.cell());
     }
 
+    // Updates can't be applied across builds: the client must reload to pick up
+    // the new build as a whole.
+    if from.build_id != to.build_id {
+        return Ok(Update::Total(TotalUpdate {
+            to: Vc::upcast::<Box<dyn Version>>(to_version)
+                .into_trait_ref()
+                .await?,
+        })
+        .cell());
+    }
+
     let content = content.await?;
 
     // There are two kind of updates nested within a chunk list update:
@@ -112,7 +130,8 @@ pub(super) async fn update_chunk_list(
 
             match &*chunk_update {
                 Update::Total(_) => {
-                    chunks.insert(chunk_path.as_ref(), ChunkUpdate::Total);
+                    let version = chunk_content.version().id().await?.clone_value();
+                    chunks.insert(chunk_path.as_ref(), ChunkUpdate::Total { version });
                 }
                 Update::Partial(partial) => {
                     chunks.insert(
@@ -129,8 +148,9 @@ pub(super) async fn update_chunk_list(
         }
     }
 
-    for chunk_path in by_path.keys() {
-        chunks.insert(chunk_path.as_ref(), ChunkUpdate::Added);
+    for (chunk_path, chunk_content) in by_path {
+        let version = chunk_content.version().id().await?.clone_value();
+        chunks.insert(chunk_path.as_ref(), ChunkUpdate::Added { version });
     }
 
     let mut merged = vec![];
@@ -160,7 +180,11 @@ pub(super) async fn update_chunk_list(
             }
         }
     }
-    let update = ChunkListUpdate { chunks, merged };
+    let update = ChunkListUpdate {
+        chunks,
+        merged,
+        build_id: content.build_id.as_deref(),
+    };
 
     let update = if update.is_empty() {
         Update::None
@@ -175,3 +199,45 @@ pub(super) async fn update_chunk_list(
 
     Ok(update.into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The runtime reads the versions of chunks from the update, see
+    /// `protocol.d.ts`.
+    #[test]
+    fn serializes_chunk_versions() {
+        let update = ChunkListUpdate {
+            chunks: IndexMap::from([
+                (
+                    "added.js",
+                    ChunkUpdate::Added {
+                        version: "v1".to_string(),
+                    },
+                ),
+                (
+                    "total.css",
+                    ChunkUpdate::Total {
+                        version: "v2".to_string(),
+                    },
+                ),
+                ("deleted.js", ChunkUpdate::Deleted),
+            ]),
+            merged: vec![],
+            build_id: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            serde_json::json!({
+                "type": "ChunkListUpdate",
+                "chunks": {
+                    "added.js": { "type": "added", "version": "v1" },
+                    "total.css": { "type": "total", "version": "v2" },
+                    "deleted.js": { "type": "deleted" },
+                },
+            })
+        );
+    }
+}
@@ -17,6 +17,8 @@ pub(super) struct EcmascriptDevChunkListVersion {
     /// A map from chunk merger to the version of the merged contents of chunks.
     #[turbo_tasks(trace_ignore)]
     pub by_merger: IndexMap<Vc<Box<dyn VersionedContentMerger>>, VersionTraitRef>,
+    /// The build id of the chunking context, if any.
+    pub build_id: Option<String>,
 }
 
 #[turbo_tasks::value_impl]
@@ -58,6 +60,7 @@ impl Version for EcmascriptDevChunkListVersion {
         for id in by_merger {
             hasher.write_value(id);
         }
+        hasher.write_value(&self.build_id);
         let hash = hasher.finish();
         let hex_hash = encode_hex(hash);
         Ok(Vc::cell(hex_hash))
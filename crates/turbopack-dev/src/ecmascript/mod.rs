@@ -1,3 +1,4 @@
+pub(crate) mod bundle;
 pub(crate) mod chunk;
 pub(crate) mod content;
 pub(crate) mod content_entry;
@@ -6,3 +7,4 @@ pub(crate) mod list;
 pub(crate) mod merged;
 pub(crate) mod update;
 pub(crate) mod version;
+pub(crate) mod worklet;
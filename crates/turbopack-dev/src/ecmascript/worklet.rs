@@ -0,0 +1,278 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::{TryFlatJoinIterExt, TryJoinIterExt, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        availability_info::AvailabilityInfo,
+        chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        precache::precache_entries,
+        Chunk, ChunkItemExt, ChunkableModule, ChunkingContext, EvaluatableAssets,
+    },
+    code_builder::{append_source_map_url, Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{
+        redact_source_map, GenerateSourceMap, OptionSourceMap, SourceMapAsset, SourceMapMode,
+    },
+};
+use turbopack_ecmascript::{
+    chunk::{EcmascriptChunk, EcmascriptChunkPlaceable},
+    downlevel::downlevel,
+    utils::StringifyJs,
+};
+
+use super::content_entry::{check_module_id_collisions, EcmascriptDevChunkContentEntries};
+use crate::DevChunkingContext;
+
+/// A standalone Ecmascript chunk for a worklet, e.g. for
+/// `CSS.paintWorklet.addModule` or `audioWorklet.addModule`.
+///
+/// Worklets load their code as a module from a URL and can't load other
+/// scripts, so the chunk contains all modules of the worklet and a minimal
+/// runtime that evaluates the entries. It doesn't register with the chunk
+/// registry of the dev runtime and doesn't receive hot updates.
+///
+/// Service workers are standalone chunks as well, as they can't load other
+/// chunks after they were installed. Their chunk is emitted at a fixed path
+/// and exposes the precache entries of the `precache` assets.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptDevWorkletChunk {
+    chunking_context: Vc<DevChunkingContext>,
+    ident: Vc<AssetIdent>,
+    evaluatable_assets: Vc<EvaluatableAssets>,
+    /// The path and the precached assets of a service worker.
+    service_worker: Option<(Vc<FileSystemPath>, Vc<OutputAssets>)>,
+}
+
+#[turbo_tasks::value(transparent)]
+struct EcmascriptChunks(Vec<Vc<EcmascriptChunk>>);
+
+#[turbo_tasks::value_impl]
+impl EcmascriptDevWorkletChunk {
+    /// Creates a new [`Vc<EcmascriptDevWorkletChunk>`].
+    #[turbo_tasks::function]
+    pub fn new(
+        chunking_context: Vc<DevChunkingContext>,
+        ident: Vc<AssetIdent>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+    ) -> Vc<Self> {
+        EcmascriptDevWorkletChunk {
+            chunking_context,
+            ident,
+            evaluatable_assets,
+            service_worker: None,
+        }
+        .cell()
+    }
+
+    /// Creates the chunk of a service worker at `path`, see
+    /// [ChunkingContext::service_worker_chunk_group].
+    #[turbo_tasks::function]
+    pub fn service_worker(
+        chunking_context: Vc<DevChunkingContext>,
+        path: Vc<FileSystemPath>,
+        evaluatable_assets: Vc<EvaluatableAssets>,
+        precache: Vc<OutputAssets>,
+    ) -> Vc<Self> {
+        EcmascriptDevWorkletChunk {
+            chunking_context,
+            ident: AssetIdent::from_path(path),
+            evaluatable_assets,
+            service_worker: Some((path, precache)),
+        }
+        .cell()
+    }
+
+    /// The chunks of the worklet's module graph. Their modules are all placed
+    /// into the worklet chunk.
+    #[turbo_tasks::function]
+    async fn chunks(&self) -> Result<Vc<EcmascriptChunks>> {
+        let entries = self
+            .evaluatable_assets
+            .await?
+            .iter()
+            .map(|&evaluatable| Vc::upcast(evaluatable))
+            .collect::<Vec<_>>();
+        let MakeChunkGroupResult { chunks, .. } = make_chunk_group(
+            Vc::upcast(self.chunking_context),
+            entries,
+            AvailabilityInfo::Root,
+        )
+        .await?;
+        check_module_id_collisions(self.chunking_context, &chunks).await?;
+        let chunks = chunks
+            .into_iter()
+            .map(|chunk| Vc::try_resolve_downcast_type::<EcmascriptChunk>(chunk))
+            .try_flat_join()
+            .await?;
+        Ok(Vc::cell(chunks))
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self: Vc<Self>) -> Result<Vc<Code>> {
+        let this = self.await?;
+        let chunk_path = self.ident().path().await?;
+
+        let entry_ids = this
+            .evaluatable_assets
+            .await?
+            .iter()
+            .map({
+                let chunking_context = this.chunking_context;
+                move |entry| async move {
+                    if let Some(placeable) =
+                        Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(*entry)
+                            .await?
+                    {
+                        Ok(Some(
+                            placeable
+                                .as_chunk_item(Vc::upcast(chunking_context))
+                                .id()
+                                .await?,
+                        ))
+                    } else {
+                        Ok(None)
+                    }
+                }
+            })
+            .try_flat_join()
+            .await?;
+
+        let mut code = CodeBuilder::default();
+
+        write!(code, "const MODULE_FACTORIES = {{")?;
+        for &chunk in self.chunks().await?.iter() {
+            let entries = EcmascriptDevChunkContentEntries::new(chunk.chunk_content()).await?;
+            for (id, entry) in entries.iter() {
+                write!(code, "\n{}: ", StringifyJs(&id))?;
+                code.push_code(&*entry.code.await?);
+                write!(code, ",")?;
+            }
+        }
+        writeln!(code, "\n}};\n")?;
+
+        if let Some((path, precache)) = this.service_worker {
+            let entries = precache_entries(path.parent(), precache).await?;
+            writeln!(
+                code,
+                "self.TURBOPACK_PRECACHE_MANIFEST = {};\n",
+                StringifyJs(&*entries)
+            )?;
+        }
+
+        let runtime_code = turbopack_ecmascript_runtime::get_worklet_runtime_code(
+            this.chunking_context.environment(),
+        );
+        code.push_code(&*runtime_code.await?);
+
+        write!(
+            code,
+            "\nevaluateWorkletEntries({});",
+            StringifyJs(&entry_ids)
+        )?;
+
+        let mut code = code.build().cell();
+        if let Some(environment) = this.chunking_context.await?.downlevel_environment() {
+            code = downlevel(self.ident().path(), code, environment);
+        }
+
+        append_source_map_url(
+            code,
+            &chunk_path,
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            this.chunking_context.source_map_privacy(),
+            |url| format!("\n\n//# sourceMappingURL={url}"),
+        )
+        .await
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptDevWorkletChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("Ecmascript Dev Worklet Chunk".to_string()))
+    }
+}
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("ecmascript dev worklet chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptDevWorkletChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        if let Some((path, _)) = self.service_worker {
+            return AssetIdent::from_path(path);
+        }
+        AssetIdent::from_path(
+            self.chunking_context
+                .chunk_path(self.ident.with_modifier(modifier()), ".js".to_string()),
+        )
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut references = Vec::new();
+
+        let include_source_map = matches!(
+            *this
+                .chunking_context
+                .reference_chunk_source_maps(Vc::upcast(self))
+                .await?,
+            SourceMapMode::External
+        );
+
+        if include_source_map {
+            references.push(Vc::upcast(SourceMapAsset::new(Vc::upcast(self))));
+        }
+
+        // Assets referenced by the modules of the worklet, e.g. images, are
+        // still emitted on their own.
+        let chunk_references = self
+            .chunks()
+            .await?
+            .iter()
+            .map(|&chunk| chunk.references())
+            .try_join()
+            .await?;
+        references.extend(
+            chunk_references
+                .iter()
+                .flat_map(|chunk_references| chunk_references.iter().copied()),
+        );
+
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptDevWorkletChunk {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptDevWorkletChunk {
+    #[turbo_tasks::function]
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(redact_source_map(
+            self.code().generate_source_map(),
+            self.await?.chunking_context.source_map_privacy(),
+        ))
+    }
+}
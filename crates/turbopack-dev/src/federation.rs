@@ -0,0 +1,139 @@
+//! Remote entries of apps exposing modules with module federation, see
+//! [turbopack_ecmascript::federation].
+//!
+//! The remote entry is an evaluated chunk group of a [RemoteContainerModule].
+//! Its chunks are loaded into the page of the host app, so the remote must be
+//! built with a distinct [chunk loading global] to not register its chunks
+//! with the runtime of the host, and should load its chunks relative to the
+//! remote entry (see [BasePathMode::Auto]), as the host page is served from a
+//! different origin. Remote modules aren't hot reloaded in the host.
+//!
+//! [chunk loading global]: crate::DevChunkingContextBuilder::chunk_loading_global
+//! [BasePathMode::Auto]: crate::BasePathMode::Auto
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkGroupResult, ChunkingContext, EvaluatableAssets,
+    },
+    ident::AssetIdent,
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+};
+use turbopack_ecmascript::federation::RemoteContainerModule;
+
+use crate::{chunking_context::DEFAULT_CHUNK_LOADING_GLOBAL, DevChunkingContext};
+
+/// The chunk group of the remote entry of `container`. Its last asset is the
+/// remote entry chunk, which hosts load.
+#[turbo_tasks::function]
+pub async fn remote_entry_chunk_group(
+    chunking_context: Vc<DevChunkingContext>,
+    container: Vc<RemoteContainerModule>,
+) -> Result<Vc<ChunkGroupResult>> {
+    if chunking_context.await?.chunk_loading_global() == DEFAULT_CHUNK_LOADING_GLOBAL {
+        bail!(
+            "the remote entry of {} must be built with a chunk loading global other than {}, as \
+             it's loaded into the page of the host",
+            container.name().await?,
+            DEFAULT_CHUNK_LOADING_GLOBAL
+        );
+    }
+    Ok(chunking_context.evaluated_chunk_group(
+        container.ident(),
+        EvaluatableAssets::one(Vc::upcast(container)),
+        Value::new(AvailabilityInfo::Root),
+    ))
+}
+
+/// The manifest of a remote entry, which hosts read to find the remote entry
+/// and the modules it exposes.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FederationManifest<'a> {
+    name: &'a str,
+    /// The path of the remote entry chunk relative to the output root.
+    remote_entry: &'a str,
+    exposes: Vec<&'a str>,
+}
+
+/// A JSON asset containing the manifest of the remote entry of a
+/// [RemoteContainerModule]. It references the chunks of the remote entry, so
+/// emitting it emits the remote entry.
+#[turbo_tasks::value]
+pub struct FederationManifestAsset {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<DevChunkingContext>,
+    container: Vc<RemoteContainerModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl FederationManifestAsset {
+    /// Creates a new manifest that will be emitted at `path`.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<DevChunkingContext>,
+        container: Vc<RemoteContainerModule>,
+    ) -> Vc<Self> {
+        FederationManifestAsset {
+            path,
+            chunking_context,
+            container,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for FederationManifestAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<OutputAssets>> {
+        Ok(
+            remote_entry_chunk_group(self.chunking_context, self.container)
+                .await?
+                .assets,
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for FederationManifestAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let assets = remote_entry_chunk_group(self.chunking_context, self.container)
+            .await?
+            .assets
+            .await?;
+        let remote_entry = assets
+            .last()
+            .context("the remote entry chunk group has no entry chunk")?
+            .ident()
+            .path()
+            .await?;
+        let output_root = self.chunking_context.output_root().await?;
+        let remote_entry = output_root
+            .get_path_to(&remote_entry)
+            .context("remote entry not in output root")?;
+        let name = self.container.name().await?;
+        let exposes = self.container.exposes().await?;
+
+        let manifest = FederationManifest {
+            name: &name,
+            remote_entry,
+            exposes: exposes.keys().map(|expose| expose.as_str()).collect(),
+        };
+        Ok(AssetContent::file(
+            File::from(serde_json::to_string_pretty(&manifest)?).into(),
+        ))
+    }
+}
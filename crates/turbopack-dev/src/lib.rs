@@ -5,9 +5,12 @@
 
 pub(crate) mod chunking_context;
 pub(crate) mod ecmascript;
+pub mod federation;
 pub mod react_refresh;
+pub mod records;
 
-pub use chunking_context::{DevChunkingContext, DevChunkingContextBuilder};
+pub use chunking_context::{BasePathMode, DevChunkingContext, DevChunkingContextBuilder};
+pub use ecmascript::bundle::EcmascriptDevBundle;
 
 pub fn register() {
     turbo_tasks::register();
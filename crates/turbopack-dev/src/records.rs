@@ -0,0 +1,70 @@
+//! Writing the records that pin module ids and chunk names across restarts of
+//! the dev server, see [DevChunkingContextBuilder::records].
+//!
+//! [DevChunkingContextBuilder::records]: crate::DevChunkingContextBuilder::records
+
+use anyhow::{Context, Result};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::records::ChunkingRecords,
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+};
+
+use crate::{ecmascript::chunk::EcmascriptDevChunk, DevChunkingContext};
+
+/// A JSON asset at the records path of the chunking context, which records
+/// the ids of all modules and the names of all chunks reachable from
+/// `assets`. These are the pinned entries of the records that were read, for
+/// the idents that are still part of the output, so entries of removed
+/// modules and chunks are dropped.
+///
+/// It doesn't reference `assets`, so it must be emitted in addition to them,
+/// e.g. whenever the output changes.
+#[turbo_tasks::value]
+pub struct ChunkingRecordsAsset {
+    chunking_context: Vc<DevChunkingContext>,
+    assets: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkingRecordsAsset {
+    #[turbo_tasks::function]
+    pub fn new(chunking_context: Vc<DevChunkingContext>, assets: Vc<OutputAssets>) -> Vc<Self> {
+        ChunkingRecordsAsset {
+            chunking_context,
+            assets,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for ChunkingRecordsAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        let path = self
+            .chunking_context
+            .await?
+            .records_path()
+            .context("the chunking context has no records path")?;
+        Ok(AssetIdent::from_path(path))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ChunkingRecordsAsset {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let mut records = ChunkingRecords::default();
+        for &asset in all_assets_from_entries(self.assets).await?.iter() {
+            if let Some(chunk) = Vc::try_resolve_downcast_type::<EcmascriptDevChunk>(asset).await? {
+                records.extend(&*chunk.records().await?);
+            }
+        }
+        Ok(AssetContent::file(File::from(records.to_json()?).into()))
+    }
+}
@@ -39,6 +39,10 @@ pub enum ClientMessage {
         #[serde(flatten)]
         resource: ResourceIdentifier,
     },
+    /// Hints that the given paths are likely to be requested next, so the
+    /// server can precompile them while idle.
+    #[serde(rename = "turbopack-precompile")]
+    Precompile { paths: Vec<String> },
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use swc_core::{
+    common::{Mark, SourceMap, Span, DUMMY_SP},
+    ecma::{
+        ast::{CallExpr, Callee, Expr, ExprOrSpread, Lit, MemberProp, Program, Str},
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+use turbopack_ecmascript::{CustomTransformer, TransformContext};
+
+/// The `console` methods whose calls are annotated. Methods that interpret
+/// their trailing arguments (e.g. `console.table` or `console.time`) are left
+/// untouched.
+const CONSOLE_METHODS: &[&str] = &["log", "info", "warn", "error", "debug", "trace"];
+
+/// Appends the original source location (`path:line:column`) of a call as the
+/// last argument to `console.*` calls.
+///
+/// This is meant to run as a source transform in development: it runs before
+/// any other transform, so the locations always refer to the original file,
+/// no matter how heavily the module is transformed afterwards.
+#[derive(Debug)]
+pub struct ConsoleLocationTransformer {
+    prefix: String,
+}
+
+impl ConsoleLocationTransformer {
+    /// `prefix` is prepended to the file path, e.g. to turn the location into
+    /// a URL the browser's devtools can link to.
+    pub fn new(prefix: String) -> Self {
+        Self { prefix }
+    }
+}
+
+impl Default for ConsoleLocationTransformer {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+#[async_trait]
+impl CustomTransformer for ConsoleLocationTransformer {
+    async fn transform(&self, program: &mut Program, ctx: &TransformContext<'_>) -> Result<()> {
+        program.visit_mut_with(&mut ConsoleLocationVisitor {
+            source_map: ctx.source_map,
+            unresolved_mark: ctx.unresolved_mark,
+            location_prefix: format!("{}{}", self.prefix, ctx.file_path_str),
+        });
+        Ok(())
+    }
+}
+
+struct ConsoleLocationVisitor<'a> {
+    source_map: &'a SourceMap,
+    unresolved_mark: Mark,
+    location_prefix: String,
+}
+
+impl ConsoleLocationVisitor<'_> {
+    fn is_console_call(&self, call: &CallExpr) -> bool {
+        let Callee::Expr(callee) = &call.callee else {
+            return false;
+        };
+        let Expr::Member(member) = &**callee else {
+            return false;
+        };
+        let Expr::Ident(obj) = &*member.obj else {
+            return false;
+        };
+        // Only the global `console`, not a local binding shadowing it.
+        if &*obj.sym != "console" || obj.span.ctxt.outer() != self.unresolved_mark {
+            return false;
+        }
+        matches!(&member.prop, MemberProp::Ident(prop) if CONSOLE_METHODS.contains(&&*prop.sym))
+    }
+
+    fn location(&self, span: Span) -> String {
+        let loc = self.source_map.lookup_char_pos(span.lo);
+        format!("{}:{}:{}", self.location_prefix, loc.line, loc.col.0 + 1)
+    }
+}
+
+impl VisitMut for ConsoleLocationVisitor<'_> {
+    fn visit_mut_call_expr(&mut self, call: &mut CallExpr) {
+        call.visit_mut_children_with(self);
+
+        if call.span.is_dummy() || !self.is_console_call(call) {
+            return;
+        }
+        let location = self.location(call.span);
+        call.args.push(ExprOrSpread {
+            spread: None,
+            expr: Box::new(Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: location.into(),
+                raw: None,
+            }))),
+        });
+    }
+}
@@ -1,3 +1,4 @@
+pub mod console_location;
 pub mod directives;
 pub mod emotion;
 pub mod modularize_imports;
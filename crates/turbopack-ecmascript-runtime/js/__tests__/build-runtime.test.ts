@@ -0,0 +1,93 @@
+import * as crypto from "node:crypto";
+import * as fs from "node:fs";
+import * as path from "node:path";
+import { loadBuildRuntime } from "./test-utils";
+
+const CHUNK = `module.exports = { "module.js": function () {} };`;
+
+function sha384(content: string): string {
+  return `sha384-${crypto.createHash("sha384").update(content).digest("base64")}`;
+}
+
+describe("subresource integrity", () => {
+  it("loads chunks that match their integrity hash", () => {
+    const runtime = loadBuildRuntime();
+    fs.writeFileSync(path.join(runtime.dir, "chunk.js"), CHUNK);
+
+    runtime.registerChunkIntegrity({ "chunk.js": sha384(CHUNK) });
+
+    expect(() => runtime.loadChunk("chunk.js")).not.toThrow();
+  });
+
+  it("refuses chunks that don't match their integrity hash", () => {
+    const runtime = loadBuildRuntime();
+    fs.writeFileSync(path.join(runtime.dir, "chunk.js"), CHUNK);
+
+    runtime.registerChunkIntegrity({ "chunk.js": sha384("tampered") });
+
+    expect(() => runtime.loadChunk("chunk.js")).toThrow(
+      "chunk chunk.js doesn't match its integrity hash"
+    );
+  });
+
+  it("loads chunks without an integrity hash", () => {
+    const runtime = loadBuildRuntime();
+    fs.writeFileSync(path.join(runtime.dir, "chunk.js"), CHUNK);
+
+    runtime.registerChunkIntegrity({ "other.js": sha384("other") });
+
+    expect(() => runtime.loadChunk("chunk.js")).not.toThrow();
+  });
+});
+
+describe("execution context", () => {
+  // The module records the execution context its module scope and its
+  // functions are executed in.
+  const CHUNK_WITH_CONTEXT = `module.exports = {
+    "module.js": function (context) {
+      const storage = globalThis[Symbol.for("turbopack.executionContext")];
+      context.m.exports.moduleScope = storage.getStore();
+      context.m.exports.current = () => storage.getStore();
+    },
+  };`;
+
+  it("provides the execution context to modules", () => {
+    const runtime = loadBuildRuntime();
+    fs.writeFileSync(path.join(runtime.dir, "chunk.js"), CHUNK_WITH_CONTEXT);
+    runtime.loadChunk("chunk.js");
+
+    const request = { id: 1 };
+    const exports = runtime.runWithExecutionContext(request, () => {
+      expect(runtime.getExecutionContext()).toBe(request);
+      return runtime.getOrInstantiateRuntimeModule("module.js", "chunk.js")
+        .exports;
+    });
+
+    expect(exports.current()).toBeUndefined();
+    expect(runtime.runWithExecutionContext(request, exports.current)).toBe(
+      request
+    );
+  });
+
+  it("evaluates module scopes outside of the execution context", () => {
+    const runtime = loadBuildRuntime();
+    fs.writeFileSync(path.join(runtime.dir, "chunk.js"), CHUNK_WITH_CONTEXT);
+    runtime.loadChunk("chunk.js");
+
+    const { exports } = runtime.runWithExecutionContext({ id: 1 }, () =>
+      runtime.getOrInstantiateRuntimeModule("module.js", "chunk.js")
+    );
+
+    expect(exports.moduleScope).toBeUndefined();
+  });
+
+  it("shares the execution context between runtimes", () => {
+    const first = loadBuildRuntime();
+    const second = loadBuildRuntime();
+
+    const request = { id: 1 };
+    first.runWithExecutionContext(request, () => {
+      expect(second.getExecutionContext()).toBe(request);
+    });
+  });
+});
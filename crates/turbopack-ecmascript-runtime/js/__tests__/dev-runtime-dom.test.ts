@@ -0,0 +1,497 @@
+import { loadDomRuntime, settle } from "./test-utils";
+
+const CHUNK_PARAMS = { otherChunks: [], runtimeModuleIds: [] };
+
+describe("chunk load retries", () => {
+  it("retries a script with a cache-busting query parameter", async () => {
+    const runtime = loadDomRuntime({ chunkLoadRetries: 2 });
+
+    const loading = runtime.loadChunk("chunk.js");
+    runtime.elements[0].onerror!();
+    await settle();
+    runtime.registerChunk("chunk.js");
+    await loading;
+
+    expect(runtime.elements.map((element) => element.src)).toEqual([
+      "chunk.js",
+      "chunk.js?turbopack-retry=1",
+    ]);
+  });
+
+  it("keeps the build id when retrying a script", async () => {
+    const runtime = loadDomRuntime({ buildId: "b1", chunkLoadRetries: 1 });
+
+    const loading = runtime.loadChunk("chunk.js");
+    runtime.elements[0].onerror!();
+    await settle();
+    runtime.registerChunk("chunk.js");
+    await loading;
+
+    expect(runtime.elements.map((element) => element.src)).toEqual([
+      "chunk.js?turbopack-build=b1",
+      "chunk.js?turbopack-build=b1&turbopack-retry=1",
+    ]);
+  });
+
+  it("retries an ES module chunk with a cache-busting query parameter", async () => {
+    let attempts = 0;
+    const runtime = loadDomRuntime({
+      esmChunks: true,
+      chunkLoadRetries: 2,
+      import: async (_url, runtime) => {
+        if (attempts++ === 0) {
+          throw new Error("network error");
+        }
+        runtime.registerChunk("chunk.js");
+      },
+    });
+
+    await runtime.loadChunk("chunk.js");
+
+    expect(runtime.imports).toEqual([
+      "http://localhost/chunk.js",
+      "http://localhost/chunk.js?turbopack-retry=1",
+    ]);
+  });
+
+  it("starts over when a failed chunk is loaded again", async () => {
+    const runtime = loadDomRuntime({ esmChunks: true, chunkLoadRetries: 1 });
+
+    await runtime.loadChunk("chunk.js").catch(() => {});
+    await runtime.loadChunk("chunk.js").catch(() => {});
+
+    expect(runtime.imports).toEqual([
+      "http://localhost/chunk.js",
+      "http://localhost/chunk.js?turbopack-retry=1",
+      "http://localhost/chunk.js",
+      "http://localhost/chunk.js?turbopack-retry=1",
+    ]);
+  });
+
+  it("fails once all retries failed", async () => {
+    const runtime = loadDomRuntime({ esmChunks: true, chunkLoadRetries: 1 });
+
+    const error = await runtime.loadChunk("chunk.js").catch((error) => error);
+
+    expect(error.message).toContain(
+      "Failed to load chunk chunk.js from module parent"
+    );
+    expect(runtime.imports).toEqual([
+      "http://localhost/chunk.js",
+      "http://localhost/chunk.js?turbopack-retry=1",
+    ]);
+  });
+
+  it("doesn't retry unless retries are enabled", async () => {
+    const runtime = loadDomRuntime();
+
+    const loading = runtime.loadChunk("chunk.js").catch((error) => error);
+    runtime.elements[0].onerror!();
+    const error = await loading;
+
+    expect(error.message).toContain("Failed to load chunk chunk.js");
+    expect(runtime.elements).toHaveLength(1);
+  });
+});
+
+describe("build id", () => {
+  it("requests the chunks of the build of the runtime", () => {
+    const runtime = loadDomRuntime({ buildId: "b1" });
+
+    runtime.loadChunk("chunk.js");
+    runtime.loadChunk("style.css");
+
+    expect(runtime.elements.map(({ src, href }) => src ?? href)).toEqual([
+      "chunk.js?turbopack-build=b1",
+      "style.css?turbopack-build=b1",
+    ]);
+  });
+
+  it("requests ES module chunks of the build of the runtime", () => {
+    const runtime = loadDomRuntime({
+      buildId: "b1",
+      esmChunks: true,
+      import: () => new Promise(() => {}),
+    });
+
+    runtime.loadChunk("chunk.js");
+
+    expect(runtime.imports).toEqual([
+      "http://localhost/chunk.js?turbopack-build=b1",
+    ]);
+  });
+
+  it("reloads when a chunk of another build registers", async () => {
+    const runtime = loadDomRuntime({ buildId: "b1" });
+
+    runtime.registerChunk("chunk.js", {}, { ...CHUNK_PARAMS, buildId: "b2" });
+    await settle();
+
+    expect(runtime.reloads).toBe(1);
+    expect(runtime.warnings).toHaveLength(1);
+  });
+
+  it("reloads when a chunk list of another build registers", async () => {
+    const runtime = loadDomRuntime({ buildId: "b1" });
+
+    runtime.registerChunkList({
+      path: "list.js",
+      chunks: [],
+      source: "entry",
+      buildId: "b2",
+    });
+    await settle();
+
+    expect(runtime.reloads).toBe(1);
+  });
+
+  it("accepts chunks of the same build and chunks without a build id", async () => {
+    const runtime = loadDomRuntime({ buildId: "b1" });
+
+    runtime.registerChunk("chunk.js", {}, { ...CHUNK_PARAMS, buildId: "b1" });
+    runtime.registerChunk("other.js", {}, CHUNK_PARAMS);
+    await settle();
+
+    expect(runtime.reloads).toBe(0);
+    expect(runtime.warnings).toEqual([]);
+  });
+
+  it("ignores build ids when the runtime has none", async () => {
+    const runtime = loadDomRuntime();
+
+    runtime.registerChunk("chunk.js", {}, { ...CHUNK_PARAMS, buildId: "b2" });
+    await settle();
+
+    expect(runtime.reloads).toBe(0);
+  });
+});
+
+describe("ES module chunks", () => {
+  // Registers an entry chunk with an asynchronous entry, like a module with
+  // top-level await, which finishes evaluating once `evaluate` is called.
+  async function registerEntryChunk(esmChunks: boolean) {
+    const runtime = loadDomRuntime({ esmChunks });
+    let evaluate!: () => void;
+    const evaluated = new Promise<void>((resolve) => {
+      evaluate = resolve;
+    });
+    let registered = false;
+
+    Promise.resolve(
+      runtime.registerChunk(
+        "main.js",
+        {
+          entry: (context: any) =>
+            context.a(async (_deps: unknown, asyncResult: () => void) => {
+              await evaluated;
+              asyncResult();
+            }, true),
+        },
+        { ...CHUNK_PARAMS, runtimeModuleIds: ["entry"] }
+      )
+    ).then(() => {
+      registered = true;
+    });
+    await settle();
+
+    return { evaluate, registered: () => registered };
+  }
+
+  it("registers entry chunks once their asynchronous entries are evaluated", async () => {
+    const chunk = await registerEntryChunk(true);
+    expect(chunk.registered()).toBe(false);
+
+    chunk.evaluate();
+    await settle();
+
+    expect(chunk.registered()).toBe(true);
+  });
+
+  it("doesn't wait for the asynchronous entries of classic scripts", async () => {
+    const chunk = await registerEntryChunk(false);
+
+    expect(chunk.registered()).toBe(true);
+  });
+});
+
+describe("hot updates", () => {
+  const CHUNK_LIST = { path: "list.js", chunks: [], source: "entry" };
+
+  it("requests added chunks with the version of their content", () => {
+    const runtime = loadDomRuntime();
+
+    runtime.registerChunkList(CHUNK_LIST);
+    runtime.applyChunkListUpdate("list.js", {
+      chunks: {
+        "added.js": { type: "added", version: "v1" },
+        "added.css": { type: "added", version: "v/2" },
+      },
+    });
+
+    expect(runtime.elements.map(({ src, href }) => src ?? href)).toEqual([
+      "added.js?turbopack-v=v1",
+      "added.css?turbopack-v=v%2F2",
+    ]);
+  });
+
+  it("appends the version after the build id", () => {
+    const runtime = loadDomRuntime({ buildId: "b1" });
+
+    runtime.registerChunkList({ ...CHUNK_LIST, buildId: "b1" });
+    runtime.applyChunkListUpdate("list.js", {
+      chunks: { "added.js": { type: "added", version: "v1" } },
+    });
+
+    expect(runtime.elements[0].src).toBe(
+      "added.js?turbopack-build=b1&turbopack-v=v1"
+    );
+  });
+
+  it("requests chunks that weren't updated without a version", () => {
+    const runtime = loadDomRuntime();
+
+    runtime.registerChunkList(CHUNK_LIST);
+    runtime.applyChunkListUpdate("list.js", {
+      chunks: { "added.js": { type: "added", version: "v1" } },
+    });
+    runtime.loadChunk("chunk.js");
+
+    expect(runtime.elements[1].src).toBe("chunk.js");
+  });
+});
+
+describe("no eval", () => {
+  const CHUNK_LIST = { path: "list.js", chunks: [], source: "entry" };
+  const MODULE_UPDATE = {
+    merged: [
+      {
+        type: "EcmascriptMergedUpdate",
+        entries: { "module.js": { code: "(() => {})", url: "module.js" } },
+        chunks: {},
+      },
+    ],
+  };
+
+  it("leaves code evaluation out of the runtime", () => {
+    // Calls only, the runtime mentions them in comments.
+    const evaluation = /(^|[^\w.])eval\(|new Function\(/;
+
+    expect(loadDomRuntime({ allowEval: false }).code).not.toMatch(evaluation);
+    expect(loadDomRuntime().code).toMatch(evaluation);
+  });
+
+  it("reloads for hot updates that change modules", () => {
+    const runtime = loadDomRuntime({ allowEval: false });
+
+    runtime.registerChunkList(CHUNK_LIST);
+    runtime.applyChunkListUpdate("list.js", MODULE_UPDATE);
+
+    expect(runtime.reloads).toBe(1);
+    expect(runtime.warnings).toHaveLength(1);
+  });
+
+  it("applies hot updates that don't change modules", () => {
+    const runtime = loadDomRuntime({ allowEval: false });
+
+    runtime.registerChunkList(CHUNK_LIST);
+    runtime.applyChunkListUpdate("list.js", {
+      chunks: { "added.js": { type: "added", version: "v1" } },
+    });
+
+    expect(runtime.reloads).toBe(0);
+    expect(runtime.elements.map(({ src }) => src)).toEqual([
+      "added.js?turbopack-v=v1",
+    ]);
+  });
+});
+
+describe("unused chunk lists", () => {
+  // Replaces the code of the route.
+  const routeUpdate = (code: string) => ({
+    merged: [
+      {
+        type: "EcmascriptMergedUpdate",
+        entries: { route: { code, url: "route.js" } },
+        chunks: {},
+      },
+    ],
+  });
+
+  // The route is evaluated by the entry chunk and imports the module of an
+  // async chunk.
+  async function loadRoute() {
+    const runtime = loadDomRuntime();
+    runtime.registerChunkList({
+      path: "main-list.js",
+      chunks: ["main.js"],
+      source: "entry",
+    });
+    runtime.registerChunkList({
+      path: "lazy-list.js",
+      chunks: ["lazy.js"],
+      source: "dynamic",
+    });
+    runtime.registerChunk("lazy.js", { lazy: () => {} });
+    runtime.registerChunk(
+      "main.js",
+      {
+        route: (context: any) => {
+          context.m.hot.accept();
+          context.r("lazy");
+        },
+      },
+      { ...CHUNK_PARAMS, runtimeModuleIds: ["route"] }
+    );
+    await settle();
+    return runtime;
+  }
+
+  afterEach(() => {
+    jest.useRealTimers();
+  });
+
+  it("unloads async chunks that aren't imported anymore after an update", async () => {
+    const runtime = await loadRoute();
+    jest.useFakeTimers();
+
+    runtime.applyChunkListUpdate(
+      "main-list.js",
+      routeUpdate("(context) => { context.m.hot.accept(); }")
+    );
+    jest.advanceTimersByTime(9_999);
+    runtime.loadChunk("lazy.js");
+    expect(runtime.elements).toEqual([]);
+
+    jest.advanceTimersByTime(1);
+    runtime.loadChunk("lazy.js");
+    expect(runtime.elements.map(({ src }) => src)).toEqual(["lazy.js"]);
+  });
+
+  it("keeps async chunks that are still imported after an update", async () => {
+    const runtime = await loadRoute();
+    jest.useFakeTimers();
+
+    runtime.applyChunkListUpdate(
+      "main-list.js",
+      routeUpdate(
+        '(context) => { context.m.hot.accept(); context.r("lazy"); }'
+      )
+    );
+    jest.advanceTimersByTime(10_000);
+    runtime.loadChunk("lazy.js");
+
+    expect(runtime.elements).toEqual([]);
+  });
+});
+
+describe("cross origin", () => {
+  it("sets the crossorigin attribute on the tags that load chunks", () => {
+    const runtime = loadDomRuntime({ crossOrigin: "use-credentials" });
+
+    runtime.loadChunk("chunk.js");
+    runtime.loadChunk("style.css");
+
+    expect(runtime.elements.map(({ crossOrigin }) => crossOrigin)).toEqual([
+      "use-credentials",
+      "use-credentials",
+    ]);
+  });
+
+  it("keeps the configured mode for chunks with an integrity hash", () => {
+    const runtime = loadDomRuntime({ crossOrigin: "use-credentials" });
+
+    runtime.registerChunk(
+      "main.js",
+      {},
+      { ...CHUNK_PARAMS, integrity: { "chunk.js": "sha384-abc" } }
+    );
+    runtime.loadChunk("chunk.js");
+
+    expect(runtime.elements[0].crossOrigin).toBe("use-credentials");
+  });
+
+  it("doesn't set the crossorigin attribute unless it's configured", () => {
+    const runtime = loadDomRuntime();
+
+    runtime.loadChunk("chunk.js");
+
+    expect(runtime.elements[0].crossOrigin).toBeNull();
+  });
+});
+
+describe("content security policy", () => {
+  it("sets the nonce on the tags that load chunks", () => {
+    const runtime = loadDomRuntime({
+      cspNonceVar: "__webpack_nonce__",
+      globals: { __webpack_nonce__: "n0nce" },
+    });
+
+    runtime.loadChunk("chunk.js");
+    runtime.loadChunk("style.css");
+
+    expect(runtime.elements.map(({ nonce }) => nonce)).toEqual([
+      "n0nce",
+      "n0nce",
+    ]);
+  });
+
+  it("doesn't set a nonce when the global isn't set", () => {
+    const runtime = loadDomRuntime({ cspNonceVar: "__webpack_nonce__" });
+
+    runtime.loadChunk("chunk.js");
+
+    expect(runtime.elements[0].nonce).toBeUndefined();
+  });
+
+  it("doesn't set a nonce unless its global is configured", () => {
+    const runtime = loadDomRuntime({
+      globals: { __webpack_nonce__: "n0nce" },
+    });
+
+    runtime.loadChunk("chunk.js");
+
+    expect(runtime.elements[0].nonce).toBeUndefined();
+  });
+});
+
+describe("subresource integrity", () => {
+  it("loads chunks with the integrity hashes of the runtime chunk", () => {
+    const runtime = loadDomRuntime();
+
+    runtime.registerChunk(
+      "main.js",
+      {},
+      {
+        ...CHUNK_PARAMS,
+        integrity: { "chunk.js": "sha384-abc", "style.css": "sha384-def" },
+      }
+    );
+    runtime.loadChunk("chunk.js");
+    runtime.loadChunk("style.css");
+
+    expect(
+      runtime.elements.map(({ tagName, integrity, crossOrigin }) => ({
+        tagName,
+        integrity,
+        crossOrigin,
+      }))
+    ).toEqual([
+      { tagName: "script", integrity: "sha384-abc", crossOrigin: "anonymous" },
+      { tagName: "link", integrity: "sha384-def", crossOrigin: "anonymous" },
+    ]);
+  });
+
+  it("loads chunks without a hash without integrity", () => {
+    const runtime = loadDomRuntime();
+
+    runtime.registerChunk(
+      "main.js",
+      {},
+      { ...CHUNK_PARAMS, integrity: { "other.js": "sha384-abc" } }
+    );
+    runtime.loadChunk("chunk.js");
+
+    expect(runtime.elements[0].integrity).toBeUndefined();
+    expect(runtime.elements[0].crossOrigin).toBeNull();
+  });
+});
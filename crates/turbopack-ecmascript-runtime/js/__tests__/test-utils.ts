@@ -0,0 +1,252 @@
+import * as fs from "node:fs";
+import * as os from "node:os";
+import * as path from "node:path";
+import * as vm from "node:vm";
+import * as ts from "typescript";
+
+const SRC_DIR = path.join(__dirname, "..", "src");
+
+/**
+ * Transpiles a file of the runtime to JavaScript. The files are scripts that
+ * are concatenated into the runtime, see `dev_runtime.rs`.
+ */
+function transpile(file: string): string {
+  const source = fs.readFileSync(path.join(SRC_DIR, file), "utf8");
+  return ts.transpileModule(source, {
+    compilerOptions: { target: ts.ScriptTarget.ES2020 },
+    fileName: file,
+  }).outputText;
+}
+
+/**
+ * A `<script>` or `<link>` tag that the runtime injects to load a chunk.
+ */
+export class FakeElement {
+  src?: string;
+  href?: string;
+  rel?: string;
+  integrity?: string;
+  crossOrigin?: string | null = null;
+  nonce?: string;
+  onload?: () => void;
+  onerror?: () => void;
+  removed = false;
+
+  constructor(readonly tagName: string) {}
+
+  remove() {
+    this.removed = true;
+  }
+}
+
+export interface DomRuntimeOptions {
+  buildId?: string;
+  esmChunks?: boolean;
+  chunkLoadRetries?: number;
+  /** Defaults to `true`, like `DevChunkingContext`. */
+  allowEval?: boolean;
+  /** The `crossorigin` attribute of the tags that load chunks. */
+  crossOrigin?: "anonymous" | "use-credentials";
+  /** The global variable the nonce of the Content Security Policy is in. */
+  cspNonceVar?: string;
+  /** Global variables of the page, e.g. the nonce. */
+  globals?: Record<string, unknown>;
+  /**
+   * Called instead of `import()` when the runtime loads an ES module chunk.
+   */
+  import?: (url: string, runtime: DomRuntime) => Promise<unknown>;
+}
+
+export interface DomRuntime {
+  /** The code of the runtime. */
+  code: string;
+  /** The tags the runtime injected, in order. */
+  elements: FakeElement[];
+  /** The URLs the runtime imported, in order. */
+  imports: string[];
+  /** The warnings the runtime logged. */
+  warnings: string[];
+  /** How often the runtime reloaded the page. */
+  reloads: number;
+  /**
+   * Registers a chunk like the code of a chunk does. Resolves once the entries
+   * of the chunk have been evaluated, when they are awaited.
+   */
+  registerChunk(
+    chunkPath: string,
+    moduleFactories?: Record<string, unknown>,
+    params?: Record<string, unknown>
+  ): Promise<void> | undefined;
+  /** Registers a chunk list like the code of a chunk list does. */
+  registerChunkList(chunkList: Record<string, unknown>): void;
+  /**
+   * Applies an update of a registered chunk list like the update server sends
+   * it.
+   */
+  applyChunkListUpdate(
+    chunkListPath: string,
+    update: Record<string, unknown>
+  ): void;
+  /** Loads a chunk as if the module `parentId` imported it. */
+  loadChunk(chunkPath: string, parentId?: string): Promise<void>;
+}
+
+/**
+ * Evaluates the development DOM runtime in a fake DOM, like
+ * `get_dev_runtime_code` assembles it for a browser environment without a
+ * chunk base path.
+ */
+export function loadDomRuntime(options: DomRuntimeOptions = {}): DomRuntime {
+  const runtime: DomRuntime = {
+    code: "",
+    elements: [],
+    imports: [],
+    warnings: [],
+    reloads: 0,
+    registerChunk(chunkPath, moduleFactories = {}, params) {
+      return context.TURBOPACK.push([chunkPath, moduleFactories, params]);
+    },
+    registerChunkList(chunkList) {
+      context.TURBOPACK_CHUNK_LISTS.push(chunkList);
+    },
+    applyChunkListUpdate(chunkListPath, update) {
+      for (const [path, handler] of context.TURBOPACK_CHUNK_UPDATE_LISTENERS) {
+        if (path === chunkListPath) {
+          handler({
+            type: "partial",
+            instruction: { type: "ChunkListUpdate", ...update },
+          });
+        }
+      }
+    },
+    loadChunk(chunkPath, parentId = "parent") {
+      return context.TURBOPACK_TEST.loadChunkPath(
+        { type: context.TURBOPACK_TEST.SourceType.Parent, parentId },
+        chunkPath
+      );
+    },
+  };
+
+  const location = {
+    origin: "http://localhost",
+    reload: () => {
+      runtime.reloads++;
+    },
+  };
+  const context: any = vm.createContext({
+    TURBOPACK: [],
+    URL,
+    // Looked up on every call, so tests can fake the timers.
+    setTimeout: (callback: () => void, delay?: number) =>
+      setTimeout(callback, delay),
+    clearTimeout: (timeout: ReturnType<typeof setTimeout>) =>
+      clearTimeout(timeout),
+    location,
+    navigator: { userAgent: "jest" },
+    console: {
+      ...console,
+      warn: (message: string) => runtime.warnings.push(message),
+    },
+    document: {
+      baseURI: "http://localhost/",
+      body: {
+        appendChild: (element: FakeElement) => runtime.elements.push(element),
+      },
+      createElement: (tagName: string) => new FakeElement(tagName),
+      querySelectorAll: () => [],
+    },
+    ...options.globals,
+    __import: (url: string) => {
+      runtime.imports.push(url);
+      return options.import
+        ? options.import(url, runtime)
+        : Promise.reject(new Error(`no chunk at ${url}`));
+    },
+  });
+  context.self = context;
+
+  // Scripts in a `vm` context can't `import()` without a module linker.
+  const backend = transpile("dev/runtime/dom/runtime-backend-dom.ts").replace(
+    /\bimport\(/g,
+    "__import("
+  );
+  const allowEval = options.allowEval ?? true;
+  const runtimeEval = allowEval
+    ? "dev/runtime/dom/runtime-eval-dom.ts"
+    : "dev/runtime/base/runtime-no-eval.ts";
+  const code = `(() => {
+    if (!Array.isArray(globalThis.TURBOPACK)) {
+      return;
+    }
+
+    const CHUNK_BASE_PATH = "";
+    const RUNTIME_PUBLIC_PATH = "";
+    const OUTPUT_ROOT = "output";
+    const BUILD_ID = ${JSON.stringify(options.buildId ?? null)};
+    const ALLOW_EVAL = ${allowEval};
+    const CSP_NONCE_VAR = ${JSON.stringify(options.cspNonceVar ?? null)};
+    const CROSS_ORIGIN = ${JSON.stringify(options.crossOrigin ?? null)};
+    const CHUNK_LOAD_RETRIES = ${options.chunkLoadRetries ?? 0};
+    const CHUNK_LOAD_RETRY_DELAY = 0;
+    const ESM_CHUNKS = ${options.esmChunks ?? false};
+    const CHUNK_LISTS_GLOBAL = "TURBOPACK_CHUNK_LISTS";
+
+    ${transpile("shared/runtime-utils.ts")}
+    ${transpile("dev/runtime/base/runtime-base.ts")}
+    ${backend}
+    ${transpile(runtimeEval)}
+
+    const chunksToRegister = globalThis.TURBOPACK;
+    globalThis.TURBOPACK = { push: registerChunk };
+    chunksToRegister.forEach(registerChunk);
+
+    globalThis.TURBOPACK_TEST = { loadChunkPath, SourceType };
+  })();`;
+  runtime.code = code;
+  vm.runInContext(code, context, { filename: "runtime.js" });
+
+  return runtime;
+}
+
+export interface BuildRuntime {
+  /** The directory the runtime and its chunks are in. */
+  dir: string;
+  registerChunkIntegrity(integrity: Record<string, string>): void;
+  loadChunk(chunkPath: string): void;
+  getOrInstantiateRuntimeModule(
+    moduleId: string,
+    chunkPath: string
+  ): { exports: any };
+  runWithExecutionContext<T>(context: unknown, fn: () => T): T;
+  getExecutionContext(): unknown;
+}
+
+/**
+ * Writes the Node.js build runtime to a temporary directory, like the runtime
+ * chunk of a build does, and loads it.
+ */
+export function loadBuildRuntime(): BuildRuntime {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), "turbopack-runtime-"));
+  const code = [
+    `const RUNTIME_PUBLIC_PATH = "runtime.js";`,
+    `const OUTPUT_ROOT = "";`,
+    `const ASSET_PREFIX = "/";`,
+    transpile("shared/runtime-utils.ts"),
+    transpile("shared-node/base-externals-utils.ts"),
+    transpile("shared-node/node-externals-utils.ts"),
+    transpile("shared-node/node-wasm-utils.ts"),
+    transpile("shared-node/node-execution-context.ts"),
+    transpile("build/runtime.ts"),
+  ].join("\n");
+  fs.writeFileSync(path.join(dir, "runtime.js"), code);
+
+  return { dir, ...require(path.join(dir, "runtime.js")) };
+}
+
+/**
+ * Waits until the runtime has handled everything that's currently pending,
+ * e.g. the timer of a retry.
+ */
+export function settle(): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, 10));
+}
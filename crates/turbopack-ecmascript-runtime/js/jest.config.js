@@ -0,0 +1,9 @@
+/** @type {import('ts-jest/dist/types').InitialOptionsTsJest} */
+module.exports = {
+  preset: "ts-jest/presets/js-with-ts",
+  testEnvironment: "node",
+  testPathIgnorePatterns: ["/__tests__/test-utils.ts"],
+  modulePathIgnorePatterns: ["<rootDir>/node_modules"],
+  verbose: process.env.RUNNER_DEBUG === "1",
+  silent: process.env.RUNNER_DEBUG !== "1",
+};
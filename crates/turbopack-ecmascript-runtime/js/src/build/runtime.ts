@@ -2,6 +2,7 @@
 /// <reference path="../shared-node/base-externals-utils.ts" />
 /// <reference path="../shared-node/node-externals-utils.ts" />
 /// <reference path="../shared-node/node-wasm-utils.ts" />
+/// <reference path="../shared-node/node-execution-context.ts" />
 
 enum SourceType {
   /**
@@ -42,6 +43,8 @@ type ModuleFactory = (
 ) => undefined;
 
 const url = require("url");
+const crypto = require("crypto");
+const fs = require("fs");
 
 const moduleFactories: ModuleFactories = Object.create(null);
 const moduleCache: ModuleCache = Object.create(null);
@@ -70,6 +73,45 @@ function createResolvePathFromModule(
   };
 }
 
+/**
+ * Maps chunk paths to their Subresource Integrity hashes, which are verified
+ * before the chunks are loaded.
+ */
+const chunkIntegrity: Map<ChunkPath, string> = new Map();
+
+/**
+ * Registers the Subresource Integrity hashes of chunks, e.g.
+ * `sha384-<base64 digest>`. This is called by entry chunks before they load
+ * other chunks.
+ */
+function registerChunkIntegrity(integrity: Record<ChunkPath, string>) {
+  for (const [chunkPath, hash] of Object.entries(integrity)) {
+    chunkIntegrity.set(chunkPath as ChunkPath, hash);
+  }
+}
+
+/**
+ * Throws if the content of the chunk at `resolved` doesn't match the
+ * Subresource Integrity hash of the chunk, if there is one.
+ */
+function verifyChunkIntegrity(chunkPath: ChunkPath, resolved: string) {
+  const integrity = chunkIntegrity.get(chunkPath);
+  if (integrity == null) {
+    return;
+  }
+  const separator = integrity.indexOf("-");
+  const algorithm = integrity.slice(0, separator);
+  const digest = crypto
+    .createHash(algorithm)
+    .update(fs.readFileSync(resolved))
+    .digest("base64");
+  if (digest !== integrity.slice(separator + 1)) {
+    throw new Error(
+      `chunk ${chunkPath} doesn't match its integrity hash ${integrity}`
+    );
+  }
+}
+
 function loadChunk(chunkData: ChunkData): void {
   if (typeof chunkData === "string") {
     return loadChunkPath(chunkData);
@@ -86,6 +128,7 @@ function loadChunkPath(chunkPath: ChunkPath): void {
   }
 
   const resolved = path.resolve(RUNTIME_ROOT, chunkPath);
+  verifyChunkIntegrity(chunkPath, resolved);
   const chunkModules: ModuleFactories = require(resolved);
 
   for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
@@ -168,30 +211,32 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
   // NOTE(alexkirsz) This can fail when the module encounters a runtime error.
   try {
     const r = commonJsRequire.bind(null, module);
-    moduleFactory.call(module.exports, {
-      a: asyncModule.bind(null, module),
-      e: module.exports,
-      r,
-      t: runtimeRequire,
-      x: externalRequire,
-      y: externalImport,
-      f: requireContext.bind(null, module),
-      i: esmImport.bind(null, module),
-      s: esmExport.bind(null, module, module.exports),
-      j: dynamicExport.bind(null, module, module.exports),
-      v: exportValue.bind(null, module),
-      n: exportNamespace.bind(null, module),
-      m: module,
-      c: moduleCache,
-      l: loadChunkAsync.bind(null, { type: SourceType.Parent, parentId: id }),
-      w: loadWebAssembly,
-      u: loadWebAssemblyModule,
-      g: globalThis,
-      p: resolveAbsolutePath,
-      U: relativeURL,
-      R: createResolvePathFromModule(r),
-      __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
-    });
+    runOutsideExecutionContext(() =>
+      moduleFactory.call(module.exports, {
+        a: asyncModule.bind(null, module),
+        e: module.exports,
+        r,
+        t: runtimeRequire,
+        x: externalRequire,
+        y: externalImport,
+        f: requireContext.bind(null, module),
+        i: esmImport.bind(null, module),
+        s: esmExport.bind(null, module, module.exports),
+        j: dynamicExport.bind(null, module, module.exports),
+        v: exportValue.bind(null, module),
+        n: exportNamespace.bind(null, module),
+        m: module,
+        c: moduleCache,
+        l: loadChunkAsync.bind(null, { type: SourceType.Parent, parentId: id }),
+        w: loadWebAssembly,
+        u: loadWebAssemblyModule,
+        g: globalThis,
+        p: resolveAbsolutePath,
+        U: relativeURL,
+        R: createResolvePathFromModule(r),
+        __dirname: module.id.replace(/(^|\/)[\/]+$/, ""),
+      })
+    );
   } catch (error) {
     module.error = error as any;
     throw error;
@@ -264,4 +309,7 @@ function getOrInstantiateRuntimeModule(
 module.exports = {
   getOrInstantiateRuntimeModule,
   loadChunk,
+  registerChunkIntegrity,
+  runWithExecutionContext,
+  getExecutionContext,
 };
@@ -46,7 +46,7 @@ export function connect({
   }
   globalThis.TURBOPACK_CHUNK_UPDATE_LISTENERS = {
     push: ([chunkPath, callback]: [ChunkPath, UpdateCallback]) => {
-      subscribeToChunkUpdate(chunkPath, sendMessage, callback);
+      return subscribeToChunkUpdate(chunkPath, sendMessage, callback);
     },
   };
 
@@ -563,6 +563,20 @@ export function subscribeToUpdate(
   };
 }
 
+/**
+ * Hints the server that `paths` (e.g. routes linked from the current page) are
+ * likely to be requested next, so it can precompile them while idle.
+ */
+export function precompile(
+  paths: string[],
+  sendMessage: SendMessage = turboSocketSendMessage
+) {
+  sendJSON(sendMessage, {
+    type: "turbopack-precompile",
+    paths,
+  });
+}
+
 function triggerUpdate(msg: ServerMessage) {
   const key = resourceKey(msg.resource);
   const callbackSet = updateCallbackSets.get(key);
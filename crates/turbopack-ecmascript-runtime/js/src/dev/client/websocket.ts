@@ -1,6 +1,9 @@
 // Adapted from https://github.com/vercel/next.js/blob/canary/packages/next/client/dev/error-overlay/websocket.ts
 
-let source: WebSocket;
+let source: WebSocket | undefined;
+// The session of the long-polling fallback, which is used when WebSockets are
+// blocked, e.g. by a corporate proxy.
+let pollingSession: { url: string; id: string } | undefined;
 const eventCallbacks: ((msg: WebSocketMessage) => void)[] = [];
 
 // TODO: add timeout again
@@ -31,6 +34,16 @@ export function addMessageListener(cb: (msg: WebSocketMessage) => void) {
 }
 
 export function sendMessage(data: any) {
+  if (pollingSession) {
+    const { url, id } = pollingSession;
+    fetch(`${url}/send?session=${encodeURIComponent(id)}`, {
+      method: "POST",
+      body: data,
+    }).catch(() => {
+      // The next poll fails as well and reconnects.
+    });
+    return;
+  }
   if (!source || source.readyState !== source.OPEN) return;
   return source.send(data);
 }
@@ -45,56 +58,122 @@ export type HMROptions = {
 export function connectHMR(options: HMROptions) {
   const { timeout = 5 * 1000 } = options;
 
+  function handleOnline() {
+    const connected = { type: "turbopack-connected" as const };
+    eventCallbacks.forEach((cb) => {
+      cb(connected);
+    });
+
+    if (options.log) console.log("[HMR] connected");
+  }
+
+  function dispatchMessage(data: string) {
+    const message = {
+      type: "turbopack-message" as const,
+      data: JSON.parse(data),
+    };
+    eventCallbacks.forEach((cb) => {
+      cb(message);
+    });
+  }
+
+  function getUrl(protocol: string) {
+    const { hostname, port } = location;
+    const assetPrefix = options.assetPrefix.replace(/^\/+/, "");
+
+    let url = `${protocol}://${hostname}:${port}${
+      assetPrefix ? `/${assetPrefix}` : ""
+    }`;
+
+    if (assetPrefix.startsWith("http")) {
+      url = `${protocol}://${assetPrefix.split("://")[1]}`;
+    }
+
+    return `${url}${options.path}`;
+  }
+
+  // Falls back to long polling, which only needs plain HTTP requests, once a
+  // WebSocket failed to open. Polls are held open by the dev server until
+  // there are updates. When the dev server can't be reached, the transport is
+  // negotiated again, starting with a WebSocket.
+  async function initLongPolling() {
+    pollingSession = undefined;
+    const url = getUrl(
+      getSocketProtocol(options.assetPrefix || "") === "ws" ? "http" : "https"
+    );
+
+    let session: { url: string; id: string };
+    try {
+      const response = await fetch(`${url}/connect`, { method: "POST" });
+      if (!response.ok) throw new Error(`status ${response.status}`);
+      session = { url, id: (await response.json()).session };
+    } catch (_) {
+      setTimeout(init, timeout);
+      return;
+    }
+    pollingSession = session;
+
+    if (options.log) console.log("[HMR] using long polling");
+    handleOnline();
+
+    while (pollingSession === session) {
+      let messages: string[];
+      try {
+        const response = await fetch(
+          `${url}/poll?session=${encodeURIComponent(session.id)}`,
+          { cache: "no-store" }
+        );
+        // The session expired, e.g. because the dev server restarted.
+        if (!response.ok) throw new Error(`status ${response.status}`);
+        messages = await response.json();
+      } catch (_) {
+        pollingSession = undefined;
+        setTimeout(init, timeout);
+        return;
+      }
+      messages.forEach(dispatchMessage);
+    }
+  }
+
   function init() {
     if (source) source.close();
 
     console.log("[HMR] connecting...");
 
-    function handleOnline() {
-      const connected = { type: "turbopack-connected" as const };
-      eventCallbacks.forEach((cb) => {
-        cb(connected);
-      });
+    let opened = false;
 
-      if (options.log) console.log("[HMR] connected");
+    function handleOpen() {
+      opened = true;
+      handleOnline();
       // lastActivity = Date.now()
     }
 
     function handleMessage(event: MessageEvent) {
       // lastActivity = Date.now()
-
-      const message = {
-        type: "turbopack-message" as const,
-        data: JSON.parse(event.data),
-      };
-      eventCallbacks.forEach((cb) => {
-        cb(message);
-      });
+      dispatchMessage(event.data);
     }
 
     // let timer: NodeJS.Timeout
 
     function handleDisconnect() {
-      source.close();
+      source?.close();
+      if (!opened) {
+        // WebSockets are likely blocked, as the dev server answers plain
+        // HTTP requests.
+        source = undefined;
+        initLongPolling();
+        return;
+      }
       setTimeout(init, timeout);
     }
 
-    const { hostname, port } = location;
-    const protocol = getSocketProtocol(options.assetPrefix || "");
-    const assetPrefix = options.assetPrefix.replace(/^\/+/, "");
-
-    let url = `${protocol}://${hostname}:${port}${
-      assetPrefix ? `/${assetPrefix}` : ""
-    }`;
-
-    if (assetPrefix.startsWith("http")) {
-      url = `${protocol}://${assetPrefix.split("://")[1]}`;
-    }
-
-    source = new window.WebSocket(`${url}${options.path}`);
-    source.onopen = handleOnline;
-    source.onerror = handleDisconnect;
-    source.onmessage = handleMessage;
+    const socket = new window.WebSocket(
+      getUrl(getSocketProtocol(options.assetPrefix || ""))
+    );
+    socket.onopen = handleOpen;
+    socket.onerror = handleDisconnect;
+    socket.onmessage = handleMessage;
+    source = socket;
   }
 
   init();
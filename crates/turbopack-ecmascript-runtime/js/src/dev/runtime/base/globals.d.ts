@@ -16,15 +16,40 @@ type ChunkListProvider = {
 };
 
 type ChunkUpdateProvider = {
-  push: (registration: [ChunkPath, UpdateCallback]) => void;
+  /**
+   * Subscribes to updates of a chunk list. Returns a function that ends the
+   * subscription, if the provider supports it.
+   */
+  push: (registration: [ChunkPath, UpdateCallback]) => (() => void) | void;
 };
 
+type RemoteContainer = {
+  get: (expose: string) => Promise<EsmNamespaceObject>;
+};
+
+type RemoteRegistration = {
+  container?: RemoteContainer;
+  promise?: Promise<RemoteContainer>;
+  resolve?: (container: RemoteContainer) => void;
+};
+
+// The chunks and chunk lists register with these globals, unless the chunking
+// context names different ones.
 declare var TURBOPACK: ChunkRegistry | ChunkRegistration[] | undefined;
 declare var TURBOPACK_CHUNK_LISTS: ChunkListProvider | ChunkList[] | undefined;
+// Maps the names of federated apps to their containers, which their remote
+// entries register.
+declare var TURBOPACK_REMOTES: Record<string, RemoteRegistration> | undefined;
+// Returns the IDs of the modules executed during this session as JSON, which
+// can be passed to turbopack to bias chunking.
+declare var TURBOPACK_USAGE_PROFILE: (() => string) | undefined;
 declare var TURBOPACK_CHUNK_UPDATE_LISTENERS:
   | ChunkUpdateProvider
   | [ChunkPath, UpdateCallback][]
   | undefined;
+// Maps evaluated chunks to the exports of their last runtime module, which
+// they export as CommonJS modules. Only the Node.js backend sets this.
+declare var TURBOPACK_CHUNK_EXPORTS: Record<ChunkPath, Exports> | undefined;
 // This is used by the Next.js integration test suite to notify it when HMR
 // updates have been completed.
 declare var __NEXT_HMR_CB: undefined | null | (() => void);
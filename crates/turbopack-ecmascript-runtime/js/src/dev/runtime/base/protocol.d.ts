@@ -40,14 +40,16 @@ type ChunkListUpdate = {
   type: "ChunkListUpdate";
   chunks?: Record<ChunkPath, ChunkUpdate>;
   merged?: MergedChunkUpdate[];
+  buildId?: string;
 };
 
 type ChunkUpdate =
   | {
       type: "added";
+      version: string;
     }
   | { type: "deleted" }
-  | { type: "total" }
+  | { type: "total"; version: string }
   // We currently don't have any chunks that can be updated partially that can't
   // be merged either. So these updates would go into `MergedChunkUpdate` instead.
   | { type: "partial"; instruction: never };
@@ -101,7 +103,15 @@ type ClientMessageUnsubscribe = {
   type: "turbopack-unsubscribe";
 } & ResourceIdentifier;
 
-type ClientMessage = ClientMessageSubscribe | ClientMessageUnsubscribe;
+type ClientMessagePrecompile = {
+  type: "turbopack-precompile";
+  paths: string[];
+};
+
+type ClientMessage =
+  | ClientMessageSubscribe
+  | ClientMessageUnsubscribe
+  | ClientMessagePrecompile;
 
 type IssueSeverity =
   | "bug"
@@ -20,6 +20,14 @@ type RefreshRuntimeGlobals =
   import("@next/react-refresh-utils/dist/runtime").RefreshRuntimeGlobals;
 
 declare var CHUNK_BASE_PATH: string;
+declare var BUILD_ID: string | null;
+declare var ALLOW_EVAL: boolean;
+declare var CSP_NONCE_VAR: string | null;
+declare var CROSS_ORIGIN: string | null;
+declare var CHUNK_LOAD_RETRIES: number;
+declare var CHUNK_LOAD_RETRY_DELAY: number;
+declare var ESM_CHUNKS: boolean;
+declare var CHUNK_LISTS_GLOBAL: string;
 declare var $RefreshHelpers$: RefreshRuntimeGlobals["$RefreshHelpers$"];
 declare var $RefreshReg$: RefreshRuntimeGlobals["$RefreshReg$"];
 declare var $RefreshSig$: RefreshRuntimeGlobals["$RefreshSig$"];
@@ -33,8 +41,15 @@ type RefreshContext = {
 
 type RefreshHelpers = RefreshRuntimeGlobals["$RefreshHelpers$"];
 
+type LoadRemote = (
+  remoteEntryUrl: string,
+  name: string,
+  expose: string
+) => Promise<EsmNamespaceObject>;
+
 interface TurbopackDevBaseContext extends TurbopackBaseContext {
   k: RefreshContext;
+  F: LoadRemote;
 }
 
 interface TurbopackDevContext extends TurbopackDevBaseContext {}
@@ -49,7 +64,10 @@ type ModuleFactory = (
 
 type DevRuntimeParams = {
   otherChunks: ChunkData[];
+  deferredChunks?: ChunkData[];
   runtimeModuleIds: ModuleId[];
+  buildId?: string;
+  integrity?: Record<ChunkPath, string>;
 };
 
 type ChunkRegistration = [
@@ -61,6 +79,7 @@ type ChunkList = {
   path: ChunkPath;
   chunks: ChunkData[];
   source: "entry" | "dynamic";
+  buildId?: string;
 };
 
 enum SourceType {
@@ -99,6 +118,16 @@ interface RuntimeBackend {
   loadChunk: (chunkPath: ChunkPath, source: SourceInfo) => Promise<void>;
   reloadChunk?: (chunkPath: ChunkPath) => Promise<void>;
   unloadChunk?: (chunkPath: ChunkPath) => void;
+  /**
+   * Executes a module factory. Backends can use this to set up the context
+   * the module scope is evaluated in.
+   */
+  runModuleFactory?: (execute: () => void) => void;
+  /**
+   * Loads the remote entry of a federated app from `url`, which registers its
+   * container in `TURBOPACK_REMOTES` once it has been evaluated.
+   */
+  loadRemoteEntry?: (url: string) => Promise<void>;
 
   restart: () => void;
 }
@@ -122,6 +151,21 @@ const queuedInvalidatedModules: Set<ModuleId> = new Set();
  * Module IDs that are instantiated as part of the runtime of a chunk.
  */
 const runtimeModules: Set<ModuleId> = new Set();
+/**
+ * Module IDs that have been instantiated during this session. They are exported
+ * as usage profile with `TURBOPACK_USAGE_PROFILE()`.
+ */
+const executedModules: Set<ModuleId> = new Set();
+/**
+ * Maps module IDs to the export getters of their current instance.
+ *
+ * Getters defined on ESM exports can't be redefined, so they delegate to this
+ * map instead. This allows replacing the exports of modules that only export
+ * functions without re-executing their importers, which keep referencing the
+ * exports object of the previous instance.
+ */
+const moduleExportGetters: Map<ModuleId, Record<string, () => any>> =
+  new Map();
 /**
  * Map from module ID to the chunks that contain this module.
  *
@@ -148,11 +192,30 @@ const chunkListChunksMap: Map<ChunkPath, Set<ChunkPath>> = new Map();
  * Map from a chunk path to the chunk lists it belongs to.
  */
 const chunkChunkListsMap: Map<ChunkPath, Set<ChunkPath>> = new Map();
+/**
+ * Chunk lists of async chunk groups. Unlike runtime chunk lists, they are
+ * collected once their modules aren't imported anymore, see
+ * `collectUnusedChunkLists`.
+ */
+const dynamicChunkLists: Set<ChunkPath> = new Set();
+/**
+ * Maps chunk lists to the function that ends their update subscription, if
+ * the update provider returned one.
+ */
+const chunkListUnsubscribes: Map<ChunkPath, () => void> = new Map();
 
 const availableModules: Map<ModuleId, Promise<any> | true> = new Map();
 
 const availableModuleChunks: Map<ChunkPath, Promise<any> | true> = new Map();
 
+/**
+ * Maps chunk paths to their Subresource Integrity hashes, which backends set
+ * on the tags they inject to load the chunks. They're registered by the
+ * evaluated chunks, and discarded once an update was applied, as the chunks
+ * changed since the hashes were computed.
+ */
+const chunkIntegrity: Map<ChunkPath, string> = new Map();
+
 async function loadChunk(
   source: SourceInfo,
   chunkData: ChunkData
@@ -232,7 +295,7 @@ async function loadChunkPath(
   chunkPath: ChunkPath
 ): Promise<any> {
   try {
-    await BACKEND.loadChunk(chunkPath, source);
+    await loadChunkWithRetries(chunkPath, source);
   } catch (error) {
     let loadReason;
     switch (source.type) {
@@ -259,6 +322,68 @@ async function loadChunkPath(
   }
 }
 
+/**
+ * Loads a chunk, retrying up to `CHUNK_LOAD_RETRIES` times with an
+ * exponentially growing delay, e.g. when the dev server was briefly
+ * unreachable. Retrying requires the backend to be able to unload the failed
+ * attempt.
+ */
+async function loadChunkWithRetries(
+  chunkPath: ChunkPath,
+  source: SourceInfo
+): Promise<void> {
+  for (let attempt = 0; ; attempt++) {
+    try {
+      await BACKEND.loadChunk(chunkPath, source);
+      chunkLoadRetries.delete(chunkPath);
+      return;
+    } catch (error) {
+      if (attempt >= CHUNK_LOAD_RETRIES || BACKEND.unloadChunk == null) {
+        chunkLoadRetries.delete(chunkPath);
+        throw error;
+      }
+      BACKEND.unloadChunk(chunkPath);
+      chunkLoadRetries.set(chunkPath, attempt + 1);
+      await new Promise((resolve) =>
+        setTimeout(resolve, CHUNK_LOAD_RETRY_DELAY * 2 ** attempt)
+      );
+    }
+  }
+}
+
+/**
+ * Loads the module that the federated app `name` exposes as `expose`, loading
+ * its remote entry from `remoteEntryUrl` first if necessary.
+ */
+async function loadRemote(
+  remoteEntryUrl: string,
+  name: string,
+  expose: string
+): Promise<EsmNamespaceObject> {
+  const remote = ((globalThis.TURBOPACK_REMOTES ??= {})[name] ??= {});
+  let container = remote.container;
+  if (container == null) {
+    if (BACKEND.loadRemoteEntry == null) {
+      throw new Error(
+        `can't load the remote ${name} from ${remoteEntryUrl} in this environment`
+      );
+    }
+    // The container is registered when the remote entry has been evaluated,
+    // which can be after the entry itself has been loaded.
+    remote.promise ??= new Promise((resolve, reject) => {
+      remote.resolve = resolve;
+      BACKEND.loadRemoteEntry!(remoteEntryUrl).catch(() => {
+        delete remote.promise;
+        reject(
+          new Error(`failed to load the remote entry ${remoteEntryUrl} of ${name}`)
+        );
+      });
+    });
+    container = await remote.promise;
+  }
+  return container.get(expose);
+}
+
 function instantiateModule(id: ModuleId, source: SourceInfo): Module {
   const moduleFactory = moduleFactories[id];
   if (typeof moduleFactory !== "function") {
@@ -313,37 +438,46 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
 
   moduleCache[id] = module;
   moduleHotState.set(module, hotState);
+  executedModules.add(id);
 
   // NOTE(alexkirsz) This can fail when the module encounters a runtime error.
   try {
     const sourceInfo: SourceInfo = { type: SourceType.Parent, parentId: id };
 
-    runModuleExecutionHooks(module, (refresh) => {
-      moduleFactory.call(
-        module.exports,
-        augmentContext({
-          a: asyncModule.bind(null, module),
-          e: module.exports,
-          r: commonJsRequire.bind(null, module),
-          t: runtimeRequire,
-          f: requireContext.bind(null, module),
-          i: esmImport.bind(null, module),
-          s: esmExport.bind(null, module, module.exports),
-          j: dynamicExport.bind(null, module, module.exports),
-          v: exportValue.bind(null, module),
-          n: exportNamespace.bind(null, module),
-          m: module,
-          c: moduleCache,
-          l: loadChunk.bind(null, sourceInfo),
-          w: loadWebAssembly.bind(null, sourceInfo),
-          u: loadWebAssemblyModule.bind(null, sourceInfo),
-          g: globalThis,
-          U: relativeURL,
-          k: refresh,
-          __dirname: module.id.replace(/(^|\/)\/+$/, ""),
-        })
-      );
-    });
+    const execute = () =>
+      runModuleExecutionHooks(module, (refresh) => {
+        moduleFactory.call(
+          module.exports,
+          augmentContext({
+            a: asyncModule.bind(null, module),
+            e: module.exports,
+            r: commonJsRequire.bind(null, module),
+            t: runtimeRequire,
+            f: requireContext.bind(null, module),
+            i: esmImport.bind(null, module),
+            s: patchableEsmExport.bind(null, module, module.exports),
+            j: dynamicExport.bind(null, module, module.exports),
+            v: exportValue.bind(null, module),
+            n: exportNamespace.bind(null, module),
+            m: module,
+            c: moduleCache,
+            l: loadChunk.bind(null, sourceInfo),
+            w: loadWebAssembly.bind(null, sourceInfo),
+            u: loadWebAssemblyModule.bind(null, sourceInfo),
+            g: globalThis,
+            U: relativeURL,
+            k: refresh,
+            F: loadRemote,
+            __dirname: String(module.id).replace(/(^|\/)\/+$/, ""),
+          })
+        );
+      });
+
+    if (BACKEND.runModuleFactory) {
+      BACKEND.runModuleFactory(execute);
+    } else {
+      execute();
+    }
   } catch (error) {
     module.error = error as any;
     throw error;
@@ -358,6 +492,84 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
   return module;
 }
 
+/**
+ * Like `esmExport`, but the exports keep resolving to the getters of the
+ * latest instance of the module, see `moduleExportGetters`.
+ */
+function patchableEsmExport(
+  module: Module,
+  exports: Exports,
+  getters: Record<string, () => any>
+) {
+  moduleExportGetters.set(module.id, getters);
+  const delegatingGetters: Record<string, () => any> = {};
+  for (const key in getters) {
+    delegatingGetters[key] = () =>
+      (moduleExportGetters.get(module.id)?.[key] ?? getters[key])();
+  }
+  esmExport(module, exports, delegatingGetters);
+}
+
+/**
+ * Returns the names of the exports of a module if it is an ESM module that
+ * only exports (non-class) functions, and `null` otherwise.
+ *
+ * When such a module is updated, only the module itself needs to be
+ * re-executed: its importers access its exports through the exports object,
+ * which then resolves to the new functions.
+ */
+function getFunctionExportNames(moduleId: ModuleId): string[] | null {
+  const module = moduleCache[moduleId];
+  const getters = moduleExportGetters.get(moduleId);
+  if (
+    !module ||
+    module.error ||
+    getters == null ||
+    module.namespaceObject !== module.exports ||
+    module[REEXPORTED_OBJECTS] != null
+  ) {
+    return null;
+  }
+
+  const names = Object.keys(getters);
+  if (names.length === 0) {
+    return null;
+  }
+  for (const name of names) {
+    let value;
+    try {
+      value = getters[name]();
+    } catch (err) {
+      return null;
+    }
+    if (
+      typeof value !== "function" ||
+      /^class\b/.test(Function.prototype.toString.call(value))
+    ) {
+      return null;
+    }
+  }
+  return names;
+}
+
+/**
+ * Returns whether the exports of a module can be patched in place when it is
+ * updated, instead of re-executing its importers.
+ */
+function canPatchModuleExports(moduleId: ModuleId): boolean {
+  const module = moduleCache[moduleId];
+  const hotState = module && moduleHotState.get(module);
+  if (
+    !hotState ||
+    hotState.selfDeclined ||
+    hotState.selfInvalidated ||
+    hotState.disposeHandlers.length > 0
+  ) {
+    return false;
+  }
+  return getFunctionExportNames(moduleId) != null;
+}
+
 /**
  * NOTE(alexkirsz) Webpack has a "module execution" interception hook that
  * Next.js' React Refresh runtime hooks into to add module context to the
@@ -546,10 +758,20 @@ function computedInvalidatedModules(
   return outdatedModules;
 }
 
+type OutdatedSelfAcceptedModule = {
+  moduleId: ModuleId;
+  errorHandler: true | Function;
+  /**
+   * When set, the module doesn't accept itself, but its exports are patched
+   * in place. The new instance must export functions of the same names.
+   */
+  patchedExportNames?: string[];
+};
+
 function computeOutdatedSelfAcceptedModules(
   outdatedModules: Iterable<ModuleId>
-): { moduleId: ModuleId; errorHandler: true | Function }[] {
-  const outdatedSelfAcceptedModules = [];
+): OutdatedSelfAcceptedModule[] {
+  const outdatedSelfAcceptedModules: OutdatedSelfAcceptedModule[] = [];
   for (const moduleId of outdatedModules) {
     const module = moduleCache[moduleId];
     const hotState = moduleHotState.get(module)!;
@@ -558,6 +780,12 @@ function computeOutdatedSelfAcceptedModules(
         moduleId,
         errorHandler: hotState.selfAccepted,
       });
+    } else if (canPatchModuleExports(moduleId)) {
+      outdatedSelfAcceptedModules.push({
+        moduleId,
+        errorHandler: true,
+        patchedExportNames: getFunctionExportNames(moduleId)!,
+      });
     }
   }
   return outdatedSelfAcceptedModules;
@@ -672,6 +900,7 @@ function disposeModule(moduleId: ModuleId, mode: "clear" | "replace") {
     case "clear":
       delete moduleCache[module.id];
       moduleHotData.delete(module.id);
+      moduleExportGetters.delete(module.id);
       break;
     case "replace":
       moduleHotData.set(module.id, data);
@@ -682,10 +911,7 @@ function disposeModule(moduleId: ModuleId, mode: "clear" | "replace") {
 }
 
 function applyPhase(
-  outdatedSelfAcceptedModules: {
-    moduleId: ModuleId;
-    errorHandler: true | Function;
-  }[],
+  outdatedSelfAcceptedModules: OutdatedSelfAcceptedModule[],
   newModuleFactories: Map<ModuleId, ModuleFactory>,
   outdatedModuleParents: Map<ModuleId, Array<ModuleId>>,
   reportError: (err: any) => void
@@ -700,12 +926,28 @@ function applyPhase(
   // TODO(alexkirsz) Dependencies: call accept handlers for outdated deps.
 
   // Re-instantiate all outdated self-accepted modules.
-  for (const { moduleId, errorHandler } of outdatedSelfAcceptedModules) {
+  for (const {
+    moduleId,
+    errorHandler,
+    patchedExportNames,
+  } of outdatedSelfAcceptedModules) {
     try {
       instantiateModule(moduleId, {
         type: SourceType.Update,
         parents: outdatedModuleParents.get(moduleId),
       });
+      if (patchedExportNames != null) {
+        const exportNames = getFunctionExportNames(moduleId);
+        if (
+          exportNames == null ||
+          exportNames.length !== patchedExportNames.length ||
+          exportNames.some((name) => !patchedExportNames.includes(name))
+        ) {
+          throw new Error(
+            `cannot apply update: the exports of module ${moduleId} changed and can't be patched in place.`
+          );
+        }
+      }
     } catch (err) {
       if (typeof errorHandler === "function") {
         try {
@@ -738,11 +980,31 @@ function applyUpdate(chunkListPath: ChunkPath, update: PartialUpdate) {
   }
 }
 
+function hasModuleEntries(merged: EcmascriptMergedUpdate): boolean {
+  return merged.entries != null && Object.keys(merged.entries).length > 0;
+}
+
 function applyChunkListUpdate(
   chunkListPath: ChunkPath,
   update: ChunkListUpdate
 ) {
+  if (isBuildIdSkewed(update.buildId)) {
+    handleBuildIdSkew(update.buildId!);
+    return;
+  }
+
   if (update.merged != null) {
+    if (!ALLOW_EVAL && update.merged.some(hasModuleEntries)) {
+      // The factories of added and changed modules can only be created with
+      // `eval`, which isn't allowed (e.g. by a strict Content Security
+      // Policy).
+      console.warn(
+        "[turbopack] updated modules can't be evaluated without `eval`. Reloading."
+      );
+      BACKEND.restart();
+      return;
+    }
+
     for (const merged of update.merged) {
       switch (merged.type) {
         case "EcmascriptMergedUpdate":
@@ -758,9 +1020,11 @@ function applyChunkListUpdate(
     for (const [chunkPath, chunkUpdate] of Object.entries(update.chunks)) {
       switch (chunkUpdate.type) {
         case "added":
+          updatedChunkVersions.set(chunkPath, chunkUpdate.version);
           BACKEND.loadChunk(chunkPath, { type: SourceType.Update });
           break;
         case "total":
+          updatedChunkVersions.set(chunkPath, chunkUpdate.version);
           BACKEND.reloadChunk?.(chunkPath);
           break;
         case "deleted":
@@ -847,6 +1111,8 @@ function applyInternal(
   if (queuedInvalidatedModules.size > 0) {
     applyInternal(new Set(), [], new Map());
   }
+
+  scheduleChunkListCollection();
 }
 
 function computeChangedModules(
@@ -982,7 +1248,10 @@ function getAffectedModuleEffects(moduleId: ModuleId): ModuleEffect {
       // it means that the module was never instantiated before.
       !module || // The module accepted itself without invalidating globalThis.
       // TODO is that right?
-      (hotState.selfAccepted && !hotState.selfInvalidated)
+      (hotState.selfAccepted && !hotState.selfInvalidated) ||
+      // The module only exports functions, which can be replaced without
+      // re-executing its importers.
+      canPatchModuleExports(moduleId)
     ) {
       continue;
     }
@@ -1029,9 +1298,15 @@ function getAffectedModuleEffects(moduleId: ModuleId): ModuleEffect {
 }
 
 function handleApply(chunkListPath: ChunkPath, update: ServerMessage) {
+  if (!chunkListChunksMap.has(chunkListPath)) {
+    // The chunk list was disposed of, but its subscription couldn't be ended.
+    return;
+  }
+
   switch (update.type) {
     case "partial": {
       // This indicates that the update is can be applied to the current state of the application.
+      chunkIntegrity.clear();
       applyUpdate(chunkListPath, update.instruction);
       break;
     }
@@ -1210,6 +1485,8 @@ function disposeChunkList(chunkListPath: ChunkPath): boolean {
     return false;
   }
   chunkListChunksMap.delete(chunkListPath);
+  dynamicChunkLists.delete(chunkListPath);
+  chunkListUnsubscribes.delete(chunkListPath);
 
   for (const chunkPath of chunkPaths) {
     const chunkChunkLists = chunkChunkListsMap.get(chunkPath)!;
@@ -1259,6 +1536,127 @@ function disposeChunk(chunkPath: ChunkPath): boolean {
   return true;
 }
 
+/**
+ * How long chunk lists of async chunk groups must stay unused after an update
+ * before they are collected. Async chunks are often imported again shortly
+ * after an update replaced their importer (e.g. when a route is rendered
+ * again), which shouldn't load them again.
+ */
+const CHUNK_LIST_COLLECTION_DELAY = 10_000;
+
+let chunkListCollectionTimeout: ReturnType<typeof setTimeout> | undefined;
+
+/**
+ * Schedules collecting the chunk lists of async chunk groups that are unused
+ * after an update.
+ */
+function scheduleChunkListCollection() {
+  // Backends that can't unload chunks can't load them again either.
+  if (BACKEND.unloadChunk == null || dynamicChunkLists.size === 0) {
+    return;
+  }
+
+  // Chunk lists that are registered later might just not have been imported
+  // yet, so only the current ones are considered.
+  const candidates = new Set(dynamicChunkLists);
+  if (chunkListCollectionTimeout != null) {
+    clearTimeout(chunkListCollectionTimeout);
+  }
+  chunkListCollectionTimeout = setTimeout(() => {
+    chunkListCollectionTimeout = undefined;
+    collectUnusedChunkLists(candidates);
+  }, CHUNK_LIST_COLLECTION_DELAY);
+}
+
+/**
+ * Collects the chunk lists of async chunk groups whose modules aren't imported
+ * from outside of the chunk group anymore, e.g. the chunks of a route that was
+ * removed from the router by an update. Their exclusive chunks are unloaded
+ * and the factories of their modules are dropped, so they are loaded again
+ * when they are imported again.
+ */
+function collectUnusedChunkLists(candidates: Set<ChunkPath>) {
+  let collected = true;
+  // Disposing of modules removes them from the parents of their children, so
+  // collecting a chunk list can make the chunk lists it imports unused.
+  while (collected) {
+    collected = false;
+    for (const chunkListPath of candidates) {
+      if (!dynamicChunkLists.has(chunkListPath)) {
+        candidates.delete(chunkListPath);
+        continue;
+      }
+
+      const modules = getChunkListExclusiveModules(chunkListPath);
+      if (modules.size === 0 || isImportedFromOutside(modules)) {
+        continue;
+      }
+
+      chunkListUnsubscribes.get(chunkListPath)?.();
+      const chunkPaths = chunkListChunksMap.get(chunkListPath)!;
+      disposeChunkList(chunkListPath);
+      for (const chunkPath of chunkPaths) {
+        if (!chunkChunkListsMap.has(chunkPath)) {
+          availableModuleChunks.delete(chunkPath);
+        }
+      }
+      for (const moduleId of modules) {
+        if (!moduleChunksMap.has(moduleId)) {
+          delete moduleFactories[moduleId];
+        }
+      }
+
+      candidates.delete(chunkListPath);
+      collected = true;
+    }
+  }
+}
+
+/**
+ * Returns the modules that are only contained in chunks of the chunk list,
+ * which are the modules that are disposed of with it.
+ */
+function getChunkListExclusiveModules(chunkListPath: ChunkPath): Set<ModuleId> {
+  const exclusiveChunks: Set<ChunkPath> = new Set();
+  for (const chunkPath of chunkListChunksMap.get(chunkListPath) ?? []) {
+    if (chunkChunkListsMap.get(chunkPath)?.size === 1) {
+      exclusiveChunks.add(chunkPath);
+    }
+  }
+
+  const modules: Set<ModuleId> = new Set();
+  for (const chunkPath of exclusiveChunks) {
+    for (const moduleId of chunkModulesMap.get(chunkPath) ?? []) {
+      const moduleChunks = moduleChunksMap.get(moduleId) ?? [];
+      if (
+        Array.from(moduleChunks).every((chunkPath) =>
+          exclusiveChunks.has(chunkPath)
+        )
+      ) {
+        modules.add(moduleId);
+      }
+    }
+  }
+  return modules;
+}
+
+/**
+ * Returns `true` when an instance of any of the modules is imported by a module
+ * that is not part of `modules`, or is a runtime module.
+ */
+function isImportedFromOutside(modules: Set<ModuleId>): boolean {
+  for (const moduleId of modules) {
+    if (runtimeModules.has(moduleId)) {
+      return true;
+    }
+    const module = moduleCache[moduleId];
+    if (module?.parents.some((parentId) => !modules.has(parentId))) {
+      return true;
+    }
+  }
+  return false;
+}
+
 /**
  * Instantiates a runtime module.
  */
@@ -1297,17 +1695,129 @@ function getChunkRelativeUrl(chunkPath: ChunkPath): string {
     .join("/");
 }
 
+/**
+ * Returns `true` when `buildId` belongs to a different build than the one this
+ * runtime was created from. Missing build ids never count as skew.
+ */
+function isBuildIdSkewed(buildId: string | undefined): boolean {
+  return BUILD_ID != null && buildId != null && buildId !== BUILD_ID;
+}
+
+/**
+ * Handles chunks, chunk lists or updates from a different build than the
+ * runtime (e.g. stale HTML referencing chunks of an older build) by reloading
+ * the application as a whole, as mixing builds is not supported.
+ */
+function handleBuildIdSkew(buildId: string) {
+  console.warn(
+    `[turbopack] build mismatch: runtime is from build ${BUILD_ID}, but received build ${buildId}. Reloading.`
+  );
+  // Chunks and chunk lists can be registered before the backend is
+  // initialized, so defer the restart.
+  Promise.resolve().then(() => BACKEND.restart());
+}
+
+/**
+ * Query parameter used to tell the server which build a chunk is requested
+ * for. This allows the server to keep serving chunks of a previous build to
+ * clients that are still running it.
+ */
+const BUILD_ID_QUERY_PARAM = "turbopack-build";
+
+/**
+ * Appends the build id of this runtime to a chunk URL, if any.
+ */
+function withBuildId(chunkUrl: string): string {
+  if (BUILD_ID == null) {
+    return chunkUrl;
+  }
+  return `${chunkUrl}?${BUILD_ID_QUERY_PARAM}=${encodeURIComponent(BUILD_ID)}`;
+}
+
+/**
+ * Query parameter used to bust caches (e.g. proxies between the device and the
+ * dev server) for chunks that were added or replaced by hot updates.
+ */
+const CHUNK_VERSION_QUERY_PARAM = "turbopack-v";
+
+/**
+ * Maps chunk paths to the version of their content that was last sent with a
+ * hot update.
+ */
+const updatedChunkVersions: Map<ChunkPath, string> = new Map();
+
+/**
+ * Appends the version of a chunk's content to its URL, if the chunk was added
+ * or replaced by a hot update.
+ */
+function withChunkVersion(chunkPath: ChunkPath, chunkUrl: string): string {
+  const version = updatedChunkVersions.get(chunkPath);
+  if (version == null) {
+    return chunkUrl;
+  }
+  const separator = chunkUrl.includes("?") ? "&" : "?";
+  return `${chunkUrl}${separator}${CHUNK_VERSION_QUERY_PARAM}=${encodeURIComponent(
+    version
+  )}`;
+}
+
+/**
+ * Query parameter used to bust caches when loading a chunk is retried. Browsers
+ * remember failed `import()`s by URL, so ES module chunks can only be retried
+ * with a different URL.
+ */
+const CHUNK_RETRY_QUERY_PARAM = "turbopack-retry";
+
+/**
+ * Maps chunk paths to the number of the retry they're currently loaded with.
+ */
+const chunkLoadRetries: Map<ChunkPath, number> = new Map();
+
+/**
+ * Appends the number of the retry to a chunk URL, if loading the chunk is
+ * being retried.
+ */
+function withRetry(chunkPath: ChunkPath, chunkUrl: string): string {
+  const retry = chunkLoadRetries.get(chunkPath);
+  if (retry == null) {
+    return chunkUrl;
+  }
+  const separator = chunkUrl.includes("?") ? "&" : "?";
+  return `${chunkUrl}${separator}${CHUNK_RETRY_QUERY_PARAM}=${retry}`;
+}
+
+/**
+ * Returns the URL a chunk is loaded from: its relative URL with the build id,
+ * the version of its last hot update and the retry, if any.
+ */
+function getChunkLoadUrl(chunkPath: ChunkPath): string {
+  return withRetry(
+    chunkPath,
+    withChunkVersion(chunkPath, withBuildId(getChunkRelativeUrl(chunkPath)))
+  );
+}
+
 /**
  * Subscribes to chunk list updates from the update server and applies them.
  */
 function registerChunkList(
-  chunkUpdateProvider: ChunkUpdateProvider,
+  chunkUpdateProvider: ChunkUpdateProvider | [ChunkPath, UpdateCallback][],
   chunkList: ChunkList
 ) {
-  chunkUpdateProvider.push([
+  if (isBuildIdSkewed(chunkList.buildId)) {
+    handleBuildIdSkew(chunkList.buildId!);
+    return;
+  }
+
+  const unsubscribe = chunkUpdateProvider.push([
     chunkList.path,
     handleApply.bind(null, chunkList.path),
   ]);
+  // Subscriptions that are queued until the update provider is initialized
+  // can't be ended.
+  if (typeof unsubscribe === "function") {
+    chunkListUnsubscribes.set(chunkList.path, unsubscribe);
+  }
 
   // Adding chunks to chunk lists and vice versa.
   const chunks = new Set(chunkList.chunks.map(getChunkPath));
@@ -1324,6 +1834,8 @@ function registerChunkList(
 
   if (chunkList.source === "entry") {
     markChunkListAsRuntime(chunkList.path);
+  } else {
+    dynamicChunkLists.add(chunkList.path);
   }
 }
 
@@ -1341,6 +1853,11 @@ function registerChunk([
   chunkModules,
   runtimeParams,
 ]: ChunkRegistration) {
+  if (isBuildIdSkewed(runtimeParams?.buildId)) {
+    handleBuildIdSkew(runtimeParams!.buildId!);
+    return;
+  }
+
   for (const [moduleId, moduleFactory] of Object.entries(chunkModules)) {
     if (!moduleFactories[moduleId]) {
       moduleFactories[moduleId] = moduleFactory;
@@ -1348,19 +1865,35 @@ function registerChunk([
     addModuleToChunk(moduleId, chunkPath);
   }
 
+  if (runtimeParams?.integrity != null) {
+    for (const [otherChunkPath, integrity] of Object.entries(
+      runtimeParams.integrity
+    )) {
+      chunkIntegrity.set(otherChunkPath as ChunkPath, integrity);
+    }
+  }
+
   return BACKEND.registerChunk(chunkPath, runtimeParams);
 }
 
+globalThis.TURBOPACK_USAGE_PROFILE = () =>
+  JSON.stringify({ executedModules: Array.from(executedModules) });
+
 globalThis.TURBOPACK_CHUNK_UPDATE_LISTENERS ??= [];
 
-const chunkListsToRegister = globalThis.TURBOPACK_CHUNK_LISTS;
+// The chunk lists are registered with a global named after the global of the
+// chunks, so that the runtimes of several apps can be loaded in one page.
+const chunkListsToRegister = (globalThis as any)[CHUNK_LISTS_GLOBAL] as
+  | ChunkListProvider
+  | ChunkList[]
+  | undefined;
 if (Array.isArray(chunkListsToRegister)) {
   for (const chunkList of chunkListsToRegister) {
     registerChunkList(globalThis.TURBOPACK_CHUNK_UPDATE_LISTENERS, chunkList);
   }
 }
 
-globalThis.TURBOPACK_CHUNK_LISTS = {
+(globalThis as any)[CHUNK_LISTS_GLOBAL] = {
   push: (chunkList) => {
     registerChunkList(globalThis.TURBOPACK_CHUNK_UPDATE_LISTENERS!, chunkList);
   },
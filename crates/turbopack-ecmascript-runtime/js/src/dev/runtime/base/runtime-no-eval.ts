@@ -0,0 +1,15 @@
+/**
+ * This file replaces the `_eval` of a runtime backend when the runtime must
+ * not use `eval` or `new Function`, e.g. in edge runtimes or under a strict
+ * Content Security Policy. The runtime doesn't contain any code evaluation
+ * then, so that it also passes static checks for it.
+ *
+ * Hot updates that add or change modules restart the runtime instead, see
+ * `ALLOW_EVAL`.
+ */
+
+/// <reference path="./runtime-base.ts" />
+
+function _eval(_: EcmascriptModuleEntry): ModuleFactory {
+  throw new Error("evaluating code is not allowed in this runtime");
+}
@@ -16,19 +16,69 @@ type ChunkResolver = {
 
 let BACKEND: RuntimeBackend;
 
+interface RequireContextEntry {
+  external?: boolean;
+}
+
+type ExternalRequire = (
+  id: ModuleId,
+  esm?: boolean
+) => Exports | EsmNamespaceObject;
+type ExternalImport = (id: ModuleId) => Promise<Exports | EsmNamespaceObject>;
+
+interface TurbopackDevContext extends TurbopackDevBaseContext {
+  x?: ExternalRequire;
+  y?: ExternalImport;
+}
+
+// These are only defined when the environment supports externals, e.g. in the
+// renderer process of an Electron app with `nodeIntegration` enabled.
+declare var externalRequire: ExternalRequire | undefined;
+declare var externalImport: ExternalImport | undefined;
+
+function supportsExternals(): boolean {
+  return typeof externalRequire === "function";
+}
+
 function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
-  return context;
+  const domContext = context as TurbopackDevContext;
+  if (supportsExternals()) {
+    domContext.x = externalRequire;
+    domContext.y = externalImport;
+  }
+  return domContext;
 }
 
 function commonJsRequireContext(
   entry: RequireContextEntry,
   sourceModule: Module
 ): Exports {
-  return commonJsRequire(sourceModule, entry.id());
+  return entry.external && supportsExternals()
+    ? externalRequire!(entry.id(), false)
+    : commonJsRequire(sourceModule, entry.id());
+}
+
+/**
+ * Sets the attributes that the runtime is configured with on a `<script>` or
+ * `<link>` tag that's injected to load a chunk: the `crossorigin` attribute
+ * and the nonce of the Content Security Policy, which is read from a global
+ * variable.
+ */
+function setLoadAttributes(element: HTMLScriptElement | HTMLLinkElement) {
+  if (CROSS_ORIGIN != null) {
+    element.crossOrigin = CROSS_ORIGIN;
+  }
+  if (CSP_NONCE_VAR == null) {
+    return;
+  }
+  const nonce = (globalThis as any)[CSP_NONCE_VAR];
+  if (typeof nonce === "string") {
+    element.nonce = nonce;
+  }
 }
 
 function fetchWebAssembly(wasmChunkPath: ChunkPath) {
-  return fetch(getChunkRelativeUrl(wasmChunkPath));
+  return fetch(withBuildId(getChunkRelativeUrl(wasmChunkPath)));
 }
 
 async function loadWebAssembly(
@@ -76,8 +126,25 @@ async function loadWebAssemblyModule(
       );
 
       if (params.runtimeModuleIds.length > 0) {
+        const evaluations: Promise<Exports>[] = [];
         for (const moduleId of params.runtimeModuleIds) {
-          getOrInstantiateRuntimeModule(moduleId, chunkPath);
+          const module = getOrInstantiateRuntimeModule(moduleId, chunkPath);
+          // The exports of async modules are the promise of their evaluation.
+          if (module.exports instanceof Promise) {
+            evaluations.push(module.exports);
+          }
+        }
+        // Deferred chunks only contain modules that haven't been executed
+        // according to the usage profile, so they aren't needed to evaluate
+        // the entries.
+        for (const deferredChunkData of params.deferredChunks ?? []) {
+          loadChunk({ type: SourceType.Runtime, chunkPath }, deferredChunkData);
+        }
+        // An entry chunk loaded as an ES module awaits the registration, so
+        // like with native ES modules, it only finishes evaluating once
+        // asynchronous entries (e.g. with top-level await) have been evaluated.
+        if (ESM_CHUNKS) {
+          await Promise.all(evaluations);
         }
       }
     },
@@ -137,6 +204,7 @@ async function loadWebAssemblyModule(
 
         const link = document.createElement("link");
         link.rel = "stylesheet";
+        setLoadAttributes(link);
 
         if (navigator.userAgent.includes("Firefox")) {
           // Firefox won't reload CSS files that were previously loaded on the current page,
@@ -146,9 +214,11 @@ async function loadWebAssemblyModule(
           //
           // Safari has a similar issue, but only if you have a `<link rel=preload ... />` tag
           // pointing to the same URL as the stylesheet: https://bugs.webkit.org/show_bug.cgi?id=187726
-          link.href = `${chunkUrl}?ts=${Date.now()}`;
+          const versionedUrl = withChunkVersion(chunkPath, chunkUrl);
+          const separator = versionedUrl.includes("?") ? "&" : "?";
+          link.href = `${versionedUrl}${separator}ts=${Date.now()}`;
         } else {
-          link.href = chunkUrl;
+          link.href = withChunkVersion(chunkPath, chunkUrl);
         }
 
         link.onerror = () => {
@@ -175,6 +245,22 @@ async function loadWebAssemblyModule(
       });
     },
 
+    loadRemoteEntry(url) {
+      return new Promise<void>((resolve, reject) => {
+        const script = document.createElement("script");
+        setLoadAttributes(script);
+        script.src = url;
+        script.onerror = () => {
+          script.remove();
+          reject(new Error(`failed to load the remote entry ${url}`));
+        };
+        script.onload = () => {
+          resolve();
+        };
+        document.body.appendChild(script);
+      });
+    },
+
     restart: () => self.location.reload(),
   };
 
@@ -210,6 +296,29 @@ async function loadWebAssemblyModule(
     chunkResolvers.delete(chunkPath);
   }
 
+  /**
+   * Sets the Subresource Integrity hash of the chunk, if it's known, so the
+   * browser refuses to evaluate a chunk whose content doesn't match it.
+   *
+   * Reloaded CSS chunks don't get one, as their content changed since the
+   * hash was computed.
+   */
+  function setIntegrity(
+    element: HTMLScriptElement | HTMLLinkElement,
+    chunkPath: ChunkPath
+  ) {
+    const integrity = chunkIntegrity.get(chunkPath);
+    if (integrity == null) {
+      return;
+    }
+    element.integrity = integrity;
+    // Browsers only verify the integrity of chunks from other origins, e.g.
+    // a CDN, when they're requested with CORS.
+    if (element.crossOrigin == null) {
+      element.crossOrigin = "anonymous";
+    }
+  }
+
   /**
    * Loads the given chunk, and returns a promise that resolves once the chunk
    * has been loaded.
@@ -251,7 +360,9 @@ async function loadWebAssemblyModule(
       } else {
         const link = document.createElement("link");
         link.rel = "stylesheet";
-        link.href = chunkUrl;
+        setLoadAttributes(link);
+        link.href = getChunkLoadUrl(chunkPath);
+        setIntegrity(link, chunkPath);
         link.onerror = () => {
           resolver.reject();
         };
@@ -274,9 +385,21 @@ async function loadWebAssemblyModule(
             resolver.reject();
           });
         }
+      } else if (ESM_CHUNKS) {
+        // `import()` resolves relative URLs against the runtime chunk, while
+        // the chunk URLs are relative to the page like the ones of `<script>`
+        // tags.
+        const url = new URL(getChunkLoadUrl(chunkPath), document.baseURI);
+        // The chunk is marked as loaded once it has been evaluated, which
+        // happens in `registerChunk`.
+        import(url.href).catch(() => {
+          resolver.reject();
+        });
       } else {
         const script = document.createElement("script");
-        script.src = chunkUrl;
+        setLoadAttributes(script);
+        script.src = getChunkLoadUrl(chunkPath);
+        setIntegrity(script, chunkPath);
         // We'll only mark the chunk as loaded once the script has been executed,
         // which happens in `registerChunk`. Hence the absence of `resolve()` in
         // this branch.
@@ -292,10 +415,3 @@ async function loadWebAssemblyModule(
     return resolver.promise;
   }
 })();
-
-function _eval({ code, url, map }: EcmascriptModuleEntry): ModuleFactory {
-  code += `\n\n//# sourceURL=${location.origin}/${CHUNK_BASE_PATH}${url}`;
-  if (map)
-    code += `\n//# sourceMappingURL=${location.origin}/${CHUNK_BASE_PATH}${map}`;
-  return eval(code);
-}
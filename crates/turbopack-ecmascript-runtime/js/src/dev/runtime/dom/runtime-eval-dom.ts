@@ -0,0 +1,16 @@
+/**
+ * This file contains how the Turbopack development ECMAScript DOM runtime
+ * creates the factories of modules in hot updates.
+ *
+ * It will be appended to the DOM runtime backend, unless the runtime must
+ * not use `eval`.
+ */
+
+/// <reference path="./runtime-backend-dom.ts" />
+
+function _eval({ code, url, map }: EcmascriptModuleEntry): ModuleFactory {
+  code += `\n\n//# sourceURL=${location.origin}/${CHUNK_BASE_PATH}${url}`;
+  if (map)
+    code += `\n//# sourceMappingURL=${location.origin}/${CHUNK_BASE_PATH}${map}`;
+  return eval(code);
+}
@@ -9,6 +9,7 @@
 /// <reference path="../../../shared-node/base-externals-utils.ts" />
 /// <reference path="../../../shared-node/node-externals-utils.ts" />
 /// <reference path="../../../shared-node/node-wasm-utils.ts" />
+/// <reference path="../../../shared-node/node-execution-context.ts" />
 
 interface RequireContextEntry {
   // Only the Node.js backend has this flag.
@@ -33,6 +34,12 @@ function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
   return nodejsContext;
 }
 
+/**
+ * The path of the first evaluated chunk, which contains this runtime. Updates
+ * are resolved relative to it, as it's the chunk `__dirname` belongs to.
+ */
+let runtimeChunkPath: ChunkPath | undefined;
+
 function resolveChunkPath(chunkPath: ChunkPath, source: SourceInfo) {
   let fromChunkPath = undefined;
   switch (source.type) {
@@ -43,13 +50,14 @@ function resolveChunkPath(chunkPath: ChunkPath, source: SourceInfo) {
       fromChunkPath = getFirstModuleChunk(source.parentId);
       break;
     case SourceType.Update:
+      fromChunkPath = runtimeChunkPath;
       break;
   }
 
   const path = require("node:path");
   return path.resolve(
     __dirname,
-    path.posix.relative(path.dirname(fromChunkPath), chunkPath)
+    path.posix.relative(path.dirname(fromChunkPath ?? ""), chunkPath)
   );
 }
 
@@ -69,9 +77,50 @@ function loadWebAssemblyModule(source: SourceInfo, chunkPath: ChunkPath) {
   return compileWebAssemblyFromPath(resolved);
 }
 
+/**
+ * Evaluates the chunk at `filename` like `require()` would, with the scope of
+ * a CommonJS module (`require`, `module`, `exports`, `__filename` and
+ * `__dirname`), but without the module cache of Node.js, so that the same file
+ * can be evaluated again after it changed.
+ *
+ * Unless the runtime may evaluate code, the chunk is required after removing
+ * it from the module cache instead.
+ */
+function runChunk(filename: string) {
+  if (!ALLOW_EVAL) {
+    delete require.cache[filename];
+    require(filename);
+    return;
+  }
+
+  const fs = require("node:fs") as typeof import("fs");
+  const path = require("node:path") as typeof import("path");
+  const vm = require("node:vm") as typeof import("vm");
+  const { createRequire } = require("node:module") as typeof import("module");
+
+  const code = fs.readFileSync(filename, "utf-8");
+  const factory = vm.compileFunction(
+    code,
+    ["exports", "require", "module", "__filename", "__dirname"],
+    { filename }
+  );
+  const module = { exports: {} };
+  factory.call(
+    module.exports,
+    module.exports,
+    createRequire(filename),
+    module,
+    filename,
+    path.dirname(filename)
+  );
+}
+
 let BACKEND: RuntimeBackend;
 
 (() => {
+  const chunkExports = (globalThis.TURBOPACK_CHUNK_EXPORTS ??=
+    Object.create(null) as Record<ChunkPath, Exports>);
+
   BACKEND = {
     registerChunk(chunkPath, params) {
       if (params == null) {
@@ -79,6 +128,8 @@ let BACKEND: RuntimeBackend;
       }
 
       if (params.runtimeModuleIds.length > 0) {
+        runtimeChunkPath ??= chunkPath;
+
         for (const otherChunkData of params.otherChunks) {
           loadChunk(getChunkPath(otherChunkData), {
             type: SourceType.Runtime,
@@ -87,7 +138,10 @@ let BACKEND: RuntimeBackend;
         }
 
         for (const moduleId of params.runtimeModuleIds) {
-          getOrInstantiateRuntimeModule(moduleId, chunkPath);
+          chunkExports[chunkPath] = getOrInstantiateRuntimeModule(
+            moduleId,
+            chunkPath
+          ).exports;
         }
       }
     },
@@ -96,6 +150,14 @@ let BACKEND: RuntimeBackend;
       loadChunk(chunkPath, source);
     },
 
+    async reloadChunk(chunkPath) {
+      loadChunk(chunkPath, { type: SourceType.Update });
+    },
+
+    runModuleFactory(execute) {
+      runOutsideExecutionContext(execute);
+    },
+
     restart: () => {
       throw new Error("restart not implemented for the Node.js backend");
     },
@@ -108,14 +170,8 @@ let BACKEND: RuntimeBackend;
       return;
     }
 
-    // We'll only mark the chunk as loaded once the script has been executed,
-    // which happens in `registerChunk`. Hence the absence of `resolve()`.
-    const resolved = resolveChunkPath(chunkPath, source);
-
-    require(resolved);
+    // Chunks register themselves with `TURBOPACK.push`, so they are evaluated
+    // again when they are reloaded after an update.
+    runChunk(resolveChunkPath(chunkPath, source));
   }
 })();
-
-function _eval(_: EcmascriptModuleEntry): ModuleFactory {
-  throw new Error("HMR evaluation is not implemented on this backend");
-}
@@ -0,0 +1,18 @@
+/**
+ * This file contains how the Turbopack development ECMAScript Node.js runtime
+ * creates the factories of modules in hot updates.
+ *
+ * It will be appended to the Node.js runtime backend, unless the runtime must
+ * not use `eval`.
+ */
+
+/// <reference path="./runtime-backend-nodejs.ts" />
+
+function _eval({ code, url, map }: EcmascriptModuleEntry): ModuleFactory {
+  const source: SourceInfo = { type: SourceType.Update };
+  if (map) {
+    code += `\n\n//# sourceMappingURL=${resolveChunkPath(map, source)}`;
+  }
+  const vm = require("node:vm") as typeof import("vm");
+  return vm.runInThisContext(code, { filename: resolveChunkPath(url, source) });
+}
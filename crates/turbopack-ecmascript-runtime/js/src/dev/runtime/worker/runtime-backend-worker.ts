@@ -0,0 +1,120 @@
+/**
+ * This file contains the runtime code specific to the Turbopack development
+ * ECMAScript runtime of web workers, e.g. of
+ * `new Worker(new URL("./worker.ts", import.meta.url))`.
+ *
+ * Workers have no DOM to inject script tags into, so chunks are loaded with
+ * `importScripts`, which is only available in classic workers, so module
+ * workers are started as classic workers. Workers don't receive hot updates.
+ *
+ * The chunks of the worker's chunk group are loaded synchronously while the
+ * entry chunk is evaluated, so the worker's entries are evaluated before the
+ * first message to the worker is dispatched.
+ *
+ * It will be appended to the base development runtime code.
+ */
+
+/// <reference path="../base/runtime-base.ts" />
+
+let BACKEND: RuntimeBackend;
+
+function augmentContext(
+  context: TurbopackDevBaseContext
+): TurbopackDevBaseContext {
+  return context;
+}
+
+function commonJsRequireContext(
+  entry: RequireContextEntry,
+  sourceModule: Module
+): Exports {
+  return commonJsRequire(sourceModule, entry.id());
+}
+
+function fetchWebAssembly(wasmChunkPath: ChunkPath) {
+  return fetch(withBuildId(getChunkRelativeUrl(wasmChunkPath)));
+}
+
+async function loadWebAssembly(
+  _source: SourceInfo,
+  wasmChunkPath: ChunkPath,
+  importsObj: WebAssembly.Imports
+): Promise<Exports> {
+  const req = fetchWebAssembly(wasmChunkPath);
+
+  const { instance } = await WebAssembly.instantiateStreaming(req, importsObj);
+
+  return instance.exports;
+}
+
+async function loadWebAssemblyModule(
+  _source: SourceInfo,
+  wasmChunkPath: ChunkPath
+): Promise<WebAssembly.Module> {
+  const req = fetchWebAssembly(wasmChunkPath);
+
+  return await WebAssembly.compileStreaming(req);
+}
+
+(() => {
+  BACKEND = {
+    registerChunk(chunkPath, params) {
+      loadedChunks.add(chunkPath);
+
+      if (params == null) {
+        return;
+      }
+
+      // The worker was started with the entry chunk only, so the other chunks
+      // of its chunk group are loaded here.
+      for (const otherChunkData of params.otherChunks) {
+        importChunk(getChunkPath(otherChunkData));
+      }
+
+      for (const moduleId of params.runtimeModuleIds) {
+        getOrInstantiateRuntimeModule(moduleId, chunkPath);
+      }
+    },
+
+    async loadChunk(chunkPath, _source) {
+      importChunk(chunkPath);
+    },
+
+    unloadChunk(chunkPath) {
+      loadedChunks.delete(chunkPath);
+    },
+
+    restart: () => {
+      throw new Error("restart is not supported in workers");
+    },
+  };
+
+  const loadedChunks: Set<ChunkPath> = new Set();
+
+  /**
+   * Loads the given chunk, unless it was already loaded. `importScripts`
+   * evaluates the chunk synchronously, which registers it.
+   */
+  function importChunk(chunkPath: ChunkPath) {
+    // Workers can't apply styles.
+    if (chunkPath.endsWith(".css") || loadedChunks.has(chunkPath)) {
+      return;
+    }
+
+    if (!chunkPath.endsWith(".js")) {
+      throw new Error(`can't infer type of chunk from path ${chunkPath}`);
+    }
+
+    if (typeof importScripts !== "function") {
+      throw new Error(
+        `can't load chunk ${chunkPath}, as chunks can only be loaded in classic workers, not in module workers`
+      );
+    }
+
+    importScripts(getChunkLoadUrl(chunkPath));
+  }
+})();
+
+function _eval(_entry: EcmascriptModuleEntry): ModuleFactory {
+  throw new Error("HMR evaluation is not supported in workers");
+}
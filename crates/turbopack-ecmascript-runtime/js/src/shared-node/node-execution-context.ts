@@ -0,0 +1,55 @@
+/// <reference path="../shared/runtime-utils.ts" />
+
+/// A per-request execution context for Node.js runtimes, backed by an
+/// `AsyncLocalStorage`.
+///
+/// The storage is stored on `globalThis` under a well-known symbol, so all
+/// runtimes in the process (one per chunk group) and the code evaluating them
+/// share it. Frameworks can use it directly:
+///
+///   const storage = globalThis[Symbol.for("turbopack.executionContext")];
+///   storage.run(requestContext, () => handleRequest());
+///   storage.getStore(); // => requestContext
+///
+/// Module factories are executed outside of any execution context, as the
+/// module scope is shared between all requests. Otherwise the request that
+/// happens to instantiate a module first would leak into e.g. timers or
+/// event listeners created at module scope.
+
+type ExecutionContextStorage = import("async_hooks").AsyncLocalStorage<unknown>;
+
+const EXECUTION_CONTEXT_STORAGE = Symbol.for("turbopack.executionContext");
+
+function getExecutionContextStorage(): ExecutionContextStorage {
+  const global = globalThis as any;
+  let storage: ExecutionContextStorage | undefined =
+    global[EXECUTION_CONTEXT_STORAGE];
+  if (storage == null) {
+    const { AsyncLocalStorage } =
+      require("async_hooks") as typeof import("async_hooks");
+    storage = new AsyncLocalStorage();
+    global[EXECUTION_CONTEXT_STORAGE] = storage;
+  }
+  return storage;
+}
+
+/**
+ * Runs `fn` with `context` as the current execution context.
+ */
+function runWithExecutionContext<T>(context: unknown, fn: () => T): T {
+  return getExecutionContextStorage().run(context, fn);
+}
+
+/**
+ * Returns the current execution context, if any.
+ */
+function getExecutionContext(): unknown {
+  return getExecutionContextStorage().getStore();
+}
+
+/**
+ * Runs `fn` outside of the current execution context.
+ */
+function runOutsideExecutionContext<T>(fn: () => T): T {
+  return getExecutionContextStorage().exit(fn);
+}
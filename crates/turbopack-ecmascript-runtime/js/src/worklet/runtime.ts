@@ -0,0 +1,131 @@
+/// <reference path="../shared/runtime-utils.ts" />
+
+/**
+ * The runtime of worklet chunks, e.g. for `CSS.paintWorklet.addModule` and
+ * `audioWorklet.addModule`.
+ *
+ * A worklet chunk contains all modules of the worklet and is loaded as a
+ * module by the worklet global scope, which can't load other scripts. So
+ * unlike the other runtimes, it doesn't load chunks and doesn't register with
+ * the `TURBOPACK` chunk registry. The chunk defines `MODULE_FACTORIES` and
+ * evaluates its entries with `evaluateWorkletEntries`.
+ */
+
+declare var MODULE_FACTORIES: ModuleFactories;
+
+type ModuleFactory = (
+  this: Module["exports"],
+  context: TurbopackBaseContext
+) => undefined;
+
+const moduleCache: ModuleCache = Object.create(null);
+
+function loadChunk(): Promise<any> {
+  return Promise.reject(
+    new Error(
+      "Worklets can't load chunks, all modules must be imported statically"
+    )
+  );
+}
+
+function loadWebAssembly(): Exports {
+  throw new Error("WebAssembly is not supported in worklets");
+}
+
+function loadWebAssemblyModule(): WebAssembly.Module {
+  throw new Error("WebAssembly is not supported in worklets");
+}
+
+function instantiateModule(id: ModuleId, parents: ModuleId[]): Module {
+  const moduleFactory = MODULE_FACTORIES[id];
+  if (typeof moduleFactory !== "function") {
+    const reason =
+      parents.length > 0
+        ? `it was required from module ${parents[0]}`
+        : "it is an entry of the worklet";
+    throw new Error(
+      `Module ${id} is not contained in the worklet chunk, but ${reason}.`
+    );
+  }
+
+  const module: Module = {
+    exports: {},
+    error: undefined,
+    loaded: false,
+    id,
+    parents,
+    children: [],
+    namespaceObject: undefined,
+  };
+  moduleCache[id] = module;
+
+  try {
+    moduleFactory.call(module.exports, {
+      a: asyncModule.bind(null, module),
+      e: module.exports,
+      r: commonJsRequire.bind(null, module),
+      t: runtimeRequire,
+      f: requireContext.bind(null, module),
+      i: esmImport.bind(null, module),
+      s: esmExport.bind(null, module, module.exports),
+      j: dynamicExport.bind(null, module, module.exports),
+      v: exportValue.bind(null, module),
+      n: exportNamespace.bind(null, module),
+      m: module,
+      c: moduleCache,
+      l: loadChunk,
+      w: loadWebAssembly,
+      u: loadWebAssemblyModule,
+      g: globalThis,
+      U: relativeURL,
+      __dirname: String(module.id).replace(/(^|\/)[\/]+$/, ""),
+    });
+  } catch (error) {
+    module.error = error as any;
+    throw error;
+  }
+
+  module.loaded = true;
+  if (module.namespaceObject && module.exports !== module.namespaceObject) {
+    // in case of a circular dependency: cjs1 -> esm2 -> cjs1
+    interopEsm(module.exports, module.namespaceObject);
+  }
+
+  return module;
+}
+
+/**
+ * Retrieves a module from the cache, or instantiate it if it is not cached.
+ */
+function getOrInstantiateModuleFromParent(
+  id: ModuleId,
+  sourceModule: Module
+): Module {
+  const module = moduleCache[id];
+
+  if (sourceModule.children.indexOf(id) === -1) {
+    sourceModule.children.push(id);
+  }
+
+  if (module) {
+    if (module.parents.indexOf(sourceModule.id) === -1) {
+      module.parents.push(sourceModule.id);
+    }
+
+    return module;
+  }
+
+  return instantiateModule(id, [sourceModule.id]);
+}
+
+/**
+ * Evaluates the entries of the worklet, in order.
+ */
+function evaluateWorkletEntries(moduleIds: ModuleId[]) {
+  for (const moduleId of moduleIds) {
+    const module = moduleCache[moduleId] ?? instantiateModule(moduleId, []);
+    if (module.error) {
+      throw module.error;
+    }
+  }
+}
@@ -24,6 +24,10 @@ pub async fn get_build_runtime_code(environment: Vc<Environment>) -> Result<Vc<C
     );
     let shared_node_wasm_utils_code =
         embed_static_code(asset_context, "shared-node/node-wasm-utils.ts".to_string());
+    let shared_node_execution_context_code = embed_static_code(
+        asset_context,
+        "shared-node/node-execution-context.ts".to_string(),
+    );
     let runtime_code = embed_static_code(asset_context, "build/runtime.ts".to_string());
 
     let mut code = CodeBuilder::default();
@@ -31,6 +35,7 @@ pub async fn get_build_runtime_code(environment: Vc<Environment>) -> Result<Vc<C
     code.push_code(&*shared_base_external_utils_code.await?);
     code.push_code(&*shared_node_external_utils_code.await?);
     code.push_code(&*shared_node_wasm_utils_code.await?);
+    code.push_code(&*shared_node_execution_context_code.await?);
     code.push_code(&*runtime_code.await?);
 
     Ok(Code::cell(code.build()))
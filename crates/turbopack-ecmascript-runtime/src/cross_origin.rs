@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput};
+
+/// The CORS mode of the tags the runtime injects to load chunks, i.e. the
+/// value of their `crossorigin` attribute.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    TraceRawVcs,
+    TaskInput,
+)]
+pub enum CrossOrigin {
+    /// Requests are sent without credentials, e.g. cookies.
+    Anonymous,
+    /// Requests are sent with credentials.
+    UseCredentials,
+}
+
+impl CrossOrigin {
+    /// The value of the `crossorigin` attribute.
+    pub fn attribute_value(&self) -> &'static str {
+        match self {
+            CrossOrigin::Anonymous => "anonymous",
+            CrossOrigin::UseCredentials => "use-credentials",
+        }
+    }
+}
@@ -8,17 +8,77 @@ use turbopack_core::{
     context::AssetContext,
     environment::{ChunkLoading, Environment},
 };
-use turbopack_ecmascript::utils::StringifyJs;
+use turbopack_ecmascript::{
+    chunk::{base_paths_global, runtime_base_path_expr},
+    utils::StringifyJs,
+};
+
+use crate::{
+    asset_context::get_runtime_asset_context, cross_origin::CrossOrigin,
+    embed_js::embed_static_code,
+};
 
-use crate::{asset_context::get_runtime_asset_context, embed_js::embed_static_code};
+/// Options of the development ECMAScript runtime.
+#[turbo_tasks::value(shared)]
+pub struct DevRuntimeOptions {
+    /// Whether the runtime is for a web worker, which loads chunks with
+    /// `importScripts` instead of the chunk loading of the environment.
+    pub worker: bool,
+    /// Whether the runtime is part of an ES module: the DOM runtime then loads
+    /// chunks as ES modules with `import()` instead of injecting classic
+    /// `<script>` tags.
+    pub esm_chunks: bool,
+    /// The base path chunks are loaded from.
+    pub chunk_base_path: Vc<Option<String>>,
+    /// Whether the base path of chunks is read at runtime from the
+    /// [base_paths_global] of `chunk_loading_global`.
+    pub runtime_base_paths: bool,
+    /// The id of the build, exposed to the runtime.
+    pub build_id: Vc<Option<String>>,
+    /// Unless set, the runtime doesn't contain any `eval` or `new Function`,
+    /// so it can run in edge runtimes and under a Content Security Policy
+    /// without `unsafe-eval`. The Node.js runtime then loads chunks with
+    /// `require()` instead of compiling them with `node:vm`.
+    pub allow_eval: bool,
+    /// When given, the runtime sets the nonce read from the global variable
+    /// with this name on the tags it injects to load chunks.
+    pub csp_nonce_var: Option<String>,
+    /// The `crossorigin` attribute of the tags the runtime injects to load
+    /// chunks.
+    pub cross_origin: Option<CrossOrigin>,
+    /// How many times chunks that fail to load are retried.
+    pub chunk_load_retries: u32,
+    /// The delay before the first retry of a chunk load. It doubles with
+    /// every retry.
+    pub retry_delay_ms: u64,
+    /// The output root, relative to which chunk paths are resolved.
+    pub output_root: Vc<String>,
+    /// The global variable the chunks register with. The chunk lists register
+    /// with the global variable of the same name suffixed with `_CHUNK_LISTS`.
+    pub chunk_loading_global: String,
+}
 
 /// Returns the code for the development ECMAScript runtime.
 #[turbo_tasks::function]
 pub async fn get_dev_runtime_code(
     environment: Vc<Environment>,
-    chunk_base_path: Vc<Option<String>>,
-    output_root: Vc<String>,
+    options: Vc<DevRuntimeOptions>,
 ) -> Result<Vc<Code>> {
+    let options = options.await?;
+    let DevRuntimeOptions {
+        worker,
+        esm_chunks,
+        chunk_base_path,
+        runtime_base_paths,
+        build_id,
+        allow_eval,
+        ref csp_nonce_var,
+        cross_origin,
+        chunk_load_retries,
+        retry_delay_ms,
+        output_root,
+        ref chunk_loading_global,
+    } = *options;
     let asset_context = get_runtime_asset_context(environment);
 
     let shared_runtime_utils_code =
@@ -37,6 +97,7 @@ pub async fn get_dev_runtime_code(
     let runtime_backend_code = embed_static_code(
         asset_context,
         match chunk_loading {
+            _ if worker => "dev/runtime/worker/runtime-backend-worker.ts".to_string(),
             ChunkLoading::None => "dev/runtime/none/runtime-backend-none.ts".to_string(),
             ChunkLoading::NodeJs => "dev/runtime/nodejs/runtime-backend-nodejs.ts".to_string(),
             ChunkLoading::Dom => "dev/runtime/dom/runtime-backend-dom.ts".to_string(),
@@ -47,22 +108,49 @@ pub async fn get_dev_runtime_code(
     let output_root = output_root.await?.to_string();
     let chunk_base_path = &*chunk_base_path.await?;
     let chunk_base_path = chunk_base_path.as_ref().map_or_else(|| "", |f| f.as_str());
+    let chunk_base_path = if runtime_base_paths {
+        runtime_base_path_expr(
+            &base_paths_global(chunk_loading_global),
+            "chunkBasePath",
+            chunk_base_path,
+        )
+    } else {
+        StringifyJs(chunk_base_path).to_string()
+    };
+    let build_id = &*build_id.await?;
 
     writedoc!(
         code,
         r#"
             (() => {{
-            if (!Array.isArray(globalThis.TURBOPACK)) {{
+            if (!Array.isArray(globalThis.{})) {{
                 return;
             }}
 
             const CHUNK_BASE_PATH = {};
             const RUNTIME_PUBLIC_PATH = {};
             const OUTPUT_ROOT = {};
+            const BUILD_ID = {};
+            const ALLOW_EVAL = {};
+            const CSP_NONCE_VAR = {};
+            const CROSS_ORIGIN = {};
+            const CHUNK_LOAD_RETRIES = {};
+            const CHUNK_LOAD_RETRY_DELAY = {};
+            const ESM_CHUNKS = {};
+            const CHUNK_LISTS_GLOBAL = {};
         "#,
-        StringifyJs(chunk_base_path),
-        StringifyJs(chunk_base_path),
+        chunk_loading_global,
+        chunk_base_path,
+        chunk_base_path,
         StringifyJs(output_root.as_str()),
+        StringifyJs(build_id),
+        allow_eval,
+        StringifyJs(&csp_nonce_var),
+        StringifyJs(&cross_origin.map(|cross_origin| cross_origin.attribute_value())),
+        chunk_load_retries,
+        retry_delay_ms,
+        esm_chunks,
+        StringifyJs(&format!("{chunk_loading_global}_CHUNK_LISTS")),
     )?;
 
     code.push_code(&*shared_runtime_utils_code.await?);
@@ -93,15 +181,37 @@ pub async fn get_dev_runtime_code(
         );
     }
 
+    if !worker && matches!(chunk_loading, ChunkLoading::NodeJs) {
+        code.push_code(
+            &*embed_static_code(
+                asset_context,
+                "shared-node/node-execution-context.ts".to_string(),
+            )
+            .await?,
+        );
+    }
+
     code.push_code(&*runtime_backend_code.await?);
 
+    // The worker and the none backends create module factories themselves.
+    let runtime_eval_code = match chunk_loading {
+        _ if worker => None,
+        ChunkLoading::None => None,
+        _ if !allow_eval => Some("dev/runtime/base/runtime-no-eval.ts"),
+        ChunkLoading::Dom => Some("dev/runtime/dom/runtime-eval-dom.ts"),
+        ChunkLoading::NodeJs => Some("dev/runtime/nodejs/runtime-eval-nodejs.ts"),
+    };
+    if let Some(runtime_eval_code) = runtime_eval_code {
+        code.push_code(&*embed_static_code(asset_context, runtime_eval_code.to_string()).await?);
+    }
+
     // Registering chunks depends on the BACKEND variable, which is set by the
     // specific runtime code, hence it must be appended after it.
     writedoc!(
         code,
         r#"
-            const chunksToRegister = globalThis.TURBOPACK;
-            globalThis.TURBOPACK = {{ push: registerChunk }};
+            const chunksToRegister = globalThis.{chunk_loading_global};
+            globalThis.{chunk_loading_global} = {{ push: registerChunk }};
             chunksToRegister.forEach(registerChunk);
             }})();
         "#
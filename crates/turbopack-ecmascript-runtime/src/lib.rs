@@ -2,18 +2,22 @@
 
 pub(crate) mod asset_context;
 pub(crate) mod build_runtime;
+pub(crate) mod cross_origin;
 pub(crate) mod dev_runtime;
 #[cfg(feature = "test")]
 pub(crate) mod dummy_runtime;
 pub(crate) mod embed_js;
 pub(crate) mod runtime_type;
+pub(crate) mod worklet_runtime;
 
 pub use build_runtime::get_build_runtime_code;
-pub use dev_runtime::get_dev_runtime_code;
+pub use cross_origin::CrossOrigin;
+pub use dev_runtime::{get_dev_runtime_code, DevRuntimeOptions};
 #[cfg(feature = "test")]
 pub use dummy_runtime::get_dummy_runtime_code;
 pub use embed_js::{embed_file, embed_file_path, embed_fs};
 pub use runtime_type::RuntimeType;
+pub use worklet_runtime::get_worklet_runtime_code;
 
 pub fn register() {
     turbo_tasks::register();
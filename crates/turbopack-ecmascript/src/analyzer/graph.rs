@@ -165,6 +165,13 @@ pub enum Effect {
         span: Span,
         in_try: bool,
     },
+    /// A call to `import.meta.resolve(...)`.
+    ImportMetaResolve {
+        input: JsValue,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
     /// A reference to `new URL(..., import.meta.url)`.
     Url {
         input: JsValue,
@@ -172,6 +179,24 @@ pub enum Effect {
         span: Span,
         in_try: bool,
     },
+    /// A reference to `new URL(..., import.meta.url)` that is passed to
+    /// `paintWorklet.addModule` or `audioWorklet.addModule`.
+    Worklet {
+        input: JsValue,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
+    /// A reference to `new URL(..., import.meta.url)` that is passed to
+    /// `new Worker` or `new SharedWorker`.
+    Worker {
+        input: JsValue,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+        /// Whether it's passed to `new SharedWorker`.
+        shared: bool,
+    },
 }
 
 impl Effect {
@@ -208,7 +233,10 @@ impl Effect {
             }
             Effect::ImportedBinding { .. } => {}
             Effect::ImportMeta { .. } => {}
-            Effect::Url { input, .. } => {
+            Effect::ImportMetaResolve { input, .. }
+            | Effect::Url { input, .. }
+            | Effect::Worklet { input, .. }
+            | Effect::Worker { input, .. } => {
                 input.normalize();
             }
         }
@@ -683,6 +711,76 @@ pub fn is_in_try(ast_path: &AstNodePath<AstParentNodeRef<'_>>) -> bool {
         .unwrap_or(false)
 }
 
+/// Returns `true` if the expression at `ast_path` is the first argument of a
+/// `*.paintWorklet.addModule(...)` or `*.audioWorklet.addModule(...)` call.
+fn is_worklet_module_arg(ast_path: &AstNodePath<AstParentNodeRef<'_>>) -> bool {
+    let mut parents = ast_path.iter().rev();
+    if !matches!(
+        (parents.next(), parents.next()),
+        (
+            Some(AstParentNodeRef::Expr(_, ExprField::New)),
+            Some(AstParentNodeRef::ExprOrSpread(_, ExprOrSpreadField::Expr))
+        )
+    ) {
+        return false;
+    }
+    let Some(AstParentNodeRef::CallExpr(call, CallExprField::Args(0))) = parents.next() else {
+        return false;
+    };
+    let Callee::Expr(callee) = &call.callee else {
+        return false;
+    };
+    let Expr::Member(MemberExpr {
+        obj,
+        prop: MemberProp::Ident(method),
+        ..
+    }) = unparen(callee)
+    else {
+        return false;
+    };
+    let Expr::Member(MemberExpr {
+        prop: MemberProp::Ident(worklet),
+        ..
+    }) = unparen(obj)
+    else {
+        return false;
+    };
+    &*method.sym == "addModule" && matches!(&*worklet.sym, "paintWorklet" | "audioWorklet")
+}
+
+/// Returns `Some(shared)` if the expression at `ast_path` is the first
+/// argument of a `new Worker(...)` or (when `shared`) a `new SharedWorker(...)`
+/// expression.
+fn worker_constructor_of_arg(
+    ast_path: &AstNodePath<AstParentNodeRef<'_>>,
+    unresolved_mark: Mark,
+) -> Option<bool> {
+    let mut parents = ast_path.iter().rev();
+    if !matches!(
+        (parents.next(), parents.next()),
+        (
+            Some(AstParentNodeRef::Expr(_, ExprField::New)),
+            Some(AstParentNodeRef::ExprOrSpread(_, ExprOrSpreadField::Expr))
+        )
+    ) {
+        return None;
+    }
+    let Some(AstParentNodeRef::NewExpr(new_expr, NewExprField::Args(0))) = parents.next() else {
+        return None;
+    };
+    let Expr::Ident(callee) = unparen(&new_expr.callee) else {
+        return None;
+    };
+    if !is_unresolved(callee, unresolved_mark) {
+        return None;
+    }
+    match &*callee.sym {
+        "Worker" => Some(false),
+        "SharedWorker" => Some(true),
+        _ => None,
+    }
+}
+
 impl Analyzer<'_> {
     fn add_value(&mut self, id: Id, value: JsValue) {
         if let Some(prev) = self.data.values.get_mut(&id) {
@@ -923,6 +1021,26 @@ impl Analyzer<'_> {
             }
             Callee::Expr(box expr) => {
                 if let Expr::Member(MemberExpr { obj, prop, .. }) = unparen(expr) {
+                    // import.meta.resolve("path")
+                    if let (
+                        Expr::MetaProp(MetaPropExpr {
+                            kind: MetaPropKind::ImportMeta,
+                            ..
+                        }),
+                        MemberProp::Ident(prop),
+                        Some(EffectArg::Value(input)),
+                    ) = (&**obj, prop, args.first())
+                    {
+                        if &*prop.sym == "resolve" {
+                            self.add_effect(Effect::ImportMetaResolve {
+                                input: input.clone(),
+                                ast_path: as_parent_path(ast_path),
+                                span: n.span(),
+                                in_try: is_in_try(ast_path),
+                            });
+                            return;
+                        }
+                    }
                     let obj_value = self.eval_context.eval(obj);
                     let prop_value = match prop {
                         // TODO avoid clone
@@ -1187,11 +1305,35 @@ impl VisitAstPath for Analyzer<'_> {
                         }) = &*args[1].expr
                         {
                             if &*prop.sym == "url" {
-                                self.add_effect(Effect::Url {
-                                    input: self.eval_context.eval(&args[0].expr),
-                                    ast_path: as_parent_path(ast_path),
-                                    span: new_expr.span(),
-                                    in_try: is_in_try(ast_path),
+                                let input = self.eval_context.eval(&args[0].expr);
+                                let ast_path_kinds = as_parent_path(ast_path);
+                                let span = new_expr.span();
+                                let in_try = is_in_try(ast_path);
+                                self.add_effect(if is_worklet_module_arg(ast_path) {
+                                    Effect::Worklet {
+                                        input,
+                                        ast_path: ast_path_kinds,
+                                        span,
+                                        in_try,
+                                    }
+                                } else if let Some(shared) = worker_constructor_of_arg(
+                                    ast_path,
+                                    self.eval_context.unresolved_mark,
+                                ) {
+                                    Effect::Worker {
+                                        input,
+                                        ast_path: ast_path_kinds,
+                                        span,
+                                        in_try,
+                                        shared,
+                                    }
+                                } else {
+                                    Effect::Url {
+                                        input,
+                                        ast_path: ast_path_kinds,
+                                        span,
+                                        in_try,
+                                    }
                                 });
                             }
                         }
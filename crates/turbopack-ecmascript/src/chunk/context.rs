@@ -1,5 +1,98 @@
+use anyhow::Result;
 use turbo_tasks::Vc;
-use turbopack_core::chunk::ChunkingContext;
+use turbopack_core::{chunk::ChunkingContext, ident::AssetIdent};
+
+use super::StrictEsm;
+use crate::utils::StringifyJs;
+
+/// Returns the global variable the base paths of chunk and asset URLs are read
+/// from when they are resolved at runtime, for the chunks registering with
+/// `chunk_loading_global`. Each app loaded into a page has its own chunk
+/// loading global, and thereby its own base paths. The variable is set by a
+/// config script served per request, which is loaded before the chunks, e.g.
+///
+/// ```js
+/// globalThis.TURBOPACK_BASE_PATHS = {
+///   chunkBasePath: "https://tenant-a.cdn.example.com/",
+///   assetBasePath: "https://tenant-a.cdn.example.com/",
+/// };
+/// ```
+pub fn base_paths_global(chunk_loading_global: &str) -> String {
+    format!("{chunk_loading_global}_BASE_PATHS")
+}
+
+/// Returns the JavaScript expression reading the base path `key` from the
+/// [base_paths_global] `global`, falling back to `default` when it isn't set.
+pub fn runtime_base_path_expr(global: &str, key: &str, default: &str) -> String {
+    format!(
+        "(globalThis.{global} && typeof globalThis.{global}.{key} === \"string\" ? \
+         globalThis.{global}.{key} : {default})",
+        default = StringifyJs(default),
+    )
+}
+
+/// Returns the JavaScript expression evaluating to the URL of the output root,
+/// derived from the URL of the currently executing script, or to the
+/// `default_expr` when it can't be derived. `chunk_path` is the path of the
+/// script relative to the output root.
+///
+/// When `module` is set, the script is an ES module and its URL is read from
+/// `import.meta.url`, as `document.currentScript` is null in module scripts.
+/// Otherwise, the expression must be evaluated synchronously while the script
+/// is executed.
+fn script_base_path_expr(chunk_path: &str, default_expr: &str, module: bool) -> String {
+    let src_expr = if module {
+        "import.meta.url"
+    } else {
+        r#"typeof document === "object" && document.currentScript
+            ? document.currentScript.src
+            : typeof self === "object" && self.location
+            ? self.location.href
+            : undefined"#
+    };
+    format!(
+        r#"((chunkPath, defaultBasePath) => {{
+    const src =
+        {src_expr};
+    if (!src) {{
+        return defaultBasePath;
+    }}
+    const url = new URL(src);
+    url.search = "";
+    url.hash = "";
+    if (!url.href.endsWith(chunkPath)) {{
+        return defaultBasePath;
+    }}
+    return url.href.slice(0, url.href.length - chunkPath.length);
+}})({chunk_path}, {default_expr})"#,
+        chunk_path = StringifyJs(chunk_path),
+    )
+}
+
+/// Returns JavaScript code that sets the [base_paths_global] `global` to the
+/// URL of the output root, derived from the URL of the currently executing
+/// script, unless it's already set. `chunk_path` is the path of the script
+/// relative to the output root. `module` is passed on to
+/// [script_base_path_expr].
+///
+/// This allows to load the chunks from hosts that aren't known at build time,
+/// e.g. when they are embedded into third-party pages.
+pub fn auto_base_paths_code(global: &str, chunk_path: &str, module: bool) -> String {
+    format!(
+        r#"(() => {{
+    if (globalThis.{global}) {{
+        return;
+    }}
+    const basePath = {base_path};
+    if (basePath === undefined) {{
+        return;
+    }}
+    globalThis.{global} = {{ chunkBasePath: basePath, assetBasePath: basePath }};
+}})();
+"#,
+        base_path = script_base_path_expr(chunk_path, "undefined", module),
+    )
+}
 
 /// [`EcmascriptChunkingContext`] must be implemented by [`ChunkingContext`]
 /// implementors that want to operate on [`EcmascriptChunk`]s.
@@ -10,4 +103,26 @@ pub trait EcmascriptChunkingContext: ChunkingContext {
     fn has_react_refresh(self: Vc<Self>) -> Vc<bool> {
         Vc::cell(false)
     }
+
+    /// Whether the getters of bindings that are re-exported by several modules
+    /// read the binding from the module declaring it, instead of calling the
+    /// getters of all modules in between.
+    fn flatten_reexports(self: Vc<Self>) -> Vc<bool> {
+        Vc::cell(false)
+    }
+
+    /// Returns the JavaScript expression evaluating to the URL of the static
+    /// asset with `ident`. By default, this is the [ChunkingContext::asset_url]
+    /// as a string literal.
+    async fn asset_url_expr(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<String>> {
+        Ok(Vc::cell(
+            StringifyJs(&*self.asset_url(ident).await?).to_string(),
+        ))
+    }
+
+    /// How CommonJS modules in chunk groups are reported, see
+    /// [super::check_strict_esm].
+    fn strict_esm(self: Vc<Self>) -> Vc<StrictEsm> {
+        StrictEsm::Off.cell()
+    }
 }
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    reference::ModuleReferences,
+};
+
+use super::{
+    EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkType, EcmascriptChunkingContext,
+};
+use crate::utils::StringifyJs;
+
+/// Replaces a module that failed to build. Its module factory throws the
+/// build error, so only the code importing the module fails at runtime, while
+/// the rest of the chunk group keeps working.
+#[turbo_tasks::value]
+pub struct FailingModuleChunkItem {
+    module: Vc<Box<dyn ChunkableModule>>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    error: String,
+}
+
+#[turbo_tasks::value_impl]
+impl FailingModuleChunkItem {
+    #[turbo_tasks::function]
+    pub fn new(
+        module: Vc<Box<dyn ChunkableModule>>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        error: String,
+    ) -> Vc<Self> {
+        Self::cell(FailingModuleChunkItem {
+            module,
+            chunking_context,
+            error,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for FailingModuleChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        // Same ident as the module, so that importers use the id of the stub.
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        ModuleReferences::empty()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.module)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for FailingModuleChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<EcmascriptChunkItemContent> {
+        let message = format!("Module failed to build:\n{}", self.error);
+        EcmascriptChunkItemContent {
+            inner_code: format!("throw new Error({});\n", StringifyJs(&message)).into(),
+            ..Default::default()
+        }
+        .cell()
+    }
+}
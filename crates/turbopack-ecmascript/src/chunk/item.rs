@@ -120,6 +120,9 @@ impl EcmascriptChunkItemContent {
             args.push("w: __turbopack_wasm__");
             args.push("u: __turbopack_wasm_module__");
         }
+        if this.options.federation {
+            args.push("F: __turbopack_load_remote__");
+        }
         let mut code = CodeBuilder::default();
         let args = FormatIter(|| args.iter().copied().intersperse(", "));
         if this.options.this {
@@ -184,6 +187,9 @@ pub struct EcmascriptChunkItemOptions {
     /// Whether this chunk item's module factory should include
     /// `__turbopack_wasm__` to load WebAssembly.
     pub wasm: bool,
+    /// Whether this chunk item's module factory should include
+    /// `__turbopack_load_remote__` to load modules exposed by remote entries.
+    pub federation: bool,
     pub placeholder_for_future_extensions: (),
 }
 
@@ -2,8 +2,10 @@ pub(crate) mod chunk_type;
 pub(crate) mod content;
 pub(crate) mod context;
 pub(crate) mod data;
+pub(crate) mod failing_item;
 pub(crate) mod item;
 pub(crate) mod placeable;
+pub(crate) mod strict_esm;
 
 use std::fmt::Write;
 
@@ -13,7 +15,7 @@ use turbo_tasks_fs::FileSystem;
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{Chunk, ChunkItem, ChunkingContext, ModuleIds},
-    ident::AssetIdent,
+    ident::{common_layer, AssetIdent},
     introspect::{
         module::IntrospectableModule,
         utils::{children_from_output_assets, content_to_details},
@@ -26,13 +28,17 @@ use turbopack_core::{
 pub use self::{
     chunk_type::EcmascriptChunkType,
     content::EcmascriptChunkContent,
-    context::EcmascriptChunkingContext,
+    context::{
+        auto_base_paths_code, base_paths_global, runtime_base_path_expr, EcmascriptChunkingContext,
+    },
     data::EcmascriptChunkData,
+    failing_item::FailingModuleChunkItem,
     item::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemExt,
         EcmascriptChunkItemOptions,
     },
     placeable::{EcmascriptChunkPlaceable, EcmascriptChunkPlaceables, EcmascriptExports},
+    strict_esm::{check_strict_esm, StrictEsm},
 };
 
 #[turbo_tasks::value]
@@ -114,6 +120,18 @@ impl Chunk for EcmascriptChunk {
             *ident = ident.resolve().await?;
         }
 
+        let chunking_context: Vc<Box<dyn ChunkingContext>> = Vc::upcast(this.chunking_context);
+        let layer = if *chunking_context.layered_chunks().await? {
+            common_layer(
+                chunk_items
+                    .iter()
+                    .map(|(chunk_item, _)| chunk_item.asset_ident()),
+            )
+            .await?
+        } else {
+            None
+        };
+
         let ident = AssetIdent {
             path: if let Some((common_path, _)) = common_path {
                 common_path
@@ -125,7 +143,7 @@ impl Chunk for EcmascriptChunk {
             assets,
             modifiers: Vec::new(),
             part: None,
-            layer: None,
+            layer,
         };
 
         Ok(AssetIdent::new(Value::new(ident)))
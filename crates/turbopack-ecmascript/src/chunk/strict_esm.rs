@@ -0,0 +1,226 @@
+//! A strict ESM mode, which reports the modules of a chunk group that use
+//! CommonJS, e.g. to complete a migration to ES modules.
+
+use std::collections::VecDeque;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{Completion, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    chunk::{ChunkableModuleReference, ChunkingType},
+    issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+    module::{Module, Modules},
+};
+
+use super::{EcmascriptChunkPlaceable, EcmascriptChunkingContext, EcmascriptExports};
+use crate::references::cjs::{CjsAssetReference, CjsRequireAssetReference};
+
+/// How CommonJS modules in chunk groups are reported, see
+/// [EcmascriptChunkingContext::strict_esm].
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum StrictEsm {
+    /// CommonJS modules aren't reported.
+    #[default]
+    Off,
+    /// Every CommonJS module is reported as a warning.
+    Warn,
+    /// Every CommonJS module is reported as an error, which fails builds.
+    Error,
+}
+
+/// Which CommonJS pattern a module uses.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Copy)]
+enum CommonJsUsage {
+    /// The module exports with `module.exports` or `exports`.
+    Exports,
+    /// The module imports with `require()`.
+    Require,
+}
+
+#[turbo_tasks::value(transparent)]
+struct OptionCommonJsUsage(Option<CommonJsUsage>);
+
+/// Which CommonJS pattern `module` uses, if any.
+#[turbo_tasks::function]
+async fn commonjs_usage(module: Vc<Box<dyn Module>>) -> Result<Vc<OptionCommonJsUsage>> {
+    if let Some(placeable) =
+        Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module).await?
+    {
+        if matches!(*placeable.get_exports().await?, EcmascriptExports::CommonJs) {
+            return Ok(Vc::cell(Some(CommonJsUsage::Exports)));
+        }
+    }
+    for &reference in module.references().await?.iter() {
+        if Vc::try_resolve_downcast_type::<CjsRequireAssetReference>(reference)
+            .await?
+            .is_some()
+            || Vc::try_resolve_downcast_type::<CjsAssetReference>(reference)
+                .await?
+                .is_some()
+        {
+            return Ok(Vc::cell(Some(CommonJsUsage::Require)));
+        }
+    }
+    Ok(Vc::cell(None))
+}
+
+/// The modules `module` places into its own chunk group, i.e. those it
+/// references except for async imports.
+#[turbo_tasks::function]
+async fn chunk_group_references(module: Vc<Box<dyn Module>>) -> Result<Vc<Modules>> {
+    let mut modules = Vec::new();
+    for &reference in module.references().await?.iter() {
+        let Some(chunkable) =
+            Vc::try_resolve_sidecast::<Box<dyn ChunkableModuleReference>>(reference).await?
+        else {
+            continue;
+        };
+        if matches!(
+            *chunkable.chunking_type().await?,
+            None | Some(ChunkingType::Async)
+        ) {
+            continue;
+        }
+        modules.extend(
+            reference
+                .resolve_reference()
+                .primary_modules()
+                .await?
+                .iter()
+                .copied(),
+        );
+    }
+    Ok(Vc::cell(modules))
+}
+
+/// Reports the CommonJS modules in the module graph of `entries` according to
+/// the [StrictEsm] mode of `chunking_context`, with the chain of modules
+/// importing them. Modules that are loaded asynchronously aren't part of the
+/// chunk group, and are checked with their own chunk group.
+///
+/// Every chunk group walks its module graph, but the modules are only
+/// analyzed once.
+#[turbo_tasks::function]
+pub async fn check_strict_esm(
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    entries: Vc<Modules>,
+) -> Result<Vc<Completion>> {
+    let mode = *chunking_context.strict_esm().await?;
+    if mode == StrictEsm::Off {
+        return Ok(Completion::new());
+    }
+
+    // Maps every visited module to the module it was first imported from, so
+    // that the shortest importer chain can be reported.
+    let mut importers: IndexMap<Vc<Box<dyn Module>>, Option<Vc<Box<dyn Module>>>> = IndexMap::new();
+    let mut queue = VecDeque::new();
+    for &entry in entries.await?.iter() {
+        if importers.insert(entry, None).is_none() {
+            queue.push_back(entry);
+        }
+    }
+
+    while let Some(module) = queue.pop_front() {
+        for &referenced in chunk_group_references(module).await?.iter() {
+            if !importers.contains_key(&referenced) {
+                importers.insert(referenced, Some(module));
+                queue.push_back(referenced);
+            }
+        }
+
+        let Some(usage) = *commonjs_usage(module).await? else {
+            continue;
+        };
+        let severity = match mode {
+            StrictEsm::Off => continue,
+            StrictEsm::Warn => IssueSeverity::Warning,
+            StrictEsm::Error => IssueSeverity::Error,
+        };
+
+        let mut chain = Vec::new();
+        let mut current = importers.get(&module).copied().flatten();
+        while let Some(importer) = current {
+            chain.push(importer);
+            current = importers.get(&importer).copied().flatten();
+        }
+        chain.reverse();
+
+        CommonJsInChunkGroupIssue {
+            path: module.ident().path(),
+            usage,
+            importers: chain,
+            severity,
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Completion::new())
+}
+
+/// A module of a chunk group uses CommonJS, while the chunking context is in
+/// [StrictEsm] mode.
+#[turbo_tasks::value(shared)]
+struct CommonJsInChunkGroupIssue {
+    path: Vc<FileSystemPath>,
+    usage: CommonJsUsage,
+    /// The chain of modules importing the module, starting with the entry.
+    importers: Vec<Vc<Box<dyn Module>>>,
+    severity: IssueSeverity,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for CommonJsInChunkGroupIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("module type".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(match self.usage {
+            CommonJsUsage::Exports => "CommonJS module in strict ESM mode".to_string(),
+            CommonJsUsage::Require => "require() call in strict ESM mode".to_string(),
+        })
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<OptionStyledString>> {
+        let mut lines = vec![StyledString::Text(
+            match self.usage {
+                CommonJsUsage::Exports => {
+                    "This module exports with `module.exports` or `exports`, but only ES modules \
+                     are expected in this chunk group."
+                }
+                CommonJsUsage::Require => {
+                    "This module imports with `require()`, but only ES modules are expected in \
+                     this chunk group."
+                }
+            }
+            .to_string(),
+        )];
+        if !self.importers.is_empty() {
+            lines.push(StyledString::Text("It is imported by:".to_string()));
+            for importer in self.importers.iter().rev() {
+                lines.push(StyledString::Code(
+                    importer.ident().path().to_string().await?.clone_value(),
+                ));
+            }
+        }
+        Ok(Vc::cell(Some(StyledString::Stack(lines).cell())))
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use swc_core::{
+    base::{try_with_handler, Compiler},
+    common::{
+        chain,
+        comments::{Comments, SingleThreadedComments},
+        FileName, FilePathMapping, Mark, SourceMap as SwcSourceMap, GLOBALS,
+    },
+    ecma::{
+        self,
+        ast::EsVersion,
+        parser::{lexer::Lexer, Parser, StringInput, Syntax},
+        preset_env::{self, Targets},
+        transforms::base::{
+            feature::FeatureFlag,
+            helpers::{inject_helpers, Helpers, HELPERS},
+            Assumptions,
+        },
+        visit::FoldWith,
+    },
+};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    code_builder::{Code, CodeBuilder},
+    environment::Environment,
+    source_map::GenerateSourceMap,
+};
+
+use crate::{minify::print_program, ParseResultSourceMap};
+
+/// Transforms the code of a chunk so that it runs in `environment`. Helpers
+/// needed by the transformed code are inlined into the chunk. Polyfills are
+/// not inserted, they need to be evaluated before the chunk. Like [minify],
+/// this drops the comment that references the source map.
+///
+/// [minify]: crate::minify::minify
+#[turbo_tasks::function]
+pub async fn downlevel(
+    path: Vc<FileSystemPath>,
+    code: Vc<Code>,
+    environment: Vc<Environment>,
+) -> Result<Vc<Code>> {
+    let path = path.await?;
+    let versions = environment.runtime_versions().await?;
+    let original_map = code.generate_source_map().await?.clone_value();
+    let code = code.await?;
+
+    let cm = Arc::new(SwcSourceMap::new(FilePathMapping::empty()));
+    let compiler = Arc::new(Compiler::new(cm.clone()));
+    let fm = compiler.cm.new_source_file(
+        FileName::Custom(path.path.to_string()),
+        code.source_code().to_str()?.to_string(),
+    );
+
+    let lexer = Lexer::new(
+        Syntax::default(),
+        EsVersion::latest(),
+        StringInput::from(&*fm),
+        None,
+    );
+    let mut parser = Parser::new_from(lexer);
+    let program = try_with_handler(cm.clone(), Default::default(), |handler| {
+        GLOBALS.set(&Default::default(), || {
+            let program = match parser.parse_program() {
+                Ok(program) => program,
+                Err(_error) => {
+                    bail!("failed to parse source code")
+                }
+            };
+            let comments = SingleThreadedComments::default();
+            let unresolved_mark = Mark::new();
+            let top_level_mark = Mark::new();
+            let config = preset_env::Config {
+                targets: Some(Targets::Versions(*versions)),
+                mode: None, // Polyfills are evaluated as separate modules
+                ..Default::default()
+            };
+
+            Ok(compiler.run_transform(handler, false, || {
+                HELPERS.set(&Helpers::new(false), || {
+                    let program =
+                        program.fold_with(&mut swc_core::ecma::transforms::base::resolver(
+                            unresolved_mark,
+                            top_level_mark,
+                            false,
+                        ));
+
+                    program.fold_with(&mut chain!(
+                        preset_env::preset_env::<&'_ dyn Comments>(
+                            top_level_mark,
+                            Some(&comments),
+                            config,
+                            Assumptions::default(),
+                            &mut FeatureFlag::empty(),
+                        ),
+                        inject_helpers(unresolved_mark),
+                        ecma::transforms::base::hygiene::hygiene(),
+                        ecma::transforms::base::fixer::fixer(Some(&comments as &dyn Comments)),
+                    ))
+                })
+            }))
+        })
+    })?;
+
+    let (src, src_map_buf) = print_program(cm.clone(), program, false)?;
+
+    let mut builder = CodeBuilder::default();
+    builder.push_source(
+        &src.into(),
+        Some(Vc::upcast(
+            ParseResultSourceMap::new(cm, src_map_buf, original_map).cell(),
+        )),
+    );
+
+    Ok(builder.build().cell())
+}
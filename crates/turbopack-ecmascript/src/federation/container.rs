@@ -0,0 +1,269 @@
+use std::fmt::Write;
+
+use anyhow::{Context, Result};
+use indoc::formatdoc;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        ChunkItem, ChunkType, ChunkableModule, ChunkableModuleReference, ChunkingContext,
+        ChunkingType, ChunkingTypeOption, EvaluatableAsset,
+    },
+    ident::AssetIdent,
+    module::Module,
+    reference::{ModuleReference, ModuleReferences},
+    resolve::ModuleResolveResult,
+};
+
+use super::ExposedModules;
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
+    },
+    utils::StringifyJs,
+};
+
+/// The module evaluated by the remote entry chunk of an app, which registers
+/// the container of the modules it exposes in the `TURBOPACK_REMOTES` global.
+/// Every exposed module is placed into its own async chunk group, which is
+/// loaded when a host imports it.
+#[turbo_tasks::value]
+pub struct RemoteContainerModule {
+    context_path: Vc<FileSystemPath>,
+    name: String,
+    exposes: Vc<ExposedModules>,
+}
+
+#[turbo_tasks::value_impl]
+impl RemoteContainerModule {
+    #[turbo_tasks::function]
+    pub fn new(
+        context_path: Vc<FileSystemPath>,
+        name: String,
+        exposes: Vc<ExposedModules>,
+    ) -> Vc<Self> {
+        RemoteContainerModule {
+            context_path,
+            name,
+            exposes,
+        }
+        .cell()
+    }
+
+    /// The name the container registers with.
+    #[turbo_tasks::function]
+    pub fn name(&self) -> Vc<String> {
+        Vc::cell(self.name.clone())
+    }
+
+    /// The modules exposed by the container.
+    #[turbo_tasks::function]
+    pub fn exposes(&self) -> Vc<ExposedModules> {
+        self.exposes
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for RemoteContainerModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.context_path)
+            .with_modifier(Vc::cell(format!("remote container {}", self.name)))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+        Ok(Vc::cell(
+            self.exposes
+                .await?
+                .iter()
+                .map(|(expose, &module)| {
+                    Vc::upcast(ExposedModuleReference::new(module, expose.clone()))
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for RemoteContainerModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("// Chunking only content".to_string()).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for RemoteContainerModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     RemoteContainerModule",
+                )?;
+        Ok(Vc::upcast(
+            RemoteContainerChunkItem {
+                chunking_context,
+                inner: self,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for RemoteContainerModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EvaluatableAsset for RemoteContainerModule {}
+
+/// A reference from a [RemoteContainerModule] to a module it exposes, which is
+/// loaded asynchronously.
+#[turbo_tasks::value]
+struct ExposedModuleReference {
+    module: Vc<Box<dyn Module>>,
+    expose: String,
+}
+
+#[turbo_tasks::value_impl]
+impl ExposedModuleReference {
+    #[turbo_tasks::function]
+    fn new(module: Vc<Box<dyn Module>>, expose: String) -> Vc<Self> {
+        ExposedModuleReference { module, expose }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ExposedModuleReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        ModuleResolveResult::module(self.module).cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ExposedModuleReference {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<String> {
+        Vc::cell(format!("exposed module {}", self.expose))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ExposedModuleReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Async))
+    }
+}
+
+#[turbo_tasks::value]
+struct RemoteContainerChunkItem {
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    inner: Vc<RemoteContainerModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for RemoteContainerChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let container = self.inner.await?;
+
+        // Every exposed module is imported like with `import()`, by the loader
+        // of its async chunk group.
+        let mut exposes = String::new();
+        for (expose, &module) in container.exposes.await?.iter() {
+            let module = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module)
+                .await?
+                .with_context(|| format!("the exposed module {} isn't chunkable", expose))?;
+            let loader_id = self
+                .chunking_context
+                .async_loader_chunk_item_id(module)
+                .await?;
+            writeln!(
+                exposes,
+                "    {}: () => __turbopack_require__({})(__turbopack_import__),",
+                StringifyJs(expose),
+                StringifyJs(&*loader_id),
+            )?;
+        }
+
+        let code = formatdoc! {
+            r#"
+                const exposes = {{
+                {exposes}}};
+                const container = {{
+                    get(expose) {{
+                        const load = exposes[expose];
+                        if (!load) {{
+                            return Promise.reject(new Error("Module " + expose + " isn't exposed by remote " + {name}));
+                        }}
+                        return load();
+                    }},
+                }};
+                const remotes = globalThis.TURBOPACK_REMOTES = globalThis.TURBOPACK_REMOTES || {{}};
+                const remote = remotes[{name}] = remotes[{name}] || {{}};
+                remote.container = container;
+                if (remote.resolve) {{
+                    remote.resolve(container);
+                }}
+                __turbopack_export_value__(container);
+            "#,
+            name = StringifyJs(&container.name),
+        };
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for RemoteContainerChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        self.inner.references()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.inner)
+    }
+}
@@ -0,0 +1,63 @@
+//! Module federation, which shares modules between separately built apps
+//! without publishing packages.
+//!
+//! A remote app exposes modules with a [RemoteContainerModule], which is
+//! evaluated by the remote entry chunk. It registers a container in the
+//! `TURBOPACK_REMOTES` global, from which the exposed modules are loaded on
+//! demand. A host app consumes them with [RemoteModule]s, which load the
+//! remote entry with the chunk loader at runtime and import the exposed module
+//! from the container.
+//!
+//! Modules like `react` break when every app brings its own copy, so they can
+//! be shared with [SharedModule]s: all apps use the copy that was registered
+//! first in the `TURBOPACK_SHARED` global.
+
+pub mod container;
+pub mod remote;
+pub mod shared;
+
+use indexmap::IndexMap;
+use turbo_tasks::Vc;
+use turbopack_core::module::Module;
+
+pub use self::{container::RemoteContainerModule, remote::RemoteModule, shared::SharedModule};
+
+/// A remote app whose exposed modules are consumed.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct FederationRemote {
+    /// The name the container of the remote registers with, which is the
+    /// first segment of the requests of its modules, e.g. `shop` for
+    /// `shop/Button`.
+    pub name: String,
+    /// The URL of the remote entry chunk.
+    pub remote_entry: String,
+}
+
+/// The modules exposed by a remote app, by the names they are requested with,
+/// e.g. `Button` for `shop/Button`.
+#[turbo_tasks::value(transparent)]
+pub struct ExposedModules(IndexMap<String, Vc<Box<dyn Module>>>);
+
+/// Whether `name` is a valid JavaScript identifier (ASCII only), e.g. to name
+/// the global variable of an app.
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers() {
+        assert!(is_identifier("TURBOPACK_shop"));
+        assert!(is_identifier("$a1"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("1a"));
+        assert!(!is_identifier("a-b"));
+        assert!(!is_identifier("a;alert(1)"));
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkingContext},
+    ident::AssetIdent,
+    module::Module,
+    reference::ModuleReferences,
+};
+
+use super::FederationRemote;
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemOptions,
+        EcmascriptChunkPlaceable, EcmascriptChunkType, EcmascriptChunkingContext,
+        EcmascriptExports,
+    },
+    references::async_module::AsyncModuleOptions,
+    utils::StringifyJs,
+};
+
+/// A module exposed by a [FederationRemote], e.g. `shop/Button`. It loads the
+/// remote entry at runtime, unless its container is already registered, and
+/// re-exports the namespace of the exposed module. It's an async module, so
+/// importing it waits until the remote is loaded.
+#[turbo_tasks::value]
+pub struct RemoteModule {
+    ident: Vc<AssetIdent>,
+    remote: Vc<FederationRemote>,
+    expose: String,
+}
+
+#[turbo_tasks::value_impl]
+impl RemoteModule {
+    #[turbo_tasks::function]
+    pub fn new(ident: Vc<AssetIdent>, remote: Vc<FederationRemote>, expose: String) -> Vc<Self> {
+        RemoteModule {
+            ident,
+            remote,
+            expose,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for RemoteModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.ident
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        // The exposed module is part of the chunks of the remote, which are built
+        // separately.
+        ModuleReferences::empty()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for RemoteModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("// Chunking only content".to_string()).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for RemoteModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use RemoteModule",
+                )?;
+        Ok(Vc::upcast(
+            RemoteChunkItem {
+                chunking_context,
+                inner: self,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for RemoteModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::DynamicNamespace.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct RemoteChunkItem {
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    inner: Vc<RemoteModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for RemoteChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let module = self.inner.await?;
+        let remote = module.remote.await?;
+        let code = format!(
+            "__turbopack_dynamic__(await __turbopack_load_remote__({}, {}, {}));\n",
+            StringifyJs(&remote.remote_entry),
+            StringifyJs(&remote.name),
+            StringifyJs(&module.expose),
+        );
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            options: EcmascriptChunkItemOptions {
+                strict: true,
+                async_module: Some(AsyncModuleOptions {
+                    has_top_level_await: true,
+                }),
+                federation: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for RemoteChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        ModuleReferences::empty()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.inner)
+    }
+
+    #[turbo_tasks::function]
+    fn is_self_async(&self) -> Vc<bool> {
+        Vc::cell(true)
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{ChunkItem, ChunkType, ChunkableModule, ChunkableModuleReference, ChunkingContext},
+    ident::AssetIdent,
+    issue::IssueSeverity,
+    module::Module,
+    reference::{ModuleReference, ModuleReferences},
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{origin::ResolveOrigin, parse::Request, ModuleResolveResult},
+};
+
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
+    },
+    resolve::esm_resolve,
+    utils::StringifyJs,
+};
+
+/// A module shared between the apps of a page, e.g. `react`, whose hooks break
+/// when a remote component brings its own copy. Shared modules are
+/// singletons: the app that evaluates a shared module first registers its copy
+/// in the `TURBOPACK_SHARED` global, and the other apps use that copy instead
+/// of their own.
+#[turbo_tasks::value]
+pub struct SharedModule {
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    name: String,
+}
+
+#[turbo_tasks::value_impl]
+impl SharedModule {
+    /// Creates a shared module for the request `name`, which is resolved from
+    /// `origin` to the copy of the app.
+    #[turbo_tasks::function]
+    pub fn new(origin: Vc<Box<dyn ResolveOrigin>>, name: String) -> Vc<Self> {
+        SharedModule { origin, name }.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn fallback(&self) -> Vc<SharedFallbackReference> {
+        SharedFallbackReference::new(self.origin, self.name.clone())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for SharedModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.origin.origin_path())
+            .with_modifier(Vc::cell(format!("shared module {}", self.name)))
+    }
+
+    #[turbo_tasks::function]
+    fn references(self: Vc<Self>) -> Vc<ModuleReferences> {
+        Vc::cell(vec![Vc::upcast(self.fallback())])
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SharedModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("// Chunking only content".to_string()).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for SharedModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use SharedModule",
+                )?;
+        Ok(Vc::upcast(
+            SharedChunkItem {
+                chunking_context,
+                inner: self,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for SharedModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::DynamicNamespace.cell()
+    }
+}
+
+/// A reference from a [SharedModule] to the copy of the module of the app,
+/// which is used when no other app has registered the shared module before.
+#[turbo_tasks::value]
+struct SharedFallbackReference {
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    name: String,
+}
+
+#[turbo_tasks::value_impl]
+impl SharedFallbackReference {
+    #[turbo_tasks::function]
+    fn new(origin: Vc<Box<dyn ResolveOrigin>>, name: String) -> Vc<Self> {
+        SharedFallbackReference { origin, name }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for SharedFallbackReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        esm_resolve(
+            self.origin,
+            Request::parse(Value::new(self.name.clone().into())),
+            Value::new(EcmaScriptModulesReferenceSubType::Import),
+            IssueSeverity::Error.cell(),
+            None,
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for SharedFallbackReference {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<String> {
+        Vc::cell(format!("fallback of shared module {}", self.name))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for SharedFallbackReference {}
+
+#[turbo_tasks::value]
+struct SharedChunkItem {
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    inner: Vc<SharedModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for SharedChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let module = self.inner.await?;
+        let fallback = (*self
+            .inner
+            .fallback()
+            .resolve_reference()
+            .first_module()
+            .await?)
+            .with_context(|| format!("unable to resolve the shared module {}", module.name))?;
+        let fallback = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(fallback)
+            .await?
+            .with_context(|| format!("the shared module {} isn't chunkable", module.name))?;
+        let fallback_id = fallback
+            .as_chunk_item(Vc::upcast(self.chunking_context))
+            .id()
+            .await?;
+        let code = format!(
+            "const shared = globalThis.TURBOPACK_SHARED = globalThis.TURBOPACK_SHARED || \
+             {{}};\n__turbopack_export_namespace__(shared[{name}] = shared[{name}] || \
+             __turbopack_import__({id}));\n",
+            name = StringifyJs(&module.name),
+            id = StringifyJs(&*fallback_id),
+        );
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for SharedChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        self.inner.references()
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.inner)
+    }
+}
@@ -10,9 +10,12 @@ pub mod analyzer;
 pub mod chunk;
 pub mod chunk_group_files_asset;
 pub mod code_gen;
+pub mod downlevel;
 mod errors;
+pub mod federation;
 pub mod magic_identifier;
 pub mod manifest;
+pub mod minify;
 pub mod parse;
 mod path_visitor;
 pub mod references;
@@ -27,6 +30,8 @@ pub mod tree_shake;
 pub mod typescript;
 pub mod utils;
 pub mod webpack;
+pub mod worker;
+pub mod worklet;
 
 use std::fmt::{Display, Formatter};
 
@@ -43,6 +48,7 @@ pub use static_code::StaticEcmascriptCode;
 use swc_core::{
     common::GLOBALS,
     ecma::{
+        ast::{ImportDecl, ModuleDecl, ModuleItem, Program},
         codegen::{text_writer::JsWriter, Emitter},
         visit::{VisitMutWith, VisitMutWithPath},
     },
@@ -63,8 +69,8 @@ use turbopack_core::{
     compile_time_info::CompileTimeInfo,
     context::AssetContext,
     ident::AssetIdent,
-    module::{Module, OptionModule},
-    reference::ModuleReferences,
+    module::{Module, OptionModule, OptionModules},
+    reference::{ModuleReference, ModuleReferences},
     reference_type::InnerAssets,
     resolve::{
         find_context_file, origin::ResolveOrigin, package_json, parse::Request,
@@ -409,6 +415,53 @@ impl EcmascriptModuleAsset {
         parse(self.source, Value::new(self.ty), self.transforms)
     }
 
+    /// The modules imported by this module when it only consists of imports
+    /// without bindings of modules that aren't JavaScript, e.g.
+    /// `import "./styles.css"`. Evaluating such a module has no effect besides
+    /// loading the imported modules. `None` for all other modules.
+    #[turbo_tasks::function]
+    pub async fn static_imports(self: Vc<Self>) -> Result<Vc<OptionModules>> {
+        let ParseResult::Ok {
+            program: Program::Module(module),
+            ..
+        } = &*self.parse().await?
+        else {
+            return Ok(Vc::cell(None));
+        };
+        let only_imports = module.body.iter().all(|item| {
+            matches!(
+                item,
+                ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    specifiers,
+                    type_only: false,
+                    ..
+                })) if specifiers.is_empty()
+            )
+        });
+        if !only_imports {
+            return Ok(Vc::cell(None));
+        }
+
+        let mut modules = Vec::new();
+        for &reference in self.failsafe_analyze().await?.references.await?.iter() {
+            for &module in reference
+                .resolve_reference()
+                .primary_modules()
+                .await?
+                .iter()
+            {
+                if Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module)
+                    .await?
+                    .is_some()
+                {
+                    return Ok(Vc::cell(None));
+                }
+                modules.push(module);
+            }
+        }
+        Ok(Vc::cell(Some(Vc::cell(modules))))
+    }
+
     #[turbo_tasks::function]
     pub(crate) async fn determine_module_type(self: Vc<Self>) -> Result<Vc<ModuleTypeResult>> {
         let this = self.await?;
@@ -662,9 +715,11 @@ impl EcmascriptChunkItem for ModuleChunkItem {
         async_module_info: Option<Vc<AsyncModuleInfo>>,
     ) -> Result<Vc<EcmascriptChunkItemContent>> {
         let this = self.await?;
+        let ident = self.asset_ident();
         let _span = tracing::info_span!(
             "code generation",
-            module = *self.asset_ident().to_string().await?
+            module = *ident.to_string().await?,
+            path = *ident.path().to_string().await?
         )
         .entered();
         let async_module_options = this
@@ -732,7 +787,7 @@ impl EcmascriptModuleContent {
             }
         }
         if let EcmascriptExports::EsmExports(exports) = *exports.await? {
-            code_gens.push(exports.code_generation(chunking_context));
+            code_gens.push(exports.code_generation(chunking_context, async_module_info));
         }
 
         // need to keep that around to allow references into that
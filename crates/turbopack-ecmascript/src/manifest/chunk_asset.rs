@@ -34,6 +34,8 @@ pub struct ManifestAsyncModule {
     pub inner: Vc<Box<dyn ChunkableModule>>,
     pub chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     pub availability_info: AvailabilityInfo,
+    /// The name of the chunk group, e.g. from a `turbopackChunkName` comment.
+    pub chunk_group_name: Option<String>,
 }
 
 #[turbo_tasks::value_impl]
@@ -48,12 +50,41 @@ impl ManifestAsyncModule {
             inner: module,
             chunking_context,
             availability_info: availability_info.into_value(),
+            chunk_group_name: None,
+        })
+    }
+
+    /// Like [ManifestAsyncModule::new], but the chunks of the chunk group are
+    /// named after `name`.
+    #[turbo_tasks::function]
+    pub fn new_named(
+        module: Vc<Box<dyn ChunkableModule>>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        availability_info: Value<AvailabilityInfo>,
+        name: String,
+    ) -> Vc<Self> {
+        Self::cell(ManifestAsyncModule {
+            inner: module,
+            chunking_context,
+            availability_info: availability_info.into_value(),
+            chunk_group_name: Some(name),
         })
     }
 
     #[turbo_tasks::function]
     pub(super) async fn chunks(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
+        if let Some(name) = &this.chunk_group_name {
+            return Ok(this
+                .chunking_context
+                .named_chunk_group(
+                    Vc::upcast(this.inner),
+                    Value::new(this.availability_info),
+                    name.clone(),
+                )
+                .await?
+                .assets);
+        }
         Ok(this
             .chunking_context
             .chunk_group_assets(Vc::upcast(this.inner), Value::new(this.availability_info)))
@@ -91,7 +122,11 @@ fn manifest_chunk_reference_description() -> Vc<String> {
 impl Module for ManifestAsyncModule {
     #[turbo_tasks::function]
     fn ident(&self) -> Vc<AssetIdent> {
-        self.inner.ident().with_modifier(modifier())
+        let ident = self.inner.ident().with_modifier(modifier());
+        match &self.chunk_group_name {
+            Some(name) => ident.with_modifier(Vc::cell(format!("chunk name {name}"))),
+            None => ident,
+        }
     }
 
     #[turbo_tasks::function]
@@ -1,4 +1,4 @@
-use std::{io::Write, sync::Arc};
+use std::sync::Arc;
 
 use anyhow::{bail, Context, Result};
 use swc_core::{
@@ -25,10 +25,18 @@ use turbopack_core::{
     code_builder::{Code, CodeBuilder},
     source_map::GenerateSourceMap,
 };
-use turbopack_ecmascript::ParseResultSourceMap;
 
+use crate::ParseResultSourceMap;
+
+/// Minifies the code of the chunk at `path`, keeping its source map. The
+/// comment that references the source map is dropped, callers append it again
+/// with [append_source_map_url](turbopack_core::code_builder::append_source_map_url).
+///
+/// Compressing is the slow part of minification. Without `compress`, only
+/// local names are mangled and whitespace and comments are removed, which is
+/// fast enough for development and still shrinks chunks considerably.
 #[turbo_tasks::function]
-pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>> {
+pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>, compress: bool) -> Result<Vc<Code>> {
     let path = path.await?;
     let original_map = code.generate_source_map().await?.clone_value();
     let code = code.await?;
@@ -75,7 +83,7 @@ pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>
                     Some(&comments),
                     None,
                     &MinifyOptions {
-                        compress: Some(Default::default()),
+                        compress: compress.then(Default::default),
                         mangle: Some(Default::default()),
                         ..Default::default()
                     },
@@ -92,7 +100,7 @@ pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>
         })
     })?;
 
-    let (src, src_map_buf) = print_program(cm.clone(), program)?;
+    let (src, src_map_buf) = print_program(cm.clone(), program, true)?;
 
     let mut builder = CodeBuilder::default();
     builder.push_source(
@@ -102,14 +110,14 @@ pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>
         )),
     );
 
-    write!(builder, "\n\n//# sourceMappingURL={}.map", path.file_name())?;
     Ok(builder.build().cell())
 }
 
 // From https://github.com/swc-project/swc/blob/11efd4e7c5e8081f8af141099d3459c3534c1e1d/crates/swc/src/lib.rs#L523-L560
-fn print_program(
+pub fn print_program(
     cm: Arc<SwcSourceMap>,
     program: Program,
+    minify: bool,
 ) -> Result<(String, Vec<(BytePos, LineCol)>)> {
     let mut src_map_buf = vec![];
 
@@ -124,7 +132,7 @@ fn print_program(
             )))) as Box<dyn WriteJs>;
 
             let mut emitter = Emitter {
-                cfg: swc_core::ecma::codegen::Config::default().with_minify(true),
+                cfg: swc_core::ecma::codegen::Config::default().with_minify(minify),
                 comments: None,
                 cm: cm.clone(),
                 wr,
@@ -155,7 +155,13 @@ pub async fn parse(
     transforms: Vc<EcmascriptInputTransforms>,
 ) -> Result<Vc<ParseResult>> {
     let name = source.ident().to_string().await?;
-    let span = tracing::info_span!("parse ecmascript", name = *name, ty = display(&*ty));
+    let path = source.ident().path().to_string().await?;
+    let span = tracing::info_span!(
+        "parse ecmascript",
+        name = *name,
+        path = *path,
+        ty = display(&*ty)
+    );
     match parse_internal(source, ty, transforms)
         .instrument(span)
         .await
@@ -8,9 +8,10 @@ use swc_core::{
 use turbo_tasks::{Value, ValueToString, Vc};
 use turbopack_core::{
     chunk::{
-        ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingContext, ChunkingType,
-        ChunkingTypeOption, ModuleId,
+        is_split_point, ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingContext,
+        ChunkingType, ChunkingTypeOption, ModuleId,
     },
+    environment::ChunkLoading,
     issue::{IssueSeverity, IssueSource},
     module::Module,
     reference::ModuleReference,
@@ -223,6 +224,33 @@ impl CodeGenerateable for EsmAssetReference {
             let import_externals = this.import_externals;
             if let Some(ident) = referenced_asset.get_ident().await? {
                 match &*referenced_asset {
+                    ReferencedAsset::Some(asset)
+                        if *is_split_point(Vc::upcast(chunking_context), Vc::upcast(*asset))
+                            .await?
+                            && !matches!(
+                                *chunking_context.environment().chunk_loading().await?,
+                                ChunkLoading::None
+                            ) =>
+                    {
+                        // Split points are placed in their own chunk group. The
+                        // importing module is an async module that waits for the
+                        // promise returned by the async loader.
+                        let loader_id = chunking_context
+                            .async_loader_chunk_item_id(Vc::upcast(*asset))
+                            .await?;
+                        visitors.push(create_visitor!(visit_mut_program(program: &mut Program) {
+                            let stmt = quote!(
+                                "var $name = __turbopack_require__($id)(__turbopack_import__);"
+                                    as Stmt,
+                                name = Ident::new(ident.clone().into(), DUMMY_SP),
+                                id: Expr = Expr::Lit(match &*loader_id {
+                                    ModuleId::String(s) => s.clone().into(),
+                                    ModuleId::Number(n) => (*n as f64).into(),
+                                })
+                            );
+                            insert_hoisted_stmt(program, stmt);
+                        }));
+                    }
                     ReferencedAsset::Some(asset) => {
                         let id = asset
                             .as_chunk_item(Vc::upcast(chunking_context))
@@ -31,6 +31,8 @@ pub struct EsmAsyncAssetReference {
     pub issue_source: Vc<IssueSource>,
     pub in_try: bool,
     pub import_externals: bool,
+    /// The name of the chunk group, from a `turbopackChunkName` comment.
+    pub chunk_name: Option<String>,
 }
 
 #[turbo_tasks::value_impl]
@@ -43,6 +45,7 @@ impl EsmAsyncAssetReference {
         issue_source: Vc<IssueSource>,
         in_try: bool,
         import_externals: bool,
+        chunk_name: Option<String>,
     ) -> Vc<Self> {
         Self::cell(EsmAsyncAssetReference {
             origin,
@@ -51,6 +54,7 @@ impl EsmAsyncAssetReference {
             issue_source,
             in_try,
             import_externals,
+            chunk_name,
         })
     }
 }
@@ -86,6 +90,11 @@ impl ChunkableModuleReference for EsmAsyncAssetReference {
     fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
         Vc::cell(Some(ChunkingType::Async))
     }
+
+    #[turbo_tasks::function]
+    fn chunk_group_name(&self) -> Vc<Option<String>> {
+        Vc::cell(self.chunk_name.clone())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -1,5 +1,6 @@
 use std::{
     collections::{BTreeMap, HashSet},
+    fmt::Write,
     ops::ControlFlow,
 };
 
@@ -15,18 +16,22 @@ use swc_core::{
 };
 use turbo_tasks::{trace::TraceRawVcs, ValueToString, Vc};
 use turbopack_core::{
+    chunk::{
+        AsyncModuleInfo, ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingType,
+    },
     ident::AssetIdent,
     issue::{analyze::AnalyzeIssue, IssueExt, IssueSeverity, StyledString},
-    module::Module,
+    module::{Module, Modules},
     reference::ModuleReference,
 };
 
 use super::base::ReferencedAsset;
 use crate::{
     chunk::{EcmascriptChunkPlaceable, EcmascriptChunkingContext, EcmascriptExports},
-    code_gen::{CodeGenerateable, CodeGeneration},
+    code_gen::{CodeGenerateableWithAsyncModuleInfo, CodeGeneration},
     create_visitor,
     references::esm::base::insert_hoisted_stmt,
+    utils::module_id_to_lit,
 };
 
 #[derive(Clone, Hash, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
@@ -236,6 +241,124 @@ async fn handle_star_reexports(
     }
 }
 
+/// A binding that is re-exported by a chain of modules.
+#[turbo_tasks::value(shared)]
+#[derive(Debug)]
+pub struct ReexportChain {
+    /// The modules re-exporting the binding from the next module, in order,
+    /// with the name they export it as.
+    pub links: Vec<(Vc<Box<dyn EcmascriptChunkPlaceable>>, String)>,
+    /// The module declaring the binding.
+    pub target: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    /// The name of the binding in `target`, or `None` when the namespace of
+    /// `target` is re-exported.
+    pub export_name: Option<String>,
+}
+
+/// Follows the re-exports of `export_name` (the namespace when `None`) of
+/// `module` through `export { a } from "..."`, `export * from "..."` and
+/// `export * as a from "..."`.
+///
+/// Only references that place the module into the same chunk group and make
+/// the importer async when the module is async are followed. So when the
+/// start of the chain isn't an async module, the target can be imported
+/// directly.
+#[turbo_tasks::function]
+pub async fn follow_reexport_chain(
+    module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
+    export_name: Option<String>,
+) -> Result<Vc<ReexportChain>> {
+    let mut module = module.resolve().await?;
+    let mut export_name = export_name;
+    let mut links = Vec::new();
+    let mut visited = HashSet::new();
+    visited.insert(module);
+    while let Some(name) = export_name.clone() {
+        let EcmascriptExports::EsmExports(exports) = *module.get_exports().await? else {
+            break;
+        };
+        let expanded = exports.expand_exports().await?;
+        let (reference, next_name) = match expanded.exports.get(&name) {
+            Some(EsmExport::ImportedBinding(reference, name)) => (*reference, Some(name.clone())),
+            Some(EsmExport::ImportedNamespace(reference)) => (*reference, None),
+            _ => break,
+        };
+        let Some(reference) =
+            Vc::try_resolve_sidecast::<Box<dyn ChunkableModuleReference>>(reference).await?
+        else {
+            break;
+        };
+        if !matches!(
+            *reference.chunking_type().await?,
+            Some(ChunkingType::ParallelInheritAsync)
+        ) {
+            break;
+        }
+        let ReferencedAsset::Some(next) =
+            *ReferencedAsset::from_resolve_result(reference.resolve_reference()).await?
+        else {
+            break;
+        };
+        let next = next.resolve().await?;
+        // Stop at cyclic re-exports.
+        if !visited.insert(next) {
+            break;
+        }
+        links.push((module, name));
+        module = next;
+        export_name = next_name;
+    }
+    Ok(ReexportChain {
+        links,
+        target: module,
+        export_name,
+    }
+    .cell())
+}
+
+/// Lists the `limit` longest chains of re-exports of the exports of
+/// `modules`, longest first.
+#[turbo_tasks::function]
+pub async fn reexport_chains_report(modules: Vc<Modules>, limit: usize) -> Result<Vc<String>> {
+    let mut chains = Vec::new();
+    for &module in modules.await?.iter() {
+        let Some(module) =
+            Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module).await?
+        else {
+            continue;
+        };
+        let EcmascriptExports::EsmExports(exports) = *module.get_exports().await? else {
+            continue;
+        };
+        for (name, export) in exports.expand_exports().await?.exports.iter() {
+            if matches!(
+                export,
+                EsmExport::ImportedBinding(..) | EsmExport::ImportedNamespace(_)
+            ) {
+                chains.push(follow_reexport_chain(module, Some(name.clone())).await?);
+            }
+        }
+    }
+    // A binding that is re-exported once doesn't need to be flattened.
+    chains.retain(|chain| chain.links.len() > 1);
+    chains.sort_by(|a, b| b.links.len().cmp(&a.links.len()));
+    chains.truncate(limit);
+
+    let mut report = String::new();
+    for chain in chains {
+        let mut line = format!("{} re-exports: ", chain.links.len());
+        for (module, name) in chain.links.iter() {
+            write!(line, "{} ({}) -> ", module.ident().to_string().await?, name)?;
+        }
+        write!(line, "{}", chain.target.ident().to_string().await?)?;
+        if let Some(name) = &chain.export_name {
+            write!(line, " ({})", name)?;
+        }
+        writeln!(report, "{}", line)?;
+    }
+    Ok(Vc::cell(report))
+}
+
 #[turbo_tasks::value]
 pub struct ExpandStarResult {
     pub star_exports: Vec<String>,
@@ -385,12 +508,83 @@ impl EsmExports {
     }
 }
 
+/// Returns the getter expression for a re-exported binding and the import of
+/// the module it reads the binding from.
+///
+/// When [EcmascriptChunkingContext::flatten_reexports] is enabled and the
+/// binding is re-exported by further modules, the getter reads it from the
+/// module declaring it, instead of calling the getters of all modules in
+/// between. This is only done when the re-exporting module isn't an async
+/// module, as the declaring module is imported without waiting for it.
+async fn reexport_getter(
+    esm_ref: Vc<Box<dyn ModuleReference>>,
+    export_name: Option<&str>,
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    async_module_info: Option<Vc<AsyncModuleInfo>>,
+) -> Result<Option<(Expr, Option<Stmt>)>> {
+    let referenced_asset =
+        ReferencedAsset::from_resolve_result(esm_ref.resolve_reference()).await?;
+    if let (ReferencedAsset::Some(asset), None, true) = (
+        &*referenced_asset,
+        async_module_info,
+        *chunking_context.flatten_reexports().await?,
+    ) {
+        let chain = follow_reexport_chain(*asset, export_name.map(|n| n.to_string())).await?;
+        if !chain.links.is_empty() {
+            let ident = ReferencedAsset::get_ident_from_placeable(&chain.target).await?;
+            let id = chain
+                .target
+                .as_chunk_item(Vc::upcast(chunking_context))
+                .id()
+                .await?;
+            let import = quote!(
+                "var $name = __turbopack_import__($id);" as Stmt,
+                name = Ident::new(ident.clone().into(), DUMMY_SP),
+                id: Expr = module_id_to_lit(&id)
+            );
+            return Ok(Some((
+                binding_getter(ident, chain.export_name.as_deref()),
+                Some(import),
+            )));
+        }
+    }
+    Ok(referenced_asset
+        .get_ident()
+        .await?
+        .map(|ident| (binding_getter(ident, export_name), None)))
+}
+
+fn binding_getter(ident: String, export_name: Option<&str>) -> Expr {
+    match export_name {
+        Some(name) => quote!(
+            "(() => $expr)" as Expr,
+            expr: Expr = Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(Ident::new(ident.into(), DUMMY_SP))),
+                prop: MemberProp::Computed(ComputedPropName {
+                    span: DUMMY_SP,
+                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: name.into(),
+                        raw: None,
+                    })))
+                })
+            })
+        ),
+        None => quote!(
+            "(() => $imported)" as Expr,
+            imported = Ident::new(ident.into(), DUMMY_SP)
+        ),
+    }
+}
+
 #[turbo_tasks::value_impl]
-impl CodeGenerateable for EsmExports {
+impl CodeGenerateableWithAsyncModuleInfo for EsmExports {
     #[turbo_tasks::function]
     async fn code_generation(
         self: Vc<Self>,
-        _context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+        async_module_info: Option<Vc<AsyncModuleInfo>>,
     ) -> Result<Vc<CodeGeneration>> {
         let mut visitors = Vec::new();
 
@@ -407,6 +601,7 @@ impl CodeGenerateable for EsmExports {
         }
 
         let mut props = Vec::new();
+        let mut imports = Vec::new();
         for (exported, local) in &expanded.exports {
             let expr = match local {
                 EsmExport::Error => Some(quote!(
@@ -416,36 +611,24 @@ impl CodeGenerateable for EsmExports {
                     "(() => $local)" as Expr,
                     local = Ident::new((name as &str).into(), DUMMY_SP)
                 )),
-                EsmExport::ImportedBinding(esm_ref, name) => {
-                    let referenced_asset =
-                        ReferencedAsset::from_resolve_result(esm_ref.resolve_reference()).await?;
-                    referenced_asset.get_ident().await?.map(|ident| {
-                        quote!(
-                            "(() => $expr)" as Expr,
-                            expr: Expr = Expr::Member(MemberExpr {
-                                span: DUMMY_SP,
-                                obj: Box::new(Expr::Ident(Ident::new(ident.into(), DUMMY_SP))),
-                                prop: MemberProp::Computed(ComputedPropName {
-                                    span: DUMMY_SP,
-                                    expr: Box::new(Expr::Lit(Lit::Str(Str {
-                                        span: DUMMY_SP,
-                                        value: (name as &str).into(),
-                                        raw: None,
-                                    })))
-                                })
-                            })
-                        )
-                    })
-                }
+                EsmExport::ImportedBinding(esm_ref, name) => reexport_getter(
+                    *esm_ref,
+                    Some(name.as_str()),
+                    chunking_context,
+                    async_module_info,
+                )
+                .await?
+                .map(|(expr, import)| {
+                    imports.extend(import);
+                    expr
+                }),
                 EsmExport::ImportedNamespace(esm_ref) => {
-                    let referenced_asset =
-                        ReferencedAsset::from_resolve_result(esm_ref.resolve_reference()).await?;
-                    referenced_asset.get_ident().await?.map(|ident| {
-                        quote!(
-                            "(() => $imported)" as Expr,
-                            imported = Ident::new(ident.into(), DUMMY_SP)
-                        )
-                    })
+                    reexport_getter(*esm_ref, None, chunking_context, async_module_info)
+                        .await?
+                        .map(|(expr, import)| {
+                            imports.extend(import);
+                            expr
+                        })
                 }
             };
             if let Some(expr) = expr {
@@ -484,6 +667,11 @@ impl CodeGenerateable for EsmExports {
                     body.insert(0, stmt);
                 }
             }
+            // The imports of flattened re-exports are inserted after all other
+            // imports, so the modules are still evaluated in the same order.
+            for import in imports.iter() {
+                insert_hoisted_stmt(program, import.clone());
+            }
             if let Some(dynamic_stmt) = dynamic_stmt.clone() {
                 insert_hoisted_stmt(program, dynamic_stmt);
             }
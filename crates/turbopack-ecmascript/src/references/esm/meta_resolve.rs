@@ -0,0 +1,229 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{CallExpr, Callee, Expr, KeyValueProp, ObjectLit, Prop, PropName, PropOrSpread},
+    quote, quote_expr,
+};
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbopack_core::{
+    chunk::{
+        ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingType, ChunkingTypeOption,
+    },
+    environment::Rendering,
+    issue::IssueSource,
+    reference::ModuleReference,
+    reference_type::{ReferenceType, UrlReferenceSubType},
+    resolve::{
+        origin::ResolveOrigin,
+        parse::Request,
+        pattern::{read_matches, Pattern, PatternMatch},
+        url_resolve, ModuleResolveResult,
+    },
+};
+
+use super::base::ReferencedAsset;
+use crate::{
+    chunk::EcmascriptChunkingContext,
+    code_gen::{CodeGenerateable, CodeGeneration},
+    create_visitor,
+    references::AstPath,
+    resolve::try_to_severity,
+    utils::module_id_to_lit,
+};
+
+/// The resolve results of the requests an `import.meta.resolve(...)` call can
+/// resolve, keyed by the request.
+#[turbo_tasks::value(transparent)]
+pub struct ImportMetaResolveMap(IndexMap<String, Vc<ModuleResolveResult>>);
+
+/// A reference for `import.meta.resolve(...)`, which is resolved at build
+/// time.
+///
+/// Resolved files are emitted like the files referenced by
+/// `new URL(..., import.meta.url)`, and the call is replaced with the URL of
+/// the emitted file. When the argument has a dynamic part, e.g.
+/// `import.meta.resolve(`./locales/${lang}.json`)`, all matching files are
+/// emitted and the URL is looked up in a map at runtime.
+#[turbo_tasks::value]
+pub struct ImportMetaResolveAssetReference {
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    pattern: Vc<Pattern>,
+    rendering: Vc<Rendering>,
+    ast_path: Vc<AstPath>,
+    issue_source: Vc<IssueSource>,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        pattern: Vc<Pattern>,
+        rendering: Vc<Rendering>,
+        ast_path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+    ) -> Vc<Self> {
+        ImportMetaResolveAssetReference {
+            origin,
+            pattern,
+            rendering,
+            ast_path,
+            issue_source,
+            in_try,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn map(&self) -> Result<Vc<ImportMetaResolveMap>> {
+        let requests = if let Pattern::Constant(request) = &*self.pattern.await? {
+            vec![request.clone()]
+        } else {
+            read_matches(
+                self.origin.origin_path().parent(),
+                "".to_string(),
+                false,
+                self.pattern,
+            )
+            .await?
+            .iter()
+            .filter_map(|m| match m {
+                PatternMatch::File(request, _) => Some(request.clone()),
+                PatternMatch::Directory(..) => None,
+            })
+            .collect()
+        };
+
+        let mut map = IndexMap::new();
+        for request in requests {
+            let result = url_resolve(
+                self.origin,
+                Request::parse(Value::new(request.clone().into())),
+                Value::new(ReferenceType::Url(UrlReferenceSubType::ImportMetaResolve)),
+                Some(self.issue_source),
+                try_to_severity(self.in_try),
+            );
+            map.insert(request, result);
+        }
+        map.sort_keys();
+
+        Ok(Vc::cell(map))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(self: Vc<Self>) -> Result<Vc<ModuleResolveResult>> {
+        let results = self.map().await?.values().copied().collect::<Vec<_>>();
+        if results.is_empty() {
+            return Ok(ModuleResolveResult::unresolveable().cell());
+        }
+        Ok(ModuleResolveResult::alternatives(results))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "import.meta.resolve({})",
+            self.pattern.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Parallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ImportMetaResolveAssetReference {
+    /// Replaces `import.meta.resolve(...)` with the URL of the emitted file.
+    /// In the browser this is resolved against the location of the page,
+    /// otherwise it's the `file://` URL of the emitted file.
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+        let client = matches!(*this.rendering.await?, Rendering::Client);
+
+        let mut urls = Vec::new();
+        for (request, &result) in self.map().await?.iter() {
+            let url = match &*ReferencedAsset::from_resolve_result(result).await? {
+                ReferencedAsset::Some(asset) => {
+                    let id = asset
+                        .as_chunk_item(Vc::upcast(chunking_context))
+                        .id()
+                        .await?;
+                    if client {
+                        quote!(
+                            "new URL(__turbopack_require__($id), location.href).href" as Expr,
+                            id: Expr = module_id_to_lit(&id),
+                        )
+                    } else {
+                        // The runtime resolves the absolute path of the file,
+                        // which is turned into a `file://` URL.
+                        quote!(
+                            "new URL(__turbopack_resolve_module_id_path__($id), \"file://\").href" as Expr,
+                            id: Expr = module_id_to_lit(&id),
+                        )
+                    }
+                }
+                ReferencedAsset::OriginalReferenceTypeExternal(request) => request.as_str().into(),
+                ReferencedAsset::None => continue,
+            };
+            urls.push((request.clone(), url));
+        }
+
+        let ast_path = this.ast_path.await?;
+        let visitor = if matches!(*this.pattern.await?, Pattern::Constant(_)) && urls.len() == 1 {
+            let (_, url) = urls.into_iter().next().unwrap();
+            create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+                *expr = url.clone();
+            })
+        } else {
+            // Requests that don't match any file throw like an unresolvable
+            // `import.meta.resolve(...)`.
+            let map = ObjectLit {
+                span: DUMMY_SP,
+                props: urls
+                    .into_iter()
+                    .map(|(request, url)| {
+                        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                            key: PropName::Str(request.as_str().into()),
+                            value: Box::new(url),
+                        })))
+                    })
+                    .collect(),
+            };
+            let resolve = quote_expr!(
+                "((map) => (request) => {
+                    if (!Object.prototype.hasOwnProperty.call(map, request)) {
+                        throw new TypeError(\"Cannot resolve '\" + request + \"'\");
+                    }
+                    return map[request];
+                })($map)",
+                map: Expr = Expr::Object(map),
+            );
+            create_visitor!(exact ast_path, visit_mut_call_expr(call_expr: &mut CallExpr) {
+                call_expr.callee = Callee::Expr(resolve.clone());
+            })
+        };
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
@@ -3,6 +3,7 @@ pub(crate) mod binding;
 pub(crate) mod dynamic;
 pub(crate) mod export;
 pub(crate) mod meta;
+pub(crate) mod meta_resolve;
 pub(crate) mod module_id;
 pub(crate) mod module_item;
 pub(crate) mod url;
@@ -11,8 +12,9 @@ pub use self::{
     base::EsmAssetReference,
     binding::EsmBinding,
     dynamic::EsmAsyncAssetReference,
-    export::{EsmExport, EsmExports},
+    export::{follow_reexport_chain, reexport_chains_report, EsmExport, EsmExports, ReexportChain},
     meta::{ImportMetaBinding, ImportMetaRef},
+    meta_resolve::ImportMetaResolveAssetReference,
     module_item::EsmModuleItem,
     url::{UrlAssetReference, UrlRewriteBehavior},
 };
@@ -0,0 +1,511 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::{
+        ast::{
+            Callee, Expr, ExprStmt, KeyValueProp, ModuleItem, ObjectLit, Prop, PropName,
+            PropOrSpread, Stmt, {self},
+        },
+        codegen::{text_writer::JsWriter, Emitter},
+    },
+    quote_expr,
+};
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        ChunkItem, ChunkItemExt, ChunkType, ChunkableModule, ChunkableModuleReference,
+        ChunkingContext, ChunkingType, ChunkingTypeOption,
+    },
+    environment::ChunkLoading,
+    ident::AssetIdent,
+    issue::{analyze::AnalyzeIssue, IssueExt, IssueSeverity, IssueSource, StyledString},
+    module::Module,
+    reference::{ModuleReference, ModuleReferences},
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{
+        origin::ResolveOrigin,
+        parse::Request,
+        pattern::{read_matches, Pattern, PatternMatch},
+        ModuleResolveResult,
+    },
+    source::Source,
+};
+
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkType,
+        EcmascriptChunkingContext, EcmascriptExports,
+    },
+    code_gen::CodeGeneration,
+    create_visitor, errors,
+    references::{
+        pattern_mapping::{PatternMapping, ResolveType},
+        AstPath,
+    },
+    resolve::esm_resolve,
+    utils::module_id_to_lit,
+    CodeGenerateable, EcmascriptChunkPlaceable,
+};
+
+/// More files matching an `import()` expression usually means that the
+/// expression matches more than intended.
+const MAX_CONTEXT_FILES: usize = 1000;
+
+#[turbo_tasks::value]
+#[derive(Debug)]
+pub struct ImportContextMapEntry {
+    pub request: Vc<Request>,
+    pub result: Vc<ModuleResolveResult>,
+}
+
+/// The resolved context map for an `import(..)` call with a dynamic part,
+/// e.g. `import(`./locales/${lang}.json`)`. The keys are the requests the
+/// expression can evaluate to.
+#[turbo_tasks::value(transparent)]
+pub struct ImportContextMap(IndexMap<String, ImportContextMapEntry>);
+
+#[turbo_tasks::value_impl]
+impl ImportContextMap {
+    #[turbo_tasks::function]
+    pub(crate) async fn generate(
+        source: Vc<Box<dyn Source>>,
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        pattern: Vc<Pattern>,
+        issue_source: Vc<IssueSource>,
+        issue_severity: Vc<IssueSeverity>,
+    ) -> Result<Vc<Self>> {
+        let matches = read_matches(
+            origin.origin_path().parent(),
+            "".to_string(),
+            false,
+            pattern,
+        )
+        .await?;
+
+        let mut map = IndexMap::new();
+
+        for m in matches.iter() {
+            if let PatternMatch::File(request, _) = m {
+                let request_vc = Request::parse(Value::new(request.clone().into()));
+                let result = esm_resolve(
+                    origin,
+                    request_vc,
+                    Value::new(EcmaScriptModulesReferenceSubType::DynamicImport),
+                    issue_severity,
+                    Some(issue_source),
+                );
+                map.insert(
+                    request.clone(),
+                    ImportContextMapEntry {
+                        request: request_vc,
+                        result,
+                    },
+                );
+            }
+        }
+
+        map.sort_keys();
+
+        let message = if map.is_empty() {
+            Some(format!(
+                "import({}) doesn't match any file. It will fail for every value at runtime.",
+                pattern.to_string().await?
+            ))
+        } else if map.len() > MAX_CONTEXT_FILES {
+            Some(format!(
+                "import({}) matches {} files, which are all placed into separate chunks. Make the \
+                 expression more specific, e.g. by adding a file extension.",
+                pattern.to_string().await?,
+                map.len()
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            AnalyzeIssue {
+                severity: IssueSeverity::Warning.into(),
+                source_ident: source.ident(),
+                title: Vc::cell("dynamic import context".to_string()),
+                message: StyledString::Text(message).cell(),
+                category: Vc::cell("analyze".to_string()),
+                code: Some(errors::failed_to_analyse::ecmascript::IMPORT_CONTEXT.to_string()),
+                source: Some(issue_source),
+            }
+            .cell()
+            .emit();
+        }
+
+        Ok(Vc::cell(map))
+    }
+}
+
+/// A reference for an `import()` with a dynamic part. The call is replaced
+/// with a call to the dispatcher exported by the [ImportContextAsset], which
+/// loads the matching module from its own async chunk.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ImportContextAssetReference {
+    pub inner: Vc<ImportContextAsset>,
+    pub pattern: Vc<Pattern>,
+    pub path: Vc<AstPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportContextAssetReference {
+    #[turbo_tasks::function]
+    pub fn new(
+        source: Vc<Box<dyn Source>>,
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        pattern: Vc<Pattern>,
+        path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        issue_severity: Vc<IssueSeverity>,
+    ) -> Vc<Self> {
+        let map = ImportContextMap::generate(source, origin, pattern, issue_source, issue_severity);
+        let inner = ImportContextAsset {
+            source,
+            origin,
+            map,
+            pattern,
+        }
+        .cell();
+
+        Self::cell(ImportContextAssetReference {
+            inner,
+            pattern,
+            path,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for ImportContextAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        ModuleResolveResult::module(Vc::upcast(self.inner)).cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportContextAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "dynamic import context {}",
+            self.pattern.to_string().await?
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportContextAssetReference {}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ImportContextAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let chunk_item = self.inner.as_chunk_item(Vc::upcast(chunking_context));
+        let module_id = chunk_item.id().await?.clone_value();
+
+        let path = &self.path.await?;
+        let visitor = create_visitor!(exact path, visit_mut_call_expr(call_expr: &mut CallExpr) {
+            call_expr.callee = Callee::Expr(quote_expr!(
+                "__turbopack_require__($id)",
+                id: Expr = module_id_to_lit(&module_id)
+            ));
+        });
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
+
+/// A reference from the [ImportContextAsset] to one of the matching modules,
+/// which places the module in its own async chunk group.
+#[turbo_tasks::value(transparent)]
+pub struct AsyncResolvedModuleReference(Vc<ModuleResolveResult>);
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for AsyncResolvedModuleReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        self.0
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for AsyncResolvedModuleReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell("resolved async reference".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for AsyncResolvedModuleReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Async))
+    }
+}
+
+#[turbo_tasks::value]
+pub struct ImportContextAsset {
+    source: Vc<Box<dyn Source>>,
+
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    map: Vc<ImportContextMap>,
+
+    pattern: Vc<Pattern>,
+}
+
+#[turbo_tasks::function]
+async fn modifier(pattern: Vc<Pattern>) -> Result<Vc<String>> {
+    Ok(Vc::cell(format!(
+        "import context {}",
+        pattern.to_string().await?
+    )))
+}
+
+#[turbo_tasks::value_impl]
+impl Module for ImportContextAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident().with_modifier(modifier(self.pattern))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+        let map = &*self.map.await?;
+
+        Ok(Vc::cell(
+            map.iter()
+                .map(|(_, entry)| {
+                    Vc::upcast(Vc::<AsyncResolvedModuleReference>::cell(entry.result))
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for ImportContextAsset {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("// Chunking only content".to_string()).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for ImportContextAsset {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn turbopack_core::chunk::ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use \
+                     ImportContextAsset",
+                )?;
+        let this = self.await?;
+        Ok(Vc::upcast(
+            ImportContextChunkItem {
+                chunking_context,
+                inner: self,
+
+                origin: this.origin,
+                map: this.map,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for ImportContextAsset {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+pub struct ImportContextChunkItem {
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    inner: Vc<ImportContextAsset>,
+
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    map: Vc<ImportContextMap>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for ImportContextChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let map = &*self.map.await?;
+
+        let resolve_type = if matches!(
+            *self.chunking_context.environment().chunk_loading().await?,
+            ChunkLoading::None
+        ) {
+            ResolveType::ChunkItem
+        } else {
+            ResolveType::AsyncChunkLoader
+        };
+
+        let mut context_map = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+
+        for (key, entry) in map {
+            let pm = PatternMapping::resolve_request(
+                entry.request,
+                self.origin,
+                Vc::upcast(self.chunking_context),
+                entry.result,
+                Value::new(resolve_type),
+            )
+            .await?;
+
+            let value = match &*pm {
+                PatternMapping::SingleLoader(_) => quote_expr!(
+                    "() => __turbopack_require__($id)(__turbopack_import__)",
+                    id: Expr = pm.create()
+                ),
+                _ => quote_expr!(
+                    "() => Promise.resolve().then(() => __turbopack_import__($id))",
+                    id: Expr = pm.create()
+                ),
+            };
+
+            let prop = KeyValueProp {
+                key: PropName::Str(key.as_str().into()),
+                value,
+            };
+
+            context_map
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(prop))));
+        }
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: import_context_code(context_map)?.into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+/// The code of the module that exports the dispatcher of an import context,
+/// given the object literal that maps the keys to functions that import the
+/// matching module. The dispatcher rejects like a failed `import()` for keys
+/// that don't match any file.
+fn import_context_code(context_map: ObjectLit) -> Result<Vec<u8>> {
+    let expr = quote_expr!(
+            "__turbopack_export_value__(((map) => {
+                const importContext = (key) => {
+                    if (!Object.prototype.hasOwnProperty.call(map, key)) {
+                        const e = new Error(\"Cannot find module '\" + key + \"'\");
+                        e.code = \"MODULE_NOT_FOUND\";
+                        return Promise.reject(e);
+                    }
+                    return map[key]();
+                };
+                importContext.keys = () => Object.keys(map);
+                return importContext;
+            })($obj));",
+        obj: Expr = Expr::Object(context_map),
+    );
+
+    let module = ast::Module {
+        span: DUMMY_SP,
+        body: vec![ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr,
+        }))],
+        shebang: None,
+    };
+
+    let source_map: Arc<swc_core::common::SourceMap> = Default::default();
+    let mut bytes: Vec<u8> = vec![];
+    let mut emitter = Emitter {
+        cfg: swc_core::ecma::codegen::Config::default(),
+        cm: source_map.clone(),
+        comments: None,
+        wr: JsWriter::new(source_map, "\n", &mut bytes, None),
+    };
+
+    emitter.emit_module(&module)?;
+
+    Ok(bytes)
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for ImportContextChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        self.inner.references()
+    }
+
+    #[turbo_tasks::function]
+    async fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_context_code_maps_keys_to_importers() {
+        let context_map = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Str("./en.json".into()),
+                value: quote_expr!("() => __turbopack_import__(\"en\")"),
+            })))],
+        };
+        let code = String::from_utf8(import_context_code(context_map).unwrap()).unwrap();
+        assert!(code.starts_with("__turbopack_export_value__("));
+        assert!(code.contains("\"./en.json\": ()=>__turbopack_import__(\"en\")"));
+        assert!(code.contains("importContext.keys = ()=>Object.keys(map)"));
+        assert!(code.contains("MODULE_NOT_FOUND"));
+    }
+}
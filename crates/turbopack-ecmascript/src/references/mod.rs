@@ -4,6 +4,7 @@ pub mod cjs;
 pub mod constant_condition;
 pub mod constant_value;
 pub mod esm;
+pub mod import_context;
 pub mod node;
 pub mod pattern_mapping;
 pub mod raw;
@@ -12,6 +13,8 @@ pub mod type_issue;
 pub mod typescript;
 pub mod unreachable;
 pub mod util;
+pub mod worker;
+pub mod worklet;
 
 use std::{
     borrow::Cow,
@@ -37,7 +40,7 @@ use swc_core::{
         errors::{DiagnosticId, Handler, HANDLER},
         pass::AstNodePath,
         source_map::Pos,
-        Globals, Span, Spanned, GLOBALS,
+        BytePos, Globals, Span, Spanned, GLOBALS,
     },
     ecma::{
         ast::*,
@@ -78,11 +81,13 @@ use self::{
     cjs::CjsAssetReference,
     esm::{
         export::EsmExport, EsmAssetReference, EsmAsyncAssetReference, EsmExports, EsmModuleItem,
-        ImportMetaBinding, ImportMetaRef, UrlAssetReference,
+        ImportMetaBinding, ImportMetaRef, ImportMetaResolveAssetReference, UrlAssetReference,
     },
     node::DirAssetReference,
     raw::FileSourceReference,
     typescript::{TsConfigReference, TsReferencePathAssetReference, TsReferenceTypeAssetReference},
+    worker::WorkerAssetReference,
+    worklet::WorkletAssetReference,
 };
 use super::{
     analyzer::{
@@ -121,11 +126,13 @@ use crate::{
         async_module::{AsyncModule, OptionAsyncModule},
         cjs::{CjsRequireAssetReference, CjsRequireCacheAccess, CjsRequireResolveAssetReference},
         esm::{module_id::EsmModuleIdAssetReference, EsmBinding, UrlRewriteBehavior},
+        import_context::ImportContextAssetReference,
         node::PackageJsonReference,
         require_context::{RequireContextAssetReference, RequireContextMap},
         type_issue::SpecifiedModuleTypeIssue,
     },
     resolve::try_to_severity,
+    swc_comments::ImmutableComments,
     tree_shake::{part_of_module, split},
     typescript::resolve::tsconfig,
     EcmascriptInputTransforms, EcmascriptModuleAsset, SpecifiedModuleType, TreeShakingMode,
@@ -338,6 +345,9 @@ struct AnalysisState<'a> {
     first_import_meta: bool,
     tree_shaking_mode: Option<TreeShakingMode>,
     import_externals: bool,
+    /// The `turbopackChunkName` magic comments of the module, see
+    /// [chunk_name_comments].
+    chunk_names: &'a [(BytePos, String)],
 }
 
 impl<'a> AnalysisState<'a> {
@@ -367,8 +377,9 @@ pub(crate) async fn analyse_ecmascript_module(
     part: Option<Vc<ModulePart>>,
 ) -> Result<Vc<AnalyzeEcmascriptModuleResult>> {
     let span = {
-        let module = module.ident().to_string().await?;
-        tracing::info_span!("analyse ecmascript module", module = *module)
+        let ident = module.ident();
+        let (module, path) = (ident.to_string().await?, ident.path().to_string().await?);
+        tracing::info_span!("analyse ecmascript module", module = *module, path = *path)
     };
     analyse_ecmascript_module_internal(module, part)
         .instrument(span)
@@ -756,6 +767,7 @@ pub(crate) async fn analyse_ecmascript_module_internal(
 
     let effects = take(&mut var_graph.effects);
 
+    let chunk_names = chunk_name_comments(comments);
     let mut analysis_state = AnalysisState {
         handler: &handler,
         source,
@@ -766,6 +778,7 @@ pub(crate) async fn analyse_ecmascript_module_internal(
         first_import_meta: true,
         tree_shaking_mode: options.tree_shaking_mode,
         import_externals: options.import_externals,
+        chunk_names: &chunk_names,
     };
 
     enum Action {
@@ -1041,6 +1054,37 @@ pub(crate) async fn analyse_ecmascript_module_internal(
 
                 analysis.add_code_gen(ImportMetaRef::new(Vc::cell(ast_path)));
             }
+            Effect::ImportMetaResolve {
+                input,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let input = analysis_state.link_value(input, in_try).await?;
+                let pat = js_value_to_pattern(&input);
+                if !matches!(pat, Pattern::Constant(_)) && !is_relative_context_pattern(&pat) {
+                    handler.span_warn_with_code(
+                        span,
+                        &format!(
+                            "import.meta.resolve({input}) is too dynamic to be resolved at build \
+                             time. Only expressions starting with a relative directory can be \
+                             analyzed, e.g. import.meta.resolve(`./locales/${{lang}}.json`)"
+                        ),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::IMPORT_META_RESOLVE.to_string(),
+                        ),
+                    );
+                    continue;
+                }
+                analysis.add_reference(ImportMetaResolveAssetReference::new(
+                    origin,
+                    Pattern::new(pat),
+                    compile_time_info.environment().rendering(),
+                    Vc::cell(ast_path),
+                    IssueSource::from_swc_offsets(source, span.lo.to_usize(), span.hi.to_usize()),
+                    in_try,
+                ));
+            }
             Effect::Url {
                 input,
                 ast_path,
@@ -1070,6 +1114,56 @@ pub(crate) async fn analyse_ecmascript_module_internal(
                         .cell(),
                 ));
             }
+            Effect::Worklet {
+                input,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let pat = js_value_to_pattern(&input);
+                if !pat.has_constant_parts() {
+                    handler.span_warn_with_code(
+                        span,
+                        &format!("worklet new URL({input}, import.meta.url) is very dynamic"),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::NEW_URL_IMPORT_META.to_string(),
+                        ),
+                    )
+                }
+                analysis.add_reference(WorkletAssetReference::new(
+                    origin,
+                    Request::parse(Value::new(pat)),
+                    Vc::cell(ast_path),
+                    IssueSource::from_swc_offsets(source, span.lo.to_usize(), span.hi.to_usize()),
+                    in_try,
+                ));
+            }
+            Effect::Worker {
+                input,
+                ast_path,
+                span,
+                in_try,
+                shared,
+            } => {
+                let pat = js_value_to_pattern(&input);
+                if !pat.has_constant_parts() {
+                    handler.span_warn_with_code(
+                        span,
+                        &format!("worker new URL({input}, import.meta.url) is very dynamic"),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::NEW_URL_IMPORT_META.to_string(),
+                        ),
+                    )
+                }
+                analysis.add_reference(WorkerAssetReference::new(
+                    origin,
+                    Request::parse(Value::new(pat)),
+                    Vc::cell(ast_path),
+                    IssueSource::from_swc_offsets(source, span.lo.to_usize(), span.hi.to_usize()),
+                    in_try,
+                    shared,
+                ));
+            }
         }
     }
 
@@ -1123,6 +1217,7 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
         origin,
         source,
         compile_time_info,
+        chunk_names,
         ..
     } = state;
     fn explain_args(args: &[JsValue]) -> (String, String) {
@@ -1179,6 +1274,29 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                             errors::failed_to_analyse::ecmascript::DYNAMIC_IMPORT.to_string(),
                         ),
                     )
+                } else if is_relative_context_pattern(&pat) {
+                    analysis.add_reference(ImportContextAssetReference::new(
+                        source,
+                        origin,
+                        Pattern::new(pat),
+                        Vc::cell(ast_path.to_vec()),
+                        issue_source(source, span),
+                        try_to_severity(in_try),
+                    ));
+                    return Ok(());
+                } else if matches!(pat, Pattern::Concatenation(_)) {
+                    let (args, hints) = explain_args(&args);
+                    handler.span_warn_with_code(
+                        span,
+                        &format!(
+                            "import({args}) is too dynamic to create a context for it. Only \
+                             expressions starting with a relative directory can be analyzed, e.g. \
+                             import(`./locales/${{lang}}.json`){hints}",
+                        ),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::IMPORT_CONTEXT.to_string(),
+                        ),
+                    )
                 }
                 analysis.add_reference(EsmAsyncAssetReference::new(
                     origin,
@@ -1187,6 +1305,7 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                     issue_source(source, span),
                     in_try,
                     state.import_externals,
+                    chunk_name_from_comments(chunk_names, span),
                 ));
                 return Ok(());
             }
@@ -1895,10 +2014,57 @@ async fn handle_free_var_reference(
     Ok(true)
 }
 
+/// Whether an `import()` with a dynamic part can be turned into an import
+/// context, which requires the request to start with a relative directory.
+fn is_relative_context_pattern(pat: &Pattern) -> bool {
+    match pat {
+        Pattern::Concatenation(list) => matches!(
+            list.first(),
+            Some(Pattern::Constant(prefix)) if prefix.starts_with("./") || prefix.starts_with("../")
+        ),
+        _ => false,
+    }
+}
+
 fn issue_source(source: Vc<Box<dyn Source>>, span: Span) -> Vc<IssueSource> {
     IssueSource::from_swc_offsets(source, span.lo.to_usize(), span.hi.to_usize())
 }
 
+/// Collects the names of chunk groups from the magic comments of a module, e.g.
+/// `/* turbopackChunkName: "settings" */`, sorted by their position.
+/// `webpackChunkName` comments are supported too.
+fn chunk_name_comments(comments: &ImmutableComments) -> Vec<(BytePos, String)> {
+    lazy_static! {
+        static ref CHUNK_NAME: Regex =
+            Regex::new(r#"^\s*(?:turbopack|webpack)ChunkName\s*:\s*["']([^"']+)["']\s*$"#).unwrap();
+    }
+    let mut chunk_names = comments
+        .leading
+        .iter()
+        .flat_map(|(&pos, comments)| comments.iter().map(move |comment| (pos, comment)))
+        .filter(|(_, comment)| matches!(comment.kind, CommentKind::Block))
+        .filter_map(|(pos, comment)| {
+            CHUNK_NAME
+                .captures(&comment.text)
+                .map(|m| (pos, m[1].to_string()))
+        })
+        .collect::<Vec<_>>();
+    chunk_names.sort_by_key(|&(pos, _)| pos);
+    chunk_names
+}
+
+/// Reads the name of the chunk group from a magic comment within the `import()`
+/// call at `span`, e.g. `import(/* turbopackChunkName: "settings" */ "./x")`.
+/// `chunk_names` are the sorted magic comments of the module, see
+/// [chunk_name_comments].
+fn chunk_name_from_comments(chunk_names: &[(BytePos, String)], span: Span) -> Option<String> {
+    let start = chunk_names.partition_point(|&(pos, _)| pos <= span.lo);
+    chunk_names[start..]
+        .first()
+        .filter(|&&(pos, _)| pos < span.hi)
+        .map(|(_, name)| name.clone())
+}
+
 fn analyze_amd_define(
     source: Vc<Box<dyn Source>>,
     analysis: &mut AnalyzeEcmascriptModuleResultBuilder,
@@ -2867,3 +3033,67 @@ fn maybe_decode_data_url(url: String) -> Vc<OptionSourceMap> {
         Vc::cell(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use swc_core::common::{
+        comments::{Comment, CommentKind},
+        BytePos, Span, DUMMY_SP,
+    };
+
+    use super::{chunk_name_comments, chunk_name_from_comments};
+    use crate::swc_comments::ImmutableComments;
+
+    fn comment(kind: CommentKind, text: &str) -> Comment {
+        Comment {
+            kind,
+            span: DUMMY_SP,
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn chunk_names_are_found_within_the_import() {
+        let comments = ImmutableComments {
+            leading: HashMap::from([
+                (
+                    BytePos(10),
+                    vec![comment(CommentKind::Block, " turbopackChunkName: \"a\" ")],
+                ),
+                (
+                    BytePos(30),
+                    vec![
+                        comment(CommentKind::Line, " webpackChunkName: \"ignored\""),
+                        comment(CommentKind::Block, "webpackChunkName: 'b'"),
+                    ],
+                ),
+                (
+                    BytePos(50),
+                    vec![comment(CommentKind::Block, " not a name ")],
+                ),
+            ]),
+            trailing: HashMap::new(),
+        };
+        let chunk_names = chunk_name_comments(&comments);
+        assert_eq!(
+            chunk_names,
+            vec![
+                (BytePos(10), "a".to_string()),
+                (BytePos(30), "b".to_string())
+            ]
+        );
+
+        let import = |lo, hi| {
+            chunk_name_from_comments(
+                &chunk_names,
+                Span::new(BytePos(lo), BytePos(hi), Default::default()),
+            )
+        };
+        assert_eq!(import(5, 20), Some("a".to_string()));
+        assert_eq!(import(20, 40), Some("b".to_string()));
+        assert_eq!(import(10, 20), None);
+        assert_eq!(import(40, 60), None);
+    }
+}
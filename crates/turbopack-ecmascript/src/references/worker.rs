@@ -0,0 +1,206 @@
+use anyhow::Result;
+use swc_core::{
+    ecma::ast::{Expr, ExprOrSpread, KeyValueProp, Lit, NewExpr, Prop, PropName, PropOrSpread},
+    quote,
+};
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbopack_core::{
+    chunk::{
+        ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingType, ChunkingTypeOption,
+    },
+    issue::IssueSource,
+    reference::ModuleReference,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    resolve::{origin::ResolveOrigin, parse::Request, url_resolve, ModuleResolveResult},
+};
+
+use super::esm::base::ReferencedAsset;
+use crate::{
+    chunk::EcmascriptChunkingContext,
+    code_gen::{CodeGenerateable, CodeGeneration},
+    create_visitor,
+    references::AstPath,
+    resolve::try_to_severity,
+    utils::module_id_to_lit,
+    worker::WorkerModule,
+};
+
+/// A reference to the entry module of a web worker, injected during code
+/// analysis when we find `new Worker(new URL("path", import.meta.url))` or
+/// `new SharedWorker(new URL("path", import.meta.url))`.
+///
+/// The referenced module is the entry of its own worker chunk group and the
+/// `new URL(...)` is rewritten to the URL of the entry chunk of that group.
+#[turbo_tasks::value]
+pub struct WorkerAssetReference {
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    request: Vc<Request>,
+    ast_path: Vc<AstPath>,
+    issue_source: Vc<IssueSource>,
+    in_try: bool,
+    /// Whether the worker is a `SharedWorker`.
+    shared: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerAssetReference {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        ast_path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+        shared: bool,
+    ) -> Vc<Self> {
+        WorkerAssetReference {
+            origin,
+            request,
+            ast_path,
+            issue_source,
+            in_try,
+            shared,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        let result = url_resolve(
+            self.origin,
+            self.request,
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Worker)),
+            Some(self.issue_source),
+            try_to_severity(self.in_try),
+        );
+        let Some(module) = *result.first_module().await? else {
+            return Ok(result);
+        };
+        Ok(ModuleResolveResult::module(Vc::upcast(WorkerModule::new(module, self.shared))).cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkerAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "worker new URL({})",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for WorkerAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Parallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for WorkerAssetReference {
+    /// Rewrites `new URL(url, import.meta.url)` to a require() of the
+    /// [WorkerModule], which exports the URL of the worker's entry chunk, and
+    /// starts module workers as classic workers, see [use_classic_worker].
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+        let mut visitors = vec![];
+
+        let referenced_asset = ReferencedAsset::from_resolve_result(self.resolve_reference());
+        if let ReferencedAsset::Some(asset) = &*referenced_asset.await? {
+            let id = asset
+                .as_chunk_item(Vc::upcast(chunking_context))
+                .id()
+                .await?;
+            let ast_path = this.ast_path.await?;
+
+            visitors.push(create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+                *expr = quote!(
+                    "__turbopack_require__($id)" as Expr,
+                    id: Expr = module_id_to_lit(&id),
+                );
+            }));
+
+            // The path ends with the `new URL(...)` expression, the argument of
+            // the `new Worker(...)` expression and the argument list of it.
+            let worker_path = &ast_path[..ast_path.len() - 3];
+            visitors.push(
+                create_visitor!(worker_path, visit_mut_expr(expr: &mut Expr) {
+                    use_classic_worker(expr);
+                }),
+            );
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+/// Starts a module worker, e.g. `new Worker(url, { type: "module" })`, as a
+/// classic worker. The entry chunk of a worker is a classic script, which loads
+/// the other chunks with `importScripts`, and that's not available in module
+/// workers. Only options given as an object literal can be rewritten.
+fn use_classic_worker(expr: &mut Expr) {
+    let Expr::New(NewExpr {
+        args: Some(args), ..
+    }) = expr
+    else {
+        return;
+    };
+    let Some(ExprOrSpread {
+        spread: None,
+        expr: box Expr::Object(options),
+    }) = args.get_mut(1)
+    else {
+        return;
+    };
+    for prop in options.props.iter_mut() {
+        let PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp { key, value })) = prop else {
+            continue;
+        };
+        let is_type = match key {
+            PropName::Ident(ident) => &*ident.sym == "type",
+            PropName::Str(str) => &*str.value == "type",
+            _ => false,
+        };
+        if is_type {
+            *value = Box::new(Expr::Lit(Lit::Str("classic".into())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{ecma::ast::Expr, quote};
+
+    use super::use_classic_worker;
+
+    #[test]
+    fn starts_module_workers_as_classic_workers() {
+        let mut expr = quote!("new Worker(url, { type: \"module\", name: \"a\" })" as Expr);
+        use_classic_worker(&mut expr);
+        assert_eq!(
+            expr,
+            quote!("new Worker(url, { type: \"classic\", name: \"a\" })" as Expr)
+        );
+    }
+
+    #[test]
+    fn keeps_options_that_are_not_object_literals() {
+        let mut expr = quote!("new SharedWorker(url, \"name\")" as Expr);
+        use_classic_worker(&mut expr);
+        assert_eq!(expr, quote!("new SharedWorker(url, \"name\")" as Expr));
+
+        let mut expr = quote!("new Worker(url, options)" as Expr);
+        use_classic_worker(&mut expr);
+        assert_eq!(expr, quote!("new Worker(url, options)" as Expr));
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use swc_core::{ecma::ast::Expr, quote};
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbopack_core::{
+    chunk::{
+        ChunkItemExt, ChunkableModule, ChunkableModuleReference, ChunkingType, ChunkingTypeOption,
+    },
+    issue::IssueSource,
+    reference::ModuleReference,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    resolve::{origin::ResolveOrigin, parse::Request, url_resolve, ModuleResolveResult},
+};
+
+use super::esm::base::ReferencedAsset;
+use crate::{
+    chunk::EcmascriptChunkingContext,
+    code_gen::{CodeGenerateable, CodeGeneration},
+    create_visitor,
+    references::AstPath,
+    resolve::try_to_severity,
+    utils::module_id_to_lit,
+    worklet::WorkletModule,
+};
+
+/// A reference to the module of a worklet, injected during code analysis when
+/// we find `CSS.paintWorklet.addModule(new URL("path", import.meta.url))` or
+/// `audioWorklet.addModule(new URL("path", import.meta.url))`.
+///
+/// The referenced module is placed into its own worklet chunk and the
+/// `new URL(...)` is rewritten to the URL of that chunk.
+#[turbo_tasks::value]
+pub struct WorkletAssetReference {
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    request: Vc<Request>,
+    ast_path: Vc<AstPath>,
+    issue_source: Vc<IssueSource>,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkletAssetReference {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        ast_path: Vc<AstPath>,
+        issue_source: Vc<IssueSource>,
+        in_try: bool,
+    ) -> Vc<Self> {
+        WorkletAssetReference {
+            origin,
+            request,
+            ast_path,
+            issue_source,
+            in_try,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for WorkletAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<Vc<ModuleResolveResult>> {
+        let result = url_resolve(
+            self.origin,
+            self.request,
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Worklet)),
+            Some(self.issue_source),
+            try_to_severity(self.in_try),
+        );
+        let Some(module) = *result.first_module().await? else {
+            return Ok(result);
+        };
+        Ok(ModuleResolveResult::module(Vc::upcast(WorkletModule::new(module))).cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkletAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "worklet new URL({})",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for WorkletAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> Vc<ChunkingTypeOption> {
+        Vc::cell(Some(ChunkingType::Parallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for WorkletAssetReference {
+    /// Rewrites `new URL(url, import.meta.url)` to a require() of the
+    /// [WorkletModule], which exports the URL of the worklet chunk.
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    ) -> Result<Vc<CodeGeneration>> {
+        let this = self.await?;
+        let mut visitors = vec![];
+
+        let referenced_asset = ReferencedAsset::from_resolve_result(self.resolve_reference());
+        if let ReferencedAsset::Some(asset) = &*referenced_asset.await? {
+            let id = asset
+                .as_chunk_item(Vc::upcast(chunking_context))
+                .id()
+                .await?;
+            let ast_path = this.ast_path.await?;
+
+            visitors.push(create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+                *expr = quote!(
+                    "__turbopack_require__($id)" as Expr,
+                    id: Expr = module_id_to_lit(&id),
+                );
+            }));
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
@@ -82,7 +82,7 @@ impl EcmascriptChunkItem for EcmascriptModuleReexportsChunkItem {
             }
         }
 
-        code_gens.push(exports.code_generation(chunking_context));
+        code_gens.push(exports.code_generation(chunking_context, async_module_info));
         let code_gens = code_gens.into_iter().try_join().await?;
         let code_gens = code_gens.iter().map(|cg| &**cg).collect::<Vec<_>>();
 
@@ -0,0 +1,372 @@
+use anyhow::{bail, Context, Result};
+use indoc::formatdoc;
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        ChunkGroupResult, ChunkItem, ChunkType, ChunkableModule, ChunkingContext, EvaluatableAsset,
+        EvaluatableAssets,
+    },
+    ident::AssetIdent,
+    module::Module,
+    output::{OptionOutputAsset, OutputAsset},
+    reference::{all_modules, ModuleReferences, SingleModuleReference, SingleOutputAssetReference},
+};
+
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
+    },
+    references::{
+        esm::{EsmAsyncAssetReference, UrlAssetReference},
+        worker::WorkerAssetReference,
+    },
+    utils::StringifyJs,
+};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("worker".to_string())
+}
+
+#[turbo_tasks::function]
+fn shared_modifier() -> Vc<String> {
+    Vc::cell("shared worker".to_string())
+}
+
+/// A module that exports the URL of the entry chunk of a worker chunk group,
+/// which evaluates the [module] in a web worker. It replaces the module in
+/// `new Worker(new URL(..., import.meta.url))` and
+/// `new SharedWorker(new URL(..., import.meta.url))`.
+///
+/// Small workers are inlined and started from a blob URL instead, see
+/// [ChunkingContext::inline_worker_size_limit].
+///
+/// [module]: WorkerModule::module
+#[turbo_tasks::value(shared)]
+pub struct WorkerModule {
+    pub module: Vc<Box<dyn Module>>,
+    /// Whether the module is started with `new SharedWorker`.
+    pub shared: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerModule {
+    #[turbo_tasks::function]
+    pub fn new(module: Vc<Box<dyn Module>>, shared: bool) -> Vc<Self> {
+        WorkerModule { module, shared }.cell()
+    }
+}
+
+#[turbo_tasks::function]
+fn worker_module_description() -> Vc<String> {
+    Vc::cell("worker module".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Module for WorkerModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.module.ident().with_modifier(if self.shared {
+            shared_modifier()
+        } else {
+            modifier()
+        })
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        // The module is placed into the worker chunk group and not into the chunks
+        // of the importer, so it's not referenced as a chunkable reference.
+        Vc::cell(vec![Vc::upcast(SingleModuleReference::new(
+            self.module,
+            worker_module_description(),
+        ))])
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WorkerModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("// Chunking only content".to_string()).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for WorkerModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use WorkerModule",
+                )?;
+        Ok(Vc::upcast(
+            WorkerChunkItem {
+                chunking_context,
+                inner: self,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WorkerModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct WorkerChunkItem {
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    inner: Vc<WorkerModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerChunkItem {
+    #[turbo_tasks::function]
+    async fn evaluatable_assets(&self) -> Result<Vc<EvaluatableAssets>> {
+        let module = self.inner.await?.module;
+        let Some(evaluatable) =
+            Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(module).await?
+        else {
+            bail!(
+                "{} can't be loaded as a worker, as it can't be evaluated",
+                module.ident().to_string().await?
+            );
+        };
+        Ok(EvaluatableAssets::one(evaluatable))
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_group(self: Vc<Self>) -> Result<Vc<ChunkGroupResult>> {
+        let this = self.await?;
+        let module = this.inner.await?.module;
+        Ok(this
+            .chunking_context
+            .worker_chunk_group(module.ident(), self.evaluatable_assets()))
+    }
+
+    /// The standalone chunk the worker is inlined as, when it's small enough.
+    /// Shared workers aren't inlined, as a blob URL is unique to the page
+    /// that created it, so they couldn't be shared between pages. Neither are
+    /// workers that load other assets, see [loads_assets].
+    #[turbo_tasks::function]
+    async fn inline_chunk(self: Vc<Self>) -> Result<Vc<OptionOutputAsset>> {
+        let this = self.await?;
+        let Some(limit) = *this.chunking_context.inline_worker_size_limit().await? else {
+            return Ok(Vc::cell(None));
+        };
+        let inner = this.inner.await?;
+        if inner.shared || *loads_assets(inner.module).await? {
+            return Ok(Vc::cell(None));
+        }
+        let chunk = this
+            .chunking_context
+            .worklet_chunk(inner.module.ident(), self.evaluatable_assets());
+        let size = match &*chunk.content().await? {
+            AssetContent::File(file) => match &*file.await? {
+                FileContent::Content(file) => file.content().len() as u64,
+                FileContent::NotFound => 0,
+            },
+            AssetContent::Redirect { .. } => 0,
+        };
+        Ok(Vc::cell((size <= limit).then_some(chunk)))
+    }
+
+    /// The chunk the worker is started with, which loads the other chunks.
+    #[turbo_tasks::function]
+    async fn entry_chunk(self: Vc<Self>) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let assets = self.chunk_group().await?.assets.await?;
+        let Some(&entry_chunk) = assets.last() else {
+            bail!("the worker chunk group has no entry chunk");
+        };
+        Ok(entry_chunk)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for WorkerChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let this = self.await?;
+        if let Some(chunk) = *self.inline_chunk().await? {
+            let AssetContent::File(file) = &*chunk.content().await? else {
+                bail!("the inline worker chunk must be a file");
+            };
+            let FileContent::Content(file) = &*file.await? else {
+                bail!("the inline worker chunk must have content");
+            };
+            let code = file.content().to_str()?;
+            // The URL of the source map is relative to the chunk, but a blob or
+            // data URL can't be a base URL, so it's made absolute when the worker
+            // is started.
+            let code = match strip_source_mapping_url(&code) {
+                Some(code) => {
+                    let source_map_url = format!(
+                        "{}.map",
+                        this.chunking_context.asset_url(chunk.ident()).await?
+                    );
+                    format!(
+                        "{} + \"\\n//# sourceMappingURL=\" + new URL({}, location.href)",
+                        StringifyJs(code),
+                        StringifyJs(&source_map_url)
+                    )
+                }
+                None => StringifyJs(&*code).to_string(),
+            };
+            // Data URLs are the fallback for environments without blob URLs.
+            let code = formatdoc! {
+                r#"
+                    const code = {};
+                    __turbopack_export_value__(
+                        typeof Blob === "function" && typeof URL.createObjectURL === "function"
+                            ? URL.createObjectURL(new Blob([code], {{ type: "text/javascript" }}))
+                            : "data:text/javascript;charset=utf-8," + encodeURIComponent(code)
+                    );
+                "#,
+                code,
+            };
+            return Ok(EcmascriptChunkItemContent {
+                inner_code: code.into(),
+                ..Default::default()
+            }
+            .cell());
+        }
+        let url = this
+            .chunking_context
+            .asset_url(self.entry_chunk().ident())
+            .await?;
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!("__turbopack_export_value__({});\n", StringifyJs(&*url)).into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+/// Whether any module of the worker imports modules with `import()` or
+/// references assets or other workers by URL. Their URLs are relative to the
+/// chunks of the worker, which an inlined worker doesn't have.
+#[turbo_tasks::function]
+async fn loads_assets(module: Vc<Box<dyn Module>>) -> Result<Vc<bool>> {
+    for &module in all_modules(module).await?.iter() {
+        for &reference in module.references().await?.iter() {
+            if Vc::try_resolve_downcast_type::<EsmAsyncAssetReference>(reference)
+                .await?
+                .is_some()
+                || Vc::try_resolve_downcast_type::<UrlAssetReference>(reference)
+                    .await?
+                    .is_some()
+                || Vc::try_resolve_downcast_type::<WorkerAssetReference>(reference)
+                    .await?
+                    .is_some()
+            {
+                return Ok(Vc::cell(true));
+            }
+        }
+    }
+    Ok(Vc::cell(false))
+}
+
+/// Returns `code` without its trailing `sourceMappingURL` comment, if it has
+/// one.
+fn strip_source_mapping_url(code: &str) -> Option<&str> {
+    let index = code.rfind("\n//# sourceMappingURL=")?;
+    // The comment must be the last line of the code.
+    if code[index + 1..].trim_end().contains('\n') {
+        return None;
+    }
+    Some(&code[..index])
+}
+
+#[turbo_tasks::function]
+fn worker_chunk_reference_description() -> Vc<String> {
+    Vc::cell("worker chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for WorkerChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
+        // An inline worker is part of this chunk item, so only the assets it
+        // references (e.g. images) are emitted.
+        let assets = if let Some(chunk) = *self.inline_chunk().await? {
+            chunk.references()
+        } else {
+            self.chunk_group().await?.assets
+        };
+        Ok(Vc::cell(
+            assets
+                .await?
+                .iter()
+                .map(|&chunk| {
+                    Vc::upcast(SingleOutputAssetReference::new(
+                        chunk,
+                        worker_chunk_reference_description(),
+                    ))
+                })
+                .collect(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_source_mapping_url() {
+        assert_eq!(
+            strip_source_mapping_url("foo();\n\n//# sourceMappingURL=worker.js.map\n"),
+            Some("foo();\n")
+        );
+        assert_eq!(strip_source_mapping_url("foo();\n"), None);
+        // A comment followed by more code doesn't apply to the whole chunk.
+        assert_eq!(
+            strip_source_mapping_url("//# sourceMappingURL=a.js.map\nfoo();\n//# x\n"),
+            None
+        );
+        assert_eq!(
+            strip_source_mapping_url("a();\n//# sourceMappingURL=a.js.map\nb();\n"),
+            None
+        );
+    }
+}
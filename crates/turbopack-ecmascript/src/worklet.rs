@@ -0,0 +1,192 @@
+use anyhow::{bail, Context, Result};
+use turbo_tasks::{ValueToString, Vc};
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        ChunkItem, ChunkType, ChunkableModule, ChunkingContext, EvaluatableAsset, EvaluatableAssets,
+    },
+    ident::AssetIdent,
+    module::Module,
+    output::OutputAsset,
+    reference::{ModuleReferences, SingleModuleReference, SingleOutputAssetReference},
+};
+
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
+    },
+    utils::StringifyJs,
+};
+
+#[turbo_tasks::function]
+fn modifier() -> Vc<String> {
+    Vc::cell("worklet".to_string())
+}
+
+/// A module that exports the URL of a worklet chunk, which contains the
+/// [module] and everything it depends on. It replaces the module in
+/// `CSS.paintWorklet.addModule(new URL(..., import.meta.url))` and
+/// `audioWorklet.addModule(new URL(..., import.meta.url))`.
+///
+/// [module]: WorkletModule::module
+#[turbo_tasks::value(shared)]
+pub struct WorkletModule {
+    pub module: Vc<Box<dyn Module>>,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkletModule {
+    #[turbo_tasks::function]
+    pub fn new(module: Vc<Box<dyn Module>>) -> Vc<Self> {
+        WorkletModule { module }.cell()
+    }
+}
+
+#[turbo_tasks::function]
+fn worklet_module_description() -> Vc<String> {
+    Vc::cell("worklet module".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl Module for WorkletModule {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.module.ident().with_modifier(modifier())
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> Vc<ModuleReferences> {
+        // The module is placed into the worklet chunk and not into the chunks
+        // of the importer, so it's not referenced as a chunkable reference.
+        Vc::cell(vec![Vc::upcast(SingleModuleReference::new(
+            self.module,
+            worklet_module_description(),
+        ))])
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WorkletModule {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("// Chunking only content".to_string()).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for WorkletModule {
+    #[turbo_tasks::function]
+    async fn as_chunk_item(
+        self: Vc<Self>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Vc<Box<dyn ChunkItem>>> {
+        let chunking_context =
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkingContext>>(chunking_context)
+                .await?
+                .context(
+                    "chunking context must impl EcmascriptChunkingContext to use WorkletModule",
+                )?;
+        Ok(Vc::upcast(
+            WorkletChunkItem {
+                chunking_context,
+                inner: self,
+            }
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WorkletModule {
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> Vc<EcmascriptExports> {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct WorkletChunkItem {
+    chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    inner: Vc<WorkletModule>,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkletChunkItem {
+    #[turbo_tasks::function]
+    async fn worklet_chunk(&self) -> Result<Vc<Box<dyn OutputAsset>>> {
+        let module = self.inner.await?.module;
+        let Some(evaluatable) =
+            Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(module).await?
+        else {
+            bail!(
+                "{} can't be loaded as a worklet, as it can't be evaluated",
+                module.ident().to_string().await?
+            );
+        };
+        Ok(self
+            .chunking_context
+            .worklet_chunk(module.ident(), EvaluatableAssets::one(evaluatable)))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for WorkletChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn EcmascriptChunkingContext>> {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<EcmascriptChunkItemContent>> {
+        let this = self.await?;
+        let url = this
+            .chunking_context
+            .asset_url(self.worklet_chunk().ident())
+            .await?;
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!("__turbopack_export_value__({});\n", StringifyJs(&*url)).into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::function]
+fn worklet_chunk_reference_description() -> Vc<String> {
+    Vc::cell("worklet chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for WorkletChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> Vc<AssetIdent> {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    fn references(self: Vc<Self>) -> Vc<ModuleReferences> {
+        Vc::cell(vec![Vc::upcast(SingleOutputAssetReference::new(
+            self.worklet_chunk(),
+            worklet_chunk_reference_description(),
+        ))])
+    }
+
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> Vc<Box<dyn ChunkingContext>> {
+        Vc::upcast(self.chunking_context)
+    }
+
+    #[turbo_tasks::function]
+    async fn ty(&self) -> Result<Vc<Box<dyn ChunkType>>> {
+        Ok(Vc::upcast(
+            Vc::<EcmascriptChunkType>::default().resolve().await?,
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        Vc::upcast(self.inner)
+    }
+}
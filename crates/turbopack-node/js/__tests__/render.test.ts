@@ -0,0 +1,125 @@
+import type { RenderData, RenderResult } from "../src/ipc/render";
+
+const RENDER_DATA: RenderData = {
+  params: {},
+  method: "GET",
+  url: "/",
+  originalUrl: "/",
+  rawQuery: "",
+  rawHeaders: [],
+  path: "/",
+};
+
+const HEADERS: [string, string][] = [["content-type", "text/html"]];
+
+/**
+ * Runs the render loop of `render.ts` for one request with an IPC channel that
+ * records the messages it sends. Resolves once the loop waits for the next
+ * request.
+ */
+async function renderOnce(
+  render: () => Promise<RenderResult> | RenderResult
+): Promise<any[]> {
+  const sent: any[] = [];
+  const messages = [{ type: "headers", data: RENDER_DATA }];
+  let idle: () => void;
+  const done = new Promise<void>((resolve) => {
+    idle = resolve;
+  });
+
+  jest.resetModules();
+  jest.doMock("../src/ipc/index", () => ({
+    IPC: {
+      recv: () => {
+        const message = messages.shift();
+        if (message == null) {
+          idle();
+          return new Promise(() => {});
+        }
+        return Promise.resolve(message);
+      },
+      send: async (message: unknown) => {
+        sent.push(message);
+      },
+      sendError: async (error: Error) => {
+        sent.push({ type: "error", message: error.message });
+      },
+    },
+  }));
+  const { run } = require("../src/ipc/render");
+  run(render);
+
+  await done;
+  return sent;
+}
+
+/** The text of the `bodyChunk` messages of `sent`. */
+function bodyText(sent: any[]): string {
+  return Buffer.from(
+    sent
+      .filter((message) => message.type === "bodyChunk")
+      .flatMap((message) => message.data)
+  ).toString("utf8");
+}
+
+it("sends a complete response at once", async () => {
+  const response: RenderResult = {
+    type: "response",
+    statusCode: 200,
+    headers: HEADERS,
+    body: "<html></html>",
+  };
+
+  expect(await renderOnce(() => response)).toEqual([response]);
+});
+
+it("sends the chunks of a streamed body as they're produced", async () => {
+  async function* body() {
+    yield "<html>";
+    yield new Uint8Array(Buffer.from("</html>"));
+  }
+
+  const sent = await renderOnce(() => ({
+    type: "stream",
+    statusCode: 200,
+    headers: HEADERS,
+    body: body(),
+  }));
+
+  expect(sent).toEqual([
+    { type: "headers", data: { status: 200, headers: HEADERS } },
+    { type: "bodyChunk", data: Array.from(Buffer.from("<html>")) },
+    { type: "bodyChunk", data: Array.from(Buffer.from("</html>")) },
+    { type: "bodyEnd" },
+  ]);
+});
+
+it("pipes pipeable streams, like `renderToPipeableStream`", async () => {
+  const sent = await renderOnce(() => ({
+    type: "stream",
+    statusCode: 404,
+    headers: HEADERS,
+    body: {
+      pipe(destination) {
+        destination.write("<html>");
+        destination.end("</html>");
+        return destination;
+      },
+    },
+  }));
+
+  expect(sent[0]).toEqual({
+    type: "headers",
+    data: { status: 404, headers: HEADERS },
+  });
+  expect(bodyText(sent)).toBe("<html></html>");
+  expect(sent[sent.length - 1]).toEqual({ type: "bodyEnd" });
+});
+
+it("sends the errors of rendering", async () => {
+  const sent = await renderOnce(() => {
+    throw new Error("render failed");
+  });
+
+  expect(sent).toEqual([{ type: "error", message: "render failed" }]);
+});
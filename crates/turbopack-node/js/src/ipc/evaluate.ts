@@ -1,5 +1,8 @@
+import { AsyncLocalStorage } from "node:async_hooks";
 import { IPC, StructuredError } from "./index";
 import type { Ipc as GenericIpc } from "./index";
+import { permissionViolation } from "./permissions";
+import type { PermissionViolation } from "./permissions";
 
 type IpcIncomingMessage = {
   type: "evaluate";
@@ -32,11 +35,24 @@ type IpcOutgoingMessage =
       type: "emittedError";
       severity: "warning" | "error";
       error: StructuredError;
-    };
+    }
+  | ({
+      type: "permissionViolation";
+    } & PermissionViolation);
 
 export type Ipc = GenericIpc<IpcIncomingMessage, IpcOutgoingMessage>;
 const ipc = IPC as Ipc;
 
+/**
+ * The execution context storage shared with the Turbopack runtime, see
+ * `shared-node/node-execution-context.ts` in the runtime.
+ */
+const getExecutionContextStorage = (): AsyncLocalStorage<object> => {
+  const key = Symbol.for("turbopack.executionContext");
+  const global = globalThis as any;
+  return (global[key] ??= new AsyncLocalStorage());
+};
+
 export const run = async (
   getValue: (ipc: Ipc, ...deserializedArgs: any[]) => any
 ) => {
@@ -46,13 +62,21 @@ export const run = async (
     switch (msg.type) {
       case "evaluate": {
         try {
-          const value = await getValue(ipc, ...msg.args);
+          // Every evaluation gets its own execution context, so that request
+          // scoped data of concurrent evaluations doesn't mix.
+          const value = await getExecutionContextStorage().run({}, () =>
+            getValue(ipc, ...msg.args)
+          );
           await ipc.send({
             type: "end",
             data:
               value === undefined ? undefined : JSON.stringify(value, null, 2),
           });
         } catch (e) {
+          const violation = permissionViolation(e);
+          if (violation) {
+            await ipc.send({ type: "permissionViolation", ...violation });
+          }
           await ipc.sendError(e as Error);
         }
         break;
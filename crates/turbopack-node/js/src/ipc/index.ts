@@ -34,10 +34,17 @@ export type Ipc<TIncoming, TOutgoing> = {
   sendError(error: Error): Promise<never>;
 };
 
+/**
+ * Connects to turbopack at `address`, which is either a port on the loopback
+ * interface or the path of a Unix domain socket. The latter is used when the
+ * process has no network access.
+ */
 function createIpc<TIncoming, TOutgoing>(
-  port: number
+  address: string
 ): Ipc<TIncoming, TOutgoing> {
-  const socket = createConnection(port, "127.0.0.1");
+  const socket = /^\d+$/.test(address)
+    ? createConnection(parseInt(address, 10), "127.0.0.1")
+    : createConnection(address);
   const packetQueue: Buffer[] = [];
   const recvPromiseResolveQueue: Array<(message: TIncoming) => void> = [];
 
@@ -146,9 +153,9 @@ function createIpc<TIncoming, TOutgoing>(
   };
 }
 
-const PORT = process.argv[2];
+const ADDRESS = process.argv[2];
 
-export const IPC = createIpc<unknown, unknown>(parseInt(PORT, 10));
+export const IPC = createIpc<unknown, unknown>(ADDRESS);
 
 process.on("uncaughtException", (err) => {
   IPC.sendError(err);
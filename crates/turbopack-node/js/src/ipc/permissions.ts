@@ -0,0 +1,27 @@
+export type PermissionViolation = {
+  permission: string;
+  resource?: string;
+};
+
+const reportedErrors = new WeakSet<object>();
+
+/**
+ * Returns the violation when `error` has been caused by a denied access,
+ * i.e. by node.js' permission model. Every error is only returned once.
+ */
+export function permissionViolation(error: unknown): PermissionViolation | undefined {
+  if (
+    typeof error !== "object" ||
+    error === null ||
+    (error as any).code !== "ERR_ACCESS_DENIED" ||
+    reportedErrors.has(error)
+  ) {
+    return undefined;
+  }
+  reportedErrors.add(error);
+  const { permission, resource } = error as any;
+  return {
+    permission: String(permission),
+    resource: resource == null ? undefined : String(resource),
+  };
+}
@@ -0,0 +1,152 @@
+import { PassThrough } from "node:stream";
+import type { Writable } from "node:stream";
+import { IPC } from "./index";
+import type { Ipc as GenericIpc } from "./index";
+
+/**
+ * The data of the request to render, see `RenderData` in
+ * `turbopack-node/src/render/mod.rs`.
+ */
+export type RenderData = {
+  params: Record<string, string | string[]>;
+  method: string;
+  url: string;
+  originalUrl: string;
+  rawQuery: string;
+  rawHeaders: [string, string][];
+  path: string;
+  data?: any;
+};
+
+type IpcIncomingMessage = {
+  type: "headers";
+  data: RenderData;
+};
+
+type IpcOutgoingMessage =
+  | {
+      type: "response";
+      statusCode: number;
+      headers: [string, string][];
+      body: string;
+    }
+  | {
+      type: "headers";
+      data: {
+        status: number;
+        headers: [string, string][];
+      };
+    }
+  | {
+      type: "bodyChunk";
+      data: number[];
+    }
+  | {
+      type: "bodyEnd";
+    }
+  | {
+      type: "rewrite";
+      path: string;
+    };
+
+export type Ipc = GenericIpc<IpcIncomingMessage, IpcOutgoingMessage>;
+const ipc = IPC as Ipc;
+
+/**
+ * A stream that writes itself to a destination, like the result of React's
+ * `renderToPipeableStream`.
+ */
+export type PipeableStream = {
+  pipe<T extends Writable>(destination: T): T;
+};
+
+export type RenderResult =
+  | {
+      type: "response";
+      statusCode: number;
+      headers: [string, string][];
+      body: string;
+    }
+  | {
+      type: "rewrite";
+      path: string;
+    }
+  | {
+      /**
+       * The status code and headers are sent before the body, so they can't
+       * depend on its content. With `renderToPipeableStream`, return this
+       * result from `onShellReady`.
+       */
+      type: "stream";
+      statusCode: number;
+      headers: [string, string][];
+      body: PipeableStream | AsyncIterable<Uint8Array | string>;
+    };
+
+function isPipeable(
+  body: PipeableStream | AsyncIterable<Uint8Array | string>
+): body is PipeableStream {
+  return typeof (body as PipeableStream).pipe === "function";
+}
+
+async function sendBody(
+  body: PipeableStream | AsyncIterable<Uint8Array | string>
+) {
+  const chunks = isPipeable(body) ? body.pipe(new PassThrough()) : body;
+  for await (const chunk of chunks) {
+    // Every chunk is sent as soon as it's produced, so that the dev server can
+    // pass it on to the client while the rest of the page is still rendering.
+    await ipc.send({
+      type: "bodyChunk",
+      data: Array.from(Buffer.from(chunk)),
+    });
+  }
+  await ipc.send({ type: "bodyEnd" });
+}
+
+/**
+ * Runs the render loop of a node.js render entry, which is evaluated by
+ * `render_static` for every request.
+ */
+export const run = async (
+  render: (data: RenderData) => Promise<RenderResult> | RenderResult
+) => {
+  while (true) {
+    const msg = await ipc.recv();
+
+    switch (msg.type) {
+      case "headers": {
+        try {
+          const result = await render(msg.data);
+          switch (result.type) {
+            case "response":
+            case "rewrite": {
+              await ipc.send(result);
+              break;
+            }
+            case "stream": {
+              await ipc.send({
+                type: "headers",
+                data: {
+                  status: result.statusCode,
+                  headers: result.headers,
+                },
+              });
+              await sendBody(result.body);
+              break;
+            }
+          }
+        } catch (e) {
+          await ipc.sendError(e as Error);
+        }
+        break;
+      }
+      default: {
+        console.error("unexpected message type", msg.type);
+        process.exit(1);
+      }
+    }
+  }
+};
+
+export type { IpcIncomingMessage, IpcOutgoingMessage };
@@ -0,0 +1,71 @@
+// @ts-ignore
+import * as codegenModule from "CODEGEN";
+import { relative, isAbsolute, sep, resolve as pathResolve } from "path";
+import type { Ipc } from "../ipc/evaluate";
+
+type CodegenResult = {
+  code: string;
+  map?: string | object;
+  dependencies?: string[];
+  contextDependencies?: string[];
+};
+
+type CodegenFunction = (context: {
+  resourcePath: string;
+  rootContext: string;
+}) => CodegenResult | Promise<CodegenResult>;
+
+const contextDir = process.cwd();
+const toPath = (file: string) => {
+  const relPath = relative(contextDir, file);
+  if (isAbsolute(relPath)) {
+    throw new Error(
+      `Cannot depend on path (${file}) outside of root directory (${contextDir})`
+    );
+  }
+  return sep !== "/" ? relPath.replaceAll(sep, "/") : relPath;
+};
+
+const transform = async (ipc: Ipc, name: string) => {
+  const codegen: CodegenFunction =
+    (codegenModule as any).default ?? codegenModule;
+  if (typeof codegen !== "function") {
+    throw new Error(
+      `Codegen module ${name} must export a function (as default export or module.exports)`
+    );
+  }
+
+  const resourcePath = pathResolve(contextDir, name);
+  const result = await codegen({ resourcePath, rootContext: contextDir });
+  if (result == null || typeof result.code !== "string") {
+    throw new Error(
+      `Codegen module ${name} must return an object with a \`code\` string`
+    );
+  }
+
+  for (const dep of result.dependencies ?? []) {
+    ipc.send({
+      type: "fileDependency",
+      path: toPath(pathResolve(contextDir, dep)),
+    });
+  }
+  for (const dep of result.contextDependencies ?? []) {
+    ipc.send({
+      type: "dirDependency",
+      path: toPath(pathResolve(contextDir, dep)),
+      glob: "**",
+    });
+  }
+
+  return {
+    code: result.code,
+    map:
+      result.map == null
+        ? undefined
+        : typeof result.map === "string"
+        ? result.map
+        : JSON.stringify(result.map),
+  };
+};
+
+export { transform as default };
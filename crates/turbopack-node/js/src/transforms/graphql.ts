@@ -0,0 +1,165 @@
+// @ts-ignore
+import { parse } from "@vercel/turbopack/graphql";
+import type { Ipc } from "../ipc/evaluate";
+
+type Mode = "document" | "inlineTags";
+
+const IMPORT_REGEX = /^#\s*import\s+(?:"([^"]+)"|'([^']+)')\s*$/gm;
+
+/**
+ * Removes `loc` information from a parsed document, which would otherwise
+ * bloat the generated code and contain absolute offsets that change on every
+ * edit.
+ */
+function stripLoc(node: any): any {
+  if (Array.isArray(node)) {
+    return node.map(stripLoc);
+  }
+  if (node !== null && typeof node === "object") {
+    const result: Record<string, any> = {};
+    for (const [key, value] of Object.entries(node)) {
+      if (key === "loc") continue;
+      result[key] = stripLoc(value);
+    }
+    return result;
+  }
+  return node;
+}
+
+function compileDocument(source: string): string {
+  return JSON.stringify(stripLoc(parse(source, { noLocation: true })));
+}
+
+// Merges the definitions of fragment documents into a document, skipping
+// definitions that are already present (e.g. shared fragments).
+const MERGE_HELPER = `function __turbopack_graphql_merge__(doc, fragments) {
+  const names = new Set(doc.definitions.map((d) => d.name && d.name.value));
+  for (const fragment of fragments) {
+    for (const definition of fragment.definitions) {
+      const name = definition.name && definition.name.value;
+      if (name && names.has(name)) continue;
+      names.add(name);
+      doc.definitions.push(definition);
+    }
+  }
+  return doc;
+}
+`;
+
+function transformDocument(content: string): string {
+  const imports: string[] = [];
+  let match;
+  while ((match = IMPORT_REGEX.exec(content)) !== null) {
+    imports.push(match[1] ?? match[2]);
+  }
+
+  const doc = compileDocument(content);
+  let code = imports
+    .map((specifier, i) => `import __fragment_${i}__ from ${JSON.stringify(specifier)};\n`)
+    .join("");
+  code += MERGE_HELPER;
+  code += `const doc = __turbopack_graphql_merge__(${doc}, [${imports
+    .map((_, i) => `__fragment_${i}__`)
+    .join(", ")}]);\n`;
+
+  // Export every named operation separately, mirroring `graphql-tag/loader`.
+  const operations = JSON.parse(doc).definitions.filter(
+    (d: any) => d.kind === "OperationDefinition" && d.name
+  );
+  for (const operation of operations) {
+    const name = operation.name.value;
+    code += `export const ${name} = { ...doc, definitions: doc.definitions.filter((d) => d.kind !== "OperationDefinition" || (d.name && d.name.value === ${JSON.stringify(
+      name
+    )})) };\n`;
+  }
+  code += "export default doc;\n";
+  return code;
+}
+
+/**
+ * Finds the end of a template literal starting at `start` (the index after the
+ * opening backtick). Returns the quasis and expression sources, or `null` when
+ * the template can't be handled statically.
+ */
+function scanTemplate(
+  content: string,
+  start: number
+): { end: number; quasis: string[]; expressions: string[] } | null {
+  const quasis: string[] = [];
+  const expressions: string[] = [];
+  let current = "";
+  let i = start;
+  while (i < content.length) {
+    const char = content[i];
+    if (char === "\\") {
+      current += content.slice(i, i + 2);
+      i += 2;
+    } else if (char === "`") {
+      quasis.push(current);
+      return { end: i + 1, quasis, expressions };
+    } else if (char === "$" && content[i + 1] === "{") {
+      const close = content.indexOf("}", i + 2);
+      if (close === -1) return null;
+      const expression = content.slice(i + 2, close).trim();
+      // Only plain identifiers (fragment documents) are supported.
+      if (!/^[A-Za-z_$][\w$.]*$/.test(expression)) return null;
+      quasis.push(current);
+      expressions.push(expression);
+      current = "";
+      i = close + 1;
+    } else {
+      current += char;
+      i++;
+    }
+  }
+  return null;
+}
+
+function transformInlineTags(content: string, name: string): string {
+  const TAG_REGEX = /\b(?:gql|graphql)`/g;
+  let result = "";
+  let last = 0;
+  let replaced = false;
+  let match;
+  while ((match = TAG_REGEX.exec(content)) !== null) {
+    const template = scanTemplate(content, match.index + match[0].length);
+    if (template === null) {
+      continue;
+    }
+    let doc;
+    try {
+      doc = compileDocument(template.quasis.join(""));
+    } catch (e) {
+      throw new Error(
+        `Failed to precompile GraphQL tag in ${name}: ${(e as Error).message}`
+      );
+    }
+    result += content.slice(last, match.index);
+    result += `__turbopack_graphql_merge__(${doc}, [${template.expressions.join(
+      ", "
+    )}])`;
+    last = template.end;
+    TAG_REGEX.lastIndex = template.end;
+    replaced = true;
+  }
+  if (!replaced) {
+    return content;
+  }
+  result += content.slice(last);
+  return `${result}\n${MERGE_HELPER}`;
+}
+
+const transform = async (
+  _ipc: Ipc,
+  content: string,
+  name: string,
+  mode: Mode
+) => {
+  const code =
+    mode === "document"
+      ? transformDocument(content)
+      : transformInlineTags(content, name);
+  return { code };
+};
+
+export { transform as default };
@@ -35,6 +35,7 @@ use crate::{
     bootstrap::NodeJsBootstrapAsset,
     embed_js::embed_file_path,
     emit, emit_package_json, internal_assets_for_source_mapping,
+    permissions::{OptionNodePermissions, PermissionViolationIssue, ResolvedNodePermissions},
     pool::{FormattingMode, NodeJsOperation, NodeJsPool},
     source_map::StructuredError,
     AssetsForSourceMapping,
@@ -72,6 +73,10 @@ enum EvalJavaScriptIncomingMessage {
     End {
         data: Option<String>,
     },
+    PermissionViolation {
+        permission: String,
+        resource: Option<String>,
+    },
     Error(StructuredError),
 }
 
@@ -97,6 +102,7 @@ pub async fn get_evaluate_pool(
     module_asset: Vc<Box<dyn Module>>,
     cwd: Vc<FileSystemPath>,
     env: Vc<Box<dyn ProcessEnv>>,
+    permissions: Vc<OptionNodePermissions>,
     asset_context: Vc<Box<dyn AssetContext>>,
     chunking_context: Vc<Box<dyn ChunkingContext>>,
     runtime_entries: Option<Vc<EvaluatableAssets>>,
@@ -190,6 +196,11 @@ pub async fn get_evaluate_pool(
     let assets_for_source_mapping = internal_assets_for_source_mapping(bootstrap, output_root);
     emit_package.await?;
     emit.await?;
+    let project_dir = chunking_context.context_path().root();
+    let mut readable = vec![cwd.clone()];
+    readable.extend(to_sys_path(project_dir).await?);
+    readable.extend(to_sys_path(output_root).await?);
+    let permissions = ResolvedNodePermissions::resolve(permissions, readable).await?;
     let pool = NodeJsPool::new(
         cwd,
         entrypoint,
@@ -200,8 +211,9 @@ pub async fn get_evaluate_pool(
             .collect(),
         assets_for_source_mapping,
         output_root,
-        chunking_context.context_path().root(),
+        project_dir,
         available_parallelism().map_or(1, |v| v.get()),
+        permissions,
         debug,
     );
     additional_invalidation.await?;
@@ -236,6 +248,7 @@ pub fn evaluate(
     module_asset: Vc<Box<dyn Module>>,
     cwd: Vc<FileSystemPath>,
     env: Vc<Box<dyn ProcessEnv>>,
+    permissions: Vc<OptionNodePermissions>,
     context_ident_for_issue: Vc<AssetIdent>,
     asset_context: Vc<Box<dyn AssetContext>>,
     chunking_context: Vc<Box<dyn ChunkingContext>>,
@@ -265,6 +278,7 @@ pub fn evaluate(
         module_asset,
         cwd,
         env,
+        permissions,
         context_ident_for_issue,
         asset_context,
         chunking_context,
@@ -300,6 +314,7 @@ async fn compute_evaluate_stream(
     module_asset: Vc<Box<dyn Module>>,
     cwd: Vc<FileSystemPath>,
     env: Vc<Box<dyn ProcessEnv>>,
+    permissions: Vc<OptionNodePermissions>,
     context_ident_for_issue: Vc<AssetIdent>,
     asset_context: Vc<Box<dyn AssetContext>>,
     chunking_context: Vc<Box<dyn ChunkingContext>>,
@@ -320,6 +335,7 @@ async fn compute_evaluate_stream(
             module_asset,
             cwd,
             env,
+            permissions,
             asset_context,
             chunking_context,
             runtime_entries,
@@ -452,6 +468,18 @@ async fn pull_operation(
                     cwd.join(path).read_glob(Glob::new(glob), false),
                 ));
             }
+            EvalJavaScriptIncomingMessage::PermissionViolation {
+                permission,
+                resource,
+            } => {
+                PermissionViolationIssue {
+                    context_ident: context_ident_for_issue,
+                    permission,
+                    resource,
+                }
+                .cell()
+                .emit();
+            }
             EvalJavaScriptIncomingMessage::EmittedError { error, severity } => {
                 EvaluateEmittedErrorIssue {
                     file_path: context_ident_for_issue.path(),
@@ -4,11 +4,16 @@ use turbo_tasks_env::ProcessEnv;
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::chunk::ChunkingContext;
 
+use crate::permissions::{NodePermissions, OptionNodePermissions};
+
 #[turbo_tasks::value]
 pub struct ExecutionContext {
     pub project_path: Vc<FileSystemPath>,
     pub chunking_context: Vc<Box<dyn ChunkingContext>>,
     pub env: Vc<Box<dyn ProcessEnv>>,
+    /// The permissions of the project code that is executed. When `None`, it
+    /// has all permissions of the Node.js process.
+    pub permissions: Vc<OptionNodePermissions>,
 }
 
 #[turbo_tasks::value_impl]
@@ -23,10 +28,27 @@ impl ExecutionContext {
             project_path,
             chunking_context,
             env,
+            permissions: OptionNodePermissions::none(),
         }
         .cell()
     }
 
+    /// Executes project code with the given permissions only.
+    #[turbo_tasks::function]
+    pub async fn with_permissions(
+        self: Vc<Self>,
+        permissions: Vc<NodePermissions>,
+    ) -> Result<Vc<Self>> {
+        let this = self.await?;
+        Ok(ExecutionContext {
+            project_path: this.project_path,
+            chunking_context: this.chunking_context,
+            env: this.env,
+            permissions: Vc::cell(Some(permissions)),
+        }
+        .cell())
+    }
+
     #[turbo_tasks::function]
     pub async fn project_path(self: Vc<Self>) -> Result<Vc<FileSystemPath>> {
         Ok(self.await?.project_path)
@@ -41,4 +63,9 @@ impl ExecutionContext {
     pub async fn env(self: Vc<Self>) -> Result<Vc<Box<dyn ProcessEnv>>> {
         Ok(self.await?.env)
     }
+
+    #[turbo_tasks::function]
+    pub async fn permissions(self: Vc<Self>) -> Result<Vc<OptionNodePermissions>> {
+        Ok(self.await?.permissions)
+    }
 }
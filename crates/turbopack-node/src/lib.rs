@@ -31,6 +31,7 @@ pub mod embed_js;
 pub mod evaluate;
 pub mod execution_context;
 mod node_entry;
+pub mod permissions;
 mod pool;
 pub mod render;
 pub mod route_matcher;
@@ -251,6 +252,7 @@ pub async fn get_renderer_pool(
         output_root,
         project_dir,
         available_parallelism().map_or(1, |v| v.get()),
+        None,
         debug,
     )
     .cell())
@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{to_sys_path, FileSystemPath};
+use turbopack_core::{
+    ident::AssetIdent,
+    issue::{Issue, OptionStyledString, StyledString},
+};
+
+/// The permissions granted to project code (e.g. postcss configs, webpack
+/// loaders or codegen modules) that is evaluated in Node.js. Without them,
+/// project code has all permissions of the Node.js process.
+///
+/// With them, the evaluated code can only read files from the project
+/// directory and the directory it has been emitted to, can't write files,
+/// spawn child processes or workers, and has no network access, unless
+/// granted explicitly. Denied file system and process accesses are reported
+/// as [PermissionViolationIssue]s.
+///
+/// All permissions are enforced for the whole Node.js process, not by the
+/// evaluated JavaScript. File system and process permissions are enforced by
+/// Node.js' experimental permission model, so they require Node.js 20 or
+/// later. Without network access, the process runs in its own user and
+/// network namespace, which requires Linux with unprivileged user namespaces
+/// and `unshare` from util-linux. Evaluation fails when the permissions can't
+/// be enforced.
+#[turbo_tasks::value(shared)]
+pub struct NodePermissions {
+    /// Additional directories or files that can be read.
+    pub read: Vec<Vc<FileSystemPath>>,
+    /// Directories or files that can be written.
+    pub write: Vec<Vc<FileSystemPath>>,
+    /// Whether child processes can be spawned.
+    pub child_processes: bool,
+    /// Whether worker threads can be created.
+    pub workers: bool,
+    /// Whether the network can be accessed.
+    pub network: bool,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionNodePermissions(Option<Vc<NodePermissions>>);
+
+#[turbo_tasks::value_impl]
+impl OptionNodePermissions {
+    #[turbo_tasks::function]
+    pub fn none() -> Vc<Self> {
+        Vc::cell(None)
+    }
+}
+
+/// [NodePermissions] resolved to paths on disk, ready to be passed to a
+/// Node.js process.
+#[derive(Clone, Debug)]
+pub(crate) struct ResolvedNodePermissions {
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+    child_processes: bool,
+    workers: bool,
+    network: bool,
+}
+
+impl ResolvedNodePermissions {
+    /// Resolves `permissions`. `readable` are the paths that are always
+    /// readable, e.g. the project directory and the directory the evaluated
+    /// code is emitted to.
+    pub(crate) async fn resolve(
+        permissions: Vc<OptionNodePermissions>,
+        readable: Vec<PathBuf>,
+    ) -> Result<Option<Self>> {
+        let Some(permissions) = *permissions.await? else {
+            return Ok(None);
+        };
+        let permissions = permissions.await?;
+        let sys_paths = |paths: &Vec<Vc<FileSystemPath>>| {
+            paths.iter().map(|&path| to_sys_path(path)).try_join()
+        };
+        let mut read = readable;
+        read.extend(sys_paths(&permissions.read).await?.into_iter().flatten());
+        let write = sys_paths(&permissions.write)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(Some(Self {
+            read,
+            write,
+            child_processes: permissions.child_processes,
+            workers: permissions.workers,
+            network: permissions.network,
+        }))
+    }
+
+    /// The arguments that enable Node.js' permission model with the granted
+    /// file system and process permissions.
+    pub(crate) fn node_args(&self) -> Vec<String> {
+        fn join(paths: &[PathBuf]) -> String {
+            paths
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(",")
+        }
+
+        let mut args = vec![
+            "--experimental-permission".to_string(),
+            format!("--allow-fs-read={}", join(&self.read)),
+        ];
+        if !self.write.is_empty() {
+            args.push(format!("--allow-fs-write={}", join(&self.write)));
+        }
+        if self.child_processes {
+            args.push("--allow-child-process".to_string());
+        }
+        if self.workers {
+            args.push("--allow-worker".to_string());
+        }
+        args
+    }
+
+    /// Whether the process must be isolated from the network.
+    pub(crate) fn isolate_network(&self) -> bool {
+        !self.network
+    }
+}
+
+/// Evaluated code tried to access something it has no capability for.
+#[turbo_tasks::value(shared)]
+pub struct PermissionViolationIssue {
+    pub context_ident: Vc<AssetIdent>,
+    /// The denied permission, e.g. `FileSystemRead` or `ChildProcess`.
+    pub permission: String,
+    /// The denied path or host, if known.
+    pub resource: Option<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for PermissionViolationIssue {
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Access denied by the permissions of evaluated code".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("permissions".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.context_ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        let text = match &self.resource {
+            Some(resource) => format!(
+                "The code evaluated in Node.js has no {} permission for {}. Grant it in the \
+                 permissions of the execution context if it's needed.",
+                self.permission, resource
+            ),
+            None => format!(
+                "The code evaluated in Node.js has no {} permission. Grant it in the permissions \
+                 of the execution context if it's needed.",
+                self.permission
+            ),
+        };
+        Vc::cell(Some(StyledString::Text(text).cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_args_grant_only_the_given_permissions() {
+        let permissions = ResolvedNodePermissions {
+            read: vec![PathBuf::from("/project"), PathBuf::from("/project/.next")],
+            write: vec![],
+            child_processes: false,
+            workers: true,
+            network: false,
+        };
+        assert_eq!(
+            permissions.node_args(),
+            vec![
+                "--experimental-permission",
+                "--allow-fs-read=/project,/project/.next",
+                "--allow-worker",
+            ]
+        );
+        assert!(permissions.isolate_network());
+    }
+
+    #[test]
+    fn node_args_grant_writes_and_child_processes() {
+        let permissions = ResolvedNodePermissions {
+            read: vec![],
+            write: vec![PathBuf::from("/tmp")],
+            child_processes: true,
+            workers: false,
+            network: true,
+        };
+        assert_eq!(
+            permissions.node_args(),
+            vec![
+                "--experimental-permission",
+                "--allow-fs-read=",
+                "--allow-fs-write=/tmp",
+                "--allow-child-process",
+            ]
+        );
+        assert!(!permissions.isolate_network());
+    }
+}
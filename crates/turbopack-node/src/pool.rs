@@ -20,7 +20,7 @@ use tokio::{
         stderr, stdout, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
         BufReader, Stderr, Stdout,
     },
-    net::{TcpListener, TcpStream},
+    net::TcpListener,
     process::{Child, ChildStderr, ChildStdout, Command},
     select,
     sync::{OwnedSemaphorePermit, Semaphore},
@@ -30,7 +30,9 @@ use turbo_tasks::Vc;
 use turbo_tasks_fs::{json::parse_json_with_source_context, FileSystemPath};
 use turbopack_ecmascript::magic_identifier::unmangle_identifiers;
 
-use crate::{source_map::apply_source_mapping, AssetsForSourceMapping};
+use crate::{
+    permissions::ResolvedNodePermissions, source_map::apply_source_mapping, AssetsForSourceMapping,
+};
 
 #[derive(Clone, Copy)]
 pub enum FormattingMode {
@@ -70,9 +72,94 @@ enum NodeJsPoolProcess {
     Running(RunningNodeJsPoolProcess),
 }
 
+/// A connection to a Node.js process.
+trait IpcConnection: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> IpcConnection for T {}
+
+/// Accepts the connection of a Node.js process.
+enum IpcListener {
+    Tcp(TcpListener),
+    /// A Unix domain socket, which can also be connected to from a process in
+    /// another network namespace. The socket file is removed when the listener
+    /// is dropped.
+    #[cfg(target_os = "linux")]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+impl IpcListener {
+    async fn bind_tcp() -> Result<Self> {
+        Ok(Self::Tcp(
+            TcpListener::bind("127.0.0.1:0")
+                .await
+                .context("binding to a port")?,
+        ))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn bind_unix() -> Result<Self> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "turbopack-{}-{}.sock",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let listener = tokio::net::UnixListener::bind(&path)
+            .with_context(|| format!("binding to {}", path.display()))?;
+        Ok(Self::Unix(listener, path))
+    }
+
+    /// The argument that tells the Node.js process where to connect to,
+    /// either a port or a socket path.
+    fn address(&self) -> Result<String> {
+        Ok(match self {
+            Self::Tcp(listener) => listener
+                .local_addr()
+                .context("getting port")?
+                .port()
+                .to_string(),
+            #[cfg(target_os = "linux")]
+            Self::Unix(_, path) => path.to_string_lossy().into_owned(),
+        })
+    }
+
+    async fn accept(&self) -> std::io::Result<Box<dyn IpcConnection>> {
+        Ok(match self {
+            Self::Tcp(listener) => Box::new(listener.accept().await?.0),
+            #[cfg(target_os = "linux")]
+            Self::Unix(listener, _) => Box::new(listener.accept().await?.0),
+        })
+    }
+}
+
+impl Drop for IpcListener {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        if let Self::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Runs `node` in new user and network namespaces, so that it can only
+/// access its own loopback interface.
+#[cfg(target_os = "linux")]
+fn network_isolated_node() -> Result<Command> {
+    let mut cmd = Command::new("unshare");
+    cmd.args(["--user", "--net", "--", "node"]);
+    Ok(cmd)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn network_isolated_node() -> Result<Command> {
+    bail!("Evaluating code without network access is only supported on Linux")
+}
+
 struct SpawnedNodeJsPoolProcess {
     child: Child,
-    listener: TcpListener,
+    listener: IpcListener,
     assets_for_source_mapping: Vc<AssetsForSourceMapping>,
     assets_root: Vc<FileSystemPath>,
     project_dir: Vc<FileSystemPath>,
@@ -83,7 +170,7 @@ struct SpawnedNodeJsPoolProcess {
 
 struct RunningNodeJsPoolProcess {
     child: Option<Child>,
-    connection: TcpStream,
+    connection: Box<dyn IpcConnection>,
     assets_for_source_mapping: Vc<AssetsForSourceMapping>,
     assets_root: Vc<FileSystemPath>,
     project_dir: Vc<FileSystemPath>,
@@ -332,19 +419,34 @@ impl NodeJsPoolProcess {
         project_dir: Vc<FileSystemPath>,
         shared_stdout: SharedOutputSet,
         shared_stderr: SharedOutputSet,
+        permissions: Option<&ResolvedNodePermissions>,
         debug: bool,
     ) -> Result<Self> {
-        let listener = TcpListener::bind("127.0.0.1:0")
-            .await
-            .context("binding to a port")?;
-        let port = listener.local_addr().context("getting port")?.port();
-        let mut cmd = Command::new("node");
+        let isolate_network = permissions.is_some_and(|permissions| permissions.isolate_network());
+        // A TCP connection to the loopback interface would stay in the network
+        // namespace of the isolated process.
+        #[cfg(target_os = "linux")]
+        let listener = if isolate_network {
+            IpcListener::bind_unix()?
+        } else {
+            IpcListener::bind_tcp().await?
+        };
+        #[cfg(not(target_os = "linux"))]
+        let listener = IpcListener::bind_tcp().await?;
+        let mut cmd = if isolate_network {
+            network_isolated_node()?
+        } else {
+            Command::new("node")
+        };
         cmd.current_dir(cwd);
         if debug {
             cmd.arg("--inspect-brk");
         }
+        if let Some(permissions) = permissions {
+            cmd.args(permissions.node_args());
+        }
         cmd.arg(entrypoint);
-        cmd.arg(port.to_string());
+        cmd.arg(listener.address()?);
         cmd.env_clear();
         cmd.env(
             "PATH",
@@ -420,7 +522,7 @@ impl NodeJsPoolProcess {
                     Ok((clean(stdout)?, clean(stderr)?))
                 }
 
-                let (connection, _) = select! {
+                let connection = select! {
                     connection = listener.accept() => connection.context("accepting connection")?,
                     status = child.wait() => {
                         match status {
@@ -561,10 +663,14 @@ pub struct NodeJsPool {
     shared_stdout: SharedOutputSet,
     #[turbo_tasks(trace_ignore, debug_ignore)]
     shared_stderr: SharedOutputSet,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    permissions: Option<ResolvedNodePermissions>,
     debug: bool,
 }
 
 impl NodeJsPool {
+    /// * permissions: The permissions of the spawned processes. When `None`,
+    ///   they have all permissions.
     /// * debug: Whether to automatically enable Node's `--inspect-brk` when
     ///   spawning it. Note: automatically overrides concurrency to 1.
     pub(super) fn new(
@@ -575,6 +681,7 @@ impl NodeJsPool {
         assets_root: Vc<FileSystemPath>,
         project_dir: Vc<FileSystemPath>,
         concurrency: usize,
+        permissions: Option<ResolvedNodePermissions>,
         debug: bool,
     ) -> Self {
         Self {
@@ -588,6 +695,7 @@ impl NodeJsPool {
             semaphore: Arc::new(Semaphore::new(if debug { 1 } else { concurrency })),
             shared_stdout: Arc::new(Mutex::new(IndexSet::new())),
             shared_stderr: Arc::new(Mutex::new(IndexSet::new())),
+            permissions,
             debug,
         }
     }
@@ -610,6 +718,7 @@ impl NodeJsPool {
                 self.project_dir,
                 self.shared_stdout.clone(),
                 self.shared_stderr.clone(),
+                self.permissions.as_ref(),
                 self.debug,
             )
             .await
@@ -68,6 +68,10 @@ impl StaticResult {
 }
 
 /// Renders a module as static HTML in a node.js process.
+///
+/// The module runs the render loop of `@vercel/turbopack-node/ipc/render`.
+/// When it responds with a stream, the body is passed on as
+/// [StaticResult::StreamedContent] while it's rendered.
 #[turbo_tasks::function]
 pub async fn render_static(
     cwd: Vc<FileSystemPath>,
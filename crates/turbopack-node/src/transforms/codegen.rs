@@ -0,0 +1,234 @@
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use indexmap::indexmap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{Completion, Value, Vc};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::{AssetContext, ProcessResult},
+    ident::AssetIdent,
+    issue::IssueDescriptionExt,
+    reference_type::{EntryReferenceSubType, InnerAssets, ReferenceType},
+    source::Source,
+    source_transform::SourceTransform,
+    virtual_source::VirtualSource,
+};
+
+use super::util::{emitted_assets_to_virtual_sources, EmittedAsset};
+use crate::{
+    debug::should_debug, embed_js::embed_file, evaluate::evaluate,
+    execution_context::ExecutionContext,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[turbo_tasks::value(transparent, serialization = "custom")]
+struct CodegenProcessingResult {
+    code: String,
+    map: Option<String>,
+    #[turbo_tasks(trace_ignore)]
+    assets: Option<Vec<EmittedAsset>>,
+}
+
+/// A source transform that replaces the content of a module with the code
+/// returned by executing that module at build time (similar to webpack's
+/// `val-loader`).
+///
+/// The module is evaluated in the Node.js pool. Its default export is called
+/// with information about the resource and must return (a promise of) an
+/// object with a `code` string. Any `dependencies` or `contextDependencies`
+/// it reports are tracked, so the module is only re-executed when one of them
+/// (or the module itself) changes. A returned `map` is inlined into the code.
+///
+/// Codegen modules are JavaScript or TypeScript modules only, they can't be
+/// WebAssembly plugins.
+#[turbo_tasks::value]
+pub struct CodegenTransform {
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+}
+
+#[turbo_tasks::value_impl]
+impl CodegenTransform {
+    #[turbo_tasks::function]
+    pub fn new(
+        evaluate_context: Vc<Box<dyn AssetContext>>,
+        execution_context: Vc<ExecutionContext>,
+    ) -> Vc<Self> {
+        CodegenTransform {
+            evaluate_context,
+            execution_context,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for CodegenTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            CodegenTransformedAsset {
+                evaluate_context: self.evaluate_context,
+                execution_context: self.execution_context,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct CodegenTransformedAsset {
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for CodegenTransformedAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for CodegenTransformedAsset {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        Ok(self
+            .process()
+            .issue_file_path(this.source.ident().path(), "Codegen module execution")
+            .await?
+            .await?
+            .content)
+    }
+}
+
+#[turbo_tasks::value]
+struct ProcessCodegenResult {
+    content: Vc<AssetContent>,
+    assets: Vec<Vc<VirtualSource>>,
+}
+
+#[turbo_tasks::function]
+fn codegen_executor(
+    asset_context: Vc<Box<dyn AssetContext>>,
+    source: Vc<Box<dyn Source>>,
+    source_path: Vc<FileSystemPath>,
+) -> Vc<ProcessResult> {
+    let codegen_asset = asset_context
+        .process(
+            source,
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        )
+        .module();
+
+    asset_context.process(
+        Vc::upcast(VirtualSource::new(
+            source_path.append("__codegen__.ts".to_string()),
+            AssetContent::File(embed_file("transforms/codegen.ts".to_string())).cell(),
+        )),
+        Value::new(ReferenceType::Internal(Vc::cell(indexmap! {
+            "CODEGEN".to_string() => codegen_asset
+        }))),
+    )
+}
+
+#[turbo_tasks::value_impl]
+impl CodegenTransformedAsset {
+    #[turbo_tasks::function]
+    async fn process(self: Vc<Self>) -> Result<Vc<ProcessCodegenResult>> {
+        let this = self.await?;
+        let ExecutionContext {
+            project_path,
+            chunking_context,
+            env,
+            permissions,
+        } = *this.execution_context.await?;
+
+        let AssetContent::File(file) = *this.source.content().await? else {
+            bail!("Codegen modules can only be files");
+        };
+        if !matches!(&*file.await?, FileContent::Content(_)) {
+            return Ok(ProcessCodegenResult {
+                content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+                assets: Vec::new(),
+            }
+            .cell());
+        }
+
+        let source_path = this.source.ident().path();
+        let executor = codegen_executor(this.evaluate_context, this.source, source_path).module();
+        let resource_path = source_path.await?.path.clone();
+
+        let value = evaluate(
+            executor,
+            project_path,
+            env,
+            permissions,
+            this.source.ident(),
+            this.evaluate_context,
+            chunking_context,
+            None,
+            vec![Vc::cell(resource_path.into())],
+            Completion::immutable(),
+            should_debug("codegen"),
+        )
+        .await?;
+
+        let SingleValue::Single(val) = value.try_into_single().await? else {
+            // An error happened, which has already been converted into an issue.
+            return Ok(ProcessCodegenResult {
+                content: AssetContent::File(FileContent::NotFound.cell()).cell(),
+                assets: Vec::new(),
+            }
+            .cell());
+        };
+        let processed: CodegenProcessingResult = parse_json_with_source_context(val.to_str()?)
+            .context("Unable to deserialize response from codegen module execution")?;
+
+        let file = File::from(with_inline_source_map(processed.code, processed.map));
+        let assets = emitted_assets_to_virtual_sources(processed.assets);
+        let content = AssetContent::File(FileContent::Content(file).cell()).cell();
+        Ok(ProcessCodegenResult { content, assets }.cell())
+    }
+}
+
+/// Appends `map` to `code` as an inline source map, which is picked up when
+/// the generated module is parsed, so that it's mapped to what the codegen
+/// module generated it from.
+fn with_inline_source_map(code: String, map: Option<String>) -> String {
+    match map {
+        Some(map) => format!(
+            "{code}\n//# sourceMappingURL=data:application/json;base64,{}",
+            STANDARD.encode(map)
+        ),
+        None => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_source_map() {
+        assert_eq!(
+            with_inline_source_map("export default 1;".to_string(), None),
+            "export default 1;"
+        );
+        assert_eq!(
+            with_inline_source_map(
+                "export default 1;".to_string(),
+                Some(r#"{"version":3}"#.to_string())
+            ),
+            "export default 1;\n//# \
+             sourceMappingURL=data:application/json;base64,eyJ2ZXJzaW9uIjozfQ=="
+        );
+    }
+}
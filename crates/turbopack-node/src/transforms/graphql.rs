@@ -0,0 +1,200 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Completion, TaskInput, Value, Vc};
+use turbo_tasks_bytes::stream::SingleValue;
+use turbo_tasks_fs::{json::parse_json_with_source_context, File, FileContent};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    context::{AssetContext, ProcessResult},
+    file_source::FileSource,
+    ident::AssetIdent,
+    issue::IssueDescriptionExt,
+    reference_type::{InnerAssets, ReferenceType},
+    resolve::options::ImportMapping,
+    source::Source,
+    source_transform::SourceTransform,
+};
+
+use crate::{
+    debug::should_debug, embed_js::embed_file_path, evaluate::evaluate,
+    execution_context::ExecutionContext,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[turbo_tasks::value(transparent, serialization = "custom")]
+struct GraphQlProcessingResult {
+    code: String,
+    map: Option<String>,
+}
+
+/// What kind of source the GraphQL transform is applied to.
+#[derive(
+    Copy, Clone, PartialEq, Eq, Debug, Hash, TraceRawVcs, Serialize, Deserialize, TaskInput,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum GraphQlTransformMode {
+    /// A `.graphql`/`.gql` document. The whole file is compiled into an
+    /// ecmascript module exporting the parsed document. `#import` statements
+    /// are turned into imports of the referenced fragment documents.
+    Document,
+    /// An ecmascript module. Static `gql` tagged templates are replaced by
+    /// their precompiled documents.
+    InlineTags,
+}
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Default)]
+pub struct GraphQlTransformOptions {
+    /// The `graphql` package used to parse documents. Defaults to the one
+    /// resolved from the project.
+    pub graphql_package: Option<Vc<ImportMapping>>,
+    /// Also precompile `gql` tagged templates in ecmascript modules.
+    pub inline_tags: bool,
+    pub placeholder_for_future_extensions: (),
+}
+
+#[turbo_tasks::value]
+pub struct GraphQlTransform {
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+    mode: GraphQlTransformMode,
+}
+
+#[turbo_tasks::value_impl]
+impl GraphQlTransform {
+    #[turbo_tasks::function]
+    pub fn new(
+        evaluate_context: Vc<Box<dyn AssetContext>>,
+        execution_context: Vc<ExecutionContext>,
+        mode: GraphQlTransformMode,
+    ) -> Vc<Self> {
+        GraphQlTransform {
+            evaluate_context,
+            execution_context,
+            mode,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for GraphQlTransform {
+    #[turbo_tasks::function]
+    fn transform(&self, source: Vc<Box<dyn Source>>) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            GraphQlTransformedAsset {
+                evaluate_context: self.evaluate_context,
+                execution_context: self.execution_context,
+                mode: self.mode,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct GraphQlTransformedAsset {
+    evaluate_context: Vc<Box<dyn AssetContext>>,
+    execution_context: Vc<ExecutionContext>,
+    mode: GraphQlTransformMode,
+    source: Vc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for GraphQlTransformedAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        self.source.ident()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for GraphQlTransformedAsset {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        Ok(self
+            .process()
+            .issue_file_path(this.source.ident().path(), "GraphQL processing")
+            .await?)
+    }
+}
+
+#[turbo_tasks::function]
+fn graphql_executor(asset_context: Vc<Box<dyn AssetContext>>) -> Vc<ProcessResult> {
+    asset_context.process(
+        Vc::upcast(FileSource::new(embed_file_path(
+            "transforms/graphql.ts".to_string(),
+        ))),
+        Value::new(ReferenceType::Internal(InnerAssets::empty())),
+    )
+}
+
+/// Returns `true` when the ecmascript `content` may contain a `gql` tagged
+/// template. This avoids a roundtrip to Node.js for the vast majority of
+/// modules.
+fn may_contain_gql_tag(content: &str) -> bool {
+    content.contains("gql`") || content.contains("graphql`")
+}
+
+#[turbo_tasks::value_impl]
+impl GraphQlTransformedAsset {
+    #[turbo_tasks::function]
+    async fn process(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let this = self.await?;
+        let ExecutionContext {
+            project_path,
+            chunking_context,
+            env,
+            permissions,
+        } = *this.execution_context.await?;
+
+        let source_content = this.source.content();
+        let AssetContent::File(file) = *source_content.await? else {
+            bail!("GraphQL transform only support transforming files");
+        };
+        let FileContent::Content(content) = &*file.await? else {
+            return Ok(AssetContent::File(FileContent::NotFound.cell()).cell());
+        };
+        let content = content.content().to_str()?;
+        if this.mode == GraphQlTransformMode::InlineTags && !may_contain_gql_tag(&content) {
+            return Ok(source_content);
+        }
+
+        let evaluate_context = this.evaluate_context;
+        let executor = graphql_executor(evaluate_context).module();
+        let resource_fs_path = this.source.ident().path().await?;
+        let resource_path = resource_fs_path.path.as_str();
+
+        let value = evaluate(
+            executor,
+            project_path,
+            env,
+            permissions,
+            this.source.ident(),
+            evaluate_context,
+            chunking_context,
+            None,
+            vec![
+                Vc::cell(content.into()),
+                Vc::cell(resource_path.into()),
+                Vc::cell(serde_json::to_value(this.mode)?),
+            ],
+            Completion::immutable(),
+            should_debug("graphql_transform"),
+        )
+        .await?;
+
+        let SingleValue::Single(val) = value.try_into_single().await? else {
+            // An error happened, which has already been converted into an issue.
+            return Ok(AssetContent::File(FileContent::NotFound.cell()).cell());
+        };
+        let processed: GraphQlProcessingResult = parse_json_with_source_context(val.to_str()?)
+            .context("Unable to deserializate response from GraphQL transform operation")?;
+
+        // TODO handle SourceMap
+        Ok(AssetContent::File(FileContent::Content(File::from(processed.code)).cell()).cell())
+    }
+}
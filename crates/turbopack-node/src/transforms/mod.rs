@@ -1,3 +1,5 @@
+pub mod codegen;
+pub mod graphql;
 pub mod postcss;
 mod util;
 pub mod webpack;
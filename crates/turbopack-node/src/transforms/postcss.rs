@@ -251,6 +251,7 @@ impl PostCssTransformedAsset {
             project_path,
             chunking_context,
             env,
+            permissions,
         } = *this.execution_context.await?;
 
         // For this postcss transform, there is no gaurantee that looking up for the
@@ -308,6 +309,7 @@ impl PostCssTransformedAsset {
             postcss_executor,
             project_path,
             env,
+            permissions,
             this.source.ident(),
             evaluate_context,
             chunking_context,
@@ -138,6 +138,7 @@ impl WebpackLoadersProcessedAsset {
             project_path,
             chunking_context,
             env,
+            permissions,
         } = *transform.execution_context.await?;
         let source_content = this.source.content();
         let AssetContent::File(file) = *source_content.await? else {
@@ -161,6 +162,7 @@ impl WebpackLoadersProcessedAsset {
             webpack_loaders_executor,
             project_path,
             env,
+            permissions,
             this.source.ident(),
             evaluate_context,
             chunking_context,
@@ -12,6 +12,7 @@
 #![feature(arbitrary_self_types)]
 
 pub mod fixed;
+pub mod manifest;
 pub mod output_asset;
 
 use anyhow::{Context, Result};
@@ -27,12 +28,9 @@ use turbopack_core::{
     source::Source,
 };
 use turbopack_css::embed::CssEmbed;
-use turbopack_ecmascript::{
-    chunk::{
-        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
-        EcmascriptChunkType, EcmascriptChunkingContext, EcmascriptExports,
-    },
-    utils::StringifyJs,
+use turbopack_ecmascript::chunk::{
+    EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable, EcmascriptChunkType,
+    EcmascriptChunkingContext, EcmascriptExports,
 };
 
 use self::output_asset::StaticAsset;
@@ -170,13 +168,11 @@ impl EcmascriptChunkItem for ModuleChunkItem {
     async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
         Ok(EcmascriptChunkItemContent {
             inner_code: format!(
-                "__turbopack_export_value__({path});",
-                path = StringifyJs(
-                    &self
-                        .chunking_context
-                        .asset_url(self.static_asset.ident())
-                        .await?
-                )
+                "__turbopack_export_value__({url});",
+                url = self
+                    .chunking_context
+                    .asset_url_expr(self.static_asset.ident())
+                    .await?
             )
             .into(),
             ..Default::default()
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::ChunkingContext,
+    ident::AssetIdent,
+    module::{Module, Modules},
+    output::{OutputAsset, OutputAssets},
+    reference::all_modules,
+    source::Source,
+    virtual_source::VirtualSource,
+};
+use turbopack_ecmascript::utils::StringifyJs;
+
+use crate::{output_asset::StaticAsset, StaticModuleAsset};
+
+/// Maps the original paths (relative to the chunking context's context path)
+/// of all static assets reachable from `entries` to their output assets.
+#[turbo_tasks::value(transparent)]
+struct StaticAssetsMap(BTreeMap<String, Vc<StaticAsset>>);
+
+#[turbo_tasks::function]
+async fn static_assets_map(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: Vc<Modules>,
+) -> Result<Vc<StaticAssetsMap>> {
+    let context_path = chunking_context.context_path().await?;
+    let modules = entries
+        .await?
+        .iter()
+        .map(|&entry| async move { Ok(all_modules(entry).await?.clone_value()) })
+        .try_join()
+        .await?;
+
+    let mut map = BTreeMap::new();
+    for module in modules.into_iter().flatten() {
+        let Some(static_module) =
+            Vc::try_resolve_downcast_type::<StaticModuleAsset>(module).await?
+        else {
+            continue;
+        };
+        let source = static_module.await?.source;
+        let source_path = source.ident().path().await?;
+        let Some(original_path) = context_path.get_path_to(&source_path) else {
+            continue;
+        };
+        map.insert(
+            original_path.to_string(),
+            StaticAsset::new(chunking_context, source),
+        );
+    }
+    Ok(Vc::cell(map))
+}
+
+/// Resolves the map of original asset paths to the URLs the assets are served
+/// from, as produced by [ChunkingContext::asset_path] and
+/// [ChunkingContext::asset_url].
+#[turbo_tasks::function]
+async fn static_asset_urls(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: Vc<Modules>,
+) -> Result<Vc<StaticAssetUrls>> {
+    let urls = static_assets_map(chunking_context, entries)
+        .await?
+        .iter()
+        .map(|(original_path, &asset)| async move {
+            let url = chunking_context.asset_url(asset.ident()).await?;
+            Ok((original_path.clone(), url.clone_value()))
+        })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(urls.into_iter().collect()))
+}
+
+#[turbo_tasks::value(transparent)]
+struct StaticAssetUrls(BTreeMap<String, String>);
+
+/// An `assets-manifest.json` style asset that maps the original paths of all
+/// static assets reachable from a set of entries to their hashed output URLs.
+#[turbo_tasks::value]
+pub struct StaticAssetsManifest {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: Vc<Modules>,
+}
+
+#[turbo_tasks::value_impl]
+impl StaticAssetsManifest {
+    /// Creates a new manifest that will be emitted at `path`.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        entries: Vc<Modules>,
+    ) -> Vc<Self> {
+        StaticAssetsManifest {
+            path,
+            chunking_context,
+            entries,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for StaticAssetsManifest {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<Vc<OutputAssets>> {
+        Ok(Vc::cell(
+            static_assets_map(self.chunking_context, self.entries)
+                .await?
+                .values()
+                .map(|&asset| Vc::upcast(asset))
+                .collect(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for StaticAssetsManifest {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let urls = static_asset_urls(self.chunking_context, self.entries).await?;
+        let json = serde_json::to_string_pretty(&*urls)?;
+        Ok(AssetContent::file(File::from(json).into()))
+    }
+}
+
+/// Returns an ecmascript source for a tiny runtime helper that resolves asset
+/// URLs by their original path.
+///
+/// The helper exports the manifest as `assets` and an `assetUrl(path)`
+/// function that throws when the asset is unknown. It should be placed at
+/// `path` and processed with the asset context of the module graph that
+/// imports it.
+#[turbo_tasks::function]
+pub async fn static_assets_manifest_helper_source(
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: Vc<Modules>,
+) -> Result<Vc<Box<dyn Source>>> {
+    let urls = static_asset_urls(chunking_context, entries).await?;
+    let code = format!(
+        r#"export const assets = Object.freeze({assets});
+
+export function assetUrl(path) {{
+    const normalized = path.replace(/^\.?\//, "");
+    if (!Object.prototype.hasOwnProperty.call(assets, normalized)) {{
+        throw new Error(`Unknown asset ${{path}}`);
+    }}
+    return assets[normalized];
+}}
+"#,
+        assets = StringifyJs(&*urls),
+    );
+    Ok(Vc::upcast(VirtualSource::new(
+        path,
+        AssetContent::file(File::from(code).into()),
+    )))
+}
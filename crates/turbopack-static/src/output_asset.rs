@@ -5,7 +5,7 @@ use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::ChunkingContext,
     ident::AssetIdent,
-    output::OutputAsset,
+    output::{OutputAsset, ServingHints},
     source::Source,
 };
 #[turbo_tasks::value]
@@ -33,21 +33,27 @@ impl OutputAsset for StaticAsset {
     #[turbo_tasks::function]
     async fn ident(&self) -> Result<Vc<AssetIdent>> {
         let content = self.source.content();
-        let content_hash = if let AssetContent::File(file) = &*content.await? {
+        let hash_algorithm = *self.chunking_context.hash_algorithm().await?;
+        let content_hash_b16 = if let AssetContent::File(file) = &*content.await? {
             if let FileContent::Content(file) = &*file.await? {
-                turbo_tasks_hash::hash_xxh3_hash64(file.content())
+                hash_algorithm.hash_hex(file.content())
             } else {
                 return Err(anyhow!("StaticAsset::path: not found"));
             }
         } else {
             return Err(anyhow!("StaticAsset::path: unsupported file content"));
         };
-        let content_hash_b16 = turbo_tasks_hash::encode_hex(content_hash);
         let asset_path = self
             .chunking_context
             .asset_path(content_hash_b16, self.source.ident());
         Ok(AssetIdent::from_path(asset_path))
     }
+
+    /// The path contains the content hash, see [ChunkingContext::asset_path].
+    #[turbo_tasks::function]
+    fn serving_hints(&self) -> Vc<ServingHints> {
+        ServingHints::immutable()
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -0,0 +1,429 @@
+//! Snapshots of the chunks emitted for a fixture, for testing chunking
+//! configurations.
+//!
+//! A [ChunkOutputSnapshot] contains every file a chunking context emits for
+//! the entry of a fixture directory. Hashes in file names are replaced with
+//! `[hash]` and emitted paths are made relative to the output root, so the
+//! snapshot only changes when the output itself changes, and not when the
+//! fixture is moved or an unrelated hash changes. Snapshots are compared with
+//! [ChunkOutputSnapshot::diff], or against a snapshot file with
+//! [assert_chunk_output].
+
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt::{self, Display},
+};
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use similar::TextDiff;
+use turbo_tasks::{Value, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkableModule, ChunkingContext, ChunkingContextExt,
+        EvaluatableAsset, EvaluatableAssets,
+    },
+    context::AssetContext,
+    file_source::FileSource,
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    reference_type::{EntryReferenceSubType, ReferenceType},
+};
+
+use crate::snapshot::diff;
+
+/// Matches hex hashes between separators of a file name, e.g. `4437c1` in
+/// `input_4437c1._.js`.
+static HASH_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(^|[_./-])([0-9a-f]{6,})([_.-]|$)").unwrap());
+
+const SECTION_START: &str = "=== ";
+const SECTION_END: &str = " ===";
+
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkOutputOptions {
+    /// Whether source maps are included in the snapshot. They change with
+    /// every change of the code and are often too noisy to review.
+    pub source_maps: bool,
+}
+
+impl Default for ChunkOutputOptions {
+    fn default() -> Self {
+        ChunkOutputOptions { source_maps: true }
+    }
+}
+
+/// The normalized files emitted for an entry, by their path relative to the
+/// output root of the chunking context.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChunkOutputSnapshot {
+    pub files: BTreeMap<String, String>,
+}
+
+impl ChunkOutputSnapshot {
+    /// Chunks `entry`, a path relative to `fixture_dir`, with
+    /// `chunking_context` and captures the emitted files. The entry is placed
+    /// into an evaluated chunk group when it can be evaluated, and into a root
+    /// chunk group otherwise.
+    pub async fn capture(
+        fixture_dir: Vc<FileSystemPath>,
+        entry: &str,
+        asset_context: Vc<Box<dyn AssetContext>>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        options: ChunkOutputOptions,
+    ) -> Result<Self> {
+        let entry_path = fixture_dir.join(entry.to_string());
+        let module = asset_context
+            .process(
+                Vc::upcast(FileSource::new(entry_path)),
+                Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+            )
+            .module();
+        let assets = if let Some(evaluatable) =
+            Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(module).await?
+        {
+            chunking_context.evaluated_chunk_group_assets(
+                module.ident(),
+                EvaluatableAssets::one(evaluatable),
+                Value::new(AvailabilityInfo::Root),
+            )
+        } else if let Some(chunkable) =
+            Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module).await?
+        {
+            chunking_context.root_chunk_group_assets(chunkable)
+        } else {
+            bail!(
+                "{} can't be placed into chunks",
+                entry_path.to_string().await?
+            );
+        };
+        Self::from_assets(chunking_context.output_root(), assets, options).await
+    }
+
+    /// Captures `assets` and the assets they reference, transitively. Only
+    /// assets inside `output_root` are included, as nothing else is emitted.
+    pub async fn from_assets(
+        output_root: Vc<FileSystemPath>,
+        assets: Vc<OutputAssets>,
+        options: ChunkOutputOptions,
+    ) -> Result<Self> {
+        let output_root = output_root.await?;
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<_> = assets.await?.iter().copied().collect();
+        let mut files = Vec::new();
+        while let Some(asset) = queue.pop_front() {
+            let path = asset.ident().path().await?;
+            if !seen.insert(path.path.clone()) {
+                continue;
+            }
+            queue.extend(asset.references().await?.iter().copied());
+
+            let Some(relative_path) = output_root.get_path_to(&path) else {
+                continue;
+            };
+            if !options.source_maps && relative_path.ends_with(".map") {
+                continue;
+            }
+            files.push((relative_path.to_string(), read_content(asset).await?));
+        }
+        Ok(normalize(files))
+    }
+
+    /// Parses a snapshot file, as written by formatting a snapshot with
+    /// [Display].
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        let mut current: Option<(String, Vec<&str>)> = None;
+        for line in content.lines() {
+            if let Some(path) = line
+                .strip_prefix(SECTION_START)
+                .and_then(|line| line.strip_suffix(SECTION_END))
+            {
+                if let Some((path, lines)) = current.take() {
+                    files.insert(path, lines.join("\n").trim().to_string());
+                }
+                current = Some((path.to_string(), Vec::new()));
+            } else if let Some((_, lines)) = &mut current {
+                lines.push(line);
+            } else if !line.trim().is_empty() {
+                bail!("expected a file header, found {line:?}");
+            }
+        }
+        if let Some((path, lines)) = current {
+            files.insert(path, lines.join("\n").trim().to_string());
+        }
+        Ok(ChunkOutputSnapshot { files })
+    }
+
+    /// Computes the changes from `self` to `other`.
+    pub fn diff(&self, other: &ChunkOutputSnapshot) -> ChunkOutputDiff {
+        let mut result = ChunkOutputDiff::default();
+        for (path, content) in &self.files {
+            match other.files.get(path) {
+                None => result.removed.push(path.clone()),
+                Some(other_content) if other_content != content => {
+                    let diff = TextDiff::from_lines(content, other_content)
+                        .unified_diff()
+                        .context_radius(3)
+                        .header(path, path)
+                        .to_string();
+                    result.changed.insert(path.clone(), diff);
+                }
+                Some(_) => {}
+            }
+        }
+        for path in other.files.keys() {
+            if !self.files.contains_key(path) {
+                result.added.push(path.clone());
+            }
+        }
+        result
+    }
+}
+
+impl Display for ChunkOutputSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (path, content) in &self.files {
+            writeln!(f, "{SECTION_START}{path}{SECTION_END}")?;
+            writeln!(f, "{}", content.trim())?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The changes between two [ChunkOutputSnapshot]s.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChunkOutputDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Maps the paths of files whose content changed to a unified diff of the
+    /// content.
+    pub changed: BTreeMap<String, String>,
+}
+
+impl ChunkOutputDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Display for ChunkOutputDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for path in &self.added {
+            writeln!(f, "added {path}")?;
+        }
+        for path in &self.removed {
+            writeln!(f, "removed {path}")?;
+        }
+        for diff in self.changed.values() {
+            write!(f, "{diff}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares `snapshot` against the snapshot file at `path`. Like other
+/// snapshots, the file is updated instead when running with `UPDATE=1`.
+pub async fn assert_chunk_output(
+    snapshot: &ChunkOutputSnapshot,
+    path: Vc<FileSystemPath>,
+) -> Result<()> {
+    diff(
+        path,
+        AssetContent::file(File::from(snapshot.to_string()).into()),
+    )
+    .await
+}
+
+async fn read_content(asset: Vc<Box<dyn OutputAsset>>) -> Result<String> {
+    Ok(match &*asset.content().await? {
+        AssetContent::File(file) => match &*file.await? {
+            FileContent::NotFound => "<not found>".to_string(),
+            FileContent::Content(file) => match file.content().to_str() {
+                Ok(content) => content.into_owned(),
+                Err(_) => format!("<binary, {} bytes>", file.content().len()),
+            },
+        },
+        AssetContent::Redirect { target, link_type } => {
+            format!(
+                "Redirect {{ target: {target}, link_type: {:?} }}",
+                link_type
+            )
+        }
+    })
+}
+
+/// Replaces the hashes in the paths of `files`, both in the paths themselves
+/// and where files refer to each other. When paths only differ by their hash,
+/// they are numbered in the order of their content.
+fn normalize(files: Vec<(String, String)>) -> ChunkOutputSnapshot {
+    let mut by_normalized_path = BTreeMap::<String, Vec<(String, String)>>::new();
+    for (path, content) in files {
+        by_normalized_path
+            .entry(normalize_hashes(&path))
+            .or_default()
+            .push((path, content));
+    }
+
+    let mut renames = Vec::new();
+    let mut files = Vec::new();
+    for (normalized_path, mut group) in by_normalized_path {
+        group.sort_by(|(_, a), (_, b)| a.cmp(b));
+        let numbered = group.len() > 1;
+        for (index, (path, content)) in group.into_iter().enumerate() {
+            let normalized_path = if numbered {
+                normalized_path.replacen("[hash]", &format!("[hash~{}]", index + 1), 1)
+            } else {
+                normalized_path.clone()
+            };
+            if path != normalized_path {
+                renames.push((
+                    file_name(&path).to_string(),
+                    file_name(&normalized_path).to_string(),
+                ));
+                renames.push((path, normalized_path.clone()));
+            }
+            files.push((normalized_path, content));
+        }
+    }
+    // Replace longer paths first, so file names don't replace parts of the full
+    // paths they are contained in.
+    renames.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    renames.dedup_by(|(a, _), (b, _)| a == b);
+
+    ChunkOutputSnapshot {
+        files: files
+            .into_iter()
+            .map(|(path, mut content)| {
+                for (from, to) in &renames {
+                    content = content.replace(from.as_str(), to);
+                }
+                (path, content.trim().to_string())
+            })
+            .collect(),
+    }
+}
+
+fn normalize_hashes(path: &str) -> String {
+    let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+    let mut name = name.to_string();
+    // Matches can't share separators, so adjacent hashes take more than one
+    // pass.
+    loop {
+        let replaced = HASH_REGEX.replace_all(&name, |captures: &Captures| {
+            // Only hex strings that contain a digit are considered hashes, so
+            // words like `facade` are kept.
+            if captures[2].bytes().any(|b| b.is_ascii_digit()) {
+                format!("{}[hash]{}", &captures[1], &captures[3])
+            } else {
+                captures[0].to_string()
+            }
+        });
+        if replaced == name {
+            break;
+        }
+        name = replaced.into_owned();
+    }
+    if dir.is_empty() {
+        name
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit_once('/').map_or(path, |(_, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(files: &[(&str, &str)]) -> Vec<(String, String)> {
+        files
+            .iter()
+            .map(|&(path, content)| (path.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn normalize_hashes_in_file_names() {
+        assert_eq!(
+            normalize_hashes("static/input_4437c1._.js"),
+            "static/input_[hash]._.js"
+        );
+        assert_eq!(
+            normalize_hashes("index_4437c1.5f3e2a91.js"),
+            "index_[hash].[hash].js"
+        );
+        // Directories and hex words without digits are kept.
+        assert_eq!(normalize_hashes("4437c1/facade.js"), "4437c1/facade.js");
+    }
+
+    #[test]
+    fn normalize_references_between_files() {
+        let snapshot = normalize(files(&[
+            ("static/a_4437c1.js", "load(\"static/b_9a8b7c.js\");"),
+            ("static/b_9a8b7c.js", "// b_9a8b7c.js"),
+        ]));
+        assert_eq!(
+            snapshot.files,
+            BTreeMap::from([
+                (
+                    "static/a_[hash].js".to_string(),
+                    "load(\"static/b_[hash].js\");".to_string()
+                ),
+                (
+                    "static/b_[hash].js".to_string(),
+                    "// b_[hash].js".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn normalize_numbers_files_differing_by_hash() {
+        let snapshot = normalize(files(&[
+            ("chunk_9a8b7c.js", "second"),
+            ("chunk_4437c1.js", "first"),
+        ]));
+        assert_eq!(
+            snapshot.files,
+            BTreeMap::from([
+                ("chunk_[hash~1].js".to_string(), "first".to_string()),
+                ("chunk_[hash~2].js".to_string(), "second".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_formatted_snapshot() {
+        let snapshot = normalize(files(&[
+            ("a.js", "a;\n\nb;"),
+            ("b.css", ".b {\n  color: red;\n}"),
+        ]));
+        assert_eq!(
+            ChunkOutputSnapshot::parse(&snapshot.to_string()).unwrap(),
+            snapshot
+        );
+        assert!(ChunkOutputSnapshot::parse("a;\n").is_err());
+    }
+
+    #[test]
+    fn diff_snapshots() {
+        let before = normalize(files(&[("a.js", "a;"), ("b.js", "b;")]));
+        let after = normalize(files(&[("a.js", "a2;"), ("c.js", "c;")]));
+        assert!(before.diff(&before).is_empty());
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.added, vec!["c.js".to_string()]);
+        assert_eq!(diff.removed, vec!["b.js".to_string()]);
+        assert_eq!(diff.changed.keys().collect::<Vec<_>>(), vec!["a.js"]);
+        assert!(diff.changed["a.js"].contains("-a;"));
+        assert!(diff.changed["a.js"].contains("+a2;"));
+    }
+}
@@ -1,5 +1,6 @@
 #![feature(min_specialization)]
 #![feature(arbitrary_self_types)]
 
+pub mod chunk_output;
 pub mod jest;
 pub mod snapshot;
@@ -0,0 +1,4 @@
+import { shared } from "./shared.js";
+
+console.log("availability-index", shared);
+import("./lazy.js");
@@ -0,0 +1,5 @@
+import { shared } from "./shared.js";
+import { helper } from "./helper.js";
+
+console.log("availability-lazy", shared, helper);
+import("./nested.js");
@@ -0,0 +1,4 @@
+import { shared } from "./shared.js";
+import { helper } from "./helper.js";
+
+console.log("availability-nested", shared, helper);
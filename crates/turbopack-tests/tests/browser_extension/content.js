@@ -0,0 +1,4 @@
+import "./style.css";
+import { shared } from "./shared.js";
+
+console.log("content script", shared);
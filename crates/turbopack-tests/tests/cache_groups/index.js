@@ -0,0 +1,5 @@
+import { render } from "react-lite";
+import { Button } from "./components/button.js";
+import { app } from "./app.js";
+
+render(Button, app);
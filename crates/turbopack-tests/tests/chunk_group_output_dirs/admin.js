@@ -0,0 +1 @@
+import("./lazy.js").then(({ lazy }) => console.log("admin", lazy));
@@ -0,0 +1,4 @@
+import "./style.css";
+
+console.log("chunk-output-index");
+import("./lazy.js");
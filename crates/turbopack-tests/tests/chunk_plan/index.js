@@ -0,0 +1,4 @@
+import { shared } from "./shared.js";
+
+console.log(shared);
+import("./lazy.js").then(({ lazy }) => console.log(lazy));
@@ -0,0 +1,10 @@
+console.log("first");
+
+function log(console) {
+  console.log("shadowed");
+}
+
+export function warn() {
+  console.warn("second");
+  console.table([]);
+}
@@ -0,0 +1,5 @@
+console.log(
+  new URL("./a/logo.svg", import.meta.url),
+  new URL("./b/icon.svg", import.meta.url),
+  new URL("./other.svg", import.meta.url)
+);
@@ -0,0 +1,5 @@
+const tokens = {
+  color: { primary: "#0070f3" },
+} satisfies Record<string, Record<string, string>>;
+
+export default tokens;
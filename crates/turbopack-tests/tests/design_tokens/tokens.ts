@@ -0,0 +1,5 @@
+export default {
+  color: { primary: "#0070f3", "on-primary": `#fff` },
+  space: { sm: 4, lg: -0.5 },
+  font: { body: '"Inter", sans-serif' },
+} as const;
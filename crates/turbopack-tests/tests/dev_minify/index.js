@@ -0,0 +1,7 @@
+// This comment is removed by minification.
+function greet(someLongLocalName) {
+  const anotherLongLocalName = `Hello ${someLongLocalName}`;
+  return anotherLongLocalName;
+}
+
+console.log(greet("minifyFixture"));
@@ -0,0 +1,3 @@
+const config = globalThis.downlevelFixture?.config;
+
+console.log(config ?? "default config");
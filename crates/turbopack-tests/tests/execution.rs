@@ -9,18 +9,22 @@ use anyhow::{Context, Result};
 use dunce::canonicalize;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{
-    debug::ValueDebugFormat, trace::TraceRawVcs, Completion, TryJoinIterExt, TurboTasks, Value, Vc,
+    debug::ValueDebugFormat, trace::TraceRawVcs, Completion, TryJoinIterExt, TurboTasks, Value,
+    ValueDefault, Vc,
 };
 use turbo_tasks_bytes::stream::SingleValue;
-use turbo_tasks_env::CommandLineProcessEnv;
+use turbo_tasks_env::{CommandLineProcessEnv, ProcessEnv};
 use turbo_tasks_fs::{
     json::parse_json_with_source_context, util::sys_to_unix, DiskFileSystem, FileContent,
     FileSystem, FileSystemEntryType, FileSystemPath,
 };
 use turbo_tasks_memory::MemoryBackend;
 use turbopack::{
-    condition::ContextCondition, ecmascript::TreeShakingMode, module_options::ModuleOptionsContext,
-    resolve_options_context::ResolveOptionsContext, ModuleAssetContext,
+    condition::ContextCondition,
+    ecmascript::TreeShakingMode,
+    module_options::{CodegenModulesOptions, ModuleOptionsContext},
+    resolve_options_context::ResolveOptionsContext,
+    ModuleAssetContext,
 };
 use turbopack_core::{
     chunk::{EvaluatableAssetExt, EvaluatableAssets},
@@ -34,7 +38,10 @@ use turbopack_core::{
     source::Source,
 };
 use turbopack_dev::DevChunkingContext;
-use turbopack_node::{debug::should_debug, evaluate::evaluate};
+use turbopack_node::{
+    debug::should_debug, evaluate::evaluate, execution_context::ExecutionContext,
+    permissions::OptionNodePermissions, transforms::graphql::GraphQlTransformOptions,
+};
 use turbopack_test_utils::jest::JestRunResult;
 
 use crate::util::REPO_ROOT;
@@ -253,6 +260,18 @@ async fn run_test(prepared_test: Vc<PreparedTest>) -> Result<Vc<RunTestResult>>
         )
         .cell();
 
+    let chunking_context = DevChunkingContext::builder(
+        project_root,
+        chunk_root_path,
+        chunk_root_path,
+        static_root_path,
+        env,
+    )
+    .build();
+    let process_env: Vc<Box<dyn ProcessEnv>> = Vc::upcast(CommandLineProcessEnv::new());
+    let execution_context =
+        ExecutionContext::new(project_root, Vc::upcast(chunking_context), process_env);
+
     let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
         Vc::cell(HashMap::new()),
         compile_time_info,
@@ -260,6 +279,15 @@ async fn run_test(prepared_test: Vc<PreparedTest>) -> Result<Vc<RunTestResult>>
             enable_typescript_transform: Some(Default::default()),
             preset_env_versions: Some(env),
             tree_shaking_mode: options.tree_shaking_mode,
+            enable_codegen_modules: Some(CodegenModulesOptions::value_default()),
+            enable_graphql: Some(
+                GraphQlTransformOptions {
+                    inline_tags: true,
+                    ..Default::default()
+                }
+                .cell(),
+            ),
+            execution_context: Some(execution_context),
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
                 ModuleOptionsContext {
@@ -290,15 +318,6 @@ async fn run_test(prepared_test: Vc<PreparedTest>) -> Result<Vc<RunTestResult>>
         Vc::cell("test".to_string()),
     ));
 
-    let chunking_context = DevChunkingContext::builder(
-        project_root,
-        chunk_root_path,
-        chunk_root_path,
-        static_root_path,
-        env,
-    )
-    .build();
-
     let jest_entry_asset = process_path_to_asset(jest_entry_path, asset_context).module();
     let jest_runtime_asset = FileSource::new(jest_runtime_path);
     let test_source = FileSource::new(test_path);
@@ -307,7 +326,8 @@ async fn run_test(prepared_test: Vc<PreparedTest>) -> Result<Vc<RunTestResult>>
     let res = evaluate(
         jest_entry_asset,
         path,
-        Vc::upcast(CommandLineProcessEnv::new()),
+        process_env,
+        OptionNodePermissions::none(),
         test_source.ident(),
         asset_context,
         Vc::upcast(chunking_context),
@@ -0,0 +1,4 @@
+it("should load async chunks in Node.js", async () => {
+  const { answer } = await import("./lazy.js");
+  expect(answer).toBe(42);
+});
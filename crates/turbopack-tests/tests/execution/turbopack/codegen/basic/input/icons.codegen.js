@@ -0,0 +1,17 @@
+const { readFileSync } = require("fs");
+const { dirname, join } = require("path");
+
+module.exports = ({ resourcePath }) => {
+  const iconsPath = join(dirname(resourcePath), "icons.json");
+  const icons = JSON.parse(readFileSync(iconsPath, "utf-8"));
+  return {
+    code: `export const icons = ${JSON.stringify(icons)};\nexport const count = ${icons.length};\n`,
+    map: {
+      version: 3,
+      sources: ["icons.json"],
+      names: [],
+      mappings: "AAAA;AACA",
+    },
+    dependencies: [iconsPath],
+  };
+};
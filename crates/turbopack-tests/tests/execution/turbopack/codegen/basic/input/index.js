@@ -0,0 +1,6 @@
+import { icons, count } from "./icons.codegen.js";
+
+it("should use the code generated at build time", () => {
+  expect(icons).toEqual(["arrow", "check", "close"]);
+  expect(count).toBe(3);
+});
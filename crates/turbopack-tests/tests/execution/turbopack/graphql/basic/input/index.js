@@ -0,0 +1,36 @@
+import doc, { GetUser, RenameUser } from "./user.graphql";
+import fields from "./fields.graphql";
+
+const gql = () => {
+  throw new Error("tagged templates should have been precompiled");
+};
+
+const names = (document) =>
+  document.definitions.map((definition) => definition.name.value);
+
+it("should precompile documents with their imported fragments", () => {
+  expect(doc.kind).toBe("Document");
+  expect(names(doc)).toEqual(["GetUser", "RenameUser", "UserFields"]);
+});
+
+it("should export each operation with the fragments", () => {
+  expect(names(GetUser)).toEqual(["GetUser", "UserFields"]);
+  expect(names(RenameUser)).toEqual(["RenameUser", "UserFields"]);
+});
+
+it("should leave out the locations of the parsed documents", () => {
+  expect(doc.loc).toBeUndefined();
+  expect(fields.definitions[0].loc).toBeUndefined();
+});
+
+it("should precompile tagged templates with their fragments", () => {
+  const query = gql`
+    query GetUsers {
+      users {
+        ...UserFields
+      }
+    }
+    ${fields}
+  `;
+  expect(names(query)).toEqual(["GetUsers", "UserFields"]);
+});
@@ -0,0 +1,4 @@
+// Generated by wasm-bindgen before 0.2.87 for `--target bundler`, where the
+// glue code and the WebAssembly module import each other.
+import * as wasm from "./cyclic_bg.wat";
+export * from "./cyclic_bg.js";
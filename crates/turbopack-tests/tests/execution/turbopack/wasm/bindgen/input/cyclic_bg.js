@@ -0,0 +1,9 @@
+import * as wasm from "./cyclic_bg.wat";
+
+export function answer() {
+  return wasm.answer();
+}
+
+export function __wbg_base_c3d4() {
+  return 41;
+}
@@ -0,0 +1,7 @@
+(module
+  (import "./cyclic_bg.js" "__wbg_base_c3d4" (func $base (result i32)))
+  (func (export "answer") (result i32)
+    (i32.add
+      (call $base)
+      (i32.const 1)))
+)
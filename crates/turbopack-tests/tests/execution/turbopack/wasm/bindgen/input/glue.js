@@ -0,0 +1,5 @@
+// Generated by wasm-bindgen 0.2.87+ for `--target bundler`.
+import * as wasm from "./glue_bg.wat";
+import { __wbg_set_wasm } from "./glue_bg.js";
+__wbg_set_wasm(wasm);
+export * from "./glue_bg.js";
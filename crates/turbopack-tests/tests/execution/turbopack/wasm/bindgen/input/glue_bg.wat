@@ -0,0 +1,7 @@
+(module
+  (import "./glue_bg.js" "__wbg_base_a1b2" (func $base (result i32)))
+  (func (export "answer") (result i32)
+    (i32.add
+      (call $base)
+      (i32.const 1)))
+)
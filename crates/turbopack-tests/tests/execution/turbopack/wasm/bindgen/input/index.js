@@ -0,0 +1,18 @@
+// The glue code is an async module, so it's required and awaited inside the
+// tests to make sure the entrypoint isn't async.
+const glue = require("./glue");
+const cyclicGlue = require("./cyclic");
+
+describe("wasm-bindgen", () => {
+  it("should pass the instantiated module to the glue code", async () => {
+    const { answer } = await glue;
+
+    expect(answer()).toBe(42);
+  });
+
+  it("should instantiate modules that import glue code importing them", async () => {
+    const { answer } = await cyclicGlue;
+
+    expect(answer()).toBe(42);
+  });
+});
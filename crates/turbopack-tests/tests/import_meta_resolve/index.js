@@ -0,0 +1,7 @@
+const logo = import.meta.resolve("./logo.svg");
+
+export function localeUrl(lang) {
+  return import.meta.resolve(`./locales/${lang}.json`);
+}
+
+console.log("import-meta-resolve", logo, localeUrl("en"));
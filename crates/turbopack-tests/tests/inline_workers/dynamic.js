@@ -0,0 +1 @@
+import("./lazy.js").then(({ lazy }) => self.postMessage(["DYNAMIC_WORKER_MARKER", lazy]));
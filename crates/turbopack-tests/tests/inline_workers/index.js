@@ -0,0 +1,2 @@
+new Worker(new URL("./simple.js", import.meta.url));
+new Worker(new URL("./dynamic.js", import.meta.url));
@@ -0,0 +1 @@
+self.postMessage("SIMPLE_WORKER_MARKER");
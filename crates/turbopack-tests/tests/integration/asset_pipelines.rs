@@ -0,0 +1,61 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::glob::Glob;
+use turbopack::module_options::{AssetPipeline, ModuleOptionsContext, ModuleRuleCondition};
+use turbopack_core::environment::Environment;
+
+use crate::util::{
+    asset_context_with, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+/// The paths and the text of the text files emitted for `index.js` of the
+/// `asset_pipelines` fixture, which imports a text file.
+async fn text_files(
+    env: Vc<Environment>,
+    asset_pipelines: Vec<AssetPipeline>,
+) -> Result<Vec<(String, Option<String>)>> {
+    let fixture = fixture("asset_pipelines");
+    let asset_context = asset_context_with(
+        env,
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            asset_pipelines,
+            ..Default::default()
+        },
+        Default::default(),
+    );
+    Ok(evaluated_files(
+        Vc::upcast(dev_chunking_context(fixture, env).build()),
+        fixture,
+        "index.js",
+        asset_context,
+    )
+    .await?
+    .into_iter()
+    .filter(|(path, _)| path.ends_with(".txt"))
+    .collect())
+}
+
+/// The assets matching the condition of a pipeline are emitted as static
+/// assets, which they aren't without it.
+#[tokio::test]
+async fn test_asset_pipelines() -> Result<()> {
+    run(async {
+        let env = browser_env();
+        assert_eq!(text_files(env, Vec::new()).await?, Vec::new());
+
+        let pipeline = AssetPipeline::new(ModuleRuleCondition::ResourceBasePathGlob(
+            Glob::new("*.txt".to_string()).await?,
+        ));
+        let files = text_files(env, vec![pipeline]).await?;
+        assert_eq!(files.len(), 1, "{files:?}");
+        let (path, content) = &files[0];
+        assert!(
+            path.starts_with("crates/turbopack-tests/tests/asset_pipelines/output/static/notes."),
+            "{path}"
+        );
+        assert_eq!(content.as_deref(), Some("Notes\n"));
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::chunk::{
+    availability_info::{AvailabilityDump, AvailabilityInfo},
+    plan::ChunkGroupPlanTree,
+    ChunkingContext,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run};
+
+/// Plans the chunk group of the `availability_dump` fixture, where `index.js`
+/// loads `lazy.js`, which loads `nested.js`.
+async fn chunk_group_plan() -> Result<ChunkGroupPlanTree> {
+    let fixture = fixture("availability_dump");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env).dry_run().build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let result = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?;
+    ChunkGroupPlanTree::capture(result.plan.context("a dry run must return a plan")?).await
+}
+
+/// Returns the async chunk group loaded for the module `name`.
+fn async_chunk_group<'a>(
+    plan: &'a ChunkGroupPlanTree,
+    name: &str,
+) -> Result<&'a ChunkGroupPlanTree> {
+    plan.async_chunk_groups
+        .iter()
+        .find(|(module, _)| module.contains(name))
+        .map(|(_, plan)| plan)
+        .with_context(|| format!("no async chunk group is loaded for {name}"))
+}
+
+/// Returns the level that made the module `name` available.
+fn level_of(available: &AvailabilityDump, name: &str) -> Option<usize> {
+    available.levels.iter().position(|level| {
+        level
+            .modules
+            .iter()
+            .any(|module| module.ident.contains(name))
+    })
+}
+
+/// The plan of each async chunk group lists the modules assumed to be
+/// available, grouped by the ancestor chunk group that made them available.
+#[tokio::test]
+async fn test_async_chunk_groups_dump_available_modules() -> Result<()> {
+    run(async {
+        let plan = chunk_group_plan().await?;
+        assert!(plan.available.is_none());
+
+        let lazy = async_chunk_group(&plan, "availability_dump/lazy.js")?;
+        let available = lazy
+            .available
+            .as_ref()
+            .context("lazy.js must see the modules of index.js")?;
+        assert!(available.tracked);
+        assert_eq!(available.levels.len(), 1);
+        assert_eq!(level_of(available, "availability_dump/shared.js"), Some(0));
+        assert_eq!(level_of(available, "availability_dump/index.js"), Some(0));
+        assert_eq!(level_of(available, "availability_dump/helper.js"), None);
+
+        let nested = async_chunk_group(lazy, "availability_dump/nested.js")?;
+        let available = nested
+            .available
+            .as_ref()
+            .context("nested.js must see the modules of index.js and lazy.js")?;
+        assert_eq!(available.levels.len(), 2);
+        assert_eq!(level_of(available, "availability_dump/shared.js"), Some(0));
+        assert_eq!(level_of(available, "availability_dump/helper.js"), Some(1));
+        assert_eq!(level_of(available, "availability_dump/lazy.js"), Some(1));
+        assert!(nested
+            .chunks
+            .iter()
+            .flat_map(|chunk| chunk.modules.iter())
+            .all(|module| !module.contains("availability_dump/helper.js")));
+
+        let text = available.to_string();
+        assert!(text.starts_with("level 0: "), "{text}");
+        assert!(text.contains("level 1: "), "{text}");
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_dev::BasePathMode;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+const OUTPUT: &str = "crates/turbopack-tests/tests/base_path_mode/output/";
+
+/// The paths and the code of the JavaScript files emitted for `index.js` of
+/// the `base_path_mode` fixture, which references an SVG file.
+async fn js_files(base_path_mode: BasePathMode) -> Result<Vec<(String, String)>> {
+    let fixture = fixture("base_path_mode");
+    let env = browser_env();
+    Ok(evaluated_files(
+        Vc::upcast(
+            dev_chunking_context(fixture, env)
+                .base_path_mode(base_path_mode)
+                .build(),
+        ),
+        fixture,
+        "index.js",
+        asset_context(env),
+    )
+    .await?
+    .into_iter()
+    .filter(|(path, _)| path.ends_with(".js"))
+    .filter_map(|(path, code)| Some((path, code?)))
+    .collect())
+}
+
+/// The configured base paths are embedded in the chunks by default.
+#[tokio::test]
+async fn test_static_base_paths() -> Result<()> {
+    run(async {
+        let files = js_files(BasePathMode::Static).await?;
+        assert!(
+            files
+                .iter()
+                .all(|(_, code)| !code.contains("TURBOPACK_BASE_PATHS")),
+            "{files:?}"
+        );
+        assert!(
+            files
+                .iter()
+                .any(|(_, code)| code.contains(r#""/static/logo."#)),
+            "{files:?}"
+        );
+        Ok(())
+    })
+    .await
+}
+
+/// Asset URLs are resolved against the base paths read at runtime, which
+/// aren't derived from the URL of the chunks.
+#[tokio::test]
+async fn test_runtime_base_paths() -> Result<()> {
+    run(async {
+        let files = js_files(BasePathMode::Runtime).await?;
+        assert!(
+            files.iter().any(|(_, code)| {
+                code.contains(r#"globalThis.TURBOPACK_BASE_PATHS.assetBasePath : "/") + "#)
+            }),
+            "{files:?}"
+        );
+        assert!(
+            files
+                .iter()
+                .all(|(_, code)| !code.contains("document.currentScript")),
+            "{files:?}"
+        );
+        Ok(())
+    })
+    .await
+}
+
+/// The evaluate chunk derives the base paths from its own URL, so chunks are
+/// loaded relative to it.
+#[tokio::test]
+async fn test_auto_base_paths() -> Result<()> {
+    run(async {
+        let files = js_files(BasePathMode::Auto).await?;
+        let (path, code) = files
+            .iter()
+            .find(|(_, code)| code.contains("runtimeModuleIds"))
+            .expect("there is no evaluate chunk");
+        let chunk_path = path.strip_prefix(OUTPUT).unwrap();
+
+        assert!(code.contains("document.currentScript"), "{code}");
+        assert!(
+            code.contains(&format!("}})({:?}, undefined)", chunk_path)),
+            "{code}"
+        );
+        assert!(
+            code.contains(
+                "globalThis.TURBOPACK_BASE_PATHS = { chunkBasePath: basePath, assetBasePath: \
+                 basePath };"
+            ),
+            "{code}"
+        );
+        Ok(())
+    })
+    .await
+}
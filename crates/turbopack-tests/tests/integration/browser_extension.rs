@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::{
+    asset::Asset,
+    chunk::{
+        extension_manifest::{content_script_resources, ServiceWorkerBootstrapAsset},
+        ChunkingContext, EvaluatableAssets,
+    },
+    environment::{
+        BrowserExtensionContext, BrowserExtensionEnvironment, ChunkLoading, Environment,
+        ExecutionEnvironment,
+    },
+    ident::AssetIdent,
+    output::OutputAsset,
+};
+
+use crate::util::{asset_context_with, dev_chunking_context, fixture, run, text};
+
+struct Extension {
+    fixture: Vc<FileSystemPath>,
+    output_root: Vc<FileSystemPath>,
+    environment: Vc<Environment>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    entries: Vc<EvaluatableAssets>,
+}
+
+/// Sets up the chunking of `entry` of the `browser_extension` fixture for
+/// `context`, with the fixture's `output` directory as the root of the
+/// extension package.
+fn extension(context: BrowserExtensionContext, entry: &str) -> Extension {
+    let fixture = fixture("browser_extension");
+    let output_root = fixture.join("output".to_string());
+
+    let environment = Environment::new(Value::new(ExecutionEnvironment::BrowserExtension(
+        BrowserExtensionEnvironment {
+            context,
+            browserslist_query: "Chrome 102".to_owned(),
+        }
+        .into(),
+    )));
+    let asset_context = asset_context_with(
+        environment,
+        ModuleOptionsContext {
+            preset_env_versions: Some(environment),
+            ..Default::default()
+        },
+        ResolveOptionsContext {
+            browser: true,
+            ..Default::default()
+        },
+    );
+    let chunking_context = Vc::upcast(dev_chunking_context(fixture, environment).build());
+    let entry = crate::util::entry(fixture, entry, asset_context);
+    Extension {
+        fixture,
+        output_root,
+        environment,
+        chunking_context,
+        entries: EvaluatableAssets::one(entry),
+    }
+}
+
+/// The chunks of a content script are listed as its resources, relative to the
+/// root of the extension package.
+#[tokio::test]
+async fn test_content_script_resources() -> Result<()> {
+    run(async {
+        let extension = extension(BrowserExtensionContext::ContentScript, "content.js");
+        assert!(matches!(
+            *extension.environment.chunk_loading().await?,
+            ChunkLoading::None
+        ));
+
+        let ident = AssetIdent::from_path(extension.fixture.join("content.js".to_string()));
+        let resources =
+            content_script_resources(extension.chunking_context, ident, extension.entries).await?;
+        assert!(!resources.js.is_empty());
+        assert!(!resources.css.is_empty());
+        for path in resources.js.iter().chain(resources.css.iter()) {
+            assert!(!path.starts_with('/') && !path.starts_with(".."), "{path}");
+        }
+        assert!(resources.js.iter().all(|path| path.ends_with(".js")));
+        assert!(resources.css.iter().all(|path| path.ends_with(".css")));
+        Ok(())
+    })
+    .await
+}
+
+/// The bootstrap script of the background service worker imports all chunks
+/// of its entries upfront, and references them so they are emitted.
+#[tokio::test]
+async fn test_service_worker_bootstrap() -> Result<()> {
+    run(async {
+        let extension = extension(
+            BrowserExtensionContext::BackgroundServiceWorker,
+            "background.js",
+        );
+        let path = extension.output_root.join("background.js".to_string());
+        let bootstrap =
+            ServiceWorkerBootstrapAsset::new(path, extension.chunking_context, extension.entries);
+        assert_eq!(bootstrap.ident().path().await?.path, path.await?.path);
+
+        let resources = content_script_resources(
+            extension.chunking_context,
+            AssetIdent::from_path(path),
+            extension.entries,
+        )
+        .await?;
+        assert!(!resources.js.is_empty());
+        let code = text(bootstrap.content())
+            .await?
+            .context("the bootstrap script must be a file")?;
+        assert!(code.starts_with("importScripts("), "{code}");
+        for chunk in resources.js.iter() {
+            assert!(
+                code.contains(&format!("\"/{chunk}\"")),
+                "{chunk} isn't imported: {code}"
+            );
+        }
+
+        let output_root = extension.output_root.await?;
+        let mut referenced = Vec::new();
+        for &asset in bootstrap.references().await?.iter() {
+            let path = asset.ident().path().await?;
+            referenced.extend(output_root.get_path_to(&path).map(|path| path.to_string()));
+        }
+        for chunk in resources.js.iter() {
+            assert!(referenced.contains(chunk), "{chunk} isn't referenced");
+        }
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_ecmascript_runtime::RuntimeType;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+/// The code of the chunk list and of the evaluate chunk of `index.js` of the
+/// `build_id` fixture, with all whitespace removed.
+async fn chunk_list_and_evaluate_chunk(build_id: Option<&str>) -> Result<(String, String)> {
+    let fixture = fixture("build_id");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env)
+        .runtime_type(RuntimeType::Default)
+        .build_id(Vc::cell(build_id.map(|id| id.to_string())))
+        .build();
+
+    let mut chunk_list = None;
+    let mut evaluate_chunk = None;
+    for (_, text) in evaluated_files(
+        Vc::upcast(chunking_context),
+        fixture,
+        "index.js",
+        asset_context(env),
+    )
+    .await?
+    {
+        let Some(text) = text else { continue };
+        let code: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if code.contains("_CHUNK_LISTS||[]).push(") {
+            chunk_list = Some(code);
+        } else if code.contains("constBUILD_ID=") {
+            evaluate_chunk = Some(code);
+        }
+    }
+    Ok((
+        chunk_list.expect("there is no chunk list"),
+        evaluate_chunk.expect("there is no evaluate chunk"),
+    ))
+}
+
+/// The build id is embedded in the chunk list, in the parameters of the
+/// evaluate chunk and in the runtime, so the runtime can detect chunks of
+/// other builds.
+#[tokio::test]
+async fn test_build_id_is_embedded() -> Result<()> {
+    run(async {
+        let (chunk_list, evaluate_chunk) = chunk_list_and_evaluate_chunk(Some("b1")).await?;
+        assert!(chunk_list.contains(r#""buildId":"b1""#), "{chunk_list}");
+        assert!(
+            evaluate_chunk.contains(r#""buildId":"b1"}]"#),
+            "the evaluate chunk parameters must contain the build id"
+        );
+        assert!(evaluate_chunk.contains(r#"constBUILD_ID="b1";"#));
+        Ok(())
+    })
+    .await
+}
+
+/// Without a build id, chunks don't carry one and the runtime doesn't check
+/// it.
+#[tokio::test]
+async fn test_build_id_is_optional() -> Result<()> {
+    run(async {
+        let (chunk_list, evaluate_chunk) = chunk_list_and_evaluate_chunk(None).await?;
+        assert!(!chunk_list.contains(r#""buildId":"#), "{chunk_list}");
+        assert!(!evaluate_chunk.contains(r#""buildId":"#));
+        assert!(evaluate_chunk.contains("constBUILD_ID=null;"));
+        Ok(())
+    })
+    .await
+}
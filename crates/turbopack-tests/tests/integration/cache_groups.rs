@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::chunk::{
+    availability_info::AvailabilityInfo, cache_group::ChunkCacheGroup, plan::ChunkPlan,
+    ChunkingContext,
+};
+
+use crate::util::{
+    asset_context, browser_env, chunk_of, dev_chunking_context, entry, fixture, run,
+};
+
+/// Plans the chunks of the `cache_groups` fixture with a `framework` group for
+/// the `react-lite` package and a `ui` group for the components.
+async fn planned_chunks() -> Result<Vec<ChunkPlan>> {
+    let fixture = fixture("cache_groups");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env)
+        .cache_group(ChunkCacheGroup::new("framework").package_name("react-lite"))
+        .cache_group(
+            ChunkCacheGroup::new("ui")
+                .path_glob("crates/turbopack-tests/tests/cache_groups/components/**"),
+        )
+        .dry_run()
+        .build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let result = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?;
+    let plan = result.plan.context("a dry run must return a plan")?.await?;
+    Ok(plan.chunks.clone())
+}
+
+/// The modules of each cache group are placed into a chunk named after the
+/// group, which isn't merged with other chunks.
+#[tokio::test]
+async fn test_cache_groups_get_their_own_chunks() -> Result<()> {
+    run(async {
+        let chunks = planned_chunks().await?;
+
+        let framework = chunk_of(&chunks, "node_modules/react-lite/index.js")?;
+        assert!(framework.key.contains("framework"), "{}", framework.key);
+        assert_eq!(framework.modules.len(), 1, "{:?}", framework.modules);
+
+        let ui = chunk_of(&chunks, "cache_groups/components/button.js")?;
+        assert!(ui.key.contains("ui"), "{}", ui.key);
+        assert_eq!(ui.modules.len(), 1, "{:?}", ui.modules);
+
+        let app = chunk_of(&chunks, "cache_groups/app.js")?;
+        assert!(!app.key.contains("framework") && !app.key.contains("-ui"));
+        assert!(app
+            .modules
+            .iter()
+            .any(|module| module.contains("cache_groups/index.js")));
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack_core::{
+    asset::Asset,
+    chunk::{
+        alias::chunk_group_alias_assets, availability_info::AvailabilityInfo, ChunkGroupResult,
+        ChunkingContext, EvaluatableAssets,
+    },
+    output::OutputAsset,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run, text};
+
+/// The evaluated chunk group of `index.js` of the `chunk_group_alias` fixture,
+/// which imports a CSS file.
+fn chunk_group() -> Vc<ChunkGroupResult> {
+    let fixture = fixture("chunk_group_alias");
+    let env = browser_env();
+    let chunking_context: Vc<Box<dyn ChunkingContext>> =
+        Vc::upcast(dev_chunking_context(fixture, env).build());
+    let entry = entry(fixture, "index.js", asset_context(env));
+    chunking_context.evaluated_chunk_group(
+        entry.ident(),
+        EvaluatableAssets::one(entry),
+        Value::new(AvailabilityInfo::Root),
+    )
+}
+
+/// An alias is a script at a fixed path that loads the chunks of its chunk
+/// group relative to its own URL, and references them, so they are emitted
+/// with it.
+#[tokio::test]
+async fn test_chunk_group_alias() -> Result<()> {
+    run(async {
+        let output_root = fixture("chunk_group_alias").join("output".to_string());
+        let chunk_group = chunk_group();
+
+        let aliases = chunk_group_alias_assets(
+            output_root,
+            output_root,
+            Vc::cell(BTreeMap::from([("app-shell".to_string(), chunk_group)])),
+        )
+        .await?;
+        assert_eq!(aliases.len(), 1);
+        let alias = aliases[0];
+        assert_eq!(
+            alias.ident().path().await?.path,
+            "crates/turbopack-tests/tests/chunk_group_alias/output/app-shell.js"
+        );
+        assert_eq!(
+            alias.references().await?.len(),
+            chunk_group.await?.assets.await?.len()
+        );
+
+        let code = text(alias.content()).await?.unwrap();
+        let output_root = output_root.await?;
+        let mut urls = Vec::new();
+        for &asset in chunk_group.await?.assets.await?.iter() {
+            let path = asset.ident().path().await?;
+            urls.push(output_root.get_path_to(&path).unwrap().to_string());
+        }
+        assert!(urls.iter().any(|url| url.ends_with(".css")), "{urls:?}");
+        for url in urls {
+            assert!(code.contains(&format!(r#"{{"url":"{url}"}}"#)), "{code}");
+        }
+        assert!(code.contains("new URL(url, base)"), "{code}");
+        Ok(())
+    })
+    .await
+}
+
+/// Aliases must be file names, as they are emitted in the alias root.
+#[tokio::test]
+async fn test_invalid_chunk_group_alias() -> Result<()> {
+    run(async {
+        let output_root = fixture("chunk_group_alias").join("output".to_string());
+        let chunk_group = chunk_group();
+
+        for alias in ["", "../app-shell", "shells/app"] {
+            let aliases = chunk_group_alias_assets(
+                output_root,
+                output_root,
+                Vc::cell(BTreeMap::from([(alias.to_string(), chunk_group)])),
+            );
+            let error = aliases.await.unwrap_err();
+            assert!(
+                format!("{error:?}").contains("invalid chunk group alias"),
+                "{error:?}"
+            );
+        }
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+/// The paths of the JS files of the evaluated chunk group of `name` of the
+/// `chunk_group_output_dirs` fixture, relative to the output root, where the
+/// chunk groups of `admin.js` are placed in the `admin` subdirectory.
+async fn chunk_paths(name: &str) -> Result<Vec<String>> {
+    let fixture = fixture("chunk_group_output_dirs");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env)
+        .chunk_group_output_dir("**/admin.js".to_string(), "admin".to_string())
+        .build();
+
+    let output_root = fixture.join("output".to_string()).await?;
+    let prefix = format!("{}/", output_root.path);
+    let mut paths = Vec::new();
+    for (path, _) in evaluated_files(
+        Vc::upcast(chunking_context),
+        fixture,
+        name,
+        asset_context(env),
+    )
+    .await?
+    {
+        if path.ends_with(".js") {
+            paths.push(path.strip_prefix(&prefix).unwrap_or(&path).to_string());
+        }
+    }
+    Ok(paths)
+}
+
+/// The chunks of a matching chunk group are placed in its subdirectory, and
+/// so are the chunks of the async chunk groups it loads.
+#[tokio::test]
+async fn test_chunk_group_output_dir() -> Result<()> {
+    run(async {
+        let admin = chunk_paths("admin.js").await?;
+        assert!(admin.len() > 2, "{admin:?}");
+        assert!(
+            admin.iter().all(|path| path.starts_with("admin/")),
+            "{admin:?}"
+        );
+        assert!(admin.iter().any(|path| path.contains("lazy")), "{admin:?}");
+
+        let app = chunk_paths("app.js").await?;
+        assert!(!app.is_empty());
+        assert!(
+            app.iter().all(|path| !path.starts_with("admin/")),
+            "{app:?}"
+        );
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{DiskFileSystem, FileSystem};
+use turbopack::emit_tagged_chunk_groups;
+use turbopack_core::{
+    asset::Asset,
+    chunk::{
+        availability_info::AvailabilityInfo,
+        tags::{tagged_chunk_group_assets, TaggedChunkGroup, TaggedChunkGroups},
+        ChunkGroupResult, ChunkingContext, EvaluatableAssets,
+    },
+    output::OutputAssets,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run};
+
+const OUTPUT: &str = "crates/turbopack-tests/tests/chunk_group_tags/output/";
+
+/// The evaluated chunk group of the file `name` of the `chunk_group_tags`
+/// fixture.
+fn chunk_group(name: &str) -> Vc<ChunkGroupResult> {
+    let fixture = fixture("chunk_group_tags");
+    let env = browser_env();
+    let chunking_context: Vc<Box<dyn ChunkingContext>> =
+        Vc::upcast(dev_chunking_context(fixture, env).build());
+    let entry = entry(fixture, name, asset_context(env));
+    chunking_context.evaluated_chunk_group(
+        entry.ident(),
+        EvaluatableAssets::one(entry),
+        Value::new(AvailabilityInfo::Root),
+    )
+}
+
+/// The chunk groups of `admin.js` and `public.js`, tagged with the name of
+/// the file, and of `error.js`, which isn't tagged.
+fn tagged_chunk_groups() -> Vc<TaggedChunkGroups> {
+    Vc::cell(vec![
+        TaggedChunkGroup::new(chunk_group("admin.js"), vec!["admin".to_string()]),
+        TaggedChunkGroup::new(chunk_group("public.js"), vec!["public".to_string()]),
+        TaggedChunkGroup::new(chunk_group("error.js"), Vec::new()),
+    ])
+}
+
+/// The paths of `assets`, relative to the output directory.
+async fn paths(assets: Vc<OutputAssets>) -> Result<BTreeSet<String>> {
+    let mut paths = BTreeSet::new();
+    for &asset in assets.await?.iter() {
+        let path = asset.ident().path().await?;
+        paths.insert(path.path.strip_prefix(OUTPUT).unwrap().to_string());
+    }
+    Ok(paths)
+}
+
+/// The assets of the chunk groups with the selected tags are selected, as well
+/// as the ones of the chunk groups without tags.
+#[tokio::test]
+async fn test_tagged_chunk_group_assets() -> Result<()> {
+    run(async {
+        let admin = paths(chunk_group("admin.js").await?.assets).await?;
+        let error = paths(chunk_group("error.js").await?.assets).await?;
+
+        let selected = paths(tagged_chunk_group_assets(
+            tagged_chunk_groups(),
+            vec!["admin".to_string()],
+        ))
+        .await?;
+        assert_eq!(selected, &admin | &error);
+
+        let selected = paths(tagged_chunk_group_assets(tagged_chunk_groups(), Vec::new())).await?;
+        assert_eq!(selected, error);
+        Ok(())
+    })
+    .await
+}
+
+/// Only the assets of the selected chunk groups are emitted into the target
+/// directory, at their path relative to the output directory.
+#[tokio::test]
+async fn test_emit_tagged_chunk_groups() -> Result<()> {
+    run(async {
+        let target_dir = std::env::temp_dir().join("turbopack-tests-chunk-group-tags");
+        let _ = std::fs::remove_dir_all(&target_dir);
+        let target_root = DiskFileSystem::new(
+            "target".to_string(),
+            target_dir.to_string_lossy().to_string(),
+        )
+        .root();
+
+        emit_tagged_chunk_groups(
+            tagged_chunk_groups(),
+            vec!["public".to_string()],
+            fixture("chunk_group_tags").join("output".to_string()),
+            target_root,
+        )
+        .await?;
+
+        let public = paths(chunk_group("public.js").await?.assets).await?;
+        let admin = paths(chunk_group("admin.js").await?.assets).await?;
+        for path in &public {
+            assert!(target_dir.join(path).is_file(), "{path} wasn't emitted");
+        }
+        for path in admin.difference(&public) {
+            assert!(!target_dir.join(path).exists(), "{path} was emitted");
+        }
+        Ok(())
+    })
+    .await
+}
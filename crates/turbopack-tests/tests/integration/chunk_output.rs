@@ -0,0 +1,58 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::hash::HashAlgorithm;
+use turbopack_test_utils::chunk_output::{ChunkOutputOptions, ChunkOutputSnapshot};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, fixture, run};
+
+/// Captures the chunk output of the `chunk_output` fixture, with chunk names
+/// hashed by `hash_algorithm` when `content_hash_chunk_names` is set.
+async fn snapshot(
+    content_hash_chunk_names: bool,
+    hash_algorithm: HashAlgorithm,
+) -> Result<ChunkOutputSnapshot> {
+    let fixture = fixture("chunk_output");
+    let env = browser_env();
+    let mut chunking_context = dev_chunking_context(fixture, env).hash_algorithm(hash_algorithm);
+    if content_hash_chunk_names {
+        chunking_context = chunking_context.content_hash_chunk_names();
+    }
+    ChunkOutputSnapshot::capture(
+        fixture,
+        "index.js",
+        asset_context(env),
+        Vc::upcast(chunking_context.build()),
+        ChunkOutputOptions { source_maps: false },
+    )
+    .await
+}
+
+/// Snapshots only change when the output does, not when only the hashes in
+/// file names do.
+#[tokio::test]
+async fn test_chunk_output_snapshots_ignore_hashes() -> Result<()> {
+    run(async {
+        let xxh3 = snapshot(true, HashAlgorithm::Xxh3Hash64).await?;
+        assert!(
+            xxh3.files.keys().any(|path| path.contains("[hash]")),
+            "{:?}",
+            xxh3.files.keys()
+        );
+        assert!(xxh3.files.keys().all(|path| !path.ends_with(".map")));
+        assert!(xxh3
+            .files
+            .values()
+            .any(|content| content.contains("chunk-output-lazy")));
+
+        let blake3 = snapshot(true, HashAlgorithm::Blake3).await?;
+        let diff = xxh3.diff(&blake3);
+        assert!(diff.is_empty(), "{diff}");
+
+        let plain = snapshot(false, HashAlgorithm::Xxh3Hash64).await?;
+        assert!(!plain.diff(&xxh3).is_empty());
+
+        assert_eq!(ChunkOutputSnapshot::parse(&xxh3.to_string())?, xxh3);
+        Ok(())
+    })
+    .await
+}
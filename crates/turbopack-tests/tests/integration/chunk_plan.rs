@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::{
+    chunk::{availability_info::AvailabilityInfo, plan::ChunkGroupPlanTree, ChunkingContext},
+    output::OutputAsset,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run};
+
+/// Creates the chunk group of the `chunk_plan` fixture. Returns its plan when
+/// `dry_run` is set, and the paths of its output assets relative to the output
+/// root otherwise.
+async fn chunk_group(dry_run: bool) -> Result<(Option<ChunkGroupPlanTree>, Vec<String>)> {
+    let fixture = fixture("chunk_plan");
+    let env = browser_env();
+    let mut chunking_context = dev_chunking_context(fixture, env);
+    if dry_run {
+        chunking_context = chunking_context.dry_run();
+    }
+    let chunking_context = chunking_context.build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let result = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?;
+
+    let plan = match result.plan {
+        Some(plan) => Some(ChunkGroupPlanTree::capture(plan).await?),
+        None => None,
+    };
+    let output_root = fixture.join("output".to_string()).await?;
+    let mut paths = Vec::new();
+    for &asset in result.assets.await?.iter() {
+        let path = asset.ident().path().await?;
+        paths.extend(output_root.get_path_to(&path).map(|path| path.to_string()));
+    }
+    Ok((plan, paths))
+}
+
+/// The plan names the chunks after the output assets the chunk group creates,
+/// and includes the chunk of the loader of the async chunk group.
+#[tokio::test]
+async fn test_chunk_plan_matches_chunk_group() -> Result<()> {
+    run(async {
+        let (plan, planned_paths) = chunk_group(true).await?;
+        let plan = plan.context("a dry-run chunking context must return a plan")?;
+        assert!(planned_paths.is_empty(), "a plan must not generate assets");
+
+        let (no_plan, paths) = chunk_group(false).await?;
+        assert!(no_plan.is_none());
+
+        let loaders = plan
+            .chunks
+            .iter()
+            .filter(|chunk| chunk.key.starts_with("async-loader-"))
+            .count();
+        assert_eq!(loaders, 1);
+        for chunk in &plan.chunks {
+            let path = chunk
+                .path
+                .as_ref()
+                .context("planned chunks must have a path")?;
+            assert!(
+                paths.contains(path),
+                "{path} is not an output asset of the chunk group: {paths:?}"
+            );
+        }
+        let modules = plan
+            .chunks
+            .iter()
+            .flat_map(|chunk| chunk.modules.iter())
+            .collect::<Vec<_>>();
+        assert!(modules
+            .iter()
+            .any(|module| module.contains("chunk_plan/shared.js")));
+        assert!(!modules
+            .iter()
+            .any(|module| module.contains("chunk_plan/lazy.js")));
+
+        assert_eq!(plan.async_chunk_groups.len(), 1);
+        let lazy = plan.async_chunk_groups.values().next().unwrap();
+        assert!(lazy
+            .chunks
+            .iter()
+            .flat_map(|chunk| chunk.modules.iter())
+            .any(|module| module.contains("chunk_plan/lazy.js")));
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack::{
+    module_options::{CustomEcmascriptTransformPlugins, ModuleOptionsContext},
+    resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_ecmascript_plugins::transform::console_location::ConsoleLocationTransformer;
+
+use crate::util::{
+    asset_context_with, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+const INDEX: &str = "crates/turbopack-tests/tests/console_location/index.js";
+
+/// Calls to the global `console` get the location of the call in the original
+/// file appended, unless the method interprets its trailing arguments.
+#[tokio::test]
+async fn test_console_location() -> Result<()> {
+    run(async {
+        let fixture = fixture("console_location");
+        let env = browser_env();
+        let asset_context = asset_context_with(
+            env,
+            ModuleOptionsContext {
+                preset_env_versions: Some(env),
+                custom_ecma_transform_plugins: Some(CustomEcmascriptTransformPlugins::cell(
+                    CustomEcmascriptTransformPlugins {
+                        source_transforms: vec![Vc::cell(Box::new(
+                            ConsoleLocationTransformer::default(),
+                        ) as _)],
+                        output_transforms: vec![],
+                    },
+                )),
+                ..Default::default()
+            },
+            ResolveOptionsContext::default(),
+        );
+        let chunking_context = Vc::upcast(dev_chunking_context(fixture, env).build());
+
+        let code = evaluated_files(chunking_context, fixture, "index.js", asset_context)
+            .await?
+            .into_iter()
+            .filter_map(|(_, code)| code)
+            .find(|code| code.contains("\"first\""))
+            .expect("the fixture must be part of a chunk");
+        assert!(
+            code.contains(&format!(r#"console.log("first", "{INDEX}:1:1")"#)),
+            "{code}"
+        );
+        assert!(
+            code.contains(&format!(r#"console.warn("second", "{INDEX}:8:3")"#)),
+            "{code}"
+        );
+        assert!(code.contains(r#"console.log("shadowed")"#), "{code}");
+        assert!(code.contains("console.table([])"), "{code}");
+        Ok(())
+    })
+    .await
+}
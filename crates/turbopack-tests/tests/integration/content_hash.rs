@@ -0,0 +1,112 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack_core::{
+    chunk::{availability_info::AvailabilityInfo, ChunkingContext},
+    hash::HashAlgorithm,
+    output::OutputAsset,
+    reference::all_assets_from_entries,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run};
+
+/// The paths of the JS and CSS chunks of the `content_hash` fixture, relative
+/// to the output root, with names hashed by `hash_algorithm`.
+async fn chunk_paths(
+    content_hash_chunk_names: bool,
+    hash_algorithm: HashAlgorithm,
+) -> Result<Vec<String>> {
+    let fixture = fixture("content_hash");
+    let env = browser_env();
+    let mut chunking_context = dev_chunking_context(fixture, env).hash_algorithm(hash_algorithm);
+    if content_hash_chunk_names {
+        chunking_context = chunking_context.content_hash_chunk_names();
+    }
+    let chunking_context = chunking_context.build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let assets = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?
+        .assets;
+
+    let output_root = fixture.join("output".to_string()).await?;
+    let mut paths = Vec::new();
+    for &asset in all_assets_from_entries(assets).await?.iter() {
+        let path = asset.ident().path().await?;
+        if matches!(path.extension_ref(), Some("js" | "css")) {
+            paths.extend(output_root.get_path_to(&path).map(|path| path.to_string()));
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Both JS and CSS chunks are named by their content, so neither is served
+/// stale after a rebuild. The chunk lists keep their names.
+#[tokio::test]
+async fn test_content_hash_names_js_and_css_chunks() -> Result<()> {
+    run(async {
+        let plain = chunk_paths(false, HashAlgorithm::Xxh3Hash64).await?;
+        let hashed = chunk_paths(true, HashAlgorithm::Xxh3Hash64).await?;
+        assert!(plain.iter().any(|path| path.ends_with(".css")));
+        assert_eq!(plain.len(), hashed.len());
+
+        let mut hashed_chunks = 0;
+        for path in &hashed {
+            if plain.contains(path) {
+                continue;
+            }
+            let (name, extension) = path.rsplit_once('.').unwrap();
+            let (name, hash) = name.rsplit_once('.').unwrap();
+            assert_eq!(hash.len(), 8, "{path} must end with a content hash");
+            assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+            assert!(
+                plain.contains(&format!("{name}.{extension}")),
+                "{path} must be named like a chunk without a content hash: {plain:?}"
+            );
+            hashed_chunks += 1;
+        }
+        assert!(hashed
+            .iter()
+            .any(|path| path.ends_with(".css") && !plain.contains(path)));
+        assert!(hashed
+            .iter()
+            .any(|path| path.ends_with(".js") && !plain.contains(path)));
+        assert!(hashed_chunks >= 2);
+        Ok(())
+    })
+    .await
+}
+
+/// Content hashes of JS and CSS chunks are computed with the configured hash
+/// algorithm.
+#[tokio::test]
+async fn test_content_hash_uses_the_hash_algorithm() -> Result<()> {
+    run(async {
+        let content_hashed = |plain: Vec<String>, hashed: Vec<String>| {
+            hashed
+                .into_iter()
+                .filter(|path| !plain.contains(path))
+                .collect::<Vec<_>>()
+        };
+        let xxh3 = content_hashed(
+            chunk_paths(false, HashAlgorithm::Xxh3Hash64).await?,
+            chunk_paths(true, HashAlgorithm::Xxh3Hash64).await?,
+        );
+        let blake3 = content_hashed(
+            chunk_paths(false, HashAlgorithm::Blake3).await?,
+            chunk_paths(true, HashAlgorithm::Blake3).await?,
+        );
+        assert_eq!(xxh3.len(), blake3.len());
+        assert!(blake3.iter().any(|path| path.ends_with(".css")));
+        assert!(blake3.iter().any(|path| path.ends_with(".js")));
+        for path in &blake3 {
+            assert!(
+                !xxh3.contains(path),
+                "{path} must be named by a BLAKE3 hash: {xxh3:?}"
+            );
+        }
+        Ok(())
+    })
+    .await
+}
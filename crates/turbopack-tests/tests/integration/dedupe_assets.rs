@@ -0,0 +1,77 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack_core::chunk::{availability_info::AvailabilityInfo, ChunkingContext};
+
+use crate::util::{
+    all_files, asset_context, browser_env, build_chunking_context, dev_chunking_context, entry,
+    fixture, run,
+};
+
+/// The distinct paths of the SVG files emitted for `index.js` of the
+/// `dedupe_assets` fixture, which references two SVG files with the same
+/// content and one with different content.
+async fn svg_paths(chunking_context: Vc<Box<dyn ChunkingContext>>) -> Result<BTreeSet<String>> {
+    let entry = entry(
+        fixture("dedupe_assets"),
+        "index.js",
+        asset_context(browser_env()),
+    );
+    let assets = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?
+        .assets;
+    Ok(all_files(assets)
+        .await?
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|path| path.ends_with(".svg"))
+        .collect())
+}
+
+/// Assets with the same content are emitted once in development.
+#[tokio::test]
+async fn test_dedupe_assets_dev() -> Result<()> {
+    run(async {
+        let fixture = fixture("dedupe_assets");
+        let env = browser_env();
+        let plain = svg_paths(Vc::upcast(dev_chunking_context(fixture, env).build())).await?;
+        assert_eq!(plain.len(), 3, "{plain:?}");
+
+        let deduped = svg_paths(Vc::upcast(
+            dev_chunking_context(fixture, env)
+                .dedupe_assets_by_content()
+                .build(),
+        ))
+        .await?;
+        assert_eq!(deduped.len(), 2, "{deduped:?}");
+        Ok(())
+    })
+    .await
+}
+
+/// Assets with the same content are emitted once in production, and are named
+/// by their content only.
+#[tokio::test]
+async fn test_dedupe_assets_build() -> Result<()> {
+    run(async {
+        let fixture = fixture("dedupe_assets");
+        let env = browser_env();
+        let plain = svg_paths(Vc::upcast(build_chunking_context(fixture, env).build())).await?;
+        assert_eq!(plain.len(), 3, "{plain:?}");
+
+        let deduped = svg_paths(Vc::upcast(
+            build_chunking_context(fixture, env)
+                .dedupe_assets_by_content()
+                .build(),
+        ))
+        .await?;
+        assert_eq!(deduped.len(), 2, "{deduped:?}");
+        for path in &deduped {
+            assert!(!path.contains("logo") && !path.contains("icon"), "{path}");
+        }
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,59 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack::design_tokens::{read_design_tokens, DesignTokensCssSource};
+use turbopack_core::asset::Asset;
+
+use crate::util::{fixture, run, text};
+
+/// The path of `name` in the `design_tokens` fixture.
+fn token_file(name: &str) -> Vc<FileSystemPath> {
+    fixture("design_tokens").join(name.to_string())
+}
+
+async fn css(path: Vc<FileSystemPath>) -> Result<String> {
+    text(DesignTokensCssSource::new(path).content())
+        .await?
+        .context("the design tokens CSS doesn't exist")
+}
+
+#[tokio::test]
+async fn test_typescript_tokens() -> Result<()> {
+    run(async {
+        let tokens = read_design_tokens(token_file("tokens.ts")).await?;
+        assert_eq!(
+            tokens.iter().collect::<Vec<_>>(),
+            vec![
+                (&"color-primary".to_string(), &json!("#0070f3")),
+                (&"color-on-primary".to_string(), &json!("#fff")),
+                (&"space-sm".to_string(), &json!(4)),
+                (&"space-lg".to_string(), &json!(-0.5)),
+                (&"font-body".to_string(), &json!("\"Inter\", sans-serif")),
+            ]
+        );
+        assert!(css(token_file("tokens.ts"))
+            .await?
+            .contains("  --font-body: \"Inter\", sans-serif;\n"));
+
+        // The default export can reference a constant.
+        let tokens = read_design_tokens(token_file("constant.ts")).await?;
+        assert_eq!(tokens.get("color-primary"), Some(&json!("#0070f3")));
+        Ok(())
+    })
+    .await
+}
+
+/// A token value that would end its declaration is rejected instead of
+/// injecting CSS.
+#[tokio::test]
+async fn test_css_injection() -> Result<()> {
+    run(async {
+        assert!(read_design_tokens(token_file("injection.json"))
+            .await?
+            .is_empty());
+        assert_eq!(css(token_file("injection.json")).await?, ":root {\n}\n");
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,82 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{asset::Asset, chunk::EvaluatableAssets};
+use turbopack_dev_server::html::DevHtmlAsset;
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run, text};
+
+/// The HTML page of `index.js` of the `dev_html` fixture. With
+/// `inline_chunk_list`, the bootstrap assets of the page are inlined.
+async fn html(inline_chunk_list: Option<bool>) -> Result<String> {
+    let fixture = fixture("dev_html");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env).build();
+    let entry = entry(fixture, "index.js", asset_context(env));
+
+    let mut html = DevHtmlAsset::new(
+        fixture.join("output/index.html".to_string()),
+        vec![(
+            Vc::upcast(entry),
+            Vc::upcast(chunking_context),
+            Some(EvaluatableAssets::empty()),
+        )],
+    );
+    if let Some(inline_chunk_list) = inline_chunk_list {
+        html = html.with_inlined_evaluate_chunk(inline_chunk_list);
+    }
+    Ok(text(html.content()).await?.unwrap())
+}
+
+/// The URLs of the inlined scripts of `html`.
+fn inlined_scripts(html: &str) -> Vec<&str> {
+    html.split("<script data-turbopack-src=\"")
+        .skip(1)
+        .map(|script| &script[..script.find('"').unwrap()])
+        .collect()
+}
+
+/// By default, the page loads all of its scripts.
+#[tokio::test]
+async fn test_dev_html_references_scripts() -> Result<()> {
+    run(async {
+        let html = html(None).await?;
+        assert_eq!(inlined_scripts(&html), Vec::<&str>::new(), "{html}");
+        // The chunk, the chunk list and the evaluate chunk.
+        assert_eq!(html.matches("<script src=\"/").count(), 3, "{html}");
+        Ok(())
+    })
+    .await
+}
+
+/// The evaluate chunk is inlined into the page, with a source URL that points
+/// to where it would be loaded from, while the other scripts are still loaded.
+#[tokio::test]
+async fn test_dev_html_inlines_evaluate_chunk() -> Result<()> {
+    run(async {
+        let html = html(Some(false)).await?;
+        let inlined = inlined_scripts(&html);
+        assert_eq!(inlined.len(), 1, "{html}");
+        let url = inlined[0];
+        assert!(
+            html.contains(&format!("//# sourceURL={url}\n</script>")),
+            "{html}"
+        );
+        assert!(!html.contains(&format!("src=\"{url}\"")), "{html}");
+        assert_eq!(html.matches("<script src=\"/").count(), 2, "{html}");
+        Ok(())
+    })
+    .await
+}
+
+/// With `inline_chunk_list`, the chunk list is inlined as well, but the chunks
+/// of the entry are still loaded.
+#[tokio::test]
+async fn test_dev_html_inlines_chunk_list() -> Result<()> {
+    run(async {
+        let html = html(Some(true)).await?;
+        assert_eq!(inlined_scripts(&html).len(), 2, "{html}");
+        assert_eq!(html.matches("<script src=\"/").count(), 1, "{html}");
+        Ok(())
+    })
+    .await
+}
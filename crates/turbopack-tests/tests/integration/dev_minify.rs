@@ -0,0 +1,64 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+/// The code of the chunk of `index.js` of the `dev_minify` fixture, and its
+/// source map.
+async fn chunk(minify: bool) -> Result<(String, Option<String>)> {
+    let fixture = fixture("dev_minify");
+    let env = browser_env();
+    let files = evaluated_files(
+        Vc::upcast(dev_chunking_context(fixture, env).minify(minify).build()),
+        fixture,
+        "index.js",
+        asset_context(env),
+    )
+    .await?;
+    let (path, code) = files
+        .iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .find_map(|(path, code)| {
+            let code = code.as_ref()?;
+            code.contains("minifyFixture").then(|| (path, code.clone()))
+        })
+        .expect("the fixture must be part of a chunk");
+    let map = files
+        .iter()
+        .find(|(map_path, _)| *map_path == format!("{path}.map"))
+        .and_then(|(_, map)| map.clone());
+    Ok((code, map))
+}
+
+/// Dev chunks aren't minified by default.
+#[tokio::test]
+async fn test_dev_chunks_are_not_minified() -> Result<()> {
+    run(async {
+        let (code, _) = chunk(false).await?;
+        assert!(code.contains("someLongLocalName"), "{code}");
+        assert!(code.contains("This comment is removed"), "{code}");
+        Ok(())
+    })
+    .await
+}
+
+/// Minified dev chunks have mangled local names and no comments, and still
+/// have a source map that maps to the fixture.
+#[tokio::test]
+async fn test_dev_chunks_are_minified() -> Result<()> {
+    run(async {
+        let (unminified, _) = chunk(false).await?;
+        let (code, map) = chunk(true).await?;
+        assert!(code.len() < unminified.len(), "{code}");
+        assert!(!code.contains("someLongLocalName"), "{code}");
+        assert!(!code.contains("anotherLongLocalName"), "{code}");
+        assert!(!code.contains("This comment is removed"), "{code}");
+
+        let map = map.expect("the chunk must have a source map");
+        assert!(map.contains("dev_minify/index.js"), "{map}");
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack::ecmascript::chunk::EcmascriptChunkPlaceable;
+use turbopack_core::chunk::{
+    availability_info::AvailabilityInfo, ChunkingContext, EvaluatableAssets,
+};
+
+use crate::util::{
+    all_files, asset_context, browser_environment, build_chunking_context, dev_chunking_context,
+    entry, fixture, run,
+};
+
+/// Chunks the `downlevel` fixture, whose modules are compiled for a modern
+/// browser, with chunks downleveled for a legacy browser, and checks that no
+/// chunk uses syntax the legacy browser doesn't support.
+async fn check_downlevel(build: bool) -> Result<()> {
+    let fixture = fixture("downlevel");
+    let env = browser_environment("Chrome 102");
+    let legacy_env = browser_environment("Chrome 50");
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let assets = if build {
+        let chunking_context = build_chunking_context(fixture, env)
+            .downlevel(legacy_env)
+            .build();
+        let module = Vc::try_resolve_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(entry)
+            .await?
+            .context("the entry must be an ECMAScript module")?;
+        let entry_chunk = chunking_context
+            .entry_chunk_group(
+                fixture.join("output/index.entry.js".to_string()),
+                module,
+                EvaluatableAssets::one(entry),
+                Value::new(AvailabilityInfo::Root),
+            )
+            .await?
+            .asset;
+        Vc::cell(vec![entry_chunk])
+    } else {
+        dev_chunking_context(fixture, env)
+            .downlevel(legacy_env)
+            .build()
+            .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+            .await?
+            .assets
+    };
+
+    let code: Vec<String> = all_files(assets)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .filter_map(|(_, code)| code)
+        .collect();
+    assert!(
+        code.iter().any(|code| code.contains("downlevelFixture")),
+        "the fixture must be part of a chunk"
+    );
+    for code in &code {
+        assert!(
+            !code.contains("?."),
+            "optional chaining must be downleveled"
+        );
+        assert!(
+            !code.contains("??"),
+            "nullish coalescing must be downleveled"
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dev_chunks_are_downleveled() -> Result<()> {
+    run(check_downlevel(false)).await
+}
+
+#[tokio::test]
+async fn test_build_chunks_are_downleveled() -> Result<()> {
+    run(check_downlevel(true)).await
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::{
+    chunk::ChunkingContext,
+    environment::{ElectronRendererEnvironment, Environment, ExecutionEnvironment},
+    ident::AssetIdent,
+};
+
+use crate::util::{
+    asset_context_with, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+fn renderer_env(node_integration: bool) -> Vc<Environment> {
+    Environment::new(Value::new(ExecutionEnvironment::ElectronRenderer(
+        ElectronRendererEnvironment {
+            node_integration,
+            browserslist_query: "Chrome 120".to_string(),
+        }
+        .cell(),
+    )))
+}
+
+/// Renderers with `nodeIntegration` require `electron` as an external.
+#[tokio::test]
+async fn test_electron_renderer_externals() -> Result<()> {
+    run(async {
+        let fixture = fixture("electron");
+        let env = renderer_env(true);
+        let asset_context = asset_context_with(
+            env,
+            ModuleOptionsContext {
+                preset_env_versions: Some(env),
+                ..Default::default()
+            },
+            ResolveOptionsContext {
+                enable_electron_externals: true,
+                ..Default::default()
+            },
+        );
+        let chunking_context = Vc::upcast(dev_chunking_context(fixture, env).build());
+
+        let files =
+            evaluated_files(chunking_context, fixture, "renderer.js", asset_context).await?;
+        assert!(
+            files.iter().any(|(_, code)| code.as_deref().is_some_and(
+                |code| code.contains(r#"__turbopack_external_require__("electron", true)"#)
+            )),
+            "{files:?}"
+        );
+        Ok(())
+    })
+    .await
+}
+
+/// The URL of the asset at `static/logo.svg` in the output directory of the
+/// `electron` fixture.
+async fn asset_url(env: Vc<Environment>) -> Result<String> {
+    let fixture = fixture("electron");
+    let chunking_context: Vc<Box<dyn ChunkingContext>> =
+        Vc::upcast(dev_chunking_context(fixture, env).build());
+    let ident = AssetIdent::from_path(fixture.join("output/static/logo.svg".to_string()));
+    Ok(chunking_context.asset_url(ident).await?.clone_value())
+}
+
+/// Renderers may be loaded with the `file:` protocol, so asset URLs are
+/// relative to the document unless there is an asset base path.
+#[tokio::test]
+async fn test_electron_renderer_asset_urls() -> Result<()> {
+    run(async {
+        assert_eq!(asset_url(renderer_env(false)).await?, "static/logo.svg");
+        assert_eq!(asset_url(browser_env()).await?, "/static/logo.svg");
+        Ok(())
+    })
+    .await
+}
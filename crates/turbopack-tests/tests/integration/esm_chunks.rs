@@ -0,0 +1,106 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{asset::Asset, chunk::EvaluatableAssets};
+use turbopack_dev::{BasePathMode, DevChunkingContextBuilder};
+use turbopack_dev_server::html::DevHtmlAsset;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, entry, evaluated_files, fixture, run, text,
+};
+
+/// The code of the evaluate chunk of `index.js` of the `esm_chunks` fixture.
+async fn evaluate_chunk(
+    configure: impl FnOnce(DevChunkingContextBuilder) -> DevChunkingContextBuilder,
+) -> Result<String> {
+    let fixture = fixture("esm_chunks");
+    let env = browser_env();
+    let files = evaluated_files(
+        Vc::upcast(configure(dev_chunking_context(fixture, env)).build()),
+        fixture,
+        "index.js",
+        asset_context(env),
+    )
+    .await?;
+    Ok(files
+        .into_iter()
+        .filter_map(|(path, code)| code.filter(|_| path.ends_with(".js")))
+        .find(|code| code.contains("runtimeModuleIds"))
+        .expect("there is no evaluate chunk"))
+}
+
+/// Classic entry chunks register themselves before the runtime is set up.
+#[tokio::test]
+async fn test_classic_entry_chunk() -> Result<()> {
+    run(async {
+        let code = evaluate_chunk(|builder| builder).await?;
+        assert!(
+            code.contains("(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["),
+            "{code}"
+        );
+        assert!(!code.contains("await globalThis.TURBOPACK"), "{code}");
+        Ok(())
+    })
+    .await
+}
+
+/// ES module entry chunks register themselves after the runtime is set up,
+/// and await the registration, so they finish evaluating with their
+/// asynchronous entries.
+#[tokio::test]
+async fn test_esm_entry_chunk() -> Result<()> {
+    run(async {
+        let code = evaluate_chunk(|builder| builder.esm_chunks()).await?;
+        assert!(
+            code.contains("globalThis.TURBOPACK = globalThis.TURBOPACK || [];\n"),
+            "{code}"
+        );
+        assert!(code.contains("await globalThis.TURBOPACK.push(["), "{code}");
+        Ok(())
+    })
+    .await
+}
+
+/// ES module entry chunks derive the base paths from `import.meta.url`, as
+/// there is no current script in module scripts.
+#[tokio::test]
+async fn test_esm_auto_base_paths() -> Result<()> {
+    run(async {
+        let code =
+            evaluate_chunk(|builder| builder.esm_chunks().base_path_mode(BasePathMode::Auto))
+                .await?;
+        assert!(code.contains("import.meta.url"), "{code}");
+        assert!(!code.contains("document.currentScript"), "{code}");
+        Ok(())
+    })
+    .await
+}
+
+/// HTML pages load ES module chunks with module scripts.
+#[tokio::test]
+async fn test_esm_chunks_html() -> Result<()> {
+    run(async {
+        let fixture = fixture("esm_chunks");
+        let env = browser_env();
+        let chunking_context = dev_chunking_context(fixture, env).esm_chunks().build();
+        let entry = entry(fixture, "index.js", asset_context(env));
+        let html = DevHtmlAsset::new(
+            fixture.join("output/index.html".to_string()),
+            vec![(
+                Vc::upcast(entry),
+                Vc::upcast(chunking_context),
+                Some(EvaluatableAssets::empty()),
+            )],
+        );
+        let html = text(html.content()).await?.unwrap();
+
+        // The chunk, the chunk list and the evaluate chunk.
+        assert_eq!(
+            html.matches("<script type=\"module\" src=\"/").count(),
+            3,
+            "{html}"
+        );
+        assert!(!html.contains("<script src="), "{html}");
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack::{
+    css::chunk::CssChunkItem, ecmascript::chunk::EcmascriptChunkItem,
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::{
+    chunk::{ChunkItem, ChunkableModule, ChunkingContext},
+    context::AssetContext,
+    file_source::FileSource,
+    module::Module,
+    reference_type::{CssReferenceSubType, ReferenceType},
+};
+
+use crate::util::{asset_context_with, browser_env, dev_chunking_context, fixture, run};
+
+const ERROR: &str = "Unexpected token */ in the module";
+
+/// Stubs for JavaScript modules throw the build error, while stubs for CSS
+/// modules stay CSS, so they are still placed into CSS chunks.
+#[tokio::test]
+async fn test_failing_modules_are_stubbed_by_their_type() -> Result<()> {
+    run(async {
+        let fixture = fixture("failing_modules");
+        let env = browser_env();
+        let asset_context = asset_context_with(
+            env,
+            ModuleOptionsContext::default(),
+            ResolveOptionsContext::default(),
+        );
+        let chunking_context = dev_chunking_context(fixture, env)
+            .stub_failing_modules(true)
+            .build();
+
+        let stub = |file: &str, reference_type: ReferenceType| {
+            let module = asset_context
+                .process(
+                    Vc::upcast(FileSource::new(fixture.join(file.to_string()))),
+                    Value::new(reference_type),
+                )
+                .module();
+            async move {
+                let module = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module)
+                    .await?
+                    .context("the module must be chunkable")?;
+                let stub = (*chunking_context
+                    .failing_module_chunk_item(module, ERROR.to_string())
+                    .await?)
+                    .context("failing modules must be stubbed")?;
+                assert_eq!(
+                    stub.asset_ident().to_string().await?,
+                    module.ident().to_string().await?,
+                    "the stub must replace the module"
+                );
+                anyhow::Ok(stub)
+            }
+        };
+
+        let js_stub = stub("index.js", ReferenceType::Undefined).await?;
+        let js_stub = Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkItem>>(js_stub)
+            .await?
+            .context("the stub of a JavaScript module must be an ECMAScript chunk item")?;
+        let code = js_stub.content().await?.inner_code.to_str()?.into_owned();
+        assert!(code.starts_with("throw new Error("));
+        assert!(code.contains("Unexpected token"));
+
+        let css_stub = stub(
+            "style.css",
+            ReferenceType::Css(CssReferenceSubType::AtImport(None)),
+        )
+        .await?;
+        assert!(
+            Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkItem>>(css_stub)
+                .await?
+                .is_none()
+        );
+        let css_stub = Vc::try_resolve_downcast::<Box<dyn CssChunkItem>>(css_stub)
+            .await?
+            .context("the stub of a CSS module must be a CSS chunk item")?;
+        let code = css_stub.content().await?.inner_code.to_str()?.into_owned();
+        assert!(code.starts_with("/* Module failed to build:"));
+        assert!(code.contains("Unexpected token"));
+        assert_eq!(
+            code.matches("*/").count(),
+            1,
+            "the error must not end the comment"
+        );
+        Ok(())
+    })
+    .await
+}
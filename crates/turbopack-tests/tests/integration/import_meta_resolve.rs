@@ -0,0 +1,65 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack_core::chunk::{availability_info::AvailabilityInfo, ChunkingContext};
+
+use crate::util::{
+    all_files, asset_context, browser_env, dev_chunking_context, entry, fixture, run,
+};
+
+/// The output assets of the chunk group of `index.js` of the
+/// `import_meta_resolve` fixture, including the ones that are only referenced
+/// by other assets, and the code of its JS chunks.
+async fn output() -> Result<(Vec<String>, String)> {
+    let fixture = fixture("import_meta_resolve");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env).build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let assets = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?
+        .assets;
+    let mut paths = Vec::new();
+    let mut code = String::new();
+    for (path, text) in all_files(assets).await? {
+        if path.ends_with(".js") {
+            code.push_str(text.as_deref().unwrap_or_default());
+        }
+        paths.push(path);
+    }
+    Ok((paths, code))
+}
+
+/// `import.meta.resolve(...)` is replaced with the URL of the emitted file.
+/// With a dynamic part, all matching files are emitted and looked up at
+/// runtime.
+#[tokio::test]
+async fn test_import_meta_resolve() -> Result<()> {
+    run(async {
+        let (paths, code) = output().await?;
+        for extension in [".svg", ".json"] {
+            assert!(
+                paths.iter().any(|path| path.ends_with(extension)),
+                "no {extension} file is emitted: {paths:?}"
+            );
+        }
+        assert_eq!(
+            paths.iter().filter(|path| path.ends_with(".json")).count(),
+            2,
+            "{paths:?}"
+        );
+
+        assert!(code.contains("import-meta-resolve"));
+        assert!(
+            !code.contains("import.meta.resolve("),
+            "import.meta.resolve must be replaced: {code}"
+        );
+        assert!(code.contains("location.href"), "{code}");
+        for request in ["locales/en.json", "locales/de.json"] {
+            assert!(code.contains(request), "{request} isn't mapped: {code}");
+        }
+        assert!(code.contains("Cannot resolve '"), "{code}");
+        Ok(())
+    })
+    .await
+}
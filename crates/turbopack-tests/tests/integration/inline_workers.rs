@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::Value;
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::{
+    chunk::{availability_info::AvailabilityInfo, ChunkingContextExt, EvaluatableAssets},
+    module::Module,
+    source_map::SourceMapMode,
+};
+
+use crate::util::{
+    all_files, asset_context_with, browser_env, dev_chunking_context, entry, fixture, project_root,
+    run,
+};
+
+/// The contents of the JavaScript assets emitted for the chunk group of the
+/// `inline_workers` fixture, by path. This includes the chunk groups of the
+/// workers that aren't inlined.
+async fn emitted_chunks() -> Result<BTreeMap<String, String>> {
+    let fixture = fixture("inline_workers");
+    let env = browser_env();
+    let asset_context = asset_context_with(
+        env,
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            ..Default::default()
+        },
+        ResolveOptionsContext {
+            enable_node_modules: Some(project_root()),
+            ..Default::default()
+        },
+    );
+    let chunking_context = dev_chunking_context(fixture, env)
+        .reference_chunk_source_maps(SourceMapMode::External)
+        .inline_workers(1_000_000)
+        .build();
+
+    let entry = entry(fixture, "index.js", asset_context);
+    let assets = chunking_context.evaluated_chunk_group_assets(
+        entry.ident(),
+        EvaluatableAssets::one(entry),
+        Value::new(AvailabilityInfo::Root),
+    );
+
+    Ok(all_files(assets)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .filter_map(|(path, code)| Some((path, code?)))
+        .collect())
+}
+
+/// The chunks of `chunks` that contain `marker`.
+fn chunks_containing<'a>(chunks: &'a BTreeMap<String, String>, marker: &str) -> Vec<&'a String> {
+    chunks
+        .values()
+        .filter(|content| content.contains(marker))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_inline_workers() -> Result<()> {
+    run(async {
+        let chunks = emitted_chunks().await?;
+
+        // The simple worker is inlined into the chunk starting it, and its
+        // source map is still found.
+        let [entry_chunk] = chunks_containing(&chunks, "SIMPLE_WORKER_MARKER")[..] else {
+            panic!("the simple worker must only be part of the entry chunk");
+        };
+        assert!(entry_chunk.contains("URL.createObjectURL"));
+        assert!(entry_chunk.contains(".js.map\", location.href)"));
+
+        // The worker using `import()` loads its chunks relative to its own
+        // chunk, so it isn't inlined.
+        let dynamic_worker_chunks = chunks_containing(&chunks, "DYNAMIC_WORKER_MARKER");
+        assert!(!dynamic_worker_chunks.is_empty());
+        assert!(!dynamic_worker_chunks.contains(&entry_chunk));
+        assert!(!chunks_containing(&chunks, "LAZY_MARKER").contains(&entry_chunk));
+        Ok(())
+    })
+    .await
+}
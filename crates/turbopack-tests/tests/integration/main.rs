@@ -0,0 +1,52 @@
+//! Tests that check the output of fixtures in ways that snapshot and execution
+//! tests can't, e.g. because they need custom chunking options or look at
+//! chunk plans. Each module tests the fixture directory of the same name next
+//! to this directory.
+
+#![cfg(test)]
+
+#[path = "../util.rs"]
+mod util;
+
+mod asset_pipelines;
+mod availability_dump;
+mod base_path_mode;
+mod browser_extension;
+mod build_id;
+mod cache_groups;
+mod chunk_group_alias;
+mod chunk_group_output_dirs;
+mod chunk_group_tags;
+mod chunk_output;
+mod chunk_plan;
+mod console_location;
+mod content_hash;
+mod dedupe_assets;
+mod design_tokens;
+mod dev_html;
+mod dev_minify;
+mod downlevel;
+mod electron;
+mod esm_chunks;
+mod failing_modules;
+mod import_meta_resolve;
+mod inline_workers;
+mod module_replacements;
+mod module_size;
+mod no_bundle;
+mod no_eval;
+mod node_dev_chunks;
+mod peer_dependencies;
+mod prefetch_manifest;
+mod runtime_globals;
+mod service_worker;
+mod single_file_bundle;
+mod split_node_modules;
+mod split_points;
+mod static_assets_manifest;
+mod strict_esm;
+mod unused_files;
+mod vendor_chunks;
+mod virtual_entry;
+mod warm_up;
+mod worklets;
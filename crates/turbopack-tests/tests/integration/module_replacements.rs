@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack::{
+    module_options::ModuleOptionsContext,
+    resolve_options_context::{ModuleReplacements, ResolveOptionsContext},
+};
+use turbopack_core::chunk::{
+    availability_info::AvailabilityInfo, plan::ChunkGroupPlanTree, ChunkingContext,
+};
+
+use crate::util::{asset_context_with, browser_env, dev_chunking_context, entry, fixture, run};
+
+/// Returns the modules the chunk group of the `module_replacements` fixture
+/// consists of, resolved for a browser when `browser` is set.
+async fn chunk_group_modules(browser: bool) -> Result<Vec<String>> {
+    let fixture = fixture("module_replacements");
+    let env = browser_env();
+    let module_replacements = ModuleReplacements {
+        context: fixture,
+        by_condition: [(
+            "browser".to_string(),
+            [("ws".to_string(), Some("./shims/ws.js".to_string()))]
+                .into_iter()
+                .collect(),
+        )]
+        .into_iter()
+        .collect(),
+    }
+    .cell();
+    let asset_context = asset_context_with(
+        env,
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            ..Default::default()
+        },
+        ResolveOptionsContext {
+            browser,
+            module_replacements: Some(module_replacements),
+            ..Default::default()
+        },
+    );
+    let chunking_context = dev_chunking_context(fixture, env).dry_run().build();
+
+    let entry = entry(fixture, "index.js", asset_context);
+    let result = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?;
+    let plan =
+        ChunkGroupPlanTree::capture(result.plan.context("a dry run must return a plan")?).await?;
+    Ok(plan
+        .chunks
+        .into_iter()
+        .flat_map(|chunk| chunk.modules)
+        .collect())
+}
+
+fn contains(modules: &[String], name: &str) -> bool {
+    modules.iter().any(|module| module.contains(name))
+}
+
+/// The replacements of the `browser` condition only apply when resolving for
+/// a browser.
+#[tokio::test]
+async fn test_module_replacements_apply_per_condition() -> Result<()> {
+    run(async {
+        let modules = chunk_group_modules(true).await?;
+        assert!(contains(&modules, "module_replacements/shims/ws.js"));
+        assert!(!contains(&modules, "node_modules/ws/index.js"));
+
+        let modules = chunk_group_modules(false).await?;
+        assert!(!contains(&modules, "module_replacements/shims/ws.js"));
+        assert!(contains(&modules, "node_modules/ws/index.js"));
+        Ok(())
+    })
+    .await
+}
+
+/// Keys of the `browser` field of `package.json` may omit the leading `./`
+/// and the extension of the file they replace.
+#[tokio::test]
+async fn test_browser_field_keys_match_loosely() -> Result<()> {
+    run(async {
+        let modules = chunk_group_modules(true).await?;
+        assert!(contains(&modules, "node_modules/pkg/lib/browser.js"));
+        assert!(!contains(&modules, "node_modules/pkg/lib/server.js"));
+
+        let modules = chunk_group_modules(false).await?;
+        assert!(contains(&modules, "node_modules/pkg/lib/server.js"));
+        assert!(!contains(&modules, "node_modules/pkg/lib/browser.js"));
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack_core::chunk::{availability_info::AvailabilityInfo, ChunkingContext};
+
+use crate::util::{
+    asset_context, browser_env, build_chunking_context, dev_chunking_context, entry, fixture,
+    issues, run,
+};
+
+/// The titles and file paths of the issues reported for the chunk group of
+/// the `module_size` fixture with the given threshold.
+async fn module_size_issues(build: bool, threshold: Option<u64>) -> Result<Vec<(String, String)>> {
+    let fixture = fixture("module_size");
+    let env = browser_env();
+    let chunking_context: Vc<Box<dyn ChunkingContext>> = if build {
+        let mut builder = build_chunking_context(fixture, env);
+        if let Some(threshold) = threshold {
+            builder = builder.module_size_warning_threshold(threshold);
+        }
+        Vc::upcast(builder.build())
+    } else {
+        let mut builder = dev_chunking_context(fixture, env);
+        if let Some(threshold) = threshold {
+            builder = builder.module_size_warning_threshold(threshold);
+        }
+        Vc::upcast(builder.build())
+    };
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let chunk_group =
+        chunking_context.chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root));
+    chunk_group.await?;
+    issues(chunk_group).await
+}
+
+async fn check_module_size(build: bool) -> Result<()> {
+    assert_eq!(
+        module_size_issues(build, Some(1_000)).await?,
+        vec![(
+            "Module is too large".to_string(),
+            "crates/turbopack-tests/tests/module_size/data.js".to_string()
+        )]
+    );
+    assert!(module_size_issues(build, Some(100_000)).await?.is_empty());
+    assert!(
+        module_size_issues(build, None).await?.is_empty(),
+        "modules aren't checked without a threshold"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dev_reports_oversized_modules() -> Result<()> {
+    run(check_module_size(false)).await
+}
+
+#[tokio::test]
+async fn test_build_reports_oversized_modules() -> Result<()> {
+    run(check_module_size(true)).await
+}
@@ -0,0 +1,78 @@
+use anyhow::{bail, Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbopack::{
+    module_options::{ModuleOptionsContext, NoBundleOptions},
+    resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::chunk::{availability_info::AvailabilityInfo, ChunkingContext};
+
+use crate::util::{
+    all_files, asset_context_with, browser_env, dev_chunking_context, entry, fixture, run,
+};
+
+/// The text of the file at `path`.
+async fn read_to_string(path: Vc<FileSystemPath>) -> Result<String> {
+    let FileContent::Content(file) = &*path.read().await? else {
+        bail!("expected the file to exist");
+    };
+    Ok(file.content().to_str()?.into_owned())
+}
+
+/// Returns the text of all output assets of the chunk group of `index.js` of
+/// the `no_bundle` fixture, including the ones that are only referenced by
+/// other assets. `sdk.js` and the `legacy` directory aren't bundled.
+async fn output() -> Result<Vec<String>> {
+    let fixture = fixture("no_bundle");
+    let env = browser_env();
+    let asset_context = asset_context_with(
+        env,
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            no_bundle: Some(
+                NoBundleOptions {
+                    base: fixture,
+                    rules: vec!["sdk.js".to_string(), "legacy/**".to_string()],
+                }
+                .cell(),
+            ),
+            ..Default::default()
+        },
+        ResolveOptionsContext::default(),
+    );
+    let chunking_context = dev_chunking_context(fixture, env).build();
+
+    let entry = entry(fixture, "index.js", asset_context);
+    let assets = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?
+        .assets;
+    Ok(all_files(assets)
+        .await?
+        .into_iter()
+        .filter_map(|(_, text)| text)
+        .collect())
+}
+
+/// Modules matching the no-bundle globs are emitted byte-identical as
+/// standalone files, while the other modules are bundled.
+#[tokio::test]
+async fn test_no_bundle_modules_are_emitted_unchanged() -> Result<()> {
+    run(async {
+        let contents = output().await?;
+        for file in ["sdk.js", "legacy/widget.js"] {
+            let source = read_to_string(fixture("no_bundle").join(file.to_string())).await?;
+            assert!(contents.contains(&source), "{file} isn't emitted unchanged");
+        }
+
+        let entry = contents
+            .iter()
+            .find(|content| content.contains("no-bundle-entry"))
+            .context("the entry must be bundled")?;
+        assert!(entry.contains("no-bundle-util"));
+        assert!(!entry.contains("no-bundle-sdk"));
+        assert!(!entry.contains("no-bundle-legacy"));
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_ecmascript_runtime::RuntimeType;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, evaluated_files, fixture, run,
+};
+
+/// Whether any JavaScript file emitted for `index.js` of the `no_eval` fixture
+/// calls `eval` or `new Function`. Mentions in comments don't count.
+async fn evaluates_code(allow_eval: bool) -> Result<bool> {
+    let fixture = fixture("no_eval");
+    let env = browser_env();
+    let mut chunking_context =
+        dev_chunking_context(fixture, env).runtime_type(RuntimeType::Default);
+    if !allow_eval {
+        chunking_context = chunking_context.disallow_eval();
+    }
+
+    let files = evaluated_files(
+        Vc::upcast(chunking_context.build()),
+        fixture,
+        "index.js",
+        asset_context(env),
+    )
+    .await?;
+    Ok(files
+        .iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .filter_map(|(_, code)| code.as_deref())
+        .any(|code| {
+            code.contains("new Function(")
+                || code.match_indices("eval(").any(|(index, _)| {
+                    !code[..index].ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '.')
+                })
+        }))
+}
+
+/// Neither the chunks nor the runtime evaluate code when eval is disallowed,
+/// while the runtime evaluates hot updates by default.
+#[tokio::test]
+async fn test_no_eval() -> Result<()> {
+    run(async {
+        assert!(evaluates_code(true).await?);
+        assert!(!evaluates_code(false).await?);
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::environment::Environment;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, evaluated_files, fixture, node_env, run,
+};
+
+const OUTPUT: &str = "crates/turbopack-tests/tests/node_dev_chunks/output/";
+
+/// The path, relative to the output root, and the code of the evaluate chunk
+/// of `index.js` of the `node_dev_chunks` fixture.
+async fn evaluate_chunk(env: Vc<Environment>) -> Result<(String, String)> {
+    let fixture = fixture("node_dev_chunks");
+    let files = evaluated_files(
+        Vc::upcast(dev_chunking_context(fixture, env).build()),
+        fixture,
+        "index.js",
+        asset_context(env),
+    )
+    .await?;
+    let (path, code) = files
+        .into_iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .filter_map(|(path, code)| Some((path, code?)))
+        .find(|(_, code)| code.contains("runtimeModuleIds"))
+        .expect("there is no evaluate chunk");
+    Ok((path.strip_prefix(OUTPUT).unwrap().to_string(), code))
+}
+
+/// In Node.js, the evaluate chunk exports the exports of its entry when it's
+/// loaded with `require()`.
+#[tokio::test]
+async fn test_node_evaluate_chunk_exports_entry() -> Result<()> {
+    run(async {
+        let (path, code) = evaluate_chunk(node_env()).await?;
+        assert!(
+            code.contains(&format!(
+                "module.exports = globalThis.TURBOPACK_CHUNK_EXPORTS?.[{path:?}];"
+            )),
+            "{code}"
+        );
+        Ok(())
+    })
+    .await
+}
+
+/// Evaluate chunks for browsers aren't CommonJS modules.
+#[tokio::test]
+async fn test_browser_evaluate_chunk_exports_nothing() -> Result<()> {
+    run(async {
+        let (_, code) = evaluate_chunk(browser_env()).await?;
+        assert!(!code.contains("TURBOPACK_CHUNK_EXPORTS"), "{code}");
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::chunk::{availability_info::AvailabilityInfo, ChunkingContext};
+
+use crate::util::{
+    asset_context_with, browser_env, dev_chunking_context, entry, fixture, issues, project_root,
+    run,
+};
+
+/// The titles of the issues reported for the chunk group of the
+/// `peer_dependencies` fixture.
+async fn peer_dependency_issues(check_peer_dependencies: bool) -> Result<Vec<String>> {
+    let fixture = fixture("peer_dependencies");
+    let env = browser_env();
+    let asset_context = asset_context_with(
+        env,
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            ..Default::default()
+        },
+        ResolveOptionsContext {
+            enable_node_modules: Some(project_root()),
+            ..Default::default()
+        },
+    );
+    let mut chunking_context = dev_chunking_context(fixture, env);
+    if check_peer_dependencies {
+        chunking_context = chunking_context.check_peer_dependencies();
+    }
+    let chunking_context = chunking_context.build();
+
+    let entry = entry(fixture, "index.js", asset_context);
+    let chunk_group =
+        chunking_context.chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root));
+    chunk_group.await?;
+
+    let mut titles: Vec<String> = issues(chunk_group)
+        .await?
+        .into_iter()
+        .map(|(title, _)| title)
+        .collect();
+    titles.sort();
+    Ok(titles)
+}
+
+/// Every package that declares a mismatching peer dependency is reported, even
+/// when another package declares the same one. Prereleases of versions in the
+/// range satisfy it.
+#[tokio::test]
+async fn test_peer_dependency_mismatches_are_reported_per_package() -> Result<()> {
+    run(async {
+        assert_eq!(
+            peer_dependency_issues(true).await?,
+            vec![
+                "Peer dependency host of plugin-a is resolved outside of its declared range"
+                    .to_string(),
+                "Peer dependency host of plugin-b is resolved outside of its declared range"
+                    .to_string(),
+            ]
+        );
+        assert!(
+            peer_dependency_issues(false).await?.is_empty(),
+            "peer dependencies are only checked when enabled"
+        );
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::{
+    asset::Asset,
+    chunk::{
+        availability_info::AvailabilityInfo,
+        prefetch_manifest::{
+            prefetch_manifest_helper_source, PrefetchManifestAsset, PrefetchRoutes,
+            RoutePrefetchResources,
+        },
+        ChunkingContext,
+    },
+    output::OutputAsset,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run, text};
+
+/// Every route lists the JS chunks of its chunk group, by their paths relative
+/// to the output root, and the helper only refers to the manifest by its URL.
+#[tokio::test]
+async fn test_prefetch_manifest_lists_route_chunks() -> Result<()> {
+    run(async {
+        let fixture = fixture("prefetch_manifest");
+        let output_root = fixture.join("output".to_string());
+        let env = browser_env();
+        let asset_context = asset_context(env);
+        let chunking_context = dev_chunking_context(fixture, env).build();
+
+        let mut routes = BTreeMap::new();
+        for route in ["a", "b"] {
+            let entry = entry(fixture, &format!("{route}.js"), asset_context);
+            routes.insert(format!("/{route}"), Vc::upcast(entry));
+        }
+        let routes: Vc<PrefetchRoutes> = Vc::cell(routes);
+
+        let manifest_asset = PrefetchManifestAsset::new(
+            output_root.join("prefetch-manifest.json".to_string()),
+            Vc::upcast(chunking_context),
+            routes,
+        );
+        let manifest: BTreeMap<String, RoutePrefetchResources> = serde_json::from_str(
+            &text(manifest_asset.content())
+                .await?
+                .context("the manifest must be a file")?,
+        )?;
+        assert_eq!(
+            manifest.keys().collect::<Vec<_>>(),
+            vec!["/a", "/b"],
+            "every route is listed"
+        );
+
+        let output_root_path = output_root.await?;
+        for (route, &module) in routes.await?.iter() {
+            let resources = manifest.get(route).context("the route is missing")?;
+            assert!(!resources.js.is_empty());
+            let assets = chunking_context
+                .chunk_group(module, Value::new(AvailabilityInfo::Root))
+                .await?
+                .assets
+                .await?;
+            let mut paths = Vec::new();
+            for &asset in assets.iter() {
+                let path = asset.ident().path().await?;
+                paths.extend(output_root_path.get_path_to(&path).map(|p| p.to_string()));
+            }
+            for js in &resources.js {
+                assert!(js.ends_with(".js"));
+                assert!(paths.contains(js), "{js} is not a chunk of {route}");
+            }
+        }
+
+        let helper = prefetch_manifest_helper_source(
+            fixture.join("prefetch.js".to_string()),
+            "/prefetch-manifest.json".to_string(),
+        );
+        let helper = text(helper.content())
+            .await?
+            .context("the helper must be a file")?;
+        assert!(helper.contains("\"/prefetch-manifest.json\""));
+        assert!(helper.contains("export async function prefetchResources(route)"));
+        for resources in manifest.values() {
+            for js in &resources.js {
+                assert!(!helper.contains(js.as_str()));
+            }
+        }
+        Ok(())
+    })
+    .await
+}
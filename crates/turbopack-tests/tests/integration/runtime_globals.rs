@@ -0,0 +1,71 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+
+use crate::util::{
+    asset_context, browser_env, dev_chunking_context, entry, evaluated_files, fixture, run,
+};
+
+/// The files of the `runtime_globals` fixture whose modules the evaluate chunk
+/// of `index.js` evaluates, in order.
+async fn runtime_modules(runtime_globals: &[&str]) -> Result<Vec<String>> {
+    let fixture = fixture("runtime_globals");
+    let env = browser_env();
+    let asset_context = asset_context(env);
+    let mut chunking_context = dev_chunking_context(fixture, env);
+    for name in runtime_globals {
+        chunking_context = chunking_context.runtime_global(entry(fixture, name, asset_context));
+    }
+
+    for (_, code) in evaluated_files(
+        Vc::upcast(chunking_context.build()),
+        fixture,
+        "index.js",
+        asset_context,
+    )
+    .await?
+    {
+        let Some(code) = code else { continue };
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        let Some((_, ids)) = code.split_once(r#""runtimeModuleIds":["#) else {
+            continue;
+        };
+        let ids = &ids[..ids.find(']').unwrap()];
+        return Ok(ids
+            .split(',')
+            .map(|id| {
+                let (_, file) = id.split_once("runtime_globals/").unwrap();
+                file[..file.find(".js").unwrap() + 3].to_string()
+            })
+            .collect());
+    }
+    panic!("there is no evaluate chunk");
+}
+
+/// Runtime globals are evaluated before the entries, in the order they are
+/// registered.
+#[tokio::test]
+async fn test_runtime_globals_are_evaluated_first() -> Result<()> {
+    run(async {
+        assert_eq!(runtime_modules(&[]).await?, vec!["index.js"]);
+        assert_eq!(
+            runtime_modules(&["router.js", "telemetry.js"]).await?,
+            vec!["router.js", "telemetry.js", "index.js"]
+        );
+        Ok(())
+    })
+    .await
+}
+
+/// An entry that is a runtime global as well is only evaluated once, as a
+/// runtime global.
+#[tokio::test]
+async fn test_runtime_globals_are_deduplicated() -> Result<()> {
+    run(async {
+        assert_eq!(
+            runtime_modules(&["index.js", "router.js"]).await?,
+            vec!["index.js", "router.js"]
+        );
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::{
+    asset::Asset,
+    chunk::{availability_info::AvailabilityInfo, ChunkingContext, EvaluatableAssets},
+    output::OutputAsset,
+};
+
+use crate::util::{
+    asset_context, browser_env, build_chunking_context, dev_chunking_context, entry, fixture, run,
+    text,
+};
+
+/// Builds `sw.js` of the `service_worker` fixture as a service worker that
+/// precaches the chunk group of `app.js`, and checks that the service worker
+/// is a single chunk at its fixed path without the development runtime.
+async fn check_service_worker(build: bool) -> Result<()> {
+    let fixture = fixture("service_worker");
+    let output_root = fixture.join("output".to_string());
+    let env = browser_env();
+    let asset_context = asset_context(env);
+    let chunking_context: Vc<Box<dyn ChunkingContext>> = if build {
+        Vc::upcast(build_chunking_context(fixture, env).build())
+    } else {
+        Vc::upcast(dev_chunking_context(fixture, env).build())
+    };
+
+    let app = entry(fixture, "app.js", asset_context);
+    let precache = chunking_context
+        .chunk_group(Vc::upcast(app), Value::new(AvailabilityInfo::Root))
+        .await?
+        .assets;
+
+    let sw = entry(fixture, "sw.js", asset_context);
+    let sw_path = output_root.join("sw.js".to_string());
+    let assets = chunking_context
+        .service_worker_chunk_group(sw_path, EvaluatableAssets::one(sw), precache)
+        .await?
+        .assets
+        .await?;
+    assert_eq!(assets.len(), 2);
+    let manifest = assets[0];
+    let chunk = *assets.last().context("the service worker has no chunk")?;
+    assert_eq!(
+        chunk.ident().path().await?.path,
+        sw_path.await?.path,
+        "the service worker must be emitted at its fixed path"
+    );
+    assert!(manifest
+        .ident()
+        .path()
+        .await?
+        .path
+        .ends_with("sw.precache.json"));
+
+    let output_root_path = output_root.await?;
+    let mut precached_chunks = Vec::new();
+    for &asset in precache.await?.iter() {
+        let path = asset.ident().path().await?;
+        if path.extension_ref() == Some("js") {
+            precached_chunks.extend(output_root_path.get_path_to(&path).map(|p| p.to_string()));
+        }
+    }
+    assert!(!precached_chunks.is_empty());
+
+    let entries: serde_json::Value = serde_json::from_str(
+        &text(manifest.content())
+            .await?
+            .context("the precache manifest must be a file")?,
+    )?;
+    let urls = entries
+        .as_array()
+        .context("the precache manifest must be an array")?
+        .iter()
+        .map(|entry| entry["url"].as_str().unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+    for chunk in &precached_chunks {
+        assert!(urls.contains(chunk), "{chunk} isn't precached: {urls:?}");
+    }
+
+    let code = text(chunk.content())
+        .await?
+        .context("the service worker must be a file")?;
+    assert!(code.contains("self.TURBOPACK_PRECACHE_MANIFEST = ["));
+    assert!(
+        code.contains("service-worker-fixture"),
+        "the imports of the service worker must be part of its chunk"
+    );
+    assert!(code.contains("evaluateWorkletEntries("));
+    assert!(
+        !code.contains("CHUNK_LISTS") && !code.contains("importScripts"),
+        "the service worker must not contain the development runtime"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dev_service_worker_is_standalone() -> Result<()> {
+    run(check_service_worker(false)).await
+}
+
+#[tokio::test]
+async fn test_build_service_worker_is_standalone() -> Result<()> {
+    run(check_service_worker(true)).await
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::{
+    asset::Asset,
+    chunk::EvaluatableAssets,
+    environment::{EdgeWorkerEnvironment, Environment, ExecutionEnvironment, ServerAddr},
+    output::OutputAsset,
+};
+use turbopack_dev::EcmascriptDevBundle;
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run, text};
+
+/// Bundles `index.js` of the `single_file_bundle` fixture into
+/// `output/bundle.js` for `environment`.
+fn bundle(environment: Vc<Environment>) -> Vc<EcmascriptDevBundle> {
+    let fixture = fixture("single_file_bundle");
+    let chunking_context = dev_chunking_context(fixture, environment).build();
+    let entry = entry(fixture, "index.js", asset_context(environment));
+    EcmascriptDevBundle::new(
+        fixture.join("output/bundle.js".to_string()),
+        chunking_context,
+        EvaluatableAssets::one(entry),
+    )
+}
+
+/// All modules, including those of async chunk groups, end up in the bundle,
+/// and the bundle references no chunks, only its source map.
+#[tokio::test]
+async fn test_single_file_bundle() -> Result<()> {
+    run(async {
+        let environment = Environment::new(Value::new(ExecutionEnvironment::EdgeWorker(
+            EdgeWorkerEnvironment {
+                server_addr: ServerAddr::default().cell(),
+            }
+            .cell(),
+        )));
+        let bundle = bundle(environment);
+
+        let code = text(bundle.content())
+            .await?
+            .context("the bundle must be a file")?;
+        for marker in ["bundle-entry", "bundle-shared", "bundle-lazy"] {
+            assert!(code.contains(marker), "{marker} isn't bundled");
+        }
+        assert!(code.ends_with("//# sourceMappingURL=bundle.js.map"));
+
+        let mut extensions = Vec::new();
+        for &reference in bundle.references().await?.iter() {
+            let path = reference.ident().path().await?;
+            extensions.push(path.extension_ref().map(|extension| extension.to_string()));
+        }
+        assert!(extensions.contains(&Some("map".to_string())));
+        assert!(!extensions.contains(&Some("js".to_string())));
+        Ok(())
+    })
+    .await
+}
+
+/// Environments that load chunks on demand can't be bundled into a single
+/// file.
+#[tokio::test]
+async fn test_single_file_bundle_requires_no_chunk_loading() -> Result<()> {
+    run(async {
+        let environment = browser_env();
+        let error = bundle(environment)
+            .content()
+            .await
+            .err()
+            .map(|error| format!("{error:#}"));
+        assert!(
+            error
+                .as_deref()
+                .is_some_and(|error| error.contains("as its environment loads chunks on demand")),
+            "{error:?}"
+        );
+        Ok(())
+    })
+    .await
+}
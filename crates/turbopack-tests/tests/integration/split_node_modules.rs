@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::chunk::{
+    availability_info::AvailabilityInfo, plan::ChunkPlan, ChunkingContext,
+};
+
+use crate::util::{
+    asset_context, browser_env, chunk_of, dev_chunking_context, entry, fixture, run,
+};
+
+/// Plans the chunks of the `split_node_modules` fixture. The minimum chunk size
+/// is disabled so that its small modules aren't merged into a single chunk.
+async fn planned_chunks(split_node_modules: bool) -> Result<Vec<ChunkPlan>> {
+    let fixture = fixture("split_node_modules");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env)
+        .min_chunk_size(0)
+        .split_node_modules(split_node_modules)
+        .dry_run()
+        .build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let result = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?;
+    let plan = result.plan.context("a dry run must return a plan")?.await?;
+    Ok(plan.chunks.clone())
+}
+
+/// By default, modules in `node_modules` are placed into vendor chunks apart
+/// from app code.
+#[tokio::test]
+async fn test_node_modules_are_split_by_default() -> Result<()> {
+    run(async {
+        let chunks = planned_chunks(true).await?;
+
+        let vendors = chunk_of(&chunks, "node_modules/tiny-format/index.js")?;
+        assert!(vendors.key.contains("-vendors"), "{}", vendors.key);
+        assert!(
+            vendors
+                .modules
+                .iter()
+                .all(|module| module.contains("node_modules/")),
+            "{:?}",
+            vendors.modules
+        );
+
+        let app = chunk_of(&chunks, "split_node_modules/app.js")?;
+        assert!(app.key.contains("-app"), "{}", app.key);
+        assert!(app
+            .modules
+            .iter()
+            .all(|module| !module.contains("node_modules/")));
+        Ok(())
+    })
+    .await
+}
+
+/// With `split_node_modules` disabled, modules in `node_modules` are chunked
+/// like app code.
+#[tokio::test]
+async fn test_node_modules_can_share_chunks_with_app_code() -> Result<()> {
+    run(async {
+        let chunks = planned_chunks(false).await?;
+
+        assert!(chunks.iter().all(|chunk| !chunk.key.contains("-vendors")));
+        let vendors = chunk_of(&chunks, "node_modules/tiny-format/index.js")?;
+        assert!(vendors
+            .modules
+            .iter()
+            .any(|module| module.contains("split_node_modules/app.js")));
+        Ok(())
+    })
+    .await
+}
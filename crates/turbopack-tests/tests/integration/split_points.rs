@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::{
+    chunk::{availability_info::AvailabilityInfo, plan::ChunkGroupPlanTree, ChunkingContext},
+    context::AssetContext,
+    file_source::FileSource,
+    module::ModulesSet,
+    reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run};
+
+/// Plans the chunk group of the `split_points` fixture, with `route.js` as a
+/// split point when `split_route` is set.
+async fn chunk_group_plan(split_route: bool) -> Result<ChunkGroupPlanTree> {
+    let fixture = fixture("split_points");
+    let env = browser_env();
+    let asset_context = asset_context(env);
+
+    let mut split_points = Vec::new();
+    if split_route {
+        split_points.push(
+            asset_context
+                .process(
+                    Vc::upcast(FileSource::new(fixture.join("route.js".to_string()))),
+                    Value::new(ReferenceType::EcmaScriptModules(
+                        EcmaScriptModulesReferenceSubType::Import,
+                    )),
+                )
+                .module()
+                .resolve()
+                .await?,
+        );
+    }
+    let chunking_context = dev_chunking_context(fixture, env)
+        .split_points(Vc::<ModulesSet>::cell(split_points.into_iter().collect()))
+        .dry_run()
+        .build();
+
+    let entry = entry(fixture, "index.js", asset_context);
+    let result = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?;
+    ChunkGroupPlanTree::capture(result.plan.context("a dry run must return a plan")?).await
+}
+
+fn contains_module(plan: &ChunkGroupPlanTree, name: &str) -> bool {
+    plan.chunks
+        .iter()
+        .flat_map(|chunk| chunk.modules.iter())
+        .any(|module| module.contains(name))
+}
+
+/// A statically imported split point is moved out of the chunk group of its
+/// importer into an async chunk group of its own.
+#[tokio::test]
+async fn test_split_points_get_their_own_chunk_group() -> Result<()> {
+    run(async {
+        let plan = chunk_group_plan(false).await?;
+        assert!(contains_module(&plan, "split_points/route.js"));
+        assert!(plan.async_chunk_groups.is_empty());
+
+        let plan = chunk_group_plan(true).await?;
+        assert!(contains_module(&plan, "split_points/index.js"));
+        assert!(contains_module(&plan, "split_points/shared.js"));
+        assert!(!contains_module(&plan, "split_points/route.js"));
+        assert_eq!(plan.async_chunk_groups.len(), 1);
+        let route = plan.async_chunk_groups.values().next().unwrap();
+        assert!(contains_module(route, "split_points/route.js"));
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{asset::Asset, chunk::ChunkingContext, output::OutputAsset};
+use turbopack_static::manifest::{static_assets_manifest_helper_source, StaticAssetsManifest};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run, text};
+
+const LOGO: &str = "crates/turbopack-tests/tests/static_assets_manifest/logo.svg";
+
+/// The manifest maps the original paths of the assets to their hashed URLs
+/// and references the assets, so they are emitted with it.
+#[tokio::test]
+async fn test_static_assets_manifest() -> Result<()> {
+    run(async {
+        let fixture = fixture("static_assets_manifest");
+        let env = browser_env();
+        let chunking_context: Vc<Box<dyn ChunkingContext>> =
+            Vc::upcast(dev_chunking_context(fixture, env).build());
+        let entries = Vc::cell(vec![Vc::upcast(entry(
+            fixture,
+            "index.js",
+            asset_context(env),
+        ))]);
+
+        let manifest = StaticAssetsManifest::new(
+            fixture.join("output/assets-manifest.json".to_string()),
+            chunking_context,
+            entries,
+        );
+        let json = text(manifest.content()).await?.unwrap();
+        let urls: BTreeMap<String, String> = serde_json::from_str(&json)?;
+        assert_eq!(urls.keys().collect::<Vec<_>>(), vec![LOGO], "{json}");
+        let url = &urls[LOGO];
+        assert!(url.starts_with("/static/logo."), "{url}");
+        assert!(url.ends_with(".svg"), "{url}");
+
+        let references = manifest.references().await?;
+        assert_eq!(references.len(), 1);
+        let asset_path = references[0].ident().path().await?;
+        assert!(url.ends_with(asset_path.file_name()), "{url}");
+
+        let helper = static_assets_manifest_helper_source(
+            fixture.join("assets.js".to_string()),
+            chunking_context,
+            entries,
+        );
+        let code = text(helper.content()).await?.unwrap();
+        assert!(code.contains("export function assetUrl(path)"), "{code}");
+        assert!(code.contains(LOGO), "{code}");
+        assert!(code.contains(url.as_str()), "{code}");
+        Ok(())
+    })
+    .await
+}
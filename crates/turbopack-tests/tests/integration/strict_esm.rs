@@ -0,0 +1,40 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack::ecmascript::chunk::{check_strict_esm, StrictEsm};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, issues, run};
+
+/// The titles and file paths of the issues reported for the chunk group of
+/// the `strict_esm` fixture in `mode`.
+async fn strict_esm_issues(mode: StrictEsm) -> Result<Vec<(String, String)>> {
+    let fixture = fixture("strict_esm");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env).strict_esm(mode).build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let check = check_strict_esm(
+        Vc::upcast(chunking_context),
+        Vc::cell(vec![Vc::upcast(entry)]),
+    );
+    check.await?;
+
+    issues(check).await
+}
+
+/// Only the CommonJS module of the chunk group is reported. The `require()`
+/// call of the lazily imported module belongs to its own chunk group.
+#[tokio::test]
+async fn test_strict_esm_reports_commonjs_modules_of_the_chunk_group() -> Result<()> {
+    run(async {
+        assert_eq!(
+            strict_esm_issues(StrictEsm::Warn).await?,
+            vec![(
+                "CommonJS module in strict ESM mode".to_string(),
+                "crates/turbopack-tests/tests/strict_esm/cjs.js".to_string()
+            )]
+        );
+        assert!(strict_esm_issues(StrictEsm::Off).await?.is_empty());
+        Ok(())
+    })
+    .await
+}
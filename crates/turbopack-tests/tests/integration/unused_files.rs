@@ -0,0 +1,83 @@
+use anyhow::Result;
+use turbo_tasks::{Value, Vc};
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::{
+    context::AssetContext,
+    file_source::FileSource,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    unused_files::{unused_files, UnusedFilesOptions},
+};
+
+use crate::util::{asset_context_with, browser_env, fixture, run};
+
+/// The TypeScript files of the `unused_files` fixture that `index.ts` doesn't
+/// import, relative to the fixture. `enable_types` keeps the imports of types.
+async fn unused_fixture_files(enable_types: bool) -> Result<Vec<String>> {
+    let fixture = fixture("unused_files");
+    let module_options_context = if enable_types {
+        ModuleOptionsContext {
+            enable_types: true,
+            ..Default::default()
+        }
+    } else {
+        ModuleOptionsContext {
+            enable_typescript_transform: Some(Default::default()),
+            ..Default::default()
+        }
+    };
+    let asset_context = asset_context_with(
+        browser_env(),
+        module_options_context,
+        ResolveOptionsContext {
+            enable_typescript: true,
+            ..Default::default()
+        },
+    );
+    let entry = asset_context
+        .process(
+            Vc::upcast(FileSource::new(fixture.join("index.ts".to_string()))),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        )
+        .module();
+    let options = UnusedFilesOptions {
+        include: vec!["**/*.ts".to_string()],
+        public_dirs: vec!["public".to_string()],
+        ..Default::default()
+    }
+    .cell();
+
+    let fixture_ref = fixture.await?;
+    let mut paths = Vec::new();
+    for &file in unused_files(fixture, Vc::cell(vec![entry]), options)
+        .await?
+        .iter()
+    {
+        paths.extend(
+            fixture_ref
+                .get_path_to(&*file.await?)
+                .map(|p| p.to_string()),
+        );
+    }
+    Ok(paths)
+}
+
+/// Files only imported for their types are used, as long as the TypeScript
+/// transform doesn't remove the imports of types. Files in public directories
+/// are never reported.
+#[tokio::test]
+async fn test_unused_files_keeps_type_imports() -> Result<()> {
+    run(async {
+        assert_eq!(
+            unused_fixture_files(true).await?,
+            vec!["unused.ts".to_string()]
+        );
+        assert_eq!(
+            unused_fixture_files(false).await?,
+            vec!["types.ts".to_string(), "unused.ts".to_string()]
+        );
+        Ok(())
+    })
+    .await
+}
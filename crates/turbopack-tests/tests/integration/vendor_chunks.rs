@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use turbo_tasks::Value;
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+};
+use turbopack_core::{
+    chunk::{availability_info::AvailabilityInfo, ChunkingContextExt, EvaluatableAssets},
+    module::Module,
+};
+
+use crate::util::{
+    all_files, asset_context_with, browser_env, dev_chunking_context, fixture, project_root, run,
+};
+
+/// The contents of the JavaScript assets emitted for the chunk group of
+/// `entry` in the `vendor_chunks` fixture, by path. This includes the async
+/// chunk groups of the entry.
+async fn emitted_chunks(
+    entry: &str,
+    isolate_vendor_chunks: bool,
+) -> Result<BTreeMap<String, String>> {
+    let fixture = fixture("vendor_chunks");
+    let env = browser_env();
+    let asset_context = asset_context_with(
+        env,
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            ..Default::default()
+        },
+        ResolveOptionsContext {
+            enable_node_modules: Some(project_root()),
+            ..Default::default()
+        },
+    );
+    let chunking_context = dev_chunking_context(fixture, env)
+        .isolate_vendor_chunks(isolate_vendor_chunks)
+        .build();
+
+    let entry = crate::util::entry(fixture, entry, asset_context);
+    let assets = chunking_context.evaluated_chunk_group_assets(
+        entry.ident(),
+        EvaluatableAssets::one(entry),
+        Value::new(AvailabilityInfo::Root),
+    );
+
+    Ok(all_files(assets)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .filter_map(|(path, code)| Some((path, code?)))
+        .collect())
+}
+
+/// The chunks of `chunks` that contain the module of the `vendor` package.
+fn vendor_chunks(chunks: &BTreeMap<String, String>) -> BTreeMap<&String, &String> {
+    chunks
+        .iter()
+        .filter(|(_, content)| content.contains("VENDOR_MARKER"))
+        .collect()
+}
+
+/// Edits to app code, including the app code of parent chunk groups, must not
+/// change the vendor chunks, so that they stay cached in the browser.
+#[tokio::test]
+async fn test_vendor_chunks_stable_across_app_edits() -> Result<()> {
+    run(async {
+        let a = emitted_chunks("a/index.js", true).await?;
+        let b = emitted_chunks("b/index.js", true).await?;
+        let vendor_a = vendor_chunks(&a);
+        assert!(!vendor_a.is_empty(), "no vendor chunk was emitted");
+        for content in vendor_a.values() {
+            assert!(!content.contains("APP_A_MARKER"));
+            assert!(!content.contains("LAZY_A_MARKER"));
+        }
+        assert_eq!(vendor_a, vendor_chunks(&b));
+
+        // The vendor module is available from the parent chunk group, but the
+        // async chunk group still loads the same vendor chunk as when it has
+        // no parent.
+        let lazy = emitted_chunks("a/lazy.js", true).await?;
+        for (path, content) in vendor_chunks(&lazy) {
+            assert_eq!(vendor_a.get(path), Some(&content));
+        }
+
+        // Without isolation, vendor modules are merged with the (small) app
+        // code.
+        let a = emitted_chunks("a/index.js", false).await?;
+        let b = emitted_chunks("b/index.js", false).await?;
+        assert_ne!(vendor_chunks(&a), vendor_chunks(&b));
+        Ok(())
+    })
+    .await
+}
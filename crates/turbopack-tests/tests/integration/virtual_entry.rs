@@ -0,0 +1,64 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileContent;
+use turbopack_core::{
+    asset::Asset,
+    chunk::EvaluatableAssets,
+    output::{OutputAsset, OutputAssets},
+    virtual_entry::VirtualEntry,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, fixture, run};
+
+/// The code of all JS chunks of `assets`, read strongly consistent so changes
+/// to the entry are picked up.
+async fn chunks_code(assets: Vc<OutputAssets>) -> Result<String> {
+    let mut code = String::new();
+    for &asset in assets.strongly_consistent().await?.iter() {
+        if asset.ident().path().await?.extension_ref() != Some("js") {
+            continue;
+        }
+        let content = asset.content().file_content().strongly_consistent().await?;
+        if let FileContent::Content(file) = &*content {
+            code.push_str(&file.content().to_str()?);
+        }
+    }
+    Ok(code)
+}
+
+/// The chunk group of a virtual entry follows changes of its code, and its
+/// imports are resolved relative to its path.
+#[tokio::test]
+async fn test_virtual_entry_code_can_be_replaced() -> Result<()> {
+    run(async {
+        let fixture = fixture("virtual_entry");
+        let env = browser_env();
+        let asset_context = asset_context(env);
+        let chunking_context = dev_chunking_context(fixture, env).build();
+
+        let entry = VirtualEntry::new(
+            fixture.join("repl.js".to_string()),
+            "import { shared } from \"./shared.js\";\nconsole.log(\"first-version\", shared);\n"
+                .to_string(),
+        );
+        let assets = entry.evaluated_chunk_group_assets(
+            asset_context,
+            Vc::upcast(chunking_context),
+            EvaluatableAssets::empty(),
+        );
+
+        let code = chunks_code(assets).await?;
+        assert!(code.contains("first-version"));
+        assert!(code.contains("virtual-shared"));
+
+        entry
+            .set_code("console.log(\"second-version\");\n".to_string())
+            .await?;
+        let code = chunks_code(assets).await?;
+        assert!(code.contains("second-version"));
+        assert!(!code.contains("first-version"));
+        assert!(!code.contains("virtual-shared"));
+        Ok(())
+    })
+    .await
+}
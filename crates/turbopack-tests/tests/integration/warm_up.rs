@@ -0,0 +1,56 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_core::{
+    chunk::{
+        warm_up::{warm_up, WarmUpEntry, WarmUpProgress},
+        EvaluatableAssets,
+    },
+    module::Module,
+};
+
+use crate::util::{asset_context, browser_env, dev_chunking_context, entry, fixture, run};
+
+/// Warms up the chunk group of one entry and the evaluated chunk group of
+/// another, and reports the progress of each.
+#[tokio::test]
+async fn test_warm_up_reports_progress() -> Result<()> {
+    run(async {
+        let fixture = fixture("warm_up");
+        let env = browser_env();
+        let asset_context = asset_context(env);
+        let chunking_context = dev_chunking_context(fixture, env).build();
+
+        let a = entry(fixture, "a.js", asset_context);
+        let b = entry(fixture, "b.js", asset_context);
+        let entries = vec![
+            WarmUpEntry::Module(Vc::upcast(a)),
+            WarmUpEntry::Evaluated {
+                ident: b.ident(),
+                evaluatable_assets: EvaluatableAssets::one(b),
+            },
+        ];
+
+        let progress: Mutex<Vec<WarmUpProgress>> = Mutex::new(Vec::new());
+        warm_up(Vc::upcast(chunking_context), entries, &|p| {
+            progress.lock().unwrap().push(p)
+        })
+        .await?;
+
+        let mut progress = progress.into_inner().unwrap();
+        progress.sort_by_key(|p| p.completed);
+        assert_eq!(progress.len(), 2);
+        assert!(progress.iter().all(|p| p.total == 2));
+        assert_eq!(progress[0].completed, 1);
+        assert_eq!(progress[1].completed, 2);
+        for file in ["warm_up/a.js", "warm_up/b.js"] {
+            assert!(
+                progress.iter().any(|p| p.entry.contains(file)),
+                "{file} isn't reported: {progress:?}"
+            );
+        }
+        Ok(())
+    })
+    .await
+}
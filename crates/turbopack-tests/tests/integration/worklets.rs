@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use turbo_tasks::{Value, Vc};
+use turbopack_core::chunk::{availability_info::AvailabilityInfo, ChunkingContext};
+
+use crate::util::{
+    all_files, asset_context, browser_env, dev_chunking_context, entry, fixture, run,
+};
+
+/// A JS output asset and its code.
+struct JsAsset {
+    file_name: String,
+    code: String,
+}
+
+/// Returns all JS assets of the chunk group of `index.js` of the `worklets`
+/// fixture, including the ones that are only referenced by other assets.
+async fn js_assets() -> Result<Vec<JsAsset>> {
+    let fixture = fixture("worklets");
+    let env = browser_env();
+    let chunking_context = dev_chunking_context(fixture, env).build();
+
+    let entry = entry(fixture, "index.js", asset_context(env));
+    let assets = chunking_context
+        .chunk_group(Vc::upcast(entry), Value::new(AvailabilityInfo::Root))
+        .await?
+        .assets;
+    Ok(all_files(assets)
+        .await?
+        .into_iter()
+        .filter(|(path, _)| path.ends_with(".js"))
+        .filter_map(|(path, code)| {
+            Some(JsAsset {
+                file_name: path.rsplit('/').next()?.to_string(),
+                code: code?,
+            })
+        })
+        .collect())
+}
+
+/// The module passed to `CSS.paintWorklet.addModule` is placed with its
+/// dependencies into a standalone worklet chunk, whose URL replaces the
+/// `new URL(...)` in the importer.
+#[tokio::test]
+async fn test_paint_worklet_chunk() -> Result<()> {
+    run(async {
+        let assets = js_assets().await?;
+        let worklet = assets
+            .iter()
+            .find(|asset| asset.code.contains("worklet-checkerboard"))
+            .context("the worklet module must be emitted")?;
+        assert!(worklet.code.contains("evaluateWorkletEntries("));
+        assert!(worklet.code.contains("fillRect(0, 0, "));
+        assert!(
+            !worklet.code.contains("CHUNK_LISTS"),
+            "the worklet chunk must not contain the development runtime"
+        );
+        assert!(
+            !worklet.code.contains("worklet-host"),
+            "the importer must not be part of the worklet chunk"
+        );
+
+        let host = assets
+            .iter()
+            .find(|asset| asset.code.contains("worklet-host"))
+            .context("the importer must be emitted")?;
+        assert!(
+            !host.code.contains("worklet-checkerboard"),
+            "the worklet module must not be part of the chunks of its importer"
+        );
+        assert!(
+            assets
+                .iter()
+                .any(|asset| !asset.code.contains("worklet-checkerboard")
+                    && asset.code.contains(&worklet.file_name)),
+            "the chunks of the importer must reference the URL of the worklet chunk"
+        );
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,4 @@
+import WebSocket from "ws";
+import { server } from "pkg/lib/server.js";
+
+console.log(WebSocket, server);
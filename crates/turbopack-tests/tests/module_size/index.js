@@ -0,0 +1,3 @@
+import data from "./data.js";
+
+console.log(data.length);
@@ -0,0 +1,5 @@
+import sdkUrl from "./sdk.js";
+import widgetUrl from "./legacy/widget.js";
+import { util } from "./util.js";
+
+console.log("no-bundle-entry", sdkUrl, widgetUrl, util);
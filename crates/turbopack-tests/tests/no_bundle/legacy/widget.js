@@ -0,0 +1 @@
+var widget = "no-bundle-legacy";
@@ -0,0 +1,2 @@
+/* no-bundle-sdk: must be served byte-identical */
+(function () { window.sdk = { version: 1 } })()
@@ -0,0 +1 @@
+export const util = "no-bundle-util";
@@ -0,0 +1,5 @@
+import a from "plugin-a";
+import b from "plugin-b";
+import next from "plugin-next";
+
+console.log(a, b, next);
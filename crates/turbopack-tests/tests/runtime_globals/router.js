@@ -0,0 +1 @@
+globalThis.router = "router";
@@ -0,0 +1 @@
+globalThis.telemetry = "telemetry";
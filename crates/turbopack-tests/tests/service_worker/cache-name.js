@@ -0,0 +1 @@
+export const cacheName = "service-worker-fixture";
@@ -0,0 +1,11 @@
+import { cacheName } from "./cache-name.js";
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(
+    caches
+      .open(cacheName)
+      .then((cache) =>
+        cache.addAll(self.TURBOPACK_PRECACHE_MANIFEST.map((entry) => entry.url))
+      )
+  );
+});
@@ -84,6 +84,8 @@ struct SnapshotOptions {
     runtime_type: RuntimeType,
     #[serde(default)]
     environment: SnapshotEnvironment,
+    #[serde(default)]
+    static_chunk_groups: bool,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -109,6 +111,7 @@ impl Default for SnapshotOptions {
             runtime: Default::default(),
             runtime_type: default_runtime_type(),
             environment: Default::default(),
+            static_chunk_groups: false,
         }
     }
 }
@@ -291,11 +294,20 @@ async fn run_test(resource: String) -> Result<Vc<FileSystemPath>> {
     let static_root_path = path.join("static".to_string());
 
     let chunking_context: Vc<Box<dyn ChunkingContext>> = match options.runtime {
-        Runtime::Dev => Vc::upcast(
-            DevChunkingContext::builder(project_root, path, chunk_root_path, static_root_path, env)
-                .runtime_type(options.runtime_type)
-                .build(),
-        ),
+        Runtime::Dev => {
+            let mut builder = DevChunkingContext::builder(
+                project_root,
+                path,
+                chunk_root_path,
+                static_root_path,
+                env,
+            )
+            .runtime_type(options.runtime_type);
+            if options.static_chunk_groups {
+                builder = builder.static_chunk_groups();
+            }
+            Vc::upcast(builder.build())
+        }
         Runtime::Build => Vc::upcast(
             BuildChunkingContext::builder(
                 project_root,
@@ -375,6 +387,18 @@ async fn run_test(resource: String) -> Result<Vc<FileSystemPath>> {
         bail!("Entry module is not chunkable, so it can't be used to bootstrap the application")
     };
 
+    if options.static_chunk_groups {
+        for chunk in chunks.await?.iter() {
+            let chunk_path = chunk.ident().path().await?;
+            if chunk_path.extension_ref() == Some("js") {
+                bail!(
+                    "static chunk group contains the JavaScript asset {}",
+                    chunk_path.path
+                );
+            }
+        }
+    }
+
     let mut seen = HashSet::new();
     let mut queue: VecDeque<_> = chunks.await?.iter().copied().collect();
 
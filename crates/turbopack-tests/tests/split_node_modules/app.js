@@ -0,0 +1 @@
+export const app = "split-node-modules-app";
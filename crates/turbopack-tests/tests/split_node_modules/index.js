@@ -0,0 +1,4 @@
+import { format } from "tiny-format";
+import { app } from "./app.js";
+
+console.log(format(app));
@@ -0,0 +1,4 @@
+import { route } from "./route.js";
+import { shared } from "./shared.js";
+
+console.log(route, shared);
@@ -0,0 +1 @@
+module.exports = { cjs: 1 };
@@ -0,0 +1,3 @@
+import "./esm.js";
+import "./cjs.js";
+import("./lazy.js");
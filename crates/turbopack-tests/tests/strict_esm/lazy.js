@@ -0,0 +1,2 @@
+const { esm } = require("./esm.js");
+console.log(esm);
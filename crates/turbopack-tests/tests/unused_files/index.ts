@@ -0,0 +1,4 @@
+import type { Options } from "./types";
+import { used } from "./used";
+
+export const options: Options = { used };
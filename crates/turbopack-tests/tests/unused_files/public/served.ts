@@ -0,0 +1 @@
+export const served = "served";
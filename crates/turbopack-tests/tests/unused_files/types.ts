@@ -0,0 +1 @@
+export type Options = { used: string };
@@ -1,7 +1,36 @@
-use std::path::PathBuf;
+// Not every test binary uses every helper.
+#![allow(dead_code)]
 
+use std::{collections::HashMap, future::Future, path::PathBuf};
+
+use anyhow::{Context, Result};
 use dunce::canonicalize;
 use once_cell::sync::Lazy;
+use turbo_tasks::{CollectiblesSource, TurboTasks, Value, Vc};
+use turbo_tasks_fs::{DiskFileSystem, FileContent, FileSystem, FileSystemPath};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack::{
+    module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+    ModuleAssetContext,
+};
+use turbopack_build::{BuildChunkingContext, BuildChunkingContextBuilder, MinifyType};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::{
+        availability_info::AvailabilityInfo, plan::ChunkPlan, ChunkingContext, EvaluatableAsset,
+        EvaluatableAssetExt, EvaluatableAssets,
+    },
+    compile_time_info::CompileTimeInfo,
+    context::AssetContext,
+    environment::{BrowserEnvironment, Environment, ExecutionEnvironment, NodeJsEnvironment},
+    file_source::FileSource,
+    issue::{Issue, IssueDescriptionExt, StyledString},
+    module::Module,
+    output::{OutputAsset, OutputAssets},
+    reference::all_assets_from_entries,
+};
+use turbopack_dev::{DevChunkingContext, DevChunkingContextBuilder};
+use turbopack_ecmascript_runtime::RuntimeType;
 
 /// The turbo repo root. Should be used as the root when building with turbopack
 /// against fixtures in this crate.
@@ -13,3 +42,202 @@ pub static REPO_ROOT: Lazy<String> = Lazy::new(|| {
         .unwrap()
         .to_string()
 });
+
+/// Registers the crates the tests build with. Tests that define turbo tasks
+/// items of their own must register these as well.
+pub fn register() {
+    turbo_tasks::register();
+    turbo_tasks_env::register();
+    turbo_tasks_fs::register();
+    turbopack::register();
+    turbopack_build::register();
+    turbopack_dev::register();
+    turbopack_dev_server::register();
+    turbopack_env::register();
+    turbopack_ecmascript_plugins::register();
+    turbopack_ecmascript_runtime::register();
+}
+
+/// Runs `test` in a turbo tasks context.
+pub async fn run(test: impl Future<Output = Result<()>> + Send + 'static) -> Result<()> {
+    register();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let task = tt.spawn_once_task(async move {
+        test.await?;
+        Ok(Vc::<()>::default())
+    });
+    tt.wait_task_completion(task, true).await
+}
+
+/// The root of the project file system, which is the turbo repo root.
+pub fn project_root() -> Vc<FileSystemPath> {
+    DiskFileSystem::new("project".to_string(), REPO_ROOT.clone()).root()
+}
+
+/// The directory of the fixture `name`, next to the test files.
+pub fn fixture(name: &str) -> Vc<FileSystemPath> {
+    project_root().join(format!("crates/turbopack-tests/tests/{name}"))
+}
+
+/// A browser environment with a DOM for `browserslist_query`.
+pub fn browser_environment(browserslist_query: &str) -> Vc<Environment> {
+    Environment::new(Value::new(ExecutionEnvironment::Browser(
+        BrowserEnvironment {
+            dom: true,
+            web_worker: false,
+            service_worker: false,
+            browserslist_query: browserslist_query.to_owned(),
+        }
+        .into(),
+    )))
+}
+
+/// The browser environment the tests build for, unless they test the
+/// environment.
+pub fn browser_env() -> Vc<Environment> {
+    browser_environment("Chrome 102")
+}
+
+/// A Node.js environment, e.g. for server-side rendering.
+pub fn node_env() -> Vc<Environment> {
+    Environment::new(Value::new(ExecutionEnvironment::NodeJsLambda(
+        NodeJsEnvironment::default().into(),
+    )))
+}
+
+/// An asset context for `env` with the given options.
+pub fn asset_context_with(
+    env: Vc<Environment>,
+    module_options_context: ModuleOptionsContext,
+    resolve_options_context: ResolveOptionsContext,
+) -> Vc<Box<dyn AssetContext>> {
+    Vc::upcast(ModuleAssetContext::new(
+        Vc::cell(HashMap::new()),
+        CompileTimeInfo::new(env),
+        module_options_context.cell(),
+        resolve_options_context.cell(),
+        Vc::cell("test".to_string()),
+    ))
+}
+
+/// An asset context for `env` that transforms for `env` and resolves with the
+/// default options.
+pub fn asset_context(env: Vc<Environment>) -> Vc<Box<dyn AssetContext>> {
+    asset_context_with(
+        env,
+        ModuleOptionsContext {
+            preset_env_versions: Some(env),
+            ..Default::default()
+        },
+        ResolveOptionsContext::default(),
+    )
+}
+
+/// A development chunking context for `env` that writes to the `output`
+/// directory of `fixture` and doesn't include a runtime.
+pub fn dev_chunking_context(
+    fixture: Vc<FileSystemPath>,
+    env: Vc<Environment>,
+) -> DevChunkingContextBuilder {
+    let output_root = fixture.join("output".to_string());
+    DevChunkingContext::builder(
+        project_root(),
+        output_root,
+        output_root,
+        output_root.join("static".to_string()),
+        env,
+    )
+    .runtime_type(RuntimeType::Dummy)
+}
+
+/// A production chunking context for `env` that writes unminified code to the
+/// `output` directory of `fixture` and doesn't include a runtime.
+pub fn build_chunking_context(
+    fixture: Vc<FileSystemPath>,
+    env: Vc<Environment>,
+) -> BuildChunkingContextBuilder {
+    let output_root = fixture.join("output".to_string());
+    BuildChunkingContext::builder(
+        project_root(),
+        output_root,
+        output_root,
+        output_root,
+        output_root.join("static".to_string()),
+        env,
+    )
+    .minify_type(MinifyType::NoMinify)
+    .runtime_type(RuntimeType::Dummy)
+}
+
+/// The file `name` of `fixture` as an evaluatable entry.
+pub fn entry(
+    fixture: Vc<FileSystemPath>,
+    name: &str,
+    asset_context: Vc<Box<dyn AssetContext>>,
+) -> Vc<Box<dyn EvaluatableAsset>> {
+    FileSource::new(fixture.join(name.to_string())).to_evaluatable(asset_context)
+}
+
+/// The text of `content`, if it's a file.
+pub async fn text(content: Vc<AssetContent>) -> Result<Option<String>> {
+    Ok(match &*content.file_content().await? {
+        FileContent::Content(file) => Some(file.content().to_str()?.into_owned()),
+        FileContent::NotFound => None,
+    })
+}
+
+/// The path and the text of `assets` and of all assets they reference.
+pub async fn all_files(assets: Vc<OutputAssets>) -> Result<Vec<(String, Option<String>)>> {
+    let mut files = Vec::new();
+    for &asset in all_assets_from_entries(assets).await?.iter() {
+        files.push((
+            asset.ident().path().await?.path.clone(),
+            text(asset.content()).await?,
+        ));
+    }
+    Ok(files)
+}
+
+/// The path and the text of the assets of the evaluated chunk group of the
+/// file `name` of `fixture`, and of all assets they reference.
+pub async fn evaluated_files(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    fixture: Vc<FileSystemPath>,
+    name: &str,
+    asset_context: Vc<Box<dyn AssetContext>>,
+) -> Result<Vec<(String, Option<String>)>> {
+    let entry = entry(fixture, name, asset_context);
+    let assets = chunking_context
+        .evaluated_chunk_group(
+            entry.ident(),
+            EvaluatableAssets::one(entry),
+            Value::new(AvailabilityInfo::Root),
+        )
+        .await?
+        .assets;
+    all_files(assets).await
+}
+
+/// The titles of the issues emitted by `source` and the paths of the files
+/// they are about. Issues with styled titles are skipped.
+pub async fn issues(
+    source: impl CollectiblesSource + Copy + Send,
+) -> Result<Vec<(String, String)>> {
+    let mut issues = Vec::new();
+    for issue in source.peek_issues_with_path().await?.iter() {
+        let StyledString::Text(title) = &*issue.title().await? else {
+            continue;
+        };
+        issues.push((title.clone(), issue.file_path().await?.path.clone()));
+    }
+    Ok(issues)
+}
+
+/// Returns the planned chunk containing the module `name`.
+pub fn chunk_of<'a>(chunks: &'a [ChunkPlan], name: &str) -> Result<&'a ChunkPlan> {
+    chunks
+        .iter()
+        .find(|chunk| chunk.modules.iter().any(|module| module.contains(name)))
+        .with_context(|| format!("{name} isn't part of any chunk"))
+}
@@ -0,0 +1,4 @@
+import { vendor } from "vendor";
+
+console.log("APP_A_MARKER", vendor);
+import("./lazy.js");
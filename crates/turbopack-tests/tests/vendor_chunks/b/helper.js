@@ -0,0 +1,3 @@
+export function helper() {
+  return "HELPER_B_MARKER";
+}
@@ -0,0 +1,5 @@
+import { vendor } from "vendor";
+import { helper } from "./helper.js";
+
+console.log("APP_B_MARKER", vendor, helper());
+import("./lazy.js");
@@ -0,0 +1,3 @@
+import { vendor } from "vendor";
+
+console.log("LAZY_B_MARKER", vendor);
@@ -0,0 +1 @@
+export const shared = "virtual-shared";
@@ -0,0 +1,2 @@
+CSS.paintWorklet.addModule(new URL("./paint.js", import.meta.url));
+console.log("worklet-host");
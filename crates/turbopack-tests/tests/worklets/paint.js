@@ -0,0 +1,10 @@
+import { size } from "./size.js";
+
+registerPaint(
+  "worklet-checkerboard",
+  class {
+    paint(ctx) {
+      ctx.fillRect(0, 0, size, size);
+    }
+  }
+);
@@ -0,0 +1 @@
+export const size = 16;
@@ -6,6 +6,9 @@
 
 pub mod exit;
 mod flavor;
+pub mod module_timings;
+#[cfg(feature = "otel")]
+pub mod otel;
 pub mod raw_trace;
 pub mod trace_writer;
 pub mod tracing;
@@ -0,0 +1,254 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Write},
+    marker::PhantomData,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use tracing::{field::Visit, span, Subscriber};
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+/// The phase of building a module a span is attributed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModulePhase {
+    /// Parsing and applying transforms.
+    Transform,
+    /// Finding the references of the module.
+    Analysis,
+    /// Generating the code of the module.
+    CodeGeneration,
+}
+
+impl ModulePhase {
+    /// The phase of the spans with the given name, if they are attributed to a
+    /// module.
+    fn from_span_name(name: &str) -> Option<Self> {
+        match name {
+            "parse ecmascript" | "parse css" => Some(ModulePhase::Transform),
+            "analyse ecmascript module" => Some(ModulePhase::Analysis),
+            "code generation" => Some(ModulePhase::CodeGeneration),
+            _ => None,
+        }
+    }
+}
+
+/// The time spent on a single module since the last
+/// [`take_module_timings`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModuleTiming {
+    pub module: String,
+    pub transform: Duration,
+    pub analysis: Duration,
+    pub code_generation: Duration,
+}
+
+impl ModuleTiming {
+    pub fn total(&self) -> Duration {
+        self.transform + self.analysis + self.code_generation
+    }
+
+    fn add(&mut self, phase: ModulePhase, duration: Duration) {
+        match phase {
+            ModulePhase::Transform => self.transform += duration,
+            ModulePhase::Analysis => self.analysis += duration,
+            ModulePhase::CodeGeneration => self.code_generation += duration,
+        }
+    }
+}
+
+static MODULE_TIMINGS: Lazy<Mutex<HashMap<String, ModuleTiming>>> = Lazy::new(Default::default);
+
+/// Returns the `limit` modules that took the longest to build since the last
+/// call, slowest first, and resets the timings. Only the time spent while the
+/// work was executing is counted, so cached work doesn't show up. Calling this
+/// after each compilation gives the slowest modules of that rebuild.
+///
+/// Timings are only recorded when a [`ModuleTimingsLayer`] is installed.
+pub fn take_module_timings(limit: usize) -> Vec<ModuleTiming> {
+    let timings = std::mem::take(&mut *MODULE_TIMINGS.lock().unwrap());
+    let mut timings = timings.into_values().collect::<Vec<_>>();
+    timings.sort_by(|a, b| b.total().cmp(&a.total()));
+    timings.truncate(limit);
+    timings
+}
+
+/// Formats a report returned by [`take_module_timings`] as a table.
+pub fn format_module_timings(timings: &[ModuleTiming]) -> String {
+    let mut out = String::new();
+    for timing in timings {
+        let _ = writeln!(
+            out,
+            "{:>8.1?} (transform {:.1?}, analysis {:.1?}, code generation {:.1?}) {}",
+            timing.total(),
+            timing.transform,
+            timing.analysis,
+            timing.code_generation,
+            timing.module
+        );
+    }
+    out
+}
+
+/// Per span data stored in the span's extensions.
+struct SpanTiming {
+    module: String,
+    phase: ModulePhase,
+    busy: Duration,
+    entered: Option<Instant>,
+}
+
+/// A tracing layer that attributes the time spent in the spans of module
+/// transforms, analysis and code generation to their modules. The report is
+/// read with [`take_module_timings`].
+pub struct ModuleTimingsLayer<S: Subscriber + for<'a> LookupSpan<'a>> {
+    _phantom: PhantomData<fn(S)>,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> ModuleTimingsLayer<S> {
+    pub fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Default for ModuleTimingsLayer<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for ModuleTimingsLayer<S> {
+    fn on_new_span(
+        &self,
+        attrs: &span::Attributes<'_>,
+        id: &span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let Some(phase) = ModulePhase::from_span_name(attrs.metadata().name()) else {
+            return;
+        };
+        let mut visitor = ModuleVisitor { module: None };
+        attrs.values().record(&mut visitor);
+        let (Some(module), Some(span)) = (visitor.module, ctx.span(id)) else {
+            return;
+        };
+        span.extensions_mut().insert(SpanTiming {
+            module,
+            phase,
+            busy: Duration::ZERO,
+            entered: None,
+        });
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                timing.entered = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                if let Some(entered) = timing.entered.take() {
+                    timing.busy += entered.elapsed();
+                }
+            }
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let mut timings = MODULE_TIMINGS.lock().unwrap();
+        timings
+            .entry(timing.module.clone())
+            .or_insert_with(|| ModuleTiming {
+                module: timing.module,
+                ..Default::default()
+            })
+            .add(timing.phase, timing.busy);
+    }
+}
+
+/// Extracts the module from the `path` field of a span. The spans of all
+/// phases record the path of the module's file there, while their `module` or
+/// `name` fields differ, e.g. the parse spans name the source instead of the
+/// module.
+struct ModuleVisitor {
+    module: Option<String>,
+}
+
+impl Visit for ModuleVisitor {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        if field.name() == "path" {
+            self.module = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn Debug) {
+        if field.name() == "path" {
+            self.module = Some(format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// Keeps `span` busy for `millis` milliseconds.
+    fn busy(span: tracing::Span, millis: u32) {
+        let _guard = span.enter();
+        sleep(MS * millis);
+    }
+
+    #[test]
+    fn attributes_busy_time_to_modules() {
+        let subscriber = tracing_subscriber::registry().with(ModuleTimingsLayer::new());
+        tracing::subscriber::with_default(subscriber, || {
+            busy(
+                tracing::info_span!("parse ecmascript", path = "slow.js"),
+                20,
+            );
+            busy(tracing::info_span!("code generation", path = "slow.js"), 10);
+            busy(
+                tracing::info_span!("analyse ecmascript module", path = "fast.js"),
+                1,
+            );
+            busy(tracing::info_span!("resolve", path = "other.js"), 1);
+
+            // Time the span isn't entered isn't counted.
+            let idle = tracing::info_span!("parse css", path = "idle.css");
+            sleep(MS * 50);
+            busy(idle, 0);
+        });
+
+        let timings = take_module_timings(2);
+        let modules: Vec<_> = timings
+            .iter()
+            .map(|timing| timing.module.as_str())
+            .collect();
+        assert_eq!(modules, ["slow.js", "fast.js"]);
+        assert!(timings[0].transform >= MS * 20, "{timings:?}");
+        assert!(timings[0].code_generation >= MS * 10, "{timings:?}");
+        assert_eq!(timings[0].analysis, Duration::ZERO);
+        assert!(timings[1].analysis >= MS, "{timings:?}");
+
+        assert_eq!(take_module_timings(2), vec![], "the timings must be reset");
+    }
+}
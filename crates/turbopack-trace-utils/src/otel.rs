@@ -0,0 +1,366 @@
+//! Exports build metrics to an OpenTelemetry collector. The metrics are
+//! derived from the tracing spans and events turbopack already emits:
+//!
+//! * `turbopack.tasks.executed`: the number of executed turbo-tasks functions,
+//!   from `turbo_tasks::function` spans.
+//! * `turbopack.tasks.lookups`: the number of lookups of turbo-tasks function
+//!   calls, with a `hit` attribute telling whether the task already existed.
+//!   This gives the cache hit rate.
+//! * `turbopack.chunking.duration`: the duration of chunking an entry, from
+//!   `chunking` spans, with the entry as `entry` attribute.
+//! * `turbopack.request.duration`: the duration of dev server requests, from
+//!   `request` spans.
+//! * `turbopack.memory.usage`: the memory allocated by turbopack.
+
+use std::{
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use opentelemetry::{
+    metrics::{Counter, Histogram, MeterProvider, Unit},
+    runtime,
+    sdk::{
+        export::metrics::{aggregation::cumulative_temporality_selector, AggregatorSelector},
+        metrics::{controllers::BasicController, selectors},
+        Resource,
+    },
+    Context, KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+use tracing_subscriber::{
+    filter::{filter_fn, FilterFn, Filtered},
+    layer::Context,
+    registry::LookupSpan,
+    Layer,
+};
+
+/// The bucket boundaries of the duration histograms, in milliseconds.
+const DURATION_BOUNDARIES: [f64; 9] = [1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0, 5000.0, 10000.0];
+
+fn aggregator_selector() -> impl AggregatorSelector + Send + Sync + 'static {
+    selectors::simple::histogram(DURATION_BOUNDARIES)
+}
+
+/// Flushes and stops the metrics export when dropped.
+pub struct MetricsExporterGuard(BasicController);
+
+impl Drop for MetricsExporterGuard {
+    fn drop(&mut self) {
+        let _ = self.0.stop(&Context::current());
+    }
+}
+
+/// Creates a layer that exports metrics to the OTLP (gRPC) collector at
+/// `endpoint` every `interval`. Must be called within a tokio runtime.
+pub fn otlp_metrics_layer<S: Subscriber + for<'a> LookupSpan<'a>>(
+    endpoint: &str,
+    interval: Duration,
+) -> Result<(Filtered<MetricsLayer<S>, FilterFn, S>, MetricsExporterGuard)> {
+    let controller = opentelemetry_otlp::new_pipeline()
+        .metrics(
+            aggregator_selector(),
+            cumulative_temporality_selector(),
+            runtime::Tokio,
+        )
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_period(interval)
+        .with_resource(Resource::new([KeyValue::new("service.name", "turbopack")]))
+        .build()?;
+    let layer = MetricsLayer::new(&controller)?.with_filter(filter_fn(
+        MetricsLayer::<S>::is_relevant as fn(&Metadata<'_>) -> bool,
+    ));
+    Ok((layer, MetricsExporterGuard(controller)))
+}
+
+/// The spans metrics are derived from.
+#[derive(Clone, Copy)]
+enum MetricsSpan {
+    Task,
+    Chunking,
+    Request,
+}
+
+impl MetricsSpan {
+    fn of(metadata: &Metadata<'_>) -> Option<Self> {
+        match metadata.name() {
+            "turbo_tasks::function" => Some(MetricsSpan::Task),
+            "chunking" => Some(MetricsSpan::Chunking),
+            "request" => Some(MetricsSpan::Request),
+            _ => None,
+        }
+    }
+}
+
+/// Stored in the extensions of spans whose duration is recorded.
+struct SpanTiming {
+    start: Instant,
+    kind: MetricsSpan,
+    attributes: Vec<KeyValue>,
+}
+
+/// A tracing layer that derives metrics from spans and events.
+pub struct MetricsLayer<S: Subscriber + for<'a> LookupSpan<'a>> {
+    tasks_executed: Counter<u64>,
+    task_lookups: Counter<u64>,
+    chunking_duration: Histogram<f64>,
+    request_duration: Histogram<f64>,
+    _phantom: PhantomData<fn(S)>,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> MetricsLayer<S> {
+    pub fn new(provider: &impl MeterProvider) -> Result<Self> {
+        let meter = provider.meter("turbopack");
+        let memory_usage = meter
+            .u64_observable_gauge("turbopack.memory.usage")
+            .with_description("Memory allocated by turbopack")
+            .with_unit(Unit::new("By"))
+            .init();
+        meter.register_callback(move |cx| {
+            memory_usage.observe(
+                cx,
+                turbo_tasks_malloc::TurboMalloc::memory_usage() as u64,
+                &[],
+            )
+        })?;
+        Ok(Self {
+            tasks_executed: meter
+                .u64_counter("turbopack.tasks.executed")
+                .with_description("Number of executed turbo-tasks functions")
+                .init(),
+            task_lookups: meter
+                .u64_counter("turbopack.tasks.lookups")
+                .with_description("Number of lookups of turbo-tasks function calls")
+                .init(),
+            chunking_duration: meter
+                .f64_histogram("turbopack.chunking.duration")
+                .with_description("Duration of chunking an entry")
+                .with_unit(Unit::new("ms"))
+                .init(),
+            request_duration: meter
+                .f64_histogram("turbopack.request.duration")
+                .with_description("Duration of dev server requests")
+                .with_unit(Unit::new("ms"))
+                .init(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Whether spans or events with `metadata` are used for metrics. Use it
+    /// as a per-layer filter, so that other layers are not affected.
+    pub fn is_relevant(metadata: &Metadata<'_>) -> bool {
+        if metadata.is_event() {
+            metadata.fields().field("task_cache_hit").is_some()
+        } else {
+            MetricsSpan::of(metadata).is_some()
+        }
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for MetricsLayer<S> {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(kind) = MetricsSpan::of(attrs.metadata()) else {
+            return;
+        };
+        let mut attributes = Vec::new();
+        match kind {
+            MetricsSpan::Task => {
+                self.tasks_executed.add(&Context::current(), 1, &[]);
+                return;
+            }
+            MetricsSpan::Chunking => attrs.record(&mut ChunkingVisitor(&mut attributes)),
+            MetricsSpan::Request => {}
+        }
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanTiming {
+                start: Instant::now(),
+                kind,
+                attributes,
+            });
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let cx = Context::current();
+        let duration = timing.start.elapsed().as_secs_f64() * 1000.0;
+        match timing.kind {
+            MetricsSpan::Chunking => {
+                self.chunking_duration
+                    .record(&cx, duration, &timing.attributes)
+            }
+            MetricsSpan::Request => self
+                .request_duration
+                .record(&cx, duration, &timing.attributes),
+            MetricsSpan::Task => {}
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = TaskCacheVisitor(None);
+        event.record(&mut visitor);
+        if let Some(hit) = visitor.0 {
+            self.task_lookups
+                .add(&Context::current(), 1, &[KeyValue::new("hit", hit)]);
+        }
+    }
+}
+
+/// Collects the entry and the chunking type of `chunking` spans.
+struct ChunkingVisitor<'a>(&'a mut Vec<KeyValue>);
+
+impl Visit for ChunkingVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "module" | "ident" => self.0.push(KeyValue::new("entry", value.to_string())),
+            "chunking_type" => self
+                .0
+                .push(KeyValue::new("chunking_type", value.to_string())),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record_str(field, &format!("{:?}", value))
+    }
+}
+
+struct TaskCacheVisitor(Option<bool>);
+
+impl Visit for TaskCacheVisitor {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if field.name() == "task_cache_hit" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use opentelemetry::sdk::{
+        export::metrics::{
+            aggregation::{Count, Sum},
+            InstrumentationLibraryReader,
+        },
+        metrics::{
+            aggregators::{HistogramAggregator, SumAggregator},
+            controllers, processors,
+        },
+    };
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    fn controller() -> BasicController {
+        controllers::basic(processors::factory(
+            aggregator_selector(),
+            cumulative_temporality_selector(),
+        ))
+        .build()
+    }
+
+    /// Collects the counters and histograms of `controller`, keyed by
+    /// `name{attributes}`. Counters map to their sum and histograms to the
+    /// number of recorded values.
+    fn collect(controller: &BasicController) -> BTreeMap<String, u64> {
+        controller.collect(&Context::current()).unwrap();
+        let mut metrics = BTreeMap::new();
+        controller
+            .try_for_each(&mut |_, reader| {
+                reader.try_for_each(&cumulative_temporality_selector(), &mut |record| {
+                    let descriptor = record.descriptor();
+                    let attributes = record
+                        .attributes()
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key.as_str(), value.as_str()))
+                        .collect::<Vec<_>>()
+                        .join(",");
+                    let Some(aggregator) = record.aggregator() else {
+                        return Ok(());
+                    };
+                    let aggregator = aggregator.as_any();
+                    let value = if let Some(sum) = aggregator.downcast_ref::<SumAggregator>() {
+                        sum.sum()?.to_u64(descriptor.number_kind())
+                    } else if let Some(histogram) = aggregator.downcast_ref::<HistogramAggregator>()
+                    {
+                        histogram.count()?
+                    } else {
+                        return Ok(());
+                    };
+                    metrics.insert(format!("{}{{{attributes}}}", descriptor.name()), value);
+                    Ok(())
+                })
+            })
+            .unwrap();
+        metrics
+    }
+
+    #[test]
+    fn metrics_from_spans_and_events() {
+        let controller = controller();
+        let subscriber = tracing_subscriber::registry().with(
+            MetricsLayer::new(&controller)
+                .unwrap()
+                .with_filter(filter_fn(
+                    MetricsLayer::<tracing_subscriber::Registry>::is_relevant
+                        as fn(&Metadata<'_>) -> bool,
+                )),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            for _ in 0..3 {
+                tracing::info_span!("turbo_tasks::function").in_scope(|| {});
+            }
+            tracing::trace!(task_cache_hit = true);
+            tracing::trace!(task_cache_hit = true);
+            tracing::trace!(task_cache_hit = false);
+            tracing::info_span!("chunking", module = "[project]/index.js").in_scope(|| {});
+            tracing::info_span!("request").in_scope(|| {});
+            tracing::info_span!("unrelated").in_scope(|| {});
+        });
+
+        let metrics = collect(&controller);
+        assert_eq!(metrics["turbopack.tasks.executed{}"], 3);
+        assert_eq!(metrics["turbopack.tasks.lookups{hit=true}"], 2);
+        assert_eq!(metrics["turbopack.tasks.lookups{hit=false}"], 1);
+        assert_eq!(
+            metrics["turbopack.chunking.duration{entry=[project]/index.js}"],
+            1
+        );
+        assert_eq!(metrics["turbopack.request.duration{}"], 1);
+        assert_eq!(metrics.len(), 5);
+    }
+
+    #[test]
+    fn ignores_unrelated_spans_and_events() {
+        let controller = controller();
+        let layer = MetricsLayer::<tracing_subscriber::Registry>::new(&controller).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer.with_filter(filter_fn(
+            MetricsLayer::<tracing_subscriber::Registry>::is_relevant as fn(&Metadata<'_>) -> bool,
+        )));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("unrelated").in_scope(|| {
+                tracing::info!(other = true);
+            });
+        });
+
+        assert!(collect(&controller).is_empty());
+    }
+}
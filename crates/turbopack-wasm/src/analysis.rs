@@ -4,7 +4,7 @@ use anyhow::Result;
 use turbo_tasks::Vc;
 use turbo_tasks_fs::FileContent;
 use turbopack_core::asset::Asset;
-use wasmparser::{Chunk, Parser, Payload};
+use wasmparser::{Chunk, Encoding, Parser, Payload};
 
 use crate::source::WebAssemblySource;
 
@@ -14,6 +14,17 @@ use crate::source::WebAssemblySource;
 pub(crate) struct WebAssemblyAnalysis {
     pub imports: BTreeMap<String, Vec<String>>,
     pub exports: Vec<String>,
+    /// The binary is a WebAssembly component rather than a core module.
+    pub is_component: bool,
+    /// The binary was generated by `wasm-bindgen` and imports its bindings
+    /// from JS glue code.
+    pub is_wasm_bindgen: bool,
+}
+
+/// Returns `true` if the import module looks like JS glue code generated by
+/// `wasm-bindgen` (e.g. `./my_crate_bg.js`).
+fn is_wasm_bindgen_glue(module: &str) -> bool {
+    module.ends_with("_bg.js")
 }
 
 /// Analyse a WebAssembly file.
@@ -23,13 +34,16 @@ pub(crate) struct WebAssemblyAnalysis {
 pub(crate) async fn analyze(source: Vc<WebAssemblySource>) -> Result<Vc<WebAssemblyAnalysis>> {
     let content = source.content().file_content().await?;
 
-    let mut analysis = WebAssemblyAnalysis::default();
-
     let FileContent::Content(file) = &*content else {
-        return Ok(analysis.cell());
+        return Ok(WebAssemblyAnalysis::default().cell());
     };
 
-    let mut bytes = &*file.content().to_bytes()?;
+    Ok(analyze_bytes(&file.content().to_bytes()?)?.cell())
+}
+
+/// Extracts the imports and exports of the WebAssembly binary `bytes`.
+fn analyze_bytes(mut bytes: &[u8]) -> Result<WebAssemblyAnalysis> {
+    let mut analysis = WebAssemblyAnalysis::default();
 
     let mut parser = Parser::new(0);
     loop {
@@ -43,10 +57,22 @@ pub(crate) async fn analyze(source: Vc<WebAssemblySource>) -> Result<Vc<WebAssem
         };
 
         match payload {
+            Payload::Version { encoding, .. } => {
+                if encoding == Encoding::Component {
+                    // Components have a different binary layout, the sections
+                    // of core modules can't be analyzed.
+                    analysis.is_component = true;
+                    break;
+                }
+            }
             Payload::ImportSection(s) => {
                 for import in s {
                     let import = import?;
 
+                    if is_wasm_bindgen_glue(import.module) {
+                        analysis.is_wasm_bindgen = true;
+                    }
+
                     analysis
                         .imports
                         .entry(import.module.to_string())
@@ -73,5 +99,56 @@ pub(crate) async fn analyze(source: Vc<WebAssemblySource>) -> Result<Vc<WebAssem
         }
     }
 
-    Ok(analysis.cell())
+    Ok(analysis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::analyze_bytes;
+
+    #[test]
+    fn analyzes_core_modules() {
+        let bytes = wat::parse_str(
+            r#"
+                (module
+                  (import "./math.js" "double" (func (param i32) (result i32)))
+                  (func (export "answer") (result i32)
+                    i32.const 21
+                    call 0))
+            "#,
+        )
+        .unwrap();
+
+        let analysis = analyze_bytes(&bytes).unwrap();
+        assert_eq!(
+            analysis.imports.into_iter().collect::<Vec<_>>(),
+            vec![("./math.js".to_string(), vec!["double".to_string()])]
+        );
+        assert_eq!(analysis.exports, vec!["answer".to_string()]);
+        assert!(!analysis.is_component);
+        assert!(!analysis.is_wasm_bindgen);
+    }
+
+    #[test]
+    fn detects_wasm_bindgen_glue_imports() {
+        let bytes = wat::parse_str(
+            r#"
+                (module
+                  (import "./my_crate_bg.js" "__wbg_log" (func (param i32))))
+            "#,
+        )
+        .unwrap();
+
+        assert!(analyze_bytes(&bytes).unwrap().is_wasm_bindgen);
+    }
+
+    #[test]
+    fn detects_components() {
+        let bytes = wat::parse_str("(component)").unwrap();
+
+        let analysis = analyze_bytes(&bytes).unwrap();
+        assert!(analysis.is_component);
+        assert!(analysis.imports.is_empty());
+        assert!(analysis.exports.is_empty());
+    }
 }
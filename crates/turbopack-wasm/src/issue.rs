@@ -0,0 +1,50 @@
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::issue::{Issue, IssueSeverity, OptionStyledString, StyledString};
+
+/// Emitted when a WebAssembly component (as opposed to a core module) is
+/// imported. Components can't be instantiated by the `WebAssembly` JS API and
+/// need to be transpiled into a core module and JS bindings first.
+#[turbo_tasks::value(shared)]
+pub(crate) struct WebAssemblyComponentIssue {
+    pub path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for WebAssemblyComponentIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("module".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("WebAssembly components can't be imported directly".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Line(vec![
+                StyledString::Text(
+                    "This file is a WebAssembly component. Transpile it into a core module with \
+                     JS bindings (e.g. with "
+                        .to_string(),
+                ),
+                StyledString::Code("jco transpile".to_string()),
+                StyledString::Text(") and import the generated bindings instead.".to_string()),
+            ])
+            .cell(),
+        ))
+    }
+}
@@ -4,11 +4,15 @@
 //!
 //! When imported from ES modules, they produce a thin module that loads and
 //! instantiates the WebAssembly module.
+//!
+//! Modules generated by `wasm-bindgen` (`--target bundler`) are supported: the
+//! JS glue code and the WebAssembly module can import each other.
 
 #![feature(min_specialization)]
 #![feature(arbitrary_self_types)]
 
 pub(crate) mod analysis;
+pub(crate) mod issue;
 pub(crate) mod loader;
 pub mod module_asset;
 pub(crate) mod output_asset;
@@ -4,10 +4,12 @@ use anyhow::Result;
 use indoc::{formatdoc, writedoc};
 use turbo_tasks::Vc;
 use turbo_tasks_fs::File;
-use turbopack_core::{asset::AssetContent, source::Source, virtual_source::VirtualSource};
+use turbopack_core::{
+    asset::AssetContent, issue::IssueExt, source::Source, virtual_source::VirtualSource,
+};
 use turbopack_ecmascript::utils::StringifyJs;
 
-use crate::{analysis::analyze, source::WebAssemblySource};
+use crate::{analysis::analyze, issue::WebAssemblyComponentIssue, source::WebAssemblySource};
 
 /// Create a javascript loader to instantiate the WebAssembly module with the
 /// necessary imports and exports to be processed by [turbopack_ecmascript].
@@ -17,6 +19,24 @@ pub(crate) async fn instantiating_loader_source(
 ) -> Result<Vc<Box<dyn Source>>> {
     let analysis = analyze(source).await?;
 
+    if analysis.is_component {
+        WebAssemblyComponentIssue {
+            path: source.ident().path(),
+        }
+        .cell()
+        .emit();
+
+        let code = formatdoc! {
+            r#"
+                throw new Error("WebAssembly components can't be imported directly");
+            "#
+        };
+        return Ok(Vc::upcast(VirtualSource::new(
+            source.ident().path().append("_.loader.mjs".to_string()),
+            AssetContent::file(File::from(code).into()),
+        )));
+    }
+
     let mut code = String::new();
 
     let mut imports_obj = "{".to_string();
@@ -30,7 +50,20 @@ pub(crate) async fn instantiating_loader_source(
 
         writeln!(imports_obj, "\n    {}: {{", StringifyJs(path))?;
         for item in items {
-            writeln!(imports_obj, "        {}: {},", StringifyJs(item), item)?;
+            if analysis.is_wasm_bindgen {
+                // wasm-bindgen glue code and the WebAssembly module import each
+                // other. Getters defer reading the bindings until the module is
+                // instantiated, when the glue's function declarations are
+                // available.
+                writeln!(
+                    imports_obj,
+                    "        get {}() {{ return {}; }},",
+                    StringifyJs(item),
+                    item
+                )?;
+            } else {
+                writeln!(imports_obj, "        {}: {},", StringifyJs(item), item)?;
+            }
         }
         writeln!(imports_obj, "    }},")?;
     }
@@ -0,0 +1,602 @@
+//! Design tokens support.
+//!
+//! Design tokens are read from a JSON file, e.g.
+//!
+//! ```json
+//! { "color": { "primary": "#0070f3" }, "space": { "sm": "4px" } }
+//! ```
+//!
+//! or from a JavaScript or TypeScript module whose default export is an object
+//! literal of string and number literals, e.g.
+//!
+//! ```ts
+//! export default {
+//!   color: { primary: "#0070f3" },
+//!   space: { sm: "4px" },
+//! } as const;
+//! ```
+//!
+//! Nested groups are joined with `-`, and tokens in the [W3C format] (objects
+//! with a `$value`) are supported too. The tokens are available as CSS custom
+//! properties (`--color-primary`) declared on `:root` by importing
+//! `design-tokens.css`, and as constants (`colorPrimary`, and all tokens in the
+//! default export) by importing `design-tokens`, which also imports the CSS.
+//!
+//! Both modules are generated from the tokens file, so they are regenerated
+//! when it changes. Token values must be valid CSS values that don't end the
+//! declaration, e.g. they can't contain `;` or `}` outside of strings.
+//!
+//! [W3C format]: https://tr.designtokens.org/format/
+
+use std::{collections::HashSet, fmt::Write};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde_json::Value as JsonValue;
+use swc_core::ecma::ast::{
+    Decl, ExportDecl, Expr, Lit, ModuleDecl, ModuleItem, Pat, Program, Prop, PropName,
+    PropOrSpread, Stmt, UnaryOp, VarDeclKind,
+};
+use turbo_tasks::{Value, Vc};
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    file_source::FileSource,
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, OptionStyledString, StyledString},
+    resolve::{
+        options::{ImportMap, ImportMapping},
+        ResolveResult,
+    },
+    source::Source,
+};
+use turbopack_ecmascript::{
+    parse::{parse, ParseResult},
+    utils::StringifyJs,
+    EcmascriptInputTransforms, EcmascriptModuleAssetType,
+};
+
+/// The request of the JavaScript module exporting the design tokens.
+pub const DESIGN_TOKENS_REQUEST: &str = "design-tokens";
+/// The request of the CSS module declaring the design tokens as custom
+/// properties.
+pub const DESIGN_TOKENS_CSS_REQUEST: &str = "design-tokens.css";
+
+/// The flattened design tokens, mapping the name of a token to its value,
+/// which is either a string or a number.
+#[turbo_tasks::value(transparent)]
+pub struct DesignTokens(IndexMap<String, JsonValue>);
+
+/// Reads the design tokens from the JSON file or the JavaScript or TypeScript
+/// module at `path`. If the file is invalid, an [Issue] is emitted and no
+/// tokens are returned.
+#[turbo_tasks::function]
+pub async fn read_design_tokens(path: Vc<FileSystemPath>) -> Result<Vc<DesignTokens>> {
+    let mut tokens = IndexMap::new();
+    let module_type = match path.await?.extension_ref() {
+        Some("js" | "mjs") => Some(EcmascriptModuleAssetType::Ecmascript),
+        Some("ts" | "mts") => Some(EcmascriptModuleAssetType::Typescript),
+        _ => None,
+    };
+    let error_message = if let Some(module_type) = module_type {
+        match read_design_tokens_module(path, module_type).await? {
+            Ok(content) => flatten_tokens(&content, &mut String::new(), &mut tokens).err(),
+            Err(message) => Some(message),
+        }
+    } else {
+        match &*path.read_json().await? {
+            FileJsonContent::Content(content) => {
+                flatten_tokens(content, &mut String::new(), &mut tokens).err()
+            }
+            FileJsonContent::NotFound => Some("The design tokens file doesn't exist".to_string()),
+            FileJsonContent::Unparseable(e) => {
+                let mut message = "invalid JSON: ".to_string();
+                if let FileContent::Content(content) = &*path.read().await? {
+                    let text = content.content().to_str()?;
+                    e.write_with_content(&mut message, &text)?;
+                } else {
+                    write!(message, "{}", e)?;
+                }
+                Some(message)
+            }
+        }
+    };
+    if let Some(error_message) = error_message {
+        DesignTokensIssue {
+            path,
+            error_message,
+        }
+        .cell()
+        .emit();
+        return Ok(Vc::cell(IndexMap::new()));
+    }
+    Ok(Vc::cell(tokens))
+}
+
+/// Reads the default export of the module at `path` as JSON. Fails with an
+/// error message when it isn't an object literal of string and number
+/// literals.
+async fn read_design_tokens_module(
+    path: Vc<FileSystemPath>,
+    module_type: EcmascriptModuleAssetType,
+) -> Result<Result<JsonValue, String>> {
+    let parsed = parse(
+        Vc::upcast(FileSource::new(path)),
+        Value::new(module_type),
+        EcmascriptInputTransforms::empty(),
+    )
+    .await?;
+    let program = match &*parsed {
+        ParseResult::Ok { program, .. } => program,
+        ParseResult::Unparseable => {
+            return Ok(Err("The design tokens module can't be parsed".to_string()))
+        }
+        ParseResult::NotFound => {
+            return Ok(Err("The design tokens file doesn't exist".to_string()))
+        }
+    };
+    let Program::Module(module) = program else {
+        return Ok(Err(
+            "The design tokens module has no default export".to_string()
+        ));
+    };
+    // The default export can also reference a constant declared in the module.
+    let mut constants = IndexMap::new();
+    let mut default_export = None;
+    for item in &module.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export)) => {
+                default_export = Some(&*export.expr);
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var)))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var),
+                ..
+            })) if var.kind == VarDeclKind::Const => {
+                for decl in &var.decls {
+                    if let (Pat::Ident(name), Some(init)) = (&decl.name, &decl.init) {
+                        constants.insert(name.id.sym.to_string(), &**init);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let Some(mut default_export) = default_export else {
+        return Ok(Err(
+            "The design tokens module has no default export".to_string()
+        ));
+    };
+    if let Expr::Ident(ident) = default_export {
+        match constants.get(&*ident.sym) {
+            Some(init) => default_export = *init,
+            None => {
+                return Ok(Err(format!(
+                    "The default export {} of the design tokens module must be a constant \
+                     declared in the module",
+                    ident.sym
+                )))
+            }
+        }
+    }
+    Ok(literal_to_json(default_export))
+}
+
+/// Converts a literal expression, including type assertions like `as const`,
+/// to JSON.
+fn literal_to_json(expr: &Expr) -> Result<JsonValue, String> {
+    match expr {
+        Expr::Lit(Lit::Str(str)) => Ok(JsonValue::String(str.value.to_string())),
+        Expr::Lit(Lit::Num(num)) => number_to_json(num.value),
+        Expr::Unary(unary) if unary.op == UnaryOp::Minus => match &*unary.arg {
+            Expr::Lit(Lit::Num(num)) => number_to_json(-num.value),
+            _ => Err("Only numbers can be negated in design tokens".to_string()),
+        },
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => Ok(JsonValue::String(
+            tpl.quasis
+                .iter()
+                .filter_map(|quasi| quasi.cooked.as_deref())
+                .collect(),
+        )),
+        Expr::Paren(paren) => literal_to_json(&paren.expr),
+        Expr::TsAs(ts_as) => literal_to_json(&ts_as.expr),
+        Expr::TsConstAssertion(assertion) => literal_to_json(&assertion.expr),
+        Expr::TsSatisfies(satisfies) => literal_to_json(&satisfies.expr),
+        Expr::Object(object) => {
+            let mut map = serde_json::Map::new();
+            for prop in &object.props {
+                let PropOrSpread::Prop(prop) = prop else {
+                    return Err("Spreads aren't supported in design tokens".to_string());
+                };
+                let Prop::KeyValue(prop) = &**prop else {
+                    return Err("Design tokens must be declared as `key: value`".to_string());
+                };
+                let key = match &prop.key {
+                    PropName::Ident(ident) => ident.sym.to_string(),
+                    PropName::Str(str) => str.value.to_string(),
+                    PropName::Num(num) => num.value.to_string(),
+                    _ => return Err("Computed keys aren't supported in design tokens".to_string()),
+                };
+                map.insert(key, literal_to_json(&prop.value)?);
+            }
+            Ok(JsonValue::Object(map))
+        }
+        _ => Err(
+            "Design tokens must be string or number literals, or object literals of them"
+                .to_string(),
+        ),
+    }
+}
+
+/// Converts a number to JSON, writing integers without a fraction.
+fn number_to_json(value: f64) -> Result<JsonValue, String> {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        return Ok(JsonValue::from(value as i64));
+    }
+    serde_json::Number::from_f64(value)
+        .map(JsonValue::Number)
+        .ok_or_else(|| format!("The design token value {} is not a finite number", value))
+}
+
+/// Checks that the string `value` can be written as the value of a custom
+/// property without ending its declaration, so that a token can't inject CSS.
+fn check_css_value(value: &str) -> Result<(), &'static str> {
+    let mut closing = Vec::new();
+    let mut quote = None;
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (_, '\n' | '\r' | '\x0c') => return Err("it contains a line break"),
+            (_, '\\') => {
+                if matches!(chars.next(), None | Some('\n' | '\r' | '\x0c')) {
+                    return Err("it contains an invalid escape");
+                }
+            }
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => closing.push(')'),
+            (None, '[') => closing.push(']'),
+            (None, ')' | ']') => {
+                if closing.pop() != Some(c) {
+                    return Err("its brackets are unbalanced");
+                }
+            }
+            (None, ';' | '{' | '}') => return Err("it contains a `;`, `{` or `}`"),
+            (None, '/') if chars.peek() == Some(&'*') => return Err("it contains a comment"),
+            (None, _) => {}
+        }
+    }
+    if quote.is_some() {
+        return Err("it contains an unterminated string");
+    }
+    if !closing.is_empty() {
+        return Err("its brackets are unbalanced");
+    }
+    Ok(())
+}
+
+fn flatten_tokens(
+    value: &JsonValue,
+    name: &mut String,
+    tokens: &mut IndexMap<String, JsonValue>,
+) -> Result<(), String> {
+    match value {
+        JsonValue::String(_) | JsonValue::Number(_) => {
+            if name.is_empty() {
+                return Err("Expected an object of design tokens".to_string());
+            }
+            if let JsonValue::String(value) = value {
+                check_css_value(value).map_err(|reason| {
+                    format!(
+                        "The design token {} is not a valid CSS value, as {}",
+                        name, reason
+                    )
+                })?;
+            }
+            tokens.insert(name.clone(), value.clone());
+        }
+        JsonValue::Object(object) => {
+            if let Some(value) = object.get("$value") {
+                return flatten_tokens(value, name, tokens);
+            }
+            for (key, value) in object {
+                // `$type`, `$description` etc. are metadata of a group.
+                if key.starts_with('$') {
+                    continue;
+                }
+                let len = name.len();
+                if !name.is_empty() {
+                    name.push('-');
+                }
+                name.extend(key.chars().map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                        c
+                    } else {
+                        '-'
+                    }
+                }));
+                flatten_tokens(value, name, tokens)?;
+                name.truncate(len);
+            }
+        }
+        _ => {
+            return Err(format!(
+                "The design token {} must be a string or a number, but is {}",
+                name, value
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Converts the name of a token to the name of the constant exporting it, e.g.
+/// `color-primary` to `colorPrimary`. Returns `None` when the name is not a
+/// valid identifier.
+fn constant_name(name: &str) -> Option<String> {
+    let mut constant = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '-' {
+            upper = !constant.is_empty();
+        } else if upper {
+            constant.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            constant.push(c);
+        }
+    }
+    const RESERVED: &[&str] = &[
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ];
+    let first = constant.chars().next()?;
+    (!first.is_ascii_digit() && !RESERVED.contains(&constant.as_str())).then_some(constant)
+}
+
+/// Inserts the aliases for [DESIGN_TOKENS_REQUEST] and
+/// [DESIGN_TOKENS_CSS_REQUEST] into `import_map`, which resolve to the
+/// modules generated from the design tokens at `path`.
+pub fn insert_design_tokens_aliases(import_map: &mut ImportMap, path: Vc<FileSystemPath>) {
+    import_map.insert_exact_alias(
+        DESIGN_TOKENS_REQUEST,
+        ImportMapping::Direct(
+            ResolveResult::source(Vc::upcast(DesignTokensJsSource::new(path))).cell(),
+        )
+        .cell(),
+    );
+    import_map.insert_exact_alias(
+        DESIGN_TOKENS_CSS_REQUEST,
+        ImportMapping::Direct(
+            ResolveResult::source(Vc::upcast(DesignTokensCssSource::new(path))).cell(),
+        )
+        .cell(),
+    );
+}
+
+/// The CSS module declaring the design tokens as custom properties on `:root`.
+#[turbo_tasks::value]
+pub struct DesignTokensCssSource {
+    path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl DesignTokensCssSource {
+    #[turbo_tasks::function]
+    pub fn new(path: Vc<FileSystemPath>) -> Vc<Self> {
+        DesignTokensCssSource { path }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for DesignTokensCssSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path.append(".css".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for DesignTokensCssSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let tokens = read_design_tokens(self.path).await?;
+
+        let mut code = ":root {\n".to_string();
+        for (name, value) in tokens.iter() {
+            match value {
+                JsonValue::String(value) => writeln!(code, "  --{}: {};", name, value)?,
+                // Numbers are written as in JSON.
+                value => writeln!(code, "  --{}: {};", name, value)?,
+            }
+        }
+        code += "}\n";
+
+        Ok(AssetContent::file(File::from(code).into()))
+    }
+}
+
+/// The JavaScript module exporting the design tokens as constants.
+#[turbo_tasks::value]
+pub struct DesignTokensJsSource {
+    path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl DesignTokensJsSource {
+    #[turbo_tasks::function]
+    pub fn new(path: Vc<FileSystemPath>) -> Vc<Self> {
+        DesignTokensJsSource { path }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for DesignTokensJsSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path.append(".js".to_string()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for DesignTokensJsSource {
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<Vc<AssetContent>> {
+        let tokens = read_design_tokens(self.path).await?;
+
+        let mut code = String::new();
+        writeln!(code, "import {};\n", StringifyJs(DESIGN_TOKENS_CSS_REQUEST))?;
+        code += "const tokens = {\n";
+        for (name, value) in tokens.iter() {
+            writeln!(code, "  {}: {},", StringifyJs(name), StringifyJs(value))?;
+        }
+        code += "};\nexport default tokens;\n";
+        let mut constants = HashSet::new();
+        for name in tokens.keys() {
+            // Tokens that only differ in casing or separators (`color-primary`
+            // and `colorPrimary`) are only exported by the first one.
+            if let Some(constant) = constant_name(name).filter(|c| constants.insert(c.clone())) {
+                writeln!(
+                    code,
+                    "export const {} = tokens[{}];",
+                    constant,
+                    StringifyJs(name)
+                )?;
+            }
+        }
+
+        Ok(AssetContent::file(File::from(code).into()))
+    }
+}
+
+#[turbo_tasks::value(shared)]
+pub struct DesignTokensIssue {
+    pub path: Vc<FileSystemPath>,
+    pub error_message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DesignTokensIssue {
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Error reading design tokens".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("parse".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.error_message.clone()).cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn flatten() {
+        let mut tokens = IndexMap::new();
+        flatten_tokens(
+            &json!({
+                "color": { "primary": "#0070f3", "$type": "color" },
+                "space": { "sm": { "$value": 4 } },
+                "font": { "body": "\"Inter\", sans-serif" },
+            }),
+            &mut String::new(),
+            &mut tokens,
+        )
+        .unwrap();
+        assert_eq!(
+            tokens.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("color-primary".to_string(), json!("#0070f3")),
+                ("space-sm".to_string(), json!(4)),
+                ("font-body".to_string(), json!("\"Inter\", sans-serif")),
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_css_injection() {
+        for value in [
+            "red; } body { display: none",
+            "red }",
+            "url(\"a.png\"",
+            "\"unterminated",
+            "red /* comment",
+            "red\n",
+            "red\\",
+        ] {
+            let mut tokens = IndexMap::new();
+            assert!(
+                flatten_tokens(&json!({ "color": value }), &mut String::new(), &mut tokens)
+                    .is_err(),
+                "{value:?} should be rejected"
+            );
+        }
+        assert!(check_css_value("url(\"a;b.png\") calc(1px + [2])").is_ok());
+        assert!(check_css_value("\"}\"").is_ok());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(number_to_json(4.0), Ok(json!(4)));
+        assert_eq!(number_to_json(-0.5), Ok(json!(-0.5)));
+        assert!(number_to_json(f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,508 @@
+//! Module federation support for apps consuming the modules exposed by remote
+//! apps, see [turbopack_ecmascript::federation].
+//!
+//! A request of a remote module, e.g. `shop/Button`, starts with the name of
+//! the remote. It's aliased to a [RemoteSource], which is turned into a
+//! [RemoteModule] by the [federation_remote_rule]. The module is resolved at
+//! runtime, by loading the remote entry and importing `Button` from the
+//! container it registers.
+//!
+//! Requests of shared modules, e.g. `react`, are aliased to [SharedSource]s,
+//! which are turned into [SharedModule]s by the [federation_shared_rule].
+//!
+//! Apps are configured with a [FederationConfig], e.g. read from a
+//! `federation.json` file:
+//!
+//! ```json
+//! {
+//!   "name": "shop",
+//!   "exposes": { "Button": "./src/Button" },
+//!   "remotes": { "checkout": "http://localhost:3001/remote-entry.js" },
+//!   "shared": ["react", "react-dom"]
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, OptionStyledString, StyledString},
+    module::Module,
+    resolve::{
+        options::{ImportMap, ImportMapResult, ImportMapping, ImportMappingReplacement},
+        origin::PlainResolveOrigin,
+        parse::Request,
+        ModulePart, ResolveResult,
+    },
+    source::Source,
+};
+use turbopack_ecmascript::federation::{
+    is_identifier, FederationRemote, RemoteModule, SharedModule,
+};
+
+use crate::{
+    module_options::{
+        CustomModuleType, ModuleRule, ModuleRuleCondition, ModuleRuleEffect, ModuleType,
+    },
+    ModuleAssetContext,
+};
+
+/// The directory the paths of [RemoteSource]s are placed in.
+const REMOTE_DIR: &str = "[remote]";
+/// The directory the paths of [SharedSource]s are placed in.
+const SHARED_DIR: &str = "[shared]";
+
+/// The module federation configuration of an app.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FederationConfig {
+    /// The name of the app, which is required to expose modules. It's the
+    /// name hosts request the exposed modules with.
+    pub name: Option<String>,
+    /// The requests of the modules the app exposes, by the names they are
+    /// exposed as.
+    pub exposes: IndexMap<String, String>,
+    /// The URLs of the remote entries of the remotes the app consumes, by
+    /// their names.
+    pub remotes: IndexMap<String, String>,
+    /// The requests of the modules that are shared with the other apps of
+    /// the page, e.g. `react`.
+    pub shared: Vec<String>,
+}
+
+impl FederationConfig {
+    /// Parses and validates the configuration.
+    pub fn parse(json: &serde_json::Value) -> Result<Self, String> {
+        let config: FederationConfig =
+            serde_json::from_value(json.clone()).map_err(|err| err.to_string())?;
+        match &config.name {
+            Some(name) if !is_identifier(name) => {
+                return Err(format!(
+                    "The name {name} must be a valid JavaScript identifier"
+                ));
+            }
+            None if !config.exposes.is_empty() => {
+                return Err("A name is required to expose modules".to_string());
+            }
+            _ => {}
+        }
+        if let Some(remote) = config
+            .remotes
+            .keys()
+            .find(|remote| remote.is_empty() || remote.contains('/'))
+        {
+            return Err(format!(
+                "The remote name \"{remote}\" must be non-empty and must not contain a /"
+            ));
+        }
+        Ok(config)
+    }
+
+    /// The remotes the app consumes.
+    pub fn remotes(&self) -> Vec<FederationRemote> {
+        self.remotes
+            .iter()
+            .map(|(name, remote_entry)| FederationRemote {
+                name: name.clone(),
+                remote_entry: remote_entry.clone(),
+            })
+            .collect()
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionFederationConfig(Option<FederationConfig>);
+
+/// Reads the module federation configuration from the JSON file at `path`.
+/// Returns `None` if the file doesn't exist. If it's invalid, an [Issue] is
+/// emitted and `None` is returned.
+#[turbo_tasks::function]
+pub async fn read_federation_config(
+    path: Vc<FileSystemPath>,
+) -> Result<Vc<OptionFederationConfig>> {
+    let error_message = match &*path.read_json().await? {
+        FileJsonContent::Content(content) => match FederationConfig::parse(content) {
+            Ok(config) => return Ok(Vc::cell(Some(config))),
+            Err(message) => message,
+        },
+        FileJsonContent::NotFound => return Ok(Vc::cell(None)),
+        FileJsonContent::Unparseable(e) => {
+            let mut message = "invalid JSON: ".to_string();
+            if let FileContent::Content(content) = &*path.read().await? {
+                let text = content.content().to_str()?;
+                e.write_with_content(&mut message, &text)?;
+            } else {
+                write!(message, "{}", e)?;
+            }
+            message
+        }
+    };
+    FederationConfigIssue {
+        path,
+        error_message,
+    }
+    .cell()
+    .emit();
+    Ok(Vc::cell(None))
+}
+
+#[turbo_tasks::value(shared)]
+pub struct FederationConfigIssue {
+    pub path: Vc<FileSystemPath>,
+    pub error_message: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for FederationConfigIssue {
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Error reading the module federation configuration".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("parse".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.error_message.clone()).cell()))
+    }
+}
+
+/// Inserts an alias for every remote in `remotes` into `import_map`, which
+/// resolves the requests of its modules (e.g. `shop/Button`) to
+/// [RemoteSource]s placed in `project_path`.
+pub fn insert_federation_remote_aliases(
+    import_map: &mut ImportMap,
+    project_path: Vc<FileSystemPath>,
+    remotes: &[FederationRemote],
+) {
+    for remote in remotes {
+        import_map.insert_wildcard_alias(
+            format!("{}/", remote.name),
+            ImportMapping::Dynamic(Vc::upcast(RemoteImportMapping::new(
+                project_path,
+                remote.clone().cell(),
+            )))
+            .cell(),
+        );
+    }
+}
+
+/// The rule turning [RemoteSource]s into [RemoteModule]s, which must be part
+/// of the `custom_rules` of the module options of apps consuming remotes.
+pub fn federation_remote_rule() -> ModuleRule {
+    ModuleRule::new(
+        ModuleRuleCondition::ResourcePathInDirectory(REMOTE_DIR.to_string()),
+        vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+            Vc::upcast(RemoteModuleType::new()),
+        ))],
+    )
+}
+
+/// Inserts an alias for every request in `shared` into `import_map`, which
+/// resolves it to a [SharedSource].
+pub fn insert_federation_shared_aliases(import_map: &mut ImportMap, shared: &[String]) {
+    for name in shared {
+        import_map.insert_exact_alias(
+            name,
+            ImportMapping::Dynamic(Vc::upcast(SharedImportMapping::new(name.clone()))).cell(),
+        );
+    }
+}
+
+/// The rule turning [SharedSource]s into [SharedModule]s, which must be part
+/// of the `custom_rules` of the module options of apps sharing modules.
+pub fn federation_shared_rule() -> ModuleRule {
+    ModuleRule::new(
+        ModuleRuleCondition::ResourcePathInDirectory(SHARED_DIR.to_string()),
+        vec![ModuleRuleEffect::ModuleType(ModuleType::Custom(
+            Vc::upcast(SharedModuleType::new()),
+        ))],
+    )
+}
+
+/// Maps the requests of the modules of a remote to [RemoteSource]s.
+#[turbo_tasks::value]
+struct RemoteImportMapping {
+    project_path: Vc<FileSystemPath>,
+    remote: Vc<FederationRemote>,
+}
+
+#[turbo_tasks::value_impl]
+impl RemoteImportMapping {
+    #[turbo_tasks::function]
+    fn new(project_path: Vc<FileSystemPath>, remote: Vc<FederationRemote>) -> Vc<Self> {
+        RemoteImportMapping {
+            project_path,
+            remote,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for RemoteImportMapping {
+    #[turbo_tasks::function]
+    fn replace(&self, capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Direct(
+            ResolveResult::source(Vc::upcast(RemoteSource::new(
+                self.project_path,
+                self.remote,
+                capture,
+            )))
+            .cell(),
+        )
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn result(
+        &self,
+        _lookup_path: Vc<FileSystemPath>,
+        _request: Vc<Request>,
+    ) -> Vc<ImportMapResult> {
+        // The mapping is only used as a wildcard alias, which is replaced with
+        // the exposed module first.
+        ImportMapResult::NoEntry.cell()
+    }
+}
+
+/// A module exposed by a remote. It has no content of its own, as the module
+/// is loaded from the remote at runtime.
+#[turbo_tasks::value]
+pub struct RemoteSource {
+    project_path: Vc<FileSystemPath>,
+    remote: Vc<FederationRemote>,
+    expose: String,
+}
+
+#[turbo_tasks::value_impl]
+impl RemoteSource {
+    #[turbo_tasks::function]
+    pub fn new(
+        project_path: Vc<FileSystemPath>,
+        remote: Vc<FederationRemote>,
+        expose: String,
+    ) -> Vc<Self> {
+        RemoteSource {
+            project_path,
+            remote,
+            expose,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for RemoteSource {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        Ok(AssetIdent::from_path(self.project_path.join(format!(
+            "{}/{}/{}",
+            REMOTE_DIR,
+            self.remote.await?.name,
+            self.expose
+        ))))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for RemoteSource {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(File::from("// Loaded from the remote at runtime".to_string()).into())
+    }
+}
+
+/// Creates [RemoteModule]s from [RemoteSource]s.
+#[turbo_tasks::value]
+struct RemoteModuleType {}
+
+#[turbo_tasks::value_impl]
+impl RemoteModuleType {
+    #[turbo_tasks::function]
+    fn new() -> Vc<Self> {
+        RemoteModuleType {}.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for RemoteModuleType {
+    #[turbo_tasks::function]
+    async fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        _module_asset_context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Result<Vc<Box<dyn Module>>> {
+        let remote_source = Vc::try_resolve_downcast_type::<RemoteSource>(source)
+            .await?
+            .context("only sources of remote modules can be federation remotes")?
+            .await?;
+        Ok(Vc::upcast(RemoteModule::new(
+            source.ident(),
+            remote_source.remote,
+            remote_source.expose.clone(),
+        )))
+    }
+}
+
+/// Maps a shared request to a [SharedSource] placed in the directory it's
+/// requested from.
+#[turbo_tasks::value]
+struct SharedImportMapping {
+    name: String,
+}
+
+#[turbo_tasks::value_impl]
+impl SharedImportMapping {
+    #[turbo_tasks::function]
+    fn new(name: String) -> Vc<Self> {
+        SharedImportMapping { name }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for SharedImportMapping {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        lookup_path: Vc<FileSystemPath>,
+        _request: Vc<Request>,
+    ) -> Result<Vc<ImportMapResult>> {
+        // The [SharedModule] resolves the copy of the app from the directory of
+        // the [SharedSource], which must resolve the request as usual.
+        if lookup_path.await?.file_name() == SHARED_DIR {
+            return Ok(ImportMapResult::NoEntry.cell());
+        }
+        Ok(ImportMapResult::Result(
+            ResolveResult::source(Vc::upcast(SharedSource::new(
+                lookup_path,
+                self.name.clone(),
+            )))
+            .cell(),
+        )
+        .cell())
+    }
+}
+
+/// A shared module requested from `lookup_path`. Its path is placed in a
+/// child directory of `lookup_path`, so the copy of the app is resolved from
+/// the same `node_modules` directories as the request.
+#[turbo_tasks::value]
+pub struct SharedSource {
+    lookup_path: Vc<FileSystemPath>,
+    name: String,
+}
+
+#[turbo_tasks::value_impl]
+impl SharedSource {
+    #[turbo_tasks::function]
+    pub fn new(lookup_path: Vc<FileSystemPath>, name: String) -> Vc<Self> {
+        SharedSource { lookup_path, name }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Source for SharedSource {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(
+            self.lookup_path
+                .join(format!("{}/{}", SHARED_DIR, self.name)),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SharedSource {
+    #[turbo_tasks::function]
+    fn content(&self) -> Vc<AssetContent> {
+        AssetContent::file(
+            File::from("// Shared with the other apps at runtime".to_string()).into(),
+        )
+    }
+}
+
+/// Creates [SharedModule]s from [SharedSource]s.
+#[turbo_tasks::value]
+struct SharedModuleType {}
+
+#[turbo_tasks::value_impl]
+impl SharedModuleType {
+    #[turbo_tasks::function]
+    fn new() -> Vc<Self> {
+        SharedModuleType {}.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CustomModuleType for SharedModuleType {
+    #[turbo_tasks::function]
+    async fn create_module(
+        &self,
+        source: Vc<Box<dyn Source>>,
+        module_asset_context: Vc<ModuleAssetContext>,
+        _part: Option<Vc<ModulePart>>,
+    ) -> Result<Vc<Box<dyn Module>>> {
+        let shared_source = Vc::try_resolve_downcast_type::<SharedSource>(source)
+            .await?
+            .context("only sources of shared modules can be shared")?
+            .await?;
+        let origin =
+            PlainResolveOrigin::new(Vc::upcast(module_asset_context), source.ident().path());
+        Ok(Vc::upcast(SharedModule::new(
+            Vc::upcast(origin),
+            shared_source.name.clone(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config = FederationConfig::parse(&json!({
+            "name": "shop",
+            "exposes": { "Button": "./src/Button" },
+            "remotes": { "checkout": "http://localhost:3001/remote-entry.js" },
+            "shared": ["react"],
+        }))
+        .unwrap();
+        assert_eq!(config.name.as_deref(), Some("shop"));
+        assert_eq!(config.exposes["Button"], "./src/Button");
+        assert_eq!(config.remotes()[0].name, "checkout");
+        assert_eq!(config.shared, vec!["react".to_string()]);
+    }
+
+    #[test]
+    fn parse_invalid_config() {
+        assert!(FederationConfig::parse(&json!({ "name": "my-shop" })).is_err());
+        assert!(FederationConfig::parse(&json!({ "exposes": { "Button": "./Button" } })).is_err());
+        assert!(FederationConfig::parse(&json!({ "remotes": { "a/b": "x.js" } })).is_err());
+        assert!(FederationConfig::parse(&json!({ "share": ["react"] })).is_err());
+        assert!(FederationConfig::parse(&json!({ "shared": ["react"] })).is_ok());
+    }
+}
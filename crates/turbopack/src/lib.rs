@@ -8,7 +8,9 @@
 #![feature(arbitrary_self_types)]
 
 pub mod condition;
+pub mod design_tokens;
 pub mod evaluate_context;
+pub mod federation;
 mod graph;
 pub mod module_options;
 pub mod rebase;
@@ -35,17 +37,20 @@ use graph::{aggregate, AggregatedGraph, AggregatedGraphNodeContent};
 use module_options::{ModuleOptions, ModuleOptionsContext, ModuleRuleEffect, ModuleType};
 pub use resolve::resolve_options;
 use tracing::Instrument;
-use turbo_tasks::{Completion, Value, ValueToString, Vc};
+use turbo_tasks::{Completion, TryFlatJoinIterExt, TryJoinIterExt, Value, ValueToString, Vc};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     asset::Asset,
+    chunk::tags::{tagged_chunk_group_assets, TaggedChunkGroups},
     compile_time_info::CompileTimeInfo,
     context::{AssetContext, ProcessResult},
+    emit_policy::{check_emit_policy, OptionEmitPolicy},
     ident::AssetIdent,
     issue::{Issue, IssueExt, OptionStyledString, StyledString},
     module::Module,
-    output::OutputAsset,
+    output::{OutputAsset, OutputAssets},
     raw_module::RawModule,
+    reference::all_assets_from_entries,
     reference_type::{
         CssReferenceSubType, EcmaScriptModulesReferenceSubType, InnerAssets, ReferenceType,
     },
@@ -689,6 +694,83 @@ pub async fn emit_with_completion(
     emit_assets_aggregated(asset, output_dir)
 }
 
+/// Like [emit_with_completion], but the assets are checked by `policy` before
+/// they're emitted, see [emit_assets_with_policy].
+#[turbo_tasks::function]
+pub fn emit_with_policy(
+    asset: Vc<Box<dyn OutputAsset>>,
+    output_dir: Vc<FileSystemPath>,
+    policy: Vc<OptionEmitPolicy>,
+) -> Vc<Completion> {
+    emit_assets_with_policy(
+        all_assets_from_entries(Vc::cell(vec![asset])),
+        output_dir,
+        policy,
+    )
+}
+
+/// Emits those of `assets` that are inside of `output_dir`. All of them are
+/// checked by `policy` first, and nothing is emitted when it vetoes any of
+/// them.
+#[turbo_tasks::function]
+pub async fn emit_assets_with_policy(
+    assets: Vc<OutputAssets>,
+    output_dir: Vc<FileSystemPath>,
+    policy: Vc<OptionEmitPolicy>,
+) -> Result<Vc<Completion>> {
+    let dir = &*output_dir.await?;
+    let assets = assets
+        .await?
+        .iter()
+        .map(|&asset| async move {
+            Ok(asset
+                .ident()
+                .path()
+                .await?
+                .is_inside_ref(dir)
+                .then_some(asset))
+        })
+        .try_flat_join()
+        .await?;
+    if let Some(policy) = *policy.await? {
+        check_emit_policy(&assets, policy).await?;
+    }
+    assets
+        .iter()
+        .map(|&asset| emit_asset(asset))
+        .try_join()
+        .await?;
+    Ok(Completion::new())
+}
+
+/// Emits the output assets of the chunk groups of `chunk_groups` that are
+/// selected by `tags` and all assets they reference into `target_dir`, e.g.
+/// the output set of one of several deployments built from the same build
+/// graph. Only assets inside `output_root` are emitted, at the same path
+/// relative to `target_dir`, so the relative URLs the chunks are loaded with
+/// stay valid.
+#[turbo_tasks::function]
+pub async fn emit_tagged_chunk_groups(
+    chunk_groups: Vc<TaggedChunkGroups>,
+    tags: Vec<String>,
+    output_root: Vc<FileSystemPath>,
+    target_dir: Vc<FileSystemPath>,
+) -> Result<Vc<Completion>> {
+    let assets = all_assets_from_entries(tagged_chunk_group_assets(chunk_groups, tags)).await?;
+    let output_root_ref = &*output_root.await?;
+    for &asset in assets.iter() {
+        let path = asset.ident().path();
+        if !path.await?.is_inside_ref(output_root_ref) {
+            continue;
+        }
+        asset
+            .content()
+            .write(FileSystemPath::rebase(path, output_root, target_dir))
+            .await?;
+    }
+    Ok(Completion::new())
+}
+
 #[turbo_tasks::function]
 async fn emit_assets_aggregated(
     asset: Vc<Box<dyn OutputAsset>>,
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Vc};
+use turbopack_core::source_transform::SourceTransform;
+
+use super::{ModuleRule, ModuleRuleCondition, ModuleRuleEffect, ModuleType};
+
+/// A chain of transforms for the static assets matching a condition, e.g.
+/// images that are resized and re-encoded before they are emitted.
+///
+/// The transforms are applied in order, each to the output of the previous
+/// one, and the result is emitted as a static asset named by its content
+/// hash. Every transform is a [SourceTransform], so the pipeline is computed
+/// incrementally and only the steps affected by a change are executed again.
+/// When a transform changes the path of the asset (e.g. `.png` to `.webp`),
+/// the module rules are matched against the new path.
+#[derive(Debug, Clone, Serialize, Deserialize, TraceRawVcs, PartialEq, Eq)]
+pub struct AssetPipeline {
+    condition: ModuleRuleCondition,
+    transforms: Vec<Vc<Box<dyn SourceTransform>>>,
+}
+
+impl AssetPipeline {
+    /// Creates a pipeline without transforms for the assets matching
+    /// `condition`, e.g. a [ModuleRuleCondition::ResourcePathGlob].
+    pub fn new(condition: ModuleRuleCondition) -> Self {
+        AssetPipeline {
+            condition,
+            transforms: Vec::new(),
+        }
+    }
+
+    /// Appends `transform` to the chain.
+    pub fn transform(mut self, transform: Vc<Box<dyn SourceTransform>>) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+
+    /// The module rule that applies the pipeline.
+    pub(crate) fn into_rule(self) -> ModuleRule {
+        let mut effects = Vec::new();
+        if !self.transforms.is_empty() {
+            effects.push(ModuleRuleEffect::SourceTransforms(Vc::cell(
+                self.transforms,
+            )));
+        }
+        effects.push(ModuleRuleEffect::ModuleType(ModuleType::Static));
+        ModuleRule::new(self.condition, effects)
+    }
+}
@@ -1,9 +1,11 @@
+pub mod asset_pipeline;
 pub(crate) mod custom_module_type;
 pub mod module_options_context;
 pub mod module_rule;
 pub mod rule_condition;
 
 use anyhow::{Context, Result};
+pub use asset_pipeline::AssetPipeline;
 pub use custom_module_type::CustomModuleType;
 pub use module_options_context::*;
 pub use module_rule::*;
@@ -17,7 +19,12 @@ use turbopack_core::{
 use turbopack_css::CssModuleAssetType;
 use turbopack_ecmascript::{EcmascriptInputTransform, EcmascriptOptions, SpecifiedModuleType};
 use turbopack_mdx::MdxTransformOptions;
-use turbopack_node::transforms::{postcss::PostCssTransform, webpack::WebpackLoaders};
+use turbopack_node::transforms::{
+    codegen::CodegenTransform,
+    graphql::{GraphQlTransform, GraphQlTransformMode},
+    postcss::PostCssTransform,
+    webpack::WebpackLoaders,
+};
 use turbopack_wasm::source::WebAssemblySourceType;
 
 use crate::evaluate_context::node_evaluate_asset_context;
@@ -71,9 +78,13 @@ impl ModuleOptions {
             enable_raw_css,
             ref enable_postcss_transform,
             ref enable_webpack_loaders,
+            enable_codegen_modules,
+            no_bundle,
+            enable_graphql,
             preset_env_versions,
             ref custom_ecma_transform_plugins,
             ref custom_rules,
+            ref asset_pipelines,
             execution_context,
             ref rules,
             esm_url_rewrite_behavior,
@@ -328,21 +339,19 @@ impl ModuleOptions {
                     },
                 )],
             ),
-            ModuleRule::new(
-                ModuleRuleCondition::any(vec![
-                    ModuleRuleCondition::ResourcePathEndsWith(".apng".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".avif".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".gif".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".ico".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".jpg".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".jpeg".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".png".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".svg".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".webp".to_string()),
-                    ModuleRuleCondition::ResourcePathEndsWith(".woff2".to_string()),
-                ]),
-                vec![ModuleRuleEffect::ModuleType(ModuleType::Static)],
-            ),
+            AssetPipeline::new(ModuleRuleCondition::any(vec![
+                ModuleRuleCondition::ResourcePathEndsWith(".apng".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".avif".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".gif".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".ico".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".jpg".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".jpeg".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".png".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".svg".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".webp".to_string()),
+                ModuleRuleCondition::ResourcePathEndsWith(".woff2".to_string()),
+            ]))
+            .into_rule(),
             ModuleRule::new(
                 ModuleRuleCondition::any(vec![ModuleRuleCondition::ResourcePathEndsWith(
                     ".wasm".to_string(),
@@ -577,8 +586,141 @@ impl ModuleOptions {
             }
         }
 
+        if let Some(codegen_modules_options) = enable_codegen_modules {
+            let codegen_modules_options = codegen_modules_options.await?;
+            let execution_context =
+                execution_context.context("execution_context is required for codegen modules")?;
+            let transform = CodegenTransform::new(
+                node_evaluate_asset_context(execution_context, None, None, "codegen".to_string()),
+                execution_context,
+            );
+            for glob in codegen_modules_options.rules.iter() {
+                rules.push(ModuleRule::new(
+                    ModuleRuleCondition::All(vec![
+                        if !glob.contains('/') {
+                            ModuleRuleCondition::ResourceBasePathGlob(
+                                Glob::new(glob.clone()).await?,
+                            )
+                        } else {
+                            ModuleRuleCondition::ResourcePathGlob {
+                                base: execution_context.project_path().await?,
+                                glob: Glob::new(glob.clone()).await?,
+                            }
+                        },
+                        ModuleRuleCondition::not(ModuleRuleCondition::ResourceIsVirtualSource),
+                    ]),
+                    vec![
+                        // Codegen modules always produce ecmascript code.
+                        ModuleRuleEffect::ModuleType(ModuleType::Ecmascript {
+                            transforms: app_transforms,
+                            options: ecmascript_options,
+                        }),
+                        ModuleRuleEffect::SourceTransforms(Vc::cell(vec![Vc::upcast(transform)])),
+                    ],
+                ));
+            }
+        }
+
+        if let Some(graphql_options) = enable_graphql {
+            let graphql_options = graphql_options.await?;
+            let execution_context = execution_context
+                .context("execution_context is required for the graphql transform")?;
+            let import_map = if let Some(graphql_package) = graphql_options.graphql_package {
+                package_import_map_from_import_mapping("graphql".to_string(), graphql_package)
+            } else {
+                package_import_map_from_context("graphql".to_string(), path)
+            };
+            let evaluate_context = node_evaluate_asset_context(
+                execution_context,
+                Some(import_map),
+                None,
+                "graphql".to_string(),
+            );
+
+            rules.push(ModuleRule::new(
+                ModuleRuleCondition::any(vec![
+                    ModuleRuleCondition::ResourcePathEndsWith(".graphql".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".gql".to_string()),
+                ]),
+                vec![
+                    ModuleRuleEffect::ModuleType(ModuleType::Ecmascript {
+                        transforms: app_transforms,
+                        options: ecmascript_options,
+                    }),
+                    ModuleRuleEffect::SourceTransforms(Vc::cell(vec![Vc::upcast(
+                        GraphQlTransform::new(
+                            evaluate_context,
+                            execution_context,
+                            GraphQlTransformMode::Document,
+                        ),
+                    )])),
+                ],
+            ));
+
+            if graphql_options.inline_tags {
+                rules.push(ModuleRule::new(
+                    ModuleRuleCondition::all(vec![
+                        ModuleRuleCondition::any(vec![
+                            ModuleRuleCondition::ResourcePathEndsWith(".js".to_string()),
+                            ModuleRuleCondition::ResourcePathEndsWith(".jsx".to_string()),
+                            ModuleRuleCondition::ResourcePathEndsWith(".mjs".to_string()),
+                            ModuleRuleCondition::ResourcePathEndsWith(".ts".to_string()),
+                            ModuleRuleCondition::ResourcePathEndsWith(".tsx".to_string()),
+                        ]),
+                        ModuleRuleCondition::not(ModuleRuleCondition::ResourcePathInDirectory(
+                            "node_modules".to_string(),
+                        )),
+                    ]),
+                    vec![ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
+                        Vc::upcast(GraphQlTransform::new(
+                            evaluate_context,
+                            execution_context,
+                            GraphQlTransformMode::InlineTags,
+                        )),
+                    ]))],
+                ));
+            }
+        }
+
+        rules.extend(
+            asset_pipelines
+                .iter()
+                .cloned()
+                .map(AssetPipeline::into_rule),
+        );
+
         rules.extend(custom_rules.iter().cloned());
 
+        if let Some(no_bundle) = no_bundle {
+            let no_bundle = no_bundle.await?;
+            let base = no_bundle.base.await?;
+            let mut conditions = Vec::new();
+            for glob in no_bundle.rules.iter() {
+                conditions.push(if !glob.contains('/') {
+                    ModuleRuleCondition::ResourceBasePathGlob(Glob::new(glob.clone()).await?)
+                } else {
+                    ModuleRuleCondition::ResourcePathGlob {
+                        base: base.clone(),
+                        glob: Glob::new(glob.clone()).await?,
+                    }
+                });
+            }
+            let condition = ModuleRuleCondition::all(vec![
+                ModuleRuleCondition::any(conditions),
+                ModuleRuleCondition::not(ModuleRuleCondition::ResourceIsVirtualSource),
+            ]);
+            // No other rule may transform the modules, so that they're emitted
+            // byte-identical.
+            rules = rules
+                .into_iter()
+                .map(|rule| rule.unless(condition.clone()))
+                .collect();
+            rules.push(ModuleRule::new(
+                condition,
+                vec![ModuleRuleEffect::ModuleType(ModuleType::Static)],
+            ));
+        }
+
         Ok(ModuleOptions::cell(ModuleOptions { rules }))
     }
 }
@@ -1,14 +1,18 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{trace::TraceRawVcs, ValueDefault, Vc};
+use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{environment::Environment, resolve::options::ImportMapping};
 use turbopack_ecmascript::{references::esm::UrlRewriteBehavior, TransformPlugin, TreeShakingMode};
 use turbopack_node::{
     execution_context::ExecutionContext,
-    transforms::{postcss::PostCssTransformOptions, webpack::WebpackLoaderItems},
+    transforms::{
+        graphql::GraphQlTransformOptions, postcss::PostCssTransformOptions,
+        webpack::WebpackLoaderItems,
+    },
 };
 
-use super::ModuleRule;
+use super::{AssetPipeline, ModuleRule};
 use crate::condition::ContextCondition;
 
 #[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
@@ -36,6 +40,43 @@ pub struct WebpackLoadersOptions {
 #[turbo_tasks::value(transparent)]
 pub struct OptionWebpackLoadersOptions(Option<Vc<WebpackLoadersOptions>>);
 
+/// Configuration for build-time codegen modules, i.e. modules whose content is
+/// produced by executing them in Node.js at build time.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct CodegenModulesOptions {
+    /// Globs matching the codegen modules. Globs without a `/` are matched
+    /// against the file name only, others against the path relative to the
+    /// project path.
+    pub rules: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl ValueDefault for CodegenModulesOptions {
+    #[turbo_tasks::function]
+    fn value_default() -> Vc<Self> {
+        CodegenModulesOptions {
+            rules: vec!["*.codegen.js".to_string(), "*.codegen.ts".to_string()],
+        }
+        .cell()
+    }
+}
+
+/// Configuration for modules that aren't bundled, but emitted as standalone
+/// files, e.g. scripts that must stay byte-identical like the snippets of
+/// payment provider SDKs or AMP components. No transforms are applied to them
+/// and importing them returns their URL, like for static assets.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct NoBundleOptions {
+    /// The path that globs containing a `/` are relative to, e.g. the project
+    /// path.
+    pub base: Vc<FileSystemPath>,
+    /// Globs matching the modules. Globs without a `/` are matched against the
+    /// file name only, others against the path relative to `base`.
+    pub rules: Vec<String>,
+}
+
 /// The kind of decorators transform to use.
 /// [TODO]: might need bikeshed for the name (Ecma)
 #[derive(Clone, PartialEq, Eq, Debug, TraceRawVcs, Serialize, Deserialize)]
@@ -136,6 +177,9 @@ pub struct ModuleOptionsContext {
     pub enable_jsx: Option<Vc<JsxTransformOptions>>,
     pub enable_postcss_transform: Option<Vc<PostCssTransformOptions>>,
     pub enable_webpack_loaders: Option<Vc<WebpackLoadersOptions>>,
+    pub enable_codegen_modules: Option<Vc<CodegenModulesOptions>>,
+    pub no_bundle: Option<Vc<NoBundleOptions>>,
+    pub enable_graphql: Option<Vc<GraphQlTransformOptions>>,
     pub enable_types: bool,
     pub enable_typescript_transform: Option<Vc<TypescriptTransformOptions>>,
     pub decorators: Option<Vc<DecoratorsOptions>>,
@@ -151,6 +195,9 @@ pub struct ModuleOptionsContext {
     pub enable_mdx_rs: Option<Vc<MdxTransformModuleOptions>>,
     pub preset_env_versions: Option<Vc<Environment>>,
     pub custom_ecma_transform_plugins: Option<Vc<CustomEcmascriptTransformPlugins>>,
+    /// Chains of transforms for static assets, applied after all default
+    /// rules.
+    pub asset_pipelines: Vec<AssetPipeline>,
     /// Custom rules to be applied after all default rules.
     pub custom_rules: Vec<ModuleRule>,
     pub execution_context: Option<Vc<ExecutionContext>>,
@@ -66,6 +66,17 @@ impl ModuleRule {
         }
     }
 
+    /// Makes the rule not match resources that match `condition`.
+    pub fn unless(self, condition: ModuleRuleCondition) -> Self {
+        ModuleRule {
+            condition: ModuleRuleCondition::all(vec![
+                self.condition,
+                ModuleRuleCondition::not(condition),
+            ]),
+            ..self
+        }
+    }
+
     pub fn effects(&self) -> impl Iterator<Item = &ModuleRuleEffect> {
         self.effects.iter()
     }
@@ -13,7 +13,7 @@ use turbopack_ecmascript::typescript::resolve::{
     apply_tsconfig_resolve_options, tsconfig, tsconfig_resolve_options,
 };
 
-use crate::resolve_options_context::ResolveOptionsContext;
+use crate::resolve_options_context::{ModuleReplacements, ResolveOptionsContext};
 
 const NODE_EXTERNALS: [&str; 51] = [
     "assert",
@@ -71,6 +71,62 @@ const NODE_EXTERNALS: [&str; 51] = [
 
 const EDGE_NODE_EXTERNALS: [&str; 5] = ["buffer", "events", "assert", "util", "async_hooks"];
 
+const ELECTRON_EXTERNALS: [&str; 4] = [
+    "electron",
+    "electron/main",
+    "electron/renderer",
+    "electron/common",
+];
+
+/// The resolve conditions of `opt`, which select the exports and imports of
+/// packages and the [ModuleReplacements] to apply.
+async fn resolve_conditions(opt: &ResolveOptionsContext) -> Result<ResolutionConditions> {
+    let mut conditions: ResolutionConditions = [
+        ("import".to_string(), ConditionValue::Unknown),
+        ("require".to_string(), ConditionValue::Unknown),
+    ]
+    .into_iter()
+    .collect();
+    if opt.browser {
+        conditions.insert("browser".to_string(), ConditionValue::Set);
+    }
+    if opt.module {
+        conditions.insert("module".to_string(), ConditionValue::Set);
+    }
+    if let Some(environment) = opt.emulate_environment {
+        for condition in environment.resolve_conditions().await?.iter() {
+            conditions.insert(condition.to_string(), ConditionValue::Set);
+        }
+    }
+    for condition in opt.custom_conditions.iter() {
+        conditions.insert(condition.to_string(), ConditionValue::Set);
+    }
+    // Infer some well-known conditions
+    let dev = conditions.get("development").cloned();
+    let prod = conditions.get("production").cloned();
+    if prod.is_none() {
+        conditions.insert(
+            "production".to_string(),
+            if matches!(dev, Some(ConditionValue::Set)) {
+                ConditionValue::Unset
+            } else {
+                ConditionValue::Unknown
+            },
+        );
+    }
+    if dev.is_none() {
+        conditions.insert(
+            "development".to_string(),
+            if matches!(prod, Some(ConditionValue::Set)) {
+                ConditionValue::Unset
+            } else {
+                ConditionValue::Unknown
+            },
+        );
+    }
+    Ok(conditions)
+}
+
 #[turbo_tasks::function]
 async fn base_resolve_options(
     resolve_path: Vc<FileSystemPath>,
@@ -114,6 +170,14 @@ async fn base_resolve_options(
             );
         }
     }
+    if opt.enable_electron_externals {
+        for req in ELECTRON_EXTERNALS {
+            direct_mappings.insert(
+                AliasPattern::exact(req),
+                ImportMapping::External(None).into(),
+            );
+        }
+    }
 
     let mut import_map = ImportMap::new(direct_mappings);
     if let Some(additional_import_map) = opt.import_map {
@@ -124,52 +188,7 @@ async fn base_resolve_options(
 
     let plugins = opt.plugins.clone();
 
-    let conditions = {
-        let mut conditions: ResolutionConditions = [
-            ("import".to_string(), ConditionValue::Unknown),
-            ("require".to_string(), ConditionValue::Unknown),
-        ]
-        .into_iter()
-        .collect();
-        if opt.browser {
-            conditions.insert("browser".to_string(), ConditionValue::Set);
-        }
-        if opt.module {
-            conditions.insert("module".to_string(), ConditionValue::Set);
-        }
-        if let Some(environment) = emulating {
-            for condition in environment.resolve_conditions().await?.iter() {
-                conditions.insert(condition.to_string(), ConditionValue::Set);
-            }
-        }
-        for condition in opt.custom_conditions.iter() {
-            conditions.insert(condition.to_string(), ConditionValue::Set);
-        }
-        // Infer some well-known conditions
-        let dev = conditions.get("development").cloned();
-        let prod = conditions.get("production").cloned();
-        if prod.is_none() {
-            conditions.insert(
-                "production".to_string(),
-                if matches!(dev, Some(ConditionValue::Set)) {
-                    ConditionValue::Unset
-                } else {
-                    ConditionValue::Unknown
-                },
-            );
-        }
-        if dev.is_none() {
-            conditions.insert(
-                "development".to_string(),
-                if matches!(prod, Some(ConditionValue::Set)) {
-                    ConditionValue::Unset
-                } else {
-                    ConditionValue::Unknown
-                },
-            );
-        }
-        conditions
-    };
+    let conditions = resolve_conditions(&opt).await?;
 
     let extensions = if let Some(environment) = emulating {
         environment.resolve_extensions().await?.clone_value()
@@ -299,6 +318,41 @@ pub async fn resolve_options(
             resolve_options.with_extended_fallback_import_map(fallback_import_map)
         })
         .unwrap_or(resolve_options);
+    // Module replacements take precedence over the import map.
+    let resolve_options = match options_context_value.module_replacements {
+        Some(module_replacements) => resolve_options.with_extended_import_map(
+            module_replacements_import_map(options_context, module_replacements),
+        ),
+        None => resolve_options,
+    };
 
     Ok(resolve_options)
 }
+
+/// Creates an import map of the [ModuleReplacements] for the resolve
+/// conditions that are set in `options_context`.
+#[turbo_tasks::function]
+async fn module_replacements_import_map(
+    options_context: Vc<ResolveOptionsContext>,
+    module_replacements: Vc<ModuleReplacements>,
+) -> Result<Vc<ImportMap>> {
+    let conditions = resolve_conditions(&*options_context.await?).await?;
+    let module_replacements = module_replacements.await?;
+    let mut import_map = ImportMap::empty();
+    for (condition, replacements) in module_replacements.by_condition.iter() {
+        if !matches!(conditions.get(condition), Some(ConditionValue::Set)) {
+            continue;
+        }
+        for (request, replacement) in replacements {
+            let mapping = match replacement {
+                Some(replacement) => ImportMapping::PrimaryAlternative(
+                    replacement.clone(),
+                    Some(module_replacements.context),
+                ),
+                None => ImportMapping::Ignore,
+            };
+            import_map.insert_exact_alias(request, mapping.cell());
+        }
+    }
+    Ok(import_map.cell())
+}
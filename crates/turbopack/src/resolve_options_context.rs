@@ -1,4 +1,5 @@
 use anyhow::Result;
+use indexmap::IndexMap;
 use turbo_tasks::{ValueDefault, Vc};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
@@ -11,6 +12,19 @@ use turbopack_core::{
 
 use crate::condition::ContextCondition;
 
+/// Modules that are replaced with shims when resolving for a specific
+/// environment, e.g. `ws` with `./shims/ws.js` in browsers.
+#[turbo_tasks::value(shared)]
+#[derive(Clone)]
+pub struct ModuleReplacements {
+    /// The directory the replacements are resolved from.
+    pub context: Vc<FileSystemPath>,
+    /// Maps a resolve condition (e.g. `browser`, `edge-light` or `worker`) to
+    /// the replacements applied when the condition is set. A replacement of
+    /// `None` ignores the module, like `false` in the `browser` field.
+    pub by_condition: IndexMap<String, IndexMap<String, Option<String>>>,
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Default, Clone)]
 pub struct ResolveOptionsContext {
@@ -39,6 +53,11 @@ pub struct ResolveOptionsContext {
     /// native `require`. e.g. buffer, events, assert
     pub enable_edge_node_externals: bool,
     #[serde(default)]
+    /// Mark `electron` and its subpath modules (e.g. `electron/renderer`) as
+    /// external imports. They are provided by the Electron main process and by
+    /// renderer processes with `nodeIntegration` enabled.
+    pub enable_electron_externals: bool,
+    #[serde(default)]
     /// Enables the "browser" field and export condition in package.json
     pub browser: bool,
     #[serde(default)]
@@ -61,6 +80,11 @@ pub struct ResolveOptionsContext {
     /// any mapping defined within will take precedence over any other.
     pub fallback_import_map: Option<Vc<ImportMap>>,
     #[serde(default)]
+    /// Modules that are replaced for the resolve conditions of this context.
+    /// They are applied after the `import_map`, so they take precedence over
+    /// any other mapping.
+    pub module_replacements: Option<Vc<ModuleReplacements>>,
+    #[serde(default)]
     /// An additional resolved map to use after modules have been resolved.
     pub resolved_map: Option<Vc<ResolvedMap>>,
     #[serde(default)]